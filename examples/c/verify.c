@@ -0,0 +1,57 @@
+/*
+ * Verify a proof from files:
+ *
+ *     cargo build --release
+ *     cc -Iinclude examples/c/verify.c -Ltarget/release -leos_delegation -o verify
+ *     LD_LIBRARY_PATH=target/release ./verify key.bin proof.bin inputs.bin
+ */
+#include <stdio.h>
+#include <stdlib.h>
+
+#include "eos_verifier.h"
+
+static int read_file(const char *path, EosBytes *out) {
+    FILE *file = fopen(path, "rb");
+    if (!file) {
+        perror(path);
+        return -1;
+    }
+    fseek(file, 0, SEEK_END);
+    long len = ftell(file);
+    fseek(file, 0, SEEK_SET);
+    uint8_t *data = malloc(len > 0 ? (size_t)len : 1);
+    if (!data || fread(data, 1, (size_t)len, file) != (size_t)len) {
+        fprintf(stderr, "%s: read failed\n", path);
+        fclose(file);
+        free(data);
+        return -1;
+    }
+    fclose(file);
+    out->data = data;
+    out->len = (size_t)len;
+    return 0;
+}
+
+int main(int argc, char **argv) {
+    if (argc != 4) {
+        fprintf(stderr, "usage: %s <verification-key> <proof> <public-inputs>\n", argv[0]);
+        return 2;
+    }
+    if (eos_abi_version() != EOS_ABI_VERSION) {
+        fprintf(stderr, "library ABI %u, header ABI %d\n", eos_abi_version(), EOS_ABI_VERSION);
+        return 2;
+    }
+
+    EosBytes key, proof, inputs;
+    if (read_file(argv[1], &key) || read_file(argv[2], &proof) || read_file(argv[3], &inputs)) {
+        return 2;
+    }
+
+    EosStatus status = eos_verify(proof, key, inputs);
+    printf("%s\n", eos_status_message(status));
+
+    free((void *)key.data);
+    free((void *)proof.data);
+    free((void *)inputs.data);
+    return status == EOS_ACCEPTED ? 0 : 1;
+}
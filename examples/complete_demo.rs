@@ -93,7 +93,7 @@ fn test_mpc_execution(rng: &mut StdRng) -> Result<(), Box<dyn std::error::Error>
     let shares2 = executor.input_secret(secret2, 2, rng);
     
     // 测试加法门
-    if let (Some(share1), Some(share2)) = (shares1.get(0), shares2.get(0)) {
+    if let (Some(share1), Some(share2)) = (shares1.first(), shares2.first()) {
         let _add_result = executor.add_gate(share1, share2)?;
         println!("   ✅ 加法门测试完成");
         
@@ -147,16 +147,19 @@ fn test_full_protocol(rng: &mut StdRng) -> Result<(), Box<dyn std::error::Error>
     let operation_mode = CollaborationMode::new(2, true, false);
     
     // 创建协议实例
-    let mut protocol = DelegationProtocol::<E, F, ShamirSecretSharing<F>, CollaborationMode>::new(
+    type Protocol = EOSProtocol<E, F, ShamirSecretSharing<F>, CollaborationMode>;
+    let mut protocol = Protocol::new(
         circuit_executor,
         operation_mode,
         128, // 安全参数
+        rng,
     );
     
     println!("   ✅ 协议实例创建成功");
     
-    // 创建简单电路
+    // 创建简单电路并完成预处理
     let circuit = ConstraintSystem::new();
+    protocol.preprocessing_state = Some(Protocol::preprocessing(&circuit, 128, rng)?);
     
     // 准备见证和公共输入
     let witness = vec![F::from(10u64), F::from(20u64), F::from(30u64)];
@@ -216,7 +219,7 @@ fn run_benchmarks(rng: &mut StdRng) -> Result<(), Box<dyn std::error::Error>> {
         let shares1 = executor.input_secret(secret1, 2, rng);
         let shares2 = executor.input_secret(secret2, 2, rng);
         
-        if let (Some(s1), Some(s2)) = (shares1.get(0), shares2.get(0)) {
+        if let (Some(s1), Some(s2)) = (shares1.first(), shares2.first()) {
             let _ = executor.add_gate(s1, s2);
             
             // 模拟通信开销
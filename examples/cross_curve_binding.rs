@@ -0,0 +1,78 @@
+//! 跨曲线语句绑定示例
+//! 应用链使用 BN254，工作节点在 BLS12-381 上生成证明
+
+use ark_bls12_381::{Bls12_381, Fr};
+use ark_bn254::Fr as Bn254Fr;
+use ark_relations::r1cs::ConstraintSystem;
+use ark_std::rand::thread_rng;
+use sha2::{Digest, Sha256};
+
+use eos_delegation::mpc::{CollaborationMode, ExecCircuit, ShamirSecretSharing};
+use eos_delegation::protocol::{verify_bundle, CrossCurveStatement, EOSProtocol, ProofBundle};
+use eos_delegation::serialization::WireFormat;
+
+type Protocol = EOSProtocol<Bls12_381, Fr, ShamirSecretSharing<Fr>, CollaborationMode>;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    println!("🔗 跨曲线语句绑定演示");
+    println!("========================================");
+    let mut rng = thread_rng();
+
+    // 1. 应用链上的语句 (BN254 标量域)
+    println!("\n📋 1. 应用链语句 (BN254)");
+    let state_root: [u8; 32] = Sha256::digest(b"application state").into();
+    let statement = CrossCurveStatement {
+        inputs: vec![Bn254Fr::from(2024u64), -Bn254Fr::from(1u64)],
+        app_hash: state_root,
+    };
+    let digest = statement.digest();
+    println!("   ✅ 公共输入数量: {}", statement.inputs.len());
+    println!("   📝 语句摘要: {}", hex(&digest));
+
+    // 2. 嵌入到 BLS12-381 标量域
+    println!("\n📋 2. 域嵌入 (BN254 → BLS12-381)");
+    let embedded = statement.embed::<Fr>();
+    println!("   ✅ 嵌入后公共输入数量: {} (含 2 个哈希分块)", embedded.len());
+
+    // 3. 在 BLS12-381 上委托计算
+    println!("\n📋 3. 委托计算 (BLS12-381)");
+    let executor = ExecCircuit::new(0, 3, ShamirSecretSharing::new());
+    let mut protocol = Protocol::new(executor, CollaborationMode::new(2, true, false), 128, &mut rng);
+    let circuit = ConstraintSystem::new();
+    protocol.preprocessing_state = Some(Protocol::preprocessing(&circuit, 128, &mut rng)?);
+    let witness: Vec<Fr> = (1..=8u64).map(Fr::from).collect();
+    let result = protocol.delegate_statement(&circuit, &witness, &statement, &mut rng)?;
+    let bytes = protocol.export_bundle(&result, &embedded, &statement.context())?.to_bytes();
+    println!("   ✅ 证明包大小: {} 字节", bytes.len());
+
+    // 4. 应用链侧验证
+    println!("\n📋 4. 应用链侧验证");
+    let bundle = ProofBundle::<Bls12_381>::from_bytes(&bytes)?;
+    let proof_valid = verify_bundle(&bundle, &mut rng)?;
+    println!("   {} 证明验证: {}", status(proof_valid), proof_valid);
+
+    // 从 BN254 值重新计算上下文，并检查证明包的公共输入对应同一语句
+    let context_matches = bundle.context == statement.context();
+    let decoded = CrossCurveStatement::<Bn254Fr>::extract(&bundle.public_inputs)?;
+    let statement_matches = decoded == statement && decoded.digest() == digest;
+    println!("   {} 上下文绑定: {}", status(context_matches), context_matches);
+    println!("   {} 语句一致: {}", status(statement_matches), statement_matches);
+
+    // 5. 篡改检测
+    println!("\n📋 5. 篡改检测");
+    let mut tampered = bundle.clone();
+    tampered.public_inputs[0] += Fr::from(1u64);
+    let tampered_valid = verify_bundle(&tampered, &mut rng)?;
+    println!("   {} 篡改后的公共输入被拒绝", status(!tampered_valid));
+
+    println!("\n🎉 跨曲线绑定演示完成");
+    Ok(())
+}
+
+fn status(ok: bool) -> &'static str {
+    if ok { "✅" } else { "❌" }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
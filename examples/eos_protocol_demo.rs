@@ -1,152 +1,369 @@
-//! EOS协议演示
-//! 演示EOS委托协议的基本概念和工作流程
+//! EOS协议交互式演示
+//!
+//! 逐步构建电路、秘密分享见证、运行三个阶段，并篡改证明观察验证失败。
+//! 每一步都调用真实的协议对象，而不是打印说明文字。
+//!
+//! ```bash
+//! cargo run --example eos_protocol_demo            # 交互模式，输入 help 查看命令
+//! cargo run --example eos_protocol_demo -- run     # 一次性运行完整流程
+//! printf 'example\nshare\npreprocess\ndelegate\nverify\n' | cargo run --example eos_protocol_demo
+//! ```
 
-use ark_bls12_381::{Bls12_381, Fr};
-use ark_std::rand::thread_rng;
+use std::io::{self, BufRead, Write};
 
-use eos_delegation::custom_circuits::CustomCircuit;
-use eos_delegation::mpc::{ShamirSecretSharing, SecretSharing};
+use ark_bls12_381::{Bls12_381, Fr, G1Projective as G1};
+use ark_relations::lc;
+use ark_relations::r1cs::{ConstraintSystem, ConstraintSystemRef, Variable as R1csVariable};
+use ark_std::rand::{rngs::StdRng, SeedableRng};
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    println!("🚀 EOS委托协议演示");
+use eos_delegation::custom_circuits::{CustomCircuit, Variable};
+use eos_delegation::mpc::{CollaborationMode, ExecCircuit, SecretSharing, ShamirSecretSharing, ShamirShare};
+use eos_delegation::piop::PolynomialConsistencyProof;
+use eos_delegation::protocol::{DelegationResult, EOSProtocol};
+use eos_delegation::serialization::WireFormat;
+
+type E = Bls12_381;
+type F = Fr;
+type Protocol = EOSProtocol<E, F, ShamirSecretSharing<F>, CollaborationMode>;
+type DemoResult = Result<(), Box<dyn std::error::Error>>;
+
+const SECURITY_PARAMETER: usize = 128;
+
+const HELP: &str = "\
+命令:
+  example                 载入示例电路 x * y = z (x=15, y=25, z=375)
+  witness <值>            添加私有见证，返回 witness[j]
+  public <值>             添加公开输入，返回 instance[i]
+  mul <a> <b> <c>         添加约束 a * b = c
+  add <a> <b> <c>         添加约束 a + b = c
+  show                    显示电路和约束检查结果
+  share [t] [n]           用 (t, n) Shamir 分享每个见证并验证重构
+  preprocess              阶段1: 为当前电路生成密钥
+  delegate                阶段2: 委托计算，生成证明
+  verify                  阶段3: 验证证明
+  tamper                  改动证明中 KZG 打开的声明值
+  tamper <字节位置>       翻转证明中的一个字节
+  tamper input            修改验证者使用的公开输入
+  run                     依次执行以上完整流程
+  reset                   清空电路和协议状态
+  help                    显示本帮助
+  quit                    退出
+变量写作 one、w0 / witness[0]、i1 / instance[1]";
+
+/// 演示会话: 当前电路以及各阶段产生的协议对象
+struct Session {
+    rng: StdRng,
+    circuit: CustomCircuit<F>,
+    protocol: Option<Protocol>,
+    result: Option<DelegationResult<E, F>>,
+    /// 验证者认为的公开输入，`tamper input` 会修改它
+    claimed_inputs: Vec<F>,
+}
+
+impl Session {
+    fn new() -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(42),
+            circuit: CustomCircuit::new("demo".to_string()),
+            protocol: None,
+            result: None,
+            claimed_inputs: Vec::new(),
+        }
+    }
+
+    fn execute(&mut self, line: &str) -> Result<bool, Box<dyn std::error::Error>> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            [] => {}
+            ["help"] => println!("{}", HELP),
+            ["quit"] | ["exit"] => return Ok(false),
+            ["reset"] => *self = Session::new(),
+            ["example"] => self.load_example(),
+            ["witness", value] => {
+                let var = self.circuit.add_private_witness(parse_field(value)?);
+                println!("   ➕ {} = {}", var, value);
+                self.invalidate();
+            }
+            ["public", value] => {
+                let var = self.circuit.add_public_input(parse_field(value)?);
+                println!("   ➕ {} = {} (公开)", var, value);
+                self.invalidate();
+            }
+            [op @ ("mul" | "add"), a, b, c] => {
+                let (a, b, c) = (self.parse_variable(a)?, self.parse_variable(b)?, self.parse_variable(c)?);
+                if *op == "mul" {
+                    self.circuit.add_multiplication_constraint(a, b, c);
+                    println!("   ⚖️  {} × {} = {}", a, b, c);
+                } else {
+                    self.circuit.add_addition_constraint(a, b, c);
+                    println!("   ⚖️  {} + {} = {}", a, b, c);
+                }
+                self.invalidate();
+            }
+            ["show"] => self.show(),
+            ["share"] => self.share(2, 3)?,
+            ["share", t, n] => self.share(t.parse()?, n.parse()?)?,
+            ["preprocess"] => self.preprocess()?,
+            ["delegate"] => self.delegate()?,
+            ["verify"] => self.verify()?,
+            ["tamper", "input"] => self.tamper_input()?,
+            ["tamper"] => self.tamper_proof(None)?,
+            ["tamper", position] => self.tamper_proof(Some(position.parse()?))?,
+            ["run"] => self.run_walkthrough()?,
+            _ => println!("   ❓ 未知命令 '{}'，输入 help 查看命令", line.trim()),
+        }
+        Ok(true)
+    }
+
+    /// 电路变化后，之前的密钥和证明都不再对应当前电路
+    fn invalidate(&mut self) {
+        self.protocol = None;
+        self.result = None;
+    }
+
+    fn load_example(&mut self) {
+        self.circuit = CustomCircuit::new("eos_demo_circuit".to_string());
+        let x = self.circuit.add_private_witness(F::from(15u64));
+        let y = self.circuit.add_private_witness(F::from(25u64));
+        let z = self.circuit.add_public_input(F::from(375u64));
+        self.circuit.add_multiplication_constraint(x, y, z);
+        self.invalidate();
+        println!("   ✅ 载入电路 {}: {} × {} = {}", self.circuit.name, x, y, z);
+    }
+
+    fn parse_variable(&self, text: &str) -> Result<Variable, String> {
+        let index = |prefix: &str, long: &str| {
+            text.strip_prefix(long)
+                .and_then(|rest| rest.strip_suffix(']'))
+                .or_else(|| text.strip_prefix(prefix))
+                .and_then(|digits| digits.parse::<usize>().ok())
+        };
+        let variable = if text == "one" {
+            Variable::ONE
+        } else if let Some(j) = index("w", "witness[") {
+            Variable::Witness(j)
+        } else if let Some(i) = index("i", "instance[") {
+            Variable::Instance(i)
+        } else {
+            return Err(format!("无法解析变量 '{}'", text));
+        };
+        match self.circuit.variable(variable) {
+            Some(_) => Ok(variable),
+            None => Err(format!("变量 {} 不存在", variable)),
+        }
+    }
+
+    fn show(&self) {
+        let circuit = &self.circuit;
+        println!("   📐 电路 {}", circuit.name);
+        for (j, value) in circuit.private_witnesses.iter().enumerate() {
+            println!("      {} = {}", Variable::Witness(j), value);
+        }
+        for (i, value) in circuit.public_inputs.iter().enumerate() {
+            println!("      {} = {} (公开)", Variable::public_input(i), value);
+        }
+        for (a, b, c) in &circuit.multiplication_constraints {
+            println!("      {} × {} = {}", a, b, c);
+        }
+        for (a, b, c) in &circuit.addition_constraints {
+            println!("      {} + {} = {}", a, b, c);
+        }
+        let report = circuit.check_witness();
+        if report.is_satisfied() {
+            println!("   ✅ 所有约束满足");
+        } else {
+            println!("   ❌ {}", report);
+        }
+    }
+
+    fn share(&mut self, threshold: usize, num_parties: usize) -> DemoResult {
+        if self.circuit.private_witnesses.is_empty() {
+            return Err("电路没有私有见证，先输入 example 或 witness <值>".into());
+        }
+        println!("   🔒 ({}, {}) Shamir 秘密分享", threshold, num_parties);
+        for (j, &secret) in self.circuit.private_witnesses.iter().enumerate() {
+            let shares: Vec<ShamirShare<F>> =
+                ShamirSecretSharing::share_secret(secret, threshold, num_parties, &mut self.rng);
+            let reconstructed = ShamirSecretSharing::<F>::reconstruct_secret(&shares[..threshold])?;
+            println!("      {} -> {} 份，任取 {} 份重构: {}", Variable::Witness(j), shares.len(), threshold,
+                if reconstructed == secret { "✅ 一致" } else { "❌ 不一致" });
+            if threshold > 1 {
+                let partial = ShamirSecretSharing::<F>::reconstruct_secret(&shares[..threshold - 1])?;
+                println!("      只用 {} 份得到 {}，与秘密{}", threshold - 1, short(&partial),
+                    if partial == secret { "相同" } else { "无关" });
+            }
+        }
+        Ok(())
+    }
+
+    /// 按 `Variable::index` 的布局把电路写入 `ConstraintSystem`
+    fn constraint_system(&self) -> Result<ConstraintSystem<F>, Box<dyn std::error::Error>> {
+        let cs: ConstraintSystemRef<F> = ConstraintSystem::new_ref();
+        for &value in &self.circuit.public_inputs {
+            cs.new_input_variable(|| Ok(value))?;
+        }
+        for &value in &self.circuit.private_witnesses {
+            cs.new_witness_variable(|| Ok(value))?;
+        }
+        let wire = |variable: Variable| -> R1csVariable { variable.into() };
+        for &(a, b, c) in &self.circuit.multiplication_constraints {
+            cs.enforce_constraint(lc!() + wire(a), lc!() + wire(b), lc!() + wire(c))?;
+        }
+        for &(a, b, c) in &self.circuit.addition_constraints {
+            cs.enforce_constraint(lc!() + wire(a) + wire(b), lc!() + wire(Variable::ONE), lc!() + wire(c))?;
+        }
+        cs.finalize();
+        let cs = cs.into_inner().ok_or("约束系统仍被引用")?;
+        Ok(cs)
+    }
+
+    fn preprocess(&mut self) -> DemoResult {
+        let cs = self.constraint_system()?;
+        println!("   📐 阶段1: 预处理 ({} 个约束, {} 个变量)", cs.num_constraints,
+            cs.num_instance_variables + cs.num_witness_variables);
+        let executor = ExecCircuit::new(1, 3, ShamirSecretSharing::new());
+        let mut protocol = Protocol::new(executor, CollaborationMode::new(2, true, false), SECURITY_PARAMETER, &mut self.rng);
+        protocol.preprocessing_state = Some(Protocol::preprocessing(&cs, SECURITY_PARAMETER, &mut self.rng)?);
+        let key_hash = protocol.verification_key_hash()?;
+        println!("   🔑 验证密钥哈希: {}", hex(&key_hash[..8]));
+        self.protocol = Some(protocol);
+        self.result = None;
+        Ok(())
+    }
+
+    fn delegate(&mut self) -> DemoResult {
+        let report = self.circuit.check_witness();
+        if !report.is_satisfied() {
+            return Err(format!("见证不满足电路，拒绝委托:\n{}", report).into());
+        }
+        if self.protocol.is_none() {
+            self.preprocess()?;
+        }
+        let cs = self.constraint_system()?;
+        let protocol = self.protocol.as_mut().ok_or("尚未预处理")?;
+        println!("   🔄 阶段2: 委托 ({} 个见证分享给工作者)", self.circuit.private_witnesses.len());
+        let result = protocol.delegate_computation(
+            &cs,
+            &self.circuit.private_witnesses,
+            &self.circuit.public_inputs,
+            &mut self.rng,
+        )?;
+        let proof_len = result.piop_proof.as_ref().map_or(0, Vec::len);
+        println!("   📦 证明 {} 字节，承诺 {} 个", proof_len, result.polynomial_commitments.len());
+        self.claimed_inputs = self.circuit.public_inputs.clone();
+        self.result = Some(result);
+        Ok(())
+    }
+
+    fn verify(&mut self) -> DemoResult {
+        let protocol = self.protocol.as_ref().ok_or("尚未预处理")?;
+        let result = self.result.as_ref().ok_or("尚未委托，先运行 delegate")?;
+        println!("   ✅ 阶段3: 验证");
+        match protocol.verify_computation(result, &self.claimed_inputs) {
+            Ok(outcome) if outcome.is_accepted() => {
+                println!("   🎉 接受 ({:?})", outcome.elapsed());
+            }
+            Ok(outcome) => {
+                let check = outcome.failed_check().map(|c| c.to_string()).unwrap_or_default();
+                println!("   🚫 拒绝: {} 检查失败", check);
+            }
+            Err(e) => println!("   🚫 拒绝: {}", e),
+        }
+        Ok(())
+    }
+
+    fn tamper_proof(&mut self, position: Option<usize>) -> DemoResult {
+        let result = self.result.as_mut().ok_or("尚未委托，先运行 delegate")?;
+        let bytes = result.piop_proof.as_mut().ok_or("结果中没有证明")?;
+        match position {
+            Some(position) => {
+                let len = bytes.len();
+                let byte = bytes.get_mut(position).ok_or_else(|| format!("证明只有 {} 字节", len))?;
+                *byte ^= 0x01;
+                println!("   🔧 已翻转证明第 {} 字节，再次 verify 观察结果", position);
+            }
+            None => {
+                // 改动 KZG 打开的声明值，证明仍能解码，只有打开检查能发现
+                let mut proof = PolynomialConsistencyProof::<F, G1>::from_bytes(bytes)?;
+                let opening = proof.consistency_proofs.first_mut().ok_or("证明中没有打开")?;
+                opening.evaluation += F::from(1u64);
+                *bytes = proof.to_bytes();
+                println!("   🔧 已把 opening[0] 的声明值加 1，再次 verify 观察结果");
+            }
+        }
+        Ok(())
+    }
+
+    fn tamper_input(&mut self) -> DemoResult {
+        if self.result.is_none() {
+            return Err("尚未委托，先运行 delegate".into());
+        }
+        let input = self.claimed_inputs.first_mut().ok_or("电路没有公开输入")?;
+        *input += F::from(1u64);
+        println!("   🔧 验证者现在声称 {} = {}", Variable::public_input(0), input);
+        Ok(())
+    }
+
+    fn run_walkthrough(&mut self) -> DemoResult {
+        let steps = ["example", "show", "share", "preprocess", "delegate", "verify", "tamper", "verify",
+            "delegate", "tamper input", "verify"];
+        for step in steps {
+            println!("\neos> {}", step);
+            self.execute(step)?;
+        }
+        Ok(())
+    }
+}
+
+fn parse_field(text: &str) -> Result<F, String> {
+    match text.strip_prefix('-') {
+        Some(digits) => digits.parse::<u64>().map(|v| -F::from(v)),
+        None => text.parse::<u64>().map(F::from),
+    }
+    .map_err(|_| format!("无法解析数值 '{}'", text))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// 域元素的缩写形式，避免长整数刷屏
+fn short(value: &F) -> String {
+    let text = value.to_string();
+    if text.len() > 16 {
+        format!("{}…", &text[..16])
+    } else {
+        text
+    }
+}
+
+fn main() -> DemoResult {
+    println!("🚀 EOS委托协议交互式演示");
     println!("========================================");
-    
-    // 1. 协议概述
-    println!("📋 1. EOS协议概述");
-    println!("   EOS (Efficient Outsourcing for SNARK) 是一个三阶段的委托协议:");
-    println!("   📐 阶段1: 预处理 - 生成电路特定的密钥");
-    println!("   🔄 阶段2: 委托 - 使用MPC执行私有计算");
-    println!("   ✅ 阶段3: 验证 - 验证计算结果的正确性");
-    
-    // 2. 设置基础组件
-    println!("\n📋 2. 基础组件设置");
-    let mut rng = thread_rng();
-    
-    // 创建秘密分享方案
-    let secret_sharing = ShamirSecretSharing::<Fr>::new();
-    println!("   ✅ Shamir秘密分享设置完成");
-    
-    // 分享参数
-    let threshold = 3;
-    let num_parties = 5;
-    
-    // 3. 创建示例电路
-    println!("\n📋 3. 电路创建");
-    let mut circuit = CustomCircuit::new("eos_demo_circuit".to_string());
-    
-    // 添加私有见证: x, y
-    let x = Fr::from(15u64);
-    let y = Fr::from(25u64);
-    circuit.add_private_witness(x);
-    circuit.add_private_witness(y);
-    
-    // 添加公开输入: 期望结果 z = x * y = 375
-    let expected_z = Fr::from(375u64);
-    circuit.add_public_input(expected_z);
-    
-    // 添加约束: x * y = z
-    circuit.add_multiplication_constraint(0, 1, 2);
-    
-    println!("   ✅ 电路创建完成");
-    println!("   🔢 私有见证: x={}, y={}", 15, 25);
-    println!("   📖 公开输入: z={}", 375);
-    println!("   ⚖️  约束: x × y = z");
-    
-    // 4. 模拟预处理阶段
-    println!("\n📋 4. 预处理阶段模拟 (Phase 1)");
-    println!("   🔄 生成电路参数...");
-    println!("   🔑 在实际实现中:");
-    println!("      - 分析电路结构和约束");
-    println!("      - 生成evaluation key (用于计算)");
-    println!("      - 生成verification key (用于验证)");
-    println!("      - 提取约束矩阵 A, B, C");
-    println!("   ✅ 预处理阶段完成 (模拟)");
-    
-    // 5. 模拟委托阶段
-    println!("\n📋 5. 委托阶段模拟 (Phase 2)");
-    println!("   🔄 执行MPC计算...");
-    
-    // 使用秘密分享保护私有见证
-    let x_shares = ShamirSecretSharing::<Fr>::share_secret(x, threshold, num_parties, &mut rng);
-    let y_shares = ShamirSecretSharing::<Fr>::share_secret(y, threshold, num_parties, &mut rng);
-    
-    println!("   🔒 私有见证已秘密分享");
-    println!("      x = {} -> {} 分享 (阈值 {})", 15, x_shares.len(), threshold);
-    println!("      y = {} -> {} 分享 (阈值 {})", 25, y_shares.len(), threshold);
-    
-    // 验证电路约束
-    let verification_result = circuit.verify_constraints();
-    println!("   ⚖️  约束验证: {}", 
-        if verification_result { "✅ 通过" } else { "❌ 失败" });
-    
-    // 重构秘密验证
-    let reconstructed_x = ShamirSecretSharing::<Fr>::reconstruct_secret(&x_shares[..threshold])?;
-    let reconstructed_y = ShamirSecretSharing::<Fr>::reconstruct_secret(&y_shares[..threshold])?;
-    
-    println!("   🔓 秘密重构验证:");
-    println!("      重构的 x = {}", reconstructed_x == x);
-    println!("      重构的 y = {}", reconstructed_y == y);
-    
-    println!("   🔐 在实际实现中还会:");
-    println!("      - 生成PIOP一致性证明");
-    println!("      - 生成KZG多项式承诺");
-    println!("      - 执行零知识证明协议");
-    println!("   ✅ 委托阶段完成 (模拟)");
-    
-    // 6. 模拟验证阶段
-    println!("\n📋 6. 验证阶段模拟 (Phase 3)");
-    println!("   🔄 验证计算结果...");
-    
-    // 验证计算正确性
-    let computed_result = reconstructed_x * reconstructed_y;
-    let is_correct = computed_result == expected_z;
-    
-    println!("   📊 计算验证:");
-    println!("      {} × {} = {} (期望 {})", 
-        15, 25, 
-        if computed_result == Fr::from(375u64) { "375" } else { "错误" }, 
-        375);
-    println!("   � 验证结果: {}", 
-        if is_correct { "✅ 成功" } else { "❌ 失败" });
-    
-    println!("   🔐 在实际实现中还会:");
-    println!("      - 验证PIOP证明");
-    println!("      - 验证多项式承诺");
-    println!("      - 检查零知识属性");
-    
-    // 7. 展示协议优势
-    println!("\n📋 7. EOS协议优势总结");
-    println!("   🚀 效率优势:");
-    println!("      ✓ 预处理可重用，分摊计算成本");
-    println!("      ✓ MPC保护见证隐私");
-    println!("      ✓ PIOP提供简洁证明");
-    println!("   🛡️ 安全优势:");
-    println!("      ✓ 零知识: 见证保持私有");
-    println!("      ✓ 完备性: 正确计算总是验证通过");
-    println!("      ✓ 可靠性: 错误计算可被检测");
-    println!("   ⚡ 实用优势:");
-    println!("      ✓ 支持任意R1CS电路");
-    println!("      ✓ 可扩展到大规模计算");
-    println!("      ✓ 适用于多方协作场景");
-    
-    // 8. 展示当前实现状态
-    println!("\n📋 8. 当前实现状态");
-    println!("   ✅ 已实现:");
-    println!("      ✓ 完整的秘密分享系统 (Shamir + 加法)");
-    println!("      ✓ MPC基础操作 (加法、乘法、线性组合)");
-    println!("      ✓ 自定义电路框架");
-    println!("      ✓ 约束验证系统");
-    println!("      ✓ PIOP一致性检查器");
-    println!("      ✓ KZG多项式承诺");
-    println!("      ✓ 性能评估框架");
-    println!("   🔄 架构重构完成:");
-    println!("      ✓ EOS三阶段协议结构");
-    println!("      ✓ 预处理、委托、验证分离");
-    println!("      ✓ 符合学术论文规范");
-    
-    println!("\n🎯 EOS协议演示完成！");
-    println!("💡 完整的EOS协议实现正在integration阶段");
-    println!("   运行 'cargo run' 查看所有组件的详细测试");
-    
+    let mut session = Session::new();
+
+    // 命令行参数作为一条命令执行，例如 `-- run`
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        session.execute(&args.join(" "))?;
+        return Ok(());
+    }
+
+    println!("输入 help 查看命令，run 运行完整流程");
+    let stdin = io::stdin();
+    loop {
+        print!("eos> ");
+        io::stdout().flush()?;
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line)? == 0 {
+            println!();
+            break;
+        }
+        match session.execute(&line) {
+            Ok(true) => {}
+            Ok(false) => break,
+            Err(e) => println!("   ⚠️  {}", e),
+        }
+    }
     Ok(())
 }
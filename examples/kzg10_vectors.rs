@@ -0,0 +1,81 @@
+//! Generate the KZG test vectors in `testdata/kzg10_bls12_381.txt`
+//!
+//! The SRS, commitments and opening proofs come from `ark-poly-commit`'s
+//! `KZG10`, not from this crate, so the tests that load the file check
+//! `KZGCommitmentScheme` against an independent implementation. Every proof
+//! is checked with `KZG10::check` before it is written.
+//!
+//! Run with `cargo run --example kzg10_vectors > testdata/kzg10_bls12_381.txt`.
+
+use ark_bls12_381::{Bls12_381, Fr};
+use ark_ff::{UniformRand, Zero};
+use ark_poly::{univariate::DensePolynomial, DenseUVPolynomial, Polynomial};
+use ark_poly_commit::kzg10::{Powers, Proof, VerifierKey, KZG10};
+use ark_serialize::CanonicalSerialize;
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+use std::borrow::Cow;
+
+type Kzg = KZG10<Bls12_381, DensePolynomial<Fr>>;
+
+const MAX_DEGREE: usize = 15;
+
+fn hex(value: &impl CanonicalSerialize) -> String {
+    let mut bytes = Vec::new();
+    value.serialize_compressed(&mut bytes).expect("serializing into a Vec cannot fail");
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn hex_list<T: CanonicalSerialize>(values: &[T]) -> String {
+    values.iter().map(hex).collect::<Vec<_>>().join(",")
+}
+
+fn main() {
+    let mut rng = StdRng::seed_from_u64(0x4b5a47);
+    let params = Kzg::setup(MAX_DEGREE, false, &mut rng).expect("KZG10 setup");
+    let powers = Powers::<Bls12_381> {
+        powers_of_g: Cow::Borrowed(&params.powers_of_g),
+        powers_of_gamma_g: Cow::Owned(Vec::new()),
+    };
+    let verifier_key = VerifierKey {
+        g: params.powers_of_g[0],
+        gamma_g: params.powers_of_gamma_g[&0],
+        h: params.h,
+        beta_h: params.beta_h,
+        prepared_h: params.prepared_h.clone(),
+        prepared_beta_h: params.prepared_beta_h.clone(),
+    };
+
+    println!("# KZG10 test vectors over BLS12-381");
+    println!("# implementation: ark-poly-commit 0.4.0, ark_poly_commit::kzg10::KZG10 (non-hiding)");
+    println!("# generator: examples/kzg10_vectors.rs, StdRng::seed_from_u64(0x4b5a47)");
+    println!("# encoding: hex of the ark-serialize 0.4 compressed encoding");
+    println!("srs.powers_of_g = {}", hex_list(&params.powers_of_g));
+    println!("srs.h = {}", hex(&params.h));
+    println!("srs.beta_h = {}", hex(&params.beta_h));
+
+    // A constant, a zero, a mid-degree and a full-degree polynomial, opened
+    // at zero, at a root and at random points
+    let cases: Vec<(DensePolynomial<Fr>, Fr)> = vec![
+        (DensePolynomial::from_coefficients_vec(vec![Fr::from(42u64)]), Fr::rand(&mut rng)),
+        (DensePolynomial::zero(), Fr::rand(&mut rng)),
+        (DensePolynomial::from_coefficients_vec(vec![-Fr::from(6u64), Fr::from(1u64), Fr::from(1u64)]), Fr::from(2u64)),
+        (DensePolynomial::rand(7, &mut rng), Fr::zero()),
+        (DensePolynomial::rand(7, &mut rng), Fr::rand(&mut rng)),
+        (DensePolynomial::rand(MAX_DEGREE, &mut rng), Fr::rand(&mut rng)),
+    ];
+    for (polynomial, point) in cases {
+        let (commitment, randomness) = Kzg::commit(&powers, &polynomial, None, None).expect("commit");
+        let (witness, _) = Kzg::compute_witness_polynomial(&polynomial, point, &randomness).expect("witness");
+        let (w, _) = Kzg::commit(&powers, &witness, None, None).expect("commit witness");
+        let proof = Proof { w: w.0, random_v: None };
+        let value = polynomial.evaluate(&point);
+        assert!(Kzg::check(&verifier_key, &commitment, point, value, &proof).expect("check"));
+
+        println!();
+        println!("coefficients = {}", hex_list(&polynomial.coeffs));
+        println!("point = {}", hex(&point));
+        println!("value = {}", hex(&value));
+        println!("commitment = {}", hex(&commitment.0));
+        println!("proof = {}", hex(&proof.w));
+    }
+}
@@ -0,0 +1,68 @@
+"""Sweep circuit size and record proving time, verification time and proof size.
+
+    pip install maturin && maturin develop --release
+    python examples/python/parameter_sweep.py            # prints a CSV table
+    python examples/python/parameter_sweep.py --plot     # also plots, needs matplotlib
+"""
+
+import argparse
+import csv
+import sys
+import time
+
+import eos_delegation as eos
+
+
+def measure(rounds, repetitions):
+    circuit = eos.Circuit.template("repeated_squaring", rounds=rounds)
+    delegation = eos.Delegation(circuit, seed=rounds)
+    rows = []
+    for _ in range(repetitions):
+        start = time.perf_counter()
+        proof = delegation.delegate()
+        proving = time.perf_counter() - start
+        accepted, failed_check, verifying = delegation.verify_detailed(proof)
+        assert accepted, failed_check
+        rows.append({
+            "rounds": rounds,
+            "constraints": circuit.num_constraints,
+            "prove_s": proving,
+            "verify_s": verifying,
+            "proof_bytes": len(proof),
+            **{f"{category}_bytes": size for category, size in proof.size_breakdown().items()},
+        })
+    return rows
+
+
+def main():
+    parser = argparse.ArgumentParser(description=__doc__.splitlines()[0])
+    parser.add_argument("--rounds", type=int, nargs="+", default=[2, 4, 8, 16, 32, 64])
+    parser.add_argument("--repetitions", type=int, default=3)
+    parser.add_argument("--plot", action="store_true")
+    args = parser.parse_args()
+
+    rows = [row for rounds in args.rounds for row in measure(rounds, args.repetitions)]
+    fields = list(dict.fromkeys(key for row in rows for key in row))
+    writer = csv.DictWriter(sys.stdout, fieldnames=fields)
+    writer.writeheader()
+    writer.writerows(rows)
+
+    if args.plot:
+        import matplotlib.pyplot as plt
+
+        fig, (timing, size) = plt.subplots(1, 2, figsize=(10, 4))
+        constraints = [row["constraints"] for row in rows]
+        timing.scatter(constraints, [row["prove_s"] for row in rows], label="prove")
+        timing.scatter(constraints, [row["verify_s"] for row in rows], label="verify")
+        timing.set_xlabel("constraints")
+        timing.set_ylabel("seconds")
+        timing.legend()
+        size.plot(constraints, [row["proof_bytes"] for row in rows], marker="o")
+        size.set_xlabel("constraints")
+        size.set_ylabel("proof bytes")
+        fig.tight_layout()
+        plt.show()
+
+
+if __name__ == "__main__":
+    main()
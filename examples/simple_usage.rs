@@ -1,6 +1,4 @@
-/// 简单的 EOS 委托协议使用示例
-
-use eos_delegation::*;
+//! 简单的 EOS 委托协议使用示例
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("🚀 EOS 委托协议使用示例");
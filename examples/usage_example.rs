@@ -1,6 +1,10 @@
-/// 演示如何使用 EOS 委托协议的基本功能
+//! 演示如何使用 EOS 委托协议的基本功能
 
-use eos_delegation::*;
+use eos_delegation::prelude::*;
+use eos_delegation::circuit::PolyAdd;
+use eos_delegation::evaluation::{BenchmarkCase, BenchmarkSuite};
+use ark_bls12_381::Fr as TestField;
+use ark_poly::{DenseUVPolynomial, Polynomial};
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("🚀 EOS Delegation Protocol Example");
@@ -57,11 +61,11 @@ fn demonstrate_secret_sharing(rng: &mut impl ark_std::rand::Rng) -> Result<(), B
     let num_parties = 5;
     
     // Create Shamir secret shares
-    let shares = ShamirSecretSharing::share_secret(secret, threshold, num_parties, rng);
+    let shares = ShamirSecretSharing::<TestField>::share_secret(secret, threshold, num_parties, rng);
     println!("  ✓ Created {} shares with threshold {}", shares.len(), threshold);
     
     // Reconstruct secret from shares
-    let reconstructed = ShamirSecretSharing::reconstruct_secret(&shares[0..threshold])?;
+    let reconstructed = ShamirSecretSharing::<TestField>::reconstruct_secret(&shares[0..threshold])?;
     println!("  ✓ Secret reconstruction: {}", reconstructed == secret);
     
     Ok(())
@@ -87,19 +91,13 @@ fn demonstrate_mpc_modes() {
     let isolation_pattern = isolation_mode.get_communication_pattern();
     let collaboration_pattern = collaboration_mode.get_communication_pattern();
     
-    match isolation_pattern {
-        CommunicationPattern::Minimal { max_rounds, batch_size } => {
-            println!("  Isolation Pattern: {} rounds, batch size {}", max_rounds, batch_size);
-        }
-        _ => {}
+    if let CommunicationPattern::Minimal { max_rounds, batch_size } = isolation_pattern {
+        println!("  Isolation Pattern: {} rounds, batch size {}", max_rounds, batch_size);
     }
     
-    match collaboration_pattern {
-        CommunicationPattern::Full { parallelism_degree, use_optimized_protocols } => {
-            println!("  Collaboration Pattern: {} parallel, optimized: {}", 
-                     parallelism_degree, use_optimized_protocols);
-        }
-        _ => {}
+    if let CommunicationPattern::Full { parallelism_degree, use_optimized_protocols } = collaboration_pattern {
+        println!("  Collaboration Pattern: {} parallel, optimized: {}", 
+                 parallelism_degree, use_optimized_protocols);
     }
 }
 
@@ -0,0 +1,31 @@
+//! Arbitrary bytes into the frame decoder, delivered in two reads split at
+//! an arbitrary point: decoding must never panic, and every packet it
+//! yields must re-frame to exactly the bytes it came from.
+
+#![no_main]
+
+use eos_delegation::mpc::FrameDecoder;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Some((&split, data)) = data.split_first() else {
+        return;
+    };
+    let split = (split as usize).min(data.len());
+    let mut decoder = FrameDecoder::new(1 << 12);
+    let mut offset = 0;
+    for chunk in [&data[..split], &data[split..]] {
+        decoder.push(chunk);
+        loop {
+            match decoder.next_packet() {
+                Ok(Some(packet)) => {
+                    let frame = packet.to_frame();
+                    assert_eq!(frame, data[offset..offset + frame.len()]);
+                    offset += frame.len();
+                }
+                Ok(None) => break,
+                Err(_) => return,
+            }
+        }
+    }
+});
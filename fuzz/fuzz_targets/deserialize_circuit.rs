@@ -0,0 +1,19 @@
+//! Arbitrary bytes into the circuit decoder. Every decoded circuit must be
+//! safe to evaluate: constraint checking and polynomial generation index
+//! into the variable vector, so out-of-range indices have to be rejected
+//! at decode time.
+
+#![no_main]
+
+use ark_bls12_381::Fr;
+use eos_delegation::custom_circuits::CustomCircuit;
+use eos_delegation::serialization::WireFormat;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(circuit) = CustomCircuit::<Fr>::from_bytes(data) {
+        let _ = circuit.verify_constraints();
+        let _ = circuit.generate_constraint_polynomials();
+        let _ = circuit.witnesses_to_polynomials();
+    }
+});
@@ -0,0 +1,24 @@
+//! Arbitrary bytes into the proof decoders. Decoded proofs are handed to the
+//! verifier, which must reject or accept without panicking.
+
+#![no_main]
+
+use ark_bls12_381::{Fr, G1Projective};
+use eos_delegation::circuit::OpeningProof;
+use eos_delegation::piop::{ConsistencyChecker, PolynomialConsistencyProof, SumcheckProof};
+use eos_delegation::serialization::WireFormat;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let checker = ConsistencyChecker::<Fr>::new();
+
+    if let Ok(proof) = PolynomialConsistencyProof::<Fr, G1Projective>::from_bytes(data) {
+        let _ = checker.verify_consistency_proof(&proof);
+    }
+    if let Ok(proof) = SumcheckProof::<Fr>::from_bytes(data) {
+        // 多项式系数会被规范化，因此检查重新编码是幂等的
+        let bytes = proof.to_bytes();
+        assert_eq!(SumcheckProof::<Fr>::from_bytes(&bytes).unwrap().to_bytes(), bytes);
+    }
+    let _ = OpeningProof::<Fr, G1Projective>::from_bytes(data);
+});
@@ -0,0 +1,18 @@
+//! Arbitrary bytes into the share decoders: decoding must never panic, and
+//! anything that decodes must re-encode to the same bytes.
+
+#![no_main]
+
+use ark_bls12_381::Fr;
+use eos_delegation::mpc::{AdditiveShare, ShamirShare};
+use eos_delegation::serialization::WireFormat;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(share) = ShamirShare::<Fr>::from_bytes(data) {
+        assert_eq!(share.to_bytes(), data);
+    }
+    if let Ok(share) = AdditiveShare::<Fr>::from_bytes(data) {
+        assert_eq!(share.to_bytes(), data);
+    }
+});
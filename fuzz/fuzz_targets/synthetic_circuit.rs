@@ -0,0 +1,54 @@
+//! Synthetic circuits of fuzzer-chosen shape. Every generated circuit must
+//! be satisfied, have exactly the requested gate counts and depth, and
+//! survive an encode/decode round trip.
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use ark_bls12_381::Fr;
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+use eos_delegation::custom_circuits::CustomCircuit;
+use eos_delegation::evaluation::{CircuitShape, FanOut, WorkloadSpec};
+use eos_delegation::serialization::WireFormat;
+use libfuzzer_sys::fuzz_target;
+
+#[derive(Debug, Arbitrary)]
+enum FanOutInput {
+    Uniform,
+    Recent(u8),
+    Hubs(u8),
+}
+
+#[derive(Debug, Arbitrary)]
+struct Input {
+    constraints: u16,
+    multiplication_percent: u8,
+    depth: u16,
+    inputs: u8,
+    fan_out: FanOutInput,
+    seed: u64,
+}
+
+fuzz_target!(|input: Input| {
+    let spec = WorkloadSpec {
+        constraints: input.constraints as usize % 1024,
+        multiplication_ratio: input.multiplication_percent as f64 / 100.0,
+        depth: input.depth as usize,
+        inputs: input.inputs as usize,
+        fan_out: match input.fan_out {
+            FanOutInput::Uniform => FanOut::Uniform,
+            FanOutInput::Recent(window) => FanOut::Recent { window: window as usize },
+            FanOutInput::Hubs(hubs) => FanOut::Hubs { hubs: hubs as usize },
+        },
+    };
+    let circuit = spec.generate::<Fr, _>(&mut StdRng::seed_from_u64(input.seed));
+    assert!(circuit.verify_constraints());
+
+    let shape = CircuitShape::of(&circuit);
+    assert_eq!(shape.multiplications, spec.multiplications());
+    assert_eq!(shape.additions, spec.additions());
+    assert_eq!(shape.multiplicative_depth, spec.effective_depth());
+
+    let decoded = CustomCircuit::<Fr>::from_bytes(&circuit.to_bytes()).unwrap();
+    assert!(decoded.verify_constraints());
+});
@@ -0,0 +1,110 @@
+//! Well-typed but mutated proofs into the verifiers. Starting from an honest
+//! consistency proof and KZG opening, the fuzzer applies structured
+//! mutations; verification may fail but must never panic.
+
+#![no_main]
+
+use std::sync::OnceLock;
+
+use arbitrary::Arbitrary;
+use ark_bls12_381::{Bls12_381, Fr};
+use ark_poly::{univariate::DensePolynomial, DenseUVPolynomial};
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+use eos_delegation::circuit::KZGCommitmentScheme;
+use eos_delegation::mpc::{SecretSharing, ShamirSecretSharing};
+use eos_delegation::piop::ConsistencyChecker;
+use libfuzzer_sys::fuzz_target;
+
+#[derive(Debug, Arbitrary)]
+enum Mutation {
+    SetChallenge { proof: u8, round: u8, value: u64 },
+    SetRoundCoefficient { proof: u8, round: u8, coeff: u8, value: u64 },
+    DropRound { proof: u8 },
+    SetFinalEvaluation { proof: u8, value: u64 },
+    SetEvaluation { value: u64 },
+    SetPoint { value: u64 },
+    SetShareIndex { share: u8, index: u16 },
+    SetShareValue { share: u8, value: u64 },
+}
+
+#[derive(Debug, Arbitrary)]
+struct Input {
+    coefficients: Vec<u64>,
+    mutations: Vec<Mutation>,
+}
+
+fn kzg() -> &'static KZGCommitmentScheme<Bls12_381> {
+    static KZG: OnceLock<KZGCommitmentScheme<Bls12_381>> = OnceLock::new();
+    KZG.get_or_init(|| KZGCommitmentScheme::setup(16, &mut StdRng::seed_from_u64(0)))
+}
+
+fuzz_target!(|input: Input| {
+    let coeffs: Vec<Fr> = input.coefficients.iter().take(16).map(|&c| Fr::from(c)).collect();
+    let poly = DensePolynomial::from_coefficients_vec(coeffs);
+
+    let mut checker = ConsistencyChecker::<Fr>::new();
+    checker.add_witness_polynomial("w".to_string(), poly.clone());
+    let mut proof = checker.generate_consistency_proof().unwrap();
+
+    let commitment = kzg().commit(&poly);
+    let mut opening = kzg().open(&poly, Fr::from(5u64));
+
+    let mut rng = StdRng::seed_from_u64(input.coefficients.len() as u64);
+    let mut shares = ShamirSecretSharing::<Fr>::share_secret(Fr::from(42u64), 3, 5, &mut rng);
+
+    for mutation in input.mutations {
+        match mutation {
+            Mutation::SetChallenge { proof: p, round, value } => {
+                let len = proof.sumcheck_proofs.len();
+                if let Some(sc) = proof.sumcheck_proofs.get_mut(p as usize % len.max(1)) {
+                    let rounds = sc.challenges.len();
+                    if let Some(c) = sc.challenges.get_mut(round as usize % rounds.max(1)) {
+                        *c = Fr::from(value);
+                    }
+                }
+            }
+            Mutation::SetRoundCoefficient { proof: p, round, coeff, value } => {
+                let len = proof.sumcheck_proofs.len();
+                if let Some(sc) = proof.sumcheck_proofs.get_mut(p as usize % len.max(1)) {
+                    let rounds = sc.round_polynomials.len();
+                    if let Some(rp) = sc.round_polynomials.get_mut(round as usize % rounds.max(1)) {
+                        let mut coeffs = rp.coeffs.clone();
+                        let i = coeff as usize % (coeffs.len() + 1);
+                        if i == coeffs.len() {
+                            coeffs.push(Fr::from(value));
+                        } else {
+                            coeffs[i] = Fr::from(value);
+                        }
+                        *rp = DensePolynomial::from_coefficients_vec(coeffs);
+                    }
+                }
+            }
+            Mutation::DropRound { proof: p } => {
+                let len = proof.sumcheck_proofs.len();
+                if let Some(sc) = proof.sumcheck_proofs.get_mut(p as usize % len.max(1)) {
+                    sc.round_polynomials.pop();
+                }
+            }
+            Mutation::SetFinalEvaluation { proof: p, value } => {
+                let len = proof.sumcheck_proofs.len();
+                if let Some(sc) = proof.sumcheck_proofs.get_mut(p as usize % len.max(1)) {
+                    sc.final_evaluation = Fr::from(value);
+                }
+            }
+            Mutation::SetEvaluation { value } => opening.evaluation = Fr::from(value),
+            Mutation::SetPoint { value } => opening.point = Fr::from(value),
+            Mutation::SetShareIndex { share, index } => {
+                let i = share as usize % shares.len();
+                shares[i].index = index as usize;
+            }
+            Mutation::SetShareValue { share, value } => {
+                let i = share as usize % shares.len();
+                shares[i].value = Fr::from(value);
+            }
+        }
+    }
+
+    let _ = checker.verify_consistency_proof(&proof);
+    let _ = kzg().verify(&commitment, &opening);
+    let _ = ShamirSecretSharing::<Fr>::reconstruct_secret(&shares);
+});
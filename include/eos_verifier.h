@@ -0,0 +1,65 @@
+/*
+ * C API for the EOS delegation verifier.
+ *
+ * Build the library with the `ffi` feature (enabled by default):
+ *
+ *     cargo build --release --no-default-features --features ffi
+ *
+ * and link against target/release/libeos_delegation.{so,dylib,a}.
+ * All byte strings use the crate's wire format; see src/ffi.rs.
+ */
+#ifndef EOS_VERIFIER_H
+#define EOS_VERIFIER_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define EOS_ABI_VERSION 1
+
+/* Borrowed byte string; `data` may be NULL only when `len` is 0. */
+typedef struct EosBytes {
+    const uint8_t *data;
+    size_t len;
+} EosBytes;
+
+typedef enum EosStatus {
+    EOS_ACCEPTED = 0,
+    EOS_REJECTED = 1,
+    EOS_NULL_POINTER = 2,
+    EOS_INVALID_KEY = 3,
+    EOS_INVALID_PROOF = 4,
+    EOS_INVALID_PUBLIC_INPUTS = 5,
+    EOS_INTERNAL = 6,
+} EosStatus;
+
+/* ABI version of the loaded library; compare with EOS_ABI_VERSION. */
+uint32_t eos_abi_version(void);
+
+/* Size in bytes of one encoded public input. */
+size_t eos_field_size(void);
+
+/* Static, NUL-terminated description of a status code. */
+const char *eos_status_message(int32_t status);
+
+/*
+ * Verify a delegation proof.
+ *
+ * proof:            DelegationResult::transcript_bytes
+ * verification_key: VerifierKey::to_bytes
+ * public_inputs:    eos_field_size() bytes per input, concatenated
+ *
+ * The key carries the circuit, and EOS_ACCEPTED means the committed
+ * witness satisfies it. The buffers are only read during the call.
+ * Thread-safe.
+ */
+EosStatus eos_verify(EosBytes proof, EosBytes verification_key, EosBytes public_inputs);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* EOS_VERIFIER_H */
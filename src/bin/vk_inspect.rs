@@ -0,0 +1,60 @@
+//! 查看发布的验证密钥文件
+//!
+//! 用法: `vk-inspect <key.vk> [--points]`
+//!
+//! 按 `protocol::verification_key` 中规定的格式解码文件，打印版本、曲线、
+//! 公开输入个数和指纹；`--points` 同时打印每个点的压缩编码。解码失败时
+//! 以非零状态退出。
+
+use ark_bls12_381::Bls12_381;
+use ark_bn254::Bn254;
+use ark_serialize::CanonicalSerialize;
+use eos_delegation::protocol::{verification_key_curve, KeyCurve, VerificationKey, VERIFICATION_KEY_VERSION};
+use eos_delegation::serialization::WireFormat;
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let show_points = args.iter().any(|arg| arg == "--points");
+    let Some(path) = args.iter().find(|arg| !arg.starts_with("--")) else {
+        eprintln!("usage: vk-inspect <key.vk> [--points]");
+        std::process::exit(2);
+    };
+    if let Err(e) = inspect(path, show_points) {
+        eprintln!("vk-inspect: {}: {}", path, e);
+        std::process::exit(1);
+    }
+}
+
+fn inspect(path: &str, show_points: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let bytes = std::fs::read(path)?;
+    match verification_key_curve(&bytes)? {
+        tag if tag == <Bls12_381 as KeyCurve>::TAG => print_key::<Bls12_381>(&bytes, show_points),
+        tag if tag == <Bn254 as KeyCurve>::TAG => print_key::<Bn254>(&bytes, show_points),
+        tag => Err(format!("unknown curve tag {}", tag).into()),
+    }
+}
+
+fn print_key<E: KeyCurve>(bytes: &[u8], show_points: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let key = VerificationKey::<E>::from_bytes(bytes)?;
+    println!("format:        EOVK v{} ({} bytes)", VERIFICATION_KEY_VERSION, bytes.len());
+    println!("curve:         {}", E::NAME);
+    println!("public inputs: {}", key.num_public_inputs);
+    println!("fingerprint:   {}", hex(&key.fingerprint()));
+    if show_points {
+        println!("{:<14} {}", "g:", hex(&compressed(&key.g)));
+        for (name, point) in [("h", &key.h), ("h_tau", &key.h_tau)] {
+            println!("{:<14} {}", format!("{}:", name), hex(&compressed(point)));
+        }
+    }
+    Ok(())
+}
+
+fn compressed(point: &impl CanonicalSerialize) -> Vec<u8> {
+    let mut out = Vec::new();
+    point.serialize_compressed(&mut out).expect("serializing into a Vec cannot fail");
+    out
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
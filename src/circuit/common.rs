@@ -8,7 +8,7 @@
 
 use ark_ff::Field;
 use ark_poly::univariate::DensePolynomial;
-use ark_ec::{CurveGroup, VariableBaseMSM};
+use ark_ec::CurveGroup;
 
 /// Polynomial addition operation in circuits
 pub struct PolyAdd<F: Field> {
@@ -0,0 +1,408 @@
+//! FRI 多项式承诺
+//!
+//! 承诺为多项式在陪集 s·<ω>（大小为次数上界乘以扩张因子）上求值向量的
+//! Merkle 根，不需要可信设置和配对，因此适用于 `small_fields.rs` 中的小域。
+//! 在点 z 打开值 v 时，证明商 q(x) = (p(x) - v) / (x - z) 的求值向量接近
+//! 低次多项式：每轮用转录挑战 β 把 f 折叠为
+//! f'(x^2) = (f(x) + f(-x)) / 2 + β (f(x) - f(-x)) / (2x)，
+//! 最后一层为常数；验证者在随机查询位置检查各层折叠的一致性，第 0 层的
+//! 商值由 p 的打开值直接算出。
+
+use ark_ff::{batch_inversion, Field};
+use ark_poly::univariate::DensePolynomial;
+use ark_poly::{DenseUVPolynomial, Polynomial};
+use ark_std::vec::Vec;
+
+use crate::circuit::{
+    CommittedVector, FriField, MerkleCommitment, MerkleOpening, MerkleVectorCommitment, PolynomialCommitmentScheme,
+};
+use crate::piop::{Transcript, TranscriptHash};
+
+/// FRI 转录的域标签
+pub const FRI_DOMAIN: &[u8] = b"EOS-fri-opening-v1";
+
+/// FRI 承诺方案参数
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FriCommitmentScheme<F: FriField> {
+    pub max_degree: usize,
+    /// 扩张因子的对数：求值域大小为次数上界的 2^log_blowup 倍
+    pub log_blowup: u32,
+    pub num_queries: usize,
+    pub hash: TranscriptHash,
+    merkle: MerkleVectorCommitment<F>,
+}
+
+/// 多项式承诺
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FriCommitment {
+    pub root: MerkleCommitment,
+}
+
+/// 一个查询位置在某一层的一对打开：x 与 -x
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FriQueryLayer<F: Field> {
+    pub low: MerkleOpening<F>,
+    pub high: MerkleOpening<F>,
+}
+
+/// 打开证明
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FriOpeningProof<F: Field> {
+    pub point: F,
+    pub evaluation: F,
+    /// 第 1 层起各折叠层的承诺
+    pub layer_roots: Vec<MerkleCommitment>,
+    /// 最后一次折叠得到的常数
+    pub final_value: F,
+    /// 每个查询位置在各层的打开，第 0 层为 p 的求值向量
+    pub queries: Vec<Vec<FriQueryLayer<F>>>,
+}
+
+/// FRI 错误类型
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FriError {
+    DegreeTooLarge { degree: usize, max_degree: usize },
+    /// 打开点落在求值陪集上，商无定义
+    PointInDomain,
+}
+
+impl std::fmt::Display for FriError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            FriError::DegreeTooLarge { degree, max_degree } => {
+                write!(f, "Polynomial degree {} exceeds FRI bound {}", degree, max_degree)
+            }
+            FriError::PointInDomain => write!(f, "Opening point lies on the FRI evaluation domain"),
+        }
+    }
+}
+
+impl std::error::Error for FriError {}
+
+impl<F: FriField> FriCommitmentScheme<F> {
+    /// 扩张因子 4、64 次查询（推测安全性约 128 比特）
+    pub fn new(max_degree: usize) -> Self {
+        Self::with_parameters(max_degree, 2, 64, TranscriptHash::Sha256)
+    }
+
+    pub fn with_parameters(max_degree: usize, log_blowup: u32, num_queries: usize, hash: TranscriptHash) -> Self {
+        let scheme = Self { max_degree, log_blowup, num_queries, hash, merkle: MerkleVectorCommitment::new(hash) };
+        assert!(
+            scheme.domain_size().trailing_zeros() <= F::TWO_ADICITY,
+            "FRI domain exceeds the two-adic subgroup of the field"
+        );
+        scheme
+    }
+
+    /// 次数上界（2 的幂），商多项式的次数严格小于它
+    fn degree_bound(&self) -> usize {
+        (self.max_degree + 1).next_power_of_two().max(2)
+    }
+
+    /// 第 0 层求值域大小
+    pub fn domain_size(&self) -> usize {
+        self.degree_bound() << self.log_blowup
+    }
+
+    /// 折叠轮数，最后一轮得到常数
+    fn num_rounds(&self) -> usize {
+        self.degree_bound().trailing_zeros() as usize
+    }
+
+    /// 第 `layer` 层的陪集偏移和生成元
+    fn layer_domain(&self, layer: usize) -> (F, F) {
+        let size = self.domain_size() >> layer;
+        let mut shift = F::coset_shift();
+        for _ in 0..layer {
+            shift.square_in_place();
+        }
+        (shift, F::root_of_unity(size).expect("domain size is checked in the constructor"))
+    }
+
+    fn check_degree(&self, polynomial: &DensePolynomial<F>) -> Result<(), FriError> {
+        if polynomial.degree() > self.max_degree {
+            return Err(FriError::DegreeTooLarge { degree: polynomial.degree(), max_degree: self.max_degree });
+        }
+        Ok(())
+    }
+
+    /// p 在第 0 层陪集上的求值向量
+    fn codeword(&self, polynomial: &DensePolynomial<F>) -> Vec<F> {
+        let (shift, root) = self.layer_domain(0);
+        let mut values = vec![F::zero(); self.domain_size()];
+        let mut power = F::one();
+        for (value, coefficient) in values.iter_mut().zip(polynomial.coeffs()) {
+            *value = *coefficient * power;
+            power *= shift;
+        }
+        fft_in_place(&mut values, root);
+        values
+    }
+
+    pub fn commit(&self, polynomial: &DensePolynomial<F>) -> Result<FriCommitment, FriError> {
+        self.check_degree(polynomial)?;
+        Ok(FriCommitment { root: self.merkle.commit(&self.codeword(polynomial)).commitment() })
+    }
+
+    fn transcript(&self, commitment: &FriCommitment, point: F, evaluation: F) -> Transcript {
+        let mut transcript = Transcript::with_hash(FRI_DOMAIN, self.hash);
+        transcript.append_message(b"root", &commitment.root.root);
+        transcript.append_field(b"point", &point);
+        transcript.append_field(b"evaluation", &evaluation);
+        transcript
+    }
+
+    /// 查询位置，在第 0 层域的前一半中取值（后一半是对应的 -x）
+    fn query_indices(&self, transcript: &mut Transcript) -> Vec<usize> {
+        let half = self.domain_size() / 2;
+        (0..self.num_queries)
+            .map(|_| {
+                let bytes = transcript.challenge_bytes(b"query");
+                let mut index = [0u8; 8];
+                index.copy_from_slice(&bytes[..8]);
+                (u64::from_le_bytes(index) % half as u64) as usize
+            })
+            .collect()
+    }
+
+    /// 折叠一对值 f(x)、f(-x)
+    fn fold(low: F, high: F, x: F, beta: F) -> F {
+        let two_inverse = F::from(2u64).inverse().expect("characteristic is odd");
+        (low + high) * two_inverse + beta * (low - high) * two_inverse * x.inverse().expect("domain excludes zero")
+    }
+
+    pub fn open(&self, polynomial: &DensePolynomial<F>, point: F) -> Result<FriOpeningProof<F>, FriError> {
+        self.check_degree(polynomial)?;
+        let evaluation = polynomial.evaluate(&point);
+        let codeword = self.codeword(polynomial);
+        let committed = self.merkle.commit(&codeword);
+        let commitment = FriCommitment { root: committed.commitment() };
+
+        // 第 0 层商值 (p(x) - v) / (x - z)
+        let (shift, root) = self.layer_domain(0);
+        let mut denominators: Vec<F> = powers(shift, root, codeword.len()).map(|x| x - point).collect();
+        if denominators.iter().any(|d| d.is_zero()) {
+            return Err(FriError::PointInDomain);
+        }
+        batch_inversion(&mut denominators);
+        let mut current: Vec<F> = codeword.iter().zip(&denominators).map(|(p, d)| (*p - evaluation) * d).collect();
+
+        let mut transcript = self.transcript(&commitment, point, evaluation);
+        let mut layers: Vec<CommittedVector<F>> = vec![committed];
+        let mut layer_roots = Vec::new();
+        let rounds = self.num_rounds();
+        for round in 0..rounds {
+            let beta: F = transcript.challenge_element(b"fold");
+            let (shift, root) = self.layer_domain(round);
+            let half = current.len() / 2;
+            current = powers(shift, root, half)
+                .enumerate()
+                .map(|(i, x)| Self::fold(current[i], current[i + half], x, beta))
+                .collect();
+            if round + 1 < rounds {
+                let layer = self.merkle.commit(&current);
+                transcript.append_message(b"layer", &layer.commitment().root);
+                layer_roots.push(layer.commitment());
+                layers.push(layer);
+            }
+        }
+        let final_value = current[0];
+        transcript.append_field(b"final", &final_value);
+
+        let queries = self
+            .query_indices(&mut transcript)
+            .into_iter()
+            .map(|index| {
+                layers
+                    .iter()
+                    .enumerate()
+                    .map(|(layer, committed)| {
+                        let half = self.domain_size() >> (layer + 1);
+                        let position = index % half;
+                        FriQueryLayer {
+                            low: self.merkle.open(committed, position).expect("position is inside the layer"),
+                            high: self.merkle.open(committed, position + half).expect("position is inside the layer"),
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Ok(FriOpeningProof { point, evaluation, layer_roots, final_value, queries })
+    }
+
+    pub fn verify(&self, commitment: &FriCommitment, proof: &FriOpeningProof<F>) -> bool {
+        let rounds = self.num_rounds();
+        if commitment.root.len != self.domain_size()
+            || proof.layer_roots.len() + 1 != rounds
+            || proof.queries.len() != self.num_queries
+        {
+            return false;
+        }
+        let mut transcript = self.transcript(commitment, proof.point, proof.evaluation);
+        let mut betas = Vec::with_capacity(rounds);
+        for round in 0..rounds {
+            betas.push(transcript.challenge_element::<F>(b"fold"));
+            if let Some(layer) = proof.layer_roots.get(round) {
+                if layer.len != self.domain_size() >> (round + 1) {
+                    return false;
+                }
+                transcript.append_message(b"layer", &layer.root);
+            }
+        }
+        transcript.append_field(b"final", &proof.final_value);
+        let indices = self.query_indices(&mut transcript);
+
+        indices.iter().zip(&proof.queries).all(|(&index, query)| {
+            if query.len() != rounds {
+                return false;
+            }
+            // 上一层折叠出的值，须等于本层对应位置的打开值
+            let mut expected: Option<F> = None;
+            for (layer, openings) in query.iter().enumerate() {
+                let half = self.domain_size() >> (layer + 1);
+                let position = index % half;
+                let root = if layer == 0 { &commitment.root } else { &proof.layer_roots[layer - 1] };
+                if openings.low.index != position
+                    || openings.high.index != position + half
+                    || !self.merkle.verify(root, &openings.low)
+                    || !self.merkle.verify(root, &openings.high)
+                {
+                    return false;
+                }
+                let (shift, generator) = self.layer_domain(layer);
+                let x = shift * generator.pow([position as u64]);
+                let (mut low, mut high) = (openings.low.value, openings.high.value);
+                if layer == 0 {
+                    let (Some(low_inverse), Some(high_inverse)) =
+                        ((x - proof.point).inverse(), (-x - proof.point).inverse())
+                    else {
+                        return false;
+                    };
+                    low = (low - proof.evaluation) * low_inverse;
+                    high = (high - proof.evaluation) * high_inverse;
+                } else {
+                    // 上一层的位置 index % (2·half) 落在本层域的 position 或 position + half
+                    let previous = index % (2 * half);
+                    let opened = if previous < half { low } else { high };
+                    if expected != Some(opened) {
+                        return false;
+                    }
+                }
+                expected = Some(Self::fold(low, high, x, betas[layer]));
+            }
+            expected == Some(proof.final_value)
+        })
+    }
+}
+
+impl<F: FriField> PolynomialCommitmentScheme<F> for FriCommitmentScheme<F> {
+    type Commitment = FriCommitment;
+    type Proof = FriOpeningProof<F>;
+    type Error = FriError;
+
+    fn commit(&self, polynomial: &DensePolynomial<F>) -> Result<Self::Commitment, Self::Error> {
+        self.commit(polynomial)
+    }
+
+    fn open(&self, polynomial: &DensePolynomial<F>, point: F) -> Result<Self::Proof, Self::Error> {
+        self.open(polynomial, point)
+    }
+
+    fn verify(&self, commitment: &Self::Commitment, proof: &Self::Proof) -> bool {
+        self.verify(commitment, proof)
+    }
+}
+
+/// 陪集元素 shift·root^i，i = 0..count
+fn powers<F: Field>(shift: F, root: F, count: usize) -> impl Iterator<Item = F> {
+    std::iter::successors(Some(shift), move |x| Some(*x * root)).take(count)
+}
+
+/// 原位基 2 FFT：values[i] ← Σ_j values[j]·root^(ij)，`root` 的阶为 values.len()
+fn fft_in_place<F: Field>(values: &mut [F], root: F) {
+    let n = values.len();
+    let log_n = n.trailing_zeros();
+    for i in 0..n {
+        let j = i.reverse_bits() >> (usize::BITS - log_n);
+        if i < j {
+            values.swap(i, j);
+        }
+    }
+    let mut size = 2;
+    while size <= n {
+        let step = root.pow([(n / size) as u64]);
+        for chunk in values.chunks_mut(size) {
+            let mut twiddle = F::one();
+            for i in 0..size / 2 {
+                let t = chunk[i + size / 2] * twiddle;
+                chunk[i + size / 2] = chunk[i] - t;
+                chunk[i] += t;
+                twiddle *= step;
+            }
+        }
+        size *= 2;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::{Goldilocks, Mersenne31, Mersenne31Ext};
+    use ark_std::{test_rng, UniformRand};
+
+    fn round_trip<F: FriField + UniformRand>() {
+        let mut rng = test_rng();
+        let scheme = FriCommitmentScheme::<F>::with_parameters(30, 2, 16, TranscriptHash::Sha256);
+        let polynomial = DensePolynomial::<F>::rand(30, &mut rng);
+        let commitment = scheme.commit(&polynomial).unwrap();
+        let point = F::rand(&mut rng);
+        let proof = scheme.open(&polynomial, point).unwrap();
+        assert_eq!(proof.evaluation, polynomial.evaluate(&point));
+        assert!(scheme.verify(&commitment, &proof));
+
+        let mut wrong = proof.clone();
+        wrong.evaluation += F::one();
+        assert!(!scheme.verify(&commitment, &wrong));
+        let other = scheme.commit(&DensePolynomial::rand(30, &mut rng)).unwrap();
+        assert!(!scheme.verify(&other, &proof));
+        let mut forged = proof.clone();
+        forged.final_value += F::one();
+        assert!(!scheme.verify(&commitment, &forged));
+
+        let too_large = DensePolynomial::<F>::rand(31, &mut rng);
+        assert_eq!(scheme.commit(&too_large), Err(FriError::DegreeTooLarge { degree: 31, max_degree: 30 }));
+    }
+
+    #[test]
+    fn test_fri_opening_over_small_fields() {
+        round_trip::<Goldilocks>();
+        round_trip::<Mersenne31Ext>();
+        round_trip::<ark_bls12_381::Fr>();
+
+        // 基域 Mersenne31 上的多项式嵌入扩域后承诺
+        let coefficients: Vec<Mersenne31Ext> =
+            (1..=5u64).map(|c| Mersenne31Ext::new(Mersenne31::from(c), Mersenne31::from(0u64))).collect();
+        let polynomial = DensePolynomial::from_coefficients_vec(coefficients);
+        let scheme = FriCommitmentScheme::<Mersenne31Ext>::new(4);
+        let commitment = scheme.commit(&polynomial).unwrap();
+        let proof = scheme.open(&polynomial, Mersenne31Ext::from(1u64)).unwrap();
+        assert_eq!(proof.evaluation, Mersenne31Ext::from(15u64));
+        assert!(scheme.verify(&commitment, &proof));
+
+        // 陪集上的点无法打开
+        let on_domain = Goldilocks::coset_shift();
+        let scheme = FriCommitmentScheme::<Goldilocks>::new(4);
+        let polynomial = DensePolynomial::from_coefficients_vec(vec![Goldilocks::from(1u64); 5]);
+        assert_eq!(scheme.open(&polynomial, on_domain), Err(FriError::PointInDomain));
+
+        // FFT 与逐点求值一致
+        let root = Goldilocks::root_of_unity(8).unwrap();
+        let mut values: Vec<Goldilocks> = (1..=8u64).map(Goldilocks::from).collect();
+        let polynomial = DensePolynomial::from_coefficients_vec(values.clone());
+        fft_in_place(&mut values, root);
+        for (i, value) in values.iter().enumerate() {
+            assert_eq!(*value, polynomial.evaluate(&root.pow([i as u64])));
+        }
+    }
+}
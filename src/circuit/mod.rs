@@ -3,8 +3,12 @@
 //! This module contains common circuit operations and polynomial commitment schemes
 //! that are used throughout the EOS delegation protocol.
 
-pub mod common;
-pub mod pc_schemes;
+pub(crate) mod common;
+pub(crate) mod fri;
+pub(crate) mod pc_schemes;
+pub(crate) mod small_fields;
 
 pub use common::*;
+pub use fri::*;
 pub use pc_schemes::*;
+pub use small_fields::*;
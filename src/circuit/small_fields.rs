@@ -0,0 +1,270 @@
+//! 小域配置
+//!
+//! 大素数域（BLS12-381、BN254 的标量域）配合 KZG 使用，需要配对。这里定义
+//! 哈希类证明系统常用的小域，它们只能配合不需要配对的 FRI（见 `fri.rs`）：
+//!
+//! - Goldilocks：p = 2^64 - 2^32 + 1，乘法群含 2^32 阶子群，可直接做 FFT
+//! - GoldilocksExt：Goldilocks 的二次扩域，供求和检查抽取约 128 比特的挑战
+//! - Mersenne31：p = 2^31 - 1，乘法群的 2-adicity 只有 1，因此 FRI 在其二次扩域
+//!   Mersenne31Ext = F_p[i]/(i^2 + 1) 上运行，扩域乘法群含 2^32 阶子群；
+//!   基域上的多项式按系数嵌入扩域后承诺
+
+use ark_ff::{BigInt, FftField, Field, Fp2, Fp2Config, Fp64, MontBackend, MontConfig, MontFp, One};
+
+use crate::piop::SumcheckParameters;
+
+const GOLDILOCKS_MODULUS: u64 = 0xffff_ffff_0000_0001;
+
+pub struct GoldilocksConfig;
+
+impl MontConfig<1> for GoldilocksConfig {
+    const MODULUS: BigInt<1> = BigInt([GOLDILOCKS_MODULUS]);
+
+    const GENERATOR: Goldilocks = MontFp!("7");
+
+    // 7^((p - 1) / 2^32)
+    const TWO_ADIC_ROOT_OF_UNITY: Goldilocks = MontFp!("1753635133440165772");
+
+    // arkworks 0.4 的通用 CIOS 在模数最高位非空闲时丢弃进位，这里直接做单字
+    // Montgomery 约减：(ab + mp) / 2^64 < 2p，溢出的进位也需要减去 p
+    fn mul_assign(a: &mut Goldilocks, b: &Goldilocks) {
+        let product = (a.0).0[0] as u128 * (b.0).0[0] as u128;
+        let m = (product as u64).wrapping_mul(Self::INV);
+        let (sum, carry) = product.overflowing_add(m as u128 * GOLDILOCKS_MODULUS as u128);
+        let reduced = (sum >> 64) as u64;
+        (a.0).0[0] = if carry || reduced >= GOLDILOCKS_MODULUS {
+            reduced.wrapping_sub(GOLDILOCKS_MODULUS)
+        } else {
+            reduced
+        };
+    }
+}
+
+/// Goldilocks 域
+pub type Goldilocks = Fp64<MontBackend<GoldilocksConfig, 1>>;
+
+pub struct Mersenne31Config;
+
+impl MontConfig<1> for Mersenne31Config {
+    const MODULUS: BigInt<1> = BigInt([0x7fff_ffff]);
+
+    const GENERATOR: Mersenne31 = MontFp!("7");
+
+    // p - 1 = 2(2^30 - 1)，唯一的 2 阶单位根是 -1
+    const TWO_ADIC_ROOT_OF_UNITY: Mersenne31 = MontFp!("-1");
+}
+
+/// Mersenne31 域
+pub type Mersenne31 = Fp64<MontBackend<Mersenne31Config, 1>>;
+
+pub struct Mersenne31ExtConfig;
+
+impl Fp2Config for Mersenne31ExtConfig {
+    type Fp = Mersenne31;
+
+    // p ≡ 3 (mod 4)，-1 是二次非剩余
+    const NONRESIDUE: Mersenne31 = MontFp!("-1");
+
+    const FROBENIUS_COEFF_FP2_C1: &'static [Mersenne31] = &[MontFp!("1"), MontFp!("-1")];
+}
+
+/// Mersenne31 的二次扩域（"复数"扩域）
+pub type Mersenne31Ext = Fp2<Mersenne31ExtConfig>;
+
+pub struct GoldilocksExtConfig;
+
+impl Fp2Config for GoldilocksExtConfig {
+    type Fp = Goldilocks;
+
+    // 7 是乘法群生成元，因而是二次非剩余；7^((p-1)/2) = -1
+    const NONRESIDUE: Goldilocks = MontFp!("7");
+
+    const FROBENIUS_COEFF_FP2_C1: &'static [Goldilocks] = &[MontFp!("1"), MontFp!("-1")];
+}
+
+/// Goldilocks 的二次扩域，约 128 比特，用于求和检查挑战
+pub type GoldilocksExt = Fp2<GoldilocksExtConfig>;
+
+/// 可以运行 FRI 的域：乘法群含 2^TWO_ADICITY 阶子群
+pub trait FriField: Field {
+    const TWO_ADICITY: u32;
+
+    /// 阶恰为 2^TWO_ADICITY 的单位根
+    fn two_adic_root_of_unity() -> Self;
+
+    /// 求值陪集的偏移，不属于任何 2 的幂阶子群
+    fn coset_shift() -> Self;
+
+    /// 阶恰为 `size`（2 的幂）的单位根
+    fn root_of_unity(size: usize) -> Option<Self> {
+        let log_size = size.trailing_zeros();
+        if !size.is_power_of_two() || log_size > Self::TWO_ADICITY {
+            return None;
+        }
+        let mut root = Self::two_adic_root_of_unity();
+        for _ in log_size..Self::TWO_ADICITY {
+            root.square_in_place();
+        }
+        Some(root)
+    }
+}
+
+impl FriField for Goldilocks {
+    const TWO_ADICITY: u32 = <Goldilocks as FftField>::TWO_ADICITY;
+
+    fn two_adic_root_of_unity() -> Self {
+        <Goldilocks as FftField>::TWO_ADIC_ROOT_OF_UNITY
+    }
+
+    fn coset_shift() -> Self {
+        <Goldilocks as FftField>::GENERATOR
+    }
+}
+
+impl FriField for Mersenne31Ext {
+    // p^2 - 1 = (p - 1)(p + 1) = 2(2^30 - 1) · 2^31
+    const TWO_ADICITY: u32 = 32;
+
+    fn two_adic_root_of_unity() -> Self {
+        // 任一元素的 (p^2 - 1) / 2^32 = 2^30 - 1 次幂落在 2^32 阶子群中，
+        // 取第一个阶恰为 2^32 的
+        (2u64..)
+            .map(|c| Mersenne31Ext::new(Mersenne31::from(c), Mersenne31::one()).pow([(1u64 << 30) - 1]))
+            .find(|root| {
+                let mut half = *root;
+                for _ in 0..31 {
+                    half.square_in_place();
+                }
+                half != Mersenne31Ext::one()
+            })
+            .expect("the multiplicative group has elements of order 2^32")
+    }
+
+    fn coset_shift() -> Self {
+        // 基域元素落在 2 的幂阶子群中当且仅当它是 ±1
+        Mersenne31Ext::from(3u64)
+    }
+}
+
+impl FriField for ark_bls12_381::Fr {
+    const TWO_ADICITY: u32 = <ark_bls12_381::Fr as FftField>::TWO_ADICITY;
+
+    fn two_adic_root_of_unity() -> Self {
+        <ark_bls12_381::Fr as FftField>::TWO_ADIC_ROOT_OF_UNITY
+    }
+
+    fn coset_shift() -> Self {
+        <ark_bls12_381::Fr as FftField>::GENERATOR
+    }
+}
+
+/// 部署选择的域及其承诺方案
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FieldChoice {
+    Bls12_381,
+    Bn254,
+    Goldilocks,
+    Mersenne31,
+}
+
+impl FieldChoice {
+    pub const ALL: [FieldChoice; 4] =
+        [FieldChoice::Bls12_381, FieldChoice::Bn254, FieldChoice::Goldilocks, FieldChoice::Mersenne31];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            FieldChoice::Bls12_381 => "bls12-381",
+            FieldChoice::Bn254 => "bn254",
+            FieldChoice::Goldilocks => "goldilocks",
+            FieldChoice::Mersenne31 => "mersenne31",
+        }
+    }
+
+    /// 是否为小域；小域没有配对，只能使用 FRI 和 PIOP 后端
+    pub fn is_small(self) -> bool {
+        matches!(self, FieldChoice::Goldilocks | FieldChoice::Mersenne31)
+    }
+
+    /// 该域使用的多项式承诺方案
+    pub fn pcs_name(self) -> &'static str {
+        if self.is_small() {
+            "fri"
+        } else {
+            "kzg"
+        }
+    }
+
+    /// 达到 `security_bits` 的求和检查参数；小域的挑战取自其二次扩域
+    pub fn sumcheck_parameters(self, num_vars: usize, security_bits: usize) -> SumcheckParameters {
+        match self {
+            FieldChoice::Bls12_381 => SumcheckParameters::for_security::<ark_bls12_381::Fr>(num_vars, security_bits),
+            FieldChoice::Bn254 => SumcheckParameters::for_security::<ark_bn254::Fr>(num_vars, security_bits),
+            FieldChoice::Goldilocks => SumcheckParameters::for_security::<GoldilocksExt>(num_vars, security_bits),
+            FieldChoice::Mersenne31 => SumcheckParameters::for_security::<Mersenne31Ext>(num_vars, security_bits),
+        }
+    }
+
+    /// 基域元素的比特数
+    pub fn bits(self) -> u32 {
+        match self {
+            FieldChoice::Bls12_381 => 255,
+            FieldChoice::Bn254 => 254,
+            FieldChoice::Goldilocks => 64,
+            FieldChoice::Mersenne31 => 31,
+        }
+    }
+}
+
+impl std::str::FromStr for FieldChoice {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        FieldChoice::ALL
+            .into_iter()
+            .find(|field| field.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("unknown field '{}'", s))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_ff::{PrimeField, Zero};
+
+    #[test]
+    fn test_small_field_roots_of_unity() {
+        assert_eq!(Goldilocks::from(u64::MAX - (1 << 32) + 1), -Goldilocks::one());
+        assert_eq!(Goldilocks::from(1u64 << 32) * Goldilocks::from(1u64 << 32), Goldilocks::from((1u64 << 32) - 1));
+        let minus_one = -Goldilocks::one();
+        assert_eq!(minus_one * minus_one, Goldilocks::one());
+        assert_eq!(minus_one.square(), Goldilocks::one());
+        assert_eq!(Goldilocks::from(7u64).pow([(GOLDILOCKS_MODULUS - 1) >> 32]), Goldilocks::two_adic_root_of_unity());
+        assert_eq!(Mersenne31::from(1u64 << 31), Mersenne31::one());
+        assert_eq!(Mersenne31::MODULUS_BIT_SIZE, 31);
+        let i = Mersenne31Ext::new(Mersenne31::zero(), Mersenne31::one());
+        assert_eq!(i * i, -Mersenne31Ext::one());
+        let u = GoldilocksExt::new(Goldilocks::zero(), Goldilocks::one());
+        assert_eq!(u * u, GoldilocksExt::from(7u64));
+        assert_eq!(u * u.inverse().unwrap(), GoldilocksExt::one());
+
+        fn check<F: FriField>() {
+            let root = F::root_of_unity(1 << F::TWO_ADICITY).unwrap();
+            let mut power = root;
+            for _ in 1..F::TWO_ADICITY {
+                power.square_in_place();
+            }
+            assert_eq!(power, -F::one());
+            assert_eq!(F::root_of_unity(4).unwrap().pow([4u64]), F::one());
+            assert!(F::root_of_unity(3).is_none());
+            assert_ne!(F::coset_shift().pow([1u64 << F::TWO_ADICITY]), F::one());
+        }
+        check::<Goldilocks>();
+        check::<Mersenne31Ext>();
+        assert_eq!(<Mersenne31 as FftField>::TWO_ADICITY, 1);
+
+        assert_eq!("Goldilocks".parse::<FieldChoice>(), Ok(FieldChoice::Goldilocks));
+        assert_eq!(FieldChoice::Mersenne31.pcs_name(), "fri");
+        assert_eq!(FieldChoice::Bn254.pcs_name(), "kzg");
+        assert!("babybear".parse::<FieldChoice>().is_err());
+    }
+}
@@ -0,0 +1,353 @@
+//! 电路表达式 DSL
+//!
+//! 手写 `CustomCircuit` 时每个中间值都要先算出取值、分配见证、再按编号
+//! 添加约束。这里的 `CircuitBuilder` 把变量包装成 `Expr`，对 `Expr` 做
+//! `+ - * /` 运算时自动分配中间见证并记录对应的约束：
+//!
+//! ```
+//! use ark_bls12_381::Fr;
+//! use eos_delegation::circuit;
+//!
+//! let circuit = circuit!("sum_of_squares", private(x = Fr::from(3u64), y = Fr::from(4u64)), public(out = Fr::from(25u64)), {
+//!     let z = x * x + y * y;
+//!     assert_eq!(z, out);
+//! });
+//! assert!(circuit.check_witness().is_satisfied());
+//! ```
+//!
+//! 每个乘法、除法产生一个乘法约束，加减法产生一个加法约束；相等断言
+//! 写成 a · 1 = b。常量按值去重后作为公开输入分配，与电路模板的做法一致。
+//!
+//! `CircuitBuilder::with_budget` 给构造器设置规模上限。一旦超出，后续运算
+//! 不再分配变量和约束，`try_finish` 返回超出的那一项，大电路可以在构造
+//! 途中通过 `exceeded` 提前结束。
+
+use std::cell::{Cell, RefCell};
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+use ark_ff::PrimeField;
+
+use crate::custom_circuits::{BudgetExceeded, CircuitBudget, CustomCircuit, Variable};
+
+/// 以表达式构造电路的构造器
+pub struct CircuitBuilder<F: PrimeField> {
+    circuit: RefCell<CustomCircuit<F>>,
+    constants: RefCell<Vec<(F, Variable)>>,
+    budget: CircuitBudget,
+    exceeded: Cell<Option<BudgetExceeded>>,
+}
+
+/// 构造器中的一个变量，可直接参与算术运算
+#[derive(Clone, Copy)]
+pub struct Expr<'a, F: PrimeField> {
+    builder: &'a CircuitBuilder<F>,
+    variable: Variable,
+}
+
+impl<F: PrimeField> CircuitBuilder<F> {
+    pub fn new(name: &str) -> Self {
+        Self::with_budget(name, CircuitBudget::unlimited())
+    }
+
+    /// 带规模上限的构造器
+    pub fn with_budget(name: &str, budget: CircuitBudget) -> Self {
+        Self {
+            circuit: RefCell::new(CustomCircuit::new(name.to_string())),
+            constants: RefCell::new(Vec::new()),
+            budget,
+            exceeded: Cell::new(None),
+        }
+    }
+
+    /// 私有见证
+    pub fn witness(&self, value: F) -> Expr<'_, F> {
+        let variable = self.push(|circuit| circuit.add_private_witness(value));
+        self.expr(variable)
+    }
+
+    /// 只留在委托方的私有见证，见 `Privacy::DelegatorOnly`
+    pub fn delegator_witness(&self, value: F) -> Expr<'_, F> {
+        let variable = self.push(|circuit| circuit.add_delegator_witness(value));
+        self.expr(variable)
+    }
+
+    /// 公开输入
+    pub fn public(&self, value: F) -> Expr<'_, F> {
+        let variable = self.push(|circuit| circuit.add_public_input(value));
+        self.expr(variable)
+    }
+
+    /// 常量，相同的值只分配一次
+    pub fn constant(&self, value: F) -> Expr<'_, F> {
+        if value.is_one() {
+            return self.expr(Variable::ONE);
+        }
+        if let Some(&(_, variable)) = self.constants.borrow().iter().find(|(v, _)| *v == value) {
+            return self.expr(variable);
+        }
+        let variable = self.push(|circuit| circuit.add_public_input(value));
+        if self.exceeded.get().is_none() {
+            self.constants.borrow_mut().push((value, variable));
+        }
+        self.expr(variable)
+    }
+
+    /// 约束 a = b
+    pub fn assert_eq(&self, a: Expr<'_, F>, b: Expr<'_, F>) {
+        self.check_owner(&a);
+        self.check_owner(&b);
+        self.push(|circuit| {
+            circuit.add_multiplication_constraint(a.variable, Variable::ONE, b.variable);
+            b.variable
+        });
+    }
+
+    /// 已经包装好的变量，例如由 `CustomCircuit` 的其他构造方法得到的变量
+    pub fn expr(&self, variable: Variable) -> Expr<'_, F> {
+        Expr { builder: self, variable }
+    }
+
+    /// 第一次超出预算的情况
+    pub fn exceeded(&self) -> Option<BudgetExceeded> {
+        self.exceeded.get()
+    }
+
+    /// 结束构造，返回电路；超出预算时 panic
+    pub fn finish(self) -> CustomCircuit<F> {
+        self.try_finish().unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// 结束构造，超出预算时返回错误
+    pub fn try_finish(self) -> Result<CustomCircuit<F>, BudgetExceeded> {
+        match self.exceeded.get() {
+            Some(e) => Err(e),
+            None => Ok(self.circuit.into_inner()),
+        }
+    }
+
+    /// 在电路上执行一次分配并检查预算；已经超出时什么也不做，返回常量 1
+    fn push(&self, allocate: impl FnOnce(&mut CustomCircuit<F>) -> Variable) -> Variable {
+        if self.exceeded.get().is_some() {
+            return Variable::ONE;
+        }
+        let mut circuit = self.circuit.borrow_mut();
+        let variable = allocate(&mut circuit);
+        if let Err(e) = circuit.check_budget(&self.budget) {
+            self.exceeded.set(Some(e));
+        }
+        variable
+    }
+
+    fn value(&self, variable: Variable) -> F {
+        self.circuit.borrow().variable(variable).expect("expression refers to an unknown variable")
+    }
+
+    fn check_owner(&self, expr: &Expr<'_, F>) {
+        assert!(std::ptr::eq(self, expr.builder), "expressions from different circuit builders");
+    }
+
+    /// c = a · b
+    fn mul(&self, a: Variable, b: Variable) -> Variable {
+        let value = self.value(a) * self.value(b);
+        self.push(|circuit| {
+            let c = circuit.add_private_witness(value);
+            circuit.add_multiplication_constraint(a, b, c);
+            c
+        })
+    }
+
+    /// c = a + b
+    fn add(&self, a: Variable, b: Variable) -> Variable {
+        let value = self.value(a) + self.value(b);
+        self.push(|circuit| {
+            let c = circuit.add_private_witness(value);
+            circuit.add_addition_constraint(a, b, c);
+            c
+        })
+    }
+
+    /// c = a − b，约束为 b + c = a
+    fn sub(&self, a: Variable, b: Variable) -> Variable {
+        let value = self.value(a) - self.value(b);
+        self.push(|circuit| {
+            let c = circuit.add_private_witness(value);
+            circuit.add_addition_constraint(b, c, a);
+            c
+        })
+    }
+
+    /// c = a / b，约束为 c · b = a
+    fn div(&self, a: Variable, b: Variable) -> Variable {
+        let inverse = self.value(b).inverse().expect("division by zero in circuit expression");
+        let value = self.value(a) * inverse;
+        self.push(|circuit| {
+            let c = circuit.add_private_witness(value);
+            circuit.add_multiplication_constraint(c, b, a);
+            c
+        })
+    }
+}
+
+impl<'a, F: PrimeField> Expr<'a, F> {
+    pub fn variable(&self) -> Variable {
+        self.variable
+    }
+
+    /// 当前见证下的取值
+    pub fn value(&self) -> F {
+        self.builder.value(self.variable)
+    }
+
+    fn binary(self, other: Expr<'a, F>, op: fn(&CircuitBuilder<F>, Variable, Variable) -> Variable) -> Self {
+        self.builder.check_owner(&other);
+        Expr { builder: self.builder, variable: op(self.builder, self.variable, other.variable) }
+    }
+
+    fn lift(self, value: F) -> Self {
+        self.builder.constant(value)
+    }
+}
+
+macro_rules! expr_operator {
+    ($trait:ident, $method:ident) => {
+        impl<'a, F: PrimeField> $trait for Expr<'a, F> {
+            type Output = Expr<'a, F>;
+
+            fn $method(self, other: Expr<'a, F>) -> Expr<'a, F> {
+                self.binary(other, CircuitBuilder::$method)
+            }
+        }
+
+        impl<'a, F: PrimeField> $trait<F> for Expr<'a, F> {
+            type Output = Expr<'a, F>;
+
+            fn $method(self, constant: F) -> Expr<'a, F> {
+                let constant = self.lift(constant);
+                self.binary(constant, CircuitBuilder::$method)
+            }
+        }
+    };
+}
+
+expr_operator!(Add, add);
+expr_operator!(Sub, sub);
+expr_operator!(Mul, mul);
+expr_operator!(Div, div);
+
+impl<'a, F: PrimeField> Neg for Expr<'a, F> {
+    type Output = Expr<'a, F>;
+
+    fn neg(self) -> Expr<'a, F> {
+        self.lift(F::zero()) - self
+    }
+}
+
+/// 用表达式写电路
+///
+/// `private(...)` 与 `public(...)` 依次声明私有见证和公开输入，花括号内
+/// 是普通 Rust 语句，其中的 `assert_eq!(a, b)` 被展开为电路约束 a = b。
+/// 宏返回构造好的 `CustomCircuit`。
+#[macro_export]
+macro_rules! circuit {
+    (@body $builder:ident;) => {};
+    (@body $builder:ident; assert_eq!($left:expr, $right:expr $(,)?); $($rest:tt)*) => {
+        $builder.assert_eq($left, $right);
+        $crate::circuit!(@body $builder; $($rest)*);
+    };
+    (@body $builder:ident; $statement:stmt; $($rest:tt)*) => {
+        $statement
+        $crate::circuit!(@body $builder; $($rest)*);
+    };
+    (
+        $name:expr,
+        private($($witness:ident = $witness_value:expr),* $(,)?),
+        public($($input:ident = $input_value:expr),* $(,)?),
+        { $($body:tt)* } $(,)?
+    ) => {{
+        let builder = $crate::circuit_dsl::CircuitBuilder::new($name);
+        {
+            #[allow(unused_variables)]
+            let __builder = &builder;
+            $(let $witness = __builder.witness($witness_value);)*
+            $(let $input = __builder.public($input_value);)*
+            $crate::circuit!(@body __builder; $($body)*);
+        }
+        builder.finish()
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::Fr;
+
+    type TestField = Fr;
+
+    #[test]
+    fn test_expressions_expand_to_constraints() {
+        let value = |v: u64| TestField::from(v);
+        let circuit = circuit!("pythagoras", private(x = value(3), y = value(4)), public(out = value(25)), {
+            let z = x * x + y * y;
+            assert_eq!(z, out);
+        });
+        assert!(circuit.check_witness().is_satisfied());
+        assert_eq!(circuit.multiplication_constraints.len(), 3);
+        assert_eq!(circuit.addition_constraints.len(), 1);
+        assert_eq!(circuit.private_witnesses, [3u64, 4, 9, 16, 25].map(TestField::from));
+
+        // 同一个约束系统，手写时需要自己维护见证编号
+        let mut manual = CustomCircuit::new("pythagoras".to_string());
+        let (x, y) = (manual.add_private_witness(value(3)), manual.add_private_witness(value(4)));
+        let out = manual.add_public_input(value(25));
+        let xx = manual.add_private_witness(value(9));
+        let yy = manual.add_private_witness(value(16));
+        let z = manual.add_private_witness(value(25));
+        manual.add_multiplication_constraint(x, x, xx);
+        manual.add_multiplication_constraint(y, y, yy);
+        manual.add_addition_constraint(xx, yy, z);
+        manual.add_multiplication_constraint(z, Variable::ONE, out);
+        assert_eq!(circuit.multiplication_constraints, manual.multiplication_constraints);
+        assert_eq!(circuit.addition_constraints, manual.addition_constraints);
+
+        // 常量、减法、除法和取负；错误的公开输出在检查见证时报告
+        let circuit = circuit!("affine", private(x = value(10)), public(out = value(4)), {
+            let half = (x - value(2)) / value(2);
+            assert_eq!(-half + value(8), out);
+        });
+        assert!(circuit.check_witness().is_satisfied());
+        assert_eq!(circuit.public_inputs, [4u64, 2, 0, 8].map(TestField::from));
+        let circuit = circuit!("affine", private(x = value(10)), public(out = value(5)), {
+            assert_eq!(x / value(2) + value(1), out);
+        });
+        assert_eq!(circuit.check_witness().violations.len(), 1);
+    }
+
+    #[test]
+    fn test_builder_stops_at_budget() {
+        let budget = CircuitBudget::unlimited().with_max_variables(4).with_max_multiplications(2);
+        let builder = CircuitBuilder::with_budget("powers", budget);
+        let x = builder.witness(TestField::from(2u64));
+        let mut power = x;
+        let mut steps = 0;
+        while builder.exceeded().is_none() {
+            power = power * x;
+            steps += 1;
+        }
+        assert_eq!(steps, 3);
+        // 超出之后的运算不再记录任何东西
+        let _ = power * x + x;
+        let exceeded = builder.exceeded().unwrap();
+        assert_eq!(exceeded.requested, 3);
+        assert_eq!(exceeded.limit, 2);
+        assert_eq!(builder.circuit.borrow().usage().variables, 4);
+        assert_eq!(builder.try_finish().unwrap_err(), exceeded);
+
+        let builder = CircuitBuilder::with_budget("square", budget);
+        let x = builder.witness(TestField::from(3u64));
+        let square = x * x;
+        builder.assert_eq(square, builder.public(TestField::from(9u64)));
+        let circuit = builder.try_finish().unwrap();
+        assert!(circuit.check_budget(&budget).is_ok());
+        assert!(circuit.check_budget(&budget.with_max_constraints(1)).is_err());
+    }
+}
@@ -2,8 +2,8 @@
 //! 
 //! 这个文件包含了EOS委托协议的所有综合测试
 
-use crate::mpc::*;
-use crate::evaluation::*;
+use eos_delegation::mpc::*;
+use eos_delegation::evaluation::*;
 use ark_bls12_381::Fr;
 use ark_std::rand::{rngs::StdRng, SeedableRng};
 
@@ -96,7 +96,7 @@ fn test_complex_mpc_circuits(rng: &mut StdRng) -> Result<(), Box<dyn std::error:
             let shares1 = executor.input_secret(secret1, 3, rng);
             let shares2 = executor.input_secret(secret2, 3, rng);
             
-            if let (Some(s1), Some(s2)) = (shares1.get(0), shares2.get(0)) {
+            if let (Some(s1), Some(s2)) = (shares1.first(), shares2.first()) {
                 // 复杂操作序列
                 let add_result = executor.add_gate(s1, s2)?;
                 let mul_result = executor.mul_gate(s1, s2)?;
@@ -173,7 +173,7 @@ fn run_large_scale_benchmarks(rng: &mut StdRng) -> Result<(), Box<dyn std::error
                     let shares1 = executor.input_secret(secret1, 4, rng);
                     let shares2 = executor.input_secret(secret2, 4, rng);
                     
-                    if let (Some(s1), Some(s2)) = (shares1.get(0), shares2.get(0)) {
+                    if let (Some(s1), Some(s2)) = (shares1.first(), shares2.first()) {
                         let _ = executor.add_gate(s1, s2);
                         let _ = executor.mul_gate(s1, s2);
                     }
@@ -235,7 +235,7 @@ fn test_security_properties(rng: &mut StdRng) -> Result<(), Box<dyn std::error::
     
     // 验证阈值以下无法重构
     if shares.len() >= threshold {
-        let insufficient_shares = &shares[..threshold-1];
+        let _insufficient_shares = &shares[..threshold-1];
         // 在实际实现中，这应该失败或产生随机值
         println!("     ✅ 阈值以下的分享无法重构原始秘密");
     }
@@ -249,12 +249,12 @@ fn test_security_properties(rng: &mut StdRng) -> Result<(), Box<dyn std::error::
     let shares1 = ShamirSecretSharing::<F>::share_secret(secret1, threshold, num_parties, rng);
     let shares2 = ShamirSecretSharing::<F>::share_secret(secret2, threshold, num_parties, rng);
     
-    if let (Some(s1), Some(s2)) = (shares1.get(0), shares2.get(0)) {
-        let add_result = ShamirSecretSharing::<F>::add_shares(s1, s2)?;
+    if let (Some(s1), Some(s2)) = (shares1.first(), shares2.first()) {
+        let _add_result = ShamirSecretSharing::<F>::add_shares(s1, s2)?;
         // 在完整实现中，应该验证这个结果对应于 secret1 + secret2
         println!("     ✅ 加法操作保持正确性");
         
-        let scalar_result = ShamirSecretSharing::<F>::scalar_mul_share(s1, F::from(3u64));
+        let _scalar_result = ShamirSecretSharing::<F>::scalar_mul_share(s1, F::from(3u64));
         // 在完整实现中，应该验证这个结果对应于 3 * secret1
         println!("     ✅ 标量乘法操作保持正确性");
     }
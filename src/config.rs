@@ -0,0 +1,413 @@
+//! Deployment configuration
+//!
+//! `EosConfig` collects the choices a deployment makes at runtime rather
+//! than at compile time. It is read from a plain `key = value` file, with
+//! `#` starting a comment; unknown keys are rejected so typos do not pass
+//! silently.
+//!
+//! Worker processes co-located on one machine should each get their own
+//! `threads` and `core_affinity` so they do not compete for the same cores;
+//! `install_thread_pool` applies both to the global rayon pool.
+//!
+//! Settings that parse on their own can still contradict each other, such
+//! as a threshold above the committee size or Groth16 over a field without
+//! a pairing. `parse` runs `validate`, which reports every such conflict at
+//! once, each with a suggested fix, rather than the first one the protocol
+//! trips over. `validate_for` additionally checks the SRS degree against a
+//! concrete circuit.
+
+use std::path::Path;
+
+use crate::circuit::FieldChoice;
+use crate::evaluation::{BackendKind, CircuitShape, ModeKind};
+use crate::piop::{SumcheckParameters, TranscriptHash};
+
+/// Runtime configuration of a deployment
+#[derive(Debug, Clone, PartialEq)]
+pub struct EosConfig {
+    /// Proof system used by the workers
+    pub backend: BackendKind,
+    /// How the workers talk to each other
+    pub mode: ModeKind,
+    /// Field of the computation; the small fields commit with FRI
+    pub field: FieldChoice,
+    pub num_parties: usize,
+    pub threshold: usize,
+    pub security_parameter: usize,
+    /// Degree of the KZG setup; circuits may have up to `max_degree + 1` variables
+    pub max_degree: usize,
+    /// Hash of transcripts and Merkle trees; some verifiers prefer a specific one
+    pub transcript_hash: TranscriptHash,
+    /// Size of the rayon pool; `None` lets rayon use every core
+    pub threads: Option<usize>,
+    /// Cores the pool threads are pinned to, round robin; empty disables pinning
+    pub core_affinity: Vec<usize>,
+    /// Hide witness-derived values from the verifier: sumchecks run the
+    /// masked variant and the witness commitment is hiding (`EOSParams::zk`)
+    pub zk: bool,
+}
+
+impl Default for EosConfig {
+    fn default() -> Self {
+        Self {
+            backend: BackendKind::Piop,
+            mode: ModeKind::Collaboration,
+            field: FieldChoice::Bls12_381,
+            num_parties: 3,
+            threshold: 2,
+            security_parameter: 128,
+            max_degree: 1 << 8,
+            transcript_hash: TranscriptHash::Sha256,
+            threads: None,
+            core_affinity: Vec::new(),
+            zk: false,
+        }
+    }
+}
+
+impl EosConfig {
+    /// Parse a configuration, starting from the defaults
+    pub fn parse(text: &str) -> Result<Self, ConfigError> {
+        let mut config = Self::default();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or(ConfigError::Syntax(number + 1))?;
+            config.set(key.trim(), value.trim())?;
+        }
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Every conflict between settings, in the order they are checked
+    pub fn issues(&self) -> Vec<ConfigIssue> {
+        let mut issues = Vec::new();
+        let (n, t) = (self.num_parties, self.threshold);
+        if t == 0 {
+            issues.push(ConfigIssue::new("threshold", "a threshold of 0 shares nothing", format!("threshold = {}", n / 2 + 1)));
+        } else if t > n {
+            issues.push(ConfigIssue::new(
+                "threshold",
+                format!("threshold {} exceeds num_parties {}, so no value could ever be opened", t, n),
+                format!("threshold = {} or num_parties = {}", n.max(1), t),
+            ));
+        }
+        if self.mode == ModeKind::Collaboration && n < 2 {
+            issues.push(ConfigIssue::new(
+                "mode",
+                format!("collaboration mode needs at least two workers, num_parties is {}", n),
+                "mode = isolation or num_parties = 2".to_string(),
+            ));
+        }
+        // Groth16 needs a pairing, which the small fields do not have
+        if self.field.is_small() && self.backend == BackendKind::Groth16 {
+            issues.push(ConfigIssue::new(
+                "field",
+                format!("{} has no pairing, which the groth16 backend needs", self.field.name()),
+                format!("backend = piop or field = {}", FieldChoice::Bls12_381.name()),
+            ));
+        }
+        // The small fields reach any level by repeating the sumcheck; a
+        // pairing curve is capped by its discrete logarithm
+        let curve_bits = match self.field {
+            FieldChoice::Bls12_381 => Some(128),
+            FieldChoice::Bn254 => Some(100),
+            FieldChoice::Goldilocks | FieldChoice::Mersenne31 => None,
+        };
+        if self.security_parameter == 0 {
+            issues.push(ConfigIssue::new("security_parameter", "security_parameter must be positive", "security_parameter = 128".to_string()));
+        } else if let Some(bits) = curve_bits.filter(|&bits| self.security_parameter > bits) {
+            issues.push(ConfigIssue::new(
+                "security_parameter",
+                format!("{} offers about {} bits of security, {} were requested", self.field.name(), bits, self.security_parameter),
+                format!("security_parameter = {} or field = goldilocks", bits),
+            ));
+        }
+        if self.max_degree == 0 {
+            issues.push(ConfigIssue::new("max_degree", "an SRS of degree 0 commits to nothing", "max_degree = 256".to_string()));
+        }
+        issues
+    }
+
+    /// Reject the configuration if any setting conflicts with another
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        Self::into_result(self.issues())
+    }
+
+    /// Like `validate`, and also check that the SRS is large enough for `shape`
+    ///
+    /// Only the PIOP backend over a pairing curve uses the configured SRS;
+    /// Groth16 sizes its own setup to the circuit and the small fields use FRI.
+    pub fn validate_for(&self, shape: &CircuitShape) -> Result<(), ConfigError> {
+        let mut issues = self.issues();
+        // ark-relations counts the constant one as a variable
+        let variables = shape.witnesses + shape.public_inputs + 1;
+        if self.backend == BackendKind::Piop && !self.field.is_small() && variables > self.max_degree + 1 {
+            issues.push(ConfigIssue::new(
+                "max_degree",
+                format!("the circuit has {} variables, an SRS of degree {} holds {}", variables, self.max_degree, self.max_degree + 1),
+                format!("max_degree = {}", variables.next_power_of_two()),
+            ));
+        }
+        Self::into_result(issues)
+    }
+
+    fn into_result(issues: Vec<ConfigIssue>) -> Result<(), ConfigError> {
+        if issues.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigError::Conflicts(issues))
+        }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    /// Sumcheck parameters for this field, security level and `zk` setting
+    pub fn sumcheck_parameters(&self, num_vars: usize) -> SumcheckParameters {
+        self.field.sumcheck_parameters(num_vars, self.security_parameter).with_zk(self.zk)
+    }
+
+    /// Build a rayon pool with the configured size and pinning
+    pub fn build_thread_pool(&self) -> Result<rayon::ThreadPool, ConfigError> {
+        self.pool_builder().build().map_err(|e| ConfigError::ThreadPool(e.to_string()))
+    }
+
+    /// Configure the global rayon pool, which arkworks uses for MSMs and FFTs
+    ///
+    /// Must run before anything uses rayon; the global pool can only be set
+    /// up once per process.
+    pub fn install_thread_pool(&self) -> Result<(), ConfigError> {
+        self.pool_builder().build_global().map_err(|e| ConfigError::ThreadPool(e.to_string()))
+    }
+
+    fn pool_builder(&self) -> rayon::ThreadPoolBuilder {
+        let mut builder = rayon::ThreadPoolBuilder::new().thread_name(|i| format!("eos-worker-{}", i));
+        if let Some(threads) = self.threads {
+            builder = builder.num_threads(threads);
+        }
+        if !self.core_affinity.is_empty() {
+            let cores = self.core_affinity.clone();
+            builder = builder.start_handler(move |i| {
+                // Pinning is best effort: a core missing from this machine
+                // leaves the thread unpinned rather than failing the pool
+                pin_current_thread(cores[i % cores.len()]);
+            });
+        }
+        builder
+    }
+
+    fn set(&mut self, key: &str, value: &str) -> Result<(), ConfigError> {
+        let invalid = || ConfigError::InvalidValue { key: key.to_string(), value: value.to_string() };
+        let number = || value.parse::<usize>().map_err(|_| invalid());
+        match key {
+            "backend" => self.backend = value.parse().map_err(|_| invalid())?,
+            "mode" => self.mode = value.parse().map_err(|_| invalid())?,
+            "field" => self.field = value.parse().map_err(|_| invalid())?,
+            "num_parties" => self.num_parties = number()?,
+            "threshold" => self.threshold = number()?,
+            "security_parameter" => self.security_parameter = number()?,
+            "max_degree" => self.max_degree = number()?,
+            "transcript_hash" => self.transcript_hash = value.parse().map_err(|_| invalid())?,
+            "threads" => {
+                self.threads = match value {
+                    "auto" => None,
+                    _ => Some(number()?).filter(|&threads| threads > 0).map(Some).ok_or_else(invalid)?,
+                }
+            }
+            "zk" => self.zk = value.parse().map_err(|_| invalid())?,
+            "core_affinity" => self.core_affinity = parse_core_list(value).ok_or_else(invalid)?,
+            _ => return Err(ConfigError::UnknownKey(key.to_string())),
+        }
+        Ok(())
+    }
+}
+
+/// Parse a core list such as `0,2,4-7`; `none` or an empty value disables pinning
+fn parse_core_list(value: &str) -> Option<Vec<usize>> {
+    if value.is_empty() || value == "none" {
+        return Some(Vec::new());
+    }
+    let mut cores = Vec::new();
+    for part in value.split(',').map(str::trim) {
+        match part.split_once('-') {
+            Some((start, end)) => {
+                let (start, end): (usize, usize) = (start.trim().parse().ok()?, end.trim().parse().ok()?);
+                if start > end {
+                    return None;
+                }
+                cores.extend(start..=end);
+            }
+            None => cores.push(part.parse().ok()?),
+        }
+    }
+    Some(cores)
+}
+
+/// Pin the calling thread to `core`; returns whether the OS accepted it
+#[cfg(target_os = "linux")]
+pub fn pin_current_thread(core: usize) -> bool {
+    if core >= libc::CPU_SETSIZE as usize {
+        return false;
+    }
+    // SAFETY: `set` is a zero-initialised cpu_set_t owned by this frame, and
+    // `core` was checked against CPU_SETSIZE
+    unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        libc::CPU_SET(core, &mut set);
+        libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set) == 0
+    }
+}
+
+/// Pin the calling thread to `core`; not supported on this platform
+#[cfg(not(target_os = "linux"))]
+pub fn pin_current_thread(_core: usize) -> bool {
+    false
+}
+
+/// Settings that contradict each other, and how to reconcile them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigIssue {
+    /// Key whose value is the likeliest mistake
+    pub key: &'static str,
+    pub problem: String,
+    /// Replacement settings, in the file's `key = value` syntax
+    pub suggestion: String,
+}
+
+impl ConfigIssue {
+    fn new(key: &'static str, problem: impl Into<String>, suggestion: String) -> Self {
+        Self { key, problem: problem.into(), suggestion }
+    }
+}
+
+impl std::fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}: {} (try: {})", self.key, self.problem, self.suggestion)
+    }
+}
+
+/// Configuration error types
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(std::io::Error),
+    Syntax(usize),
+    UnknownKey(String),
+    InvalidValue { key: String, value: String },
+    /// Every conflict `validate` found, never empty
+    Conflicts(Vec<ConfigIssue>),
+    ThreadPool(String),
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ConfigError::Io(e) => write!(f, "Configuration I/O error: {}", e),
+            ConfigError::Syntax(line) => write!(f, "Expected 'key = value' on line {}", line),
+            ConfigError::UnknownKey(key) => write!(f, "Unknown configuration key: {}", key),
+            ConfigError::InvalidValue { key, value } => write!(f, "Invalid value '{}' for {}", value, key),
+            ConfigError::Conflicts(issues) => {
+                write!(f, "{} configuration problem(s)", issues.len())?;
+                issues.iter().try_for_each(|issue| write!(f, "\n  {}", issue))
+            }
+            ConfigError::ThreadPool(e) => write!(f, "Could not build thread pool: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl From<std::io::Error> for ConfigError {
+    fn from(e: std::io::Error) -> Self {
+        ConfigError::Io(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_config() {
+        let config = EosConfig::parse(
+            "# deployment\nbackend = groth16\nnum_parties = 5 # committee\nthreshold=3\ntranscript_hash = poseidon\n",
+        )
+        .unwrap();
+        assert_eq!(config.backend, BackendKind::Groth16);
+        assert_eq!(config.num_parties, 5);
+        assert_eq!(config.threshold, 3);
+        assert_eq!(config.security_parameter, 128);
+        assert_eq!(config.transcript_hash, TranscriptHash::Poseidon);
+        assert!(!config.zk);
+        assert!(EosConfig::parse("zk = true").unwrap().sumcheck_parameters(4).zk);
+        assert!(matches!(EosConfig::parse("zk = yes"), Err(ConfigError::InvalidValue { .. })));
+
+        assert!(matches!(EosConfig::parse("backend = plonk"), Err(ConfigError::InvalidValue { .. })));
+        assert!(matches!(EosConfig::parse("backends = piop"), Err(ConfigError::UnknownKey(_))));
+        assert!(matches!(EosConfig::parse("\nbackend"), Err(ConfigError::Syntax(2))));
+        assert!(matches!(EosConfig::parse("threshold = 4"), Err(ConfigError::Conflicts(_))));
+
+        assert_eq!(EosConfig::parse("field = goldilocks").unwrap().field, FieldChoice::Goldilocks);
+        assert!(matches!(
+            EosConfig::parse("backend = groth16\nfield = mersenne31"),
+            Err(ConfigError::Conflicts(_))
+        ));
+    }
+
+    #[test]
+    fn test_validation_reports_every_conflict() {
+        let text = "backend = groth16\nfield = mersenne31\nnum_parties = 1\nthreshold = 3\n";
+        let Err(ConfigError::Conflicts(issues)) = EosConfig::parse(text) else {
+            panic!("conflicting configuration accepted");
+        };
+        let keys: Vec<&str> = issues.iter().map(|issue| issue.key).collect();
+        assert_eq!(keys, ["threshold", "mode", "field"]);
+        assert_eq!(issues[0].suggestion, "threshold = 1 or num_parties = 3");
+        let message = ConfigError::Conflicts(issues).to_string();
+        assert!(message.starts_with("3 configuration problem(s)\n  threshold:"));
+
+        let bn254 = EosConfig { field: FieldChoice::Bn254, ..EosConfig::default() };
+        assert_eq!(bn254.issues()[0].suggestion, "security_parameter = 100 or field = goldilocks");
+        assert!(EosConfig::parse("field = bn254\nsecurity_parameter = 100\nmode = isolation").is_ok());
+
+        // The default SRS holds 257 variables
+        let shape = |witnesses| CircuitShape { multiplications: 1, additions: 0, witnesses, public_inputs: 0, multiplicative_depth: 1 };
+        let config = EosConfig::default();
+        assert!(config.validate_for(&shape(256)).is_ok());
+        let Err(ConfigError::Conflicts(issues)) = config.validate_for(&shape(600)) else {
+            panic!("undersized SRS accepted");
+        };
+        assert_eq!(issues[0].suggestion, "max_degree = 1024");
+        let groth16 = EosConfig { backend: BackendKind::Groth16, ..config };
+        assert!(groth16.validate_for(&shape(600)).is_ok());
+    }
+
+    #[test]
+    fn test_thread_pool_configuration() {
+        let config = EosConfig::parse("threads = 2\ncore_affinity = 0, 3-5\n").unwrap();
+        assert_eq!(config.threads, Some(2));
+        assert_eq!(config.core_affinity, vec![0, 3, 4, 5]);
+        assert_eq!(EosConfig::parse("threads = auto\ncore_affinity = none").unwrap(), EosConfig::default());
+        for bad in ["threads = 0", "core_affinity = 5-3", "core_affinity = a"] {
+            assert!(matches!(EosConfig::parse(bad), Err(ConfigError::InvalidValue { .. })), "{}", bad);
+        }
+
+        let pinned = EosConfig { threads: Some(2), core_affinity: vec![0], ..EosConfig::default() };
+        let pool = pinned.build_thread_pool().unwrap();
+        assert_eq!(pool.current_num_threads(), 2);
+        #[cfg(target_os = "linux")]
+        {
+            let allowed = pool.install(|| unsafe {
+                let mut set: libc::cpu_set_t = std::mem::zeroed();
+                libc::sched_getaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &mut set);
+                libc::CPU_COUNT(&set)
+            });
+            assert_eq!(allowed, 1);
+        }
+    }
+}
@@ -1,25 +1,128 @@
-use ark_ff::{Field, PrimeField};
+use ark_bls12_381::Fr as BlsFr;
+use ark_ec::{AffineRepr, CurveGroup, twisted_edwards::TECurveConfig};
+use ark_ed_on_bls12_381::{EdwardsAffine, Fr as JubjubScalar, JubjubConfig};
+use ark_ff::{BigInteger, Field, PrimeField, UniformRand};
 use ark_poly::{DenseUVPolynomial, univariate::DensePolynomial};
-use ark_std::vec::Vec;
-use crate::piop::ConsistencyChecker;
+use ark_std::{rand::{Rng, RngCore}, vec::Vec};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::ops::RangeInclusive;
+use std::time::{Duration, Instant};
+use ark_relations::lc;
+use ark_relations::r1cs::ConstraintSystem;
+use num_bigint::BigUint;
+use sha2::{Digest, Sha256};
+use crate::piop::{pad_column, pad_r1cs, ConsistencyChecker, PaddingStrategy};
+
+/// 电路变量，编号与 ark-relations 的 R1CS 一致
+///
+/// 实例变量中 `Instance(0)` 是常量 1，第 i 个公开输入是 `Instance(i + 1)`；
+/// 第 j 个私有见证是 `Witness(j)`。需要线性编号（序列化、指纹）时实例变量在前、
+/// 见证在后，与 `ConstraintSystem` 的 instance ++ witness 布局相同。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Variable {
+    Instance(usize),
+    Witness(usize),
+}
+
+impl Variable {
+    /// 常量 1
+    pub const ONE: Variable = Variable::Instance(0);
+
+    /// 第 i 个公开输入
+    pub fn public_input(i: usize) -> Self {
+        Variable::Instance(i + 1)
+    }
+
+    /// 线性编号，`num_instance` 为实例变量数（公开输入数 + 1）
+    pub fn index(self, num_instance: usize) -> usize {
+        match self {
+            Variable::Instance(i) => i,
+            Variable::Witness(j) => num_instance + j,
+        }
+    }
+
+    /// `index` 的逆
+    pub fn from_index(index: usize, num_instance: usize) -> Self {
+        if index < num_instance {
+            Variable::Instance(index)
+        } else {
+            Variable::Witness(index - num_instance)
+        }
+    }
+}
+
+impl From<Variable> for ark_relations::r1cs::Variable {
+    fn from(variable: Variable) -> Self {
+        match variable {
+            Variable::Instance(0) => ark_relations::r1cs::Variable::One,
+            Variable::Instance(i) => ark_relations::r1cs::Variable::Instance(i),
+            Variable::Witness(j) => ark_relations::r1cs::Variable::Witness(j),
+        }
+    }
+}
+
+impl std::fmt::Display for Variable {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Variable::Instance(0) => write!(f, "one"),
+            Variable::Instance(i) => write!(f, "instance[{}]", i),
+            Variable::Witness(j) => write!(f, "witness[{}]", j),
+        }
+    }
+}
+
+/// 变量的隐私级别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Privacy {
+    /// 公开输入和常量 1，验证者可见
+    Public,
+    /// 秘密分享给工作者的私有见证
+    #[default]
+    Shared,
+    /// 只留在委托方的私有见证，由委托方单独承诺后并入证明
+    DelegatorOnly,
+}
 
 /// 自定义电路定义
+///
+/// 见证和公开输入按添加顺序编号，约束引用返回的 `Variable`：
+///
+/// ```
+/// use ark_bls12_381::Fr;
+/// use eos_delegation::custom_circuits::CustomCircuit;
+///
+/// // 证明知道 x 使得 x^2 + x = 12
+/// let mut circuit = CustomCircuit::new("quadratic".to_string());
+/// let x = circuit.add_private_witness(Fr::from(3u64));
+/// let square = circuit.add_private_witness(Fr::from(9u64));
+/// let out = circuit.add_public_input(Fr::from(12u64));
+/// circuit.add_multiplication_constraint(x, x, square);
+/// circuit.add_addition_constraint(square, x, out);
+/// assert!(circuit.check_witness().is_satisfied());
+/// assert!(circuit.to_constraint_system().is_satisfied().unwrap());
+///
+/// // 错误的见证指出违反的约束
+/// circuit.private_witnesses[1] = Fr::from(10u64);
+/// assert!(!circuit.check_witness().is_satisfied());
+/// ```
 #[derive(Debug, Clone)]
 pub struct CustomCircuit<F: PrimeField> {
     /// 电路名称
     pub name: String,
     /// 约束数量
     pub num_constraints: usize,
-    /// 变量数量
+    /// 变量数量（私有见证 + 公开输入，不含常量 1）
     pub num_variables: usize,
     /// 私有见证
     pub private_witnesses: Vec<F>,
     /// 公开输入
     pub public_inputs: Vec<F>,
     /// 乘法约束定义 (a, b, c) 表示 a * b = c 的约束
-    pub multiplication_constraints: Vec<(usize, usize, usize)>,
+    pub multiplication_constraints: Vec<(Variable, Variable, Variable)>,
     /// 加法约束定义 (a, b, c) 表示 a + b = c 的约束
-    pub addition_constraints: Vec<(usize, usize, usize)>,
+    pub addition_constraints: Vec<(Variable, Variable, Variable)>,
+    /// 隐私级别为 `DelegatorOnly` 的私有见证编号，其余私有见证为 `Shared`
+    pub delegator_only: BTreeSet<usize>,
 }
 
 impl<F: PrimeField> CustomCircuit<F> {
@@ -33,82 +136,221 @@ impl<F: PrimeField> CustomCircuit<F> {
             public_inputs: Vec::new(),
             multiplication_constraints: Vec::new(),
             addition_constraints: Vec::new(),
+            delegator_only: BTreeSet::new(),
         }
     }
     
     /// 添加私有见证
-    pub fn add_private_witness(&mut self, witness: F) -> usize {
+    pub fn add_private_witness(&mut self, witness: F) -> Variable {
         self.private_witnesses.push(witness);
-        let index = self.num_variables;
         self.num_variables += 1;
-        index
+        Variable::Witness(self.private_witnesses.len() - 1)
     }
     
+    /// 添加只留在委托方的私有见证
+    pub fn add_delegator_witness(&mut self, witness: F) -> Variable {
+        let variable = self.add_private_witness(witness);
+        self.delegator_only.insert(self.private_witnesses.len() - 1);
+        variable
+    }
+
+    /// 修改私有见证的隐私级别，只能在 `Shared` 与 `DelegatorOnly` 之间切换
+    pub fn set_privacy(&mut self, variable: Variable, privacy: Privacy) {
+        let Variable::Witness(j) = variable else {
+            panic!("{} is public", variable);
+        };
+        assert!(j < self.private_witnesses.len(), "{} does not exist", variable);
+        match privacy {
+            Privacy::Shared => {
+                self.delegator_only.remove(&j);
+            }
+            Privacy::DelegatorOnly => {
+                self.delegator_only.insert(j);
+            }
+            Privacy::Public => panic!("{} is a private witness", variable),
+        }
+    }
+
+    /// 变量的隐私级别
+    pub fn privacy(&self, variable: Variable) -> Privacy {
+        match variable {
+            Variable::Instance(_) => Privacy::Public,
+            Variable::Witness(j) if self.delegator_only.contains(&j) => Privacy::DelegatorOnly,
+            Variable::Witness(_) => Privacy::Shared,
+        }
+    }
+
     /// 添加公开输入
-    pub fn add_public_input(&mut self, input: F) -> usize {
+    pub fn add_public_input(&mut self, input: F) -> Variable {
         self.public_inputs.push(input);
-        let index = self.num_variables;
         self.num_variables += 1;
-        index
+        Variable::public_input(self.public_inputs.len() - 1)
     }
     
     /// 添加约束: var_a * var_b = var_c
-    pub fn add_multiplication_constraint(&mut self, var_a: usize, var_b: usize, var_c: usize) {
+    pub fn add_multiplication_constraint(&mut self, var_a: Variable, var_b: Variable, var_c: Variable) {
         self.multiplication_constraints.push((var_a, var_b, var_c));
         self.num_constraints += 1;
     }
     
     /// 添加约束: var_a + var_b = var_c
-    pub fn add_addition_constraint(&mut self, var_a: usize, var_b: usize, var_c: usize) {
+    pub fn add_addition_constraint(&mut self, var_a: Variable, var_b: Variable, var_c: Variable) {
         self.addition_constraints.push((var_a, var_b, var_c));
         self.num_constraints += 1;
     }
     
+    /// 以 `condition` 为条件构造分支选择，条件被约束为布尔值 (c·c = c)
+    ///
+    /// 选择结果作为私有见证追加。
+    pub fn when(&mut self, condition: Variable) -> If<'_, F> {
+        self.add_multiplication_constraint(condition, condition, condition);
+        If { circuit: self, condition, then_values: Vec::new() }
+    }
+
+    /// 单值选择: condition ? a : b
+    pub fn select(&mut self, condition: Variable, a: Variable, b: Variable) -> Variable {
+        self.when(condition).then(&[a]).otherwise(&[b])[0]
+    }
+
+    /// 实例变量数（公开输入 + 常量 1）
+    pub fn num_instance_variables(&self) -> usize {
+        self.public_inputs.len() + 1
+    }
+
+    /// 变量的取值；变量不存在时返回 None
+    pub fn variable(&self, variable: Variable) -> Option<F> {
+        match variable {
+            Variable::Instance(0) => Some(F::one()),
+            Variable::Instance(i) => self.public_inputs.get(i - 1).copied(),
+            Variable::Witness(j) => self.private_witnesses.get(j).copied(),
+        }
+    }
+
+    /// 转换为 arkworks 约束系统，变量编号不变
+    ///
+    /// 乘法约束为 a · b = c，加法约束为 (a + b) · 1 = c。
+    pub fn to_constraint_system(&self) -> ConstraintSystem<F> {
+        let cs = ConstraintSystem::new_ref();
+        for value in &self.public_inputs {
+            cs.new_input_variable(|| Ok(*value)).expect("allocation without a setup mode cannot fail");
+        }
+        for value in &self.private_witnesses {
+            cs.new_witness_variable(|| Ok(*value)).expect("allocation without a setup mode cannot fail");
+        }
+        let wire = ark_relations::r1cs::Variable::from;
+        let one = ark_relations::r1cs::Variable::One;
+        let constraints = self
+            .multiplication_constraints
+            .iter()
+            .map(|&(a, b, c)| (lc!() + wire(a), lc!() + wire(b), lc!() + wire(c)))
+            .chain(
+                self.addition_constraints
+                    .iter()
+                    .map(|&(a, b, c)| (lc!() + wire(a) + wire(b), lc!() + one, lc!() + wire(c))),
+            );
+        for (a, b, c) in constraints {
+            cs.enforce_constraint(a, b, c).expect("constraint over allocated variables");
+        }
+        cs.into_inner().expect("no other references to the constraint system")
+    }
+
+    /// 电路当前的规模
+    pub fn usage(&self) -> CircuitUsage {
+        CircuitUsage {
+            constraints: self.num_constraints,
+            variables: self.private_witnesses.len(),
+            multiplications: self.multiplication_constraints.len(),
+        }
+    }
+
+    /// 检查电路是否在预算之内
+    pub fn check_budget(&self, budget: &CircuitBudget) -> Result<(), BudgetExceeded> {
+        budget.check(&self.usage())
+    }
+
     /// 验证电路约束是否满足
     pub fn verify_constraints(&self) -> bool {
-        let mut all_variables: Vec<F> = Vec::new();
-        all_variables.extend(&self.private_witnesses);
-        all_variables.extend(&self.public_inputs);
-        
-        // 验证乘法约束
-        for &(a_idx, b_idx, c_idx) in &self.multiplication_constraints {
-            if a_idx >= all_variables.len() || 
-               b_idx >= all_variables.len() || 
-               c_idx >= all_variables.len() {
-                return false;
-            }
-            
-            let a: F = all_variables[a_idx];
-            let b: F = all_variables[b_idx];
-            let c: F = all_variables[c_idx];
-            
-            if a * b != c {
-                println!("   ❌ 乘法约束失败: {} × {} ≠ {} (期望 {})", a, b, c, a * b);
-                return false;
-            }
+        let report = self.check_witness();
+        if let Some(violation) = report.violations.first() {
+            println!("   ❌ {}", violation);
         }
-        
-        // 验证加法约束
-        for &(a_idx, b_idx, c_idx) in &self.addition_constraints {
-            if a_idx >= all_variables.len() || 
-               b_idx >= all_variables.len() || 
-               c_idx >= all_variables.len() {
-                return false;
-            }
-            
-            let a: F = all_variables[a_idx];
-            let b: F = all_variables[b_idx];
-            let c: F = all_variables[c_idx];
-            
-            if a + b != c {
-                println!("   ❌ 加法约束失败: {} + {} ≠ {} (期望 {})", a, b, c, a + b);
-                return false;
-            }
+        report.is_satisfied()
+    }
+
+    /// 在委托之前于本地明文见证上检查全部约束
+    ///
+    /// 与 `verify_constraints` 不同，这里不会在第一个失败处停止，而是报告
+    /// 每一个不满足的约束、涉及的变量及其取值，避免为一个注定验证失败的
+    /// 见证支付完整的 MPC 开销。
+    pub fn check_witness(&self) -> WitnessReport<F> {
+        let value = |wire: Variable| self.variable(wire);
+
+        let mut violations = Vec::new();
+        let constraints = self
+            .multiplication_constraints
+            .iter()
+            .map(|c| (ConstraintKind::Multiplication, c))
+            .chain(self.addition_constraints.iter().map(|c| (ConstraintKind::Addition, c)));
+        let mut counts: HashMap<ConstraintKind, usize> = HashMap::new();
+        for (kind, &(a, b, c)) in constraints {
+            let position = counts.entry(kind).or_default();
+            let index = *position;
+            *position += 1;
+
+            let wires = [a, b, c];
+            let reason = match (value(a), value(b), value(c)) {
+                (Some(va), Some(vb), Some(vc)) => {
+                    let expected = match kind {
+                        ConstraintKind::Multiplication => va * vb,
+                        ConstraintKind::Addition => va + vb,
+                    };
+                    if expected == vc {
+                        continue;
+                    }
+                    ViolationReason::Mismatch { inputs: [va, vb], expected, actual: vc }
+                }
+                _ => {
+                    let missing = [a, b, c]
+                        .into_iter()
+                        .find(|&w| value(w).is_none())
+                        .expect("some wire has no value");
+                    ViolationReason::UnknownWire(missing)
+                }
+            };
+            violations.push(ConstraintViolation { kind, index, wires, reason });
+        }
+        WitnessReport {
+            circuit_name: self.name.clone(),
+            num_constraints: self.multiplication_constraints.len() + self.addition_constraints.len(),
+            violations,
         }
-        
-        true
     }
     
+    /// 按约束顺序排列的 R1CS 三列，补齐到 2 的幂
+    ///
+    /// 乘法约束一行为 (a, b, c)，加法约束为 (a + b, 1, c)，乘法约束在前。
+    /// 补上的行满足 a·b = c，见 `piop::padding`。
+    pub fn constraint_tables<R: Rng + ?Sized>(&self, strategy: PaddingStrategy, rng: &mut R) -> [Vec<F>; 3] {
+        let value = |variable: Variable| self.variable(variable).expect("constraint reads an unknown variable");
+        let rows = self
+            .multiplication_constraints
+            .iter()
+            .map(|&(a, b, c)| (value(a), value(b), value(c)))
+            .chain(self.addition_constraints.iter().map(|&(a, b, c)| (value(a) + value(b), F::one(), value(c))));
+        let (mut a, mut b, mut c) = (Vec::new(), Vec::new(), Vec::new());
+        for (x, y, z) in rows {
+            a.push(x);
+            b.push(y);
+            c.push(z);
+        }
+        pad_r1cs([&a, &b, &c], strategy, rng).expect("the three columns have one entry per constraint")
+    }
+
+    /// 私有见证补齐到 2 的幂；补上的位置不被任何约束引用
+    pub fn padded_witness<R: Rng + ?Sized>(&self, strategy: PaddingStrategy, rng: &mut R) -> Vec<F> {
+        pad_column(&self.private_witnesses, strategy, rng)
+    }
+
     /// 将见证转换为多项式表示
     pub fn witnesses_to_polynomials(&self) -> Vec<DensePolynomial<F>> {
         self.private_witnesses.iter()
@@ -118,17 +360,13 @@ impl<F: PrimeField> CustomCircuit<F> {
     
     /// 生成约束多项式
     pub fn generate_constraint_polynomials(&self) -> Vec<DensePolynomial<F>> {
-        let mut all_variables: Vec<F> = Vec::new();
-        all_variables.extend(&self.private_witnesses);
-        all_variables.extend(&self.public_inputs);
-        
+        let value = |variable: Variable| self.variable(variable).expect("constraint reads an unknown variable");
+
         let mut constraint_polys = Vec::new();
         
         // 处理乘法约束
         for &(a_idx, b_idx, c_idx) in &self.multiplication_constraints {
-            let a: F = all_variables[a_idx];
-            let b: F = all_variables[b_idx];
-            let c: F = all_variables[c_idx];
+            let (a, b, c) = (value(a_idx), value(b_idx), value(c_idx));
             
             // 约束多项式: a * b - c (应该等于0)
             let constraint_value = a * b - c;
@@ -137,9 +375,7 @@ impl<F: PrimeField> CustomCircuit<F> {
         
         // 处理加法约束
         for &(a_idx, b_idx, c_idx) in &self.addition_constraints {
-            let a: F = all_variables[a_idx];
-            let b: F = all_variables[b_idx];
-            let c: F = all_variables[c_idx];
+            let (a, b, c) = (value(a_idx), value(b_idx), value(c_idx));
             
             // 约束多项式: a + b - c (应该等于0)
             let constraint_value = a + b - c;
@@ -150,6 +386,231 @@ impl<F: PrimeField> CustomCircuit<F> {
     }
 }
 
+/// 条件分支构造器
+///
+/// 电路没有控制流：两个分支都要求值，再按布尔条件逐个选择分支的输出。
+/// 每个输出 out = c·a + (1−c)·b 按 b + c·(a − b) 计算，只需一个乘法约束。
+pub struct If<'a, F: PrimeField> {
+    circuit: &'a mut CustomCircuit<F>,
+    condition: Variable,
+    then_values: Vec<Variable>,
+}
+
+impl<F: PrimeField> If<'_, F> {
+    /// 条件为 1 时的输出
+    pub fn then(mut self, values: &[Variable]) -> Self {
+        self.then_values = values.to_vec();
+        self
+    }
+
+    /// 条件为 0 时的输出；返回按位置选出的结果变量
+    pub fn otherwise(self, values: &[Variable]) -> Vec<Variable> {
+        assert_eq!(self.then_values.len(), values.len(), "branches must have the same number of outputs");
+        let circuit = self.circuit;
+        let value = |circuit: &CustomCircuit<F>, variable: Variable| {
+            circuit.variable(variable).expect("selection reads an unknown variable")
+        };
+        let c = value(circuit, self.condition);
+        self.then_values
+            .iter()
+            .zip(values)
+            .map(|(&a, &b)| {
+                let (va, vb) = (value(circuit, a), value(circuit, b));
+                // b + diff = a, c · diff = chosen, b + chosen = out
+                let diff = circuit.add_private_witness(va - vb);
+                circuit.add_addition_constraint(b, diff, a);
+                let chosen = circuit.add_private_witness(c * (va - vb));
+                circuit.add_multiplication_constraint(self.condition, diff, chosen);
+                let out = circuit.add_private_witness(vb + c * (va - vb));
+                circuit.add_addition_constraint(b, chosen, out);
+                out
+            })
+            .collect()
+    }
+}
+
+/// 约束类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConstraintKind {
+    Multiplication,
+    Addition,
+}
+
+/// 约束不满足的原因
+#[derive(Debug, Clone, PartialEq)]
+pub enum ViolationReason<F: PrimeField> {
+    /// 引用了不存在的变量
+    UnknownWire(Variable),
+    /// 两个输入算出的 `expected` 与输出变量的取值 `actual` 不同
+    Mismatch { inputs: [F; 2], expected: F, actual: F },
+}
+
+/// 单个不满足的约束
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConstraintViolation<F: PrimeField> {
+    pub kind: ConstraintKind,
+    /// 在同类约束列表中的位置
+    pub index: usize,
+    /// (a, b, c) 三个变量
+    pub wires: [Variable; 3],
+    pub reason: ViolationReason<F>,
+}
+
+impl<F: PrimeField> std::fmt::Display for ConstraintViolation<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let (name, op) = match self.kind {
+            ConstraintKind::Multiplication => ("multiplication", "*"),
+            ConstraintKind::Addition => ("addition", "+"),
+        };
+        let [a, b, c] = &self.wires;
+        write!(f, "{} constraint #{}: {} {} {} = {}: ", name, self.index, a, op, b, c)?;
+        match &self.reason {
+            ViolationReason::UnknownWire(wire) => write!(f, "{} does not exist", wire),
+            ViolationReason::Mismatch { inputs, expected, actual } => write!(
+                f,
+                "{} {} {} = {} but {} = {}",
+                inputs[0], op, inputs[1], expected, c, actual
+            ),
+        }
+    }
+}
+
+/// 委托前见证检查的结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct WitnessReport<F: PrimeField> {
+    pub circuit_name: String,
+    pub num_constraints: usize,
+    pub violations: Vec<ConstraintViolation<F>>,
+}
+
+impl<F: PrimeField> WitnessReport<F> {
+    pub fn is_satisfied(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+impl<F: PrimeField> std::fmt::Display for WitnessReport<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "circuit {}: {} of {} constraints violated",
+            self.circuit_name,
+            self.violations.len(),
+            self.num_constraints
+        )?;
+        for violation in &self.violations {
+            write!(f, "\n  {}", violation)?;
+        }
+        Ok(())
+    }
+}
+
+/// 电路规模上限，`None` 表示不限制
+///
+/// 预处理的容量是有限的：KZG 参数只能承诺长度不超过 SRS 的见证多项式，
+/// MPC 执行时每个乘法约束消耗一个 Beaver 三元组。超出上限的电路应在
+/// 构造或委托之前就被拒绝，而不是在秘密分享之后才失败。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CircuitBudget {
+    /// 约束总数
+    pub max_constraints: Option<usize>,
+    /// 私有见证变量数，即承诺的见证多项式长度
+    pub max_variables: Option<usize>,
+    /// 乘法约束数，即需要的三元组数
+    pub max_multiplications: Option<usize>,
+}
+
+/// 电路的实际规模，各项含义与 `CircuitBudget` 相同
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CircuitUsage {
+    pub constraints: usize,
+    pub variables: usize,
+    pub multiplications: usize,
+}
+
+/// 预算中的一项
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BudgetResource {
+    Constraints,
+    Variables,
+    Multiplications,
+}
+
+/// 电路超出了预算
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BudgetExceeded {
+    pub resource: BudgetResource,
+    pub limit: usize,
+    pub requested: usize,
+}
+
+impl CircuitBudget {
+    pub fn unlimited() -> Self {
+        Self::default()
+    }
+
+    pub fn with_max_constraints(mut self, limit: usize) -> Self {
+        self.max_constraints = Some(limit);
+        self
+    }
+
+    pub fn with_max_variables(mut self, limit: usize) -> Self {
+        self.max_variables = Some(limit);
+        self
+    }
+
+    pub fn with_max_multiplications(mut self, limit: usize) -> Self {
+        self.max_multiplications = Some(limit);
+        self
+    }
+
+    /// 逐项取两个预算中较紧的上限
+    pub fn intersect(self, other: Self) -> Self {
+        let tighter = |a: Option<usize>, b: Option<usize>| match (a, b) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        Self {
+            max_constraints: tighter(self.max_constraints, other.max_constraints),
+            max_variables: tighter(self.max_variables, other.max_variables),
+            max_multiplications: tighter(self.max_multiplications, other.max_multiplications),
+        }
+    }
+
+    /// 返回第一个超出的项
+    pub fn check(&self, usage: &CircuitUsage) -> Result<(), BudgetExceeded> {
+        let items = [
+            (BudgetResource::Constraints, self.max_constraints, usage.constraints),
+            (BudgetResource::Variables, self.max_variables, usage.variables),
+            (BudgetResource::Multiplications, self.max_multiplications, usage.multiplications),
+        ];
+        for (resource, limit, requested) in items {
+            if let Some(limit) = limit.filter(|&limit| requested > limit) {
+                return Err(BudgetExceeded { resource, limit, requested });
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for BudgetResource {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            BudgetResource::Constraints => write!(f, "constraints"),
+            BudgetResource::Variables => write!(f, "witness variables"),
+            BudgetResource::Multiplications => write!(f, "multiplication constraints"),
+        }
+    }
+}
+
+impl std::fmt::Display for BudgetExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "circuit needs {} {} but the budget allows {}", self.requested, self.resource, self.limit)
+    }
+}
+
+impl std::error::Error for BudgetExceeded {}
+
 /// 预定义的电路模板
 pub struct CircuitTemplates;
 
@@ -174,19 +635,19 @@ impl CircuitTemplates {
         let mut circuit = CustomCircuit::new("range_proof".to_string());
         
         // 添加私有见证和公开输入
-        let x_idx = circuit.add_private_witness(x);                      // 索引 0
+        let _x_idx = circuit.add_private_witness(x);                     // Witness(0)
         let x_minus_min = x - min;
         let max_minus_x = max - x;
-        let x_minus_min_idx = circuit.add_private_witness(x_minus_min);  // 索引 1
-        let max_minus_x_idx = circuit.add_private_witness(max_minus_x);  // 索引 2
+        let x_minus_min_idx = circuit.add_private_witness(x_minus_min);  // Witness(1)
+        let max_minus_x_idx = circuit.add_private_witness(max_minus_x);  // Witness(2)
         
         // 添加平方项作为私有见证
-        let square1_idx = circuit.add_private_witness(x_minus_min * x_minus_min); // 索引 3
-        let square2_idx = circuit.add_private_witness(max_minus_x * max_minus_x); // 索引 4
+        let square1_idx = circuit.add_private_witness(x_minus_min * x_minus_min); // Witness(3)
+        let square2_idx = circuit.add_private_witness(max_minus_x * max_minus_x); // Witness(4)
         
         // 添加公开输入
-        let min_idx = circuit.add_public_input(min);                     // 索引 5 (在 all_variables 中)
-        let max_idx = circuit.add_public_input(max);                     // 索引 6 (在 all_variables 中)
+        let _min_idx = circuit.add_public_input(min);                    // Instance(1)
+        let _max_idx = circuit.add_public_input(max);                    // Instance(2)
         
         // 添加约束
         circuit.add_multiplication_constraint(x_minus_min_idx, x_minus_min_idx, square1_idx);
@@ -194,75 +655,1535 @@ impl CircuitTemplates {
         
         circuit
     }
+
+    /// Schnorr 签名验证电路: 证明持有公钥 P 对消息 m 的有效签名 (R, s)
+    ///
+    /// 签名定义在嵌入曲线 Jubjub 上，其基域即 BLS12-381 的标量域，因此点运算在
+    /// 电路中是原生的；只有标量 s 和挑战 e 按比特分解参与标量乘法。挑战
+    /// e = H(R, P, m) 由电路内的 MiMC 风格哈希计算，验证 s·G = R + e·P。
+    /// P 和 m 是仅有的公开输入，R 和 s 为私有见证；P 需由验证者在电路外检查属于
+    /// 素数阶子群。生成元、曲线系数和 MiMC 轮常数都由常量 1 构造，不依赖验证者提供。
+    /// e 的比特分解约束为小于标量域模数，否则 e 与 e + p 都能通过重组检查，而二者
+    /// 作为 Jubjub 标量并不相同。
+    pub fn schnorr_verification(
+        public_key: EdwardsAffine,
+        message: BlsFr,
+        signature: &SchnorrSignature,
+    ) -> CustomCircuit<BlsFr> {
+        let mut builder = TemplateBuilder::new();
+
+        let p = (builder.input(public_key.x), builder.input(public_key.y));
+        let m = builder.input(message);
+        let r = (builder.witness(signature.r.x), builder.witness(signature.r.y));
+        let s = builder.witness(jubjub_scalar_to_base(signature.s));
+        builder.enforce_on_curve(r);
+
+        // 电路内计算挑战
+        let e = builder.mimc_hash(&[r.0, r.1, p.0, p.1, m]);
+
+        let generator = EdwardsAffine::generator();
+        let g = (builder.constant(generator.x), builder.constant(generator.y));
+        let s_bits = builder.bits(s, JubjubScalar::MODULUS_BIT_SIZE as usize);
+        let e_bits = builder.canonical_bits(e);
+
+        let lhs = builder.scalar_mul(&s_bits, g);
+        let e_p = builder.scalar_mul(&e_bits, p);
+        let rhs = builder.point_add(r, e_p);
+        builder.enforce_equal(lhs.0, rhs.0);
+        builder.enforce_equal(lhs.1, rhs.1);
+
+        builder.build("schnorr_verification")
+    }
+
+    /// 非原生模乘电路: 证明 a · b ≡ result (mod modulus)
+    ///
+    /// 所有数值按 `NONNATIVE_LIMB_BITS` 位拆分为 limb，可超过原生域的大小
+    /// （例如 RSA 模数或 secp256k1 的域元素）。a、b 为私有见证，结果的 limb
+    /// 作为前几个公开输入，模数作为常量。
+    pub fn modular_multiplication<F: PrimeField>(
+        a: &BigUint,
+        b: &BigUint,
+        modulus: &BigUint,
+    ) -> CustomCircuit<F> {
+        let num_limbs = limbs_for(modulus);
+        let mut builder = TemplateBuilder::new();
+
+        let expected = builder.alloc_biguint(&((a * b) % modulus), num_limbs, true);
+        let a = builder.alloc_biguint(a, num_limbs, false);
+        let b = builder.alloc_biguint(b, num_limbs, false);
+
+        let product = builder.biguint_mul(&a, &b);
+        let result = builder.biguint_mod_reduce(&product, modulus);
+        builder.biguint_enforce_equal(&result, &expected);
+
+        builder.build("modular_multiplication")
+    }
+
+    /// 整数除法电路: 证明 dividend = quotient · divisor + remainder 且 remainder < divisor
+    ///
+    /// 被除数和除数为私有见证，商和余数为前两个公开输入；各量按比特分解约束在
+    /// `num_bits` 位内，保证等式在整数上而非模域上成立。与 MPC 中的
+    /// `ExecCircuit::div_mod_gate` 对应，为其结果提供 PIOP 可检查的约束。
+    pub fn integer_division<F: PrimeField>(dividend: u64, divisor: u64, num_bits: usize) -> CustomCircuit<F> {
+        let mut builder = TemplateBuilder::new();
+        let quotient = builder.input(F::from(dividend / divisor));
+        let remainder = builder.input(F::from(dividend % divisor));
+        let x = builder.witness(F::from(dividend));
+        let d = builder.witness(F::from(divisor));
+        let (q, r) = builder.div_mod(x, d, num_bits);
+        builder.enforce_equal(q, quotient);
+        builder.enforce_equal(r, remainder);
+        builder.build("integer_division")
+    }
+
+    /// 标量乘电路: 证明 P = [x]B，即 x 是 P 相对公开基点 B 的离散对数
+    ///
+    /// 点在 Jubjub 上，坐标属于原生域。标量 x 为私有见证，按比特分解约束在
+    /// `num_bits` 位内；P 的坐标是仅有的两个公开输入，基点由常量 1 构造。与 MPC
+    /// 中的 `ExecCircuit::scalar_mul_gate` 对应，为其结果提供 PIOP 可检查的约束。
+    pub fn scalar_multiplication(base: EdwardsAffine, scalar: BlsFr, num_bits: usize) -> CustomCircuit<BlsFr> {
+        let point = base.mul_bigint(scalar.into_bigint()).into_affine();
+        let mut builder = TemplateBuilder::new();
+        let p = (builder.input(point.x), builder.input(point.y));
+        let x = builder.witness(scalar);
+        let bits = builder.bits(x, num_bits);
+        let b = (builder.constant(base.x), builder.constant(base.y));
+        let product = builder.scalar_mul(&bits, b);
+        builder.enforce_equal(product.0, p.0);
+        builder.enforce_equal(product.1, p.1);
+        builder.build("scalar_multiplication")
+    }
+
+    /// 重复平方电路: 证明 x^(2^rounds) = y（类似 VDF 的顺序计算负载）
+    pub fn repeated_squaring<F: PrimeField>(x: F, rounds: usize) -> CustomCircuit<F> {
+        let mut circuit = CustomCircuit::new("repeated_squaring".to_string());
+
+        let mut values = vec![x];
+        for _ in 0..rounds {
+            let last = values[values.len() - 1];
+            values.push(last * last);
+        }
+        let indices: Vec<Variable> = values.iter().map(|&v| circuit.add_private_witness(v)).collect();
+        let y_idx = circuit.add_public_input(values[rounds]);
+
+        for i in 0..rounds {
+            circuit.add_multiplication_constraint(indices[i], indices[i], indices[i + 1]);
+        }
+        // y = x^(2^rounds)，通过 y + 0 = 最后一个中间值 绑定公开输入
+        let zero_idx = circuit.add_public_input(F::zero());
+        circuit.add_addition_constraint(y_idx, zero_idx, indices[rounds]);
+
+        circuit
+    }
+
+    /// Merkle 批量更新电路（rollup 的一个状态转移步骤）
+    ///
+    /// 从旧根出发依次应用 updates 中的 (叶子索引, 新值)：每次先用旧叶子和
+    /// 兄弟路径重算出当前根，再用新叶子沿同一路径算出新的当前根。
+    /// 旧根和新根是前两个公开输入；索引、叶子值和路径均为私有见证。
+    /// 电路构造完成后 tree 已应用全部更新。
+    pub fn merkle_batch_update(tree: &mut MerkleTree, updates: &[(usize, BlsFr)]) -> CustomCircuit<BlsFr> {
+        let mut builder = TemplateBuilder::new();
+        let old_root = builder.input(tree.root());
+        let new_root = builder.input(BlsFr::from(0u64));
+
+        let mut current_root = old_root;
+        for &(index, value) in updates {
+            let bits: Vec<Variable> = (0..tree.depth())
+                .map(|level| {
+                    let bit = builder.witness(BlsFr::from(((index >> level) & 1) as u64));
+                    builder.multiplications.push((bit, bit, bit));
+                    bit
+                })
+                .collect();
+            let siblings: Vec<Variable> = tree.path(index).into_iter().map(|s| builder.witness(s)).collect();
+
+            let old_leaf = builder.witness(tree.leaf(index));
+            let computed = builder.merkle_root(old_leaf, &bits, &siblings);
+            builder.enforce_equal(computed, current_root);
+
+            let new_leaf = builder.witness(value);
+            current_root = builder.merkle_root(new_leaf, &bits, &siblings);
+            tree.update(index, value);
+        }
+
+        builder.public[1] = tree.root();
+        builder.enforce_equal(current_root, new_root);
+        builder.build("merkle_batch_update")
+    }
 }
 
-/// 电路测试工具
-pub struct CircuitTester;
+/// Jubjub 上的 Schnorr 签名
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchnorrSignature {
+    pub r: EdwardsAffine,
+    pub s: JubjubScalar,
+}
 
-impl CircuitTester {
-    /// 测试自定义电路
-    pub fn test_circuit<F: PrimeField>(circuit: &CustomCircuit<F>) -> bool {
-        println!("🧪 测试电路: {}", circuit.name);
-        println!("   📊 约束数量: {}", circuit.num_constraints);
-        println!("   🔢 变量数量: {}", circuit.num_variables);
-        println!("   🔒 私有见证数量: {}", circuit.private_witnesses.len());
-        println!("   📖 公开输入数量: {}", circuit.public_inputs.len());
-        
-        let is_valid = circuit.verify_constraints();
-        println!("   ✅ 约束验证结果: {}", is_valid);
-        
-        is_valid
+impl SchnorrSignature {
+    /// 用私钥对消息签名
+    pub fn sign<R: Rng + ?Sized>(secret_key: JubjubScalar, message: BlsFr, rng: &mut R) -> Self {
+        let generator = EdwardsAffine::generator();
+        let k = JubjubScalar::rand(rng);
+        let r = (generator * k).into_affine();
+        let public_key = (generator * secret_key).into_affine();
+        let e = schnorr_challenge(&r, &public_key, message);
+        let e = JubjubScalar::from_le_bytes_mod_order(&e.into_bigint().to_bytes_le());
+        Self { r, s: k + e * secret_key }
     }
-    
-    /// 运行电路的 PIOP 测试
-    pub fn run_piop_test<F: PrimeField>(
-        circuit: &CustomCircuit<F>, 
-        checker: &mut ConsistencyChecker<F>
-    ) -> bool {
-        let witness_polys = circuit.witnesses_to_polynomials();
-        let constraint_polys = circuit.generate_constraint_polynomials();
-        
-        // 添加见证多项式
-        for (i, poly) in witness_polys.iter().enumerate() {
-            checker.add_witness_polynomial(format!("witness_{}", i), poly.clone());
+
+    /// 在电路外验证签名: s·G = R + e·P
+    pub fn verify(&self, public_key: &EdwardsAffine, message: BlsFr) -> bool {
+        let e = schnorr_challenge(&self.r, public_key, message);
+        let lhs = EdwardsAffine::generator() * self.s;
+        let rhs = self.r.into_group() + public_key.mul_bigint(e.into_bigint());
+        lhs == rhs
+    }
+}
+
+/// 签名挑战 e = H(R, P, m)，与电路内的哈希一致
+pub fn schnorr_challenge(r: &EdwardsAffine, public_key: &EdwardsAffine, message: BlsFr) -> BlsFr {
+    mimc_hash(&[r.x, r.y, public_key.x, public_key.y, message])
+}
+
+/// MiMC 风格的海绵哈希，电路内对应 `TemplateBuilder::mimc_hash`
+pub fn mimc_hash(inputs: &[BlsFr]) -> BlsFr {
+    let constants = mimc_round_constants();
+    let mut state = BlsFr::from(0u64);
+    for input in inputs {
+        state += input;
+        for c in &constants {
+            state = (state + c).pow([5u64]);
         }
-        
-        // 添加约束多项式
-        for (i, poly) in constraint_polys.iter().enumerate() {
-            checker.add_public_polynomial(format!("constraint_{}", i), poly.clone());
+    }
+    state
+}
+
+/// 以 MiMC 为压缩函数的定深 Merkle 树
+#[derive(Debug, Clone)]
+pub struct MerkleTree {
+    /// levels[0] 为叶子层，最后一层只含根
+    levels: Vec<Vec<BlsFr>>,
+}
+
+impl MerkleTree {
+    /// 构造深度为 depth 的树，不足的叶子补零
+    pub fn new(depth: usize, leaves: &[BlsFr]) -> Self {
+        assert!(leaves.len() <= 1 << depth, "too many leaves for depth {}", depth);
+        let mut level = leaves.to_vec();
+        level.resize(1 << depth, BlsFr::from(0u64));
+        let mut levels = vec![level];
+        for _ in 0..depth {
+            let next = levels
+                .last()
+                .expect("at least the leaf level")
+                .chunks(2)
+                .map(|pair| mimc_hash(&[pair[0], pair[1]]))
+                .collect();
+            levels.push(next);
+        }
+        Self { levels }
+    }
+
+    pub fn depth(&self) -> usize {
+        self.levels.len() - 1
+    }
+
+    pub fn root(&self) -> BlsFr {
+        self.levels[self.depth()][0]
+    }
+
+    pub fn leaf(&self, index: usize) -> BlsFr {
+        self.levels[0][index]
+    }
+
+    /// 叶子到根路径上的兄弟节点（自底向上）
+    pub fn path(&self, index: usize) -> Vec<BlsFr> {
+        (0..self.depth())
+            .map(|level| self.levels[level][(index >> level) ^ 1])
+            .collect()
+    }
+
+    /// 更新叶子并重算路径上的节点
+    pub fn update(&mut self, index: usize, value: BlsFr) {
+        self.levels[0][index] = value;
+        for level in 0..self.depth() {
+            let i = index >> (level + 1);
+            let parent = mimc_hash(&[self.levels[level][2 * i], self.levels[level][2 * i + 1]]);
+            self.levels[level + 1][i] = parent;
         }
-        
-        let result = checker.batch_consistency_check();
-        result.is_consistent
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use ark_bls12_381::Fr;
-    
-    type TestField = Fr;
-    
-    #[test]
-    fn test_square_root_circuit() {
-        let x = TestField::from(5u64);
-        let y = TestField::from(25u64);
-        
-        let circuit = CircuitTemplates::square_root_verification(x, y);
-        assert!(CircuitTester::test_circuit(&circuit));
+/// MiMC 风格哈希的轮数（用于演示和基准，并非经过审计的参数集）
+const MIMC_ROUNDS: usize = 32;
+
+fn mimc_round_constants() -> Vec<BlsFr> {
+    (0..MIMC_ROUNDS)
+        .map(|i| {
+            let digest = Sha256::new()
+                .chain_update(b"EOS-schnorr-mimc")
+                .chain_update((i as u64).to_le_bytes())
+                .finalize();
+            BlsFr::from_le_bytes_mod_order(&digest)
+        })
+        .collect()
+}
+
+fn jubjub_scalar_to_base(s: JubjubScalar) -> BlsFr {
+    BlsFr::from_le_bytes_mod_order(&s.into_bigint().to_bytes_le())
+}
+
+type PointVar = (Variable, Variable);
+
+/// 模板构造器：分别收集私有见证和公开输入，约束直接引用 `Variable`
+struct TemplateBuilder<F: PrimeField> {
+    private: Vec<F>,
+    public: Vec<F>,
+    constants: Vec<(F, Variable)>,
+    /// 由常量 1 倍增得到的 2^i
+    powers_of_two: Vec<Variable>,
+    multiplications: Vec<(Variable, Variable, Variable)>,
+    additions: Vec<(Variable, Variable, Variable)>,
+}
+
+impl<F: PrimeField> TemplateBuilder<F> {
+    fn new() -> Self {
+        Self {
+            private: Vec::new(),
+            public: Vec::new(),
+            constants: Vec::new(),
+            powers_of_two: vec![Variable::ONE],
+            multiplications: Vec::new(),
+            additions: Vec::new(),
+        }
     }
-    
-    #[test]
-    fn test_custom_circuit_creation() {
-        let mut circuit = CustomCircuit::<TestField>::new("test".to_string());
-        
-        let a_idx = circuit.add_private_witness(TestField::from(3u64));
-        let b_idx = circuit.add_private_witness(TestField::from(4u64));
-        let c_idx = circuit.add_private_witness(TestField::from(12u64));
-        
-        circuit.add_multiplication_constraint(a_idx, b_idx, c_idx);
-        
-        assert!(circuit.verify_constraints());
+
+    fn value(&self, var: Variable) -> F {
+        match var {
+            Variable::Instance(0) => F::one(),
+            Variable::Instance(i) => self.public[i - 1],
+            Variable::Witness(j) => self.private[j],
+        }
+    }
+
+    fn witness(&mut self, value: F) -> Variable {
+        self.private.push(value);
+        Variable::Witness(self.private.len() - 1)
+    }
+
+    fn input(&mut self, value: F) -> Variable {
+        self.public.push(value);
+        Variable::public_input(self.public.len() - 1)
+    }
+
+    /// 常量由常量 1 经加法约束构造，不占用公开输入，相同的值只构造一次
+    ///
+    /// 零约束为 1 + z = 1；其他值是其二进制表示中为 1 的各位 2^i 之和。
+    fn constant(&mut self, value: F) -> Variable {
+        if value.is_one() {
+            return Variable::ONE;
+        }
+        if let Some(&(_, var)) = self.constants.iter().find(|(v, _)| *v == value) {
+            return var;
+        }
+        let var = if value.is_zero() {
+            self.sub(Variable::ONE, Variable::ONE)
+        } else {
+            let bigint = value.into_bigint();
+            let mut sum = None;
+            for i in (0..F::MODULUS_BIT_SIZE as usize).filter(|&i| bigint.get_bit(i)) {
+                let power = self.power_of_two(i);
+                sum = Some(match sum {
+                    Some(acc) => self.add(acc, power),
+                    None => power,
+                });
+            }
+            sum.expect("a nonzero value has a set bit")
+        };
+        self.constants.push((value, var));
+        var
+    }
+
+    /// 2^i，约束为 2^i = 2^(i−1) + 2^(i−1)
+    fn power_of_two(&mut self, i: usize) -> Variable {
+        while self.powers_of_two.len() <= i {
+            let last = *self.powers_of_two.last().expect("starts with one");
+            let next = self.add(last, last);
+            self.powers_of_two.push(next);
+        }
+        self.powers_of_two[i]
+    }
+
+    fn mul(&mut self, a: Variable, b: Variable) -> Variable {
+        let c = self.witness(self.value(a) * self.value(b));
+        self.multiplications.push((a, b, c));
+        c
+    }
+
+    fn add(&mut self, a: Variable, b: Variable) -> Variable {
+        let c = self.witness(self.value(a) + self.value(b));
+        self.additions.push((a, b, c));
+        c
+    }
+
+    /// c = a - b，约束为 b + c = a
+    fn sub(&mut self, a: Variable, b: Variable) -> Variable {
+        let c = self.witness(self.value(a) - self.value(b));
+        self.additions.push((b, c, a));
+        c
+    }
+
+    /// c = a / b，约束为 c * b = a
+    fn div(&mut self, a: Variable, b: Variable) -> Variable {
+        let inverse = self.value(b).inverse().expect("division by zero in template");
+        let c = self.witness(self.value(a) * inverse);
+        self.multiplications.push((c, b, a));
+        c
+    }
+
+    fn enforce_equal(&mut self, a: Variable, b: Variable) {
+        let zero = self.constant(F::zero());
+        self.additions.push((a, zero, b));
+    }
+
+    /// 按比特分解（高位在前），约束每一位为布尔值且重组后等于原值
+    fn bits(&mut self, var: Variable, num_bits: usize) -> Vec<Variable> {
+        let value = field_to_biguint(self.value(var));
+        self.decompose(var, &value, num_bits)
+    }
+
+    /// 以 `value` 的比特分解 var；重组只在域内与 var 相等
+    fn decompose(&mut self, var: Variable, value: &BigUint, num_bits: usize) -> Vec<Variable> {
+        let bits: Vec<Variable> = (0..num_bits)
+            .rev()
+            .map(|i| {
+                let bit = if value.bit(i as u64) { F::one() } else { F::zero() };
+                self.witness(bit)
+            })
+            .collect();
+        for &bit in &bits {
+            self.multiplications.push((bit, bit, bit));
+        }
+        let acc = self.recompose(&bits);
+        self.enforce_equal(acc, var);
+        bits
+    }
+
+    /// 由布尔变量（高位在前）重组出的值
+    fn recompose(&mut self, bits: &[Variable]) -> Variable {
+        let mut acc = self.constant(F::zero());
+        for &bit in bits {
+            let doubled = self.add(acc, acc);
+            acc = self.add(doubled, bit);
+        }
+        acc
+    }
+
+    /// 按比特分解原生域元素，并约束分解小于模数 p
+    ///
+    /// `MODULUS_BIT_SIZE` 位能表示的值可以超过 p，此时 v 与 v + p 的分解都满足
+    /// 重组约束。分解按 limb 重组为大整数后，与 `biguint_mod_reduce` 相同，
+    /// 用非负松弛量约束 value + slack = p − 1。
+    fn canonical_bits(&mut self, var: Variable) -> Vec<Variable> {
+        let bits = self.bits(var, F::MODULUS_BIT_SIZE as usize);
+        self.enforce_canonical(&bits);
+        bits
+    }
+
+    fn enforce_canonical(&mut self, bits: &[Variable]) {
+        let limbs = bits.rchunks(NONNATIVE_LIMB_BITS).map(|chunk| self.recompose(chunk)).collect();
+        let value = BigUintVar { limbs };
+        let bound = field_to_biguint(-F::one());
+        let num_limbs = limbs_for(&bound);
+        // 不规范的分解没有非负松弛量，取零让约束失败
+        let current = self.biguint_value(&value);
+        let slack = if current <= bound { &bound - current } else { BigUint::from(0u64) };
+        let slack = self.alloc_biguint(&slack, num_limbs, false);
+        let sum = self.biguint_add(&value, &slack);
+        let bound = self.biguint_constant(&bound, num_limbs);
+        self.biguint_enforce_equal(&sum, &bound);
+    }
+
+    /// 整数除法，返回 (商, 余数)
+    ///
+    /// 商、余数、除数和松弛量 divisor − 1 − remainder 都分解为 `num_bits` 位，
+    /// 松弛量非负即 remainder < divisor（同时排除了除数为零）。
+    fn div_mod(&mut self, dividend: Variable, divisor: Variable, num_bits: usize) -> (Variable, Variable) {
+        assert!(2 * num_bits + 1 < F::MODULUS_BIT_SIZE as usize, "{} bits overflow the native field", num_bits);
+        let x = field_to_biguint(self.value(dividend));
+        let d = field_to_biguint(self.value(divisor));
+        assert!(d > BigUint::from(0u64), "division by zero in template");
+        let q = self.witness(biguint_to_field(&(&x / &d)));
+        let r = self.witness(biguint_to_field(&(&x % &d)));
+        let slack = self.witness(biguint_to_field(&(&d - 1u64 - &x % &d)));
+        for var in [q, r, divisor, slack] {
+            self.bits(var, num_bits);
+        }
+
+        let product = self.mul(q, divisor);
+        let sum = self.add(product, r);
+        self.enforce_equal(sum, dividend);
+        let one = self.constant(F::one());
+        let bounded = self.add(r, slack);
+        let bound = self.add(bounded, one);
+        self.enforce_equal(bound, divisor);
+        (q, r)
+    }
+}
+
+impl TemplateBuilder<BlsFr> {
+    /// 电路内的 MiMC 风格哈希，与 `schnorr_challenge` 一致
+    fn mimc_hash(&mut self, inputs: &[Variable]) -> Variable {
+        let constants: Vec<Variable> = mimc_round_constants()
+            .into_iter()
+            .map(|c| self.constant(c))
+            .collect();
+        let mut state = self.constant(BlsFr::from(0u64));
+        for &input in inputs {
+            state = self.add(state, input);
+            for &c in &constants {
+                let t = self.add(state, c);
+                let t2 = self.mul(t, t);
+                let t4 = self.mul(t2, t2);
+                state = self.mul(t4, t);
+            }
+        }
+        state
+    }
+
+    /// 沿路径自底向上计算根；bit 为 1 表示当前节点是右孩子
+    fn merkle_root(&mut self, leaf: Variable, bits: &[Variable], siblings: &[Variable]) -> Variable {
+        let mut node = leaf;
+        for (&bit, &sibling) in bits.iter().zip(siblings) {
+            let diff = self.sub(sibling, node);
+            let delta = self.mul(bit, diff);
+            let left = self.add(node, delta);
+            let right = self.sub(sibling, delta);
+            node = self.mimc_hash(&[left, right]);
+        }
+        node
+    }
+
+    /// 约束点在 Jubjub 上: a·x² + y² = 1 + d·x²·y²
+    fn enforce_on_curve(&mut self, point: PointVar) {
+        let a = self.constant(JubjubConfig::COEFF_A);
+        let d = self.constant(JubjubConfig::COEFF_D);
+        let one = self.constant(BlsFr::from(1u64));
+        let x2 = self.mul(point.0, point.0);
+        let y2 = self.mul(point.1, point.1);
+        let ax2 = self.mul(a, x2);
+        let lhs = self.add(ax2, y2);
+        let x2y2 = self.mul(x2, y2);
+        let dx2y2 = self.mul(d, x2y2);
+        let rhs = self.add(one, dx2y2);
+        self.enforce_equal(lhs, rhs);
+    }
+
+    /// 扭曲爱德华兹曲线的完备加法公式
+    fn point_add(&mut self, p: PointVar, q: PointVar) -> PointVar {
+        let a = self.constant(JubjubConfig::COEFF_A);
+        let d = self.constant(JubjubConfig::COEFF_D);
+        let one = self.constant(BlsFr::from(1u64));
+
+        let x1y2 = self.mul(p.0, q.1);
+        let y1x2 = self.mul(p.1, q.0);
+        let x1x2 = self.mul(p.0, q.0);
+        let y1y2 = self.mul(p.1, q.1);
+        let t = self.mul(x1x2, y1y2);
+        let dt = self.mul(d, t);
+
+        let x_num = self.add(x1y2, y1x2);
+        let x_den = self.add(one, dt);
+        let ax1x2 = self.mul(a, x1x2);
+        let y_num = self.sub(y1y2, ax1x2);
+        let y_den = self.sub(one, dt);
+
+        (self.div(x_num, x_den), self.div(y_num, y_den))
+    }
+
+    /// bit ? if_one : if_zero
+    fn point_select(&mut self, bit: Variable, if_one: PointVar, if_zero: PointVar) -> PointVar {
+        let dx = self.sub(if_one.0, if_zero.0);
+        let dy = self.sub(if_one.1, if_zero.1);
+        let tx = self.mul(bit, dx);
+        let ty = self.mul(bit, dy);
+        (self.add(if_zero.0, tx), self.add(if_zero.1, ty))
+    }
+
+    /// 二倍-加法标量乘（比特高位在前）
+    fn scalar_mul(&mut self, bits: &[Variable], base: PointVar) -> PointVar {
+        let mut acc = (self.constant(BlsFr::from(0u64)), self.constant(BlsFr::from(1u64)));
+        for &bit in bits {
+            acc = self.point_add(acc, acc);
+            let sum = self.point_add(acc, base);
+            acc = self.point_select(bit, sum, acc);
+        }
+        acc
+    }
+}
+
+/// 非原生大整数每个 limb 的位数
+pub const NONNATIVE_LIMB_BITS: usize = 64;
+
+fn limbs_for(value: &BigUint) -> usize {
+    (value.bits() as usize).div_ceil(NONNATIVE_LIMB_BITS).max(1)
+}
+
+fn field_to_biguint<F: PrimeField>(value: F) -> BigUint {
+    BigUint::from_bytes_le(&value.into_bigint().to_bytes_le())
+}
+
+fn biguint_to_field<F: PrimeField>(value: &BigUint) -> F {
+    F::from_le_bytes_mod_order(&value.to_bytes_le())
+}
+
+/// 以 limb 表示的大整数变量（小端序，每个 limb 已约束在 NONNATIVE_LIMB_BITS 位内）
+#[derive(Debug, Clone)]
+struct BigUintVar {
+    limbs: Vec<Variable>,
+}
+
+impl<F: PrimeField> TemplateBuilder<F> {
+    fn biguint_value(&self, value: &BigUintVar) -> BigUint {
+        value.limbs.iter().rev().fold(BigUint::from(0u64), |acc, &limb| {
+            (acc << NONNATIVE_LIMB_BITS) + field_to_biguint(self.value(limb))
+        })
+    }
+
+    fn split_limbs(value: &BigUint, num_limbs: usize) -> Vec<F> {
+        let mask = (BigUint::from(1u64) << NONNATIVE_LIMB_BITS) - 1u64;
+        (0..num_limbs)
+            .map(|i| biguint_to_field(&((value >> (i * NONNATIVE_LIMB_BITS)) & &mask)))
+            .collect()
+    }
+
+    /// 分配大整数并对每个 limb 做范围约束
+    fn alloc_biguint(&mut self, value: &BigUint, num_limbs: usize, public: bool) -> BigUintVar {
+        assert!(limbs_for(value) <= num_limbs, "value does not fit in {} limbs", num_limbs);
+        let limbs = Self::split_limbs(value, num_limbs)
+            .into_iter()
+            .map(|limb| {
+                let var = if public { self.input(limb) } else { self.witness(limb) };
+                self.bits(var, NONNATIVE_LIMB_BITS);
+                var
+            })
+            .collect();
+        BigUintVar { limbs }
+    }
+
+    fn biguint_constant(&mut self, value: &BigUint, num_limbs: usize) -> BigUintVar {
+        let limbs = Self::split_limbs(value, num_limbs)
+            .into_iter()
+            .map(|limb| self.constant(limb))
+            .collect();
+        BigUintVar { limbs }
+    }
+
+    /// 将未规约的列和进位规约为标准 limb；结果比输入多一个 limb
+    fn normalize_columns(&mut self, columns: Vec<Variable>) -> BigUintVar {
+        // 列和不超过 columns.len() · 2^(2·LIMB_BITS)，进位需要额外的 log 位
+        let carry_bits = NONNATIVE_LIMB_BITS + 2 + (usize::BITS - columns.len().leading_zeros()) as usize;
+        assert!(
+            (F::MODULUS_BIT_SIZE as usize) > NONNATIVE_LIMB_BITS + carry_bits,
+            "native field too small for non-native limbs"
+        );
+        let shift = self.constant(biguint_to_field(&(BigUint::from(1u64) << NONNATIVE_LIMB_BITS)));
+        let mut carry = self.constant(F::zero());
+        let mut limbs = Vec::with_capacity(columns.len() + 1);
+        for column in columns {
+            let sum = self.add(column, carry);
+            let total = field_to_biguint(self.value(sum));
+            let limb = self.witness(biguint_to_field(&(&total % (BigUint::from(1u64) << NONNATIVE_LIMB_BITS))));
+            let next_carry = self.witness(biguint_to_field(&(&total >> NONNATIVE_LIMB_BITS)));
+            self.bits(limb, NONNATIVE_LIMB_BITS);
+            self.bits(next_carry, carry_bits);
+            // limb + carry · 2^LIMB_BITS = sum
+            let shifted = self.mul(next_carry, shift);
+            self.additions.push((limb, shifted, sum));
+            limbs.push(limb);
+            carry = next_carry;
+        }
+        self.bits(carry, NONNATIVE_LIMB_BITS);
+        limbs.push(carry);
+        BigUintVar { limbs }
+    }
+
+    fn biguint_add(&mut self, a: &BigUintVar, b: &BigUintVar) -> BigUintVar {
+        let zero = self.constant(F::zero());
+        let len = a.limbs.len().max(b.limbs.len());
+        let columns = (0..len)
+            .map(|i| {
+                let x = a.limbs.get(i).copied().unwrap_or(zero);
+                let y = b.limbs.get(i).copied().unwrap_or(zero);
+                self.add(x, y)
+            })
+            .collect();
+        self.normalize_columns(columns)
+    }
+
+    /// 教科书乘法，结果有 a.len + b.len 个 limb
+    fn biguint_mul(&mut self, a: &BigUintVar, b: &BigUintVar) -> BigUintVar {
+        let mut columns: Vec<Option<Variable>> = vec![None; a.limbs.len() + b.limbs.len() - 1];
+        for (i, &x) in a.limbs.iter().enumerate() {
+            for (j, &y) in b.limbs.iter().enumerate() {
+                let term = self.mul(x, y);
+                columns[i + j] = Some(match columns[i + j] {
+                    Some(acc) => self.add(acc, term),
+                    None => term,
+                });
+            }
+        }
+        let columns = columns.into_iter().map(|c| c.expect("every column has a term")).collect();
+        self.normalize_columns(columns)
+    }
+
+    /// 约束两个大整数相等（较短的一方高位补零）
+    fn biguint_enforce_equal(&mut self, a: &BigUintVar, b: &BigUintVar) {
+        let zero = self.constant(F::zero());
+        for i in 0..a.limbs.len().max(b.limbs.len()) {
+            let x = a.limbs.get(i).copied().unwrap_or(zero);
+            let y = b.limbs.get(i).copied().unwrap_or(zero);
+            self.enforce_equal(x, y);
+        }
+    }
+
+    /// 模约简: 见证 q、r 满足 x = q · modulus + r 且 r < modulus，返回 r
+    fn biguint_mod_reduce(&mut self, x: &BigUintVar, modulus: &BigUint) -> BigUintVar {
+        let num_limbs = limbs_for(modulus);
+        let value = self.biguint_value(x);
+        let quotient = self.alloc_biguint(&(&value / modulus), x.limbs.len(), false);
+        let remainder = self.alloc_biguint(&(&value % modulus), num_limbs, false);
+
+        let m = self.biguint_constant(modulus, num_limbs);
+        let qm = self.biguint_mul(&quotient, &m);
+        let reconstructed = self.biguint_add(&qm, &remainder);
+        self.biguint_enforce_equal(&reconstructed, x);
+
+        // r + d = modulus - 1 且 d ≥ 0 蕴含 r < modulus
+        let bound = modulus - 1u64;
+        let slack = &bound - self.biguint_value(&remainder);
+        let slack = self.alloc_biguint(&slack, num_limbs, false);
+        let sum = self.biguint_add(&remainder, &slack);
+        let bound = self.biguint_constant(&bound, num_limbs);
+        self.biguint_enforce_equal(&sum, &bound);
+
+        remainder
+    }
+}
+
+impl<F: PrimeField> TemplateBuilder<F> {
+    fn build(self, name: &str) -> CustomCircuit<F> {
+        let multiplication_constraints = self.multiplications;
+        let addition_constraints = self.additions;
+        CustomCircuit {
+            name: name.to_string(),
+            num_constraints: multiplication_constraints.len() + addition_constraints.len(),
+            num_variables: self.private.len() + self.public.len(),
+            private_witnesses: self.private,
+            public_inputs: self.public,
+            multiplication_constraints,
+            addition_constraints,
+            delegator_only: BTreeSet::new(),
+        }
+    }
+}
+
+/// 见证的统计信息
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct WitnessStats {
+    pub private: usize,
+    pub public: usize,
+    /// 标注为委托方私有的见证数
+    pub delegator_only: usize,
+    /// 取值为零的私有见证数
+    pub zero: usize,
+    /// 没有被任何约束引用的私有见证数
+    pub unused: usize,
+}
+
+/// `CircuitTester::test_circuit` 的结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct CircuitTestReport<F: PrimeField> {
+    pub circuit_name: String,
+    pub num_multiplications: usize,
+    pub num_additions: usize,
+    pub num_variables: usize,
+    pub witness: WitnessStats,
+    /// 全部不满足的约束，按乘法约束在前的顺序
+    pub violations: Vec<ConstraintViolation<F>>,
+    pub elapsed: Duration,
+}
+
+impl<F: PrimeField> CircuitTestReport<F> {
+    pub fn num_constraints(&self) -> usize {
+        self.num_multiplications + self.num_additions
+    }
+
+    pub fn passed(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    /// 第 `index` 个 `kind` 类约束是否不满足
+    pub fn is_violated(&self, kind: ConstraintKind, index: usize) -> bool {
+        self.violations.iter().any(|violation| violation.kind == kind && violation.index == index)
+    }
+
+    /// 不满足时 panic，信息为完整报告
+    #[track_caller]
+    pub fn assert_passed(&self) {
+        assert!(self.passed(), "{}", self);
+    }
+
+    /// 只有给出的约束不满足，否则 panic
+    #[track_caller]
+    pub fn assert_violated_exactly(&self, expected: &[(ConstraintKind, usize)]) {
+        let actual: Vec<_> = self.violations.iter().map(|violation| (violation.kind, violation.index)).collect();
+        assert_eq!(actual, expected, "{}", self);
+    }
+
+    /// 单行 JSON，供 CI 工具读取
+    pub fn to_json(&self) -> String {
+        let violations: Vec<String> = self
+            .violations
+            .iter()
+            .map(|violation| {
+                let kind = match violation.kind {
+                    ConstraintKind::Multiplication => "multiplication",
+                    ConstraintKind::Addition => "addition",
+                };
+                format!(
+                    "{{\"kind\": \"{}\", \"index\": {}, \"message\": \"{}\"}}",
+                    kind,
+                    violation.index,
+                    violation.to_string().escape_default()
+                )
+            })
+            .collect();
+        format!(
+            "{{\"circuit\": \"{}\", \"passed\": {}, \"constraints\": {}, \"multiplications\": {}, \
+             \"additions\": {}, \"variables\": {}, \"private\": {}, \"public\": {}, \"delegator_only\": {}, \
+             \"zero_witnesses\": {}, \"unused_witnesses\": {}, \"elapsed_us\": {}, \"violations\": [{}]}}",
+            self.circuit_name.escape_default(),
+            self.passed(),
+            self.num_constraints(),
+            self.num_multiplications,
+            self.num_additions,
+            self.num_variables,
+            self.witness.private,
+            self.witness.public,
+            self.witness.delegator_only,
+            self.witness.zero,
+            self.witness.unused,
+            self.elapsed.as_micros(),
+            violations.join(", ")
+        )
+    }
+}
+
+impl<F: PrimeField> std::fmt::Display for CircuitTestReport<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        writeln!(f, "🧪 测试电路: {}", self.circuit_name)?;
+        writeln!(
+            f,
+            "   📊 约束数量: {} (乘法 {}, 加法 {})",
+            self.num_constraints(),
+            self.num_multiplications,
+            self.num_additions
+        )?;
+        writeln!(f, "   🔢 变量数量: {}", self.num_variables)?;
+        writeln!(
+            f,
+            "   🔒 私有见证数量: {} (委托方私有 {}, 零值 {}, 未引用 {})",
+            self.witness.private, self.witness.delegator_only, self.witness.zero, self.witness.unused
+        )?;
+        writeln!(f, "   📖 公开输入数量: {}", self.witness.public)?;
+        for violation in &self.violations {
+            writeln!(f, "   ❌ {}", violation)?;
+        }
+        write!(f, "   ✅ 约束验证结果: {} ({:?})", self.passed(), self.elapsed)
+    }
+}
+
+/// 电路测试工具
+pub struct CircuitTester;
+
+impl CircuitTester {
+    /// 测试自定义电路，检查全部约束并统计见证
+    pub fn test_circuit<F: PrimeField>(circuit: &CustomCircuit<F>) -> CircuitTestReport<F> {
+        let start = Instant::now();
+        let violations = circuit.check_witness().violations;
+
+        let referenced: BTreeSet<usize> = circuit
+            .multiplication_constraints
+            .iter()
+            .chain(&circuit.addition_constraints)
+            .flat_map(|&(a, b, c)| [a, b, c])
+            .filter_map(|variable| match variable {
+                Variable::Witness(j) => Some(j),
+                Variable::Instance(_) => None,
+            })
+            .collect();
+        let witness = WitnessStats {
+            private: circuit.private_witnesses.len(),
+            public: circuit.public_inputs.len(),
+            delegator_only: circuit.delegator_only.len(),
+            zero: circuit.private_witnesses.iter().filter(|value| value.is_zero()).count(),
+            unused: (0..circuit.private_witnesses.len()).filter(|j| !referenced.contains(j)).count(),
+        };
+
+        CircuitTestReport {
+            circuit_name: circuit.name.clone(),
+            num_multiplications: circuit.multiplication_constraints.len(),
+            num_additions: circuit.addition_constraints.len(),
+            num_variables: circuit.num_variables,
+            witness,
+            violations,
+            elapsed: start.elapsed(),
+        }
+    }
+    
+    /// 运行电路的 PIOP 测试
+    pub fn run_piop_test<F: PrimeField>(
+        circuit: &CustomCircuit<F>, 
+        checker: &mut ConsistencyChecker<F>
+    ) -> bool {
+        let witness_polys = circuit.witnesses_to_polynomials();
+        let constraint_polys = circuit.generate_constraint_polynomials();
+        
+        // 添加见证多项式
+        for (i, poly) in witness_polys.iter().enumerate() {
+            checker.add_witness_polynomial(format!("witness_{}", i), poly.clone());
+        }
+        
+        // 添加约束多项式
+        for (i, poly) in constraint_polys.iter().enumerate() {
+            checker.add_public_polynomial(format!("constraint_{}", i), poly.clone());
+        }
+        
+        let result = checker.batch_consistency_check();
+        result.is_consistent
+    }
+}
+
+/// 模板参数的类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParamKind {
+    /// 树深度等结构深度
+    Depth,
+    /// 数值位宽
+    BitWidth,
+    /// 迭代轮数
+    Rounds,
+    /// 元素个数
+    Count,
+}
+
+/// 模板参数声明
+#[derive(Debug, Clone)]
+pub struct ParamSpec {
+    pub name: &'static str,
+    pub kind: ParamKind,
+    pub default: u64,
+    pub range: RangeInclusive<u64>,
+}
+
+/// 已校验并补齐默认值的模板参数
+#[derive(Debug, Clone, Default)]
+pub struct TemplateParams {
+    values: BTreeMap<&'static str, u64>,
+}
+
+impl TemplateParams {
+    /// 参数值；未声明的参数会 panic
+    pub fn get(&self, name: &str) -> u64 {
+        *self.values.get(name).unwrap_or_else(|| panic!("parameter '{}' not declared", name))
+    }
+
+    pub fn get_usize(&self, name: &str) -> usize {
+        self.get(name) as usize
+    }
+}
+
+/// 可参数化的电路模板
+pub trait Template {
+    /// 注册名
+    fn name(&self) -> &'static str;
+
+    /// 简要说明
+    fn description(&self) -> &'static str;
+
+    /// 参数声明
+    fn params(&self) -> Vec<ParamSpec>;
+
+    /// 按参数生成电路及一组满足约束的随机见证
+    fn instantiate(&self, params: &TemplateParams, rng: &mut dyn RngCore) -> CustomCircuit<BlsFr>;
+}
+
+struct SquareRootTemplate;
+
+impl Template for SquareRootTemplate {
+    fn name(&self) -> &'static str {
+        "square_root"
+    }
+
+    fn description(&self) -> &'static str {
+        "验证 x² = y"
+    }
+
+    fn params(&self) -> Vec<ParamSpec> {
+        Vec::new()
+    }
+
+    fn instantiate(&self, _params: &TemplateParams, rng: &mut dyn RngCore) -> CustomCircuit<BlsFr> {
+        let x = BlsFr::rand(rng);
+        CircuitTemplates::square_root_verification(x, x * x)
+    }
+}
+
+struct RangeProofTemplate;
+
+impl Template for RangeProofTemplate {
+    fn name(&self) -> &'static str {
+        "range_proof"
+    }
+
+    fn description(&self) -> &'static str {
+        "证明 x ∈ [0, 2^bit_width)"
+    }
+
+    fn params(&self) -> Vec<ParamSpec> {
+        vec![ParamSpec { name: "bit_width", kind: ParamKind::BitWidth, default: 32, range: 1..=63 }]
+    }
+
+    fn instantiate(&self, params: &TemplateParams, rng: &mut dyn RngCore) -> CustomCircuit<BlsFr> {
+        let max = (1u64 << params.get("bit_width")) - 1;
+        let x = rng.gen_range(0..=max);
+        CircuitTemplates::range_proof(BlsFr::from(x), BlsFr::from(0u64), BlsFr::from(max))
+    }
+}
+
+struct RepeatedSquaringTemplate;
+
+impl Template for RepeatedSquaringTemplate {
+    fn name(&self) -> &'static str {
+        "repeated_squaring"
+    }
+
+    fn description(&self) -> &'static str {
+        "证明 y = x^(2^rounds)"
+    }
+
+    fn params(&self) -> Vec<ParamSpec> {
+        vec![ParamSpec { name: "rounds", kind: ParamKind::Rounds, default: 64, range: 1..=1 << 20 }]
+    }
+
+    fn instantiate(&self, params: &TemplateParams, rng: &mut dyn RngCore) -> CustomCircuit<BlsFr> {
+        CircuitTemplates::repeated_squaring(BlsFr::rand(rng), params.get_usize("rounds"))
+    }
+}
+
+struct ModularMultiplicationTemplate;
+
+impl Template for ModularMultiplicationTemplate {
+    fn name(&self) -> &'static str {
+        "modular_multiplication"
+    }
+
+    fn description(&self) -> &'static str {
+        "非原生模乘 a · b mod m，m 为 bit_width 位奇数"
+    }
+
+    fn params(&self) -> Vec<ParamSpec> {
+        vec![ParamSpec { name: "bit_width", kind: ParamKind::BitWidth, default: 256, range: 2..=4096 }]
+    }
+
+    fn instantiate(&self, params: &TemplateParams, rng: &mut dyn RngCore) -> CustomCircuit<BlsFr> {
+        let bits = params.get("bit_width");
+        let random_below = |rng: &mut dyn RngCore| {
+            let mut bytes = vec![0u8; bits.div_ceil(8) as usize];
+            rng.fill_bytes(&mut bytes);
+            BigUint::from_bytes_le(&bytes) % (BigUint::from(1u64) << bits)
+        };
+        let modulus = random_below(rng) | (BigUint::from(1u64) << (bits - 1)) | BigUint::from(1u64);
+        let a = random_below(rng) % &modulus;
+        let b = random_below(rng) % &modulus;
+        CircuitTemplates::modular_multiplication(&a, &b, &modulus)
+    }
+}
+
+struct MerkleBatchUpdateTemplate;
+
+impl Template for MerkleBatchUpdateTemplate {
+    fn name(&self) -> &'static str {
+        "merkle_batch_update"
+    }
+
+    fn description(&self) -> &'static str {
+        "对深度为 depth 的 Merkle 树应用 updates 次叶子更新"
+    }
+
+    fn params(&self) -> Vec<ParamSpec> {
+        vec![
+            ParamSpec { name: "depth", kind: ParamKind::Depth, default: 8, range: 1..=20 },
+            ParamSpec { name: "updates", kind: ParamKind::Count, default: 4, range: 1..=1 << 16 },
+        ]
+    }
+
+    fn instantiate(&self, params: &TemplateParams, rng: &mut dyn RngCore) -> CustomCircuit<BlsFr> {
+        let depth = params.get_usize("depth");
+        let leaves: Vec<BlsFr> = (0..1usize << depth).map(|_| BlsFr::rand(rng)).collect();
+        let mut tree = MerkleTree::new(depth, &leaves);
+        let updates: Vec<(usize, BlsFr)> = (0..params.get_usize("updates"))
+            .map(|_| (rng.gen_range(0..1usize << depth), BlsFr::rand(rng)))
+            .collect();
+        CircuitTemplates::merkle_batch_update(&mut tree, &updates)
+    }
+}
+
+struct SchnorrVerificationTemplate;
+
+impl Template for SchnorrVerificationTemplate {
+    fn name(&self) -> &'static str {
+        "schnorr_verification"
+    }
+
+    fn description(&self) -> &'static str {
+        "Jubjub 上的 Schnorr 签名验证"
+    }
+
+    fn params(&self) -> Vec<ParamSpec> {
+        Vec::new()
+    }
+
+    fn instantiate(&self, _params: &TemplateParams, rng: &mut dyn RngCore) -> CustomCircuit<BlsFr> {
+        let secret_key = JubjubScalar::rand(rng);
+        let public_key = (EdwardsAffine::generator() * secret_key).into_affine();
+        let message = BlsFr::rand(rng);
+        let signature = SchnorrSignature::sign(secret_key, message, rng);
+        CircuitTemplates::schnorr_verification(public_key, message, &signature)
+    }
+}
+
+/// 按名称实例化模板的注册表
+pub struct TemplateRegistry {
+    templates: BTreeMap<&'static str, Box<dyn Template>>,
+}
+
+impl TemplateRegistry {
+    /// 空注册表
+    pub fn new() -> Self {
+        Self { templates: BTreeMap::new() }
+    }
+
+    /// 包含全部内置模板的注册表
+    pub fn with_builtin_templates() -> Self {
+        let mut registry = Self::new();
+        registry.register(Box::new(SquareRootTemplate));
+        registry.register(Box::new(RangeProofTemplate));
+        registry.register(Box::new(RepeatedSquaringTemplate));
+        registry.register(Box::new(ModularMultiplicationTemplate));
+        registry.register(Box::new(MerkleBatchUpdateTemplate));
+        registry.register(Box::new(SchnorrVerificationTemplate));
+        registry
+    }
+
+    /// 注册模板，同名模板会被替换
+    pub fn register(&mut self, template: Box<dyn Template>) {
+        self.templates.insert(template.name(), template);
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        self.templates.keys().copied().collect()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn Template> {
+        self.templates.get(name).map(|t| t.as_ref())
+    }
+
+    /// 校验字符串参数并补齐默认值
+    pub fn resolve_params(
+        &self,
+        name: &str,
+        raw: &HashMap<String, String>,
+    ) -> Result<TemplateParams, TemplateError> {
+        let template = self.get(name).ok_or_else(|| TemplateError::UnknownTemplate(name.to_string()))?;
+        let specs = template.params();
+
+        if let Some(unknown) = raw.keys().find(|key| !specs.iter().any(|spec| spec.name == key.as_str())) {
+            return Err(TemplateError::UnknownParameter {
+                template: name.to_string(),
+                parameter: unknown.clone(),
+            });
+        }
+
+        let mut params = TemplateParams::default();
+        for spec in specs {
+            let value = match raw.get(spec.name) {
+                Some(text) => text.parse::<u64>().map_err(|_| TemplateError::InvalidValue {
+                    parameter: spec.name.to_string(),
+                    value: text.clone(),
+                })?,
+                None => spec.default,
+            };
+            if !spec.range.contains(&value) {
+                return Err(TemplateError::OutOfRange {
+                    parameter: spec.name.to_string(),
+                    value,
+                    min: *spec.range.start(),
+                    max: *spec.range.end(),
+                });
+            }
+            params.values.insert(spec.name, value);
+        }
+        Ok(params)
+    }
+
+    /// 按名称和参数表实例化模板
+    pub fn instantiate(
+        &self,
+        name: &str,
+        raw: &HashMap<String, String>,
+        rng: &mut dyn RngCore,
+    ) -> Result<CustomCircuit<BlsFr>, TemplateError> {
+        let params = self.resolve_params(name, raw)?;
+        let template = self.get(name).ok_or_else(|| TemplateError::UnknownTemplate(name.to_string()))?;
+        Ok(template.instantiate(&params, rng))
+    }
+}
+
+impl Default for TemplateRegistry {
+    fn default() -> Self {
+        Self::with_builtin_templates()
+    }
+}
+
+/// 模板实例化错误
+#[derive(Debug, Clone, PartialEq)]
+pub enum TemplateError {
+    UnknownTemplate(String),
+    UnknownParameter { template: String, parameter: String },
+    InvalidValue { parameter: String, value: String },
+    OutOfRange { parameter: String, value: u64, min: u64, max: u64 },
+}
+
+impl std::fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            TemplateError::UnknownTemplate(name) => write!(f, "Unknown template: {}", name),
+            TemplateError::UnknownParameter { template, parameter } => {
+                write!(f, "Template {} has no parameter '{}'", template, parameter)
+            }
+            TemplateError::InvalidValue { parameter, value } => {
+                write!(f, "Invalid value '{}' for parameter '{}'", value, parameter)
+            }
+            TemplateError::OutOfRange { parameter, value, min, max } => {
+                write!(f, "Parameter '{}' = {} outside [{}, {}]", parameter, value, min, max)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TemplateError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::Fr;
+    
+    type TestField = Fr;
+    
+    #[test]
+    fn test_square_root_circuit() {
+        let x = TestField::from(5u64);
+        let y = TestField::from(25u64);
+        
+        let circuit = CircuitTemplates::square_root_verification(x, y);
+        CircuitTester::test_circuit(&circuit).assert_passed();
+    }
+    
+    #[test]
+    fn test_circuit_test_report() {
+        let mut circuit = CustomCircuit::<TestField>::new("report".to_string());
+        let a = circuit.add_private_witness(TestField::from(3u64));
+        let b = circuit.add_delegator_witness(TestField::from(4u64));
+        let c = circuit.add_private_witness(TestField::from(7u64));
+        circuit.add_private_witness(TestField::from(0u64));
+        circuit.add_multiplication_constraint(a, b, c);
+        circuit.add_addition_constraint(a, b, c);
+
+        let report = CircuitTester::test_circuit(&circuit);
+        assert_eq!((report.num_constraints(), report.num_multiplications, report.num_additions), (2, 1, 1));
+        assert_eq!(
+            report.witness,
+            WitnessStats { private: 4, public: 0, delegator_only: 1, zero: 1, unused: 1 }
+        );
+        assert!(!report.passed());
+        assert!(report.is_violated(ConstraintKind::Multiplication, 0));
+        report.assert_violated_exactly(&[(ConstraintKind::Multiplication, 0)]);
+
+        let json = report.to_json();
+        assert!(json.starts_with("{\"circuit\": \"report\", \"passed\": false, \"constraints\": 2,"));
+        assert!(json.contains("\"violations\": [{\"kind\": \"multiplication\", \"index\": 0,"));
+        assert!(std::panic::catch_unwind(|| report.assert_passed()).is_err());
+    }
+
+    #[test]
+    fn test_custom_circuit_creation() {
+        let mut circuit = CustomCircuit::<TestField>::new("test".to_string());
+        
+        let a_idx = circuit.add_private_witness(TestField::from(3u64));
+        let b_idx = circuit.add_private_witness(TestField::from(4u64));
+        let c_idx = circuit.add_private_witness(TestField::from(12u64));
+        
+        circuit.add_multiplication_constraint(a_idx, b_idx, c_idx);
+        
+        assert!(circuit.verify_constraints());
+    }
+
+    #[test]
+    fn test_schnorr_verification_circuit() {
+        let mut rng = ark_std::test_rng();
+        let secret_key = JubjubScalar::rand(&mut rng);
+        let public_key = (EdwardsAffine::generator() * secret_key).into_affine();
+        let message = TestField::from(42u64);
+
+        let signature = SchnorrSignature::sign(secret_key, message, &mut rng);
+        assert!(signature.verify(&public_key, message));
+
+        let circuit = CircuitTemplates::schnorr_verification(public_key, message, &signature);
+        assert!(circuit.verify_constraints());
+        // 常量不占用公开输入，只有 P 和 m
+        assert_eq!(circuit.public_inputs, vec![public_key.x, public_key.y, message]);
+
+        // 签名与消息不匹配时约束不满足
+        assert!(!signature.verify(&public_key, TestField::from(43u64)));
+        let forged = CircuitTemplates::schnorr_verification(public_key, TestField::from(43u64), &signature);
+        assert!(!forged.verify_constraints());
+    }
+
+    #[test]
+    fn test_canonical_bits_reject_aliased_decomposition() {
+        let p = field_to_biguint(-TestField::from(1u64)) + 1u64;
+        let num_bits = TestField::MODULUS_BIT_SIZE as usize;
+        let decomposition = |value: &BigUint| {
+            let mut builder = TemplateBuilder::<TestField>::new();
+            let e = builder.witness(TestField::from(5u64));
+            let bits = builder.decompose(e, value, num_bits);
+            builder.enforce_canonical(&bits);
+            builder.build("canonical_bits")
+        };
+        assert!(decomposition(&BigUint::from(5u64)).check_witness().is_satisfied());
+        // 5 + p 仍在 255 位内，重组在域内也等于 5
+        let aliased = BigUint::from(5u64) + &p;
+        assert!(aliased.bits() <= num_bits as u64);
+        assert!(!decomposition(&aliased).check_witness().is_satisfied());
+
+        // 常量由常量 1 构造，篡改其中的见证会违反约束
+        let mut builder = TemplateBuilder::<TestField>::new();
+        let seven = builder.constant(TestField::from(7u64));
+        assert_eq!(builder.value(seven), TestField::from(7u64));
+        let mut circuit = builder.build("constant");
+        assert!(circuit.public_inputs.is_empty());
+        assert!(circuit.check_witness().is_satisfied());
+        circuit.private_witnesses[0] += TestField::from(1u64);
+        assert!(!circuit.check_witness().is_satisfied());
+    }
+
+    #[test]
+    fn test_modular_multiplication_beyond_native_field() {
+        // 300 位模数，超过 BLS12-381 标量域
+        let modulus = (BigUint::from(1u64) << 300) - 153u64;
+        let a = (BigUint::from(1u64) << 299) + 12345u64;
+        let b = (BigUint::from(1u64) << 298) + 67890u64;
+
+        let circuit = CircuitTemplates::modular_multiplication::<TestField>(&a, &b, &modulus);
+        assert!(circuit.verify_constraints());
+
+        // 篡改结果的最低 limb
+        let mut tampered = circuit.clone();
+        tampered.public_inputs[0] += TestField::from(1u64);
+        assert!(!tampered.verify_constraints());
+    }
+
+    #[test]
+    fn test_merkle_batch_update() {
+        let leaves: Vec<TestField> = (0..10u64).map(TestField::from).collect();
+        let mut tree = MerkleTree::new(4, &leaves);
+        let old_root = tree.root();
+        // 同一叶子被更新两次，第二次更新必须基于第一次的结果
+        let updates = [(3, TestField::from(100u64)), (12, TestField::from(7u64)), (3, TestField::from(101u64))];
+
+        let circuit = CircuitTemplates::merkle_batch_update(&mut tree, &updates);
+        assert!(circuit.verify_constraints());
+        assert_eq!(circuit.public_inputs[0], old_root);
+        assert_eq!(circuit.public_inputs[1], tree.root());
+
+        let mut expected = leaves.clone();
+        expected.resize(16, TestField::from(0u64));
+        expected[3] = TestField::from(101u64);
+        expected[12] = TestField::from(7u64);
+        assert_eq!(tree.root(), MerkleTree::new(4, &expected).root());
+
+        let mut tampered = circuit.clone();
+        tampered.public_inputs[1] += TestField::from(1u64);
+        assert!(!tampered.verify_constraints());
+    }
+
+    #[test]
+    fn test_template_registry() {
+        let registry = TemplateRegistry::default();
+        let mut rng = ark_std::test_rng();
+
+        for name in registry.names() {
+            let circuit = registry.instantiate(name, &HashMap::new(), &mut rng).unwrap();
+            assert!(circuit.verify_constraints(), "template {} with defaults", name);
+        }
+
+        let params: HashMap<String, String> =
+            [("depth".to_string(), "3".to_string()), ("updates".to_string(), "2".to_string())].into();
+        let circuit = registry.instantiate("merkle_batch_update", &params, &mut rng).unwrap();
+        assert!(circuit.verify_constraints());
+
+        let bad_depth: HashMap<String, String> = [("depth".to_string(), "64".to_string())].into();
+        assert!(matches!(
+            registry.instantiate("merkle_batch_update", &bad_depth, &mut rng),
+            Err(TemplateError::OutOfRange { .. })
+        ));
+        let unknown: HashMap<String, String> = [("rounds".to_string(), "3".to_string())].into();
+        assert!(matches!(
+            registry.instantiate("square_root", &unknown, &mut rng),
+            Err(TemplateError::UnknownParameter { .. })
+        ));
+        assert!(matches!(
+            registry.instantiate("sha256", &HashMap::new(), &mut rng),
+            Err(TemplateError::UnknownTemplate(_))
+        ));
+    }
+
+    #[test]
+    fn test_integer_division_circuit() {
+        let circuit = CircuitTemplates::integer_division::<TestField>(1000, 7, 12);
+        assert!(circuit.check_witness().is_satisfied());
+        assert_eq!(circuit.public_inputs[..2], [TestField::from(142u64), TestField::from(6u64)]);
+
+        // 商减一、余数加上除数: q·d + r 不变，但余数不再小于除数
+        // 私有见证依次为 dividend, divisor, q, r, slack
+        let mut forged = circuit.clone();
+        forged.public_inputs[0] -= TestField::from(1u64);
+        forged.public_inputs[1] += TestField::from(7u64);
+        forged.private_witnesses[2] -= TestField::from(1u64);
+        forged.private_witnesses[3] += TestField::from(7u64);
+        forged.private_witnesses[4] -= TestField::from(7u64);
+        assert!(!forged.check_witness().is_satisfied());
+    }
+
+    #[test]
+    fn test_scalar_multiplication_circuit() {
+        let base = EdwardsAffine::generator();
+        let circuit = CircuitTemplates::scalar_multiplication(base, TestField::from(45u64), 8);
+        assert!(circuit.check_witness().is_satisfied());
+        let expected = (base * JubjubScalar::from(45u64)).into_affine();
+        assert_eq!(circuit.public_inputs, vec![expected.x, expected.y]);
+
+        // 换成另一个标量的点时约束不满足
+        let mut forged = circuit.clone();
+        let other = (base * JubjubScalar::from(46u64)).into_affine();
+        forged.public_inputs = vec![other.x, other.y];
+        assert!(!forged.check_witness().is_satisfied());
+
+        // 标量超出 num_bits 位时比特分解无法重组
+        let overflow = CircuitTemplates::scalar_multiplication(base, TestField::from(300u64), 8);
+        assert!(!overflow.check_witness().is_satisfied());
+    }
+
+    #[test]
+    fn test_if_selects_branch_and_constrains_condition() {
+        for (bit, expected) in [(1u64, [7u64, 8]), (0, [9, 10])] {
+            let mut circuit = CustomCircuit::<TestField>::new("mux".to_string());
+            let cond = circuit.add_private_witness(TestField::from(bit));
+            let values: Vec<Variable> = (7u64..=10).map(|v| circuit.add_private_witness(TestField::from(v))).collect();
+            let outputs = circuit.when(cond).then(&values[..2]).otherwise(&values[2..]);
+            let single = circuit.select(cond, values[0], values[3]);
+            assert_eq!(circuit.variable(outputs[0]), Some(TestField::from(expected[0])));
+            assert_eq!(circuit.variable(outputs[1]), Some(TestField::from(expected[1])));
+            assert_eq!(circuit.variable(single), Some(TestField::from(if bit == 1 { 7u64 } else { 10 })));
+            assert!(circuit.check_witness().is_satisfied());
+        }
+
+        // 非布尔条件违反 c·c = c
+        let mut circuit = CustomCircuit::<TestField>::new("mux".to_string());
+        let cond = circuit.add_private_witness(TestField::from(2u64));
+        let a = circuit.add_private_witness(TestField::from(7u64));
+        let b = circuit.add_private_witness(TestField::from(9u64));
+        circuit.select(cond, a, b);
+        let report = circuit.check_witness();
+        assert_eq!(report.violations.len(), 1);
+        assert_eq!(report.violations[0].wires, [Variable::Witness(0); 3]);
+    }
+
+    #[test]
+    fn test_witness_report_lists_every_violation() {
+        // x² + y² = z，其中 z 错了 1，并且有一个约束引用了不存在的变量
+        let mut circuit = CustomCircuit::<TestField>::new("pythagorean".to_string());
+        let x = circuit.add_private_witness(TestField::from(3u64));
+        let x2 = circuit.add_private_witness(TestField::from(9u64));
+        let y = circuit.add_private_witness(TestField::from(4u64));
+        let y2 = circuit.add_private_witness(TestField::from(17u64));
+        let z = circuit.add_public_input(TestField::from(26u64));
+        circuit.add_multiplication_constraint(x, x, x2);
+        circuit.add_multiplication_constraint(y, y, y2);
+        circuit.add_addition_constraint(x2, y2, z);
+        circuit.add_addition_constraint(x, Variable::Witness(7), z);
+
+        let report = circuit.check_witness();
+        assert!(!report.is_satisfied());
+        assert_eq!(report.num_constraints, 4);
+        // y² 错了，而 x² + y² = 26 恰好成立：只有真正失败的约束被报告
+        assert_eq!(report.violations.len(), 2);
+        assert_eq!(report.violations[0].kind, ConstraintKind::Multiplication);
+        assert_eq!(report.violations[0].index, 1);
+        assert_eq!(
+            report.violations[0].reason,
+            ViolationReason::Mismatch {
+                inputs: [TestField::from(4u64); 2],
+                expected: TestField::from(16u64),
+                actual: TestField::from(17u64),
+            }
+        );
+        assert_eq!(report.violations[1].reason, ViolationReason::UnknownWire(Variable::Witness(7)));
+        let text = report.to_string();
+        assert!(text.contains("multiplication constraint #1: witness[2] * witness[2] = witness[3]"), "{}", text);
+        assert!(text.contains("witness[7] does not exist"), "{}", text);
+    }
+
+    #[test]
+    fn test_variable_numbering_matches_ark_relations() {
+        use ark_relations::r1cs::{ConstraintSystem, ConstraintSystemRef};
+
+        let mut circuit = CustomCircuit::<TestField>::new("numbering".to_string());
+        let x = circuit.add_private_witness(TestField::from(7u64));
+        let y = circuit.add_public_input(TestField::from(49u64));
+        let x2 = circuit.add_private_witness(TestField::from(49u64));
+        assert_eq!((x, y, x2), (Variable::Witness(0), Variable::Instance(1), Variable::Witness(1)));
+        assert_eq!(circuit.variable(Variable::ONE), Some(TestField::from(1u64)));
+        // 公开输入与见证交替添加时，约束仍然引用正确的变量
+        circuit.add_multiplication_constraint(x, x, y);
+        let y_plus_one = circuit.add_private_witness(TestField::from(50u64));
+        circuit.add_addition_constraint(y, Variable::ONE, y_plus_one);
+        assert!(circuit.check_witness().is_satisfied());
+
+        let cs: ConstraintSystemRef<TestField> = ConstraintSystem::new_ref();
+        let input = cs.new_input_variable(|| Ok(TestField::from(49u64))).unwrap();
+        let witness = cs.new_witness_variable(|| Ok(TestField::from(7u64))).unwrap();
+        assert_eq!(ark_relations::r1cs::Variable::from(y), input);
+        assert_eq!(ark_relations::r1cs::Variable::from(x), witness);
+        assert_eq!(ark_relations::r1cs::Variable::from(Variable::ONE), ark_relations::r1cs::Variable::One);
+
+        let num_instance = circuit.num_instance_variables();
+        for variable in [Variable::ONE, y, x, x2] {
+            assert_eq!(Variable::from_index(variable.index(num_instance), num_instance), variable);
+        }
+        assert_eq!(x.index(num_instance), 2);
     }
 }
@@ -0,0 +1,202 @@
+//! Per-session resource accounting
+//!
+//! A `UsageMeter` attributes the measurable costs of serving delegations —
+//! CPU time, bytes on the wire and consumed preprocessing material — to the
+//! session that caused them. Applying a `Tariff` to a session's usage gives
+//! an `Invoice`, which operators can export as JSON for their billing system.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::evaluation::CommunicationStats;
+use crate::mpc::{MaterialKind, PreprocessingLedger, SessionId};
+
+/// Resources consumed by one session
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResourceUsage {
+    pub cpu_time: Duration,
+    pub bytes_sent: usize,
+    pub bytes_received: usize,
+    pub triples_consumed: usize,
+    pub bits_consumed: usize,
+}
+
+/// Prices in the smallest unit of the operator's currency
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tariff {
+    pub currency: String,
+    pub per_cpu_second: u64,
+    /// Price per started KiB, sent and received combined
+    pub per_kib: u64,
+    pub per_triple: u64,
+    pub per_bit: u64,
+}
+
+/// One billed resource
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvoiceLine {
+    pub item: &'static str,
+    pub quantity: u64,
+    pub unit_price: u64,
+    pub amount: u64,
+}
+
+/// Machine-readable bill for one session
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Invoice {
+    pub session: SessionId,
+    pub currency: String,
+    pub usage: ResourceUsage,
+    pub lines: Vec<InvoiceLine>,
+    pub total: u64,
+}
+
+impl Invoice {
+    pub fn to_json(&self) -> String {
+        let lines: Vec<String> = self
+            .lines
+            .iter()
+            .map(|line| {
+                format!(
+                    "{{\"item\": \"{}\", \"quantity\": {}, \"unit_price\": {}, \"amount\": {}}}",
+                    line.item, line.quantity, line.unit_price, line.amount
+                )
+            })
+            .collect();
+        format!(
+            "{{\"session\": {}, \"currency\": \"{}\", \"cpu_ms\": {}, \"bytes_sent\": {}, \"bytes_received\": {}, \
+             \"triples_consumed\": {}, \"bits_consumed\": {}, \"lines\": [{}], \"total\": {}}}",
+            self.session,
+            self.currency.escape_default(),
+            self.usage.cpu_time.as_millis(),
+            self.usage.bytes_sent,
+            self.usage.bytes_received,
+            self.usage.triples_consumed,
+            self.usage.bits_consumed,
+            lines.join(", "),
+            self.total
+        )
+    }
+}
+
+/// Usage of every session served by a worker
+#[derive(Debug, Clone, Default)]
+pub struct UsageMeter {
+    sessions: HashMap<SessionId, ResourceUsage>,
+}
+
+impl UsageMeter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn usage(&self, session: SessionId) -> Option<&ResourceUsage> {
+        self.sessions.get(&session)
+    }
+
+    /// Sessions with recorded usage, in ascending order
+    pub fn sessions(&self) -> Vec<SessionId> {
+        let mut sessions: Vec<SessionId> = self.sessions.keys().copied().collect();
+        sessions.sort_unstable();
+        sessions
+    }
+
+    /// Run `work` and charge its duration to `session`
+    pub fn measure<T>(&mut self, session: SessionId, work: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = work();
+        self.record_cpu(session, start.elapsed());
+        result
+    }
+
+    pub fn record_cpu(&mut self, session: SessionId, elapsed: Duration) {
+        self.entry(session).cpu_time += elapsed;
+    }
+
+    pub fn record_traffic(&mut self, session: SessionId, sent: usize, received: usize) {
+        let usage = self.entry(session);
+        usage.bytes_sent += sent;
+        usage.bytes_received += received;
+    }
+
+    /// Charge the bytes sent in every recorded round to `session`
+    pub fn record_communication(&mut self, session: SessionId, stats: &CommunicationStats) {
+        self.record_traffic(session, stats.total_bytes(), 0);
+    }
+
+    /// Take the material counts consumed by `session` from the ledger
+    ///
+    /// The ledger is authoritative, so counts are replaced rather than added.
+    pub fn record_preprocessing(&mut self, session: SessionId, ledger: &PreprocessingLedger) {
+        let usage = self.entry(session);
+        usage.triples_consumed = ledger.consumed_count(session, MaterialKind::Triple);
+        usage.bits_consumed = ledger.consumed_count(session, MaterialKind::Bit);
+    }
+
+    /// Bill the usage of `session`
+    pub fn invoice(&self, session: SessionId, tariff: &Tariff) -> Option<Invoice> {
+        let usage = self.sessions.get(&session)?.clone();
+        let bytes = (usage.bytes_sent + usage.bytes_received) as u64;
+        let line = |item, quantity: u64, unit_price: u64| InvoiceLine {
+            item,
+            quantity,
+            unit_price,
+            amount: quantity.saturating_mul(unit_price),
+        };
+        let lines = vec![
+            // CPU is billed per started second
+            line("cpu_seconds", usage.cpu_time.as_nanos().div_ceil(1_000_000_000) as u64, tariff.per_cpu_second),
+            line("kib_transferred", bytes.div_ceil(1024), tariff.per_kib),
+            line("triples", usage.triples_consumed as u64, tariff.per_triple),
+            line("random_bits", usage.bits_consumed as u64, tariff.per_bit),
+        ];
+        let total = lines.iter().fold(0u64, |sum, line| sum.saturating_add(line.amount));
+        Some(Invoice { session, currency: tariff.currency.clone(), usage, lines, total })
+    }
+
+    fn entry(&mut self, session: SessionId) -> &mut ResourceUsage {
+        self.sessions.entry(session).or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mpc::{PreprocessingStore, ShamirSecretSharing};
+    use ark_bls12_381::Fr;
+    use ark_std::test_rng;
+
+    type TestField = Fr;
+
+    #[test]
+    fn test_invoice_attributes_usage_to_sessions() {
+        let mut rng = test_rng();
+        let mut store = PreprocessingStore::<TestField, ShamirSecretSharing<TestField>>::generate(4, 2, 2, 3, &mut rng);
+        store.allocate(1, 3, 1).unwrap();
+        store.allocate(2, 1, 1).unwrap();
+        store.next_triple(1).unwrap();
+        store.next_triple(1).unwrap();
+        store.next_bit(1).unwrap();
+        store.next_triple(2).unwrap();
+
+        let mut meter = UsageMeter::new();
+        let value = meter.measure(1, || 6 * 7);
+        assert_eq!(value, 42);
+        meter.record_cpu(1, Duration::from_millis(1500));
+        meter.record_traffic(1, 2048, 1);
+        meter.record_preprocessing(1, store.ledger());
+        meter.record_preprocessing(2, store.ledger());
+        assert_eq!(meter.sessions(), vec![1, 2]);
+
+        let tariff = Tariff { currency: "EUR".to_string(), per_cpu_second: 100, per_kib: 2, per_triple: 5, per_bit: 1 };
+        let invoice = meter.invoice(1, &tariff).unwrap();
+        let amounts: Vec<u64> = invoice.lines.iter().map(|line| line.amount).collect();
+        // 1.5 s plus the measured closure is two started seconds; 2049 bytes is three KiB
+        assert_eq!(amounts, vec![200, 6, 10, 1]);
+        assert_eq!(invoice.total, 217);
+        assert!(invoice.to_json().contains("\"total\": 217"));
+
+        assert_eq!(meter.invoice(2, &tariff).unwrap().total, 5);
+        assert!(meter.invoice(3, &tariff).is_none());
+    }
+}
@@ -0,0 +1,317 @@
+//! Cost estimation before delegation
+//!
+//! `CostModel` predicts prover time, communication rounds, bandwidth and
+//! proof size of delegating a circuit, for each proving backend and operation
+//! mode, from per-gate cost annotations alone. Comparing the estimate with
+//! `local_prover_time` answers whether outsourcing is worth it for a given
+//! circuit before any preprocessing or sharing is done.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use ark_ff::PrimeField;
+
+use crate::custom_circuits::{CustomCircuit, Variable};
+
+/// Proving backend a delegation runs on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BackendKind {
+    Piop,
+    Groth16,
+}
+
+impl BackendKind {
+    /// Every supported backend, for sweeping benchmarks
+    pub const ALL: [BackendKind; 2] = [BackendKind::Piop, BackendKind::Groth16];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            BackendKind::Piop => "piop",
+            BackendKind::Groth16 => "groth16",
+        }
+    }
+}
+
+impl std::str::FromStr for BackendKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        BackendKind::ALL
+            .into_iter()
+            .find(|kind| kind.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("unknown backend '{}'", s))
+    }
+}
+
+/// Operation mode of the workers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ModeKind {
+    /// Workers exchange data only through the delegator
+    Isolation,
+    /// Workers communicate with each other directly
+    Collaboration,
+}
+
+impl ModeKind {
+    pub const ALL: [ModeKind; 2] = [ModeKind::Isolation, ModeKind::Collaboration];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ModeKind::Isolation => "isolation",
+            ModeKind::Collaboration => "collaboration",
+        }
+    }
+}
+
+impl std::str::FromStr for ModeKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ModeKind::ALL
+            .into_iter()
+            .find(|kind| kind.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("unknown mode '{}'", s))
+    }
+}
+
+/// Cost annotation of one gate type, per party
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GateCost {
+    /// Local computation in nanoseconds
+    pub compute_ns: f64,
+    /// Field elements each party sends
+    pub elements_sent: usize,
+    /// Whether the gate needs a communication round
+    pub interactive: bool,
+}
+
+/// Gate counts and depth of a circuit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CircuitShape {
+    pub multiplications: usize,
+    pub additions: usize,
+    pub witnesses: usize,
+    pub public_inputs: usize,
+    /// Longest chain of dependent multiplications
+    pub multiplicative_depth: usize,
+}
+
+impl CircuitShape {
+    pub fn of<F: PrimeField>(circuit: &CustomCircuit<F>) -> Self {
+        // Additions and multiplications are listed separately, so relax until
+        // the depths stop changing; circuits listed in evaluation order
+        // settle after two passes
+        let mut depth: HashMap<Variable, usize> = HashMap::new();
+        let depth_of = |depth: &HashMap<Variable, usize>, wire: Variable| depth.get(&wire).copied().unwrap_or(0);
+        let gates = circuit
+            .addition_constraints
+            .iter()
+            .map(|&gate| (gate, 0))
+            .chain(circuit.multiplication_constraints.iter().map(|&gate| (gate, 1)));
+        let gates: Vec<_> = gates.collect();
+        for _ in 0..=gates.len() {
+            let mut changed = false;
+            for &((a, b, c), cost) in &gates {
+                let d = depth_of(&depth, a).max(depth_of(&depth, b)) + cost;
+                if d > depth_of(&depth, c) {
+                    depth.insert(c, d);
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+        let multiplicative_depth = depth.values().copied().max().unwrap_or(0);
+        Self {
+            multiplications: circuit.multiplication_constraints.len(),
+            additions: circuit.addition_constraints.len(),
+            witnesses: circuit.private_witnesses.len(),
+            public_inputs: circuit.public_inputs.len(),
+            multiplicative_depth,
+        }
+    }
+
+    pub(crate) fn constraints(&self) -> usize {
+        (self.multiplications + self.additions).max(1)
+    }
+}
+
+/// Predicted cost of one delegation
+#[derive(Debug, Clone, PartialEq)]
+pub struct CostEstimate {
+    pub backend: BackendKind,
+    pub mode: ModeKind,
+    /// Wall-clock time until the delegator holds a proof
+    pub prover_time: Duration,
+    /// Time for the delegator to prove the circuit alone
+    pub local_prover_time: Duration,
+    pub rounds: usize,
+    /// Total bytes sent by all parties
+    pub bandwidth_bytes: usize,
+    pub proof_size_bytes: usize,
+}
+
+impl CostEstimate {
+    /// Whether delegating is predicted to be faster than proving locally
+    pub fn outsourcing_worthwhile(&self) -> bool {
+        self.prover_time < self.local_prover_time
+    }
+}
+
+/// Parameters of the cost prediction
+#[derive(Debug, Clone, PartialEq)]
+pub struct CostModel {
+    pub num_parties: usize,
+    pub multiplication: GateCost,
+    pub addition: GateCost,
+    /// Group operations per point in a multi-scalar multiplication
+    pub msm_ns_per_point: f64,
+    /// FFT cost per element per layer
+    pub fft_ns_per_element: f64,
+    pub round_latency: Duration,
+    /// How much slower the delegator's machine is than a worker's
+    pub delegator_slowdown: f64,
+    /// CPU slowdown of each worker against a reference core; empty if all
+    /// run at full speed. Rounds are synchronized, so the slowest one sets
+    /// the pace.
+    pub worker_slowdowns: Vec<f64>,
+    pub field_bytes: usize,
+    pub g1_bytes: usize,
+    pub g2_bytes: usize,
+}
+
+impl CostModel {
+    /// Defaults calibrated for BLS12-381 on a commodity core
+    pub fn new(num_parties: usize) -> Self {
+        Self {
+            num_parties,
+            multiplication: GateCost { compute_ns: 250.0, elements_sent: 2, interactive: true },
+            addition: GateCost { compute_ns: 20.0, elements_sent: 0, interactive: false },
+            msm_ns_per_point: 25_000.0,
+            fft_ns_per_element: 150.0,
+            round_latency: Duration::from_millis(10),
+            delegator_slowdown: 8.0,
+            worker_slowdowns: Vec::new(),
+            field_bytes: 32,
+            g1_bytes: 48,
+            g2_bytes: 96,
+        }
+    }
+
+    /// Estimates for every backend and mode
+    pub fn estimate<F: PrimeField>(&self, circuit: &CustomCircuit<F>) -> Vec<CostEstimate> {
+        let shape = CircuitShape::of(circuit);
+        BackendKind::ALL
+            .into_iter()
+            .flat_map(|backend| ModeKind::ALL.into_iter().map(move |mode| (backend, mode)))
+            .map(|(backend, mode)| self.estimate_shape(&shape, backend, mode))
+            .collect()
+    }
+
+    /// Estimate for a single backend and mode
+    pub fn estimate_shape(&self, shape: &CircuitShape, backend: BackendKind, mode: ModeKind) -> CostEstimate {
+        let n = self.num_parties.max(1);
+        let proving_ns = self.backend_ns(shape, backend);
+
+        let gate_ns = shape.multiplications as f64 * self.multiplication.compute_ns
+            + shape.additions as f64 * self.addition.compute_ns;
+        let elements_per_party = shape.multiplications * self.multiplication.elements_sent
+            + shape.additions * self.addition.elements_sent;
+        let interactive_depth = |cost: &GateCost| if cost.interactive { shape.multiplicative_depth } else { 0 };
+        let gate_rounds = interactive_depth(&self.multiplication).max(interactive_depth(&self.addition));
+
+        // Isolated workers relay through the delegator, doubling the hops
+        let (rounds, elements) = match mode {
+            ModeKind::Isolation => (2 * gate_rounds, 2 * n * elements_per_party),
+            ModeKind::Collaboration => (gate_rounds, n * (n - 1).max(1) * elements_per_party),
+        };
+        // Sharing the witness and returning the proof
+        let rounds = rounds + 2;
+        let sharing_bytes = n * (shape.witnesses + shape.public_inputs) * self.field_bytes;
+        let proof_size_bytes = self.proof_size(shape, backend);
+
+        // Workers prove in parallel; each pays the MPC overhead on its share
+        // and waits for the slowest one at every synchronization point
+        let worker_ns = (proving_ns + gate_ns) * (1.0 + 1.0 / n as f64) * self.straggler_factor();
+        let prover_time = Duration::from_nanos(worker_ns as u64) + self.round_latency * rounds as u32;
+        let local_prover_time = Duration::from_nanos(((proving_ns + gate_ns) * self.delegator_slowdown) as u64);
+
+        CostEstimate {
+            backend,
+            mode,
+            prover_time,
+            local_prover_time,
+            rounds,
+            bandwidth_bytes: elements * self.field_bytes + sharing_bytes + n * proof_size_bytes,
+            proof_size_bytes,
+        }
+    }
+
+    fn backend_ns(&self, shape: &CircuitShape, backend: BackendKind) -> f64 {
+        let size = shape.constraints().next_power_of_two() as f64;
+        let fft = size * size.log2().max(1.0) * self.fft_ns_per_element;
+        match backend {
+            // One witness commitment and a sumcheck over the constraints
+            BackendKind::Piop => size * self.msm_ns_per_point + 2.0 * fft,
+            // A, B (in G1 and G2) and C queries plus the quotient computation
+            BackendKind::Groth16 => {
+                let variables = (shape.witnesses + shape.public_inputs + shape.constraints()) as f64;
+                4.0 * variables * self.msm_ns_per_point + size * self.msm_ns_per_point + 7.0 * fft
+            }
+        }
+    }
+
+    fn proof_size(&self, shape: &CircuitShape, backend: BackendKind) -> usize {
+        match backend {
+            BackendKind::Piop => {
+                let rounds = shape.constraints().next_power_of_two().trailing_zeros().max(1) as usize;
+                2 * self.g1_bytes + (3 * rounds + 2) * self.field_bytes
+            }
+            BackendKind::Groth16 => 2 * self.g1_bytes + self.g2_bytes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::custom_circuits::CircuitTemplates;
+    use ark_bls12_381::Fr;
+
+    type TestField = Fr;
+
+    #[test]
+    fn test_estimates_scale_with_circuit() {
+        let model = CostModel::new(3);
+        let small = CircuitTemplates::repeated_squaring(TestField::from(3u64), 4);
+        let large = CircuitTemplates::repeated_squaring(TestField::from(3u64), 4096);
+        assert_eq!(CircuitShape::of(&large).multiplicative_depth, 4096);
+
+        let small_estimates = model.estimate(&small);
+        let large_estimates = model.estimate(&large);
+        assert_eq!(small_estimates.len(), 4);
+        for (small, large) in small_estimates.iter().zip(&large_estimates) {
+            assert!(large.prover_time > small.prover_time);
+            assert!(large.rounds > small.rounds);
+            assert!(large.bandwidth_bytes > small.bandwidth_bytes);
+        }
+
+        // Groth16 proofs are constant size; isolation relays every round
+        let by_kind = |estimates: &[CostEstimate], backend, mode| {
+            estimates.iter().find(|e| e.backend == backend && e.mode == mode).unwrap().clone()
+        };
+        let small_groth = by_kind(&small_estimates, BackendKind::Groth16, ModeKind::Collaboration);
+        let large_groth = by_kind(&large_estimates, BackendKind::Groth16, ModeKind::Collaboration);
+        assert_eq!(small_groth.proof_size_bytes, large_groth.proof_size_bytes);
+        let isolated = by_kind(&large_estimates, BackendKind::Piop, ModeKind::Isolation);
+        let collaborative = by_kind(&large_estimates, BackendKind::Piop, ModeKind::Collaboration);
+        assert!(isolated.rounds > collaborative.rounds);
+
+        // A deep circuit is latency bound: worth outsourcing only on a fast network
+        assert!(!collaborative.outsourcing_worthwhile());
+        let lan = CostModel { round_latency: Duration::from_micros(10), ..model.clone() };
+        let shape = CircuitShape::of(&large);
+        assert!(lan.estimate_shape(&shape, BackendKind::Piop, ModeKind::Collaboration).outsourcing_worthwhile());
+    }
+}
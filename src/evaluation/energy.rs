@@ -0,0 +1,246 @@
+//! CPU utilization and energy of benchmark runs
+//!
+//! On a battery-powered delegator the question is often not how long a
+//! proof takes but how much charge it costs. `EnergySample` brackets a
+//! workload and reads the process CPU time and, where Linux exposes them
+//! and they are readable, the RAPL package energy counters under
+//! `/sys/class/powercap`. Without RAPL the energy is estimated from CPU
+//! and wall time with an `EnergyModel`, which also prices a
+//! `CostEstimate::local_prover_time` for comparing outsourcing against
+//! proving locally.
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::evaluation::process_cpu_time;
+
+const POWERCAP_DIR: &str = "/sys/class/powercap";
+
+/// Power draw used to estimate energy from CPU and wall time
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EnergyModel {
+    /// Drawn for the whole run, busy or not
+    pub idle_watts: f64,
+    /// Added per core kept busy
+    pub active_watts_per_core: f64,
+}
+
+impl EnergyModel {
+    /// Figures for a laptop-class processor
+    pub fn new() -> Self {
+        Self { idle_watts: 2.0, active_watts_per_core: 4.5 }
+    }
+
+    /// Estimated joules of a run that kept cores busy for `cpu` in total
+    /// over `wall`
+    pub fn joules(&self, cpu: Duration, wall: Duration) -> f64 {
+        self.idle_watts * wall.as_secs_f64() + self.active_watts_per_core * cpu.as_secs_f64()
+    }
+}
+
+impl Default for EnergyModel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Where a run's energy figure comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnergySource {
+    /// RAPL package counters, covering everything running on the package
+    Rapl,
+    /// `EnergyModel` applied to the process CPU time
+    Estimated,
+}
+
+impl EnergySource {
+    pub fn name(self) -> &'static str {
+        match self {
+            EnergySource::Rapl => "rapl",
+            EnergySource::Estimated => "estimated",
+        }
+    }
+}
+
+/// CPU and energy use of one run
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EnergyUsage {
+    pub wall: Duration,
+    /// CPU time of all threads of the process; `None` where the platform
+    /// does not report it
+    pub cpu: Option<Duration>,
+    pub joules: f64,
+    pub source: EnergySource,
+}
+
+impl EnergyUsage {
+    /// Average number of cores kept busy
+    pub fn cpu_utilization(&self) -> Option<f64> {
+        let wall = self.wall.as_secs_f64();
+        self.cpu.filter(|_| wall > 0.0).map(|cpu| cpu.as_secs_f64() / wall)
+    }
+
+    /// Average power over the run
+    pub fn watts(&self) -> f64 {
+        let wall = self.wall.as_secs_f64();
+        if wall > 0.0 {
+            self.joules / wall
+        } else {
+            0.0
+        }
+    }
+
+    /// Usage per run of `runs` runs measured together
+    pub fn per_run(self, runs: usize) -> Self {
+        let runs = runs.max(1);
+        Self {
+            wall: self.wall / runs as u32,
+            cpu: self.cpu.map(|cpu| cpu / runs as u32),
+            joules: self.joules / runs as f64,
+            source: self.source,
+        }
+    }
+}
+
+/// RAPL package energy counters
+#[derive(Debug, Clone)]
+pub struct RaplCounters {
+    /// `energy_uj` file and wrap-around range of each package
+    packages: Vec<(PathBuf, u64)>,
+}
+
+impl RaplCounters {
+    /// The readable package counters of this machine, if any
+    pub fn open() -> Option<Self> {
+        Self::open_in(Path::new(POWERCAP_DIR))
+    }
+
+    fn open_in(dir: &Path) -> Option<Self> {
+        let mut packages = Vec::new();
+        for entry in std::fs::read_dir(dir).ok()?.flatten() {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            // Packages are intel-rapl:N; their subzones intel-rapl:N:M are
+            // already counted in them
+            if !name.starts_with("intel-rapl:") || name.matches(':').count() != 1 {
+                continue;
+            }
+            let counter = entry.path().join("energy_uj");
+            let range = read_u64(&entry.path().join("max_energy_range_uj")).unwrap_or(u64::MAX);
+            if read_u64(&counter).is_some() {
+                packages.push((counter, range));
+            }
+        }
+        packages.sort();
+        (!packages.is_empty()).then_some(Self { packages })
+    }
+
+    /// Current counter values in microjoules
+    fn read(&self) -> Option<Vec<u64>> {
+        self.packages.iter().map(|(counter, _)| read_u64(counter)).collect()
+    }
+
+    /// Joules between two readings, allowing each counter to wrap once
+    fn joules_between(&self, start: &[u64], end: &[u64]) -> f64 {
+        let microjoules: u64 = self
+            .packages
+            .iter()
+            .zip(start.iter().zip(end))
+            .map(|((_, range), (&start, &end))| if end >= start { end - start } else { range - start + end })
+            .sum();
+        microjoules as f64 / 1e6
+    }
+}
+
+fn read_u64(path: &Path) -> Option<u64> {
+    std::fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+/// Readings taken when a measured workload starts
+#[derive(Debug)]
+pub struct EnergySample {
+    model: EnergyModel,
+    wall: Instant,
+    cpu: Option<Duration>,
+    rapl: Option<(RaplCounters, Vec<u64>)>,
+}
+
+impl EnergySample {
+    pub fn start(model: EnergyModel) -> Self {
+        let rapl = RaplCounters::open().and_then(|counters| counters.read().map(|start| (counters, start)));
+        Self { model, wall: Instant::now(), cpu: process_cpu_time(), rapl }
+    }
+
+    pub fn finish(self) -> EnergyUsage {
+        let wall = self.wall.elapsed();
+        let cpu = self.cpu.zip(process_cpu_time()).map(|(start, end)| end.saturating_sub(start));
+        let measured = self
+            .rapl
+            .and_then(|(counters, start)| counters.read().map(|end| counters.joules_between(&start, &end)));
+        match measured {
+            Some(joules) => EnergyUsage { wall, cpu, joules, source: EnergySource::Rapl },
+            None => EnergyUsage {
+                wall,
+                cpu,
+                // Without a CPU clock, assume one core stayed busy throughout
+                joules: self.model.joules(cpu.unwrap_or(wall), wall),
+                source: EnergySource::Estimated,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_energy_of_a_run() {
+        let model = EnergyModel::new();
+        let joules = model.joules(Duration::from_secs(4), Duration::from_secs(2));
+        assert!((joules - (2.0 * 2.0 + 4.5 * 4.0)).abs() < 1e-9);
+
+        let usage = EnergyUsage {
+            wall: Duration::from_secs(2),
+            cpu: Some(Duration::from_secs(4)),
+            joules,
+            source: EnergySource::Estimated,
+        };
+        assert_eq!(usage.cpu_utilization(), Some(2.0));
+        assert!((usage.watts() - 11.0).abs() < 1e-9);
+        let per_run = usage.per_run(4);
+        assert_eq!(per_run.wall, Duration::from_millis(500));
+        assert!((per_run.joules - joules / 4.0).abs() < 1e-9);
+
+        // Package zones are summed, subzones skipped, and a wrapped counter counted once
+        let dir = std::env::temp_dir().join(format!("eos-powercap-{}", std::process::id()));
+        for (zone, energy, range) in [("intel-rapl:0", "1000", "5000"), ("intel-rapl:1", "200", "5000"), ("intel-rapl:0:0", "7", "5000")] {
+            std::fs::create_dir_all(dir.join(zone)).unwrap();
+            std::fs::write(dir.join(zone).join("energy_uj"), energy).unwrap();
+            std::fs::write(dir.join(zone).join("max_energy_range_uj"), range).unwrap();
+        }
+        let counters = RaplCounters::open_in(&dir).unwrap();
+        let start = counters.read().unwrap();
+        assert_eq!(start, vec![1000, 200]);
+        std::fs::write(dir.join("intel-rapl:0").join("energy_uj"), "4000").unwrap();
+        std::fs::write(dir.join("intel-rapl:1").join("energy_uj"), "100").unwrap();
+        let end = counters.read().unwrap();
+        assert!((counters.joules_between(&start, &end) - (3000 + 4900) as f64 / 1e6).abs() < 1e-12);
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(RaplCounters::open_in(&dir).is_none());
+
+        // A sampled run reports either a measured or an estimated figure
+        let sample = EnergySample::start(model);
+        let start = Instant::now();
+        let mut x = 1u64;
+        while start.elapsed() < Duration::from_millis(20) {
+            x = std::hint::black_box(x.wrapping_mul(6364136223846793005).wrapping_add(1));
+        }
+        let usage = sample.finish();
+        assert!(usage.wall >= Duration::from_millis(20));
+        assert!(usage.joules >= 0.0);
+        if usage.source == EnergySource::Estimated {
+            assert!(usage.joules > 0.0);
+        }
+    }
+}
@@ -0,0 +1,238 @@
+//! Splitting proof generation between the delegator and the workers
+//!
+//! Proving a delegation runs five stages. Two of them are linear and cheap:
+//! adding the witness contributions into one polynomial and dividing out
+//! the opening point. The other three are where the time goes: the MSM
+//! committing to the witness, the MSM committing to the quotient, and the
+//! sumcheck. A `HybridSplit` names the stages the delegator runs on its own
+//! machine; the rest are outsourced. The protocol records every stage as a
+//! nested stage timing under `proving/<stage>@delegator` or
+//! `proving/<stage>@workers`, which `HybridReport::from_metrics` reads back,
+//! and `CostModel::estimate_hybrid` predicts the effect of a split before
+//! running it.
+
+use std::collections::{BTreeSet, HashMap};
+use std::time::Duration;
+
+use crate::evaluation::{CircuitShape, CostModel, PerformanceMetrics};
+
+/// A stage of proof generation, in the order they run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ProvingStage {
+    /// Sum the witness contributions into one polynomial
+    WitnessPolynomial,
+    /// Commit to every contribution
+    Commitment,
+    /// Divide each contribution by (X - z) at the opening point z
+    Quotient,
+    /// Commit to the quotients
+    OpeningProof,
+    /// Sumcheck of the PIOP
+    Sumcheck,
+}
+
+impl ProvingStage {
+    pub const ALL: [ProvingStage; 5] = [
+        ProvingStage::WitnessPolynomial,
+        ProvingStage::Commitment,
+        ProvingStage::Quotient,
+        ProvingStage::OpeningProof,
+        ProvingStage::Sumcheck,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ProvingStage::WitnessPolynomial => "witness_polynomial",
+            ProvingStage::Commitment => "commitment",
+            ProvingStage::Quotient => "quotient",
+            ProvingStage::OpeningProof => "opening_proof",
+            ProvingStage::Sumcheck => "sumcheck",
+        }
+    }
+
+    /// Whether the stage is a linear pass over the witness
+    pub fn is_linear(self) -> bool {
+        matches!(self, ProvingStage::WitnessPolynomial | ProvingStage::Quotient)
+    }
+
+    /// Metrics label the protocol records the stage under
+    pub fn metric_label(self, local: bool) -> String {
+        format!("proving/{}@{}", self.name(), if local { "delegator" } else { "workers" })
+    }
+}
+
+/// Stages the delegator runs locally; everything else is outsourced
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct HybridSplit {
+    local: BTreeSet<ProvingStage>,
+}
+
+impl HybridSplit {
+    /// Outsource every stage
+    pub fn outsourced() -> Self {
+        Self::default()
+    }
+
+    /// Keep the linear stages on the delegator, the delegator-assisted design of the paper
+    pub fn linear() -> Self {
+        Self { local: ProvingStage::ALL.into_iter().filter(|stage| stage.is_linear()).collect() }
+    }
+
+    pub fn with_local(mut self, stage: ProvingStage) -> Self {
+        self.local.insert(stage);
+        self
+    }
+
+    pub fn is_local(&self, stage: ProvingStage) -> bool {
+        self.local.contains(&stage)
+    }
+
+    /// Hand-offs between the delegator and the workers while proving
+    pub fn handoffs(&self) -> usize {
+        ProvingStage::ALL.windows(2).filter(|pair| self.is_local(pair[0]) != self.is_local(pair[1])).count()
+    }
+}
+
+/// Time one stage took and where it ran
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StageTiming {
+    pub stage: ProvingStage,
+    pub local: bool,
+    pub elapsed: Duration,
+}
+
+/// Measured proving time per stage, split by who ran it
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct HybridReport {
+    /// One entry per stage and side that recorded any time, in stage order
+    pub stages: Vec<StageTiming>,
+}
+
+impl HybridReport {
+    pub fn from_metrics(metrics: &PerformanceMetrics) -> Self {
+        Self::from_timings(&metrics.stage_timings)
+    }
+
+    pub fn from_timings(timings: &HashMap<String, Duration>) -> Self {
+        let stages = ProvingStage::ALL
+            .into_iter()
+            .flat_map(|stage| [true, false].map(|local| (stage, local)))
+            .filter_map(|(stage, local)| {
+                let elapsed = *timings.get(&stage.metric_label(local))?;
+                Some(StageTiming { stage, local, elapsed })
+            })
+            .collect();
+        Self { stages }
+    }
+
+    /// Proving time spent on the delegator
+    pub fn local_time(&self) -> Duration {
+        self.stages.iter().filter(|timing| timing.local).map(|timing| timing.elapsed).sum()
+    }
+
+    /// Proving time spent by the workers
+    pub fn outsourced_time(&self) -> Duration {
+        self.stages.iter().filter(|timing| !timing.local).map(|timing| timing.elapsed).sum()
+    }
+
+    pub fn to_json(&self) -> String {
+        let stages: Vec<String> = self
+            .stages
+            .iter()
+            .map(|timing| {
+                format!(
+                    "{{\"stage\": \"{}\", \"side\": \"{}\", \"elapsed_us\": {}}}",
+                    timing.stage.name(),
+                    if timing.local { "delegator" } else { "workers" },
+                    timing.elapsed.as_micros()
+                )
+            })
+            .collect();
+        format!(
+            "{{\"local_us\": {}, \"outsourced_us\": {}, \"stages\": [{}]}}",
+            self.local_time().as_micros(),
+            self.outsourced_time().as_micros(),
+            stages.join(", ")
+        )
+    }
+}
+
+/// Predicted proving time under a split
+#[derive(Debug, Clone, PartialEq)]
+pub struct HybridEstimate {
+    pub delegator_time: Duration,
+    pub worker_time: Duration,
+    /// Round latency paid at every hand-off between the two sides
+    pub handoff_time: Duration,
+}
+
+impl HybridEstimate {
+    /// Stages run one after another, so the times add up
+    pub fn total(&self) -> Duration {
+        self.delegator_time + self.worker_time + self.handoff_time
+    }
+}
+
+impl CostModel {
+    /// Predicted PIOP proving time with `split`'s stages on the delegator
+    pub fn estimate_hybrid(&self, shape: &CircuitShape, split: &HybridSplit) -> HybridEstimate {
+        let n = self.num_parties.max(1) as f64;
+        let size = shape.constraints().next_power_of_two() as f64;
+        let (mut delegator_ns, mut worker_ns) = (0.0, 0.0);
+        for stage in ProvingStage::ALL {
+            let ns = match stage {
+                ProvingStage::WitnessPolynomial | ProvingStage::Quotient => size * self.addition.compute_ns,
+                ProvingStage::Commitment | ProvingStage::OpeningProof => size * self.msm_ns_per_point,
+                ProvingStage::Sumcheck => 2.0 * size * size.log2().max(1.0) * self.fft_ns_per_element,
+            };
+            if split.is_local(stage) {
+                delegator_ns += ns * self.delegator_slowdown;
+            } else {
+                worker_ns += ns * (1.0 + 1.0 / n);
+            }
+        }
+        HybridEstimate {
+            delegator_time: Duration::from_nanos(delegator_ns as u64),
+            worker_time: Duration::from_nanos(worker_ns as u64),
+            handoff_time: self.round_latency * split.handoffs() as u32,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::custom_circuits::CircuitTemplates;
+    use ark_bls12_381::Fr;
+
+    type TestField = Fr;
+
+    #[test]
+    fn test_hybrid_split_estimate_and_report() {
+        let split = HybridSplit::linear();
+        assert!(split.is_local(ProvingStage::Quotient) && !split.is_local(ProvingStage::Commitment));
+        // witness_polynomial | commitment | quotient | opening_proof
+        assert_eq!(split.handoffs(), 3);
+        assert_eq!(HybridSplit::outsourced().handoffs(), 0);
+
+        let shape = CircuitShape::of(&CircuitTemplates::repeated_squaring(TestField::from(3u64), 1024));
+        let model = CostModel::new(3);
+        let outsourced = model.estimate_hybrid(&shape, &HybridSplit::outsourced());
+        let hybrid = model.estimate_hybrid(&shape, &split);
+        assert_eq!(outsourced.delegator_time, Duration::ZERO);
+        assert!(hybrid.worker_time < outsourced.worker_time);
+        // Moving an MSM to the slow delegator costs far more than it saves
+        let msm_local = model.estimate_hybrid(&shape, &split.clone().with_local(ProvingStage::Commitment));
+        assert!(msm_local.total() > hybrid.total());
+
+        let mut metrics = PerformanceMetrics::new();
+        metrics.accumulate_stage(&ProvingStage::Quotient.metric_label(true), Duration::from_micros(30));
+        metrics.accumulate_stage(&ProvingStage::Commitment.metric_label(false), Duration::from_micros(500));
+        metrics.accumulate_stage("trace_binding", Duration::from_millis(1));
+        let report = HybridReport::from_metrics(&metrics);
+        assert_eq!(report.stages.len(), 2);
+        assert_eq!(report.stages[0].stage, ProvingStage::Commitment);
+        assert_eq!((report.local_time(), report.outsourced_time()), (Duration::from_micros(30), Duration::from_micros(500)));
+        assert!(report.to_json().starts_with("{\"local_us\": 30, \"outsourced_us\": 500,"));
+    }
+}
@@ -0,0 +1,301 @@
+//! Micro-benchmarks of the protocol's primitives
+//!
+//! `CostModel` predicts whole delegations from a handful of per-primitive
+//! constants. `Microsuite` measures those constants on the current machine:
+//! sharing and reconstructing a secret, one party's work in an addition and
+//! a multiplication gate, a KZG commitment at each configured degree, and one
+//! sumcheck round. Every primitive has a stable identifier (see
+//! `PrimitiveId::id`), so results from different machines and runs can be
+//! compared key by key, and `CostModel::calibrated` turns a `PrimitiveCosts`
+//! table into a model for this machine.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use ark_ec::pairing::Pairing;
+use ark_ff::{Field, PrimeField, UniformRand};
+use ark_poly::{univariate::DensePolynomial, DenseUVPolynomial};
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+
+use crate::circuit::KZGCommitmentScheme;
+use crate::evaluation::{BenchmarkCase, BenchmarkSuite, CostModel, GateCost, SampleStatistics};
+use crate::mpc::{SecretSharing, ShamirSecretSharing, ShamirShare};
+
+type Shamir<F> = ShamirSecretSharing<F>;
+
+/// A measured primitive
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PrimitiveId {
+    /// Sharing one secret among all parties
+    Share,
+    /// Reconstructing one secret from a threshold of shares
+    Reconstruct,
+    /// One party's work in an addition gate
+    AddGate,
+    /// One party's work in a multiplication gate: the local product, its
+    /// resharing and the recombination of the received subshares
+    MulGate,
+    /// Committing to a polynomial of the given degree
+    KzgCommit { degree: usize },
+    /// One round of sumcheck over `2^SUMCHECK_ROUND_LOG_SIZE` evaluations:
+    /// the round polynomial and the fold with the challenge
+    SumcheckRound,
+}
+
+/// Log size of the table a sumcheck round is measured on
+pub const SUMCHECK_ROUND_LOG_SIZE: usize = 12;
+
+impl PrimitiveId {
+    /// Stable identifier, e.g. `mpc.gate.mul` or `pcs.kzg.commit.d1024`
+    pub fn id(&self) -> String {
+        match self {
+            PrimitiveId::Share => "mpc.share".to_string(),
+            PrimitiveId::Reconstruct => "mpc.reconstruct".to_string(),
+            PrimitiveId::AddGate => "mpc.gate.add".to_string(),
+            PrimitiveId::MulGate => "mpc.gate.mul".to_string(),
+            PrimitiveId::KzgCommit { degree } => format!("pcs.kzg.commit.d{}", degree),
+            PrimitiveId::SumcheckRound => "piop.sumcheck.round".to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for PrimitiveId {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.id())
+    }
+}
+
+impl std::str::FromStr for PrimitiveId {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fixed = [
+            PrimitiveId::Share,
+            PrimitiveId::Reconstruct,
+            PrimitiveId::AddGate,
+            PrimitiveId::MulGate,
+            PrimitiveId::SumcheckRound,
+        ];
+        if let Some(primitive) = fixed.into_iter().find(|primitive| primitive.id() == s) {
+            return Ok(primitive);
+        }
+        s.strip_prefix("pcs.kzg.commit.d")
+            .and_then(|degree| degree.parse().ok())
+            .map(|degree| PrimitiveId::KzgCommit { degree })
+            .ok_or_else(|| format!("unknown primitive '{}'", s))
+    }
+}
+
+/// Measured cost of every primitive of a run
+#[derive(Debug, Clone, Default)]
+pub struct PrimitiveCosts {
+    pub num_parties: usize,
+    pub threshold: usize,
+    pub statistics: BTreeMap<PrimitiveId, SampleStatistics>,
+}
+
+impl PrimitiveCosts {
+    /// Median time of one run of a primitive
+    pub fn cost(&self, primitive: PrimitiveId) -> Option<Duration> {
+        self.statistics.get(&primitive).map(|statistics| statistics.median)
+    }
+
+    /// Median time looked up by stable identifier
+    pub fn cost_by_id(&self, id: &str) -> Option<Duration> {
+        self.cost(id.parse().ok()?)
+    }
+
+    /// KZG commitment cost per coefficient at the largest measured degree,
+    /// where fixed overheads matter least
+    pub fn kzg_ns_per_coefficient(&self) -> Option<f64> {
+        self.statistics.iter().rev().find_map(|(primitive, statistics)| match primitive {
+            PrimitiveId::KzgCommit { degree } => Some(statistics.median.as_nanos() as f64 / (degree + 1) as f64),
+            _ => None,
+        })
+    }
+
+    /// `(id, median ns)` rows in identifier order
+    pub fn rows(&self) -> Vec<(String, u128)> {
+        self.statistics
+            .iter()
+            .map(|(primitive, statistics)| (primitive.id(), statistics.median.as_nanos()))
+            .collect()
+    }
+}
+
+/// Benchmark runner for the primitives
+pub struct Microsuite {
+    pub num_parties: usize,
+    pub threshold: usize,
+    pub kzg_degrees: Vec<usize>,
+    pub suite: BenchmarkSuite,
+}
+
+impl Microsuite {
+    pub fn new(num_parties: usize, threshold: usize) -> Self {
+        Self {
+            num_parties,
+            threshold,
+            kzg_degrees: vec![64, 256, 1024],
+            suite: BenchmarkSuite::new(),
+        }
+    }
+
+    pub fn with_kzg_degrees(mut self, degrees: &[usize]) -> Self {
+        self.kzg_degrees = degrees.to_vec();
+        self
+    }
+
+    fn measure(&self, primitive: PrimitiveId, workload: impl FnMut()) -> SampleStatistics {
+        let case = BenchmarkCase {
+            name: primitive.id(),
+            description: String::new(),
+            circuit_size: 1,
+            num_parties: self.num_parties,
+            expected_duration_ms: 0,
+        };
+        self.suite.run_case_with(&case, workload).statistics
+    }
+
+    /// Measure every primitive over the scalar field of `E`
+    pub fn run<E: Pairing>(&self) -> PrimitiveCosts {
+        let (n, t) = (self.num_parties, self.threshold);
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut statistics = BTreeMap::new();
+        let x = E::ScalarField::rand(&mut rng);
+        let y = E::ScalarField::rand(&mut rng);
+        let xs = Shamir::<E::ScalarField>::share_secret(x, t, n, &mut rng);
+        let ys = Shamir::<E::ScalarField>::share_secret(y, t, n, &mut rng);
+
+        statistics.insert(PrimitiveId::Share, self.measure(PrimitiveId::Share, || {
+            std::hint::black_box(Shamir::<E::ScalarField>::share_secret(x, t, n, &mut rng));
+        }));
+        statistics.insert(PrimitiveId::Reconstruct, self.measure(PrimitiveId::Reconstruct, || {
+            std::hint::black_box(Shamir::<E::ScalarField>::reconstruct_secret(&xs[..t]).unwrap());
+        }));
+        statistics.insert(PrimitiveId::AddGate, self.measure(PrimitiveId::AddGate, || {
+            std::hint::black_box(Shamir::<E::ScalarField>::add_shares(&xs[0], &ys[0]).unwrap());
+        }));
+
+        // Degree reduction: party i reshares its product, then combines the
+        // subshares it receives with the Lagrange weights at zero
+        let weights = lagrange_at_zero::<E::ScalarField>(n);
+        let received: Vec<ShamirShare<E::ScalarField>> = xs.clone();
+        statistics.insert(PrimitiveId::MulGate, self.measure(PrimitiveId::MulGate, || {
+            let product = Shamir::<E::ScalarField>::mul_shares(&xs[0], &ys[0]).unwrap();
+            std::hint::black_box(Shamir::<E::ScalarField>::share_secret(product.value, t, n, &mut rng));
+            let combined: E::ScalarField = received.iter().zip(&weights).map(|(share, w)| share.value * w).sum();
+            std::hint::black_box(combined);
+        }));
+
+        let max_degree = self.kzg_degrees.iter().copied().max().unwrap_or(0);
+        let scheme = KZGCommitmentScheme::<E>::setup(max_degree, &mut rng);
+        for &degree in &self.kzg_degrees {
+            let polynomial = DensePolynomial::<E::ScalarField>::rand(degree, &mut rng);
+            let primitive = PrimitiveId::KzgCommit { degree };
+            statistics.insert(primitive, self.measure(primitive, || {
+                std::hint::black_box(scheme.commit(&polynomial));
+            }));
+        }
+
+        let table: Vec<E::ScalarField> =
+            (0..1 << SUMCHECK_ROUND_LOG_SIZE).map(|_| E::ScalarField::rand(&mut rng)).collect();
+        statistics.insert(PrimitiveId::SumcheckRound, self.measure(PrimitiveId::SumcheckRound, || {
+            std::hint::black_box(sumcheck_round(&table, x));
+        }));
+
+        PrimitiveCosts { num_parties: n, threshold: t, statistics }
+    }
+}
+
+/// Lagrange coefficients at zero for the points `1..=n`
+fn lagrange_at_zero<F: PrimeField>(n: usize) -> Vec<F> {
+    (1..=n as u64)
+        .map(|i| {
+            let xi = F::from(i);
+            (1..=n as u64).filter(|j| *j != i).fold(F::one(), |acc, j| {
+                let xj = F::from(j);
+                acc * xj * (xj - xi).inverse().expect("points are distinct")
+            })
+        })
+        .collect()
+}
+
+/// One sumcheck round on a multilinear table: the round polynomial's values
+/// at 0 and 1, and the table folded at `challenge`
+fn sumcheck_round<F: Field>(table: &[F], challenge: F) -> ((F, F), Vec<F>) {
+    let (low, high) = table.split_at(table.len() / 2);
+    let at_zero = low.iter().copied().sum();
+    let at_one = high.iter().copied().sum();
+    let folded = low.iter().zip(high).map(|(l, h)| *l + challenge * (*h - l)).collect();
+    ((at_zero, at_one), folded)
+}
+
+impl CostModel {
+    /// Model with gate and commitment costs measured on this machine
+    ///
+    /// Constants the microsuite does not measure (FFT, latency, sizes) keep
+    /// their defaults from `CostModel::new`.
+    pub fn calibrated(costs: &PrimitiveCosts) -> Self {
+        let mut model = CostModel::new(costs.num_parties);
+        let ns = |primitive| costs.cost(primitive).map(|cost: Duration| cost.as_nanos() as f64);
+        if let Some(compute_ns) = ns(PrimitiveId::MulGate) {
+            model.multiplication = GateCost { compute_ns, ..model.multiplication };
+        }
+        if let Some(compute_ns) = ns(PrimitiveId::AddGate) {
+            model.addition = GateCost { compute_ns, ..model.addition };
+        }
+        if let Some(per_point) = costs.kzg_ns_per_coefficient() {
+            model.msm_ns_per_point = per_point;
+        }
+        model
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::{Bls12_381, Fr};
+
+    type TestField = Fr;
+
+    #[test]
+    fn test_microsuite_feeds_cost_model() {
+        let mut suite = Microsuite::new(3, 2).with_kzg_degrees(&[4, 16]);
+        suite.suite = BenchmarkSuite::new().with_iterations(0, 3);
+        let costs = suite.run::<Bls12_381>();
+
+        let ids: Vec<String> = costs.rows().into_iter().map(|(id, _)| id).collect();
+        assert_eq!(
+            ids,
+            [
+                "mpc.share",
+                "mpc.reconstruct",
+                "mpc.gate.add",
+                "mpc.gate.mul",
+                "pcs.kzg.commit.d4",
+                "pcs.kzg.commit.d16",
+                "piop.sumcheck.round",
+            ]
+        );
+        for id in &ids {
+            assert_eq!(id.parse::<PrimitiveId>().unwrap().id(), *id);
+        }
+        assert!("pcs.kzg.commit.dx".parse::<PrimitiveId>().is_err());
+        assert_eq!(costs.cost_by_id("mpc.gate.mul"), costs.cost(PrimitiveId::MulGate));
+
+        let model = CostModel::calibrated(&costs);
+        assert_eq!(model.num_parties, 3);
+        assert_eq!(model.multiplication.compute_ns, costs.cost(PrimitiveId::MulGate).unwrap().as_nanos() as f64);
+        assert_eq!(model.msm_ns_per_point, costs.kzg_ns_per_coefficient().unwrap());
+        assert_eq!(model.fft_ns_per_element, CostModel::new(3).fft_ns_per_element);
+
+        // The measured round is a real sumcheck step
+        let table: Vec<TestField> = (1..=4u64).map(TestField::from).collect();
+        let ((at_zero, at_one), folded) = sumcheck_round(&table, TestField::from(2u64));
+        assert_eq!((at_zero, at_one), (TestField::from(3u64), TestField::from(7u64)));
+        assert_eq!(folded, [TestField::from(5u64), TestField::from(6u64)]);
+        let weights = lagrange_at_zero::<TestField>(3);
+        assert_eq!(weights.iter().sum::<TestField>(), TestField::from(1u64));
+    }
+}
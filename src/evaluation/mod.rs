@@ -3,10 +3,43 @@
 //! This module provides tools and functions for evaluating the performance
 //! of the EOS delegation protocol, including benchmarking, profiling,
 //! and comparative analysis.
+//!
+//! The metrics collector and cost model are always available; benchmarks,
+//! report rendering and proof-size accounting need the `evaluation` feature.
 
 use std::time::{Duration, Instant};
 use std::collections::HashMap;
 
+#[cfg(feature = "evaluation")]
+pub(crate) mod accounting;
+pub(crate) mod cost_model;
+#[cfg(feature = "evaluation")]
+pub(crate) mod energy;
+pub(crate) mod hybrid;
+pub(crate) mod stragglers;
+pub(crate) mod workload;
+#[cfg(feature = "evaluation")]
+pub(crate) mod microbench;
+#[cfg(feature = "evaluation")]
+pub(crate) mod proof_size;
+#[cfg(feature = "evaluation")]
+pub(crate) mod render;
+
+#[cfg(feature = "evaluation")]
+pub use accounting::*;
+pub use cost_model::*;
+#[cfg(feature = "evaluation")]
+pub use energy::*;
+pub use hybrid::*;
+pub use stragglers::*;
+pub use workload::*;
+#[cfg(feature = "evaluation")]
+pub use microbench::*;
+#[cfg(feature = "evaluation")]
+pub use proof_size::*;
+#[cfg(feature = "evaluation")]
+pub use render::*;
+
 /// Performance metrics collector
 #[derive(Debug, Clone)]
 pub struct PerformanceMetrics {
@@ -18,6 +51,10 @@ pub struct PerformanceMetrics {
     pub communication_stats: CommunicationStats,
     /// Circuit-specific metrics
     pub circuit_metrics: CircuitMetrics,
+    /// CPU and wait breakdown of phases timed with `Timer::finish`
+    pub phase_timings: HashMap<String, PhaseTiming>,
+    /// Stages nested inside a phase, kept apart so totals do not count them twice
+    pub stage_timings: HashMap<String, Duration>,
 }
 
 impl PerformanceMetrics {
@@ -28,6 +65,8 @@ impl PerformanceMetrics {
             memory_stats: MemoryStats::new(),
             communication_stats: CommunicationStats::new(),
             circuit_metrics: CircuitMetrics::new(),
+            phase_timings: HashMap::new(),
+            stage_timings: HashMap::new(),
         }
     }
     
@@ -51,12 +90,15 @@ impl PerformanceMetrics {
         PerformanceReport {
             total_time: self.total_time(),
             phase_breakdown: self.timings.clone(),
+            phase_timings: self.phase_timings.clone(),
+            stage_breakdown: self.stage_timings.clone(),
             memory_peak: if self.memory_stats.peak_usage_bytes == 0 { 
                 // 估算内存使用 - 基于实际运行的合理估算
                 1024 * 1024  // 1MB 基础内存使用
             } else { 
                 self.memory_stats.peak_usage_bytes 
             },
+            srs_memory_saved: self.memory_stats.srs_bytes_saved,
             communication_overhead: if self.communication_stats.total_bytes() == 0 {
                 // 估算通信开销 - 基于操作类型的合理估算
                 self.timings.len() * 512  // 每个操作大约512字节
@@ -72,6 +114,60 @@ impl PerformanceMetrics {
         }
     }
     
+    /// Capture the current state, to be compared later with `diff`
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        MetricsSnapshot {
+            taken_at: Instant::now(),
+            timings: self.timings.clone(),
+            communication_bytes: self.communication_stats.total_bytes(),
+            communication_rounds: self.communication_stats.rounds,
+            allocation_count: self.memory_stats.allocation_count,
+            peak_usage_bytes: self.memory_stats.peak_usage_bytes,
+        }
+    }
+
+    /// What changed since `snapshot` was taken
+    pub fn diff(&self, snapshot: &MetricsSnapshot) -> MetricsDiff {
+        let timings = self
+            .timings
+            .iter()
+            .filter_map(|(phase, duration)| {
+                let before = snapshot.timings.get(phase).copied().unwrap_or_default();
+                let delta = duration.saturating_sub(before);
+                (!delta.is_zero()).then(|| (phase.clone(), delta))
+            })
+            .collect();
+        MetricsDiff {
+            elapsed: snapshot.taken_at.elapsed(),
+            timings,
+            communication_bytes: self.communication_stats.total_bytes().saturating_sub(snapshot.communication_bytes),
+            communication_rounds: self.communication_stats.rounds.saturating_sub(snapshot.communication_rounds),
+            allocation_count: self.memory_stats.allocation_count.saturating_sub(snapshot.allocation_count),
+            peak_increase_bytes: self.memory_stats.peak_usage_bytes.saturating_sub(snapshot.peak_usage_bytes),
+        }
+    }
+
+    /// Record a phase with its CPU and wait breakdown, adding to earlier runs
+    pub fn record_phase(&mut self, timing: PhaseTiming) {
+        self.accumulate_timing(&timing.phase, timing.wall);
+        match self.phase_timings.get_mut(&timing.phase) {
+            Some(existing) => existing.absorb(&timing),
+            None => {
+                self.phase_timings.insert(timing.phase.clone(), timing);
+            }
+        }
+    }
+
+    /// Add `duration` to a phase, for phases that run several times
+    pub fn accumulate_timing(&mut self, phase: &str, duration: Duration) {
+        *self.timings.entry(phase.to_string()).or_default() += duration;
+    }
+
+    /// Add time to a stage nested inside one of the timed phases
+    pub fn accumulate_stage(&mut self, stage: &str, duration: Duration) {
+        *self.stage_timings.entry(stage.to_string()).or_default() += duration;
+    }
+
     /// Compare with baseline metrics
     pub fn compare_with_baseline(&self, baseline: &PerformanceMetrics) -> ComparisonResult {
         let speedup = baseline.total_time().as_secs_f64() / self.total_time().as_secs_f64();
@@ -87,10 +183,81 @@ impl PerformanceMetrics {
     }
 }
 
+impl Default for PerformanceMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Point-in-time copy of `PerformanceMetrics`
+#[derive(Debug, Clone)]
+pub struct MetricsSnapshot {
+    pub taken_at: Instant,
+    pub timings: HashMap<String, Duration>,
+    pub communication_bytes: usize,
+    pub communication_rounds: usize,
+    pub allocation_count: usize,
+    pub peak_usage_bytes: usize,
+}
+
+/// Metrics accumulated between a snapshot and now
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricsDiff {
+    /// Wall-clock time since the snapshot
+    pub elapsed: Duration,
+    /// Time added to each phase; phases without change are omitted
+    pub timings: HashMap<String, Duration>,
+    pub communication_bytes: usize,
+    pub communication_rounds: usize,
+    pub allocation_count: usize,
+    pub peak_increase_bytes: usize,
+}
+
+/// CPU time consumed so far by the calling thread, where the platform reports it
+pub fn thread_cpu_time() -> Option<Duration> {
+    cpu_clock(ClockKind::Thread)
+}
+
+/// CPU time consumed so far by all threads of the process
+pub fn process_cpu_time() -> Option<Duration> {
+    cpu_clock(ClockKind::Process)
+}
+
+enum ClockKind {
+    Thread,
+    Process,
+}
+
+#[cfg(unix)]
+fn cpu_clock(kind: ClockKind) -> Option<Duration> {
+    let clock = match kind {
+        ClockKind::Thread => libc::CLOCK_THREAD_CPUTIME_ID,
+        ClockKind::Process => libc::CLOCK_PROCESS_CPUTIME_ID,
+    };
+    let mut time = libc::timespec { tv_sec: 0, tv_nsec: 0 };
+    // SAFETY: `time` is a valid, writable timespec for the duration of the call
+    if unsafe { libc::clock_gettime(clock, &mut time) } != 0 {
+        return None;
+    }
+    Some(Duration::new(time.tv_sec as u64, time.tv_nsec as u32))
+}
+
+#[cfg(not(unix))]
+fn cpu_clock(_kind: ClockKind) -> Option<Duration> {
+    None
+}
+
 /// Timer for measuring execution phases
+///
+/// Besides wall-clock time the timer reads the thread and process CPU
+/// clocks, and callers mark the sections spent waiting on the network with
+/// `wait`. Together they tell compute-bound phases from network-bound ones.
 pub struct Timer {
     phase: String,
     start_time: Instant,
+    thread_cpu_start: Option<Duration>,
+    process_cpu_start: Option<Duration>,
+    wait: Duration,
 }
 
 impl Timer {
@@ -98,6 +265,9 @@ impl Timer {
         Self {
             phase,
             start_time: Instant::now(),
+            thread_cpu_start: thread_cpu_time(),
+            process_cpu_start: process_cpu_time(),
+            wait: Duration::ZERO,
         }
     }
     
@@ -105,6 +275,74 @@ impl Timer {
     pub fn stop(self) -> (String, Duration) {
         (self.phase, self.start_time.elapsed())
     }
+
+    /// Run `blocking` and count its duration as communication wait
+    pub fn wait<T>(&mut self, blocking: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = blocking();
+        self.wait += start.elapsed();
+        result
+    }
+
+    /// Count an externally measured wait
+    pub fn record_wait(&mut self, duration: Duration) {
+        self.wait += duration;
+    }
+
+    /// Stop the timer and return the full breakdown
+    ///
+    /// Must be called on the thread that started the timer for the thread
+    /// CPU time to be meaningful.
+    pub fn finish(self) -> PhaseTiming {
+        let wall = self.start_time.elapsed();
+        let since = |start: Option<Duration>, now: Option<Duration>| Some(now?.saturating_sub(start?));
+        PhaseTiming {
+            phase: self.phase,
+            wall,
+            thread_cpu: since(self.thread_cpu_start, thread_cpu_time()),
+            process_cpu: since(self.process_cpu_start, process_cpu_time()),
+            wait: self.wait.min(wall),
+            runs: 1,
+        }
+    }
+}
+
+/// Wall-clock, CPU and wait time of one phase
+#[derive(Debug, Clone, PartialEq)]
+pub struct PhaseTiming {
+    pub phase: String,
+    pub wall: Duration,
+    /// CPU time of the timing thread; `None` where unsupported
+    pub thread_cpu: Option<Duration>,
+    /// CPU time of the whole process, including worker threads
+    pub process_cpu: Option<Duration>,
+    /// Time marked as waiting for communication
+    pub wait: Duration,
+    /// Number of runs accumulated into this entry
+    pub runs: usize,
+}
+
+impl PhaseTiming {
+    /// Wall time neither computing on the timing thread nor marked as wait,
+    /// e.g. descheduling or waiting on locks
+    pub fn idle(&self) -> Option<Duration> {
+        Some(self.wall.saturating_sub(self.thread_cpu? + self.wait))
+    }
+
+    /// Whether more time went to waiting than to computing
+    pub fn is_network_bound(&self) -> bool {
+        let compute = self.thread_cpu.unwrap_or(self.wall.saturating_sub(self.wait));
+        self.wait > compute
+    }
+
+    fn absorb(&mut self, other: &PhaseTiming) {
+        let add = |a: Option<Duration>, b: Option<Duration>| Some(a? + b?);
+        self.wall += other.wall;
+        self.thread_cpu = add(self.thread_cpu, other.thread_cpu);
+        self.process_cpu = add(self.process_cpu, other.process_cpu);
+        self.wait += other.wait;
+        self.runs += other.runs;
+    }
 }
 
 /// Memory usage statistics
@@ -116,6 +354,8 @@ pub struct MemoryStats {
     pub current_usage_bytes: usize,
     /// Number of allocations
     pub allocation_count: usize,
+    /// SRS memory not spent because only the needed prefix was loaded
+    pub srs_bytes_saved: usize,
 }
 
 impl MemoryStats {
@@ -124,6 +364,7 @@ impl MemoryStats {
             peak_usage_bytes: 0,
             current_usage_bytes: 0,
             allocation_count: 0,
+            srs_bytes_saved: 0,
         }
     }
     
@@ -137,6 +378,12 @@ impl MemoryStats {
     }
 }
 
+impl Default for MemoryStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Communication statistics
 #[derive(Debug, Clone)]
 pub struct CommunicationStats {
@@ -146,6 +393,8 @@ pub struct CommunicationStats {
     pub bytes_per_round: Vec<usize>,
     /// Latency per round in milliseconds
     pub latency_per_round: Vec<u64>,
+    /// Round trips avoided by merging messages of the same round
+    pub rounds_saved: usize,
 }
 
 impl CommunicationStats {
@@ -154,6 +403,7 @@ impl CommunicationStats {
             rounds: 0,
             bytes_per_round: Vec::new(),
             latency_per_round: Vec::new(),
+            rounds_saved: 0,
         }
     }
     
@@ -164,6 +414,11 @@ impl CommunicationStats {
         self.latency_per_round.push(latency_ms);
     }
     
+    /// Record round trips avoided by message piggybacking
+    pub fn record_rounds_saved(&mut self, saved: usize) {
+        self.rounds_saved += saved;
+    }
+    
     /// Get total bytes communicated
     pub fn total_bytes(&self) -> usize {
         self.bytes_per_round.iter().sum()
@@ -184,6 +439,12 @@ impl CommunicationStats {
     }
 }
 
+impl Default for CommunicationStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Circuit-specific metrics
 #[derive(Debug, Clone)]
 pub struct CircuitMetrics {
@@ -221,43 +482,47 @@ impl CircuitMetrics {
     }
 }
 
+impl Default for CircuitMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Performance report structure
 #[derive(Debug, Clone)]
 pub struct PerformanceReport {
     pub total_time: Duration,
     pub phase_breakdown: HashMap<String, Duration>,
+    /// CPU and wait breakdown, for phases timed with `Timer::finish`
+    pub phase_timings: HashMap<String, PhaseTiming>,
+    /// Stages nested inside the phases, e.g. the proving stages
+    pub stage_breakdown: HashMap<String, Duration>,
     pub memory_peak: usize,
+    /// SRS memory saved by prefix loading
+    pub srs_memory_saved: usize,
     pub communication_overhead: usize,
     pub circuit_size: usize,
 }
 
 impl PerformanceReport {
+    /// Proving stages split by whether the delegator or the workers ran them
+    pub fn hybrid(&self) -> HybridReport {
+        HybridReport::from_timings(&self.stage_breakdown)
+    }
+
     /// Print formatted report
+    #[cfg(feature = "evaluation")]
     pub fn print_report(&self) {
-        println!("=== EOS Delegation Protocol Performance Report ===");
-        println!("Total Execution Time: {:.2?}", self.total_time);
-        println!("Peak Memory Usage: {:.2} MB", self.memory_peak as f64 / 1_048_576.0);
-        println!("Communication Overhead: {:.2} KB", self.communication_overhead as f64 / 1024.0);
-        println!("Circuit Size: {} constraints", self.circuit_size);
-        println!();
-        
-        println!("Phase Breakdown:");
-        let mut sorted_phases: Vec<_> = self.phase_breakdown.iter().collect();
-        sorted_phases.sort_by_key(|(_, duration)| *duration);
-        sorted_phases.reverse();
-        
-        for (phase, duration) in sorted_phases {
-            let percentage = duration.as_secs_f64() / self.total_time.as_secs_f64() * 100.0;
-            println!("  {}: {:.2?} ({:.1}%)", phase, duration, percentage);
-        }
+        println!("{}", self.to_markdown());
     }
     
     /// Export report to JSON
     pub fn to_json(&self) -> String {
         // TODO: Implement JSON serialization
-        format!("{{\"total_time_ms\": {}, \"memory_peak_bytes\": {}, \"communication_bytes\": {}, \"circuit_size\": {}}}",
+        format!("{{\"total_time_ms\": {}, \"memory_peak_bytes\": {}, \"srs_memory_saved_bytes\": {}, \"communication_bytes\": {}, \"circuit_size\": {}}}",
                 self.total_time.as_millis(),
                 self.memory_peak,
+                self.srs_memory_saved,
                 self.communication_overhead,
                 self.circuit_size)
     }
@@ -287,22 +552,47 @@ impl ComparisonResult {
     }
 }
 
+#[cfg(feature = "evaluation")]
 /// Benchmark suite for comprehensive evaluation
 pub struct BenchmarkSuite {
     /// Test cases for different circuit sizes
     pub test_cases: Vec<BenchmarkCase>,
     /// Baseline measurements
     pub baselines: HashMap<String, PerformanceMetrics>,
+    /// Unmeasured runs before sampling, to warm caches and allocators
+    pub warmup_iterations: usize,
+    /// Measured runs per case
+    pub repetitions: usize,
+    /// Sample CPU utilization and energy of the measured runs, estimating
+    /// with this model where RAPL counters are unavailable
+    pub energy: Option<EnergyModel>,
 }
 
+#[cfg(feature = "evaluation")]
 impl BenchmarkSuite {
     /// Create new benchmark suite
     pub fn new() -> Self {
         Self {
             test_cases: Vec::new(),
             baselines: HashMap::new(),
+            warmup_iterations: 1,
+            repetitions: 10,
+            energy: None,
         }
     }
+
+    /// Set the number of warmup and measured runs per case
+    pub fn with_iterations(mut self, warmup_iterations: usize, repetitions: usize) -> Self {
+        self.warmup_iterations = warmup_iterations;
+        self.repetitions = repetitions.max(1);
+        self
+    }
+
+    /// Report CPU utilization and energy per run of every case
+    pub fn with_energy(mut self, model: EnergyModel) -> Self {
+        self.energy = Some(model);
+        self
+    }
     
     /// Add a benchmark test case
     pub fn add_test_case(&mut self, case: BenchmarkCase) {
@@ -329,13 +619,30 @@ impl BenchmarkSuite {
     
     /// Run a single benchmark test
     fn run_single_benchmark(&self, test_case: &BenchmarkCase) -> BenchmarkResult {
-        let mut metrics = PerformanceMetrics::new();
-        
         // Simulate benchmark execution
-        let timer = metrics.start_timer("total");
-        std::thread::sleep(Duration::from_millis(test_case.expected_duration_ms));
-        let (phase, duration) = timer.stop();
-        metrics.record_timing(phase, duration);
+        let duration = Duration::from_millis(test_case.expected_duration_ms);
+        self.run_case_with(test_case, || std::thread::sleep(duration))
+    }
+
+    /// Measure `workload` for `test_case`: warm up, then sample it
+    /// `repetitions` times
+    pub fn run_case_with(&self, test_case: &BenchmarkCase, mut workload: impl FnMut()) -> BenchmarkResult {
+        for _ in 0..self.warmup_iterations {
+            workload();
+        }
+        let energy = self.energy.map(EnergySample::start);
+        let samples: Vec<Duration> = (0..self.repetitions.max(1))
+            .map(|_| {
+                let start = Instant::now();
+                workload();
+                start.elapsed()
+            })
+            .collect();
+        let energy = energy.map(|sample| sample.finish().per_run(samples.len()));
+        let statistics = SampleStatistics::from_samples(&samples);
+
+        let mut metrics = PerformanceMetrics::new();
+        metrics.record_timing("total".to_string(), statistics.mean);
         
         // Update circuit metrics
         metrics.circuit_metrics.constraint_count = test_case.circuit_size;
@@ -344,6 +651,8 @@ impl BenchmarkSuite {
         BenchmarkResult {
             test_case: test_case.clone(),
             metrics,
+            statistics,
+            energy,
             passed: true,
         }
     }
@@ -361,6 +670,114 @@ impl BenchmarkSuite {
     }
 }
 
+#[cfg(feature = "evaluation")]
+/// Summary statistics over repeated measurements
+#[derive(Debug, Clone, PartialEq)]
+pub struct SampleStatistics {
+    pub samples: Vec<Duration>,
+    pub mean: Duration,
+    pub median: Duration,
+    /// Sample standard deviation
+    pub std_dev: Duration,
+    /// 95% confidence interval of the mean (Student's t)
+    pub ci95: (Duration, Duration),
+    /// Indices of samples outside the Tukey fences (1.5 IQR beyond the quartiles)
+    pub outliers: Vec<usize>,
+}
+
+#[cfg(feature = "evaluation")]
+impl SampleStatistics {
+    pub fn from_samples(samples: &[Duration]) -> Self {
+        let n = samples.len();
+        let secs: Vec<f64> = samples.iter().map(Duration::as_secs_f64).collect();
+        let mut sorted = secs.clone();
+        sorted.sort_by(f64::total_cmp);
+        let mean = if n == 0 { 0.0 } else { secs.iter().sum::<f64>() / n as f64 };
+        let variance = if n < 2 {
+            0.0
+        } else {
+            secs.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1) as f64
+        };
+        let std_dev = variance.sqrt();
+        let half_width = if n < 2 { 0.0 } else { t_critical_95(n - 1) * std_dev / (n as f64).sqrt() };
+
+        let (q1, q3) = (quantile(&sorted, 0.25), quantile(&sorted, 0.75));
+        let fence = 1.5 * (q3 - q1);
+        let outliers = secs
+            .iter()
+            .enumerate()
+            .filter(|(_, x)| **x < q1 - fence || **x > q3 + fence)
+            .map(|(i, _)| i)
+            .collect();
+
+        let duration = |secs: f64| Duration::from_secs_f64(secs.max(0.0));
+        Self {
+            samples: samples.to_vec(),
+            mean: duration(mean),
+            median: duration(quantile(&sorted, 0.5)),
+            std_dev: duration(std_dev),
+            ci95: (duration(mean - half_width), duration(mean + half_width)),
+            outliers,
+        }
+    }
+
+    /// Relative half-width of the confidence interval
+    pub fn relative_error(&self) -> f64 {
+        let mean = self.mean.as_secs_f64();
+        if mean == 0.0 {
+            0.0
+        } else {
+            (self.ci95.1 - self.ci95.0).as_secs_f64() / 2.0 / mean
+        }
+    }
+
+    /// Whether the confidence intervals of two cases do not overlap, i.e.
+    /// the difference between them is significant at the 5% level
+    pub fn differs_from(&self, other: &SampleStatistics) -> bool {
+        self.ci95.1 < other.ci95.0 || other.ci95.1 < self.ci95.0
+    }
+}
+
+#[cfg(feature = "evaluation")]
+/// Linear-interpolated quantile of sorted data
+fn quantile(sorted: &[f64], q: f64) -> f64 {
+    match sorted.len() {
+        0 => 0.0,
+        1 => sorted[0],
+        n => {
+            let position = q * (n - 1) as f64;
+            let lower = position.floor() as usize;
+            let upper = position.ceil() as usize;
+            sorted[lower] + (sorted[upper] - sorted[lower]) * (position - lower as f64)
+        }
+    }
+}
+
+#[cfg(feature = "evaluation")]
+/// Two-sided 95% critical value of Student's t distribution
+fn t_critical_95(degrees_of_freedom: usize) -> f64 {
+    const TABLE: [f64; 30] = [
+        12.706, 4.303, 3.182, 2.776, 2.571, 2.447, 2.365, 2.306, 2.262, 2.228,
+        2.201, 2.179, 2.160, 2.145, 2.131, 2.120, 2.110, 2.101, 2.093, 2.086,
+        2.080, 2.074, 2.069, 2.064, 2.060, 2.056, 2.052, 2.048, 2.045, 2.042,
+    ];
+    match degrees_of_freedom {
+        0 => f64::INFINITY,
+        df if df <= TABLE.len() => TABLE[df - 1],
+        df if df <= 60 => 2.000,
+        df if df <= 120 => 1.980,
+        _ => 1.960,
+    }
+}
+
+#[cfg(feature = "evaluation")]
+impl Default for BenchmarkSuite {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "evaluation")]
 /// Individual benchmark test case
 #[derive(Debug, Clone)]
 pub struct BenchmarkCase {
@@ -371,14 +788,21 @@ pub struct BenchmarkCase {
     pub expected_duration_ms: u64,
 }
 
+#[cfg(feature = "evaluation")]
 /// Result of a single benchmark test
 #[derive(Debug, Clone)]
 pub struct BenchmarkResult {
     pub test_case: BenchmarkCase,
+    /// Metrics with the mean of the measured runs as total time
     pub metrics: PerformanceMetrics,
+    pub statistics: SampleStatistics,
+    /// CPU and energy use of one run, one delegation for protocol cases,
+    /// if the suite sampled them
+    pub energy: Option<EnergyUsage>,
     pub passed: bool,
 }
 
+#[cfg(feature = "evaluation")]
 /// Comprehensive benchmark report
 #[derive(Debug, Clone)]
 pub struct BenchmarkReport {
@@ -388,20 +812,77 @@ pub struct BenchmarkReport {
     pub results: Vec<BenchmarkResult>,
 }
 
+#[cfg(feature = "evaluation")]
 impl BenchmarkReport {
     /// Print benchmark summary
     pub fn print_summary(&self) {
-        println!("=== Benchmark Summary ===");
-        println!("Tests Run: {}", self.test_count);
-        println!("Tests Passed: {}", self.passed_count);
-        println!("Success Rate: {:.1}%", self.passed_count as f64 / self.test_count as f64 * 100.0);
-        println!("Average Execution Time: {:.2?}", self.average_time);
-        println!();
-        
-        // Print individual results
-        for result in &self.results {
-            let status = if result.passed { "PASS" } else { "FAIL" };
-            println!("[{}] {}: {:.2?}", status, result.test_case.name, result.metrics.total_time());
+        println!("{}", self.to_markdown());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timer_separates_compute_from_wait() {
+        let mut metrics = PerformanceMetrics::new();
+
+        let mut timer = metrics.start_timer("exchange");
+        timer.wait(|| std::thread::sleep(Duration::from_millis(20)));
+        let exchange = timer.finish();
+        assert!(exchange.wait >= Duration::from_millis(20));
+        assert!(exchange.is_network_bound());
+
+        let timer = metrics.start_timer("compute");
+        let mut x = 1u64;
+        let start = Instant::now();
+        while start.elapsed() < Duration::from_millis(20) {
+            x = std::hint::black_box(x.wrapping_mul(6364136223846793005).wrapping_add(1));
+        }
+        let compute = timer.finish();
+        assert!(!compute.is_network_bound());
+        if let (Some(cpu), Some(idle)) = (compute.thread_cpu, compute.idle()) {
+            assert!(cpu >= Duration::from_millis(10));
+            assert!(idle < compute.wall);
         }
+
+        metrics.record_phase(exchange.clone());
+        metrics.record_phase(exchange);
+        assert_eq!(metrics.phase_timings["exchange"].runs, 2);
+        assert_eq!(metrics.timings["exchange"], metrics.phase_timings["exchange"].wall);
+    }
+
+    #[test]
+    #[cfg(feature = "evaluation")]
+    fn test_sample_statistics() {
+        let ms = |values: &[u64]| values.iter().map(|v| Duration::from_millis(*v)).collect::<Vec<_>>();
+        let stats = SampleStatistics::from_samples(&ms(&[10, 11, 9, 10, 10, 11, 9, 50]));
+        assert_eq!(stats.median, Duration::from_millis(10));
+        assert_eq!(stats.outliers, vec![7]);
+        assert!(stats.ci95.0 < stats.mean && stats.mean < stats.ci95.1);
+
+        let tight = SampleStatistics::from_samples(&ms(&[9, 10, 11, 10, 9, 11]));
+        let slow = SampleStatistics::from_samples(&ms(&[20, 21, 19, 20, 20, 21]));
+        assert!(tight.outliers.is_empty());
+        assert!(tight.differs_from(&slow));
+        assert!(!tight.differs_from(&stats));
+
+        let suite = BenchmarkSuite::new().with_iterations(2, 4);
+        let case = BenchmarkCase {
+            name: "count".to_string(),
+            description: String::new(),
+            circuit_size: 10,
+            num_parties: 3,
+            expected_duration_ms: 0,
+        };
+        let mut runs = 0;
+        let result = suite.run_case_with(&case, || runs += 1);
+        assert_eq!(runs, 6);
+        assert_eq!(result.statistics.samples.len(), 4);
+        assert!(result.energy.is_none());
+        let result = suite.with_energy(EnergyModel::new()).run_case_with(&case, || runs += 1);
+        assert_eq!(runs, 12);
+        assert!(result.energy.unwrap().joules >= 0.0);
     }
 }
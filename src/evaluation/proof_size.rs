@@ -0,0 +1,217 @@
+//! Per-component proof size accounting
+//!
+//! `ProofSizeReport` attributes every byte of a delegation transcript to the
+//! part of the proof that produced it: each witness commitment, each opening,
+//! each sumcheck round. Length prefixes and flags are reported as framing, so
+//! the entries always add up to the encoded size and can be compared with the
+//! proof-size breakdowns in the paper.
+
+use std::collections::BTreeMap;
+
+use ark_ec::{pairing::Pairing, CurveGroup};
+use ark_ff::Field;
+
+use crate::circuit::MultilinearOpeningProof;
+use crate::piop::PolynomialConsistencyProof;
+use crate::protocol::DelegationResult;
+use crate::serialization::{field_size, DecodeError, WireFormat};
+
+/// Kind of proof component an entry belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SizeCategory {
+    Commitment,
+    Opening,
+    SumcheckRound,
+    /// Challenges and final evaluation of a sumcheck
+    SumcheckClaim,
+    /// Seed commitment and verification key hash
+    Binding,
+    /// Length prefixes and presence flags
+    Framing,
+}
+
+impl SizeCategory {
+    pub fn name(self) -> &'static str {
+        match self {
+            SizeCategory::Commitment => "commitment",
+            SizeCategory::Opening => "opening",
+            SizeCategory::SumcheckRound => "sumcheck round",
+            SizeCategory::SumcheckClaim => "sumcheck claim",
+            SizeCategory::Binding => "binding",
+            SizeCategory::Framing => "framing",
+        }
+    }
+}
+
+/// Encoded size of one proof component
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SizeEntry {
+    /// Path of the component, e.g. `sumcheck[0].round[2]`
+    pub component: String,
+    pub category: SizeCategory,
+    pub bytes: usize,
+}
+
+/// Size breakdown of an encoded proof
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProofSizeReport {
+    pub entries: Vec<SizeEntry>,
+}
+
+impl ProofSizeReport {
+    /// Breakdown of `proof.to_bytes()`
+    pub fn of_consistency_proof<F: Field, G: CurveGroup>(proof: &PolynomialConsistencyProof<F, G>) -> Self {
+        let mut report = Self::default();
+        for (i, commitment) in proof.witness_commitments.iter().enumerate() {
+            report.push(format!("witness_commitment[{}]", i), SizeCategory::Commitment, commitment.to_bytes().len());
+        }
+        for (i, opening) in proof.consistency_proofs.iter().enumerate() {
+            report.push(format!("opening[{}]", i), SizeCategory::Opening, opening.to_bytes().len());
+        }
+        for (k, sumcheck) in proof.sumcheck_proofs.iter().enumerate() {
+            for (r, round) in sumcheck.round_polynomials.iter().enumerate() {
+                let component = format!("sumcheck[{}].round[{}]", k, r);
+                report.push(component, SizeCategory::SumcheckRound, round.to_bytes().len());
+            }
+            let claim = (sumcheck.challenges.len() + 1) * field_size::<F>();
+            report.push(format!("sumcheck[{}].claim", k), SizeCategory::SumcheckClaim, claim);
+        }
+        if let Some(claims) = &proof.constraint_claims {
+            report.push("constraint.row_evaluations".to_string(), SizeCategory::SumcheckClaim, 3 * field_size::<F>());
+            report.push_multilinear_opening("constraint", "witness_claim", &claims.witness_opening);
+            for (k, mask) in claims.masks.iter().enumerate() {
+                let component = format!("constraint.mask[{}]", k);
+                let commitment = mask.mask.commitment.to_bytes().len();
+                report.push(format!("{}.commitment", component), SizeCategory::Commitment, commitment);
+                report.push(format!("{}.sum", component), SizeCategory::SumcheckClaim, field_size::<F>());
+                report.push_multilinear_opening(&component, "claim", &mask.opening);
+            }
+        }
+        report.fill_framing(proof.to_bytes().len());
+        report
+    }
+
+    /// Breakdown of `result.transcript_bytes()`, the bytes the verifier receives
+    pub fn of_result<E: Pairing>(result: &DelegationResult<E, E::ScalarField>) -> Result<Self, DecodeError> {
+        let mut report = match &result.piop_proof {
+            Some(bytes) => Self::of_consistency_proof(
+                &PolynomialConsistencyProof::<E::ScalarField, E::G1>::from_bytes(bytes)?,
+            ),
+            None => Self::default(),
+        };
+        for (i, commitment) in result.polynomial_commitments.iter().enumerate() {
+            report.push(format!("polynomial_commitment[{}]", i), SizeCategory::Commitment, commitment.len());
+        }
+        if let Some(commitment) = &result.seed_commitment {
+            report.push("seed_commitment".to_string(), SizeCategory::Binding, commitment.len());
+        }
+        report.push("verification_key_hash".to_string(), SizeCategory::Binding, result.verification_key_hash.len());
+        report.fill_framing(result.transcript_bytes().len());
+        Ok(report)
+    }
+
+    fn push(&mut self, component: String, category: SizeCategory, bytes: usize) {
+        self.entries.push(SizeEntry { component, category, bytes });
+    }
+
+    /// The folds, openings and claimed evaluation of a multilinear opening
+    fn push_multilinear_opening<F: Field, G: CurveGroup>(
+        &mut self,
+        prefix: &str,
+        claim: &str,
+        opening: &MultilinearOpeningProof<F, G>,
+    ) {
+        for (i, fold) in opening.folds.iter().enumerate() {
+            self.push(format!("{}.fold[{}]", prefix, i), SizeCategory::Commitment, fold.to_bytes().len());
+        }
+        for (i, aggregated) in opening.openings.iter().enumerate() {
+            self.push(format!("{}.opening[{}]", prefix, i), SizeCategory::Opening, aggregated.to_bytes().len());
+        }
+        let bytes = (opening.point.len() + 1) * field_size::<F>();
+        self.push(format!("{}.{}", prefix, claim), SizeCategory::SumcheckClaim, bytes);
+    }
+
+    /// Attribute whatever the components do not account for to framing
+    fn fill_framing(&mut self, encoded_len: usize) {
+        let framing = encoded_len - self.total();
+        let existing = self.entries.iter_mut().find(|entry| entry.category == SizeCategory::Framing);
+        match existing {
+            Some(entry) => entry.bytes += framing,
+            None => self.push("framing".to_string(), SizeCategory::Framing, framing),
+        }
+    }
+
+    pub fn total(&self) -> usize {
+        self.entries.iter().map(|entry| entry.bytes).sum()
+    }
+
+    pub fn by_category(&self) -> BTreeMap<SizeCategory, usize> {
+        let mut totals = BTreeMap::new();
+        for entry in &self.entries {
+            *totals.entry(entry.category).or_default() += entry.bytes;
+        }
+        totals
+    }
+
+    /// Entries sorted by decreasing size, the first candidates for optimization
+    pub fn largest(&self, count: usize) -> Vec<&SizeEntry> {
+        let mut entries: Vec<&SizeEntry> = self.entries.iter().collect();
+        entries.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.component.cmp(&b.component)));
+        entries.truncate(count);
+        entries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::evaluation::ReportFormat;
+    use crate::mpc::{CollaborationMode, ExecCircuit, ShamirSecretSharing};
+    use crate::protocol::EOSProtocol;
+    use ark_bls12_381::{Bls12_381, Fr};
+    use ark_relations::r1cs::ConstraintSystem;
+    use ark_std::test_rng;
+
+    type TestField = Fr;
+    type Protocol = EOSProtocol<Bls12_381, TestField, ShamirSecretSharing<TestField>, CollaborationMode>;
+
+    #[test]
+    fn test_size_report_accounts_for_every_byte() {
+        let mut rng = test_rng();
+        let executor = ExecCircuit::new(1, 3, ShamirSecretSharing::new());
+        let mut protocol = Protocol::new(executor, CollaborationMode::new(2, true, false), 128, &mut rng);
+        let circuit = ConstraintSystem::new();
+        protocol.preprocessing_state = Some(Protocol::preprocessing(&circuit, 128, &mut rng).unwrap());
+        let witness: Vec<TestField> = (1..=8u64).map(TestField::from).collect();
+        let result = protocol
+            .delegate_computation(&circuit, &witness, &[TestField::from(7u64)], &mut rng)
+            .unwrap();
+
+        let report = ProofSizeReport::of_result(&result).unwrap();
+        assert_eq!(report.total(), result.transcript_bytes().len());
+        let by_category = report.by_category();
+        assert_eq!(by_category[&SizeCategory::Binding], 32);
+        assert!(by_category[&SizeCategory::Commitment] > 0);
+        assert!(by_category[&SizeCategory::Opening] > 0);
+        assert_eq!(report.entries.iter().filter(|e| e.category == SizeCategory::Framing).count(), 1);
+        let largest = report.largest(2);
+        assert!(largest[0].bytes >= largest[1].bytes);
+
+        let proof = PolynomialConsistencyProof::<TestField, <Bls12_381 as Pairing>::G1>::from_bytes(
+            result.piop_proof.as_deref().unwrap(),
+        )
+        .unwrap();
+        let inner = ProofSizeReport::of_consistency_proof(&proof);
+        assert_eq!(inner.total(), result.piop_proof.as_ref().unwrap().len());
+        let rounds: usize = proof.sumcheck_proofs.iter().map(|s| s.round_polynomials.len()).sum();
+        assert_eq!(inner.entries.iter().filter(|e| e.category == SizeCategory::SumcheckRound).count(), rounds);
+
+        let markdown = report.render(ReportFormat::Markdown);
+        assert!(markdown.contains("| verification_key_hash | binding | 32 |"), "{}", markdown);
+        assert!(report.render(ReportFormat::Html).contains("<svg"));
+
+        let mut truncated = result;
+        truncated.piop_proof.as_mut().unwrap().pop();
+        assert!(ProofSizeReport::of_result(&truncated).is_err());
+    }
+}
@@ -0,0 +1,508 @@
+//! Markdown and HTML rendering of evaluation reports
+//!
+//! `PerformanceReport`, `BenchmarkReport` and `ProofSizeReport` render to
+//! Markdown tables or to a self-contained HTML page; benchmark reports add
+//! an energy table when the suite sampled energy. HTML pages carry inline
+//! SVG charts (phase breakdown, time against circuit size, bytes against
+//! parties, proof bytes per category) and need no external assets; Markdown
+//! uses text bars, which every viewer displays.
+
+use std::fmt::Write;
+use std::time::Duration;
+
+use crate::evaluation::{BenchmarkReport, PerformanceReport, ProofSizeReport};
+
+/// Output format of a rendered report
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Markdown,
+    Html,
+}
+
+const BAR_WIDTH: usize = 30;
+const CHART_WIDTH: f64 = 480.0;
+const CHART_HEIGHT: f64 = 240.0;
+const CHART_MARGIN: f64 = 48.0;
+const ENERGY_HEADER: [&str; 5] = ["Case", "CPU (cores)", "Energy (J)", "Power (W)", "Source"];
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Markdown table cells must not contain pipes or line breaks
+fn escape_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+fn text_bar(value: f64, max: f64) -> String {
+    let filled = if max > 0.0 { (value / max * BAR_WIDTH as f64).round() as usize } else { 0 };
+    "█".repeat(filled.min(BAR_WIDTH))
+}
+
+fn markdown_table(out: &mut String, header: &[&str], rows: &[Vec<String>]) {
+    let _ = writeln!(out, "| {} |", header.join(" | "));
+    let _ = writeln!(out, "|{}|", vec!["---"; header.len()].join("|"));
+    for row in rows {
+        let cells: Vec<String> = row.iter().map(|cell| escape_cell(cell)).collect();
+        let _ = writeln!(out, "| {} |", cells.join(" | "));
+    }
+    out.push('\n');
+}
+
+fn html_table(out: &mut String, header: &[&str], rows: &[Vec<String>]) {
+    out.push_str("<table>\n<tr>");
+    for cell in header {
+        let _ = write!(out, "<th>{}</th>", escape_html(cell));
+    }
+    out.push_str("</tr>\n");
+    for row in rows {
+        out.push_str("<tr>");
+        for cell in row {
+            let _ = write!(out, "<td>{}</td>", escape_html(cell));
+        }
+        out.push_str("</tr>\n");
+    }
+    out.push_str("</table>\n");
+}
+
+fn html_page(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n<style>\n\
+         body {{ font-family: sans-serif; margin: 2em; }}\n\
+         table {{ border-collapse: collapse; margin-bottom: 1.5em; }}\n\
+         th, td {{ border: 1px solid #ccc; padding: 4px 10px; text-align: right; }}\n\
+         th:first-child, td:first-child {{ text-align: left; }}\n\
+         svg {{ display: block; margin-bottom: 1.5em; }}\n\
+         </style>\n</head>\n<body>\n<h1>{title}</h1>\n{body}</body>\n</html>\n",
+        title = escape_html(title),
+        body = body
+    )
+}
+
+/// Horizontal bar chart of labelled values
+pub fn svg_bar_chart(title: &str, unit: &str, bars: &[(String, f64)]) -> String {
+    let row_height = 22.0;
+    let label_width = 160.0;
+    let height = CHART_MARGIN + bars.len() as f64 * row_height + 10.0;
+    let max = bars.iter().map(|(_, value)| *value).fold(0.0, f64::max);
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n\
+         <text x=\"{w2}\" y=\"20\" text-anchor=\"middle\" font-size=\"14\">{title}</text>\n",
+        w = CHART_WIDTH,
+        h = height,
+        w2 = CHART_WIDTH / 2.0,
+        title = escape_html(title)
+    );
+    let available = CHART_WIDTH - label_width - 90.0;
+    for (i, (label, value)) in bars.iter().enumerate() {
+        let y = CHART_MARGIN + i as f64 * row_height;
+        let width = if max > 0.0 { value / max * available } else { 0.0 };
+        let _ = writeln!(
+            svg,
+            "<text x=\"{lx}\" y=\"{ty}\" text-anchor=\"end\" font-size=\"12\">{label}</text>\
+             <rect x=\"{x}\" y=\"{y}\" width=\"{width:.1}\" height=\"{bh}\" fill=\"#4a7fb5\"/>\
+             <text x=\"{vx:.1}\" y=\"{ty}\" font-size=\"11\">{value:.2} {unit}</text>",
+            lx = label_width - 6.0,
+            ty = y + 13.0,
+            label = escape_html(label),
+            x = label_width,
+            bh = row_height - 6.0,
+            vx = label_width + width + 4.0,
+            unit = escape_html(unit),
+        );
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Line chart of `(x, y)` points, sorted by x
+pub fn svg_line_chart(title: &str, x_label: &str, y_label: &str, points: &[(f64, f64)]) -> String {
+    let mut points = points.to_vec();
+    points.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let (x_min, x_max) = points
+        .iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), (x, _)| (lo.min(*x), hi.max(*x)));
+    let y_max = points.iter().map(|(_, y)| *y).fold(0.0, f64::max);
+    let plot_width = CHART_WIDTH - 2.0 * CHART_MARGIN;
+    let plot_height = CHART_HEIGHT - 2.0 * CHART_MARGIN;
+    let scale_x = |x: f64| {
+        let span = x_max - x_min;
+        CHART_MARGIN + if span > 0.0 { (x - x_min) / span * plot_width } else { plot_width / 2.0 }
+    };
+    let scale_y = |y: f64| CHART_HEIGHT - CHART_MARGIN - if y_max > 0.0 { y / y_max * plot_height } else { 0.0 };
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n\
+         <text x=\"{w2}\" y=\"20\" text-anchor=\"middle\" font-size=\"14\">{title}</text>\n\
+         <line x1=\"{m}\" y1=\"{base}\" x2=\"{right}\" y2=\"{base}\" stroke=\"#333\"/>\n\
+         <line x1=\"{m}\" y1=\"{m}\" x2=\"{m}\" y2=\"{base}\" stroke=\"#333\"/>\n\
+         <text x=\"{w2}\" y=\"{xl}\" text-anchor=\"middle\" font-size=\"12\">{x_label}</text>\n\
+         <text x=\"14\" y=\"{h2}\" text-anchor=\"middle\" font-size=\"12\" transform=\"rotate(-90 14 {h2})\">{y_label}</text>\n\
+         <text x=\"{ym}\" y=\"{m}\" text-anchor=\"end\" font-size=\"10\">{y_max:.1}</text>\n",
+        w = CHART_WIDTH,
+        h = CHART_HEIGHT,
+        w2 = CHART_WIDTH / 2.0,
+        h2 = CHART_HEIGHT / 2.0,
+        m = CHART_MARGIN,
+        ym = CHART_MARGIN - 4.0,
+        base = CHART_HEIGHT - CHART_MARGIN,
+        right = CHART_WIDTH - CHART_MARGIN,
+        xl = CHART_HEIGHT - 12.0,
+        title = escape_html(title),
+        x_label = escape_html(x_label),
+        y_label = escape_html(y_label),
+        y_max = y_max,
+    );
+    let path: Vec<String> = points
+        .iter()
+        .map(|(x, y)| format!("{:.1},{:.1}", scale_x(*x), scale_y(*y)))
+        .collect();
+    if points.len() > 1 {
+        let _ = writeln!(svg, "<polyline points=\"{}\" fill=\"none\" stroke=\"#4a7fb5\" stroke-width=\"2\"/>", path.join(" "));
+    }
+    for (x, y) in &points {
+        let _ = writeln!(
+            svg,
+            "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"3\" fill=\"#c0504d\"/>\
+             <text x=\"{:.1}\" y=\"{}\" text-anchor=\"middle\" font-size=\"10\">{}</text>",
+            scale_x(*x),
+            scale_y(*y),
+            scale_x(*x),
+            CHART_HEIGHT - CHART_MARGIN + 14.0,
+            x
+        );
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+impl PerformanceReport {
+    /// Phases sorted by decreasing time
+    fn sorted_phases(&self) -> Vec<(&String, &Duration)> {
+        let mut phases: Vec<_> = self.phase_breakdown.iter().collect();
+        phases.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+        phases
+    }
+
+    fn summary_rows(&self) -> Vec<Vec<String>> {
+        let mut rows = vec![
+            vec!["Total execution time".to_string(), format!("{:.2?}", self.total_time)],
+            vec!["Peak memory".to_string(), format!("{:.2} MB", self.memory_peak as f64 / 1_048_576.0)],
+            vec!["Communication".to_string(), format!("{:.2} KB", self.communication_overhead as f64 / 1024.0)],
+            vec!["Circuit size".to_string(), format!("{} constraints", self.circuit_size)],
+        ];
+        if self.srs_memory_saved > 0 {
+            rows.insert(
+                2,
+                vec!["SRS memory saved".to_string(), format!("{:.2} MB", self.srs_memory_saved as f64 / 1_048_576.0)],
+            );
+        }
+        rows
+    }
+
+    fn phase_rows(&self) -> Vec<Vec<String>> {
+        let total = self.total_time.as_secs_f64();
+        self.sorted_phases()
+            .into_iter()
+            .map(|(phase, duration)| {
+                let share = if total > 0.0 { duration.as_secs_f64() / total * 100.0 } else { 0.0 };
+                let timing = self.phase_timings.get(phase);
+                let cpu = timing
+                    .and_then(|t| t.thread_cpu)
+                    .map_or("n/a".to_string(), |cpu| format!("{:.2?}", cpu));
+                let wait = timing.map_or("n/a".to_string(), |t| format!("{:.2?}", t.wait));
+                let bound = timing.map_or("", |t| if t.is_network_bound() { "network" } else { "compute" });
+                vec![
+                    phase.to_string(),
+                    format!("{:.2?}", duration),
+                    format!("{:.1}%", share),
+                    cpu,
+                    wait,
+                    bound.to_string(),
+                ]
+            })
+            .collect()
+    }
+
+    pub fn render(&self, format: ReportFormat) -> String {
+        match format {
+            ReportFormat::Markdown => self.to_markdown(),
+            ReportFormat::Html => self.to_html(),
+        }
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::from("# EOS Delegation Protocol Performance Report\n\n");
+        markdown_table(&mut out, &["Metric", "Value"], &self.summary_rows());
+        out.push_str("## Phase breakdown\n\n");
+        let header = ["Phase", "Wall", "Share", "CPU", "Wait", "Bound"];
+        markdown_table(&mut out, &header, &self.phase_rows());
+        let max = self.sorted_phases().first().map_or(0.0, |(_, d)| millis(**d));
+        out.push_str("```\n");
+        for (phase, duration) in self.sorted_phases() {
+            let _ = writeln!(out, "{:<24} {} {:.2} ms", phase, text_bar(millis(*duration), max), millis(*duration));
+        }
+        out.push_str("```\n");
+        out
+    }
+
+    pub fn to_html(&self) -> String {
+        let mut body = String::new();
+        html_table(&mut body, &["Metric", "Value"], &self.summary_rows());
+        body.push_str("<h2>Phase breakdown</h2>\n");
+        let header = ["Phase", "Wall", "Share", "CPU", "Wait", "Bound"];
+        html_table(&mut body, &header, &self.phase_rows());
+        let bars: Vec<(String, f64)> = self
+            .sorted_phases()
+            .into_iter()
+            .map(|(phase, duration)| (phase.clone(), millis(*duration)))
+            .collect();
+        body.push_str(&svg_bar_chart("Time per phase", "ms", &bars));
+        html_page("EOS Delegation Protocol Performance Report", &body)
+    }
+}
+
+impl BenchmarkReport {
+    fn result_rows(&self) -> Vec<Vec<String>> {
+        self.results
+            .iter()
+            .map(|result| {
+                vec![
+                    result.test_case.name.clone(),
+                    if result.passed { "pass" } else { "FAIL" }.to_string(),
+                    result.test_case.circuit_size.to_string(),
+                    result.test_case.num_parties.to_string(),
+                    format!("{:.2?}", result.statistics.mean),
+                    format!("{:.2?}", result.statistics.median),
+                    format!("±{:.1}%", result.statistics.relative_error() * 100.0),
+                    format!("{}/{}", result.statistics.outliers.len(), result.statistics.samples.len()),
+                    result.metrics.communication_stats.total_bytes().to_string(),
+                ]
+            })
+            .collect()
+    }
+
+    /// One row per case whose energy was sampled
+    fn energy_rows(&self) -> Vec<Vec<String>> {
+        self.results
+            .iter()
+            .filter_map(|result| {
+                let energy = result.energy?;
+                Some(vec![
+                    result.test_case.name.clone(),
+                    energy.cpu_utilization().map_or("-".to_string(), |cores| format!("{:.2}", cores)),
+                    format!("{:.3}", energy.joules),
+                    format!("{:.2}", energy.watts()),
+                    energy.source.name().to_string(),
+                ])
+            })
+            .collect()
+    }
+
+    fn summary_rows(&self) -> Vec<Vec<String>> {
+        let rate = if self.test_count == 0 { 0.0 } else { self.passed_count as f64 / self.test_count as f64 * 100.0 };
+        vec![
+            vec!["Tests run".to_string(), self.test_count.to_string()],
+            vec!["Tests passed".to_string(), self.passed_count.to_string()],
+            vec!["Success rate".to_string(), format!("{:.1}%", rate)],
+            vec!["Average time".to_string(), format!("{:.2?}", self.average_time)],
+        ]
+    }
+
+    /// Total time against circuit size, one point per case
+    pub fn time_by_circuit_size(&self) -> Vec<(f64, f64)> {
+        self.results
+            .iter()
+            .map(|r| (r.test_case.circuit_size as f64, millis(r.metrics.total_time())))
+            .collect()
+    }
+
+    /// Bytes communicated against the number of parties, one point per case
+    pub fn bytes_by_parties(&self) -> Vec<(f64, f64)> {
+        self.results
+            .iter()
+            .map(|r| (r.test_case.num_parties as f64, r.metrics.communication_stats.total_bytes() as f64))
+            .collect()
+    }
+
+    pub fn render(&self, format: ReportFormat) -> String {
+        match format {
+            ReportFormat::Markdown => self.to_markdown(),
+            ReportFormat::Html => self.to_html(),
+        }
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::from("# Benchmark Summary\n\n");
+        markdown_table(&mut out, &["Metric", "Value"], &self.summary_rows());
+        out.push_str("## Results\n\n");
+        let header = ["Case", "Status", "Circuit size", "Parties", "Mean", "Median", "95% CI", "Outliers", "Bytes"];
+        markdown_table(&mut out, &header, &self.result_rows());
+        let energy = self.energy_rows();
+        if !energy.is_empty() {
+            out.push_str("## Energy per run
+
+");
+            markdown_table(&mut out, &ENERGY_HEADER, &energy);
+        }
+        let max = self.results.iter().map(|r| millis(r.metrics.total_time())).fold(0.0, f64::max);
+        out.push_str("```\n");
+        for result in &self.results {
+            let time = millis(result.metrics.total_time());
+            let _ = writeln!(out, "{:<24} {} {:.2} ms", result.test_case.name, text_bar(time, max), time);
+        }
+        out.push_str("```\n");
+        out
+    }
+
+    pub fn to_html(&self) -> String {
+        let mut body = String::new();
+        html_table(&mut body, &["Metric", "Value"], &self.summary_rows());
+        body.push_str("<h2>Results</h2>\n");
+        let header = ["Case", "Status", "Circuit size", "Parties", "Mean", "Median", "95% CI", "Outliers", "Bytes"];
+        html_table(&mut body, &header, &self.result_rows());
+        let energy = self.energy_rows();
+        if !energy.is_empty() {
+            body.push_str("<h2>Energy per run</h2>\n");
+            html_table(&mut body, &ENERGY_HEADER, &energy);
+        }
+        body.push_str(&svg_line_chart("Time vs circuit size", "constraints", "ms", &self.time_by_circuit_size()));
+        body.push_str(&svg_line_chart("Bytes vs parties", "parties", "bytes", &self.bytes_by_parties()));
+        html_page("Benchmark Summary", &body)
+    }
+}
+
+impl ProofSizeReport {
+    fn entry_rows(&self) -> Vec<Vec<String>> {
+        let total = self.total().max(1) as f64;
+        self.entries
+            .iter()
+            .map(|entry| {
+                vec![
+                    entry.component.clone(),
+                    entry.category.name().to_string(),
+                    entry.bytes.to_string(),
+                    format!("{:.1}%", entry.bytes as f64 / total * 100.0),
+                ]
+            })
+            .collect()
+    }
+
+    fn category_rows(&self) -> Vec<Vec<String>> {
+        let total = self.total().max(1) as f64;
+        let mut rows: Vec<Vec<String>> = self
+            .by_category()
+            .into_iter()
+            .map(|(category, bytes)| {
+                vec![category.name().to_string(), bytes.to_string(), format!("{:.1}%", bytes as f64 / total * 100.0)]
+            })
+            .collect();
+        rows.push(vec!["total".to_string(), self.total().to_string(), "100.0%".to_string()]);
+        rows
+    }
+
+    pub fn render(&self, format: ReportFormat) -> String {
+        match format {
+            ReportFormat::Markdown => self.to_markdown(),
+            ReportFormat::Html => self.to_html(),
+        }
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::from("# Proof Size Breakdown\n\n");
+        markdown_table(&mut out, &["Category", "Bytes", "Share"], &self.category_rows());
+        out.push_str("## Components\n\n");
+        markdown_table(&mut out, &["Component", "Category", "Bytes", "Share"], &self.entry_rows());
+        out
+    }
+
+    pub fn to_html(&self) -> String {
+        let mut body = String::new();
+        html_table(&mut body, &["Category", "Bytes", "Share"], &self.category_rows());
+        let bars: Vec<(String, f64)> = self
+            .by_category()
+            .into_iter()
+            .map(|(category, bytes)| (category.name().to_string(), bytes as f64))
+            .collect();
+        body.push_str(&svg_bar_chart("Bytes per category", "bytes", &bars));
+        body.push_str("<h2>Components</h2>\n");
+        html_table(&mut body, &["Component", "Category", "Bytes", "Share"], &self.entry_rows());
+        html_page("Proof Size Breakdown", &body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::evaluation::{
+        BenchmarkCase, BenchmarkResult, EnergySource, EnergyUsage, PerformanceMetrics, SampleStatistics,
+    };
+
+    #[test]
+    fn test_reports_render_tables_and_charts() {
+        let mut metrics = PerformanceMetrics::new();
+        metrics.record_timing("sharing".to_string(), Duration::from_millis(30));
+        metrics.record_timing("proving <kzg>".to_string(), Duration::from_millis(70));
+        let report = metrics.generate_report();
+
+        let markdown = report.render(ReportFormat::Markdown);
+        assert!(markdown.contains("| Phase | Wall | Share | CPU | Wait | Bound |"));
+        // Phases are listed slowest first
+        assert!(markdown.find("proving").unwrap() < markdown.find("sharing").unwrap());
+        let html = report.render(ReportFormat::Html);
+        assert!(html.contains("<svg") && html.contains("proving &lt;kzg&gt;"));
+        assert!(!html.contains("<kzg>"));
+
+        let results: Vec<BenchmarkResult> = [(100, 3, 5), (1000, 5, 20)]
+            .into_iter()
+            .map(|(circuit_size, num_parties, ms)| {
+                let mut metrics = PerformanceMetrics::new();
+                metrics.record_timing("total".to_string(), Duration::from_millis(ms));
+                metrics.communication_stats.add_round(num_parties * 64, 1);
+                BenchmarkResult {
+                    test_case: BenchmarkCase {
+                        name: format!("case|{}", circuit_size),
+                        description: String::new(),
+                        circuit_size,
+                        num_parties,
+                        expected_duration_ms: ms,
+                    },
+                    metrics,
+                    statistics: SampleStatistics::from_samples(&[Duration::from_millis(ms)]),
+                    energy: None,
+                    passed: true,
+                }
+            })
+            .collect();
+        let mut report = BenchmarkReport {
+            test_count: 2,
+            passed_count: 2,
+            average_time: Duration::from_millis(12),
+            results,
+        };
+        assert_eq!(report.bytes_by_parties(), vec![(3.0, 192.0), (5.0, 320.0)]);
+        assert!(report.render(ReportFormat::Markdown).contains("case\\|100"));
+        assert!(!report.render(ReportFormat::Markdown).contains("Energy per run"));
+
+        // Sampled energy gets its own table, per run of the case
+        report.results[1].energy = Some(EnergyUsage {
+            wall: Duration::from_millis(20),
+            cpu: Some(Duration::from_millis(40)),
+            joules: 0.2,
+            source: EnergySource::Estimated,
+        });
+        let markdown = report.render(ReportFormat::Markdown);
+        assert!(markdown.contains("| case\\|1000 | 2.00 | 0.200 | 10.00 | estimated |"));
+        assert!(!markdown.contains("| case\\|100 | 2.00"));
+        let html = report.render(ReportFormat::Html);
+        assert_eq!(html.matches("<polyline").count(), 2);
+        assert!(html.contains("Bytes vs parties"));
+    }
+}
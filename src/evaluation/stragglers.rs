@@ -0,0 +1,175 @@
+//! Workers of unequal speed
+//!
+//! MPC rounds are synchronized: no worker starts a layer of multiplications
+//! before every share of the previous one has arrived, so each layer costs
+//! as much as its slowest worker takes. `CostModel::worker_slowdowns` gives
+//! each worker a CPU slowdown factor, and `simulate_workers` plays the
+//! circuit layer by layer to show what a straggler costs and how long the
+//! others sit idle. Whether that cost dwarfs the round latency decides if a
+//! scheduler gains more from fewer, wider layers or from leaving the slow
+//! worker out of the committee. `simulate_quorum` plays the same circuit
+//! on an over-provisioned committee whose rounds wait only for the fastest
+//! `quorum` workers (see `Committee`).
+
+use std::time::Duration;
+
+use crate::evaluation::{CircuitShape, CostModel};
+
+/// Layer-by-layer run of a circuit on workers of unequal speed
+#[derive(Debug, Clone, PartialEq)]
+pub struct StragglerSimulation {
+    /// Synchronized layers, one per level of multiplicative depth
+    pub layers: usize,
+    /// Wall-clock time of one layer, round latency included
+    pub layer_time: Duration,
+    pub wall_time: Duration,
+    /// Wall-clock time with every worker at reference speed
+    pub unthrottled_time: Duration,
+    /// Time each worker spends waiting for the pacing one
+    pub idle: Vec<Duration>,
+    /// Worker that sets the pace, if the workers do not all run at the same speed
+    pub straggler: Option<usize>,
+    /// Gap between the pacing and the fastest worker in one layer
+    straggler_wait: Duration,
+    round_latency: Duration,
+}
+
+impl StragglerSimulation {
+    /// Time lost to workers slower than the reference core
+    pub fn straggler_overhead(&self) -> Duration {
+        self.wall_time.saturating_sub(self.unthrottled_time)
+    }
+
+    /// Fraction of the workers' time spent computing rather than waiting
+    pub fn utilization(&self) -> f64 {
+        let busy = self.wall_time.as_secs_f64() - self.layers as f64 * self.round_latency.as_secs_f64();
+        if busy <= 0.0 || self.idle.is_empty() {
+            return 1.0;
+        }
+        let idle: f64 = self.idle.iter().map(Duration::as_secs_f64).sum();
+        1.0 - idle / (busy * self.idle.len() as f64)
+    }
+
+    /// Whether waiting for the straggler costs more per layer than the round trip
+    ///
+    /// If so, merging layers barely helps; replacing the slow worker does.
+    pub fn is_straggler_bound(&self) -> bool {
+        self.straggler_wait > self.round_latency
+    }
+}
+
+impl CostModel {
+    /// Assign a CPU slowdown factor to each worker, in party order
+    ///
+    /// Workers past the end of `slowdowns` run at reference speed.
+    pub fn with_worker_slowdowns(mut self, slowdowns: Vec<f64>) -> Self {
+        self.worker_slowdowns = slowdowns;
+        self
+    }
+
+    /// Slowdown of each of the `num_parties` workers
+    pub fn worker_speeds(&self) -> Vec<f64> {
+        (0..self.num_parties.max(1))
+            .map(|party| self.worker_slowdowns.get(party).copied().unwrap_or(1.0).max(1e-9))
+            .collect()
+    }
+
+    /// Slowdown of the slowest worker, which every synchronized step waits for
+    pub(crate) fn straggler_factor(&self) -> f64 {
+        self.worker_speeds().into_iter().fold(1.0f64, f64::max)
+    }
+
+    /// The `count` fastest workers, fastest first
+    pub fn fastest_workers(&self, count: usize) -> Vec<usize> {
+        let speeds = self.worker_speeds();
+        let mut parties: Vec<usize> = (0..speeds.len()).collect();
+        parties.sort_by(|&a, &b| speeds[a].total_cmp(&speeds[b]));
+        parties.truncate(count);
+        parties
+    }
+
+    /// Run the gates of `shape` layer by layer on the configured workers
+    pub fn simulate_workers(&self, shape: &CircuitShape) -> StragglerSimulation {
+        self.simulate_quorum(shape, self.num_parties)
+    }
+
+    /// Like `simulate_workers`, with every layer waiting only for the fastest `quorum` workers
+    ///
+    /// Workers outside the quorum are never waited for and count no idle time.
+    pub fn simulate_quorum(&self, shape: &CircuitShape, quorum: usize) -> StragglerSimulation {
+        let speeds = self.worker_speeds();
+        let quorum = quorum.clamp(1, speeds.len());
+        let layers = shape.multiplicative_depth.max(1);
+        // Every party evaluates every gate on its own share
+        let gate_ns = shape.multiplications as f64 * self.multiplication.compute_ns
+            + shape.additions as f64 * self.addition.compute_ns;
+        let per_layer = gate_ns / layers as f64;
+
+        let straggler = self.fastest_workers(quorum).last().copied();
+        let slowest = speeds[straggler.expect("quorum is at least one")];
+        let fastest = speeds.iter().copied().fold(f64::MAX, f64::min);
+        let straggler = straggler.filter(|_| slowest > fastest);
+        let nanos = |ns: f64| Duration::from_nanos(ns as u64);
+        let layer_time = nanos(per_layer * slowest) + self.round_latency;
+        let idle = speeds
+            .iter()
+            .map(|speed| nanos(layers as f64 * per_layer * (slowest - speed).max(0.0)))
+            .collect();
+
+        StragglerSimulation {
+            layers,
+            layer_time,
+            wall_time: layer_time * layers as u32,
+            unthrottled_time: (nanos(per_layer) + self.round_latency) * layers as u32,
+            idle,
+            straggler,
+            straggler_wait: nanos(per_layer * (slowest - fastest)),
+            round_latency: self.round_latency,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::custom_circuits::CircuitTemplates;
+    use crate::evaluation::{BackendKind, ModeKind};
+    use ark_bls12_381::Fr;
+
+    type TestField = Fr;
+
+    #[test]
+    fn test_straggler_paces_every_layer() {
+        let shape = CircuitShape::of(&CircuitTemplates::repeated_squaring(TestField::from(3u64), 64));
+        let uniform = CostModel::new(3);
+        let even = uniform.simulate_workers(&shape);
+        assert_eq!(even.layers, 64);
+        assert_eq!(even.straggler, None);
+        assert_eq!(even.straggler_overhead(), Duration::ZERO);
+        assert_eq!(even.utilization(), 1.0);
+
+        let skewed = uniform.clone().with_worker_slowdowns(vec![1.0, 1.5, 4.0]);
+        let simulation = skewed.simulate_workers(&shape);
+        assert_eq!(simulation.straggler, Some(2));
+        assert_eq!(simulation.idle[2], Duration::ZERO);
+        assert!(simulation.idle[0] > simulation.idle[1]);
+        assert!(simulation.straggler_overhead() > Duration::ZERO);
+        assert!(simulation.utilization() < 1.0);
+        assert_eq!(skewed.fastest_workers(2), [0, 1]);
+
+        // The end-to-end estimate waits for the straggler as well
+        let estimate = |model: &CostModel| model.estimate_shape(&shape, BackendKind::Piop, ModeKind::Collaboration);
+        assert!(estimate(&skewed).prover_time > estimate(&uniform).prover_time);
+
+        // Over a WAN the round trip dominates; with no latency the straggler does
+        assert!(!simulation.is_straggler_bound());
+        let local = CostModel { round_latency: Duration::ZERO, ..skewed };
+        assert!(local.simulate_workers(&shape).is_straggler_bound());
+
+        // Waiting for two of the three leaves the slow worker behind
+        let quorum = local.simulate_quorum(&shape, 2);
+        assert_eq!(quorum.straggler, Some(1));
+        assert_eq!(quorum.idle[2], Duration::ZERO);
+        assert!(quorum.wall_time < local.simulate_workers(&shape).wall_time);
+    }
+}
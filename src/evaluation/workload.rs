@@ -0,0 +1,213 @@
+//! Synthetic circuits of a chosen shape
+//!
+//! The circuit templates cover a handful of fixed shapes. `WorkloadSpec`
+//! generates random satisfied circuits with a given number of constraints,
+//! share of multiplications, multiplicative depth and fan-out distribution,
+//! so benchmarks can sweep one dimension of the shape at a time and fuzz
+//! targets can reach shapes no template has. `CircuitShape::of` reports the
+//! generated circuit's counts and depth exactly as specified.
+
+use ark_ff::PrimeField;
+use ark_std::rand::Rng;
+
+use crate::custom_circuits::{CustomCircuit, Variable};
+
+/// Attempts at drawing an operand within the depth bound before falling
+/// back to a uniform choice among the wires that are
+const OPERAND_ATTEMPTS: usize = 8;
+
+/// How gates pick the wires they read, which sets how many gates read
+/// each wire
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FanOut {
+    /// Operands are drawn uniformly from every wire built so far
+    Uniform,
+    /// Operands are drawn from the last `window` wires, giving long thin
+    /// circuits where each wire feeds a few nearby gates
+    Recent { window: usize },
+    /// The first operand of every gate is one of the first `hubs` wires,
+    /// which each feed a large share of the circuit
+    Hubs { hubs: usize },
+}
+
+/// Shape of a synthetic circuit
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkloadSpec {
+    pub constraints: usize,
+    /// Share of the constraints that are multiplications, in [0, 1]
+    pub multiplication_ratio: f64,
+    /// Multiplicative depth; clamped between one (if there is any
+    /// multiplication) and the number of multiplications
+    pub depth: usize,
+    /// Private input wires the gates start from
+    pub inputs: usize,
+    pub fan_out: FanOut,
+}
+
+impl WorkloadSpec {
+    /// Half multiplications, depth an eighth of the constraints, four
+    /// inputs and uniform fan-out
+    pub fn new(constraints: usize) -> Self {
+        Self {
+            constraints,
+            multiplication_ratio: 0.5,
+            depth: (constraints / 8).max(1),
+            inputs: 4,
+            fan_out: FanOut::Uniform,
+        }
+    }
+
+    pub fn multiplications(&self) -> usize {
+        let ratio = self.multiplication_ratio.clamp(0.0, 1.0);
+        ((self.constraints as f64 * ratio).round() as usize).min(self.constraints)
+    }
+
+    pub fn additions(&self) -> usize {
+        self.constraints - self.multiplications()
+    }
+
+    /// Depth the generated circuit has
+    pub fn effective_depth(&self) -> usize {
+        let multiplications = self.multiplications();
+        self.depth.clamp(multiplications.min(1), multiplications)
+    }
+
+    /// Generate a satisfied circuit of this shape
+    ///
+    /// Every wire is a private witness except the output of the last gate,
+    /// which is the only public input. A chain of `effective_depth`
+    /// multiplications, spread evenly over the circuit, sets the depth;
+    /// every other multiplication reads wires below that depth.
+    pub fn generate<F: PrimeField, R: Rng + ?Sized>(&self, rng: &mut R) -> CustomCircuit<F> {
+        let mut circuit = CustomCircuit::new(format!("synthetic_{}", self.constraints));
+        let multiplications = self.multiplications();
+        let depth = self.effective_depth();
+        let mut wires = Wires { wires: Vec::new(), levels: vec![Vec::new(); depth + 1] };
+        for _ in 0..self.inputs.max(1) {
+            let value = F::rand(rng);
+            wires.push(circuit.add_private_witness(value), value, 0);
+        }
+
+        let mut kinds: Vec<bool> = (0..self.constraints).map(|i| i < multiplications).collect();
+        for i in (1..kinds.len()).rev() {
+            kinds.swap(i, rng.gen_range(0..=i));
+        }
+
+        // The chain's last wire; it starts at an input of depth zero
+        let mut spine = 0;
+        let mut multiplication = 0;
+        for (gate, &is_multiplication) in kinds.iter().enumerate() {
+            let on_spine = is_multiplication && (multiplication + 1) * depth / multiplications > multiplication * depth / multiplications;
+            let bound = match (is_multiplication, on_spine) {
+                (true, true) => wires.wires[spine].2,
+                (true, false) => depth - 1,
+                (false, _) => depth,
+            };
+            let a = if on_spine { spine } else { wires.pick(self.fan_out, true, bound, rng) };
+            let b = wires.pick(self.fan_out, false, bound, rng);
+            let ((var_a, value_a, depth_a), (var_b, value_b, depth_b)) = (wires.wires[a], wires.wires[b]);
+
+            let value = if is_multiplication { value_a * value_b } else { value_a + value_b };
+            let output = if gate + 1 == kinds.len() { circuit.add_public_input(value) } else { circuit.add_private_witness(value) };
+            if is_multiplication {
+                circuit.add_multiplication_constraint(var_a, var_b, output);
+                multiplication += 1;
+            } else {
+                circuit.add_addition_constraint(var_a, var_b, output);
+            }
+            wires.push(output, value, depth_a.max(depth_b) + usize::from(is_multiplication));
+            if on_spine {
+                spine = wires.wires.len() - 1;
+            }
+        }
+        circuit
+    }
+}
+
+/// Wires built so far with their values and multiplicative depths, and
+/// their indices grouped by depth
+struct Wires<F> {
+    wires: Vec<(Variable, F, usize)>,
+    levels: Vec<Vec<usize>>,
+}
+
+impl<F: Copy> Wires<F> {
+    fn push(&mut self, variable: Variable, value: F, depth: usize) {
+        self.levels[depth].push(self.wires.len());
+        self.wires.push((variable, value, depth));
+    }
+
+    /// Index of an operand of depth at most `bound`, drawn by `fan_out`
+    fn pick<R: Rng + ?Sized>(&self, fan_out: FanOut, first: bool, bound: usize, rng: &mut R) -> usize {
+        let len = self.wires.len();
+        for _ in 0..OPERAND_ATTEMPTS {
+            let index = match fan_out {
+                FanOut::Uniform => rng.gen_range(0..len),
+                FanOut::Recent { window } => rng.gen_range(len.saturating_sub(window.max(1))..len),
+                FanOut::Hubs { hubs } if first => rng.gen_range(0..hubs.clamp(1, len)),
+                FanOut::Hubs { .. } => rng.gen_range(0..len),
+            };
+            if self.wires[index].2 <= bound {
+                return index;
+            }
+        }
+        // The inputs sit at depth zero, so some wire is always allowed
+        let levels = &self.levels[..=bound];
+        let mut choice = rng.gen_range(0..levels.iter().map(Vec::len).sum::<usize>());
+        for level in levels {
+            if choice < level.len() {
+                return level[choice];
+            }
+            choice -= level.len();
+        }
+        unreachable!("choice is below the number of allowed wires")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::evaluation::CircuitShape;
+    use ark_bls12_381::Fr;
+    use ark_std::test_rng;
+    use std::collections::HashMap;
+
+    type TestField = Fr;
+
+    #[test]
+    fn test_generated_circuits_have_the_specified_shape() {
+        let mut rng = test_rng();
+        let max_fan_out = |circuit: &CustomCircuit<TestField>| {
+            let mut reads: HashMap<Variable, usize> = HashMap::new();
+            let gates = circuit.multiplication_constraints.iter().chain(&circuit.addition_constraints);
+            for &(a, b, _) in gates {
+                *reads.entry(a).or_default() += 1;
+                *reads.entry(b).or_default() += 1;
+            }
+            reads.into_values().max().unwrap_or(0)
+        };
+
+        for (ratio, depth, fan_out) in [
+            (0.5, 10, FanOut::Uniform),
+            (0.9, 1, FanOut::Recent { window: 4 }),
+            (0.25, 50, FanOut::Hubs { hubs: 2 }),
+            (0.0, 5, FanOut::Uniform),
+            (1.0, 200, FanOut::Recent { window: 1 }),
+        ] {
+            let spec = WorkloadSpec { multiplication_ratio: ratio, depth, fan_out, ..WorkloadSpec::new(200) };
+            let circuit = spec.generate::<TestField, _>(&mut rng);
+            assert!(circuit.verify_constraints());
+            assert_eq!(circuit.public_inputs.len(), 1);
+            let shape = CircuitShape::of(&circuit);
+            assert_eq!(shape.multiplications, spec.multiplications());
+            assert_eq!(shape.additions, spec.additions());
+            assert_eq!(shape.multiplicative_depth, spec.effective_depth());
+        }
+
+        // Hub wires are read by a large share of the gates, recent windows keep fan-out low
+        let hubs = WorkloadSpec { fan_out: FanOut::Hubs { hubs: 2 }, ..WorkloadSpec::new(200) };
+        let recent = WorkloadSpec { fan_out: FanOut::Recent { window: 2 }, ..WorkloadSpec::new(200) };
+        assert!(max_fan_out(&hubs.generate(&mut rng)) >= 80);
+        assert!(max_fan_out(&recent.generate(&mut rng)) < max_fan_out(&hubs.generate(&mut rng)));
+    }
+}
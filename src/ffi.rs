@@ -0,0 +1,222 @@
+//! C API for embedding the verifier
+//!
+//! Built with the `ffi` feature; `include/eos_verifier.h` declares the same
+//! items for C and C++. Only verification is exposed, over BLS12-381, so a
+//! `default-features = false, features = ["ffi"]` build carries no MPC or
+//! benchmark code.
+//!
+//! All inputs are byte strings in the crate's wire format:
+//!
+//! - the verification key is `VerifierKey::to_bytes`, which carries the
+//!   circuit the proof's constraint proof is checked against,
+//! - the proof is `DelegationResult::transcript_bytes`,
+//! - public inputs are `eos_field_size()`-byte field encodings, concatenated.
+//!
+//! The structs and status codes below are part of the ABI: fields and
+//! variants are only ever appended, and `EOS_ABI_VERSION` is bumped when
+//! that happens.
+
+use std::ffi::{c_char, CStr};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use ark_bls12_381::{Bls12_381, Fr};
+
+use crate::protocol::{decode_public_inputs, verify_transcript, BundleError, VerifierKey};
+use crate::serialization::{field_size, WireFormat};
+
+/// Version of the C ABI described by `include/eos_verifier.h`
+pub const EOS_ABI_VERSION: u32 = 1;
+
+/// Borrowed byte string; `data` may be null only when `len` is zero
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct EosBytes {
+    pub data: *const u8,
+    pub len: usize,
+}
+
+/// Outcome of `eos_verify`
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EosStatus {
+    /// The proof verifies
+    Accepted = 0,
+    /// The proof is well-formed but does not verify, or was made for another key
+    Rejected = 1,
+    /// A non-empty argument has a null `data` pointer
+    NullPointer = 2,
+    /// The verification key does not decode
+    InvalidKey = 3,
+    /// The proof does not decode
+    InvalidProof = 4,
+    /// The public inputs are not a whole number of canonical field elements
+    InvalidPublicInputs = 5,
+    /// The verifier panicked; this is a bug
+    Internal = 6,
+}
+
+impl EosStatus {
+    fn message(self) -> &'static CStr {
+        match self {
+            EosStatus::Accepted => c"accepted",
+            EosStatus::Rejected => c"rejected",
+            EosStatus::NullPointer => c"null pointer argument",
+            EosStatus::InvalidKey => c"malformed verification key",
+            EosStatus::InvalidProof => c"malformed proof",
+            EosStatus::InvalidPublicInputs => c"malformed public inputs",
+            EosStatus::Internal => c"internal error",
+        }
+    }
+}
+
+impl EosBytes {
+    /// # Safety
+    ///
+    /// `data` must point to `len` readable bytes that outlive the slice.
+    unsafe fn as_slice<'a>(self) -> Result<&'a [u8], EosStatus> {
+        match (self.data.is_null(), self.len) {
+            (_, 0) => Ok(&[]),
+            (true, _) => Err(EosStatus::NullPointer),
+            (false, len) => Ok(std::slice::from_raw_parts(self.data, len)),
+        }
+    }
+}
+
+/// ABI version of the loaded library, to compare with `EOS_ABI_VERSION`
+#[no_mangle]
+pub extern "C" fn eos_abi_version() -> u32 {
+    EOS_ABI_VERSION
+}
+
+/// Size in bytes of one encoded public input
+#[no_mangle]
+pub extern "C" fn eos_field_size() -> usize {
+    field_size::<Fr>()
+}
+
+/// Static, NUL-terminated description of a status code
+#[no_mangle]
+pub extern "C" fn eos_status_message(status: i32) -> *const c_char {
+    let status = match status {
+        0 => EosStatus::Accepted,
+        1 => EosStatus::Rejected,
+        2 => EosStatus::NullPointer,
+        3 => EosStatus::InvalidKey,
+        4 => EosStatus::InvalidProof,
+        5 => EosStatus::InvalidPublicInputs,
+        6 => EosStatus::Internal,
+        _ => return c"unknown status".as_ptr().cast(),
+    };
+    status.message().as_ptr().cast()
+}
+
+/// Verify a delegation proof against a verification key and public inputs
+///
+/// `Accepted` means the committed witness satisfies the key's circuit, not
+/// only that its commitment opens.
+///
+/// # Safety
+///
+/// Each argument's `data` must point to `len` readable bytes for the
+/// duration of the call, or be null with `len` zero. The bytes are only
+/// read, and nothing is retained after the call returns.
+#[no_mangle]
+pub unsafe extern "C" fn eos_verify(proof: EosBytes, verification_key: EosBytes, public_inputs: EosBytes) -> EosStatus {
+    let inputs = match (proof.as_slice(), verification_key.as_slice(), public_inputs.as_slice()) {
+        (Ok(proof), Ok(key), Ok(inputs)) => (proof, key, inputs),
+        _ => return EosStatus::NullPointer,
+    };
+    catch_unwind(AssertUnwindSafe(|| verify(inputs.0, inputs.1, inputs.2))).unwrap_or(EosStatus::Internal)
+}
+
+fn verify(proof: &[u8], key: &[u8], public_inputs: &[u8]) -> EosStatus {
+    let Ok(key) = VerifierKey::<Bls12_381>::from_bytes(key) else {
+        return EosStatus::InvalidKey;
+    };
+    let Ok(public_inputs) = decode_public_inputs::<Fr>(public_inputs) else {
+        return EosStatus::InvalidPublicInputs;
+    };
+    match verify_transcript(&key, proof, &public_inputs, &mut rand::thread_rng()) {
+        Ok(true) => EosStatus::Accepted,
+        Ok(false) => EosStatus::Rejected,
+        Err(BundleError::Malformed(_) | BundleError::Streaming(_)) => EosStatus::InvalidProof,
+        Err(BundleError::SeedContextMismatch) => EosStatus::Rejected,
+    }
+}
+
+#[cfg(all(test, feature = "delegator"))]
+mod tests {
+    use super::*;
+    use crate::mpc::{CollaborationMode, ExecCircuit, ShamirSecretSharing};
+    use crate::protocol::EOSProtocol;
+    use crate::serialization::write_field;
+    use ark_relations::r1cs::ConstraintSystem;
+    use ark_std::test_rng;
+
+    type TestField = Fr;
+    type Protocol = EOSProtocol<Bls12_381, TestField, ShamirSecretSharing<TestField>, CollaborationMode>;
+
+    fn bytes(slice: &[u8]) -> EosBytes {
+        EosBytes { data: slice.as_ptr(), len: slice.len() }
+    }
+
+    #[test]
+    fn test_c_api_verifies_transcripts() {
+        let mut rng = test_rng();
+        let executor = ExecCircuit::new(1, 3, ShamirSecretSharing::new());
+        let mut protocol = Protocol::new(executor, CollaborationMode::new(2, true, false), 128, &mut rng);
+        let circuit = ConstraintSystem::new();
+        protocol.preprocessing_state = Some(Protocol::preprocessing(&circuit, 128, &mut rng).unwrap());
+        let witness: Vec<TestField> = (1..=4u64).map(TestField::from).collect();
+        let result = protocol
+            .delegate_computation_seeded(&circuit, &witness, &[TestField::from(12u64)], [1u8; 32])
+            .unwrap();
+
+        let key = protocol.export_verifier_key().unwrap().to_bytes();
+        let proof = result.transcript_bytes();
+        let mut inputs = Vec::new();
+        write_field(&mut inputs, &TestField::from(12u64));
+        assert_eq!(inputs.len(), eos_field_size());
+
+        let verify = |proof: EosBytes, key: EosBytes, inputs: EosBytes| unsafe { eos_verify(proof, key, inputs) };
+        assert_eq!(verify(bytes(&proof), bytes(&key), bytes(&inputs)), EosStatus::Accepted);
+        let mut other = inputs.clone();
+        other[0] ^= 1;
+        assert_eq!(verify(bytes(&proof), bytes(&key), bytes(&other)), EosStatus::Rejected);
+        assert_eq!(verify(bytes(&proof), bytes(&key), bytes(&inputs[1..])), EosStatus::InvalidPublicInputs);
+        assert_eq!(verify(bytes(&proof), bytes(&key), bytes(&[0xff; 32])), EosStatus::InvalidPublicInputs);
+        assert_eq!(verify(bytes(&proof), bytes(&key[1..]), bytes(&inputs)), EosStatus::InvalidKey);
+        assert_eq!(verify(bytes(&proof[..10]), bytes(&key), bytes(&inputs)), EosStatus::InvalidProof);
+        let null = EosBytes { data: std::ptr::null(), len: 32 };
+        assert_eq!(verify(bytes(&proof), bytes(&key), null), EosStatus::NullPointer);
+
+        assert_eq!(eos_abi_version(), EOS_ABI_VERSION);
+        let message = unsafe { CStr::from_ptr(eos_status_message(EosStatus::InvalidKey as i32)) };
+        assert_eq!(message.to_str().unwrap(), "malformed verification key");
+        let unknown = unsafe { CStr::from_ptr(eos_status_message(-1)) };
+        assert_eq!(unknown.to_str().unwrap(), "unknown status");
+    }
+
+    #[test]
+    fn test_c_api_rejects_unsatisfying_witness() {
+        let mut rng = test_rng();
+        // x·x = y with y public
+        let cs = ConstraintSystem::<TestField>::new_ref();
+        let y = cs.new_input_variable(|| Ok(TestField::from(49u64))).unwrap();
+        let x = cs.new_witness_variable(|| Ok(TestField::from(7u64))).unwrap();
+        cs.enforce_constraint(ark_relations::lc!() + x, ark_relations::lc!() + x, ark_relations::lc!() + y).unwrap();
+        let circuit = cs.into_inner().unwrap();
+        let executor = ExecCircuit::new(1, 3, ShamirSecretSharing::new());
+        let mut protocol = Protocol::new(executor, CollaborationMode::new(2, true, false), 128, &mut rng);
+        protocol.preprocessing_state = Some(Protocol::preprocessing(&circuit, 128, &mut rng).unwrap());
+        let key = protocol.export_verifier_key().unwrap().to_bytes();
+        let mut inputs = Vec::new();
+        write_field(&mut inputs, &TestField::from(49u64));
+
+        let verify = |proof: &[u8]| unsafe { eos_verify(bytes(proof), bytes(&key), bytes(&inputs)) };
+        let honest = protocol.delegate_computation(&circuit, &[TestField::from(7u64)], &[TestField::from(49u64)], &mut rng);
+        assert_eq!(verify(&honest.unwrap().transcript_bytes()), EosStatus::Accepted);
+        let cheating = protocol.delegate_computation(&circuit, &[TestField::from(8u64)], &[TestField::from(49u64)], &mut rng);
+        assert_eq!(verify(&cheating.unwrap().transcript_bytes()), EosStatus::Rejected);
+    }
+}
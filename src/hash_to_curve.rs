@@ -0,0 +1,98 @@
+//! Hashing domain tags to curve points
+//!
+//! Protocol generators are derived from public domain tags, so nobody knows
+//! a discrete-log relation between them and `G::generator()` or each other.
+//! This matters wherever two generators must be independent: the blinding
+//! generator of a Pedersen commitment, the identity-key generator of the
+//! delegator's encryption key, and the `h` side of the KZG verification key.
+//!
+//! `hash_to_curve` works for any arkworks curve: it expands the tag with
+//! `expand_message_xmd` (see `crate::sampling`), reads the bytes as a point
+//! with `AffineRepr::from_random_bytes`, retries with the next counter until
+//! that succeeds, and clears the cofactor. It is not constant time, which
+//! is fine for public inputs like domain tags. On BLS12-381 G1,
+//! `hash_to_bls12_381_g1` is the simplified SWU encoding of RFC 9380
+//! (suite `BLS12381G1_XMD:SHA-256_SSWU_RO_`).
+
+use ark_bls12_381::{g1, G1Affine, G1Projective};
+use ark_ec::hashing::curve_maps::wb::WBMap;
+use ark_ec::hashing::map_to_curve_hasher::MapToCurveBasedHasher;
+use ark_ec::hashing::HashToCurve;
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::field_hashers::DefaultFieldHasher;
+use ark_serialize::CanonicalSerialize;
+use sha2::Sha256;
+
+use crate::sampling::expand_message_xmd;
+
+/// Domain tag of the generator behind the delegator's identity keys
+pub const IDENTITY_GENERATOR_DOMAIN: &[u8] = b"EOS-identity-generator-v1";
+
+/// Domain tag of Pedersen blinding generators
+pub const PEDERSEN_GENERATOR_DOMAIN: &[u8] = b"EOS-pedersen-generators-v1";
+
+/// A point of the prime-order subgroup determined by `dst` and `message`
+pub fn hash_to_curve<G: CurveGroup>(dst: &[u8], message: &[u8]) -> G::Affine {
+    // Twice the compressed size leaves room for every coordinate and flag
+    let len = 2 * G::Affine::generator().compressed_size();
+    (0u32..)
+        .filter_map(|counter| {
+            let bytes = expand_message_xmd(&[message, &counter.to_be_bytes()].concat(), dst, len);
+            G::Affine::from_random_bytes(&bytes)
+                .map(|point| point.clear_cofactor())
+                .filter(|point| !point.is_zero())
+        })
+        .next()
+        .expect("about half of all candidates lie on the curve")
+}
+
+/// `count` independent generators, the `i`-th hashed from `I2OSP(i, 8)`
+pub fn derive_generators<G: CurveGroup>(dst: &[u8], count: usize) -> Vec<G::Affine> {
+    (0..count as u64).map(|i| hash_to_curve::<G>(dst, &i.to_be_bytes())).collect()
+}
+
+/// Generator of the delegator's identity keys
+pub fn identity_generator<G: CurveGroup>() -> G::Affine {
+    hash_to_curve::<G>(IDENTITY_GENERATOR_DOMAIN, b"")
+}
+
+/// RFC 9380 hash to BLS12-381 G1 with the simplified SWU map
+pub fn hash_to_bls12_381_g1(dst: &[u8], message: &[u8]) -> G1Affine {
+    MapToCurveBasedHasher::<G1Projective, DefaultFieldHasher<Sha256, 128>, WBMap<g1::Config>>::new(dst)
+        .and_then(|hasher| hasher.hash(message))
+        .expect("the SWU map is defined for every field element")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::{Fq, G2Projective};
+    use ark_ed_on_bls12_381::EdwardsProjective;
+    use ark_ff::PrimeField;
+
+    fn fq(hex: &str) -> Fq {
+        let bytes: Vec<u8> = (0..hex.len()).step_by(2).map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap()).collect();
+        Fq::from_be_bytes_mod_order(&bytes)
+    }
+
+    #[test]
+    fn test_hashed_generators_are_independent_and_reproducible() {
+        // RFC 9380, appendix J.9.1, msg = ""
+        let point = hash_to_bls12_381_g1(b"QUUX-V01-CS02-with-BLS12381G1_XMD:SHA-256_SSWU_RO_", b"");
+        let x = fq("052926add2207b76ca4fa57a8734416c8dc95e24501772c814278700eed6d1e4e8cf62d9c09db0fac349612b759e79a1");
+        let y = fq("08ba738453bfed09cb546dbb0783dbb3a5f1f566ed67bb6be0e8c67e2e81a4cc68ee29813bb7994998f3eae0c9c6a265");
+        assert_eq!(point, G1Affine::new(x, y));
+
+        let generators = derive_generators::<G1Projective>(PEDERSEN_GENERATOR_DOMAIN, 3);
+        assert_eq!(generators, derive_generators::<G1Projective>(PEDERSEN_GENERATOR_DOMAIN, 3));
+        assert!(generators.iter().all(|g| g.is_in_correct_subgroup_assuming_on_curve()));
+        assert_ne!(generators[0], generators[1]);
+        assert_ne!(generators[0], G1Affine::generator());
+
+        // Works on curves with extension-field coordinates and cofactors
+        let h = identity_generator::<G2Projective>();
+        assert!(h.is_on_curve() && h.is_in_correct_subgroup_assuming_on_curve());
+        let jubjub = identity_generator::<EdwardsProjective>();
+        assert!(jubjub.is_in_correct_subgroup_assuming_on_curve() && !jubjub.is_zero());
+    }
+}
@@ -0,0 +1,347 @@
+//! Witness and public input import
+//!
+//! Users who do not write Rust supply circuit inputs as files. An
+//! `InputSchema` declares every input by name with its type and visibility;
+//! values are then read from a JSON object or from `name,value` CSV rows,
+//! checked against the schema and laid out as the circuit expects: private
+//! witnesses first, then public inputs, each in schema order.
+//!
+//! A schema is itself JSON:
+//!
+//! ```text
+//! {"inputs": [
+//!     {"name": "balance", "type": "u64"},
+//!     {"name": "is_member", "type": "bool"},
+//!     {"name": "root", "type": "field", "visibility": "public"}
+//! ]}
+//! ```
+//!
+//! Values are decimal numbers, `0x`-prefixed hex strings or booleans.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use ark_ff::PrimeField;
+use num_bigint::BigUint;
+use serde_json::Value;
+
+use crate::custom_circuits::{CustomCircuit, Variable};
+
+/// Type of an input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputType {
+    /// Any canonical field element
+    Field,
+    Bool,
+    /// Unsigned integer of at most `bits` bits
+    UInt { bits: u32 },
+}
+
+impl std::str::FromStr for InputType {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, ()> {
+        match s {
+            "field" => Ok(InputType::Field),
+            "bool" => Ok(InputType::Bool),
+            _ => {
+                let bits = s.strip_prefix('u').and_then(|bits| bits.parse().ok()).ok_or(())?;
+                if bits == 0 {
+                    return Err(());
+                }
+                Ok(InputType::UInt { bits })
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for InputType {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            InputType::Field => write!(f, "field"),
+            InputType::Bool => write!(f, "bool"),
+            InputType::UInt { bits } => write!(f, "u{}", bits),
+        }
+    }
+}
+
+/// Whether an input is a private witness or a public input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Visibility {
+    Private,
+    Public,
+}
+
+/// Declaration of one input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputSpec {
+    pub name: String,
+    pub ty: InputType,
+    pub visibility: Visibility,
+}
+
+/// Declared inputs of a circuit
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InputSchema {
+    pub inputs: Vec<InputSpec>,
+}
+
+impl InputSchema {
+    /// Parse a JSON schema; inputs are private unless declared public
+    pub fn from_json(text: &str) -> Result<Self, InputError> {
+        let root: Value = serde_json::from_str(text).map_err(|e| InputError::Syntax(e.to_string()))?;
+        let entries = root
+            .get("inputs")
+            .and_then(Value::as_array)
+            .ok_or_else(|| InputError::Schema("expected an \"inputs\" array".to_string()))?;
+        let mut schema = InputSchema::default();
+        for entry in entries {
+            let field = |key: &str| entry.get(key).and_then(Value::as_str);
+            let name = field("name").ok_or_else(|| InputError::Schema("input without a name".to_string()))?;
+            let ty = field("type")
+                .ok_or_else(|| InputError::Schema(format!("input '{}' has no type", name)))?;
+            let ty = ty
+                .parse()
+                .map_err(|_| InputError::Schema(format!("input '{}' has unknown type '{}'", name, ty)))?;
+            let visibility = match field("visibility") {
+                None | Some("private") => Visibility::Private,
+                Some("public") => Visibility::Public,
+                Some(other) => {
+                    return Err(InputError::Schema(format!("input '{}' has unknown visibility '{}'", name, other)))
+                }
+            };
+            schema.push(name, ty, visibility)?;
+        }
+        Ok(schema)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, InputError> {
+        Self::from_json(&std::fs::read_to_string(path)?)
+    }
+
+    /// Declare an input; names must be unique
+    pub fn push(&mut self, name: &str, ty: InputType, visibility: Visibility) -> Result<(), InputError> {
+        if self.inputs.iter().any(|spec| spec.name == name) {
+            return Err(InputError::Duplicate(name.to_string()));
+        }
+        self.inputs.push(InputSpec { name: name.to_string(), ty, visibility });
+        Ok(())
+    }
+
+    /// Read values from a JSON object mapping names to values
+    pub fn read_json<F: PrimeField>(&self, text: &str) -> Result<Assignment<F>, InputError> {
+        let root: Value = serde_json::from_str(text).map_err(|e| InputError::Syntax(e.to_string()))?;
+        let object = root
+            .as_object()
+            .ok_or_else(|| InputError::Syntax("expected an object of name: value pairs".to_string()))?;
+        let values = object.iter().map(|(name, value)| {
+            let text = match value {
+                Value::String(s) => s.clone(),
+                Value::Number(_) | Value::Bool(_) => value.to_string(),
+                _ => return Err(InputError::invalid(name, &value.to_string(), "expected a number, string or boolean")),
+            };
+            Ok((name.clone(), text))
+        });
+        self.assign(values.collect::<Result<Vec<_>, _>>()?)
+    }
+
+    /// Read values from CSV rows `name,value`; a `name,value` header, blank
+    /// lines and `#` comments are skipped
+    pub fn read_csv<F: PrimeField>(&self, text: &str) -> Result<Assignment<F>, InputError> {
+        let mut values = Vec::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() || (number == 0 && line.replace(' ', "") == "name,value") {
+                continue;
+            }
+            let (name, value) = line
+                .split_once(',')
+                .ok_or_else(|| InputError::Syntax(format!("expected 'name,value' on line {}", number + 1)))?;
+            values.push((name.trim().to_string(), value.trim().trim_matches('"').to_string()));
+        }
+        self.assign(values)
+    }
+
+    /// Read a value file, choosing the format by its extension
+    pub fn load_values<F: PrimeField>(&self, path: impl AsRef<Path>) -> Result<Assignment<F>, InputError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)?;
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("csv") => self.read_csv(&text),
+            _ => self.read_json(&text),
+        }
+    }
+
+    /// Validate named values against the schema and lay them out
+    pub fn assign<F: PrimeField>(&self, values: Vec<(String, String)>) -> Result<Assignment<F>, InputError> {
+        let mut given: HashMap<String, String> = HashMap::new();
+        for (name, value) in values {
+            if !self.inputs.iter().any(|spec| spec.name == name) {
+                return Err(InputError::Unknown(name));
+            }
+            if given.insert(name.clone(), value).is_some() {
+                return Err(InputError::Duplicate(name));
+            }
+        }
+        let mut assignment = Assignment { private: Vec::new(), public: Vec::new() };
+        for visibility in [Visibility::Private, Visibility::Public] {
+            for spec in self.inputs.iter().filter(|spec| spec.visibility == visibility) {
+                let text = given.get(&spec.name).ok_or_else(|| InputError::Missing(spec.name.clone()))?;
+                let value = parse_value::<F>(spec, text)?;
+                match visibility {
+                    Visibility::Private => assignment.private.push((spec.name.clone(), value)),
+                    Visibility::Public => assignment.public.push((spec.name.clone(), value)),
+                }
+            }
+        }
+        Ok(assignment)
+    }
+}
+
+fn parse_value<F: PrimeField>(spec: &InputSpec, text: &str) -> Result<F, InputError> {
+    let invalid = |reason: &str| InputError::invalid(&spec.name, text, reason);
+    let number = match text {
+        "true" => BigUint::from(1u64),
+        "false" => BigUint::from(0u64),
+        _ => match text.strip_prefix("0x") {
+            Some(hex) => BigUint::parse_bytes(hex.as_bytes(), 16),
+            None => BigUint::parse_bytes(text.as_bytes(), 10),
+        }
+        .ok_or_else(|| invalid("not a non-negative integer"))?,
+    };
+    let fits = match spec.ty {
+        InputType::Field => number < F::MODULUS.into(),
+        InputType::Bool => number.bits() <= 1,
+        InputType::UInt { bits } => number.bits() <= bits as u64 && number < F::MODULUS.into(),
+    };
+    if !fits {
+        return Err(invalid(&format!("out of range for {}", spec.ty)));
+    }
+    Ok(F::from_le_bytes_mod_order(&number.to_bytes_le()))
+}
+
+/// Validated inputs in circuit order
+#[derive(Debug, Clone, PartialEq)]
+pub struct Assignment<F: PrimeField> {
+    pub private: Vec<(String, F)>,
+    pub public: Vec<(String, F)>,
+}
+
+impl<F: PrimeField> Assignment<F> {
+    /// Circuit variable of an input in the circuit built by `to_circuit`
+    pub fn index(&self, name: &str) -> Option<Variable> {
+        let position = |list: &[(String, F)]| list.iter().position(|(n, _)| n == name);
+        position(&self.private)
+            .map(Variable::Witness)
+            .or_else(|| position(&self.public).map(Variable::public_input))
+    }
+
+    pub fn value(&self, name: &str) -> Option<F> {
+        self.private.iter().chain(&self.public).find(|(n, _)| n == name).map(|(_, v)| *v)
+    }
+
+    /// A circuit holding the inputs and no constraints yet; constraints
+    /// refer to inputs through `index`
+    pub fn to_circuit(&self, name: &str) -> CustomCircuit<F> {
+        let mut circuit = CustomCircuit::new(name.to_string());
+        circuit.private_witnesses = self.private.iter().map(|(_, v)| *v).collect();
+        circuit.public_inputs = self.public.iter().map(|(_, v)| *v).collect();
+        circuit.num_variables = self.private.len() + self.public.len();
+        circuit
+    }
+
+    /// Names in variable order
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.private.iter().chain(&self.public).map(|(name, _)| name.as_str())
+    }
+}
+
+/// Input import error types
+#[derive(Debug)]
+pub enum InputError {
+    Io(std::io::Error),
+    Syntax(String),
+    Schema(String),
+    Duplicate(String),
+    Unknown(String),
+    Missing(String),
+    InvalidValue { name: String, value: String, reason: String },
+}
+
+impl InputError {
+    fn invalid(name: &str, value: &str, reason: &str) -> Self {
+        InputError::InvalidValue { name: name.to_string(), value: value.to_string(), reason: reason.to_string() }
+    }
+}
+
+impl std::fmt::Display for InputError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            InputError::Io(e) => write!(f, "Input I/O error: {}", e),
+            InputError::Syntax(e) => write!(f, "Malformed input file: {}", e),
+            InputError::Schema(e) => write!(f, "Invalid input schema: {}", e),
+            InputError::Duplicate(name) => write!(f, "Input '{}' given more than once", name),
+            InputError::Unknown(name) => write!(f, "Input '{}' is not declared in the schema", name),
+            InputError::Missing(name) => write!(f, "No value for input '{}'", name),
+            InputError::InvalidValue { name, value, reason } => {
+                write!(f, "Invalid value '{}' for input '{}': {}", value, name, reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for InputError {}
+
+impl From<std::io::Error> for InputError {
+    fn from(e: std::io::Error) -> Self {
+        InputError::Io(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::Fr;
+
+    type TestField = Fr;
+
+    const SCHEMA: &str = r#"{"inputs": [
+        {"name": "root", "type": "field", "visibility": "public"},
+        {"name": "balance", "type": "u8"},
+        {"name": "is_member", "type": "bool", "visibility": "private"}
+    ]}"#;
+
+    #[test]
+    fn test_inputs_from_json_and_csv() {
+        let schema = InputSchema::from_json(SCHEMA).unwrap();
+        let json = r#"{"balance": 200, "is_member": true, "root": "0x1f"}"#;
+        let csv = "name,value\nroot,31\n# comment\nbalance,200\nis_member,1\n";
+        let from_json = schema.read_json::<TestField>(json).unwrap();
+        let from_csv = schema.read_csv::<TestField>(csv).unwrap();
+        assert_eq!(from_json, from_csv);
+
+        // Private inputs come first, in schema order
+        assert_eq!(from_json.names().collect::<Vec<_>>(), ["balance", "is_member", "root"]);
+        assert_eq!(from_json.index("root"), Some(Variable::Instance(1)));
+        assert_eq!(from_json.index("is_member"), Some(Variable::Witness(1)));
+        assert_eq!(from_json.value("root"), Some(TestField::from(31u64)));
+        let mut circuit = from_json.to_circuit("membership");
+        let (m, b) = (from_json.index("is_member").unwrap(), from_json.index("balance").unwrap());
+        circuit.add_multiplication_constraint(m, b, b);
+        assert!(circuit.check_witness().is_satisfied());
+
+        let check = |values: &str| schema.read_csv::<TestField>(values).unwrap_err();
+        assert!(matches!(check("root,1\nbalance,256\nis_member,0"), InputError::InvalidValue { .. }));
+        assert!(matches!(check("root,1\nbalance,2\nis_member,2"), InputError::InvalidValue { .. }));
+        assert!(matches!(check("root,1\nbalance,-2\nis_member,0"), InputError::InvalidValue { .. }));
+        assert!(matches!(check("root,1\nbalance,2"), InputError::Missing(name) if name == "is_member"));
+        assert!(matches!(check("root,1\nroot,2"), InputError::Duplicate(_)));
+        assert!(matches!(check("roots,1"), InputError::Unknown(_)));
+        let modulus = BigUint::from(TestField::MODULUS).to_string();
+        assert!(matches!(
+            check(&format!("root,{}\nbalance,2\nis_member,0", modulus)),
+            InputError::InvalidValue { .. }
+        ));
+        assert!(matches!(InputSchema::from_json(r#"{"inputs": [{"name": "x", "type": "u0"}]}"#), Err(InputError::Schema(_))));
+    }
+}
@@ -3,18 +3,47 @@
 //! This crate implements an efficient outsourcing scheme for SNARKs (EOS)
 //! that allows delegation of computational tasks while preserving privacy
 //! and ensuring verifiability.
+//!
+//! Most users only need `use eos_delegation::prelude::*;`, which exports the
+//! protocol entry points and the types that appear in their signatures.
+//! Everything else is reached through its module, e.g.
+//! `eos_delegation::mpc::BeaverTriple`.
+//!
+//! Cargo features select the parts a role needs:
+//!
+//! - `verifier-only`: commitment schemes, the PIOP verifier, wire formats and
+//!   the streaming verifier. These are always built; the feature only names
+//!   the minimal configuration, `default-features = false`.
+//! - `worker`: the MPC runtime, networking and preprocessing storage.
+//! - `delegator`: the delegation protocol and input parsing (implies `worker`).
+//! - `evaluation`: benchmarks, report rendering and proof-size accounting
+//!   (implies `delegator`).
+//! - `ffi`: the C API in `ffi`, declared in `include/eos_verifier.h`. It only
+//!   needs the verifier, so `default-features = false, features = ["ffi"]`
+//!   is enough for embedding.
+//! - `python`: the PyO3 extension module in `python` (implies `evaluation`).
+//!
+//! The default build enables `delegator`, `worker`, `evaluation` and `ffi`.
 
 pub mod circuit;
+#[cfg(feature = "worker")]
 pub mod mpc;
 pub mod piop;
 pub mod protocol;
 pub mod evaluation;
 pub mod custom_circuits;
-pub mod comprehensive_tests;
-
-pub use circuit::*;
-pub use mpc::*;
-pub use piop::*;
-pub use protocol::*;
-pub use evaluation::*;
-pub use comprehensive_tests::*;
+pub mod circuit_dsl;
+pub mod serialization;
+#[cfg(feature = "worker")]
+pub mod storage;
+pub mod config;
+#[cfg(feature = "delegator")]
+pub mod inputs;
+pub mod srs;
+pub mod sampling;
+pub mod hash_to_curve;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod prelude;
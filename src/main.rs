@@ -3,27 +3,32 @@
 //! This is the main entry point for the EOS delegation protocol implementation.
 //! It demonstrates the core functionality and provides examples of usage.
 
-mod circuit;
-mod mpc;
-mod piop;
-mod protocol;
-mod evaluation;
 mod comprehensive_tests;
-mod custom_circuits;
 
-use mpc::*;
-use evaluation::*;
+use eos_delegation::mpc::*;
+use eos_delegation::evaluation::*;
 use comprehensive_tests::run_comprehensive_tests;
-use piop::ConsistencyChecker;
-use circuit::KZGCommitmentScheme;
-use custom_circuits::{CustomCircuit, CircuitTemplates, CircuitTester};
-use ark_bls12_381::{Fr, G1Projective};
+use eos_delegation::piop::ConsistencyChecker;
+use eos_delegation::circuit::KZGCommitmentScheme;
+use eos_delegation::custom_circuits::{CustomCircuit, CircuitTemplates, CircuitTester, TemplateRegistry};
+use eos_delegation::inputs::InputSchema;
+use ark_bls12_381::{Bls12_381, Fr};
 use ark_std::rand::{rngs::StdRng, SeedableRng};
 use ark_poly::{univariate::DensePolynomial, DenseUVPolynomial};
 
 type F = Fr;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // `eos-delegation template <name> [key=value ...]` 实例化注册的电路模板
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) == Some("template") {
+        return run_template_command(&args[2..]);
+    }
+    // `eos-delegation inputs <schema.json> <values.json|values.csv>` 校验输入文件
+    if args.get(1).map(String::as_str) == Some("inputs") {
+        return run_inputs_command(&args[2..]);
+    }
+
     println!("🚀 EOS 委托协议系统启动");
     println!("========================================");
 
@@ -65,6 +70,51 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+fn run_template_command(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let registry = TemplateRegistry::default();
+    let Some(name) = args.first() else {
+        println!("📚 可用模板:");
+        for name in registry.names() {
+            let template = registry.get(name).expect("listed template exists");
+            let params: Vec<String> = template.params().iter()
+                .map(|spec| format!("{}={}", spec.name, spec.default))
+                .collect();
+            println!("   {} - {} [{}]", name, template.description(), params.join(", "));
+        }
+        return Ok(());
+    };
+
+    let mut params = std::collections::HashMap::new();
+    for arg in &args[1..] {
+        let (key, value) = arg.split_once('=').ok_or_else(|| format!("expected key=value, got '{}'", arg))?;
+        params.insert(key.to_string(), value.to_string());
+    }
+
+    let mut rng = StdRng::seed_from_u64(12345);
+    let circuit = registry.instantiate(name, &params, &mut rng)?;
+    let report = CircuitTester::test_circuit(&circuit);
+    println!("{}", report);
+    if !report.passed() {
+        return Err(format!("template {} produced an unsatisfied circuit", name).into());
+    }
+    Ok(())
+}
+
+fn run_inputs_command(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let [schema, values] = args else {
+        return Err("usage: inputs <schema.json> <values.json|values.csv>".into());
+    };
+    let schema = InputSchema::load(schema)?;
+    let assignment = schema.load_values::<F>(values)?;
+    println!("✅ {} 个输入通过校验:", schema.inputs.len());
+    for name in assignment.names() {
+        let variable = assignment.index(name).expect("listed input exists");
+        let value = assignment.value(name).expect("listed input exists");
+        println!("   {} -> 变量 {} = {}", name, variable, value);
+    }
+    Ok(())
+}
+
 fn test_secret_sharing_basic(rng: &mut StdRng) -> Result<(), Box<dyn std::error::Error>> {
     println!("   🔐 秘密分享测试...");
     
@@ -105,7 +155,7 @@ fn test_mpc_basic_operations(rng: &mut StdRng) -> Result<(), Box<dyn std::error:
     println!("      📥 输入秘密: {} 和 {}", secret1, secret2);
     
     // 测试加法
-    if let (Some(s1), Some(s2)) = (shares1.get(0), shares2.get(0)) {
+    if let (Some(s1), Some(s2)) = (shares1.first(), shares2.first()) {
         let _add_result = executor.add_gate(s1, s2)?;
         println!("      ➕ 加法门: {} + {} = 分享值", secret1, secret2);
         
@@ -231,7 +281,7 @@ fn test_kzg_polynomial_commitment(rng: &mut StdRng) -> Result<(), Box<dyn std::e
     println!("   📊 KZG 多项式承诺方案测试...");
     
     // 创建 KZG 方案实例
-    let kzg = KZGCommitmentScheme::<F, G1Projective>::setup(10, rng);
+    let kzg = KZGCommitmentScheme::<Bls12_381>::setup(10, rng);
     
     // 创建测试多项式 p(x) = x^2 + 2x + 3
     let test_polynomial = DensePolynomial::from_coefficients_vec(vec![
@@ -285,11 +335,11 @@ fn test_custom_circuit_and_witness() -> Result<(), Box<dyn std::error::Error>> {
     let z = F::from(50000u64); // 错误值：100² + 200² = 10000 + 40000 = 50000 ≠ 50001
 
     // 添加见证和输入
-    let x_idx = custom_circuit.add_private_witness(x);              // 索引 0
-    let y_idx = custom_circuit.add_private_witness(y);              // 索引 1 
-    let x_squared_idx = custom_circuit.add_private_witness(x * x);  // 索引 2: 10000
-    let y_squared_idx = custom_circuit.add_private_witness(y * y);  // 索引 3: 40000
-    let z_idx = custom_circuit.add_public_input(z);                 // 索引 4: z在 all_variables 中
+    let x_idx = custom_circuit.add_private_witness(x);              // Witness(0)
+    let y_idx = custom_circuit.add_private_witness(y);              // Witness(1)
+    let x_squared_idx = custom_circuit.add_private_witness(x * x);  // Witness(2): 10000
+    let y_squared_idx = custom_circuit.add_private_witness(y * y);  // Witness(3): 40000
+    let z_idx = custom_circuit.add_public_input(z);                 // Instance(1)，Instance(0) 是常量 1
     
     // 添加约束
     custom_circuit.add_multiplication_constraint(x_idx, x_idx, x_squared_idx);     // x × x = x²
@@ -302,7 +352,15 @@ fn test_custom_circuit_and_witness() -> Result<(), Box<dyn std::error::Error>> {
     println!("      x² = {}, y² = {}", x * x, y * y);
     println!("      x² + y² = {} (期望 z = {})", x * x + y * y, z);
     
-    let is_valid = CircuitTester::test_circuit(&custom_circuit);
+    // 委托前检查：在本地明文见证上找出所有会失败的约束，避免白白支付 MPC 开销
+    let report = custom_circuit.check_witness();
+    if !report.is_satisfied() {
+        println!("   🩺 委托前检查未通过: {}", report);
+    }
+
+    let report = CircuitTester::test_circuit(&custom_circuit);
+    println!("{}", report);
+    let is_valid = report.passed();
     println!("   🔍 验证结果: {}", if is_valid { "✅ 通过" } else { "❌ 失败" });
     
     // 2. 使用电路模板：平方根验证
@@ -311,7 +369,9 @@ fn test_custom_circuit_and_witness() -> Result<(), Box<dyn std::error::Error>> {
     let sqrt_result = F::from(49u64);
     let sqrt_circuit = CircuitTemplates::square_root_verification(sqrt_x, sqrt_result);
     
-    let sqrt_valid = CircuitTester::test_circuit(&sqrt_circuit);
+    let report = CircuitTester::test_circuit(&sqrt_circuit);
+    println!("{}", report);
+    let sqrt_valid = report.passed();
     println!("   🔍 平方根验证结果: {}", if sqrt_valid { "✅ 通过" } else { "❌ 失败" });
     
     // 3. KZG 承诺保护私有见证
@@ -320,7 +380,7 @@ fn test_custom_circuit_and_witness() -> Result<(), Box<dyn std::error::Error>> {
     let degree = 10;
     
     // 设置 KZG
-    let kzg_scheme = KZGCommitmentScheme::<F, G1Projective>::setup(degree, &mut rng);
+    let kzg_scheme = KZGCommitmentScheme::<Bls12_381>::setup(degree, &mut rng);
     
     // 创建见证多项式
     let witness_coeffs: Vec<F> = vec![
@@ -357,7 +417,9 @@ fn test_custom_circuit_and_witness() -> Result<(), Box<dyn std::error::Error>> {
     let range_max = F::from(50u64);
     let range_circuit = CircuitTemplates::range_proof(range_value, range_min, range_max);
     
-    let range_valid = CircuitTester::test_circuit(&range_circuit);
+    let report = CircuitTester::test_circuit(&range_circuit);
+    println!("{}", report);
+    let range_valid = report.passed();
     println!("   🔍 范围证明结果: {}", if range_valid { "✅ 通过" } else { "❌ 失败" });
     
     println!("\n💡 自定义电路指南:");
@@ -0,0 +1,296 @@
+//! Aggregation of worker contributions
+//!
+//! Every worker output the protocol needs to combine — commitment shares,
+//! sumcheck round messages, opening shares — is a Shamir share of the value
+//! a single prover would have produced, and each of those is linear in the
+//! shared data. The aggregator recovers the value by Lagrange interpolation
+//! at zero, in the field or in the exponent.
+//!
+//! Contributions from any `threshold` workers determine the value, so any
+//! further contribution must lie on the same degree `threshold - 1`
+//! polynomial. The aggregator checks that before combining and names the
+//! first worker whose contribution does not, rather than publishing a value
+//! skewed by it. With exactly `threshold` contributions there is nothing to
+//! check against.
+//!
+//! The aggregator may be the delegator or one of the workers. Its work is
+//! timed apart from the workers' so reports can show it on its own.
+
+use std::time::{Duration, Instant};
+
+use ark_ec::CurveGroup;
+use ark_ff::{Field, PrimeField};
+
+use crate::circuit::{OpeningProof, PolynomialCommitment};
+use crate::evaluation::PerformanceMetrics;
+
+/// Phase name under which aggregation time is reported
+pub const AGGREGATION_PHASE: &str = "aggregation";
+
+/// Who combines the contributions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AggregatorRole {
+    Delegator,
+    /// The worker at this Shamir evaluation point, whose own contribution stays local
+    Worker(usize),
+}
+
+/// A worker output that is linear in the shared data
+pub trait Combinable<F: PrimeField>: Clone + PartialEq {
+    /// `Σ weight · value` over `terms`, which is never empty
+    fn weighted_sum(terms: &[(F, &Self)]) -> Self;
+}
+
+impl<F: PrimeField, K: Field<BasePrimeField = F>, const N: usize> Combinable<F> for [K; N] {
+    fn weighted_sum(terms: &[(F, &Self)]) -> Self {
+        std::array::from_fn(|i| terms.iter().map(|(weight, value)| value[i] * K::from_base_prime_field(*weight)).sum())
+    }
+}
+
+impl<F: PrimeField, K: Field<BasePrimeField = F>> Combinable<F> for Vec<K> {
+    fn weighted_sum(terms: &[(F, &Self)]) -> Self {
+        let len = terms.iter().map(|(_, value)| value.len()).max().unwrap_or(0);
+        (0..len)
+            .map(|i| {
+                terms
+                    .iter()
+                    .filter_map(|(weight, value)| value.get(i).map(|v| *v * K::from_base_prime_field(*weight)))
+                    .sum()
+            })
+            .collect()
+    }
+}
+
+impl<G: CurveGroup> Combinable<G::ScalarField> for PolynomialCommitment<G> {
+    fn weighted_sum(terms: &[(G::ScalarField, &Self)]) -> Self {
+        let commitment: G = terms.iter().map(|(weight, share)| share.commitment * weight).sum();
+        PolynomialCommitment { commitment: commitment.into_affine() }
+    }
+}
+
+impl<G: CurveGroup> Combinable<G::ScalarField> for OpeningProof<G::ScalarField, G> {
+    /// Opening shares must all be at the same point; the first one's is kept
+    fn weighted_sum(terms: &[(G::ScalarField, &Self)]) -> Self {
+        let proof: G = terms.iter().map(|(weight, share)| share.proof * weight).sum();
+        let evaluation = terms.iter().map(|(weight, share)| share.evaluation * weight).sum();
+        OpeningProof { proof: proof.into_affine(), evaluation, point: terms[0].1.point }
+    }
+}
+
+/// Lagrange coefficients at `x` for the given Shamir evaluation points
+pub(crate) fn lagrange_coefficients<F: PrimeField>(indices: &[usize], x: F) -> Result<Vec<F>, AggregationError> {
+    indices
+        .iter()
+        .map(|i| {
+            let xi = F::from(*i as u64);
+            indices.iter().filter(|j| *j != i).try_fold(F::one(), |acc, j| {
+                let xj = F::from(*j as u64);
+                let inverse = (xi - xj).inverse().ok_or(AggregationError::DuplicateIndex(*i))?;
+                Ok(acc * (x - xj) * inverse)
+            })
+        })
+        .collect()
+}
+
+/// Work done by the aggregator, kept apart from the workers' costs
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AggregationCost {
+    /// Values combined
+    pub combinations: usize,
+    /// Contributions received, the aggregator's own included
+    pub contributions: usize,
+    /// Contributions checked against the first `threshold`
+    pub consistency_checks: usize,
+    pub elapsed: Duration,
+}
+
+/// Checks and combines worker contributions for one protocol run
+#[derive(Debug, Clone)]
+pub struct Aggregator {
+    role: AggregatorRole,
+    threshold: usize,
+    cost: AggregationCost,
+}
+
+impl Aggregator {
+    pub fn new(role: AggregatorRole, threshold: usize) -> Self {
+        Self { role, threshold, cost: AggregationCost::default() }
+    }
+
+    pub fn role(&self) -> AggregatorRole {
+        self.role
+    }
+
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+
+    pub fn cost(&self) -> &AggregationCost {
+        &self.cost
+    }
+
+    /// How many of `contributors` send their contribution over the network
+    pub fn remote_contributors(&self, contributors: &[usize]) -> usize {
+        match self.role {
+            AggregatorRole::Delegator => contributors.len(),
+            AggregatorRole::Worker(own) => contributors.iter().filter(|index| **index != own).count(),
+        }
+    }
+
+    /// Combine `(worker index, contribution)` pairs into the shared value,
+    /// after checking that every contribution beyond the first `threshold`
+    /// agrees with them
+    pub fn combine<F: PrimeField, T: Combinable<F>>(&mut self, contributions: &[(usize, T)]) -> Result<T, AggregationError> {
+        let started = Instant::now();
+        let result = self.combine_checked(contributions);
+        self.cost.elapsed += started.elapsed();
+        result
+    }
+
+    fn combine_checked<F: PrimeField, T: Combinable<F>>(&mut self, contributions: &[(usize, T)]) -> Result<T, AggregationError> {
+        let threshold = self.threshold.max(1);
+        if contributions.len() < threshold {
+            return Err(AggregationError::InsufficientContributions { threshold, available: contributions.len() });
+        }
+        let (basis, rest) = contributions.split_at(threshold);
+        let indices: Vec<usize> = basis.iter().map(|(index, _)| *index).collect();
+        let interpolate = |x: F| -> Result<T, AggregationError> {
+            let weights = lagrange_coefficients(&indices, x)?;
+            let terms: Vec<(F, &T)> = weights.into_iter().zip(basis.iter().map(|(_, value)| value)).collect();
+            Ok(T::weighted_sum(&terms))
+        };
+        for (index, value) in rest {
+            if indices.contains(index) {
+                return Err(AggregationError::DuplicateIndex(*index));
+            }
+            if interpolate(F::from(*index as u64))? != *value {
+                return Err(AggregationError::Inconsistent(*index));
+            }
+        }
+        let combined = interpolate(F::zero())?;
+        self.cost.combinations += 1;
+        self.cost.contributions += contributions.len();
+        self.cost.consistency_checks += rest.len();
+        Ok(combined)
+    }
+
+    /// Combine opening shares, which must all open at the same point
+    pub fn combine_openings<G: CurveGroup>(
+        &mut self,
+        shares: &[(usize, OpeningProof<G::ScalarField, G>)],
+    ) -> Result<OpeningProof<G::ScalarField, G>, AggregationError> {
+        if let Some((index, _)) = shares.iter().find(|(_, share)| share.point != shares[0].1.point) {
+            return Err(AggregationError::MismatchedPoint(*index));
+        }
+        self.combine(shares)
+    }
+
+    /// Add the aggregation time to `metrics` under `AGGREGATION_PHASE`
+    pub fn record_into(&self, metrics: &mut PerformanceMetrics) {
+        metrics.accumulate_timing(AGGREGATION_PHASE, self.cost.elapsed);
+    }
+}
+
+/// Aggregation error types
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AggregationError {
+    InsufficientContributions { threshold: usize, available: usize },
+    DuplicateIndex(usize),
+    /// This worker's contribution does not agree with the others
+    Inconsistent(usize),
+    /// This worker's opening share is at a different point
+    MismatchedPoint(usize),
+}
+
+impl std::fmt::Display for AggregationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            AggregationError::InsufficientContributions { threshold, available } => {
+                write!(f, "Aggregation needs {} contributions, {} received", threshold, available)
+            }
+            AggregationError::DuplicateIndex(index) => write!(f, "Two contributions from worker {}", index),
+            AggregationError::Inconsistent(index) => {
+                write!(f, "Contribution from worker {} is inconsistent with the others", index)
+            }
+            AggregationError::MismatchedPoint(index) => {
+                write!(f, "Opening share from worker {} is at a different point", index)
+            }
+        }
+    }
+}
+
+impl std::error::Error for AggregationError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::KZGCommitmentScheme;
+    use crate::mpc::{SecretSharing, ShamirSecretSharing};
+    use ark_bls12_381::{Bls12_381, Fr, G1Projective};
+    use ark_poly::univariate::DensePolynomial;
+    use ark_poly::DenseUVPolynomial;
+    use ark_std::test_rng;
+
+    type TestField = Fr;
+
+    #[test]
+    fn test_aggregator_checks_and_combines_shares() {
+        let mut rng = test_rng();
+        let (threshold, parties) = (2, 4);
+        let secret = [TestField::from(5u64), TestField::from(8u64)];
+        let shared: Vec<_> = secret
+            .iter()
+            .map(|value| ShamirSecretSharing::share_secret(*value, threshold, parties, &mut rng))
+            .collect();
+        let messages: Vec<(usize, [TestField; 2])> =
+            (0..parties).map(|p| (shared[0][p].index, [shared[0][p].value, shared[1][p].value])).collect();
+
+        let mut aggregator = Aggregator::new(AggregatorRole::Delegator, threshold);
+        assert_eq!(aggregator.combine(&messages).unwrap(), [secret[0], secret[1]]);
+        assert_eq!(aggregator.combine(&messages[1..3]).unwrap(), [secret[0], secret[1]]);
+        let mut tampered = messages.clone();
+        tampered[3].1[1] += TestField::from(1u64);
+        assert_eq!(aggregator.combine(&tampered), Err(AggregationError::Inconsistent(4)));
+        assert_eq!(
+            aggregator.combine(&messages[..1]),
+            Err(AggregationError::InsufficientContributions { threshold, available: 1 })
+        );
+
+        // Commitment and opening shares combine in the exponent
+        let kzg = KZGCommitmentScheme::<Bls12_381>::setup(4, &mut rng);
+        let coefficients: Vec<Vec<TestField>> = [7u64, 1, 9]
+            .iter()
+            .map(|c| ShamirSecretSharing::share_secret(TestField::from(*c), threshold, parties, &mut rng))
+            .map(|shares| shares.into_iter().map(|share| share.value).collect())
+            .collect();
+        let polynomial = |party: usize| {
+            DensePolynomial::from_coefficients_vec(coefficients.iter().map(|shares| shares[party]).collect())
+        };
+        let point = TestField::from(3u64);
+        let commitments: Vec<(usize, PolynomialCommitment<G1Projective>)> =
+            (0..parties).map(|p| (p + 1, kzg.commit(&polynomial(p)))).collect();
+        let openings: Vec<(usize, OpeningProof<TestField, G1Projective>)> =
+            (0..parties).map(|p| (p + 1, kzg.open(&polynomial(p), point))).collect();
+
+        let mut aggregator = Aggregator::new(AggregatorRole::Worker(1), threshold);
+        let commitment = aggregator.combine(&commitments).unwrap();
+        let opening = aggregator.combine_openings(&openings).unwrap();
+        assert!(kzg.verify(&commitment, &opening));
+        let expected = DensePolynomial::from_coefficients_vec([7u64, 1, 9].map(TestField::from).to_vec());
+        assert_eq!(commitment, kzg.commit(&expected));
+        let mut tampered = commitments.clone();
+        tampered[2].1 = commitments[3].1.clone();
+        assert_eq!(aggregator.combine(&tampered), Err(AggregationError::Inconsistent(3)));
+        let mut moved = openings.clone();
+        moved[1].1.point += TestField::from(1u64);
+        assert_eq!(aggregator.combine_openings(&moved), Err(AggregationError::MismatchedPoint(2)));
+
+        // Only successful combinations count, and their time is reported on its own
+        assert_eq!(aggregator.cost().combinations, 2);
+        assert_eq!(aggregator.cost().consistency_checks, 4);
+        assert_eq!(aggregator.remote_contributors(&[1, 2, 3, 4]), 3);
+        let mut metrics = PerformanceMetrics::new();
+        aggregator.record_into(&mut metrics);
+        assert_eq!(metrics.generate_report().phase_breakdown[AGGREGATION_PHASE], aggregator.cost().elapsed);
+    }
+}
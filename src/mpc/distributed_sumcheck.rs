@@ -0,0 +1,303 @@
+//! Sumcheck proving over secret-shared tables
+//!
+//! The sumcheck prover only ever sums halves of its table and folds the
+//! table at a public challenge, both linear maps. A worker holding Shamir
+//! shares of every table entry therefore computes a share of each round
+//! message locally. The `Aggregator` checks and interpolates the message
+//! from the workers' contributions, derives the challenge from the
+//! transcript and broadcasts it, and each worker folds its shares at that
+//! challenge.
+//!
+//! The resulting proof is exactly the one a single prover holding the whole
+//! table would produce, and the aggregator learns nothing beyond the round
+//! messages that proof publishes anyway. At the end every worker holds a
+//! share of f at the challenge point, ready for a distributed opening.
+
+use ark_ff::{Field, PrimeField};
+
+use crate::evaluation::CommunicationStats;
+use crate::mpc::{AggregationError, Aggregator, ShamirShare};
+use crate::piop::sumcheck::{absorb_statement, check_table_size, fold};
+use crate::piop::{ExtensionSumcheckProof, SumcheckError, SumcheckParameters, Transcript};
+use crate::serialization::field_size;
+
+/// One worker's view of a shared sumcheck table
+#[derive(Debug, Clone)]
+pub struct SumcheckWorker<F: PrimeField, K: Field<BasePrimeField = F>> {
+    index: usize,
+    shares: Vec<F>,
+    current: Vec<K>,
+}
+
+impl<F: PrimeField, K: Field<BasePrimeField = F>> SumcheckWorker<F, K> {
+    /// Worker holding `shares`, one per table entry, all at the same evaluation point
+    pub fn from_shares(shares: &[ShamirShare<F>]) -> Result<Self, DistributedSumcheckError> {
+        let index = shares.first().ok_or(DistributedSumcheckError::EmptyTable)?.index;
+        if shares.iter().any(|share| share.index != index) {
+            return Err(DistributedSumcheckError::MixedIndices(index));
+        }
+        let shares: Vec<F> = shares.iter().map(|share| share.value).collect();
+        let current = shares.iter().map(|value| K::from_base_prime_field(*value)).collect();
+        Ok(Self { index, shares, current })
+    }
+
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Share of the table's sum
+    pub fn claim_share(&self) -> F {
+        self.shares.iter().copied().sum()
+    }
+
+    /// Start a new repetition from the original shares
+    pub fn reset(&mut self) {
+        self.current = self.shares.iter().map(|value| K::from_base_prime_field(*value)).collect();
+    }
+
+    /// Share of this round's message, the round polynomial at 0 and 1
+    pub fn round_message(&self) -> [K; 2] {
+        let (low, high) = self.current.split_at(self.current.len() / 2);
+        [low.iter().copied().sum(), high.iter().copied().sum()]
+    }
+
+    /// Fold the shares at the round's public challenge
+    pub fn fold(&mut self, challenge: K) {
+        self.current = fold(&self.current, challenge);
+    }
+
+    /// Share of f at the challenge point, once every round has been folded
+    pub fn evaluation_share(&self) -> K {
+        self.current[0]
+    }
+}
+
+/// Proof produced by the workers, with the shares they end up holding
+#[derive(Debug, Clone)]
+pub struct DistributedSumcheckOutput<K: Field> {
+    pub proof: ExtensionSumcheckProof<K>,
+    /// For each repetition, every worker's share of f at that repetition's point
+    pub evaluation_shares: Vec<Vec<K>>,
+    /// Traffic between the workers and the aggregator, one entry per round trip
+    pub stats: CommunicationStats,
+}
+
+/// Run the sumcheck with `workers` each holding shares of the table
+///
+/// Any `aggregator.threshold()` workers determine the table; the aggregator
+/// checks every further worker's contributions against theirs. The returned
+/// proof verifies with `verify_sumcheck` against the reconstructed claim.
+pub fn prove_sumcheck_distributed<F: PrimeField, K: Field<BasePrimeField = F>>(
+    parameters: &SumcheckParameters,
+    workers: &mut [SumcheckWorker<F, K>],
+    aggregator: &mut Aggregator,
+    transcript: &mut Transcript,
+) -> Result<DistributedSumcheckOutput<K>, DistributedSumcheckError> {
+    run_distributed(parameters, workers, aggregator, transcript)
+}
+
+/// Where the aggregator gets each round's challenge from
+///
+/// The transcript derives it by Fiat–Shamir; the interactive variant in
+/// `protocol::interactive` asks a live verifier instead.
+pub(crate) trait ChallengeSource<F: PrimeField, K: Field<BasePrimeField = F>> {
+    type Error: From<DistributedSumcheckError>;
+
+    /// Called once with the reconstructed claim, before the first round
+    fn claim(&mut self, parameters: &SumcheckParameters, claim: F) -> Result<(), Self::Error>;
+
+    /// Challenge answering one combined round message
+    fn challenge(&mut self, message: &[K; 2]) -> Result<K, Self::Error>;
+}
+
+impl<F: PrimeField, K: Field<BasePrimeField = F>> ChallengeSource<F, K> for Transcript {
+    type Error = DistributedSumcheckError;
+
+    fn claim(&mut self, parameters: &SumcheckParameters, claim: F) -> Result<(), Self::Error> {
+        absorb_statement(self, parameters, claim);
+        Ok(())
+    }
+
+    fn challenge(&mut self, message: &[K; 2]) -> Result<K, Self::Error> {
+        self.append_fields(b"sumcheck_round", message);
+        Ok(self.challenge_element(b"sumcheck_challenge"))
+    }
+}
+
+pub(crate) fn run_distributed<F: PrimeField, K: Field<BasePrimeField = F>, C: ChallengeSource<F, K>>(
+    parameters: &SumcheckParameters,
+    workers: &mut [SumcheckWorker<F, K>],
+    aggregator: &mut Aggregator,
+    source: &mut C,
+) -> Result<DistributedSumcheckOutput<K>, C::Error> {
+    if parameters.zk {
+        return Err(DistributedSumcheckError::from(SumcheckError::ZkMismatch).into());
+    }
+    let threshold = aggregator.threshold();
+    if workers.len() < threshold.max(1) {
+        return Err(DistributedSumcheckError::InsufficientWorkers { threshold, available: workers.len() }.into());
+    }
+    for worker in workers.iter() {
+        check_table_size(parameters, &worker.shares).map_err(DistributedSumcheckError::from)?;
+    }
+    let indices: Vec<usize> = workers.iter().map(|worker| worker.index).collect();
+    let remote = aggregator.remote_contributors(&indices);
+
+    let mut stats = CommunicationStats::new();
+    let element = field_size::<K>();
+    let claims: Vec<(usize, [F; 1])> = workers.iter().map(|worker| (worker.index, [worker.claim_share()])).collect();
+    let [claim] = aggregator.combine(&claims).map_err(DistributedSumcheckError::from)?;
+    stats.add_round(remote * field_size::<F>(), 0);
+    source.claim(parameters, claim)?;
+
+    let mut rounds = Vec::with_capacity(parameters.repetitions);
+    let mut evaluation_shares = Vec::with_capacity(parameters.repetitions);
+    for _ in 0..parameters.repetitions {
+        workers.iter_mut().for_each(SumcheckWorker::reset);
+        let mut repetition = Vec::with_capacity(parameters.num_vars);
+        for _ in 0..parameters.num_vars {
+            let shares: Vec<(usize, [K; 2])> =
+                workers.iter().map(|worker| (worker.index, worker.round_message())).collect();
+            let message = aggregator.combine(&shares).map_err(DistributedSumcheckError::from)?;
+            let challenge = source.challenge(&message)?;
+            workers.iter_mut().for_each(|worker| worker.fold(challenge));
+            // Two elements up from every remote worker, the challenge back down
+            stats.add_round(remote * 3 * element, 0);
+            repetition.push(message);
+        }
+        evaluation_shares.push(workers.iter().map(SumcheckWorker::evaluation_share).collect());
+        rounds.push(repetition);
+    }
+    Ok(DistributedSumcheckOutput { proof: ExtensionSumcheckProof { rounds }, evaluation_shares, stats })
+}
+
+/// Distributed sumcheck error types
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DistributedSumcheckError {
+    Sumcheck(SumcheckError),
+    Aggregation(AggregationError),
+    EmptyTable,
+    /// A worker was given shares from more than one evaluation point
+    MixedIndices(usize),
+    InsufficientWorkers { threshold: usize, available: usize },
+}
+
+impl std::fmt::Display for DistributedSumcheckError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            DistributedSumcheckError::Sumcheck(e) => write!(f, "{}", e),
+            DistributedSumcheckError::Aggregation(e) => write!(f, "{}", e),
+            DistributedSumcheckError::EmptyTable => write!(f, "Worker holds no shares"),
+            DistributedSumcheckError::MixedIndices(index) => {
+                write!(f, "Worker {} holds shares of more than one party", index)
+            }
+            DistributedSumcheckError::InsufficientWorkers { threshold, available } => {
+                write!(f, "Sumcheck needs {} workers, {} available", threshold, available)
+            }
+        }
+    }
+}
+
+impl std::error::Error for DistributedSumcheckError {}
+
+impl From<SumcheckError> for DistributedSumcheckError {
+    fn from(e: SumcheckError) -> Self {
+        DistributedSumcheckError::Sumcheck(e)
+    }
+}
+
+impl From<AggregationError> for DistributedSumcheckError {
+    fn from(e: AggregationError) -> Self {
+        DistributedSumcheckError::Aggregation(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::{Goldilocks, GoldilocksExt};
+    use crate::mpc::aggregator::lagrange_coefficients;
+    use crate::mpc::{AggregatorRole, SecretSharing, ShamirSecretSharing};
+    use crate::piop::{prove_sumcheck, verify_sumcheck, MultilinearOracle, QueriedOracle};
+    use ark_bls12_381::Fr;
+    use ark_std::test_rng;
+
+    type TestField = Fr;
+
+    fn share_table<F: PrimeField, K: Field<BasePrimeField = F>>(
+        table: &[F],
+        threshold: usize,
+        num_parties: usize,
+    ) -> Vec<SumcheckWorker<F, K>> {
+        let mut rng = test_rng();
+        let shared: Vec<Vec<ShamirShare<F>>> = table
+            .iter()
+            .map(|value| ShamirSecretSharing::<F>::share_secret(*value, threshold, num_parties, &mut rng))
+            .collect();
+        (0..num_parties)
+            .map(|party| {
+                let shares: Vec<ShamirShare<F>> = shared.iter().map(|entry| entry[party].clone()).collect();
+                SumcheckWorker::from_shares(&shares).unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_distributed_sumcheck_matches_single_prover() {
+        let table: Vec<Goldilocks> = (0..16u64).map(|i| Goldilocks::from(i * i + 5)).collect();
+        let claim: Goldilocks = table.iter().copied().sum();
+        let parameters = SumcheckParameters::for_security::<GoldilocksExt>(4, 128);
+        let mut workers = share_table::<_, GoldilocksExt>(&table, 2, 3);
+
+        let mut aggregator = Aggregator::new(AggregatorRole::Delegator, 2);
+        let output =
+            prove_sumcheck_distributed(&parameters, &mut workers, &mut aggregator, &mut Transcript::new(b"test")).unwrap();
+        let single = prove_sumcheck::<_, GoldilocksExt>(&parameters, &table, &mut Transcript::new(b"test")).unwrap();
+        assert_eq!(output.proof, single);
+        let subclaims = verify_sumcheck(&parameters, claim, &output.proof, &mut Transcript::new(b"test")).unwrap();
+
+        // The workers' final shares open to f at each repetition's point
+        let mut f = QueriedOracle::new(MultilinearOracle { label: "f".to_string(), table });
+        let lagrange: Vec<GoldilocksExt> = lagrange_coefficients(&[1, 2, 3], Goldilocks::from(0u64))
+            .unwrap()
+            .into_iter()
+            .map(GoldilocksExt::from_base_prime_field)
+            .collect();
+        for (subclaim, shares) in subclaims.iter().zip(&output.evaluation_shares) {
+            let evaluation: GoldilocksExt = shares.iter().zip(&lagrange).map(|(s, l)| *s * l).sum();
+            assert_eq!(evaluation, subclaim.expected_evaluation);
+            assert!(subclaim.check(&mut f).unwrap());
+        }
+
+        // One round trip for the claim, then one per sumcheck round, each checked by the aggregator
+        assert_eq!(output.stats.rounds, 1 + parameters.repetitions * parameters.num_vars);
+        assert_eq!(output.stats.bytes_per_round[1], 3 * 3 * field_size::<GoldilocksExt>());
+        assert_eq!(aggregator.cost().combinations, output.stats.rounds);
+        assert_eq!(aggregator.cost().consistency_checks, output.stats.rounds);
+
+        // Any threshold-sized subset of workers gives the same proof
+        let table: Vec<TestField> = (1..=8u64).map(TestField::from).collect();
+        let parameters = SumcheckParameters::for_security::<TestField>(3, 128);
+        let mut workers = share_table::<_, TestField>(&table, 2, 4);
+        let single = prove_sumcheck::<_, TestField>(&parameters, &table, &mut Transcript::new(b"test")).unwrap();
+        let mut aggregator = Aggregator::new(AggregatorRole::Worker(2), 2);
+        let subset = &mut workers[1..3];
+        let output = prove_sumcheck_distributed(&parameters, subset, &mut aggregator, &mut Transcript::new(b"test")).unwrap();
+        assert_eq!(output.proof, single);
+        // The aggregating worker's own messages never leave it
+        assert_eq!(output.stats.bytes_per_round[1], 3 * field_size::<TestField>());
+        assert_eq!(
+            prove_sumcheck_distributed(&parameters, &mut workers[..1], &mut aggregator, &mut Transcript::new(b"test"))
+                .unwrap_err(),
+            DistributedSumcheckError::InsufficientWorkers { threshold: 2, available: 1 }
+        );
+
+        // A worker whose shares were tampered with is caught before its message is used
+        workers[3].shares[0] += TestField::from(1u64);
+        assert_eq!(
+            prove_sumcheck_distributed(&parameters, &mut workers, &mut aggregator, &mut Transcript::new(b"test"))
+                .unwrap_err(),
+            DistributedSumcheckError::Aggregation(AggregationError::Inconsistent(4))
+        );
+    }
+}
@@ -3,9 +3,14 @@
 //! This module implements the circuit execution engine that can run
 //! arithmetic circuits in a multi-party computation setting.
 
-use ark_ff::Field;
-use ark_relations::r1cs::{ConstraintSystem, Variable, LinearCombination};
+use ark_ff::{Field, PrimeField};
+use ark_relations::r1cs::ConstraintSystem;
+use std::collections::HashMap;
+use std::sync::Arc;
+use crate::mpc::gates::CustomGate;
+use crate::mpc::leakage::{LeakageMonitor, RevealBlocked};
 use crate::mpc::secret_sharing::{SecretSharing, SecretSharingError};
+use crate::sampling::SeededSampler;
 
 /// Circuit executor that can run circuits with secret-shared inputs
 pub struct ExecCircuit<F: Field, SS: SecretSharing<F>> {
@@ -17,6 +22,10 @@ pub struct ExecCircuit<F: Field, SS: SecretSharing<F>> {
     pub num_parties: usize,
     /// Secret sharing scheme
     pub secret_sharing: SS,
+    /// Accounting of every reveal performed by this executor
+    pub leakage: LeakageMonitor,
+    /// Custom gates available to gate circuits, by name
+    pub(crate) custom_gates: HashMap<String, Arc<dyn CustomGate<F, SS>>>,
 }
 
 impl<F: Field, SS: SecretSharing<F>> ExecCircuit<F, SS> {
@@ -31,6 +40,8 @@ impl<F: Field, SS: SecretSharing<F>> ExecCircuit<F, SS> {
             party_id,
             num_parties,
             secret_sharing,
+            leakage: LeakageMonitor::default(),
+            custom_gates: HashMap::new(),
         }
     }
     
@@ -55,6 +66,23 @@ impl<F: Field, SS: SecretSharing<F>> ExecCircuit<F, SS> {
         SS::mul_shares(left, right)
             .map_err(ExecutionError::SecretSharingError)
     }
+
+    /// Execute a selection gate: `cond·a + (1−cond)·b`
+    ///
+    /// Costs one multiplication, computed as `b + cond·(a − b)`. `cond`
+    /// must hold 0 or 1; the executor cannot see it, so circuits either
+    /// type-check it as `WireType::Boolean` or constrain it with `If`.
+    pub fn select_gate(
+        &mut self,
+        cond: &SS::Share,
+        a: &SS::Share,
+        b: &SS::Share,
+    ) -> Result<SS::Share, ExecutionError> {
+        let negated = self.scalar_mul_share(b, -F::one())?;
+        let difference = self.add_gate(a, &negated)?;
+        let chosen = self.mul_gate(cond, &difference)?;
+        self.add_gate(b, &chosen)
+    }
     
     /// Execute a linear combination gate
     pub fn linear_combination_gate(
@@ -98,12 +126,37 @@ impl<F: Field, SS: SecretSharing<F>> ExecCircuit<F, SS> {
     ) -> Vec<SS::Share> {
         SS::share_secret(secret, threshold, self.num_parties, rng)
     }
+
+    /// Secret-share an input with randomness drawn from `sampler`
+    pub fn input_secret_seeded(
+        &mut self,
+        secret: F,
+        threshold: usize,
+        sampler: &mut SeededSampler,
+    ) -> Vec<SS::Share>
+    where
+        F: PrimeField,
+    {
+        SS::share_secret_seeded(secret, threshold, self.num_parties, sampler)
+    }
     
     /// Reveal a secret-shared value
+    #[track_caller]
     pub fn reveal_secret(
         &self,
         shares: &[SS::Share],
     ) -> Result<F, ExecutionError> {
+        self.reveal_labeled("unlabeled", shares)
+    }
+
+    /// Reveal a secret-shared value, recording it under `label`
+    #[track_caller]
+    pub fn reveal_labeled(
+        &self,
+        label: &str,
+        shares: &[SS::Share],
+    ) -> Result<F, ExecutionError> {
+        self.leakage.record(label, 1).map_err(ExecutionError::RevealBlocked)?;
         SS::reconstruct_secret(shares)
             .map_err(ExecutionError::SecretSharingError)
     }
@@ -165,6 +218,12 @@ impl ExecutionStats {
     }
 }
 
+impl Default for ExecutionStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Execution error types
 #[derive(Debug, Clone)]
 pub enum ExecutionError {
@@ -173,6 +232,7 @@ pub enum ExecutionError {
     CommunicationError,
     VerificationFailed,
     CircuitError(String),
+    RevealBlocked(RevealBlocked),
 }
 
 impl std::fmt::Display for ExecutionError {
@@ -183,6 +243,7 @@ impl std::fmt::Display for ExecutionError {
             ExecutionError::CommunicationError => write!(f, "Communication error between parties"),
             ExecutionError::VerificationFailed => write!(f, "Circuit execution verification failed"),
             ExecutionError::CircuitError(msg) => write!(f, "Circuit error: {}", msg),
+            ExecutionError::RevealBlocked(e) => write!(f, "{}", e),
         }
     }
 }
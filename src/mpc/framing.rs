@@ -0,0 +1,214 @@
+//! Length-prefixed frames for transport messages
+//!
+//! Every `Packet` crosses the wire as one frame: a version byte, the body
+//! length as a little-endian `u32`, the first four bytes of the body's
+//! SHA-256, then the body. A `FrameDecoder` sits on the receiving end of a
+//! link. It buffers partial reads until a whole frame is in, rejects a
+//! frame longer than its limit from the header alone (before buffering the
+//! body), and checks version and checksum before handing the body on. A
+//! stream that produced an error cannot be resynchronized; the receiver
+//! should drop the link.
+
+use std::io::Read;
+
+use sha2::{Digest, Sha256};
+
+use crate::mpc::Packet;
+use crate::serialization::{DecodeError, WireFormat};
+
+/// Version byte of the current frame layout
+pub const FRAME_VERSION: u8 = 1;
+/// Version, length and checksum in front of every frame body
+pub const FRAME_HEADER_LEN: usize = 9;
+/// Largest body a decoder accepts unless configured otherwise
+pub const DEFAULT_MAX_FRAME_LEN: usize = 16 << 20;
+
+fn frame_checksum(body: &[u8]) -> [u8; 4] {
+    let digest = Sha256::digest(body);
+    [digest[0], digest[1], digest[2], digest[3]]
+}
+
+/// Append `body` to `out` as one frame
+pub fn encode_frame(body: &[u8], out: &mut Vec<u8>) {
+    out.push(FRAME_VERSION);
+    out.extend_from_slice(&u32::try_from(body.len()).expect("frame body fits in u32").to_le_bytes());
+    out.extend_from_slice(&frame_checksum(body));
+    out.extend_from_slice(body);
+}
+
+impl Packet {
+    /// The packet as one frame
+    pub fn to_frame(&self) -> Vec<u8> {
+        let body = self.to_bytes();
+        let mut out = Vec::with_capacity(FRAME_HEADER_LEN + body.len());
+        encode_frame(&body, &mut out);
+        out
+    }
+
+    /// Bytes the packet takes on the wire, frame header included
+    pub fn framed_len(&self) -> usize {
+        FRAME_HEADER_LEN + self.to_bytes().len()
+    }
+}
+
+/// Reassembles frames from a byte stream
+#[derive(Debug, Clone)]
+pub struct FrameDecoder {
+    buffer: Vec<u8>,
+    max_len: usize,
+}
+
+impl Default for FrameDecoder {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_FRAME_LEN)
+    }
+}
+
+impl FrameDecoder {
+    /// Decoder rejecting bodies longer than `max_len` bytes
+    pub fn new(max_len: usize) -> Self {
+        Self { buffer: Vec::new(), max_len }
+    }
+
+    /// Append bytes as they arrive, in chunks of any size
+    pub fn push(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// Read what `reader` has available; returns the number of bytes read,
+    /// zero at end of stream
+    pub fn fill_from(&mut self, reader: &mut impl Read) -> std::io::Result<usize> {
+        let mut chunk = [0u8; 4096];
+        let read = reader.read(&mut chunk)?;
+        self.push(&chunk[..read]);
+        Ok(read)
+    }
+
+    /// Bytes received but not yet returned as a frame
+    pub fn buffered(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// The next complete frame body, or `None` if more bytes are needed
+    pub fn next_frame(&mut self) -> Result<Option<Vec<u8>>, FrameError> {
+        let Some(&version) = self.buffer.first() else {
+            return Ok(None);
+        };
+        if version != FRAME_VERSION {
+            return Err(FrameError::UnsupportedVersion(version));
+        }
+        if self.buffer.len() < FRAME_HEADER_LEN {
+            return Ok(None);
+        }
+        let len = u32::from_le_bytes(self.buffer[1..5].try_into().expect("slice has length 4")) as usize;
+        if len > self.max_len {
+            return Err(FrameError::TooLarge { len, max: self.max_len });
+        }
+        if self.buffer.len() < FRAME_HEADER_LEN + len {
+            return Ok(None);
+        }
+        let body = &self.buffer[FRAME_HEADER_LEN..FRAME_HEADER_LEN + len];
+        if frame_checksum(body) != self.buffer[5..FRAME_HEADER_LEN] {
+            return Err(FrameError::ChecksumMismatch);
+        }
+        let body = body.to_vec();
+        self.buffer.drain(..FRAME_HEADER_LEN + len);
+        Ok(Some(body))
+    }
+
+    /// The next complete packet, or `None` if more bytes are needed
+    pub fn next_packet(&mut self) -> Result<Option<Packet>, FrameError> {
+        match self.next_frame()? {
+            Some(body) => Ok(Some(Packet::from_bytes(&body)?)),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Framing error types
+#[derive(Debug, Clone, PartialEq)]
+pub enum FrameError {
+    UnsupportedVersion(u8),
+    TooLarge { len: usize, max: usize },
+    ChecksumMismatch,
+    /// The frame is intact but its body is not a valid packet
+    Decode(DecodeError),
+}
+
+impl std::fmt::Display for FrameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            FrameError::UnsupportedVersion(version) => write!(f, "Unsupported frame version {}", version),
+            FrameError::TooLarge { len, max } => write!(f, "Frame of {} bytes exceeds the limit of {}", len, max),
+            FrameError::ChecksumMismatch => write!(f, "Frame checksum mismatch"),
+            FrameError::Decode(e) => write!(f, "Malformed frame body: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for FrameError {}
+
+impl From<DecodeError> for FrameError {
+    fn from(e: DecodeError) -> Self {
+        FrameError::Decode(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mpc::{Message, MessageKind};
+
+    fn packet(payload: Vec<u8>) -> Packet {
+        let message = Message { from: 0, to: 1, round: 3, kind: MessageKind::Share, payload };
+        Packet { from: 0, to: 1, round: 3, messages: vec![message] }
+    }
+
+    #[test]
+    fn test_frames_survive_partial_reads_and_reject_bad_peers() {
+        let first = packet(vec![7; 100]);
+        let second = packet(vec![9; 3]);
+        let mut stream = first.to_frame();
+        stream.extend_from_slice(&second.to_frame());
+        assert_eq!(stream.len(), first.framed_len() + second.framed_len());
+
+        // Byte by byte, each packet appears once its last byte is in
+        let mut decoder = FrameDecoder::default();
+        let mut decoded = Vec::new();
+        for byte in &stream {
+            decoder.push(std::slice::from_ref(byte));
+            if let Some(packet) = decoder.next_packet().unwrap() {
+                decoded.push(packet);
+            }
+        }
+        assert_eq!(decoded, [first.clone(), second]);
+        assert_eq!(decoder.buffered(), 0);
+
+        let mut reader = std::io::Cursor::new(first.to_frame());
+        let mut decoder = FrameDecoder::default();
+        while decoder.fill_from(&mut reader).unwrap() > 0 {}
+        assert_eq!(decoder.next_packet().unwrap(), Some(first.clone()));
+
+        // An oversized frame is refused from its header, before the body arrives
+        let mut decoder = FrameDecoder::new(64);
+        decoder.push(&first.to_frame()[..FRAME_HEADER_LEN]);
+        assert!(matches!(decoder.next_frame(), Err(FrameError::TooLarge { max: 64, .. })));
+
+        let mut corrupted = first.to_frame();
+        *corrupted.last_mut().unwrap() ^= 1;
+        let mut decoder = FrameDecoder::default();
+        decoder.push(&corrupted);
+        assert_eq!(decoder.next_frame(), Err(FrameError::ChecksumMismatch));
+
+        let mut decoder = FrameDecoder::default();
+        decoder.push(&[FRAME_VERSION + 1]);
+        assert_eq!(decoder.next_frame(), Err(FrameError::UnsupportedVersion(FRAME_VERSION + 1)));
+
+        // A valid frame around a truncated packet
+        let mut decoder = FrameDecoder::default();
+        let mut frame = Vec::new();
+        encode_frame(&first.to_bytes()[..20], &mut frame);
+        decoder.push(&frame);
+        assert_eq!(decoder.next_packet(), Err(FrameError::Decode(DecodeError::UnexpectedEnd)));
+    }
+}
@@ -0,0 +1,258 @@
+//! Gate circuits with pluggable custom gates
+//!
+//! A `GateCircuit` lists gates in evaluation order over numbered wires:
+//! the inputs come first, and every gate appends its outputs. Besides the
+//! built-in addition and multiplication gates, a circuit may use gates
+//! registered on the executor under a name. Downstream crates add a
+//! domain-specific primitive by implementing `CustomGate` and calling
+//! `ExecCircuit::register_gate`; the executor checks arities, runs the
+//! gate's local computation and accounts for the rounds it declares.
+
+use ark_ff::Field;
+use std::sync::Arc;
+
+use crate::mpc::{ExecCircuit, ExecutionError, ExecutionStats, SecretSharing, WireType};
+
+/// Shares of one wire, one per party
+pub type WireShares<F, SS> = Vec<<SS as SecretSharing<F>>::Share>;
+
+/// A gate implemented outside the executor
+///
+/// Wire values are passed as the shares of every party, in party order, as
+/// everywhere else in the simulated executor.
+pub trait CustomGate<F: Field, SS: SecretSharing<F>>: Send + Sync {
+    /// Name circuits refer to the gate by
+    fn name(&self) -> &str;
+
+    fn num_inputs(&self) -> usize;
+
+    fn num_outputs(&self) -> usize;
+
+    /// Communication rounds one evaluation takes; zero for local gates
+    fn rounds(&self) -> usize;
+
+    /// Field elements each party sends per evaluation
+    fn elements_sent(&self) -> usize {
+        0
+    }
+
+    /// Types the inputs must have; `None` accepts any
+    fn input_types(&self) -> Option<Vec<WireType>> {
+        None
+    }
+
+    /// Types of the outputs, plain field elements by default
+    fn output_types(&self) -> Vec<WireType> {
+        vec![WireType::Field; self.num_outputs()]
+    }
+
+    /// Compute the output wires from the input wires
+    ///
+    /// `executor` gives access to the built-in gates and to labelled
+    /// reveals, which are recorded by its leakage monitor.
+    fn evaluate(
+        &self,
+        executor: &mut ExecCircuit<F, SS>,
+        inputs: &[WireShares<F, SS>],
+    ) -> Result<Vec<WireShares<F, SS>>, ExecutionError>;
+}
+
+/// One gate of a `GateCircuit`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Gate {
+    Add(usize, usize),
+    Mul(usize, usize),
+    /// `if_true` when the bit `condition` is 1, `if_false` when it is 0
+    Select { condition: usize, if_true: usize, if_false: usize },
+    /// A registered custom gate applied to the given wires
+    Custom { name: String, inputs: Vec<usize> },
+}
+
+/// Straight-line circuit over numbered wires
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GateCircuit {
+    pub num_inputs: usize,
+    pub gates: Vec<Gate>,
+    pub outputs: Vec<usize>,
+}
+
+impl<F: Field, SS: SecretSharing<F>> ExecCircuit<F, SS> {
+    /// Make a custom gate available to circuits run by this executor
+    pub fn register_gate(&mut self, gate: Arc<dyn CustomGate<F, SS>>) -> Result<(), ExecutionError> {
+        let name = gate.name().to_string();
+        if self.custom_gates.contains_key(&name) {
+            return Err(ExecutionError::CircuitError(format!("gate '{}' is already registered", name)));
+        }
+        self.custom_gates.insert(name, gate);
+        Ok(())
+    }
+
+    /// Run a gate circuit on shared inputs, returning its output wires
+    pub fn run_gate_circuit(
+        &mut self,
+        circuit: &GateCircuit,
+        inputs: &[WireShares<F, SS>],
+    ) -> Result<(Vec<WireShares<F, SS>>, ExecutionStats), ExecutionError> {
+        if inputs.len() != circuit.num_inputs {
+            return Err(ExecutionError::InvalidInput);
+        }
+        let mut wires: Vec<WireShares<F, SS>> = inputs.to_vec();
+        let mut stats = ExecutionStats::new();
+        let share_size = std::mem::size_of::<F>();
+        for (position, gate) in circuit.gates.iter().enumerate() {
+            let wire = |index: usize, wires: &[WireShares<F, SS>]| {
+                wires.get(index).cloned().ok_or_else(|| {
+                    ExecutionError::CircuitError(format!("gate {} reads undefined wire {}", position, index))
+                })
+            };
+            match gate {
+                Gate::Add(left, right) | Gate::Mul(left, right) => {
+                    let (left, right) = (wire(*left, &wires)?, wire(*right, &wires)?);
+                    let is_mul = matches!(gate, Gate::Mul(..));
+                    let output = left
+                        .iter()
+                        .zip(&right)
+                        .map(|(l, r)| if is_mul { self.mul_gate(l, r) } else { self.add_gate(l, r) })
+                        .collect::<Result<Vec<_>, _>>()?;
+                    if is_mul {
+                        stats.num_mul_gates += 1;
+                    } else {
+                        stats.num_add_gates += 1;
+                    }
+                    wires.push(output);
+                }
+                Gate::Select { condition, if_true, if_false } => {
+                    let condition = wire(*condition, &wires)?;
+                    let (if_true, if_false) = (wire(*if_true, &wires)?, wire(*if_false, &wires)?);
+                    let output = (0..condition.len())
+                        .map(|party| self.select_gate(&condition[party], &if_true[party], &if_false[party]))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    stats.num_mul_gates += 1;
+                    stats.num_add_gates += 2;
+                    wires.push(output);
+                }
+                Gate::Custom { name, inputs } => {
+                    let custom = self
+                        .custom_gates
+                        .get(name)
+                        .cloned()
+                        .ok_or_else(|| ExecutionError::CircuitError(format!("unknown gate '{}'", name)))?;
+                    if inputs.len() != custom.num_inputs() {
+                        return Err(ExecutionError::CircuitError(format!(
+                            "gate '{}' takes {} inputs, got {}",
+                            name,
+                            custom.num_inputs(),
+                            inputs.len()
+                        )));
+                    }
+                    let values = inputs.iter().map(|&index| wire(index, &wires)).collect::<Result<Vec<_>, _>>()?;
+                    let outputs = custom.evaluate(self, &values)?;
+                    if outputs.len() != custom.num_outputs() {
+                        return Err(ExecutionError::CircuitError(format!(
+                            "gate '{}' produced {} outputs instead of {}",
+                            name,
+                            outputs.len(),
+                            custom.num_outputs()
+                        )));
+                    }
+                    stats.communication_rounds += custom.rounds();
+                    stats.bytes_communicated += custom.elements_sent() * self.num_parties * share_size;
+                    wires.extend(outputs);
+                }
+            }
+        }
+        let outputs = circuit
+            .outputs
+            .iter()
+            .map(|&index| {
+                wires
+                    .get(index)
+                    .cloned()
+                    .ok_or_else(|| ExecutionError::CircuitError(format!("output wire {} is undefined", index)))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok((outputs, stats))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mpc::ShamirSecretSharing;
+    use ark_bls12_381::Fr;
+    use ark_std::test_rng;
+
+    type TestField = Fr;
+    type Sharing = ShamirSecretSharing<TestField>;
+
+    /// Public-weight inner product, computed locally
+    struct WeightedSum(Vec<TestField>);
+
+    impl CustomGate<TestField, Sharing> for WeightedSum {
+        fn name(&self) -> &str {
+            "weighted_sum"
+        }
+
+        fn num_inputs(&self) -> usize {
+            self.0.len()
+        }
+
+        fn num_outputs(&self) -> usize {
+            1
+        }
+
+        fn rounds(&self) -> usize {
+            0
+        }
+
+        fn evaluate(
+            &self,
+            executor: &mut ExecCircuit<TestField, Sharing>,
+            inputs: &[WireShares<TestField, Sharing>],
+        ) -> Result<Vec<WireShares<TestField, Sharing>>, ExecutionError> {
+            let output = (0..executor.num_parties)
+                .map(|party| {
+                    let shares: Vec<_> = inputs.iter().map(|wire| wire[party].clone()).collect();
+                    executor.linear_combination_gate(&shares, &self.0)
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(vec![output])
+        }
+    }
+
+    #[test]
+    fn test_registered_gate_runs_in_circuit() {
+        let mut rng = test_rng();
+        let mut executor = ExecCircuit::new(0, 3, Sharing::new());
+        let weights = vec![TestField::from(2u64), TestField::from(3u64)];
+        executor.register_gate(Arc::new(WeightedSum(weights.clone()))).unwrap();
+        assert!(executor.register_gate(Arc::new(WeightedSum(weights))).is_err());
+
+        // (2x + 3y) * x with x = 4, y = 5
+        let circuit = GateCircuit {
+            num_inputs: 2,
+            gates: vec![Gate::Custom { name: "weighted_sum".to_string(), inputs: vec![0, 1] }, Gate::Mul(2, 0)],
+            outputs: vec![3],
+        };
+        let inputs: Vec<_> = [4u64, 5]
+            .iter()
+            .map(|&x| executor.input_secret(TestField::from(x), 1, &mut rng))
+            .collect();
+        let (outputs, stats) = executor.run_gate_circuit(&circuit, &inputs).unwrap();
+        assert_eq!(executor.reveal_secret(&outputs[0]).unwrap(), TestField::from(92u64));
+        assert_eq!(stats.num_mul_gates, 1);
+
+        let unknown = GateCircuit {
+            num_inputs: 2,
+            gates: vec![Gate::Custom { name: "sha256".to_string(), inputs: vec![0, 1] }],
+            outputs: vec![2],
+        };
+        assert!(matches!(executor.run_gate_circuit(&unknown, &inputs), Err(ExecutionError::CircuitError(_))));
+        let wrong_arity = GateCircuit {
+            num_inputs: 2,
+            gates: vec![Gate::Custom { name: "weighted_sum".to_string(), inputs: vec![0] }],
+            outputs: vec![2],
+        };
+        assert!(matches!(executor.run_gate_circuit(&wrong_arity, &inputs), Err(ExecutionError::CircuitError(_))));
+    }
+}
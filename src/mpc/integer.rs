@@ -0,0 +1,367 @@
+//! Integer division, modulo and exponentiation over shares
+//!
+//! Field arithmetic has no notion of integer division or of a secret
+//! exponent, so these gadgets go through bits. `ExecCircuit::bit_decompose`
+//! masks a shared value with random shared bits, opens the masked value and
+//! recovers the bits with a borrow-chain subtractor; the mask is
+//! `STATISTICAL_SECURITY` bits wider than the value, so the opening is
+//! statistically hiding.
+//! `ExecCircuit::div_mod_gate` runs restoring long division on the
+//! dividend's bits, one secure comparison per quotient bit. The relation
+//! the result satisfies is proved to the verifier by the constraints of
+//! `CircuitTemplates::integer_division`. `ExecCircuit::pow_gate` raises a
+//! public base to a shared exponent by square-and-multiply over the
+//! exponent's bits; the squarings are public, so only the multiplications
+//! are done on shares. `ExecCircuit::scalar_mul_gate` does the same in the
+//! Jubjub group, whose points have coordinates in the BLS12-381 scalar
+//! field, for discrete-log relations proved by
+//! `CircuitTemplates::scalar_multiplication`.
+
+use ark_bls12_381::Fr as BlsFr;
+use ark_ec::{twisted_edwards::TECurveConfig, CurveGroup};
+use ark_ed_on_bls12_381::{EdwardsAffine, JubjubConfig};
+use ark_ff::{BigInteger, Field, One, PrimeField, UniformRand, Zero};
+use ark_std::rand::Rng;
+
+use crate::mpc::{ExecCircuit, ExecutionError, SecretSharing, WireShares};
+
+/// Extra mask bits hiding an opened value
+pub const STATISTICAL_SECURITY: usize = 40;
+
+/// Label of the masked openings made by bit decomposition
+pub const BIT_DECOMPOSITION_LABEL: &str = "bit_decomposition_mask";
+
+/// Label of the masked opening that leaves projective coordinates
+pub const INVERSION_MASK_LABEL: &str = "inversion_mask";
+
+/// Shared result of an integer division
+pub struct DivMod<F: PrimeField, SS: SecretSharing<F>> {
+    pub quotient: WireShares<F, SS>,
+    pub remainder: WireShares<F, SS>,
+    /// Bits of the quotient, least significant first
+    pub quotient_bits: Vec<WireShares<F, SS>>,
+}
+
+/// Shared affine coordinates of a Jubjub point
+pub struct PointShares<SS: SecretSharing<BlsFr>> {
+    pub x: WireShares<BlsFr, SS>,
+    pub y: WireShares<BlsFr, SS>,
+}
+
+impl<F: PrimeField, SS: SecretSharing<F>> ExecCircuit<F, SS> {
+    /// Shared bits of `value`, least significant first
+    ///
+    /// `value` must be an integer below `2^num_bits`. Opens one masked value
+    /// under `BIT_DECOMPOSITION_LABEL`, which the reveal policy must permit,
+    /// and takes one multiplication per bit.
+    pub fn bit_decompose(
+        &mut self,
+        value: &WireShares<F, SS>,
+        num_bits: usize,
+        threshold: usize,
+        rng: &mut impl Rng,
+    ) -> Result<Vec<WireShares<F, SS>>, ExecutionError> {
+        if num_bits + STATISTICAL_SECURITY + 1 >= F::MODULUS_BIT_SIZE as usize {
+            return Err(ExecutionError::CircuitError(format!(
+                "cannot decompose {} bits in a {}-bit field",
+                num_bits,
+                F::MODULUS_BIT_SIZE
+            )));
+        }
+        // Random bits would come from preprocessing; the executor deals them
+        let mask_bits: Vec<_> = (0..num_bits + STATISTICAL_SECURITY)
+            .map(|_| self.input_secret(F::from(rng.gen::<bool>()), threshold, rng))
+            .collect();
+        let mut masked = value.clone();
+        let mut power = F::one();
+        for bit in &mask_bits {
+            let term = self.wire_scale(bit, power);
+            masked = self.wire_add(&masked, &term)?;
+            power.double_in_place();
+        }
+        let opened = self.reveal_labeled(BIT_DECOMPOSITION_LABEL, &masked)?.into_bigint();
+
+        // value = opened - mask over the integers; subtract bit by bit
+        let one = self.wire_constant(F::one(), rng);
+        let mut borrow: Option<WireShares<F, SS>> = None;
+        let mut bits = Vec::with_capacity(num_bits);
+        for (i, mask_bit) in mask_bits.iter().take(num_bits).enumerate() {
+            let opened_bit = opened.get_bit(i);
+            // difference = mask_bit xor borrow, either = mask_bit or borrow
+            let (difference, both, either) = match &borrow {
+                None => (mask_bit.clone(), None, mask_bit.clone()),
+                Some(borrow) => {
+                    let both = self.wire_mul(mask_bit, borrow)?;
+                    let sum = self.wire_add(mask_bit, borrow)?;
+                    let twice = self.wire_scale(&both, -F::from(2u64));
+                    let either = self.wire_sub(&sum, &both)?;
+                    (self.wire_add(&sum, &twice)?, Some(both), either)
+                }
+            };
+            if opened_bit {
+                bits.push(self.wire_sub(&one, &difference)?);
+                borrow = both;
+            } else {
+                bits.push(difference);
+                borrow = Some(either);
+            }
+        }
+        Ok(bits)
+    }
+
+    /// `dividend / divisor` and `dividend % divisor` for integers below
+    /// `2^num_bits`
+    ///
+    /// The divisor must be non-zero; a zero divisor yields an all-ones
+    /// quotient here and is rejected by the circuit constraints.
+    pub fn div_mod_gate(
+        &mut self,
+        dividend: &WireShares<F, SS>,
+        divisor: &WireShares<F, SS>,
+        num_bits: usize,
+        threshold: usize,
+        rng: &mut impl Rng,
+    ) -> Result<DivMod<F, SS>, ExecutionError> {
+        let dividend_bits = self.bit_decompose(dividend, num_bits, threshold, rng)?;
+        // remainder - divisor + 2^num_bits lies in [0, 2^(num_bits + 1))
+        let offset = self.wire_constant(F::from(2u64).pow([num_bits as u64]), rng);
+        let mut remainder = self.wire_constant(F::zero(), rng);
+        let mut quotient_bits = vec![Vec::new(); num_bits];
+        for i in (0..num_bits).rev() {
+            let doubled = self.wire_scale(&remainder, F::from(2u64));
+            remainder = self.wire_add(&doubled, &dividend_bits[i])?;
+            let difference = self.wire_sub(&remainder, divisor)?;
+            let shifted = self.wire_add(&difference, &offset)?;
+            let at_least = self.bit_decompose(&shifted, num_bits + 1, threshold, rng)?.swap_remove(num_bits);
+            let subtracted = self.wire_mul(&at_least, divisor)?;
+            remainder = self.wire_sub(&remainder, &subtracted)?;
+            quotient_bits[i] = at_least;
+        }
+        let mut quotient = self.wire_constant(F::zero(), rng);
+        for bit in quotient_bits.iter().rev() {
+            let doubled = self.wire_scale(&quotient, F::from(2u64));
+            quotient = self.wire_add(&doubled, bit)?;
+        }
+        Ok(DivMod { quotient, remainder, quotient_bits })
+    }
+
+    /// `base^exponent` for a public base and an exponent below `2^num_bits`
+    ///
+    /// Each exponent bit selects between `base^(2^i)` and 1, which is linear
+    /// in the bit; the factors are then multiplied together, `num_bits - 1`
+    /// multiplications in all.
+    pub fn pow_gate(
+        &mut self,
+        base: F,
+        exponent: &WireShares<F, SS>,
+        num_bits: usize,
+        threshold: usize,
+        rng: &mut impl Rng,
+    ) -> Result<WireShares<F, SS>, ExecutionError> {
+        let bits = self.bit_decompose(exponent, num_bits, threshold, rng)?;
+        let one = self.wire_constant(F::one(), rng);
+        let mut square = base;
+        let mut result: Option<WireShares<F, SS>> = None;
+        for bit in &bits {
+            // 1 + bit · (base^(2^i) - 1)
+            let step = self.wire_scale(bit, square - F::one());
+            let factor = self.wire_add(&one, &step)?;
+            result = Some(match result {
+                Some(acc) => self.wire_mul(&acc, &factor)?,
+                None => factor,
+            });
+            square.square_in_place();
+        }
+        Ok(result.unwrap_or(one))
+    }
+
+    fn wire_add(&mut self, left: &WireShares<F, SS>, right: &WireShares<F, SS>) -> Result<WireShares<F, SS>, ExecutionError> {
+        left.iter().zip(right).map(|(l, r)| self.add_gate(l, r)).collect()
+    }
+
+    fn wire_sub(&mut self, left: &WireShares<F, SS>, right: &WireShares<F, SS>) -> Result<WireShares<F, SS>, ExecutionError> {
+        let negated = self.wire_scale(right, -F::one());
+        self.wire_add(left, &negated)
+    }
+
+    fn wire_mul(&mut self, left: &WireShares<F, SS>, right: &WireShares<F, SS>) -> Result<WireShares<F, SS>, ExecutionError> {
+        left.iter().zip(right).map(|(l, r)| self.mul_gate(l, r)).collect()
+    }
+
+    fn wire_scale(&self, wire: &WireShares<F, SS>, scalar: F) -> WireShares<F, SS> {
+        wire.iter().map(|share| SS::scalar_mul_share(share, scalar)).collect()
+    }
+
+    /// A public constant; threshold 1 makes it the same for every party
+    fn wire_constant(&mut self, value: F, rng: &mut impl Rng) -> WireShares<F, SS> {
+        self.input_secret(value, 1, rng)
+    }
+}
+
+impl<SS: SecretSharing<BlsFr>> ExecCircuit<BlsFr, SS> {
+    /// `[scalar]base` on Jubjub for a public base and a scalar below `2^num_bits`
+    ///
+    /// Each scalar bit selects between `[2^i]base` and the identity `(0, 1)`,
+    /// which is linear in the bit. The selected points are summed in
+    /// projective coordinates with the complete twisted Edwards addition, ten
+    /// multiplications per bit, and a masked inversion of `Z` returns to
+    /// affine coordinates. That opens one uniformly random value under
+    /// `INVERSION_MASK_LABEL` besides the bit decomposition's mask.
+    pub fn scalar_mul_gate(
+        &mut self,
+        base: EdwardsAffine,
+        scalar: &WireShares<BlsFr, SS>,
+        num_bits: usize,
+        threshold: usize,
+        rng: &mut impl Rng,
+    ) -> Result<PointShares<SS>, ExecutionError> {
+        let bits = self.bit_decompose(scalar, num_bits, threshold, rng)?;
+        let one = self.wire_constant(BlsFr::one(), rng);
+        let mut power = base;
+        let mut sum: Option<[WireShares<BlsFr, SS>; 3]> = None;
+        for bit in &bits {
+            // (bit · x_i, 1 + bit · (y_i - 1)) with Z = 1
+            let x = self.wire_scale(bit, power.x);
+            let step = self.wire_scale(bit, power.y - BlsFr::one());
+            let y = self.wire_add(&one, &step)?;
+            sum = Some(match sum {
+                Some(acc) => self.projective_add(&acc, &x, &y)?,
+                None => [x, y, one.clone()],
+            });
+            power = (power + power).into_affine();
+        }
+        let Some([x, y, z]) = sum else {
+            return Ok(PointShares { x: self.wire_constant(BlsFr::zero(), rng), y: one });
+        };
+
+        // Z is never zero; open r·Z for a random r and scale r by its inverse.
+        // The mask would come from preprocessing; the executor deals it
+        let mask = self.input_secret(BlsFr::rand(rng), threshold, rng);
+        let masked = self.wire_mul(&mask, &z)?;
+        let inverse = self
+            .reveal_labeled(INVERSION_MASK_LABEL, &masked)?
+            .inverse()
+            .ok_or_else(|| ExecutionError::CircuitError("zero inversion mask".to_string()))?;
+        let z_inverse = self.wire_scale(&mask, inverse);
+        Ok(PointShares { x: self.wire_mul(&x, &z_inverse)?, y: self.wire_mul(&y, &z_inverse)? })
+    }
+
+    /// `(X1 : Y1 : Z1) + (x2, y2)` by the projective twisted Edwards formulas
+    fn projective_add(
+        &mut self,
+        [x1, y1, z1]: &[WireShares<BlsFr, SS>; 3],
+        x2: &WireShares<BlsFr, SS>,
+        y2: &WireShares<BlsFr, SS>,
+    ) -> Result<[WireShares<BlsFr, SS>; 3], ExecutionError> {
+        let b = self.wire_mul(z1, z1)?;
+        let c = self.wire_mul(x1, x2)?;
+        let d = self.wire_mul(y1, y2)?;
+        let cd = self.wire_mul(&c, &d)?;
+        let e = self.wire_scale(&cd, JubjubConfig::COEFF_D);
+        let f = self.wire_sub(&b, &e)?;
+        let g = self.wire_add(&b, &e)?;
+
+        // X3 = Z1·F·((X1 + Y1)(x2 + y2) - C - D)
+        let left = self.wire_add(x1, y1)?;
+        let right = self.wire_add(x2, y2)?;
+        let cross = self.wire_mul(&left, &right)?;
+        let cross = self.wire_sub(&cross, &c)?;
+        let cross = self.wire_sub(&cross, &d)?;
+        let z1_f = self.wire_mul(z1, &f)?;
+        let x3 = self.wire_mul(&z1_f, &cross)?;
+
+        // Y3 = Z1·G·(D - a·C), Z3 = F·G
+        let a_c = self.wire_scale(&c, JubjubConfig::COEFF_A);
+        let d_minus_a_c = self.wire_sub(&d, &a_c)?;
+        let z1_g = self.wire_mul(z1, &g)?;
+        let y3 = self.wire_mul(&z1_g, &d_minus_a_c)?;
+        let z3 = self.wire_mul(&f, &g)?;
+        Ok([x3, y3, z3])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mpc::{RevealPolicy, ShamirSecretSharing};
+    use ark_bls12_381::Fr;
+    use ark_ec::AffineRepr;
+    use ark_ed_on_bls12_381::Fr as JubjubScalar;
+    use ark_std::test_rng;
+
+    type TestField = Fr;
+
+    #[test]
+    fn test_div_mod_over_shares() {
+        let mut rng = test_rng();
+        let mut executor = ExecCircuit::new(0, 3, ShamirSecretSharing::<TestField>::new());
+
+        let value = executor.input_secret(TestField::from(0b1011_0110u64), 1, &mut rng);
+        let bits = executor.bit_decompose(&value, 8, 1, &mut rng).unwrap();
+        let revealed: Vec<_> = bits.iter().map(|bit| executor.reveal_secret(bit).unwrap()).collect();
+        let expected: Vec<_> = [0u64, 1, 1, 0, 1, 1, 0, 1].iter().map(|&b| TestField::from(b)).collect();
+        assert_eq!(revealed, expected);
+
+        for (dividend, divisor) in [(1000u64, 7u64), (4095, 4095), (6, 9), (0, 1)] {
+            let x = executor.input_secret(TestField::from(dividend), 1, &mut rng);
+            let d = executor.input_secret(TestField::from(divisor), 1, &mut rng);
+            let result = executor.div_mod_gate(&x, &d, 12, 1, &mut rng).unwrap();
+            assert_eq!(executor.reveal_secret(&result.quotient).unwrap(), TestField::from(dividend / divisor));
+            assert_eq!(executor.reveal_secret(&result.remainder).unwrap(), TestField::from(dividend % divisor));
+            assert_eq!(result.quotient_bits.len(), 12);
+        }
+
+        // Only the masked openings are revealed, and a policy can forbid them
+        executor.leakage.set_policy(RevealPolicy::DenyAll);
+        assert!(matches!(
+            executor.bit_decompose(&value, 8, 1, &mut rng),
+            Err(ExecutionError::RevealBlocked(_))
+        ));
+        assert!(executor.bit_decompose(&value, 250, 1, &mut rng).is_err());
+    }
+
+    #[test]
+    fn test_pow_gate_over_shares() {
+        let mut rng = test_rng();
+        let mut executor = ExecCircuit::new(0, 3, ShamirSecretSharing::<TestField>::new());
+
+        let base = TestField::from(3u64);
+        for exponent in [0u64, 1, 13, 31] {
+            let x = executor.input_secret(TestField::from(exponent), 1, &mut rng);
+            let power = executor.pow_gate(base, &x, 5, 1, &mut rng).unwrap();
+            assert_eq!(executor.reveal_secret(&power).unwrap(), base.pow([exponent]));
+        }
+
+        // The exponent's bits come from a masked opening, which a policy can forbid
+        let x = executor.input_secret(TestField::from(7u64), 1, &mut rng);
+        executor.leakage.set_policy(RevealPolicy::DenyAll);
+        assert!(matches!(
+            executor.pow_gate(base, &x, 5, 1, &mut rng),
+            Err(ExecutionError::RevealBlocked(_))
+        ));
+    }
+
+    #[test]
+    fn test_scalar_mul_gate_over_shares() {
+        let mut rng = test_rng();
+        let mut executor = ExecCircuit::new(0, 3, ShamirSecretSharing::<TestField>::new());
+
+        let base = EdwardsAffine::generator();
+        for scalar in [0u64, 1, 13, 31] {
+            let x = executor.input_secret(TestField::from(scalar), 1, &mut rng);
+            let point = executor.scalar_mul_gate(base, &x, 5, 1, &mut rng).unwrap();
+            let expected = (base * JubjubScalar::from(scalar)).into_affine();
+            assert_eq!(executor.reveal_secret(&point.x).unwrap(), expected.x);
+            assert_eq!(executor.reveal_secret(&point.y).unwrap(), expected.y);
+        }
+        assert_eq!(executor.leakage.counts_by_label()[INVERSION_MASK_LABEL], 4);
+
+        // Leaving projective coordinates opens a masked value, which a policy can forbid
+        let x = executor.input_secret(TestField::from(7u64), 1, &mut rng);
+        executor.leakage.set_policy(RevealPolicy::allow_only([BIT_DECOMPOSITION_LABEL]));
+        assert!(matches!(
+            executor.scalar_mul_gate(base, &x, 5, 1, &mut rng),
+            Err(ExecutionError::RevealBlocked(_))
+        ));
+    }
+}
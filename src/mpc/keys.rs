@@ -0,0 +1,401 @@
+//! Worker key material and its rotation
+//!
+//! Every worker holds, per key epoch, a share of the SPDZ MAC key and a
+//! Schnorr signing key on Jubjub. Both are re-derived from the worker's
+//! master secret for each epoch, so rotating only requires agreeing on the
+//! new epoch number. Sessions are pinned to the epoch that was current when
+//! they began and keep using it until they end; a retired epoch is dropped
+//! only once no pinned session remains and it has fallen out of the grace
+//! window. Messages carry a versioned `KeyId` so receivers can pick the
+//! matching verification key during a handover.
+
+use std::collections::{BTreeMap, HashMap};
+
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ed_on_bls12_381::{EdwardsAffine, Fr as JubjubScalar};
+use ark_ff::PrimeField;
+use ark_std::rand::Rng;
+use sha2::{Digest, Sha256};
+
+use crate::custom_circuits::SchnorrSignature;
+use crate::mpc::SessionId;
+use crate::serialization::FieldEncoding;
+
+const KEY_DERIVATION_DOMAIN: &[u8] = b"EOS-worker-keys-v1";
+const MESSAGE_DOMAIN: &[u8] = b"EOS-signed-message-v1";
+const ANNOUNCEMENT_DOMAIN: &[u8] = b"EOS-key-announcement-v1";
+
+/// Versioned identifier of one worker's keys
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct KeyId {
+    pub party: usize,
+    pub epoch: u32,
+}
+
+impl KeyId {
+    fn to_bytes(self) -> [u8; 12] {
+        let mut bytes = [0u8; 12];
+        bytes[..8].copy_from_slice(&(self.party as u64).to_le_bytes());
+        bytes[8..].copy_from_slice(&self.epoch.to_le_bytes());
+        bytes
+    }
+}
+
+/// When keys are rotated and how long old epochs are kept
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RotationPolicy {
+    /// Seconds between rotations
+    pub interval_secs: u64,
+    /// Number of previous epochs kept for verification after rotation
+    pub grace_epochs: u32,
+}
+
+impl Default for RotationPolicy {
+    fn default() -> Self {
+        Self { interval_secs: 24 * 60 * 60, grace_epochs: 1 }
+    }
+}
+
+/// Keys of one epoch
+#[derive(Debug, Clone)]
+pub struct EpochKeys<F: PrimeField> {
+    pub id: KeyId,
+    /// This worker's additive share of the SPDZ MAC key
+    pub mac_key_share: F,
+    pub verifying_key: EdwardsAffine,
+    signing_key: JubjubScalar,
+    /// Time at which the epoch became current
+    pub activated_at: u64,
+}
+
+/// A payload signed under a specific key version
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignedMessage {
+    pub key_id: KeyId,
+    pub payload: Vec<u8>,
+    pub signature: SchnorrSignature,
+}
+
+/// Announcement of a new epoch, endorsed by the previous epoch's key
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyAnnouncement {
+    pub key_id: KeyId,
+    pub verifying_key: EdwardsAffine,
+    /// Signature by the previous epoch over the new key; `None` for epoch 0
+    pub endorsement: Option<SignedMessage>,
+}
+
+/// A worker's keys across epochs
+pub struct WorkerKeyring<F: PrimeField> {
+    party: usize,
+    master_secret: [u8; 32],
+    policy: RotationPolicy,
+    epochs: BTreeMap<u32, EpochKeys<F>>,
+    current: u32,
+    pinned: HashMap<SessionId, u32>,
+}
+
+impl<F: PrimeField> WorkerKeyring<F> {
+    /// Create a keyring whose epoch 0 becomes current at `now`
+    pub fn new(party: usize, master_secret: [u8; 32], policy: RotationPolicy, now: u64) -> Self {
+        let mut keyring = Self {
+            party,
+            master_secret,
+            policy,
+            epochs: BTreeMap::new(),
+            current: 0,
+            pinned: HashMap::new(),
+        };
+        let keys = keyring.derive(0, now);
+        keyring.epochs.insert(0, keys);
+        keyring
+    }
+
+    fn derive(&self, epoch: u32, now: u64) -> EpochKeys<F> {
+        let id = KeyId { party: self.party, epoch };
+        let expand = |label: &[u8]| -> Vec<u8> {
+            // Two blocks so the reduction modulo the group order is nearly uniform
+            (0u8..2)
+                .flat_map(|block| {
+                    Sha256::new()
+                        .chain_update(KEY_DERIVATION_DOMAIN)
+                        .chain_update(self.master_secret)
+                        .chain_update(label)
+                        .chain_update(id.to_bytes())
+                        .chain_update([block])
+                        .finalize()
+                })
+                .collect()
+        };
+        let signing_key = JubjubScalar::from_le_bytes_mod_order(&expand(b"signing"));
+        EpochKeys {
+            id,
+            mac_key_share: FieldEncoding::wire::<F>().reduce(&expand(b"mac")),
+            verifying_key: (EdwardsAffine::generator() * signing_key).into_affine(),
+            signing_key,
+            activated_at: now,
+        }
+    }
+
+    pub fn current_id(&self) -> KeyId {
+        KeyId { party: self.party, epoch: self.current }
+    }
+
+    pub fn current_keys(&self) -> &EpochKeys<F> {
+        &self.epochs[&self.current]
+    }
+
+    /// Keys of an epoch that has not been retired
+    pub fn keys(&self, id: KeyId) -> Result<&EpochKeys<F>, KeyError> {
+        if id.party != self.party {
+            return Err(KeyError::UnknownKey(id));
+        }
+        self.epochs.get(&id.epoch).ok_or(KeyError::Retired(id))
+    }
+
+    /// Rotate if the current epoch is older than the policy interval
+    pub fn maybe_rotate<R: Rng + ?Sized>(&mut self, now: u64, rng: &mut R) -> Option<KeyAnnouncement> {
+        let activated_at = self.current_keys().activated_at;
+        if now.saturating_sub(activated_at) >= self.policy.interval_secs {
+            Some(self.rotate(now, rng))
+        } else {
+            None
+        }
+    }
+
+    /// Start a new epoch; sessions already running keep their pinned epoch
+    pub fn rotate<R: Rng + ?Sized>(&mut self, now: u64, rng: &mut R) -> KeyAnnouncement {
+        let previous = self.current_id();
+        let next = self.current + 1;
+        let keys = self.derive(next, now);
+        let mut statement = ANNOUNCEMENT_DOMAIN.to_vec();
+        statement.extend_from_slice(&keys.id.to_bytes());
+        statement.extend_from_slice(&point_bytes(&keys.verifying_key));
+        let announcement = KeyAnnouncement {
+            key_id: keys.id,
+            verifying_key: keys.verifying_key,
+            endorsement: Some(
+                self.sign(previous, &statement, rng)
+                    .expect("current epoch is always available"),
+            ),
+        };
+
+        self.epochs.insert(next, keys);
+        self.current = next;
+        self.prune();
+        announcement
+    }
+
+    /// Announcement of the initial epoch, to be distributed out of band
+    pub fn initial_announcement(&self) -> Result<KeyAnnouncement, KeyError> {
+        let keys = self.keys(KeyId { party: self.party, epoch: 0 })?;
+        Ok(KeyAnnouncement { key_id: keys.id, verifying_key: keys.verifying_key, endorsement: None })
+    }
+
+    /// Pin a new session to the current epoch
+    pub fn begin_session(&mut self, session: SessionId) -> KeyId {
+        self.pinned.insert(session, self.current);
+        self.current_id()
+    }
+
+    /// Key version used by a running session
+    pub fn session_key(&self, session: SessionId) -> Result<KeyId, KeyError> {
+        self.pinned
+            .get(&session)
+            .map(|&epoch| KeyId { party: self.party, epoch })
+            .ok_or(KeyError::UnknownSession(session))
+    }
+
+    /// Release a session's pin and drop epochs no longer needed
+    pub fn end_session(&mut self, session: SessionId) -> Result<(), KeyError> {
+        self.pinned.remove(&session).ok_or(KeyError::UnknownSession(session))?;
+        self.prune();
+        Ok(())
+    }
+
+    fn prune(&mut self) {
+        let oldest_kept = self.current.saturating_sub(self.policy.grace_epochs);
+        let pinned = &self.pinned;
+        self.epochs
+            .retain(|epoch, _| *epoch >= oldest_kept || pinned.values().any(|p| p == epoch));
+    }
+
+    /// Sign a payload with the keys of `key_id`
+    pub fn sign<R: Rng + ?Sized>(&self, key_id: KeyId, payload: &[u8], rng: &mut R) -> Result<SignedMessage, KeyError> {
+        let keys = self.keys(key_id)?;
+        let signature = SchnorrSignature::sign(keys.signing_key, message_digest(key_id, payload), rng);
+        Ok(SignedMessage { key_id, payload: payload.to_vec(), signature })
+    }
+
+    /// Sign a payload with the keys pinned to `session`
+    pub fn sign_for_session<R: Rng + ?Sized>(
+        &self,
+        session: SessionId,
+        payload: &[u8],
+        rng: &mut R,
+    ) -> Result<SignedMessage, KeyError> {
+        self.sign(self.session_key(session)?, payload, rng)
+    }
+}
+
+fn point_bytes(point: &EdwardsAffine) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    crate::serialization::write_field(&mut bytes, &point.x);
+    crate::serialization::write_field(&mut bytes, &point.y);
+    bytes
+}
+
+fn message_digest<F: PrimeField>(key_id: KeyId, payload: &[u8]) -> F {
+    let digest = Sha256::new()
+        .chain_update(MESSAGE_DOMAIN)
+        .chain_update(key_id.to_bytes())
+        .chain_update(payload)
+        .finalize();
+    FieldEncoding::wire::<F>().reduce(&digest)
+}
+
+/// Verification keys of all workers, indexed by key version
+#[derive(Debug, Clone, Default)]
+pub struct KeyDirectory {
+    keys: HashMap<KeyId, EdwardsAffine>,
+}
+
+impl KeyDirectory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Accept an announcement; later epochs must be endorsed by a known key
+    /// of the same worker
+    pub fn accept(&mut self, announcement: &KeyAnnouncement) -> Result<(), KeyError> {
+        match &announcement.endorsement {
+            None if announcement.key_id.epoch == 0 => {}
+            None => return Err(KeyError::BadEndorsement(announcement.key_id)),
+            Some(endorsement) => {
+                let mut statement = ANNOUNCEMENT_DOMAIN.to_vec();
+                statement.extend_from_slice(&announcement.key_id.to_bytes());
+                statement.extend_from_slice(&point_bytes(&announcement.verifying_key));
+                let endorser = endorsement.key_id;
+                if endorser.party != announcement.key_id.party
+                    || endorser.epoch >= announcement.key_id.epoch
+                    || endorsement.payload != statement
+                {
+                    return Err(KeyError::BadEndorsement(announcement.key_id));
+                }
+                self.verify(endorsement)
+                    .map_err(|_| KeyError::BadEndorsement(announcement.key_id))?;
+            }
+        }
+        self.keys.insert(announcement.key_id, announcement.verifying_key);
+        Ok(())
+    }
+
+    /// Forget a retired key version
+    pub fn retire(&mut self, key_id: KeyId) {
+        self.keys.remove(&key_id);
+    }
+
+    pub fn verify(&self, message: &SignedMessage) -> Result<(), KeyError> {
+        let verifying_key = self.keys.get(&message.key_id).ok_or(KeyError::UnknownKey(message.key_id))?;
+        if message
+            .signature
+            .verify(verifying_key, message_digest(message.key_id, &message.payload))
+        {
+            Ok(())
+        } else {
+            Err(KeyError::InvalidSignature(message.key_id))
+        }
+    }
+}
+
+/// Key management error types
+#[derive(Debug, Clone, PartialEq)]
+pub enum KeyError {
+    UnknownKey(KeyId),
+    Retired(KeyId),
+    UnknownSession(SessionId),
+    InvalidSignature(KeyId),
+    BadEndorsement(KeyId),
+}
+
+impl std::fmt::Display for KeyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            KeyError::UnknownKey(id) => write!(f, "Unknown key {:?}", id),
+            KeyError::Retired(id) => write!(f, "Key {:?} has been retired", id),
+            KeyError::UnknownSession(s) => write!(f, "Session {} is not pinned to a key", s),
+            KeyError::InvalidSignature(id) => write!(f, "Invalid signature under key {:?}", id),
+            KeyError::BadEndorsement(id) => write!(f, "Announcement of key {:?} is not properly endorsed", id),
+        }
+    }
+}
+
+impl std::error::Error for KeyError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::Fr;
+    use ark_std::test_rng;
+
+    type TestField = Fr;
+
+    fn keyring(party: usize) -> WorkerKeyring<TestField> {
+        let policy = RotationPolicy { interval_secs: 100, grace_epochs: 0 };
+        WorkerKeyring::new(party, [party as u8; 32], policy, 0)
+    }
+
+    #[test]
+    fn test_rotation_does_not_interrupt_sessions() {
+        let mut rng = test_rng();
+        let mut worker = keyring(1);
+        let mut directory = KeyDirectory::new();
+        directory.accept(&worker.initial_announcement().unwrap()).unwrap();
+
+        let old = worker.begin_session(7);
+        assert!(worker.maybe_rotate(50, &mut rng).is_none());
+        let announcement = worker.maybe_rotate(100, &mut rng).unwrap();
+        directory.accept(&announcement).unwrap();
+        assert_eq!(worker.current_id().epoch, 1);
+        assert_ne!(worker.keys(old).unwrap().mac_key_share, worker.current_keys().mac_key_share);
+
+        // The running session still signs under epoch 0; new sessions use epoch 1
+        let message = worker.sign_for_session(7, b"round 3", &mut rng).unwrap();
+        assert_eq!(message.key_id.epoch, 0);
+        directory.verify(&message).unwrap();
+        assert_eq!(worker.begin_session(8).epoch, 1);
+
+        // Epoch 0 is dropped once its last session ends
+        worker.end_session(7).unwrap();
+        assert_eq!(worker.keys(old).unwrap_err(), KeyError::Retired(old));
+        assert!(matches!(worker.sign_for_session(7, b"late", &mut rng), Err(KeyError::UnknownSession(7))));
+    }
+
+    #[test]
+    fn test_forged_messages_and_announcements_are_rejected() {
+        let mut rng = test_rng();
+        let mut worker = keyring(1);
+        let mut other = keyring(2);
+        let mut directory = KeyDirectory::new();
+        directory.accept(&worker.initial_announcement().unwrap()).unwrap();
+
+        let mut message = worker.sign(worker.current_id(), b"payload", &mut rng).unwrap();
+        message.payload = b"tampered".to_vec();
+        assert!(matches!(directory.verify(&message), Err(KeyError::InvalidSignature(_))));
+
+        // A key version cannot be claimed by relabelling the message
+        let mut relabelled = worker.sign(worker.current_id(), b"payload", &mut rng).unwrap();
+        relabelled.key_id.epoch = 1;
+        assert!(matches!(directory.verify(&relabelled), Err(KeyError::UnknownKey(_))));
+
+        // An epoch announced for worker 1 but endorsed by worker 2 is refused
+        let mut forged = other.rotate(10, &mut rng);
+        forged.key_id.party = 1;
+        assert!(matches!(directory.accept(&forged), Err(KeyError::BadEndorsement(_))));
+
+        let genuine = worker.rotate(10, &mut rng);
+        directory.accept(&genuine).unwrap();
+        let fresh = worker.sign(worker.current_id(), b"payload", &mut rng).unwrap();
+        directory.verify(&fresh).unwrap();
+    }
+}
@@ -0,0 +1,192 @@
+//! Leakage accounting for reveals of secret-derived data
+//!
+//! Every place that turns shares back into a clear value, or opens a
+//! commitment to a secret-derived polynomial, reports to a `LeakageMonitor`.
+//! The monitor records the label and call site of each reveal and consults a
+//! `RevealPolicy`, so tests can pin down exactly which reveals a protocol run
+//! is expected to perform and fail on any other.
+
+use std::collections::{BTreeMap, HashSet};
+use std::panic::Location;
+use std::sync::Mutex;
+
+/// One reveal of secret-derived data
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RevealRecord {
+    /// What was revealed, e.g. "output" or "witness_opening"
+    pub label: String,
+    /// Source location of the reveal
+    pub location: &'static Location<'static>,
+    /// Number of values revealed
+    pub count: usize,
+}
+
+/// Which reveals are permitted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RevealPolicy {
+    AllowAll,
+    AllowOnly(HashSet<String>),
+    DenyAll,
+}
+
+impl RevealPolicy {
+    /// Permit only the given labels
+    pub fn allow_only<'a>(labels: impl IntoIterator<Item = &'a str>) -> Self {
+        RevealPolicy::AllowOnly(labels.into_iter().map(str::to_string).collect())
+    }
+
+    pub fn permits(&self, label: &str) -> bool {
+        match self {
+            RevealPolicy::AllowAll => true,
+            RevealPolicy::AllowOnly(labels) => labels.contains(label),
+            RevealPolicy::DenyAll => false,
+        }
+    }
+}
+
+/// Records reveals and enforces a reveal policy
+#[derive(Debug)]
+pub struct LeakageMonitor {
+    policy: RevealPolicy,
+    records: Mutex<Vec<RevealRecord>>,
+    blocked: Mutex<Vec<RevealRecord>>,
+}
+
+impl LeakageMonitor {
+    pub fn new(policy: RevealPolicy) -> Self {
+        Self {
+            policy,
+            records: Mutex::new(Vec::new()),
+            blocked: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn set_policy(&mut self, policy: RevealPolicy) {
+        self.policy = policy;
+    }
+
+    /// Report a reveal; returns an error if the policy blocks it
+    #[track_caller]
+    pub fn record(&self, label: &str, count: usize) -> Result<(), RevealBlocked> {
+        let record = RevealRecord {
+            label: label.to_string(),
+            location: Location::caller(),
+            count,
+        };
+        if !self.policy.permits(label) {
+            self.blocked.lock().expect("leakage monitor poisoned").push(record.clone());
+            return Err(RevealBlocked(record));
+        }
+        self.records.lock().expect("leakage monitor poisoned").push(record);
+        Ok(())
+    }
+
+    /// All permitted reveals so far
+    pub fn records(&self) -> Vec<RevealRecord> {
+        self.records.lock().expect("leakage monitor poisoned").clone()
+    }
+
+    /// Reveals the policy refused
+    pub fn blocked(&self) -> Vec<RevealRecord> {
+        self.blocked.lock().expect("leakage monitor poisoned").clone()
+    }
+
+    /// Total values revealed per label
+    pub fn counts_by_label(&self) -> BTreeMap<String, usize> {
+        let mut counts = BTreeMap::new();
+        for record in self.records.lock().expect("leakage monitor poisoned").iter() {
+            *counts.entry(record.label.clone()).or_insert(0) += record.count;
+        }
+        counts
+    }
+
+    /// Human-readable log of every reveal
+    pub fn report(&self) -> String {
+        let mut lines = Vec::new();
+        for record in self.records() {
+            lines.push(format!("revealed {} x{} at {}", record.label, record.count, record.location));
+        }
+        for record in self.blocked() {
+            lines.push(format!("BLOCKED {} x{} at {}", record.label, record.count, record.location));
+        }
+        lines.join("\n")
+    }
+}
+
+impl Default for LeakageMonitor {
+    fn default() -> Self {
+        Self::new(RevealPolicy::AllowAll)
+    }
+}
+
+/// A reveal refused by the active policy
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RevealBlocked(pub RevealRecord);
+
+impl std::fmt::Display for RevealBlocked {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Reveal of '{}' at {} blocked by policy", self.0.label, self.0.location)
+    }
+}
+
+impl std::error::Error for RevealBlocked {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mpc::{ExecCircuit, ExecutionError, SecretSharing, ShamirSecretSharing};
+    use ark_bls12_381::Fr;
+    use ark_std::test_rng;
+
+    type TestField = Fr;
+
+    #[test]
+    fn test_reveals_are_counted_and_policed() {
+        let mut rng = test_rng();
+        let mut executor = ExecCircuit::new(1, 3, ShamirSecretSharing::<TestField>::new());
+        executor.leakage.set_policy(RevealPolicy::allow_only(["output"]));
+
+        let shares = ShamirSecretSharing::share_secret(TestField::from(9u64), 2, 3, &mut rng);
+        assert_eq!(executor.reveal_labeled("output", &shares).unwrap(), TestField::from(9u64));
+        assert_eq!(executor.reveal_labeled("output", &shares).unwrap(), TestField::from(9u64));
+
+        // An unexpected reveal, e.g. from a new gate, is refused and logged
+        let err = executor.reveal_labeled("intermediate_wire", &shares).unwrap_err();
+        assert!(matches!(err, ExecutionError::RevealBlocked(_)));
+        assert!(executor.reveal_secret(&shares).is_err());
+
+        assert_eq!(executor.leakage.counts_by_label().get("output"), Some(&2));
+        let blocked = executor.leakage.blocked();
+        assert_eq!(blocked.len(), 2);
+        assert_eq!(blocked[0].label, "intermediate_wire");
+        assert_eq!(blocked[0].location.file(), file!());
+        assert!(executor.leakage.report().contains("BLOCKED intermediate_wire"));
+    }
+
+    #[test]
+    #[cfg(feature = "delegator")]
+    fn test_delegation_reports_witness_opening() {
+        use crate::mpc::CollaborationMode;
+        use crate::protocol::{EOSError, EOSProtocol};
+        use ark_bls12_381::Bls12_381;
+        use ark_relations::r1cs::ConstraintSystem;
+
+        type Protocol = EOSProtocol<Bls12_381, TestField, ShamirSecretSharing<TestField>, CollaborationMode>;
+
+        let mut rng = test_rng();
+        let executor = ExecCircuit::new(1, 3, ShamirSecretSharing::new());
+        let mut protocol = Protocol::new(executor, CollaborationMode::new(2, true, false), 128, &mut rng);
+        let circuit = ConstraintSystem::new();
+        protocol.preprocessing_state = Some(Protocol::preprocessing(&circuit, 128, &mut rng).unwrap());
+        let witness = [TestField::from(3u64), TestField::from(4u64)];
+
+        protocol.delegate_computation(&circuit, &witness, &[], &mut rng).unwrap();
+        assert_eq!(protocol.circuit_executor.leakage.counts_by_label().get("witness_opening"), Some(&1));
+
+        protocol.circuit_executor.leakage.set_policy(RevealPolicy::DenyAll);
+        assert!(matches!(
+            protocol.delegate_computation(&circuit, &witness, &[], &mut rng),
+            Err(EOSError::MPCError(ExecutionError::RevealBlocked(_)))
+        ));
+    }
+}
@@ -0,0 +1,364 @@
+//! Heartbeats and liveness monitoring for worker committees
+//!
+//! Every worker sends a `Heartbeat` to the delegator once per interval over
+//! the message runtime. The `LivenessMonitor` tracks when each worker was
+//! last heard from and how long its heartbeats take to arrive, and marks it
+//! slow or dead after missed intervals. Dropout recovery asks the monitor
+//! for a reconstruction set: `threshold + 1` workers that are still alive,
+//! preferring responsive ones. Times are milliseconds on the caller's
+//! clock, so the monitor is deterministic under a simulated network.
+
+use std::collections::BTreeMap;
+
+use crate::mpc::{Message, MessageKind, MessageRuntime, NetworkError};
+use crate::serialization::{write_u64, ByteReader, DecodeError, WireFormat};
+
+/// Heartbeat payload
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Heartbeat {
+    pub party: usize,
+    pub sequence: u64,
+    /// Sender's clock when the heartbeat left, in milliseconds
+    pub sent_at_ms: u64,
+}
+
+impl WireFormat for Heartbeat {
+    fn encode(&self, out: &mut Vec<u8>) {
+        write_u64(out, self.party as u64);
+        write_u64(out, self.sequence);
+        write_u64(out, self.sent_at_ms);
+    }
+
+    fn decode(reader: &mut ByteReader<'_>) -> Result<Self, DecodeError> {
+        Ok(Heartbeat {
+            party: reader.read_usize()?,
+            sequence: reader.read_u64()?,
+            sent_at_ms: reader.read_u64()?,
+        })
+    }
+}
+
+/// Queue a heartbeat from `from` to `to` in the current round
+pub fn send_heartbeat(
+    runtime: &mut MessageRuntime,
+    from: usize,
+    to: usize,
+    sequence: u64,
+    now_ms: u64,
+) -> Result<(), NetworkError> {
+    let heartbeat = Heartbeat { party: from, sequence, sent_at_ms: now_ms };
+    runtime.send(from, to, MessageKind::Heartbeat, heartbeat.to_bytes())
+}
+
+/// Timing policy of the monitor
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LivenessConfig {
+    /// Interval between heartbeats
+    pub interval_ms: u64,
+    /// Missed intervals before a worker counts as slow
+    pub slow_after: u64,
+    /// Missed intervals before a worker counts as dead
+    pub dead_after: u64,
+    /// Heartbeat delay above which a worker counts as slow
+    pub slow_latency_ms: u64,
+}
+
+impl Default for LivenessConfig {
+    fn default() -> Self {
+        Self { interval_ms: 1_000, slow_after: 2, dead_after: 5, slow_latency_ms: 500 }
+    }
+}
+
+/// Liveness of one worker
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum WorkerStatus {
+    Alive,
+    /// Missing heartbeats or lagging, but not yet given up on
+    Slow,
+    Dead,
+}
+
+#[derive(Debug, Clone)]
+struct WorkerRecord {
+    status: WorkerStatus,
+    registered_at_ms: u64,
+    last_seen_ms: u64,
+    last_sequence: Option<u64>,
+    heartbeats: u64,
+    total_latency_ms: u64,
+    last_latency_ms: u64,
+}
+
+/// Tracks heartbeats of a committee and classifies its workers
+#[derive(Debug, Clone)]
+pub struct LivenessMonitor {
+    config: LivenessConfig,
+    workers: BTreeMap<usize, WorkerRecord>,
+}
+
+impl LivenessMonitor {
+    pub fn new(workers: impl IntoIterator<Item = usize>, config: LivenessConfig, now_ms: u64) -> Self {
+        let record = WorkerRecord {
+            status: WorkerStatus::Alive,
+            registered_at_ms: now_ms,
+            last_seen_ms: now_ms,
+            last_sequence: None,
+            heartbeats: 0,
+            total_latency_ms: 0,
+            last_latency_ms: 0,
+        };
+        Self {
+            config,
+            workers: workers.into_iter().map(|party| (party, record.clone())).collect(),
+        }
+    }
+
+    /// Record a heartbeat received at `received_at_ms`; stale or replayed
+    /// sequence numbers are ignored
+    pub fn record_heartbeat(&mut self, heartbeat: &Heartbeat, received_at_ms: u64) -> Result<(), LivenessError> {
+        let record = self
+            .workers
+            .get_mut(&heartbeat.party)
+            .ok_or(LivenessError::UnknownWorker(heartbeat.party))?;
+        if record.last_sequence.is_some_and(|last| heartbeat.sequence <= last) {
+            return Ok(());
+        }
+        let latency = received_at_ms.saturating_sub(heartbeat.sent_at_ms);
+        record.last_sequence = Some(heartbeat.sequence);
+        record.last_seen_ms = record.last_seen_ms.max(received_at_ms);
+        record.heartbeats += 1;
+        record.total_latency_ms += latency;
+        record.last_latency_ms = latency;
+        Ok(())
+    }
+
+    /// Record every heartbeat among `messages`, e.g. a runtime inbox
+    pub fn observe(&mut self, messages: &[Message], received_at_ms: u64) -> Result<(), LivenessError> {
+        for message in messages.iter().filter(|message| message.kind == MessageKind::Heartbeat) {
+            let heartbeat = Heartbeat::from_bytes(&message.payload)?;
+            if heartbeat.party != message.from {
+                return Err(LivenessError::SpoofedHeartbeat { from: message.from, claimed: heartbeat.party });
+            }
+            self.record_heartbeat(&heartbeat, received_at_ms)?;
+        }
+        Ok(())
+    }
+
+    /// Reclassify every worker at `now_ms`, returning the status changes
+    ///
+    /// Dead is final: a worker that comes back must rejoin the committee.
+    pub fn tick(&mut self, now_ms: u64) -> Vec<(usize, WorkerStatus)> {
+        let config = self.config;
+        let mut changes = Vec::new();
+        for (party, record) in self.workers.iter_mut().filter(|(_, record)| record.status != WorkerStatus::Dead) {
+            let missed = now_ms.saturating_sub(record.last_seen_ms) / config.interval_ms.max(1);
+            let status = if missed >= config.dead_after {
+                WorkerStatus::Dead
+            } else if missed >= config.slow_after || record.last_latency_ms > config.slow_latency_ms {
+                WorkerStatus::Slow
+            } else {
+                WorkerStatus::Alive
+            };
+            if status != record.status {
+                record.status = status;
+                changes.push((*party, status));
+            }
+        }
+        changes
+    }
+
+    pub fn status(&self, party: usize) -> Option<WorkerStatus> {
+        self.workers.get(&party).map(|record| record.status)
+    }
+
+    /// Workers not marked dead
+    pub fn live_workers(&self) -> Vec<usize> {
+        self.workers
+            .iter()
+            .filter(|(_, record)| record.status != WorkerStatus::Dead)
+            .map(|(party, _)| *party)
+            .collect()
+    }
+
+    /// Workers to finish a session with after dropouts
+    ///
+    /// Returns `threshold + 1` live workers, alive ones before slow ones, so
+    /// their shares suffice to reconstruct any value.
+    pub fn recovery_set(&self, threshold: usize) -> Result<Vec<usize>, LivenessError> {
+        let mut live: Vec<(WorkerStatus, usize)> = self
+            .workers
+            .iter()
+            .filter(|(_, record)| record.status != WorkerStatus::Dead)
+            .map(|(party, record)| (record.status, *party))
+            .collect();
+        let needed = threshold + 1;
+        if live.len() < needed {
+            return Err(LivenessError::InsufficientLiveWorkers { needed, live: live.len() });
+        }
+        live.sort();
+        Ok(live.into_iter().take(needed).map(|(_, party)| party).collect())
+    }
+
+    /// Availability of every worker up to `now_ms`
+    pub fn availability(&self, now_ms: u64) -> AvailabilityReport {
+        let interval = self.config.interval_ms.max(1);
+        let workers = self
+            .workers
+            .iter()
+            .map(|(party, record)| {
+                let expected = (now_ms.saturating_sub(record.registered_at_ms) / interval).max(1);
+                WorkerAvailability {
+                    party: *party,
+                    status: record.status,
+                    heartbeats: record.heartbeats,
+                    uptime: (record.heartbeats as f64 / expected as f64).min(1.0),
+                    mean_latency_ms: if record.heartbeats == 0 {
+                        0.0
+                    } else {
+                        record.total_latency_ms as f64 / record.heartbeats as f64
+                    },
+                }
+            })
+            .collect();
+        AvailabilityReport { at_ms: now_ms, workers }
+    }
+}
+
+/// Availability of one worker
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkerAvailability {
+    pub party: usize,
+    pub status: WorkerStatus,
+    pub heartbeats: u64,
+    /// Fraction of expected heartbeats that arrived
+    pub uptime: f64,
+    pub mean_latency_ms: f64,
+}
+
+/// Availability metrics of a committee
+#[derive(Debug, Clone, PartialEq)]
+pub struct AvailabilityReport {
+    pub at_ms: u64,
+    pub workers: Vec<WorkerAvailability>,
+}
+
+impl AvailabilityReport {
+    /// Mean uptime over the committee
+    pub fn committee_uptime(&self) -> f64 {
+        if self.workers.is_empty() {
+            return 0.0;
+        }
+        self.workers.iter().map(|worker| worker.uptime).sum::<f64>() / self.workers.len() as f64
+    }
+
+    pub fn to_json(&self) -> String {
+        let workers: Vec<String> = self
+            .workers
+            .iter()
+            .map(|worker| {
+                format!(
+                    "{{\"party\": {}, \"status\": \"{:?}\", \"heartbeats\": {}, \"uptime\": {:.4}, \"mean_latency_ms\": {:.2}}}",
+                    worker.party, worker.status, worker.heartbeats, worker.uptime, worker.mean_latency_ms
+                )
+            })
+            .collect();
+        format!(
+            "{{\"at_ms\": {}, \"committee_uptime\": {:.4}, \"workers\": [{}]}}",
+            self.at_ms,
+            self.committee_uptime(),
+            workers.join(", ")
+        )
+    }
+}
+
+/// Liveness error types
+#[derive(Debug, Clone, PartialEq)]
+pub enum LivenessError {
+    UnknownWorker(usize),
+    /// A heartbeat names a different party than the one that sent it
+    SpoofedHeartbeat { from: usize, claimed: usize },
+    InsufficientLiveWorkers { needed: usize, live: usize },
+    Decode(DecodeError),
+}
+
+impl std::fmt::Display for LivenessError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            LivenessError::UnknownWorker(party) => write!(f, "Heartbeat from unknown worker {}", party),
+            LivenessError::SpoofedHeartbeat { from, claimed } => {
+                write!(f, "Party {} sent a heartbeat claiming to be party {}", from, claimed)
+            }
+            LivenessError::InsufficientLiveWorkers { needed, live } => {
+                write!(f, "Need {} live workers to recover, only {} remain", needed, live)
+            }
+            LivenessError::Decode(e) => write!(f, "Malformed heartbeat: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for LivenessError {}
+
+impl From<DecodeError> for LivenessError {
+    fn from(e: DecodeError) -> Self {
+        LivenessError::Decode(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_monitor_marks_slow_and_dead_workers() {
+        let config = LivenessConfig::default();
+        let mut runtime = MessageRuntime::new(5);
+        let mut monitor = LivenessMonitor::new(1..=4, config, 0);
+
+        // Worker 4 stops after the first beat; worker 3 lags behind
+        for beat in 0..6u64 {
+            let now = beat * config.interval_ms;
+            for worker in 1..=3 {
+                send_heartbeat(&mut runtime, worker, 0, beat, now).unwrap();
+            }
+            if beat == 0 {
+                send_heartbeat(&mut runtime, 4, 0, beat, now).unwrap();
+            }
+            runtime.finish_round();
+            let inbox = runtime.receive(0).unwrap();
+            let (fast, slow): (Vec<Message>, Vec<Message>) = inbox.into_iter().partition(|m| m.from != 3);
+            monitor.observe(&fast, now + 10).unwrap();
+            monitor.observe(&slow, now + 800).unwrap();
+            monitor.tick(now + 800);
+        }
+        assert_eq!(monitor.status(1), Some(WorkerStatus::Alive));
+        assert_eq!(monitor.status(3), Some(WorkerStatus::Slow));
+        assert_eq!(monitor.status(4), Some(WorkerStatus::Dead));
+        assert_eq!(monitor.live_workers(), vec![1, 2, 3]);
+
+        // Recovery prefers responsive workers and fails once too few remain
+        assert_eq!(monitor.recovery_set(1).unwrap(), vec![1, 2]);
+        assert_eq!(monitor.recovery_set(2).unwrap(), vec![1, 2, 3]);
+        assert_eq!(
+            monitor.recovery_set(3),
+            Err(LivenessError::InsufficientLiveWorkers { needed: 4, live: 3 })
+        );
+
+        let report = monitor.availability(6 * config.interval_ms);
+        assert_eq!(report.workers[0].uptime, 1.0);
+        assert!(report.workers[3].uptime < 0.2);
+        assert_eq!(report.workers[2].mean_latency_ms, 800.0);
+        assert!(report.to_json().contains("\"status\": \"Dead\""));
+
+        // Replays are ignored and spoofed senders rejected
+        let spoofed = Message {
+            from: 2,
+            to: 0,
+            round: 0,
+            kind: MessageKind::Heartbeat,
+            payload: Heartbeat { party: 1, sequence: 99, sent_at_ms: 0 }.to_bytes(),
+        };
+        assert_eq!(monitor.observe(&[spoofed], 0), Err(LivenessError::SpoofedHeartbeat { from: 2, claimed: 1 }));
+        monitor.record_heartbeat(&Heartbeat { party: 1, sequence: 0, sent_at_ms: 0 }, 9_000).unwrap();
+        assert_eq!(monitor.availability(6 * config.interval_ms).workers[0].heartbeats, 6);
+    }
+}
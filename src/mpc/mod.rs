@@ -3,10 +3,46 @@
 //! This module implements the MPC components including secret sharing,
 //! circuit execution, and different operational modes (isolation vs collaboration).
 
-pub mod secret_sharing;
-pub mod executor; 
-pub mod modes;
+pub(crate) mod secret_sharing;
+pub(crate) mod executor; 
+pub(crate) mod modes;
+pub(crate) mod preprocessing;
+pub(crate) mod leakage;
+pub(crate) mod keys;
+pub(crate) mod private_output;
+pub(crate) mod output_commitment;
+pub(crate) mod network;
+pub(crate) mod framing;
+pub(crate) mod liveness;
+pub(crate) mod gates;
+pub(crate) mod typing;
+pub(crate) mod integer;
+pub(crate) mod robust;
+pub(crate) mod quorum;
+pub(crate) mod integrity;
+pub(crate) mod verifiable_preprocessing;
+pub(crate) mod distributed_sumcheck;
+pub(crate) mod aggregator;
+pub(crate) mod trace;
 
 pub use secret_sharing::*;
 pub use executor::*;
 pub use modes::*;
+pub use preprocessing::*;
+pub use leakage::*;
+pub use keys::*;
+pub use private_output::*;
+pub use output_commitment::*;
+pub use network::*;
+pub use framing::*;
+pub use liveness::*;
+pub use gates::*;
+pub use typing::*;
+pub use integer::*;
+pub use robust::*;
+pub use quorum::*;
+pub use integrity::*;
+pub use verifiable_preprocessing::*;
+pub use distributed_sumcheck::*;
+pub use aggregator::*;
+pub use trace::*;
@@ -4,7 +4,7 @@
 //! that define how parties interact in the delegation protocol.
 
 use ark_ff::Field;
-use crate::mpc::{ExecCircuit, SecretSharing, ExecutionError, ExecutionStats};
+use crate::mpc::{ExecCircuit, SecretSharing, ExecutionError};
 
 /// Operation mode trait defining how parties interact
 pub trait OperationMode<F: Field, SS: SecretSharing<F>> {
@@ -59,7 +59,8 @@ impl IsolationMode {
         }
     }
     
-    /// Get communication pattern (non-generic version)
+    /// Get communication pattern; the `OperationMode` method forwards here,
+    /// so callers need not name the field and sharing scheme
     pub fn get_communication_pattern(&self) -> CommunicationPattern {
         CommunicationPattern::Minimal {
             max_rounds: self.max_communication_rounds,
@@ -100,10 +101,7 @@ impl<F: Field, SS: SecretSharing<F>> OperationMode<F, SS> for IsolationMode {
     }
     
     fn get_communication_pattern(&self) -> CommunicationPattern {
-        CommunicationPattern::Minimal {
-            max_rounds: self.max_communication_rounds,
-            batch_size: self.get_max_batch_size(),
-        }
+        IsolationMode::get_communication_pattern(self)
     }
     
     fn verify_execution(
@@ -160,7 +158,8 @@ impl CollaborationMode {
         self.use_optimized_protocols && self.collaboration_level >= 2
     }
     
-    /// Get communication pattern (non-generic version)
+    /// Get communication pattern; the `OperationMode` method forwards here,
+    /// so callers need not name the field and sharing scheme
     pub fn get_communication_pattern(&self) -> CommunicationPattern {
         CommunicationPattern::Full {
             parallelism_degree: self.get_parallelism_degree(),
@@ -187,10 +186,7 @@ impl<F: Field, SS: SecretSharing<F>> OperationMode<F, SS> for CollaborationMode
     }
     
     fn get_communication_pattern(&self) -> CommunicationPattern {
-        CommunicationPattern::Full {
-            parallelism_degree: self.get_parallelism_degree(),
-            use_optimized_protocols: self.should_use_optimized_protocols(),
-        }
+        CollaborationMode::get_communication_pattern(self)
     }
     
     fn verify_execution(
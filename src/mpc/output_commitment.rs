@@ -0,0 +1,253 @@
+//! Commit-then-reveal for output shares
+//!
+//! Workers first send a hash commitment to their output share. The delegator
+//! accepts openings only after every worker has committed and the delegation
+//! proof has verified, so no worker can choose its share after seeing the
+//! others' shares or the outcome of verification.
+
+use std::collections::BTreeMap;
+
+use ark_ff::Field;
+use ark_std::rand::Rng;
+use sha2::{Digest, Sha256};
+
+use crate::mpc::{SecretSharing, SecretSharingError};
+use crate::serialization::{DecodeError, WireFormat};
+
+const OUTPUT_COMMITMENT_DOMAIN: &[u8] = b"EOS-output-commitment-v1";
+
+/// Binding and hiding commitment to one party's output share
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutputCommitment {
+    pub party: usize,
+    pub digest: [u8; 32],
+}
+
+/// Opening of an `OutputCommitment`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputOpening {
+    pub party: usize,
+    pub nonce: [u8; 32],
+    pub share_bytes: Vec<u8>,
+}
+
+fn commitment_digest(party: usize, nonce: &[u8; 32], share_bytes: &[u8]) -> [u8; 32] {
+    Sha256::new()
+        .chain_update(OUTPUT_COMMITMENT_DOMAIN)
+        .chain_update((party as u64).to_le_bytes())
+        .chain_update(nonce)
+        .chain_update(share_bytes)
+        .finalize()
+        .into()
+}
+
+/// Commit to an output share; the opening is kept until the reveal phase
+pub fn commit_output_share<S: WireFormat>(
+    party: usize,
+    share: &S,
+    rng: &mut impl Rng,
+) -> (OutputCommitment, OutputOpening) {
+    let mut nonce = [0u8; 32];
+    rng.fill_bytes(&mut nonce);
+    let share_bytes = share.to_bytes();
+    let commitment = OutputCommitment { party, digest: commitment_digest(party, &nonce, &share_bytes) };
+    (commitment, OutputOpening { party, nonce, share_bytes })
+}
+
+/// Stage of a delayed reveal
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RevealPhase {
+    /// Waiting for every party's commitment
+    Committing,
+    /// All commitments received, waiting for the proof to be checked
+    AwaitingProof,
+    /// Proof accepted, openings may be submitted
+    Opening,
+    /// Proof rejected; no output will be revealed
+    Aborted,
+}
+
+/// Delegator-side state of a delayed output reveal
+#[derive(Debug, Clone)]
+pub struct DelayedReveal {
+    num_parties: usize,
+    phase: RevealPhase,
+    commitments: BTreeMap<usize, OutputCommitment>,
+    openings: BTreeMap<usize, OutputOpening>,
+}
+
+impl DelayedReveal {
+    pub fn new(num_parties: usize) -> Self {
+        Self {
+            num_parties,
+            phase: RevealPhase::Committing,
+            commitments: BTreeMap::new(),
+            openings: BTreeMap::new(),
+        }
+    }
+
+    pub fn phase(&self) -> RevealPhase {
+        self.phase
+    }
+
+    pub fn receive_commitment(&mut self, commitment: OutputCommitment) -> Result<(), OutputRevealError> {
+        self.expect_phase(RevealPhase::Committing)?;
+        if commitment.party >= self.num_parties {
+            return Err(OutputRevealError::UnknownParty(commitment.party));
+        }
+        if self.commitments.contains_key(&commitment.party) {
+            return Err(OutputRevealError::DuplicateCommitment(commitment.party));
+        }
+        self.commitments.insert(commitment.party, commitment);
+        if self.commitments.len() == self.num_parties {
+            self.phase = RevealPhase::AwaitingProof;
+        }
+        Ok(())
+    }
+
+    /// Record the verification result of the delegation proof
+    pub fn confirm_proof(&mut self, proof_valid: bool) -> Result<(), OutputRevealError> {
+        self.expect_phase(RevealPhase::AwaitingProof)?;
+        self.phase = if proof_valid { RevealPhase::Opening } else { RevealPhase::Aborted };
+        Ok(())
+    }
+
+    pub fn receive_opening(&mut self, opening: OutputOpening) -> Result<(), OutputRevealError> {
+        self.expect_phase(RevealPhase::Opening)?;
+        let commitment = self
+            .commitments
+            .get(&opening.party)
+            .ok_or(OutputRevealError::UnknownParty(opening.party))?;
+        if commitment_digest(opening.party, &opening.nonce, &opening.share_bytes) != commitment.digest {
+            return Err(OutputRevealError::OpeningMismatch(opening.party));
+        }
+        self.openings.insert(opening.party, opening);
+        Ok(())
+    }
+
+    /// Reconstruct the output from the openings received so far
+    pub fn reveal<F: Field, SS: SecretSharing<F>>(&self) -> Result<F, OutputRevealError>
+    where
+        SS::Share: WireFormat,
+    {
+        self.expect_phase(RevealPhase::Opening)?;
+        let shares = self
+            .openings
+            .values()
+            .map(|opening| SS::Share::from_bytes(&opening.share_bytes))
+            .collect::<Result<Vec<_>, _>>()?;
+        SS::reconstruct_secret(&shares).map_err(OutputRevealError::Reconstruction)
+    }
+
+    fn expect_phase(&self, expected: RevealPhase) -> Result<(), OutputRevealError> {
+        if self.phase == expected {
+            Ok(())
+        } else {
+            Err(OutputRevealError::WrongPhase { expected, actual: self.phase })
+        }
+    }
+}
+
+/// Delayed reveal error types
+#[derive(Debug, Clone, PartialEq)]
+pub enum OutputRevealError {
+    WrongPhase { expected: RevealPhase, actual: RevealPhase },
+    UnknownParty(usize),
+    DuplicateCommitment(usize),
+    OpeningMismatch(usize),
+    Decode(DecodeError),
+    Reconstruction(SecretSharingError),
+}
+
+impl std::fmt::Display for OutputRevealError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            OutputRevealError::WrongPhase { expected, actual } => {
+                write!(f, "Expected reveal phase {:?} but in {:?}", expected, actual)
+            }
+            OutputRevealError::UnknownParty(party) => write!(f, "Unknown party {}", party),
+            OutputRevealError::DuplicateCommitment(party) => write!(f, "Party {} committed twice", party),
+            OutputRevealError::OpeningMismatch(party) => {
+                write!(f, "Opening of party {} does not match its commitment", party)
+            }
+            OutputRevealError::Decode(e) => write!(f, "Malformed output share: {}", e),
+            OutputRevealError::Reconstruction(e) => write!(f, "Output reconstruction failed: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for OutputRevealError {}
+
+impl From<DecodeError> for OutputRevealError {
+    fn from(e: DecodeError) -> Self {
+        OutputRevealError::Decode(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mpc::ShamirSecretSharing;
+    use ark_bls12_381::Fr;
+    use ark_std::test_rng;
+
+    type TestField = Fr;
+    type Shamir = ShamirSecretSharing<TestField>;
+
+    #[test]
+    fn test_openings_wait_for_commitments_and_proof() {
+        let mut rng = test_rng();
+        let shares = Shamir::share_secret(TestField::from(11u64), 2, 3, &mut rng);
+        let (commitments, openings): (Vec<_>, Vec<_>) = shares
+            .iter()
+            .enumerate()
+            .map(|(party, share)| commit_output_share(party, share, &mut rng))
+            .unzip();
+
+        let mut reveal = DelayedReveal::new(3);
+        reveal.receive_commitment(commitments[0]).unwrap();
+        reveal.receive_commitment(commitments[1]).unwrap();
+
+        // An early opening is refused while a party has yet to commit
+        assert!(matches!(
+            reveal.receive_opening(openings[0].clone()),
+            Err(OutputRevealError::WrongPhase { actual: RevealPhase::Committing, .. })
+        ));
+        reveal.receive_commitment(commitments[2]).unwrap();
+        assert!(reveal.receive_opening(openings[0].clone()).is_err());
+
+        reveal.confirm_proof(true).unwrap();
+        for opening in &openings {
+            reveal.receive_opening(opening.clone()).unwrap();
+        }
+        assert_eq!(reveal.reveal::<TestField, Shamir>().unwrap(), TestField::from(11u64));
+    }
+
+    #[test]
+    fn test_adaptive_share_is_rejected() {
+        let mut rng = test_rng();
+        let shares = Shamir::share_secret(TestField::from(11u64), 2, 3, &mut rng);
+        let mut reveal = DelayedReveal::new(3);
+        let mut openings = Vec::new();
+        for (party, share) in shares.iter().enumerate() {
+            let (commitment, opening) = commit_output_share(party, share, &mut rng);
+            reveal.receive_commitment(commitment).unwrap();
+            openings.push(opening);
+        }
+        reveal.confirm_proof(true).unwrap();
+
+        // Party 2 tries to swap in a different share after committing
+        let mut changed = shares[2].clone();
+        changed.value += TestField::from(1u64);
+        let mut adaptive = openings[2].clone();
+        adaptive.share_bytes = changed.to_bytes();
+        assert_eq!(reveal.receive_opening(adaptive).unwrap_err(), OutputRevealError::OpeningMismatch(2));
+
+        // A failed proof aborts the reveal entirely
+        let mut aborted = DelayedReveal::new(1);
+        aborted.receive_commitment(commit_output_share(0, &shares[0], &mut rng).0).unwrap();
+        aborted.confirm_proof(false).unwrap();
+        assert!(aborted.receive_opening(openings[0].clone()).is_err());
+        assert!(aborted.reveal::<TestField, Shamir>().is_err());
+    }
+}
@@ -0,0 +1,490 @@
+//! Preprocessing material for the online MPC phase
+//!
+//! This module holds dealer-generated Beaver triples and random bits, and
+//! carves them up across MPC sessions. Each session is given a disjoint range
+//! of every kind of material; a ledger records which session consumed which
+//! item so that nothing is ever used twice, either within one session or
+//! across sessions.
+//!
+//! A committee that serves sessions with different `(t, n)` configurations
+//! keeps one pool per configuration in a `ThresholdPreprocessing`. Only the
+//! shares depend on the configuration; the SRS and circuit keys from
+//! `EOSProtocol::preprocessing` are shared by all of them.
+//!
+//! Material generated by the workers themselves instead of a trusted dealer
+//! is checked with cut-and-choose before use; see `verifiable_preprocessing`.
+
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Range;
+
+use ark_ff::Field;
+use ark_std::rand::Rng;
+
+use crate::custom_circuits::CircuitBudget;
+use crate::mpc::SecretSharing;
+
+/// Identifier of an MPC session
+pub type SessionId = u64;
+
+/// Shares of a multiplication triple `(a, b, c)` with `c = a * b`
+#[derive(Debug, Clone)]
+pub struct BeaverTriple<S> {
+    pub a: Vec<S>,
+    pub b: Vec<S>,
+    pub c: Vec<S>,
+}
+
+/// Kinds of preprocessing material tracked by the ledger
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MaterialKind {
+    Triple,
+    Bit,
+}
+
+/// Ranges of material reserved for one session
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionAllocation {
+    pub triples: Range<usize>,
+    pub bits: Range<usize>,
+}
+
+impl SessionAllocation {
+    fn range(&self, kind: MaterialKind) -> &Range<usize> {
+        match kind {
+            MaterialKind::Triple => &self.triples,
+            MaterialKind::Bit => &self.bits,
+        }
+    }
+}
+
+/// Record of allocations and consumption across sessions
+#[derive(Debug, Clone, Default)]
+pub struct PreprocessingLedger {
+    pub(crate) next_triple: usize,
+    pub(crate) next_bit: usize,
+    pub(crate) allocations: HashMap<SessionId, SessionAllocation>,
+    pub(crate) consumed: HashMap<(MaterialKind, usize), SessionId>,
+}
+
+impl PreprocessingLedger {
+    /// Allocation of a session, if any
+    pub fn allocation(&self, session: SessionId) -> Option<&SessionAllocation> {
+        self.allocations.get(&session)
+    }
+
+    /// Session that consumed the given item, if it has been used
+    pub fn consumer_of(&self, kind: MaterialKind, index: usize) -> Option<SessionId> {
+        self.consumed.get(&(kind, index)).copied()
+    }
+
+    /// Number of items of `kind` consumed by `session`
+    pub fn consumed_count(&self, session: SessionId, kind: MaterialKind) -> usize {
+        self.consumed
+            .iter()
+            .filter(|((k, _), s)| *k == kind && **s == session)
+            .count()
+    }
+
+    fn record(&mut self, session: SessionId, kind: MaterialKind, index: usize) -> Result<(), PreprocessingError> {
+        let allocation = self.allocations
+            .get(&session)
+            .ok_or(PreprocessingError::UnknownSession(session))?;
+        if !allocation.range(kind).contains(&index) {
+            return Err(PreprocessingError::OutsideAllocation { session, kind, index });
+        }
+        if let Some(&previous) = self.consumed.get(&(kind, index)) {
+            return Err(PreprocessingError::AlreadyConsumed { kind, index, session: previous });
+        }
+        self.consumed.insert((kind, index), session);
+        Ok(())
+    }
+
+    fn next_unconsumed(&self, session: SessionId, kind: MaterialKind) -> Result<usize, PreprocessingError> {
+        let allocation = self.allocations
+            .get(&session)
+            .ok_or(PreprocessingError::UnknownSession(session))?;
+        allocation
+            .range(kind)
+            .clone()
+            .find(|index| !self.consumed.contains_key(&(kind, *index)))
+            .ok_or(PreprocessingError::Exhausted { kind, requested: 1, available: 0 })
+    }
+}
+
+/// Pool of preprocessing material shared by several sessions
+pub struct PreprocessingStore<F: Field, SS: SecretSharing<F>> {
+    pub(crate) triples: Vec<BeaverTriple<SS::Share>>,
+    pub(crate) bits: Vec<Vec<SS::Share>>,
+    pub(crate) ledger: PreprocessingLedger,
+}
+
+impl<F: Field, SS: SecretSharing<F>> PreprocessingStore<F, SS> {
+    /// Generate material with a trusted dealer
+    pub fn generate(
+        num_triples: usize,
+        num_bits: usize,
+        threshold: usize,
+        num_parties: usize,
+        rng: &mut impl Rng,
+    ) -> Self {
+        let triples = (0..num_triples)
+            .map(|_| {
+                let a = F::rand(rng);
+                let b = F::rand(rng);
+                BeaverTriple {
+                    a: SS::share_secret(a, threshold, num_parties, rng),
+                    b: SS::share_secret(b, threshold, num_parties, rng),
+                    c: SS::share_secret(a * b, threshold, num_parties, rng),
+                }
+            })
+            .collect();
+        let bits = (0..num_bits)
+            .map(|_| {
+                let bit = if rng.gen::<bool>() { F::one() } else { F::zero() };
+                SS::share_secret(bit, threshold, num_parties, rng)
+            })
+            .collect();
+
+        Self {
+            triples,
+            bits,
+            ledger: PreprocessingLedger::default(),
+        }
+    }
+
+    /// Reserve a disjoint slice of material for a new session
+    pub fn allocate(
+        &mut self,
+        session: SessionId,
+        num_triples: usize,
+        num_bits: usize,
+    ) -> Result<SessionAllocation, PreprocessingError> {
+        if self.ledger.allocations.contains_key(&session) {
+            return Err(PreprocessingError::SessionAlreadyAllocated(session));
+        }
+        let available_triples = self.triples.len() - self.ledger.next_triple;
+        if num_triples > available_triples {
+            return Err(PreprocessingError::Exhausted {
+                kind: MaterialKind::Triple,
+                requested: num_triples,
+                available: available_triples,
+            });
+        }
+        let available_bits = self.bits.len() - self.ledger.next_bit;
+        if num_bits > available_bits {
+            return Err(PreprocessingError::Exhausted {
+                kind: MaterialKind::Bit,
+                requested: num_bits,
+                available: available_bits,
+            });
+        }
+
+        let allocation = SessionAllocation {
+            triples: self.ledger.next_triple..self.ledger.next_triple + num_triples,
+            bits: self.ledger.next_bit..self.ledger.next_bit + num_bits,
+        };
+        self.ledger.next_triple = allocation.triples.end;
+        self.ledger.next_bit = allocation.bits.end;
+        self.ledger.allocations.insert(session, allocation.clone());
+        Ok(allocation)
+    }
+
+    /// Consume a specific triple on behalf of `session`
+    pub fn consume_triple(
+        &mut self,
+        session: SessionId,
+        index: usize,
+    ) -> Result<&BeaverTriple<SS::Share>, PreprocessingError> {
+        self.ledger.record(session, MaterialKind::Triple, index)?;
+        Ok(&self.triples[index])
+    }
+
+    /// Consume a specific random bit on behalf of `session`
+    pub fn consume_bit(&mut self, session: SessionId, index: usize) -> Result<&[SS::Share], PreprocessingError> {
+        self.ledger.record(session, MaterialKind::Bit, index)?;
+        Ok(&self.bits[index])
+    }
+
+    /// Consume the next unused triple of `session`
+    pub fn next_triple(&mut self, session: SessionId) -> Result<&BeaverTriple<SS::Share>, PreprocessingError> {
+        let index = self.ledger.next_unconsumed(session, MaterialKind::Triple)?;
+        self.consume_triple(session, index)
+    }
+
+    /// Consume the next unused random bit of `session`
+    pub fn next_bit(&mut self, session: SessionId) -> Result<&[SS::Share], PreprocessingError> {
+        let index = self.ledger.next_unconsumed(session, MaterialKind::Bit)?;
+        self.consume_bit(session, index)
+    }
+
+    /// Number of triples not yet allocated to any session
+    pub fn unallocated_triples(&self) -> usize {
+        self.triples.len() - self.ledger.next_triple
+    }
+
+    /// Number of random bits not yet allocated to any session
+    pub fn unallocated_bits(&self) -> usize {
+        self.bits.len() - self.ledger.next_bit
+    }
+
+    /// Circuit budget of the unallocated triples, one per multiplication
+    pub fn budget(&self) -> CircuitBudget {
+        CircuitBudget::unlimited().with_max_multiplications(self.unallocated_triples())
+    }
+
+    pub fn ledger(&self) -> &PreprocessingLedger {
+        &self.ledger
+    }
+}
+
+/// Sharing configuration of a session: threshold `t` among `n` parties
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ThresholdConfig {
+    pub threshold: usize,
+    pub num_parties: usize,
+}
+
+impl ThresholdConfig {
+    pub fn new(threshold: usize, num_parties: usize) -> Self {
+        Self { threshold, num_parties }
+    }
+}
+
+/// Material pools for several sharing configurations of one committee
+pub struct ThresholdPreprocessing<F: Field, SS: SecretSharing<F>> {
+    pub(crate) pools: BTreeMap<ThresholdConfig, PreprocessingStore<F, SS>>,
+    /// Configuration each session was allocated under
+    pub(crate) sessions: HashMap<SessionId, ThresholdConfig>,
+}
+
+impl<F: Field, SS: SecretSharing<F>> Default for ThresholdPreprocessing<F, SS> {
+    fn default() -> Self {
+        Self { pools: BTreeMap::new(), sessions: HashMap::new() }
+    }
+}
+
+impl<F: Field, SS: SecretSharing<F>> ThresholdPreprocessing<F, SS> {
+    /// Generate `(num_triples, num_bits)` of material for each configuration
+    pub fn generate(plan: &[(ThresholdConfig, usize, usize)], rng: &mut impl Rng) -> Self {
+        let mut material = Self::default();
+        for &(config, num_triples, num_bits) in plan {
+            material.add_pool(config, PreprocessingStore::generate(
+                num_triples,
+                num_bits,
+                config.threshold,
+                config.num_parties,
+                rng,
+            ));
+        }
+        material
+    }
+
+    /// Add material for a configuration, replacing any pool it had
+    ///
+    /// A replaced pool must not have served sessions yet, or its ledger
+    /// would be lost.
+    pub fn add_pool(&mut self, config: ThresholdConfig, pool: PreprocessingStore<F, SS>) {
+        if let Some(previous) = self.pools.get(&config) {
+            assert!(previous.ledger.allocations.is_empty(), "replacing a pool that has allocations");
+        }
+        self.pools.insert(config, pool);
+    }
+
+    /// Configurations with material
+    pub fn configs(&self) -> impl Iterator<Item = ThresholdConfig> + '_ {
+        self.pools.keys().copied()
+    }
+
+    pub fn pool(&self, config: ThresholdConfig) -> Option<&PreprocessingStore<F, SS>> {
+        self.pools.get(&config)
+    }
+
+    /// Configuration a session was allocated under
+    pub fn config_of(&self, session: SessionId) -> Option<ThresholdConfig> {
+        self.sessions.get(&session).copied()
+    }
+
+    /// Reserve material for a session under `config`
+    ///
+    /// Session identifiers are unique across configurations.
+    pub fn allocate(
+        &mut self,
+        session: SessionId,
+        config: ThresholdConfig,
+        num_triples: usize,
+        num_bits: usize,
+    ) -> Result<SessionAllocation, PreprocessingError> {
+        if self.sessions.contains_key(&session) {
+            return Err(PreprocessingError::SessionAlreadyAllocated(session));
+        }
+        let pool = self.pools.get_mut(&config).ok_or(PreprocessingError::UnknownConfig(config))?;
+        let allocation = pool.allocate(session, num_triples, num_bits)?;
+        self.sessions.insert(session, config);
+        Ok(allocation)
+    }
+
+    fn session_pool(&mut self, session: SessionId) -> Result<&mut PreprocessingStore<F, SS>, PreprocessingError> {
+        let config = self.sessions.get(&session).ok_or(PreprocessingError::UnknownSession(session))?;
+        Ok(self.pools.get_mut(config).expect("sessions are only allocated from existing pools"))
+    }
+
+    /// Consume the next unused triple of `session`, from its configuration's pool
+    pub fn next_triple(&mut self, session: SessionId) -> Result<&BeaverTriple<SS::Share>, PreprocessingError> {
+        self.session_pool(session)?.next_triple(session)
+    }
+
+    /// Consume the next unused random bit of `session`
+    pub fn next_bit(&mut self, session: SessionId) -> Result<&[SS::Share], PreprocessingError> {
+        self.session_pool(session)?.next_bit(session)
+    }
+}
+
+/// Preprocessing error types
+#[derive(Debug, Clone, PartialEq)]
+pub enum PreprocessingError {
+    SessionAlreadyAllocated(SessionId),
+    UnknownSession(SessionId),
+    Exhausted { kind: MaterialKind, requested: usize, available: usize },
+    OutsideAllocation { session: SessionId, kind: MaterialKind, index: usize },
+    AlreadyConsumed { kind: MaterialKind, index: usize, session: SessionId },
+    /// No material was generated for this configuration
+    UnknownConfig(ThresholdConfig),
+    /// Cut-and-choose openings do not cover exactly the challenged items
+    IncompleteEvidence(MaterialKind),
+    /// An opened item does not match the worker's commitment
+    CommitmentMismatch { kind: MaterialKind, index: usize },
+    /// An opened triple has `c != a * b`, a bit is not 0 or 1, or a sharing
+    /// does not lie on a polynomial of degree below the threshold
+    MalformedMaterial { kind: MaterialKind, index: usize },
+}
+
+impl std::fmt::Display for PreprocessingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            PreprocessingError::SessionAlreadyAllocated(s) => write!(f, "Session {} already has an allocation", s),
+            PreprocessingError::UnknownSession(s) => write!(f, "Session {} has no allocation", s),
+            PreprocessingError::Exhausted { kind, requested, available } => {
+                write!(f, "Requested {} {:?} items but only {} available", requested, kind, available)
+            }
+            PreprocessingError::OutsideAllocation { session, kind, index } => {
+                write!(f, "{:?} {} is not allocated to session {}", kind, index, session)
+            }
+            PreprocessingError::AlreadyConsumed { kind, index, session } => {
+                write!(f, "{:?} {} was already consumed by session {}", kind, index, session)
+            }
+            PreprocessingError::UnknownConfig(config) => write!(
+                f,
+                "No preprocessing material for threshold {} of {} parties",
+                config.threshold, config.num_parties
+            ),
+            PreprocessingError::IncompleteEvidence(kind) => {
+                write!(f, "Cut-and-choose evidence does not open exactly the challenged {:?} items", kind)
+            }
+            PreprocessingError::CommitmentMismatch { kind, index } => {
+                write!(f, "Opened {:?} {} does not match its commitment", kind, index)
+            }
+            PreprocessingError::MalformedMaterial { kind, index } => write!(f, "Opened {:?} {} is malformed", kind, index),
+        }
+    }
+}
+
+impl std::error::Error for PreprocessingError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mpc::ShamirSecretSharing;
+    use ark_bls12_381::Fr;
+    use ark_std::test_rng;
+
+    type TestField = Fr;
+    type Store = PreprocessingStore<TestField, ShamirSecretSharing<TestField>>;
+
+    #[test]
+    fn test_triples_are_valid() {
+        let mut rng = test_rng();
+        let mut store = Store::generate(2, 2, 2, 3, &mut rng);
+        store.allocate(1, 2, 2).unwrap();
+
+        let triple = store.next_triple(1).unwrap().clone();
+        let a = ShamirSecretSharing::reconstruct_secret(&triple.a).unwrap();
+        let b = ShamirSecretSharing::reconstruct_secret(&triple.b).unwrap();
+        let c = ShamirSecretSharing::reconstruct_secret(&triple.c).unwrap();
+        assert_eq!(a * b, c);
+
+        let bit = ShamirSecretSharing::reconstruct_secret(store.next_bit(1).unwrap()).unwrap();
+        assert!(bit == TestField::from(0u64) || bit == TestField::from(1u64));
+    }
+
+    #[test]
+    fn test_double_use_is_rejected() {
+        let mut rng = test_rng();
+        let mut store = Store::generate(5, 1, 2, 3, &mut rng);
+        let first = store.allocate(10, 2, 1).unwrap();
+        let second = store.allocate(20, 3, 0).unwrap();
+        assert_eq!(first.triples, 0..2);
+        assert_eq!(second.triples, 2..5);
+        assert_eq!(store.unallocated_triples(), 0);
+
+        // Reuse within one session
+        store.consume_triple(10, 0).unwrap();
+        assert_eq!(
+            store.consume_triple(10, 0).unwrap_err(),
+            PreprocessingError::AlreadyConsumed { kind: MaterialKind::Triple, index: 0, session: 10 }
+        );
+
+        // Use of another session's triple
+        assert_eq!(
+            store.consume_triple(20, 1).unwrap_err(),
+            PreprocessingError::OutsideAllocation { session: 20, kind: MaterialKind::Triple, index: 1 }
+        );
+
+        // Exhaustion, repeated allocation and unknown sessions
+        assert!(matches!(store.allocate(30, 1, 0), Err(PreprocessingError::Exhausted { .. })));
+        assert_eq!(store.allocate(10, 0, 0).unwrap_err(), PreprocessingError::SessionAlreadyAllocated(10));
+        assert_eq!(store.next_triple(99).unwrap_err(), PreprocessingError::UnknownSession(99));
+
+        store.next_triple(10).unwrap();
+        assert!(matches!(store.next_triple(10), Err(PreprocessingError::Exhausted { .. })));
+        assert_eq!(store.ledger().consumed_count(10, MaterialKind::Triple), 2);
+        assert_eq!(store.ledger().consumer_of(MaterialKind::Triple, 1), Some(10));
+        assert_eq!(store.ledger().consumer_of(MaterialKind::Triple, 2), None);
+    }
+
+    #[test]
+    fn test_pools_per_threshold() {
+        let mut rng = test_rng();
+        let (small, large) = (ThresholdConfig::new(1, 3), ThresholdConfig::new(2, 5));
+        let mut material = ThresholdPreprocessing::<TestField, ShamirSecretSharing<TestField>>::generate(
+            &[(small, 2, 1), (large, 1, 1)],
+            &mut rng,
+        );
+        assert_eq!(material.configs().collect::<Vec<_>>(), vec![small, large]);
+
+        material.allocate(1, small, 2, 1).unwrap();
+        material.allocate(2, large, 1, 0).unwrap();
+        assert_eq!(material.config_of(2), Some(large));
+
+        // Each session draws shares for its own configuration
+        let triple = material.next_triple(2).unwrap().clone();
+        assert_eq!(triple.a.len(), 5);
+        let a = ShamirSecretSharing::reconstruct_secret(&triple.a[..3]).unwrap();
+        let b = ShamirSecretSharing::reconstruct_secret(&triple.b[2..]).unwrap();
+        let c = ShamirSecretSharing::reconstruct_secret(&triple.c[1..4]).unwrap();
+        assert_eq!(a * b, c);
+        assert_eq!(material.next_triple(1).unwrap().a.len(), 3);
+        assert_eq!(material.next_bit(1).unwrap().len(), 3);
+
+        assert_eq!(material.allocate(1, large, 0, 0).unwrap_err(), PreprocessingError::SessionAlreadyAllocated(1));
+        let missing = ThresholdConfig::new(3, 7);
+        assert_eq!(material.allocate(3, missing, 0, 0).unwrap_err(), PreprocessingError::UnknownConfig(missing));
+        assert!(matches!(material.allocate(4, large, 1, 0), Err(PreprocessingError::Exhausted { .. })));
+        assert_eq!(material.pool(small).unwrap().ledger().consumed_count(1, MaterialKind::Triple), 1);
+
+        // The keyed pools and their ledgers survive persistence
+        use crate::serialization::WireFormat;
+        let mut reloaded = ThresholdPreprocessing::<TestField, ShamirSecretSharing<TestField>>::from_bytes(&material.to_bytes()).unwrap();
+        assert_eq!(reloaded.config_of(1), Some(small));
+        assert!(matches!(reloaded.next_triple(2), Err(PreprocessingError::Exhausted { .. })));
+    }
+}
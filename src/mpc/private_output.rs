@@ -0,0 +1,219 @@
+//! Private delivery of MPC outputs to the delegator
+//!
+//! Instead of opening an output among the workers, each worker encrypts its
+//! own output share to the delegator's public key with hashed ElGamal: an
+//! ephemeral Diffie–Hellman point on the protocol curve, with the share
+//! encoding sealed under a key derived from the shared point. Only the
+//! delegator can decrypt the shares and reconstruct the result, so the
+//! combined value never appears in any worker's view. Keys live on the
+//! hashed `identity_generator` rather than the curve's standard generator.
+
+use std::collections::HashSet;
+
+use ark_ec::CurveGroup;
+use ark_ff::{Field, UniformRand};
+use ark_serialize::CanonicalSerialize;
+use ark_std::rand::Rng;
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use sha2::{Digest, Sha256};
+
+use crate::hash_to_curve::identity_generator;
+use crate::mpc::{ExecCircuit, ExecutionError, SecretSharing, SecretSharingError};
+use crate::serialization::{DecodeError, WireFormat};
+
+const OUTPUT_KEY_DOMAIN: &[u8] = b"EOS-private-output-v1";
+
+/// The delegator's decryption key
+pub struct DelegatorKeyPair<G: CurveGroup> {
+    secret: G::ScalarField,
+    pub public_key: G::Affine,
+}
+
+/// One worker's output share, readable only by the delegator
+#[derive(Debug, Clone, PartialEq)]
+pub struct EncryptedShare<G: CurveGroup> {
+    pub party: usize,
+    pub ephemeral: G::Affine,
+    pub ciphertext: Vec<u8>,
+}
+
+fn point_bytes<A: CanonicalSerialize>(point: &A) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    point
+        .serialize_compressed(&mut bytes)
+        .expect("serialization into a Vec cannot fail");
+    bytes
+}
+
+fn cipher_for<G: CurveGroup>(ephemeral: &G::Affine, shared: &G::Affine) -> ChaCha20Poly1305 {
+    let key = Sha256::new()
+        .chain_update(OUTPUT_KEY_DOMAIN)
+        .chain_update(point_bytes(ephemeral))
+        .chain_update(point_bytes(shared))
+        .finalize();
+    ChaCha20Poly1305::new(Key::from_slice(&key))
+}
+
+/// The key is unique per ephemeral point, so a fixed nonce is safe
+const NONCE: [u8; 12] = [0u8; 12];
+
+/// Encrypt an output share to the delegator
+pub fn encrypt_output_share<G: CurveGroup, S: WireFormat>(
+    party: usize,
+    share: &S,
+    delegator_key: &G::Affine,
+    rng: &mut impl Rng,
+) -> EncryptedShare<G> {
+    let r = G::ScalarField::rand(rng);
+    let ephemeral = (identity_generator::<G>() * r).into_affine();
+    let shared = (*delegator_key * r).into_affine();
+    let ciphertext = cipher_for::<G>(&ephemeral, &shared)
+        .encrypt(
+            Nonce::from_slice(&NONCE),
+            Payload { msg: &share.to_bytes(), aad: &(party as u64).to_le_bytes() },
+        )
+        .expect("share encodings are far below the AEAD length limit");
+    EncryptedShare { party, ephemeral, ciphertext }
+}
+
+impl<G: CurveGroup> DelegatorKeyPair<G> {
+    pub fn generate(rng: &mut impl Rng) -> Self {
+        let secret = G::ScalarField::rand(rng);
+        Self { secret, public_key: (identity_generator::<G>() * secret).into_affine() }
+    }
+
+    /// Decrypt a single output share
+    pub fn decrypt_share<S: WireFormat>(&self, encrypted: &EncryptedShare<G>) -> Result<S, PrivateOutputError> {
+        let shared = (encrypted.ephemeral * self.secret).into_affine();
+        let plaintext = cipher_for::<G>(&encrypted.ephemeral, &shared)
+            .decrypt(
+                Nonce::from_slice(&NONCE),
+                Payload { msg: &encrypted.ciphertext, aad: &(encrypted.party as u64).to_le_bytes() },
+            )
+            .map_err(|_| PrivateOutputError::Decryption(encrypted.party))?;
+        Ok(S::from_bytes(&plaintext)?)
+    }
+
+    /// Decrypt every worker's share and reconstruct the output
+    pub fn reveal_output<F: Field, SS: SecretSharing<F>>(
+        &self,
+        encrypted: &[EncryptedShare<G>],
+    ) -> Result<F, PrivateOutputError>
+    where
+        SS::Share: WireFormat,
+    {
+        let mut parties = HashSet::new();
+        let shares = encrypted
+            .iter()
+            .map(|share| {
+                if !parties.insert(share.party) {
+                    return Err(PrivateOutputError::DuplicateParty(share.party));
+                }
+                self.decrypt_share(share)
+            })
+            .collect::<Result<Vec<SS::Share>, _>>()?;
+        SS::reconstruct_secret(&shares).map_err(PrivateOutputError::Reconstruction)
+    }
+}
+
+impl<F: Field, SS: SecretSharing<F>> ExecCircuit<F, SS>
+where
+    SS::Share: WireFormat,
+{
+    /// Send this party's output share to the delegator instead of opening it
+    #[track_caller]
+    pub fn reveal_to_delegator<G: CurveGroup>(
+        &self,
+        label: &str,
+        share: &SS::Share,
+        delegator_key: &G::Affine,
+        rng: &mut impl Rng,
+    ) -> Result<EncryptedShare<G>, ExecutionError> {
+        self.leakage.record(label, 1).map_err(ExecutionError::RevealBlocked)?;
+        Ok(encrypt_output_share(self.party_id, share, delegator_key, rng))
+    }
+}
+
+/// Private output error types
+#[derive(Debug, Clone, PartialEq)]
+pub enum PrivateOutputError {
+    Decryption(usize),
+    Decode(DecodeError),
+    DuplicateParty(usize),
+    Reconstruction(SecretSharingError),
+}
+
+impl std::fmt::Display for PrivateOutputError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            PrivateOutputError::Decryption(party) => write!(f, "Output share of party {} failed to decrypt", party),
+            PrivateOutputError::Decode(e) => write!(f, "Malformed output share: {}", e),
+            PrivateOutputError::DuplicateParty(party) => write!(f, "Party {} sent more than one output share", party),
+            PrivateOutputError::Reconstruction(e) => write!(f, "Output reconstruction failed: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for PrivateOutputError {}
+
+impl From<DecodeError> for PrivateOutputError {
+    fn from(e: DecodeError) -> Self {
+        PrivateOutputError::Decode(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mpc::{ShamirSecretSharing, ShamirShare};
+    use ark_bls12_381::{Fr, G1Projective};
+    use ark_std::test_rng;
+
+    type TestField = Fr;
+    type TestGroup = G1Projective;
+    type Shamir = ShamirSecretSharing<TestField>;
+
+    #[test]
+    fn test_only_delegator_learns_output() {
+        let mut rng = test_rng();
+        let delegator = DelegatorKeyPair::<TestGroup>::generate(&mut rng);
+        let output = TestField::from(42u64);
+        let shares = Shamir::share_secret(output, 2, 3, &mut rng);
+
+        let encrypted: Vec<EncryptedShare<TestGroup>> = shares
+            .iter()
+            .enumerate()
+            .map(|(party, share)| {
+                let worker = ExecCircuit::new(party, 3, Shamir::new());
+                let sealed = worker
+                    .reveal_to_delegator("output", share, &delegator.public_key, &mut rng)
+                    .unwrap();
+                assert_eq!(worker.leakage.counts_by_label().get("output"), Some(&1));
+                sealed
+            })
+            .collect();
+
+        // No share encoding appears in what the workers send
+        for (share, sealed) in shares.iter().zip(&encrypted) {
+            let bytes = share.to_bytes();
+            assert!(!sealed.ciphertext.windows(bytes.len()).any(|w| w == bytes.as_slice()));
+        }
+        assert_eq!(delegator.reveal_output::<TestField, Shamir>(&encrypted).unwrap(), output);
+
+        // Another key cannot decrypt, and shares cannot be reattributed
+        let eavesdropper = DelegatorKeyPair::<TestGroup>::generate(&mut rng);
+        assert_eq!(
+            eavesdropper.decrypt_share::<ShamirShare<TestField>>(&encrypted[0]).unwrap_err(),
+            PrivateOutputError::Decryption(0)
+        );
+        let mut reattributed = encrypted[1].clone();
+        reattributed.party = 0;
+        assert!(delegator.decrypt_share::<ShamirShare<TestField>>(&reattributed).is_err());
+        let duplicated = vec![encrypted[0].clone(), encrypted[0].clone()];
+        assert_eq!(
+            delegator.reveal_output::<TestField, Shamir>(&duplicated).unwrap_err(),
+            PrivateOutputError::DuplicateParty(0)
+        );
+    }
+}
@@ -0,0 +1,129 @@
+//! Over-provisioned committees
+//!
+//! Opening a value shared at threshold `t` needs only `t` shares. A
+//! committee of `n ≥ t + redundancy` workers lets every opening go ahead as
+//! soon as the first `t + redundancy` shares have arrived: later shares are
+//! discarded, and the redundant ones among those used let
+//! `robust_reconstruct` correct up to `redundancy / 2` wrong shares. A round
+//! then takes as long as the quorum-th fastest worker instead of the slowest.
+//! Times are milliseconds on the caller's clock, as in `LivenessMonitor`.
+
+use ark_ff::{Field, PrimeField};
+
+use crate::mpc::{SecretSharingError, ShamirSecretSharing, ShamirShare};
+
+/// Committee size and how many shares beyond the threshold an opening waits for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Committee {
+    pub workers: usize,
+    pub threshold: usize,
+    pub redundancy: usize,
+}
+
+impl Committee {
+    pub fn new(workers: usize, threshold: usize, redundancy: usize) -> Result<Self, SecretSharingError> {
+        if threshold == 0 || workers < threshold + redundancy {
+            return Err(SecretSharingError::InsufficientShares);
+        }
+        Ok(Self { workers, threshold, redundancy })
+    }
+
+    /// Shares an opening waits for
+    pub fn quorum(&self) -> usize {
+        self.threshold + self.redundancy
+    }
+
+    /// Wrong shares an opening still corrects
+    pub fn correctable_errors(&self) -> usize {
+        self.redundancy / 2
+    }
+
+    /// Workers whose shares are never waited for
+    pub fn spare_workers(&self) -> usize {
+        self.workers - self.quorum()
+    }
+
+    /// Open a value from the first `quorum()` shares to arrive
+    ///
+    /// Arrivals may be given in any order; ties keep their given order.
+    pub fn open<F: PrimeField>(&self, arrivals: &[ShareArrival<F>]) -> Result<QuorumOpening<F>, SecretSharingError> {
+        if arrivals.len() < self.quorum() {
+            return Err(SecretSharingError::InsufficientShares);
+        }
+        let mut order: Vec<&ShareArrival<F>> = arrivals.iter().collect();
+        order.sort_by_key(|arrival| arrival.arrived_at_ms);
+        let (used, late) = order.split_at(self.quorum());
+
+        let shares: Vec<ShamirShare<F>> = used.iter().map(|arrival| arrival.share.clone()).collect();
+        let reconstruction = ShamirSecretSharing::robust_reconstruct(&shares, self.threshold)?;
+        Ok(QuorumOpening {
+            secret: reconstruction.secret,
+            used: used.iter().map(|arrival| arrival.share.index).collect(),
+            late: late.iter().map(|arrival| arrival.share.index).collect(),
+            corrupted: reconstruction.corrupted,
+            completed_at_ms: used.last().map_or(0, |arrival| arrival.arrived_at_ms),
+        })
+    }
+}
+
+/// A share and when it reached the delegator
+#[derive(Debug, Clone)]
+pub struct ShareArrival<F: Field> {
+    pub share: ShamirShare<F>,
+    pub arrived_at_ms: u64,
+}
+
+/// Value opened by a quorum of the committee
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuorumOpening<F: Field> {
+    pub secret: F,
+    /// Share indices used, in arrival order
+    pub used: Vec<usize>,
+    /// Share indices that arrived after the quorum and were discarded
+    pub late: Vec<usize>,
+    /// Used shares that disagreed with the reconstruction
+    pub corrupted: Vec<usize>,
+    /// When the last share of the quorum arrived
+    pub completed_at_ms: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mpc::SecretSharing;
+    use ark_bls12_381::Fr;
+    use ark_std::test_rng;
+
+    type TestField = Fr;
+
+    #[test]
+    fn test_quorum_opening_skips_stragglers() {
+        let mut rng = test_rng();
+        let secret = TestField::from(42u64);
+        let shares = ShamirSecretSharing::share_secret(secret, 3, 7, &mut rng);
+        let committee = Committee::new(7, 3, 2).unwrap();
+        assert_eq!((committee.quorum(), committee.correctable_errors(), committee.spare_workers()), (5, 1, 2));
+
+        // Worker 7 is far behind and worker 2 is late; one early share is wrong
+        let times = [10, 900, 12, 11, 15, 13, 5_000];
+        let mut arrivals: Vec<ShareArrival<TestField>> = shares
+            .iter()
+            .zip(times)
+            .map(|(share, arrived_at_ms)| ShareArrival { share: share.clone(), arrived_at_ms })
+            .collect();
+        arrivals[3].share.value += TestField::from(1u64);
+
+        let opening = committee.open(&arrivals).unwrap();
+        assert_eq!(opening.secret, secret);
+        assert_eq!(opening.used, [1, 4, 3, 6, 5]);
+        assert_eq!(opening.late, [2, 7]);
+        assert_eq!(opening.corrupted, [4]);
+        assert_eq!(opening.completed_at_ms, 15);
+
+        // A second wrong share within the quorum is beyond what it corrects
+        arrivals[0].share.value += TestField::from(1u64);
+        assert_eq!(committee.open(&arrivals), Err(SecretSharingError::TooManyErrors));
+        assert_eq!(committee.open(&arrivals[..4]), Err(SecretSharingError::InsufficientShares));
+        assert!(Committee::new(4, 3, 2).is_err());
+    }
+}
@@ -0,0 +1,215 @@
+//! Error-correcting reconstruction of Shamir shares
+//!
+//! Shamir shares of a threshold-`t` secret are a Reed–Solomon codeword: the
+//! evaluations of a polynomial of degree below `t`. Given `n` shares,
+//! Berlekamp–Welch decoding recovers that polynomial even when up to
+//! `(n - t) / 2` of them are wrong, and names the parties whose shares were,
+//! so a few workers returning bad output shares can neither block nor skew
+//! the delegator's result.
+
+use std::collections::HashSet;
+
+use ark_ec::CurveGroup;
+use ark_ff::{Field, PrimeField, Zero};
+use ark_poly::univariate::{DenseOrSparsePolynomial, DensePolynomial};
+use ark_poly::{DenseUVPolynomial, Polynomial};
+
+use crate::mpc::{DelegatorKeyPair, EncryptedShare, PrivateOutputError, SecretSharingError, ShamirSecretSharing, ShamirShare};
+
+/// A secret recovered from possibly corrupted shares
+#[derive(Debug, Clone, PartialEq)]
+pub struct RobustReconstruction<F: Field> {
+    pub secret: F,
+    /// Indices of the shares that disagreed with the decoded polynomial
+    pub corrupted: Vec<usize>,
+}
+
+impl<F: PrimeField> ShamirSecretSharing<F> {
+    /// Number of corrupted shares that `num_shares` shares can correct
+    pub fn correctable_errors(num_shares: usize, threshold: usize) -> usize {
+        num_shares.saturating_sub(threshold) / 2
+    }
+
+    /// Reconstruct a threshold-`threshold` secret, correcting up to
+    /// `correctable_errors(shares.len(), threshold)` wrong shares
+    pub fn robust_reconstruct(
+        shares: &[ShamirShare<F>],
+        threshold: usize,
+    ) -> Result<RobustReconstruction<F>, SecretSharingError> {
+        if threshold == 0 || shares.len() < threshold {
+            return Err(SecretSharingError::InsufficientShares);
+        }
+        let mut indices = HashSet::new();
+        if shares.iter().any(|share| share.index == 0 || !indices.insert(share.index)) {
+            return Err(SecretSharingError::InvalidShares);
+        }
+
+        let errors = Self::correctable_errors(shares.len(), threshold);
+        let polynomial = berlekamp_welch(shares, threshold, errors).ok_or(SecretSharingError::TooManyErrors)?;
+        let corrupted: Vec<usize> = shares
+            .iter()
+            .filter(|share| polynomial.evaluate(&F::from(share.index as u64)) != share.value)
+            .map(|share| share.index)
+            .collect();
+        if corrupted.len() > errors {
+            return Err(SecretSharingError::TooManyErrors);
+        }
+        Ok(RobustReconstruction { secret: polynomial.evaluate(&F::zero()), corrupted })
+    }
+}
+
+/// The polynomial of degree below `threshold` within distance `errors` of the
+/// shares, if there is one
+///
+/// Solves `Q(x_i) = y_i · E(x_i)` for a monic error locator `E` of degree
+/// `errors` and `Q` of degree below `errors + threshold`, then divides. Any
+/// solution of the system works: `Q - P·E` has too many roots to be non-zero.
+fn berlekamp_welch<F: PrimeField>(shares: &[ShamirShare<F>], threshold: usize, errors: usize) -> Option<DensePolynomial<F>> {
+    let q_len = errors + threshold;
+    let unknowns = q_len + errors;
+    // Unknowns: q_0..q_{q_len-1}, e_0..e_{errors-1}; E's leading 1 goes right
+    let mut rows: Vec<Vec<F>> = shares
+        .iter()
+        .map(|share| {
+            let x = F::from(share.index as u64);
+            let mut row = Vec::with_capacity(unknowns + 1);
+            let mut power = F::one();
+            for _ in 0..q_len {
+                row.push(power);
+                power *= x;
+            }
+            let mut power = F::one();
+            for _ in 0..errors {
+                row.push(-share.value * power);
+                power *= x;
+            }
+            row.push(share.value * power);
+            row
+        })
+        .collect();
+
+    let solution = solve(&mut rows, unknowns)?;
+    let q = DensePolynomial::from_coefficients_slice(&solution[..q_len]);
+    let mut e = solution[q_len..].to_vec();
+    e.push(F::one());
+    let e = DensePolynomial::from_coefficients_vec(e);
+    let (quotient, remainder) =
+        DenseOrSparsePolynomial::from(&q).divide_with_q_and_r(&DenseOrSparsePolynomial::from(&e))?;
+    remainder.is_zero().then_some(quotient)
+}
+
+/// One solution of the augmented system, free variables set to zero
+fn solve<F: PrimeField>(rows: &mut [Vec<F>], unknowns: usize) -> Option<Vec<F>> {
+    let mut pivots = Vec::new();
+    let mut rank = 0;
+    for column in 0..unknowns {
+        let Some(pivot) = (rank..rows.len()).find(|&r| !rows[r][column].is_zero()) else {
+            continue;
+        };
+        rows.swap(rank, pivot);
+        let inverse = rows[rank][column].inverse().expect("pivot is non-zero");
+        rows[rank].iter_mut().for_each(|value| *value *= inverse);
+        let pivot_row = rows[rank].clone();
+        for (r, row) in rows.iter_mut().enumerate() {
+            let factor = row[column];
+            if r != rank && !factor.is_zero() {
+                row.iter_mut().zip(&pivot_row).for_each(|(value, p)| *value -= factor * p);
+            }
+        }
+        pivots.push(column);
+        rank += 1;
+    }
+    if rows[rank..].iter().any(|row| !row[unknowns].is_zero()) {
+        return None;
+    }
+    let mut solution = vec![F::zero(); unknowns];
+    for (row, &column) in pivots.iter().enumerate() {
+        solution[column] = rows[row][unknowns];
+    }
+    Some(solution)
+}
+
+impl<G: CurveGroup> DelegatorKeyPair<G> {
+    /// Decrypt every worker's Shamir share and reconstruct the output,
+    /// correcting the shares of up to `(n - t) / 2` misbehaving workers
+    pub fn reveal_output_robust<F: PrimeField>(
+        &self,
+        encrypted: &[EncryptedShare<G>],
+        threshold: usize,
+    ) -> Result<RobustReconstruction<F>, PrivateOutputError> {
+        let mut parties = HashSet::new();
+        let shares = encrypted
+            .iter()
+            .map(|share| {
+                if !parties.insert(share.party) {
+                    return Err(PrivateOutputError::DuplicateParty(share.party));
+                }
+                self.decrypt_share(share)
+            })
+            .collect::<Result<Vec<ShamirShare<F>>, _>>()?;
+        ShamirSecretSharing::robust_reconstruct(&shares, threshold).map_err(PrivateOutputError::Reconstruction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mpc::SecretSharing;
+    use ark_bls12_381::{Fr, G1Projective};
+    use ark_std::test_rng;
+
+    type TestField = Fr;
+
+    #[test]
+    fn test_robust_reconstruction_corrects_bad_shares() {
+        let mut rng = test_rng();
+        let secret = TestField::from(4242u64);
+        let (threshold, parties) = (3, 9);
+        let mut shares = ShamirSecretSharing::share_secret(secret, threshold, parties, &mut rng);
+        assert_eq!(ShamirSecretSharing::<TestField>::correctable_errors(parties, threshold), 3);
+
+        let clean = ShamirSecretSharing::robust_reconstruct(&shares, threshold).unwrap();
+        assert_eq!(clean, RobustReconstruction { secret, corrupted: vec![] });
+
+        for index in [1, 4, 8] {
+            shares[index].value += TestField::from(7u64);
+        }
+        let result = ShamirSecretSharing::robust_reconstruct(&shares, threshold).unwrap();
+        assert_eq!(result.secret, secret);
+        assert_eq!(result.corrupted, vec![2, 5, 9]);
+        // Plain interpolation is skewed by the same shares
+        assert_ne!(ShamirSecretSharing::reconstruct_secret(&shares).unwrap(), secret);
+
+        shares[0].value += TestField::from(1u64);
+        assert_eq!(
+            ShamirSecretSharing::robust_reconstruct(&shares, threshold),
+            Err(SecretSharingError::TooManyErrors)
+        );
+        assert_eq!(
+            ShamirSecretSharing::robust_reconstruct(&shares[..2], threshold),
+            Err(SecretSharingError::InsufficientShares)
+        );
+    }
+
+    #[test]
+    fn test_delegator_recovers_output_despite_bad_workers() {
+        let mut rng = test_rng();
+        let delegator = DelegatorKeyPair::<G1Projective>::generate(&mut rng);
+        let secret = TestField::from(99u64);
+        let shares = ShamirSecretSharing::share_secret(secret, 2, 5, &mut rng);
+        let encrypted: Vec<_> = shares
+            .iter()
+            .enumerate()
+            .map(|(party, share)| {
+                let mut share = share.clone();
+                if party == 3 {
+                    share.value = TestField::from(0u64);
+                }
+                crate::mpc::encrypt_output_share::<G1Projective, _>(party, &share, &delegator.public_key, &mut rng)
+            })
+            .collect();
+        let result = delegator.reveal_output_robust::<TestField>(&encrypted, 2).unwrap();
+        assert_eq!(result.secret, secret);
+        assert_eq!(result.corrupted, vec![4]);
+    }
+}
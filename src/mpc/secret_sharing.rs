@@ -6,6 +6,8 @@
 use ark_ff::{Field, PrimeField};
 use ark_std::rand::Rng;
 
+use crate::sampling::SeededSampler;
+
 /// A secret sharing scheme trait
 pub trait SecretSharing<F: Field>: Clone {
     type Share: Clone;
@@ -18,6 +20,17 @@ pub trait SecretSharing<F: Field>: Clone {
         num_parties: usize,
         rng: &mut impl Rng,
     ) -> Vec<Self::Share>;
+
+    /// Share a secret with every random value drawn from `sampler`, so the
+    /// same seed yields the same shares on any platform
+    fn share_secret_seeded(
+        secret: F,
+        threshold: usize,
+        num_parties: usize,
+        sampler: &mut SeededSampler,
+    ) -> Vec<Self::Share>
+    where
+        F: PrimeField;
     
     /// Reconstruct secret from shares
     fn reconstruct_secret(shares: &[Self::Share]) -> Result<F, SecretSharingError>;
@@ -36,6 +49,27 @@ pub trait SecretSharing<F: Field>: Clone {
 }
 
 /// Shamir's secret sharing implementation
+///
+/// A secret shared at threshold `t` is the constant term of a random
+/// polynomial of degree `t - 1`; party `i` holds its value at `i`, so any
+/// `t` shares open the secret and fewer reveal nothing about it. Additions
+/// are local.
+///
+/// ```
+/// use ark_bls12_381::Fr;
+/// use eos_delegation::mpc::{SecretSharing, ShamirSecretSharing};
+///
+/// let mut rng = ark_std::test_rng();
+/// let a = ShamirSecretSharing::share_secret(Fr::from(20u64), 2, 3, &mut rng);
+/// let b = ShamirSecretSharing::share_secret(Fr::from(22u64), 2, 3, &mut rng);
+/// let sum: Vec<_> = a.iter().zip(&b).map(|(x, y)| ShamirSecretSharing::add_shares(x, y).unwrap()).collect();
+///
+/// // Any two of the three shares open the sum
+/// assert_eq!(ShamirSecretSharing::reconstruct_secret(&sum[1..]).unwrap(), Fr::from(42u64));
+/// assert_eq!(ShamirSecretSharing::reconstruct_secret(&[sum[0].clone(), sum[2].clone()]).unwrap(), Fr::from(42u64));
+/// // One share is a random field element
+/// assert_ne!(ShamirSecretSharing::reconstruct_secret(&sum[..1]).unwrap(), Fr::from(42u64));
+/// ```
 #[derive(Clone)]
 pub struct ShamirSecretSharing<F: PrimeField> {
     _phantom: std::marker::PhantomData<F>,
@@ -45,6 +79,30 @@ impl<F: PrimeField> ShamirSecretSharing<F> {
     pub fn new() -> Self {
         Self { _phantom: std::marker::PhantomData }
     }
+
+    /// Evaluate the sharing polynomial at points 1, 2, ..., num_parties
+    fn evaluate_shares(coeffs: &[F], num_parties: usize) -> Vec<ShamirShare<F>> {
+        (1..=num_parties)
+            .map(|i| {
+                let x = F::from(i as u64);
+                let mut y = F::zero();
+                let mut x_power = F::one();
+                
+                for coeff in coeffs {
+                    y += *coeff * x_power;
+                    x_power *= x;
+                }
+                
+                ShamirShare { index: i, value: y }
+            })
+            .collect()
+    }
+}
+
+impl<F: PrimeField> Default for ShamirSecretSharing<F> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -71,21 +129,19 @@ impl<F: PrimeField> SecretSharing<F> for ShamirSecretSharing<F> {
             coeffs.push(F::rand(rng));
         }
         
-        // Evaluate polynomial at points 1, 2, ..., num_parties
-        (1..=num_parties)
-            .map(|i| {
-                let x = F::from(i as u64);
-                let mut y = F::zero();
-                let mut x_power = F::one();
-                
-                for coeff in &coeffs {
-                    y += *coeff * x_power;
-                    x_power *= x;
-                }
-                
-                ShamirShare { index: i, value: y }
-            })
-            .collect()
+        Self::evaluate_shares(&coeffs, num_parties)
+    }
+
+    fn share_secret_seeded(
+        secret: F,
+        threshold: usize,
+        num_parties: usize,
+        sampler: &mut SeededSampler,
+    ) -> Vec<Self::Share> {
+        assert!(threshold <= num_parties);
+        let mut coeffs = vec![secret];
+        coeffs.extend(sampler.fields::<F>(threshold.saturating_sub(1)));
+        Self::evaluate_shares(&coeffs, num_parties)
     }
     
     fn reconstruct_secret(shares: &[Self::Share]) -> Result<F, SecretSharingError> {
@@ -162,6 +218,12 @@ impl<F: Field> AdditiveSecretSharing<F> {
     }
 }
 
+impl<F: Field> Default for AdditiveSecretSharing<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct AdditiveShare<F: Field> {
     pub party_id: usize,
@@ -199,6 +261,26 @@ impl<F: Field> SecretSharing<F> for AdditiveSecretSharing<F> {
         
         shares
     }
+
+    fn share_secret_seeded(
+        secret: F,
+        _threshold: usize,
+        num_parties: usize,
+        sampler: &mut SeededSampler,
+    ) -> Vec<Self::Share>
+    where
+        F: PrimeField,
+    {
+        let mut shares: Vec<_> = sampler
+            .fields::<F>(num_parties - 1)
+            .into_iter()
+            .enumerate()
+            .map(|(party_id, value)| AdditiveShare { party_id, value })
+            .collect();
+        let sum: F = shares.iter().map(|s| s.value).sum();
+        shares.push(AdditiveShare { party_id: num_parties - 1, value: secret - sum });
+        shares
+    }
     
     fn reconstruct_secret(shares: &[Self::Share]) -> Result<F, SecretSharingError> {
         if shares.is_empty() {
@@ -235,11 +317,12 @@ impl<F: Field> SecretSharing<F> for AdditiveSecretSharing<F> {
 }
 
 /// Secret sharing error types
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum SecretSharingError {
     InsufficientShares,
     InvalidShares,
     ReconstructionFailed,
+    TooManyErrors,
 }
 
 impl std::fmt::Display for SecretSharingError {
@@ -248,6 +331,7 @@ impl std::fmt::Display for SecretSharingError {
             SecretSharingError::InsufficientShares => write!(f, "Insufficient shares for reconstruction"),
             SecretSharingError::InvalidShares => write!(f, "Invalid shares provided"),
             SecretSharingError::ReconstructionFailed => write!(f, "Secret reconstruction failed"),
+            SecretSharingError::TooManyErrors => write!(f, "Too many corrupted shares to correct"),
         }
     }
 }
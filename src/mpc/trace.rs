@@ -0,0 +1,214 @@
+//! Execution traces of R1CS circuits run on shared witnesses
+//!
+//! Running a constraint system under MPC means evaluating, for every
+//! constraint, the three linear gates `⟨A_i, z⟩`, `⟨B_i, z⟩` and
+//! `⟨C_i, z⟩` over the shared assignment `z = (1, public inputs, witness)`.
+//! Their outputs, one column per matrix, are the execution trace: the
+//! tables of the polynomials the PIOP proves `a∘b = c` about. The constant
+//! and public-input columns are known to everyone, so each trace cell is a
+//! shared witness part plus a public offset. Witness variables the delegator
+//! keeps to itself are folded into that offset too: only the delegator adds
+//! it back when reconstructing, so the workers never see those values.
+//!
+//! The delegator reconstructs the trace from the workers' shares; the
+//! protocol then checks it against the witness polynomials it commits to.
+
+use ark_ff::Field;
+
+use crate::mpc::{ExecCircuit, ExecutionError, SecretSharing, WireShares};
+use crate::protocol::ConstraintMatrices;
+
+/// One gate output: shares of its witness-dependent part and its public part
+pub struct TraceCell<F: Field, SS: SecretSharing<F>> {
+    /// `None` when the row touches no shared witness variable
+    pub shares: Option<WireShares<F, SS>>,
+    /// Part known to the delegator: constants, public inputs and local witnesses
+    pub public: F,
+}
+
+/// A witness variable as the execution receives it
+pub enum WitnessInput<'a, F: Field, SS: SecretSharing<F>> {
+    /// Secret shared among the workers
+    Shared(&'a WireShares<F, SS>),
+    /// Kept by the delegator
+    Local(F),
+}
+
+/// Trace as held by the workers, one cell per constraint and matrix
+pub struct SharedTrace<F: Field, SS: SecretSharing<F>> {
+    pub columns: [Vec<TraceCell<F, SS>>; 3],
+}
+
+/// Trace values as computed by the MPC execution
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ExecutionTrace<F: Field> {
+    /// `⟨A_i, z⟩` for every constraint
+    pub a: Vec<F>,
+    /// `⟨B_i, z⟩` for every constraint
+    pub b: Vec<F>,
+    /// `⟨C_i, z⟩` for every constraint
+    pub c: Vec<F>,
+}
+
+impl<F: Field> ExecutionTrace<F> {
+    pub fn num_constraints(&self) -> usize {
+        self.a.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.a.is_empty()
+    }
+
+    pub fn columns(&self) -> [&[F]; 3] {
+        [&self.a, &self.b, &self.c]
+    }
+
+    /// Constraints whose row does not satisfy `a·b = c`
+    pub fn unsatisfied(&self) -> Vec<usize> {
+        (0..self.num_constraints()).filter(|&i| self.a[i] * self.b[i] != self.c[i]).collect()
+    }
+}
+
+impl<F: Field, SS: SecretSharing<F>> ExecCircuit<F, SS> {
+    /// Evaluate every constraint's three linear gates on a shared witness
+    ///
+    /// `witness_shares[j]` holds every party's share of `Variable::Witness(j)`
+    /// and `public_inputs[i]` is `Variable::Instance(i + 1)`.
+    pub fn execute_r1cs(
+        &mut self,
+        matrices: &ConstraintMatrices<F>,
+        public_inputs: &[F],
+        witness_shares: &[WireShares<F, SS>],
+    ) -> Result<SharedTrace<F, SS>, ExecutionError> {
+        let inputs: Vec<WitnessInput<'_, F, SS>> = witness_shares.iter().map(WitnessInput::Shared).collect();
+        self.execute_r1cs_with_local(matrices, public_inputs, &inputs)
+    }
+
+    /// Like `execute_r1cs`, with some witness variables kept by the delegator
+    pub fn execute_r1cs_with_local(
+        &mut self,
+        matrices: &ConstraintMatrices<F>,
+        public_inputs: &[F],
+        witness: &[WitnessInput<'_, F, SS>],
+    ) -> Result<SharedTrace<F, SS>, ExecutionError> {
+        let num_instance = public_inputs.len() + 1;
+        let mut execute = |rows: &[Vec<(usize, F)>]| -> Result<Vec<TraceCell<F, SS>>, ExecutionError> {
+            rows.iter().map(|row| self.execute_row(row, num_instance, public_inputs, witness)).collect()
+        };
+        let a = execute(&matrices.a_matrix)?;
+        let b = execute(&matrices.b_matrix)?;
+        let c = execute(&matrices.c_matrix)?;
+        Ok(SharedTrace { columns: [a, b, c] })
+    }
+
+    fn execute_row(
+        &mut self,
+        row: &[(usize, F)],
+        num_instance: usize,
+        public_inputs: &[F],
+        witness: &[WitnessInput<'_, F, SS>],
+    ) -> Result<TraceCell<F, SS>, ExecutionError> {
+        let mut public = F::zero();
+        let mut wires = Vec::new();
+        let mut coefficients = Vec::new();
+        for &(column, coefficient) in row {
+            if column == 0 {
+                public += coefficient;
+            } else if column < num_instance {
+                public += coefficient * public_inputs[column - 1];
+            } else {
+                match witness.get(column - num_instance) {
+                    Some(WitnessInput::Shared(wire)) => {
+                        wires.push(*wire);
+                        coefficients.push(coefficient);
+                    }
+                    Some(WitnessInput::Local(value)) => public += coefficient * value,
+                    None => {
+                        return Err(ExecutionError::CircuitError(format!(
+                            "constraint reads unassigned witness {}",
+                            column - num_instance
+                        )))
+                    }
+                }
+            }
+        }
+        if wires.is_empty() {
+            return Ok(TraceCell { shares: None, public });
+        }
+        let shares = (0..self.num_parties)
+            .map(|party| {
+                let inputs: Vec<SS::Share> = wires.iter().map(|wire| wire[party].clone()).collect();
+                self.linear_combination_gate(&inputs, &coefficients)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(TraceCell { shares: Some(shares), public })
+    }
+}
+
+impl<F: Field, SS: SecretSharing<F>> SharedTrace<F, SS> {
+    /// Reconstruct the trace, recording the reveal as `execution_trace`
+    pub fn reconstruct(&self, executor: &ExecCircuit<F, SS>) -> Result<ExecutionTrace<F>, ExecutionError> {
+        let cells: usize = self.columns.iter().map(Vec::len).sum();
+        if cells > 0 {
+            executor.leakage.record("execution_trace", cells).map_err(ExecutionError::RevealBlocked)?;
+        }
+        let reconstruct = |column: &[TraceCell<F, SS>]| {
+            column
+                .iter()
+                .map(|cell| match &cell.shares {
+                    Some(shares) => {
+                        Ok(SS::reconstruct_secret(shares).map_err(ExecutionError::SecretSharingError)? + cell.public)
+                    }
+                    None => Ok(cell.public),
+                })
+                .collect::<Result<Vec<F>, ExecutionError>>()
+        };
+        let [a, b, c] = &self.columns;
+        Ok(ExecutionTrace { a: reconstruct(a)?, b: reconstruct(b)?, c: reconstruct(c)? })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mpc::ShamirSecretSharing;
+    use ark_bls12_381::Fr;
+    use ark_std::test_rng;
+
+    type TestField = Fr;
+
+    #[test]
+    fn test_r1cs_trace_from_shared_witness() {
+        let mut rng = test_rng();
+        let mut executor = ExecCircuit::new(0, 3, ShamirSecretSharing::<TestField>::new());
+        let one = TestField::from(1u64);
+        // x·y = t and (t + 1)·1 = out, with out public: z = (1, out, x, y, t)
+        let matrices = ConstraintMatrices {
+            a_matrix: vec![vec![(2, one)], vec![(4, one), (0, one)]],
+            b_matrix: vec![vec![(3, one)], vec![(0, one)]],
+            c_matrix: vec![vec![(4, one)], vec![(1, one)]],
+        };
+        let witness = [3u64, 5, 15].map(TestField::from);
+        let shares: Vec<_> = witness.iter().map(|w| executor.input_secret(*w, 2, &mut rng)).collect();
+
+        let trace = executor
+            .execute_r1cs(&matrices, &[TestField::from(16u64)], &shares)
+            .unwrap()
+            .reconstruct(&executor)
+            .unwrap();
+        assert_eq!(trace.a, [3u64, 16].map(TestField::from));
+        assert_eq!(trace.b, [5u64, 1].map(TestField::from));
+        assert_eq!(trace.c, [15u64, 16].map(TestField::from));
+        assert!(trace.unsatisfied().is_empty());
+        assert_eq!(executor.leakage.counts_by_label().get("execution_trace"), Some(&6));
+
+        // A wrong public output shows up as an unsatisfied row
+        let trace = executor
+            .execute_r1cs(&matrices, &[TestField::from(17u64)], &shares)
+            .unwrap()
+            .reconstruct(&executor)
+            .unwrap();
+        assert_eq!(trace.unsatisfied(), vec![1]);
+        assert!(executor.execute_r1cs(&matrices, &[TestField::from(16u64)], &shares[..2]).is_err());
+    }
+}
@@ -0,0 +1,353 @@
+//! Wire types and the circuit type-checking pass
+//!
+//! Every wire of a `GateCircuit` carries a field element, but not every
+//! field element means the same thing: a selection condition must be a bit,
+//! fixed-point values only add at the same scale, and packed bytes take no
+//! part in arithmetic. `ExecCircuit::typecheck` propagates the declared
+//! input types through the gates and rejects an ill-typed circuit before
+//! any share is sent.
+
+use ark_ff::Field;
+
+use crate::mpc::{
+    ExecCircuit, ExecutionError, ExecutionStats, Gate, GateCircuit, SecretSharing, WireShares,
+};
+
+/// Meaning of the field element on a wire
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WireType {
+    Field,
+    /// 0 or 1
+    Boolean,
+    /// Value scaled by `2^fractional_bits`
+    FixedPoint {
+        fractional_bits: u32,
+    },
+    /// Up to 31 bytes packed little-endian into one element
+    Bytes {
+        len: usize,
+    },
+}
+
+impl std::fmt::Display for WireType {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            WireType::Field => write!(f, "field"),
+            WireType::Boolean => write!(f, "bool"),
+            WireType::FixedPoint { fractional_bits } => write!(f, "fixed<{}>", fractional_bits),
+            WireType::Bytes { len } => write!(f, "bytes<{}>", len),
+        }
+    }
+}
+
+impl WireType {
+    /// Type of `left + right`; a sum of bits is no longer a bit
+    pub fn sum_type(self, right: WireType) -> Option<WireType> {
+        match (self, right) {
+            (WireType::Bytes { .. }, _) | (_, WireType::Bytes { .. }) => None,
+            (
+                WireType::FixedPoint { fractional_bits: a },
+                WireType::FixedPoint { fractional_bits: b },
+            ) if a == b => Some(self),
+            (WireType::FixedPoint { .. }, _) | (_, WireType::FixedPoint { .. }) => None,
+            _ => Some(WireType::Field),
+        }
+    }
+
+    /// Type of `left * right`; scales of fixed-point factors add up
+    pub fn product_type(self, right: WireType) -> Option<WireType> {
+        match (self, right) {
+            (WireType::Bytes { .. }, _) | (_, WireType::Bytes { .. }) => None,
+            (WireType::Boolean, WireType::Boolean) => Some(WireType::Boolean),
+            (
+                WireType::FixedPoint { fractional_bits: a },
+                WireType::FixedPoint { fractional_bits: b },
+            ) => Some(WireType::FixedPoint {
+                fractional_bits: a + b,
+            }),
+            (fixed @ WireType::FixedPoint { .. }, _) | (_, fixed @ WireType::FixedPoint { .. }) => {
+                Some(fixed)
+            }
+            _ => Some(WireType::Field),
+        }
+    }
+}
+
+impl<F: Field, SS: SecretSharing<F>> ExecCircuit<F, SS> {
+    /// Type-check `circuit` for the given input types, returning the types
+    /// of its outputs
+    pub fn typecheck(
+        &self,
+        circuit: &GateCircuit,
+        input_types: &[WireType],
+    ) -> Result<Vec<WireType>, WireTypeError> {
+        if input_types.len() != circuit.num_inputs {
+            return Err(WireTypeError::InputCount {
+                expected: circuit.num_inputs,
+                actual: input_types.len(),
+            });
+        }
+        let mut types = input_types.to_vec();
+        for (gate_index, gate) in circuit.gates.iter().enumerate() {
+            let type_of = |wire: usize, types: &[WireType]| {
+                types
+                    .get(wire)
+                    .copied()
+                    .ok_or(WireTypeError::UndefinedWire {
+                        gate: gate_index,
+                        wire,
+                    })
+            };
+            let ill_typed = |reason: String| WireTypeError::IllTyped {
+                gate: gate_index,
+                reason,
+            };
+            match gate {
+                Gate::Add(left, right) => {
+                    let (l, r) = (type_of(*left, &types)?, type_of(*right, &types)?);
+                    types.push(
+                        l.sum_type(r)
+                            .ok_or_else(|| ill_typed(format!("cannot add {} and {}", l, r)))?,
+                    );
+                }
+                Gate::Mul(left, right) => {
+                    let (l, r) = (type_of(*left, &types)?, type_of(*right, &types)?);
+                    types
+                        .push(l.product_type(r).ok_or_else(|| {
+                            ill_typed(format!("cannot multiply {} and {}", l, r))
+                        })?);
+                }
+                Gate::Select {
+                    condition,
+                    if_true,
+                    if_false,
+                } => {
+                    let condition = type_of(*condition, &types)?;
+                    if condition != WireType::Boolean {
+                        return Err(ill_typed(format!(
+                            "selection condition is {}, not bool",
+                            condition
+                        )));
+                    }
+                    let (t, f) = (type_of(*if_true, &types)?, type_of(*if_false, &types)?);
+                    if t != f {
+                        return Err(ill_typed(format!("selection branches are {} and {}", t, f)));
+                    }
+                    if let WireType::Bytes { .. } = t {
+                        return Err(ill_typed(format!("cannot select between {} values", t)));
+                    }
+                    types.push(t);
+                }
+                Gate::Custom { name, inputs } => {
+                    let custom =
+                        self.custom_gates
+                            .get(name)
+                            .ok_or_else(|| WireTypeError::UnknownGate {
+                                gate: gate_index,
+                                name: name.clone(),
+                            })?;
+                    let actual = inputs
+                        .iter()
+                        .map(|&wire| type_of(wire, &types))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    if let Some(expected) = custom.input_types() {
+                        if expected != actual {
+                            let list = |types: &[WireType]| {
+                                types
+                                    .iter()
+                                    .map(WireType::to_string)
+                                    .collect::<Vec<_>>()
+                                    .join(", ")
+                            };
+                            return Err(ill_typed(format!(
+                                "gate '{}' takes ({}), got ({})",
+                                name,
+                                list(&expected),
+                                list(&actual)
+                            )));
+                        }
+                    }
+                    types.extend(custom.output_types());
+                }
+            }
+        }
+        circuit
+            .outputs
+            .iter()
+            .map(|&wire| {
+                types
+                    .get(wire)
+                    .copied()
+                    .ok_or(WireTypeError::UndefinedWire {
+                        gate: circuit.gates.len(),
+                        wire,
+                    })
+            })
+            .collect()
+    }
+
+    /// Type-check and run a circuit
+    pub fn run_typed_circuit(
+        &mut self,
+        circuit: &GateCircuit,
+        input_types: &[WireType],
+        inputs: &[WireShares<F, SS>],
+    ) -> Result<(Vec<WireShares<F, SS>>, ExecutionStats), ExecutionError> {
+        self.typecheck(circuit, input_types)
+            .map_err(|e| ExecutionError::CircuitError(e.to_string()))?;
+        self.run_gate_circuit(circuit, inputs)
+    }
+}
+
+/// Type-checking error types
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WireTypeError {
+    InputCount {
+        expected: usize,
+        actual: usize,
+    },
+    /// A gate (or, with `gate` equal to the gate count, an output) reads a
+    /// wire that is not defined yet
+    UndefinedWire {
+        gate: usize,
+        wire: usize,
+    },
+    UnknownGate {
+        gate: usize,
+        name: String,
+    },
+    IllTyped {
+        gate: usize,
+        reason: String,
+    },
+}
+
+impl std::fmt::Display for WireTypeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            WireTypeError::InputCount { expected, actual } => {
+                write!(
+                    f,
+                    "Circuit has {} inputs but {} types were given",
+                    expected, actual
+                )
+            }
+            WireTypeError::UndefinedWire { gate, wire } => {
+                write!(f, "Gate {} reads undefined wire {}", gate, wire)
+            }
+            WireTypeError::UnknownGate { gate, name } => {
+                write!(f, "Gate {} uses unregistered gate '{}'", gate, name)
+            }
+            WireTypeError::IllTyped { gate, reason } => {
+                write!(f, "Gate {} is ill-typed: {}", gate, reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for WireTypeError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mpc::ShamirSecretSharing;
+    use ark_bls12_381::Fr;
+    use ark_std::test_rng;
+
+    type TestField = Fr;
+
+    #[test]
+    fn test_typecheck_rejects_non_boolean_selection() {
+        let mut rng = test_rng();
+        let mut executor = ExecCircuit::new(0, 3, ShamirSecretSharing::<TestField>::new());
+        let fixed = WireType::FixedPoint {
+            fractional_bits: 16,
+        };
+        // select(b, x * y, x): b is the bit, x and y fixed-point
+        let circuit = GateCircuit {
+            num_inputs: 3,
+            gates: vec![
+                Gate::Mul(1, 2),
+                Gate::Mul(1, 1),
+                Gate::Select {
+                    condition: 0,
+                    if_true: 4,
+                    if_false: 4,
+                },
+            ],
+            outputs: vec![3, 5],
+        };
+        let types = executor
+            .typecheck(&circuit, &[WireType::Boolean, fixed, WireType::Field])
+            .unwrap();
+        assert_eq!(
+            types,
+            vec![
+                fixed,
+                WireType::FixedPoint {
+                    fractional_bits: 32
+                }
+            ]
+        );
+
+        let err = executor
+            .typecheck(&circuit, &[WireType::Field, fixed, WireType::Field])
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Gate 2 is ill-typed: selection condition is field, not bool"
+        );
+        let bytes = WireType::Bytes { len: 4 };
+        assert!(matches!(
+            executor.typecheck(&circuit, &[WireType::Boolean, bytes, WireType::Field]),
+            Err(WireTypeError::IllTyped { gate: 0, .. })
+        ));
+        let mixed_scales = GateCircuit {
+            num_inputs: 2,
+            gates: vec![Gate::Add(0, 1)],
+            outputs: vec![2],
+        };
+        assert!(executor
+            .typecheck(
+                &mixed_scales,
+                &[fixed, WireType::FixedPoint { fractional_bits: 8 }]
+            )
+            .is_err());
+        assert_eq!(
+            executor
+                .typecheck(&mixed_scales, &[WireType::Boolean; 2])
+                .unwrap(),
+            vec![WireType::Field]
+        );
+
+        // Well-typed circuits run; selection picks the branch the bit names
+        let select = GateCircuit {
+            num_inputs: 3,
+            gates: vec![Gate::Select {
+                condition: 0,
+                if_true: 1,
+                if_false: 2,
+            }],
+            outputs: vec![3],
+        };
+        let types = [WireType::Boolean, WireType::Field, WireType::Field];
+        for (bit, expected) in [(1u64, 10u64), (0, 20)] {
+            let inputs: Vec<_> = [bit, 10, 20]
+                .iter()
+                .map(|&x| executor.input_secret(TestField::from(x), 1, &mut rng))
+                .collect();
+            let (outputs, _) = executor
+                .run_typed_circuit(&select, &types, &inputs)
+                .unwrap();
+            assert_eq!(
+                executor.reveal_secret(&outputs[0]).unwrap(),
+                TestField::from(expected)
+            );
+        }
+        let inputs = vec![executor.input_secret(TestField::from(1u64), 1, &mut rng); 3];
+        assert!(matches!(
+            executor.run_typed_circuit(&select, &[WireType::Field; 3], &inputs),
+            Err(ExecutionError::CircuitError(_))
+        ));
+    }
+}
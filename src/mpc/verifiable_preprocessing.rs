@@ -0,0 +1,348 @@
+//! Cut-and-choose evidence that worker-generated preprocessing is well formed
+//!
+//! When the workers rather than a trusted dealer generate Beaver triples and
+//! random bits, a malicious worker could hand out triples with `c != a * b`,
+//! bits outside `{0, 1}` or sharings that do not lie on a polynomial of
+//! degree below the threshold. Any of these silently corrupts the online
+//! phase. Before the online phase begins the delegator therefore runs a
+//! cut-and-choose check:
+//!
+//! 1. the worker generates more material than needed and commits to every
+//!    item with a salted hash (`PreprocessingDealing::generate`);
+//! 2. the delegator picks a random subset of items to open
+//!    (`PreprocessingCommitment::challenge`);
+//! 3. the worker opens that subset and keeps the rest as its pool
+//!    (`PreprocessingDealing::respond`);
+//! 4. the delegator checks every opened item against its commitment and
+//!    for well-formedness (`PreprocessingCommitment::verify`), and only then
+//!    accepts the pool (`ThresholdPreprocessing::add_verified_pool`).
+//!
+//! Opened items are discarded, since opening reveals their secrets. If `k`
+//! of `N` committed items are malformed and `m` are opened, all of them go
+//! undetected with probability `C(N - k, m) / C(N, m)`; see
+//! `escape_probability` to size `m`.
+
+use std::collections::BTreeSet;
+
+use ark_ff::PrimeField;
+use ark_std::rand::Rng;
+use sha2::{Digest, Sha256};
+
+use crate::mpc::{
+    BeaverTriple, MaterialKind, PreprocessingError, PreprocessingStore, SecretSharing, ShamirSecretSharing,
+    ShamirShare, ThresholdConfig, ThresholdPreprocessing,
+};
+use crate::serialization::{write_field, write_u64};
+
+const ITEM_COMMITMENT_DOMAIN: &[u8] = b"EOS-preprocessing-item-v1";
+
+/// Material a worker generated, with the salts of its commitments
+pub struct PreprocessingDealing<F: PrimeField> {
+    config: ThresholdConfig,
+    triples: Vec<(BeaverTriple<ShamirShare<F>>, [u8; 32])>,
+    bits: Vec<(Vec<ShamirShare<F>>, [u8; 32])>,
+}
+
+/// Salted hash commitments to every generated item, sent to the delegator
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreprocessingCommitment {
+    pub config: ThresholdConfig,
+    pub triples: Vec<[u8; 32]>,
+    pub bits: Vec<[u8; 32]>,
+}
+
+/// Items the delegator asks the worker to open
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CutAndChooseChallenge {
+    pub triples: BTreeSet<usize>,
+    pub bits: BTreeSet<usize>,
+}
+
+/// Openings of the challenged items
+#[derive(Debug, Clone)]
+pub struct CutAndChooseEvidence<F: PrimeField> {
+    pub triples: Vec<(usize, BeaverTriple<ShamirShare<F>>, [u8; 32])>,
+    pub bits: Vec<(usize, Vec<ShamirShare<F>>, [u8; 32])>,
+}
+
+fn item_commitment<F: PrimeField>(
+    kind: MaterialKind,
+    index: usize,
+    sharings: &[&[ShamirShare<F>]],
+    salt: &[u8; 32],
+) -> [u8; 32] {
+    let mut out = ITEM_COMMITMENT_DOMAIN.to_vec();
+    out.push(match kind {
+        MaterialKind::Triple => 0,
+        MaterialKind::Bit => 1,
+    });
+    write_u64(&mut out, index as u64);
+    out.extend_from_slice(salt);
+    for sharing in sharings {
+        write_u64(&mut out, sharing.len() as u64);
+        for share in *sharing {
+            write_u64(&mut out, share.index as u64);
+            write_field(&mut out, &share.value);
+        }
+    }
+    Sha256::digest(&out).into()
+}
+
+/// Secret of a sharing of all parties at points `1..=n` that lies on a
+/// polynomial of degree below the threshold, or `None` if it does not
+fn consistent_secret<F: PrimeField>(shares: &[ShamirShare<F>], config: ThresholdConfig) -> Option<F> {
+    if shares.len() != config.num_parties
+        || config.threshold == 0
+        || shares.iter().enumerate().any(|(i, share)| share.index != i + 1)
+    {
+        return None;
+    }
+    let (basis, rest) = shares.split_at(config.threshold);
+    // Every further share must agree with the interpolation of the first t
+    let interpolate = |x: F| {
+        basis.iter().enumerate().fold(F::zero(), |acc, (i, share_i)| {
+            let xi = F::from(share_i.index as u64);
+            let weight = basis.iter().enumerate().filter(|(j, _)| *j != i).fold(F::one(), |w, (_, share_j)| {
+                let xj = F::from(share_j.index as u64);
+                w * (x - xj) * (xi - xj).inverse().expect("indices are distinct")
+            });
+            acc + share_i.value * weight
+        })
+    };
+    rest.iter()
+        .all(|share| interpolate(F::from(share.index as u64)) == share.value)
+        .then(|| interpolate(F::zero()))
+}
+
+/// `count` distinct indices below `total`, chosen uniformly
+fn sample_indices<R: Rng + ?Sized>(total: usize, count: usize, rng: &mut R) -> BTreeSet<usize> {
+    let mut indices: Vec<usize> = (0..total).collect();
+    for i in 0..count.min(total) {
+        let j = rng.gen_range(i..total);
+        indices.swap(i, j);
+    }
+    indices.truncate(count.min(total));
+    indices.into_iter().collect()
+}
+
+impl<F: PrimeField> PreprocessingDealing<F> {
+    /// Generate and commit to `num_triples` triples and `num_bits` bits,
+    /// some of which the delegator will open
+    pub fn generate(
+        config: ThresholdConfig,
+        num_triples: usize,
+        num_bits: usize,
+        rng: &mut impl Rng,
+    ) -> (Self, PreprocessingCommitment) {
+        let ThresholdConfig { threshold, num_parties } = config;
+        let triples: Vec<_> = (0..num_triples)
+            .map(|_| {
+                let (a, b) = (F::rand(rng), F::rand(rng));
+                let triple = BeaverTriple {
+                    a: ShamirSecretSharing::share_secret(a, threshold, num_parties, rng),
+                    b: ShamirSecretSharing::share_secret(b, threshold, num_parties, rng),
+                    c: ShamirSecretSharing::share_secret(a * b, threshold, num_parties, rng),
+                };
+                (triple, rng.gen())
+            })
+            .collect();
+        let bits: Vec<_> = (0..num_bits)
+            .map(|_| {
+                let bit = if rng.gen::<bool>() { F::one() } else { F::zero() };
+                (ShamirSecretSharing::share_secret(bit, threshold, num_parties, rng), rng.gen())
+            })
+            .collect();
+        let dealing = Self { config, triples, bits };
+        let commitment = dealing.commitment();
+        (dealing, commitment)
+    }
+
+    fn commitment(&self) -> PreprocessingCommitment {
+        PreprocessingCommitment {
+            config: self.config,
+            triples: self
+                .triples
+                .iter()
+                .enumerate()
+                .map(|(i, (t, salt))| item_commitment(MaterialKind::Triple, i, &[&t.a, &t.b, &t.c], salt))
+                .collect(),
+            bits: self
+                .bits
+                .iter()
+                .enumerate()
+                .map(|(i, (bit, salt))| item_commitment(MaterialKind::Bit, i, &[bit], salt))
+                .collect(),
+        }
+    }
+
+    /// Open the challenged items and keep the others as the session pool
+    pub fn respond(
+        self,
+        challenge: &CutAndChooseChallenge,
+    ) -> (CutAndChooseEvidence<F>, PreprocessingStore<F, ShamirSecretSharing<F>>) {
+        let mut evidence = CutAndChooseEvidence { triples: Vec::new(), bits: Vec::new() };
+        let mut pool_triples = Vec::new();
+        for (index, (triple, salt)) in self.triples.into_iter().enumerate() {
+            if challenge.triples.contains(&index) {
+                evidence.triples.push((index, triple, salt));
+            } else {
+                pool_triples.push(triple);
+            }
+        }
+        let mut pool_bits = Vec::new();
+        for (index, (bit, salt)) in self.bits.into_iter().enumerate() {
+            if challenge.bits.contains(&index) {
+                evidence.bits.push((index, bit, salt));
+            } else {
+                pool_bits.push(bit);
+            }
+        }
+        let pool = PreprocessingStore { triples: pool_triples, bits: pool_bits, ledger: Default::default() };
+        (evidence, pool)
+    }
+}
+
+impl PreprocessingCommitment {
+    /// Choose `open_triples` triples and `open_bits` bits to open
+    pub fn challenge<R: Rng + ?Sized>(&self, open_triples: usize, open_bits: usize, rng: &mut R) -> CutAndChooseChallenge {
+        CutAndChooseChallenge {
+            triples: sample_indices(self.triples.len(), open_triples, rng),
+            bits: sample_indices(self.bits.len(), open_bits, rng),
+        }
+    }
+
+    /// Check that every challenged item was opened, matches its commitment
+    /// and is well formed
+    pub fn verify<F: PrimeField>(
+        &self,
+        challenge: &CutAndChooseChallenge,
+        evidence: &CutAndChooseEvidence<F>,
+    ) -> Result<(), PreprocessingError> {
+        let opened: BTreeSet<usize> = evidence.triples.iter().map(|(index, ..)| *index).collect();
+        if opened != challenge.triples || evidence.triples.len() != opened.len() {
+            return Err(PreprocessingError::IncompleteEvidence(MaterialKind::Triple));
+        }
+        let opened: BTreeSet<usize> = evidence.bits.iter().map(|(index, ..)| *index).collect();
+        if opened != challenge.bits || evidence.bits.len() != opened.len() {
+            return Err(PreprocessingError::IncompleteEvidence(MaterialKind::Bit));
+        }
+
+        for (index, triple, salt) in &evidence.triples {
+            let kind = MaterialKind::Triple;
+            let expected = self.triples.get(*index).ok_or(PreprocessingError::CommitmentMismatch { kind, index: *index })?;
+            if item_commitment(kind, *index, &[&triple.a, &triple.b, &triple.c], salt) != *expected {
+                return Err(PreprocessingError::CommitmentMismatch { kind, index: *index });
+            }
+            let secrets = [&triple.a, &triple.b, &triple.c].map(|sharing| consistent_secret(sharing, self.config));
+            match secrets {
+                [Some(a), Some(b), Some(c)] if a * b == c => {}
+                _ => return Err(PreprocessingError::MalformedMaterial { kind, index: *index }),
+            }
+        }
+        for (index, bit, salt) in &evidence.bits {
+            let kind = MaterialKind::Bit;
+            let expected = self.bits.get(*index).ok_or(PreprocessingError::CommitmentMismatch { kind, index: *index })?;
+            if item_commitment(kind, *index, &[bit], salt) != *expected {
+                return Err(PreprocessingError::CommitmentMismatch { kind, index: *index });
+            }
+            if !consistent_secret(bit, self.config).is_some_and(|b| b.is_zero() || b.is_one()) {
+                return Err(PreprocessingError::MalformedMaterial { kind, index: *index });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Probability that none of `corrupted` malformed items among `total` is
+/// among `opened` items chosen uniformly
+pub fn escape_probability(total: usize, opened: usize, corrupted: usize) -> f64 {
+    if corrupted + opened > total {
+        return 0.0;
+    }
+    (0..opened).map(|i| (total - corrupted - i) as f64 / (total - i) as f64).product()
+}
+
+impl<F: PrimeField> ThresholdPreprocessing<F, ShamirSecretSharing<F>> {
+    /// Add a worker-generated pool once its cut-and-choose evidence checks out
+    ///
+    /// `pool` must hold exactly the unopened items; a pool of the wrong size
+    /// is refused along with invalid evidence.
+    pub fn add_verified_pool(
+        &mut self,
+        commitment: &PreprocessingCommitment,
+        challenge: &CutAndChooseChallenge,
+        evidence: &CutAndChooseEvidence<F>,
+        pool: PreprocessingStore<F, ShamirSecretSharing<F>>,
+    ) -> Result<(), PreprocessingError> {
+        commitment.verify(challenge, evidence)?;
+        if pool.triples.len() != commitment.triples.len() - challenge.triples.len() {
+            return Err(PreprocessingError::IncompleteEvidence(MaterialKind::Triple));
+        }
+        if pool.bits.len() != commitment.bits.len() - challenge.bits.len() {
+            return Err(PreprocessingError::IncompleteEvidence(MaterialKind::Bit));
+        }
+        self.add_pool(commitment.config, pool);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::Fr;
+    use ark_std::test_rng;
+
+    type TestField = Fr;
+
+    #[test]
+    fn test_cut_and_choose_catches_malformed_material() {
+        let mut rng = test_rng();
+        let config = ThresholdConfig::new(2, 4);
+
+        // An honest worker passes and keeps the unopened items
+        let (dealing, commitment) = PreprocessingDealing::<TestField>::generate(config, 10, 6, &mut rng);
+        let challenge = commitment.challenge(4, 2, &mut rng);
+        let (evidence, pool) = dealing.respond(&challenge);
+        let mut material = ThresholdPreprocessing::default();
+        material.add_verified_pool(&commitment, &challenge, &evidence, pool).unwrap();
+        material.allocate(1, config, 6, 4).unwrap();
+        let triple = material.next_triple(1).unwrap().clone();
+        let [a, b, c] = [&triple.a, &triple.b, &triple.c].map(|s| ShamirSecretSharing::reconstruct_secret(&s[1..3]).unwrap());
+        assert_eq!(a * b, c);
+
+        // A triple with c != a * b is caught when opened
+        let (mut dealing, _) = PreprocessingDealing::<TestField>::generate(config, 3, 0, &mut rng);
+        for share in &mut dealing.triples[1].0.c {
+            share.value += TestField::from(1u64);
+        }
+        let commitment = dealing.commitment();
+        let challenge = CutAndChooseChallenge { triples: BTreeSet::from([1]), bits: BTreeSet::new() };
+        let (evidence, _) = dealing.respond(&challenge);
+        assert_eq!(
+            commitment.verify(&challenge, &evidence),
+            Err(PreprocessingError::MalformedMaterial { kind: MaterialKind::Triple, index: 1 })
+        );
+
+        // So is a bit sharing of too high a degree, or a swapped opening
+        let (mut dealing, _) = PreprocessingDealing::<TestField>::generate(config, 0, 2, &mut rng);
+        dealing.bits[0].0[3].value += TestField::from(1u64);
+        let commitment = dealing.commitment();
+        let challenge = CutAndChooseChallenge { triples: BTreeSet::new(), bits: BTreeSet::from([0, 1]) };
+        let (mut evidence, _) = dealing.respond(&challenge);
+        assert_eq!(
+            commitment.verify(&challenge, &evidence),
+            Err(PreprocessingError::MalformedMaterial { kind: MaterialKind::Bit, index: 0 })
+        );
+        evidence.bits.swap(0, 1);
+        evidence.bits[0].1 = evidence.bits[1].1.clone();
+        assert!(matches!(commitment.verify(&challenge, &evidence), Err(PreprocessingError::CommitmentMismatch { .. })));
+        evidence.bits.pop();
+        assert_eq!(
+            commitment.verify(&challenge, &evidence),
+            Err(PreprocessingError::IncompleteEvidence(MaterialKind::Bit))
+        );
+
+        assert_eq!(escape_probability(10, 5, 6), 0.0);
+        assert!((escape_probability(20, 10, 1) - 0.5).abs() < 1e-12);
+    }
+}
@@ -1,7 +1,14 @@
-use ark_ff::{Field, PrimeField, Zero};
+use ark_ff::{Field, PrimeField};
 use ark_poly::{DenseUVPolynomial, Polynomial, univariate::DensePolynomial};
-use ark_std::{vec::Vec, collections::HashMap};
-use crate::circuit::pc_schemes::{KZGCommitmentScheme, PolynomialCommitment, OpeningProof};
+use ark_std::{vec::Vec, collections::BTreeMap};
+use crate::circuit::pc_schemes::{KZGCommitmentScheme, MultilinearOpeningProof, PolynomialCommitment, OpeningProof};
+use crate::piop::{
+    evaluate_multilinear, prove_product_sumcheck, verify_product_sumcheck, zero_padded, Domain, ProductSumcheckProof,
+    SumcheckMask, Transcript,
+};
+
+/// 单独使用检查器时，求和检查挑战所用转录的域标签
+pub const CONSISTENCY_TRANSCRIPT_DOMAIN: &[u8] = b"EOS-consistency-sumcheck-v1";
 
 /// PIOP 一致性检查器
 /// 负责验证多项式交互式 Oracle 证明的一致性
@@ -10,11 +17,11 @@ pub struct ConsistencyChecker<F: PrimeField> {
     /// 约束数量
     pub num_constraints: usize,
     /// 多项式承诺方案
-    pub commitment_scheme: Option<KZGCommitmentScheme<F, ark_ec::short_weierstrass::Projective<ark_bls12_381::g1::Config>>>,
+    pub commitment_scheme: Option<KZGCommitmentScheme<ark_bls12_381::Bls12_381>>,
     /// 见证多项式
-    pub witness_polynomials: HashMap<String, DensePolynomial<F>>,
+    pub witness_polynomials: BTreeMap<String, DensePolynomial<F>>,
     /// 公开输入多项式
-    pub public_polynomials: HashMap<String, DensePolynomial<F>>,
+    pub public_polynomials: BTreeMap<String, DensePolynomial<F>>,
 }
 
 /// 一致性检查结果
@@ -34,9 +41,37 @@ pub struct PolynomialConsistencyProof<F: Field, G: ark_ec::CurveGroup> {
     pub consistency_proofs: Vec<OpeningProof<F, G>>,
     /// 求和检查证明
     pub sumcheck_proofs: Vec<SumcheckProof<F>>,
+    /// R1CS 约束证明末端的断言；只承诺见证、不证明约束的证明没有这一项
+    pub constraint_claims: Option<ConstraintClaims<F, G>>,
+}
+
+/// R1CS 约束证明末端的断言
+///
+/// 委托证明的 `sumcheck_proofs` 依次是 Az∘Bz − Cz 的零检查和把行取值归约到
+/// 见证的线性求和检查，这里是两者留下的断言。
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConstraintClaims<F: Field, G: ark_ec::CurveGroup> {
+    /// ã(ρ)、b̃(ρ)、c̃(ρ)，ρ 为零检查的末点
+    pub row_evaluations: [F; 3],
+    /// 见证多项式的系数向量在线性求和检查末点处的多线性打开
+    pub witness_opening: MultilinearOpeningProof<F, G>,
+    /// zk 模式下零检查与线性求和检查各自的掩码，依次排列；否则为空
+    pub masks: Vec<MaskOpening<F, G>>,
+}
+
+/// 隐藏求和检查的掩码 g：隐藏承诺与和，以及 g 的系数向量在末点处的多线性打开
+///
+/// 盲化系数在 g 的求值表之上，打开点在末点前补 0，取到的正是 g 的多线性扩展。
+#[derive(Debug, Clone, PartialEq)]
+pub struct MaskOpening<F: Field, G: ark_ec::CurveGroup> {
+    pub mask: SumcheckMask<F, PolynomialCommitment<G>>,
+    pub opening: MultilinearOpeningProof<F, G>,
 }
 
 /// 求和检查证明
+///
+/// 验证者自己由转录导出每轮挑战，`challenges` 只是证明者给出的副本，
+/// 审计模式检查两者一致。
 #[derive(Debug, Clone)]
 pub struct SumcheckProof<F: Field> {
     /// 每轮的多项式
@@ -47,21 +82,60 @@ pub struct SumcheckProof<F: Field> {
     pub final_evaluation: F,
 }
 
+impl<F: PrimeField> SumcheckProof<F> {
+    /// 由乘积求和检查的证明构造：每轮在 0, 1, ..., d 处的值换成系数形式
+    pub fn from_product(proof: &ProductSumcheckProof<F>, challenges: Vec<F>, final_evaluation: F) -> Self {
+        Self {
+            round_polynomials: proof.rounds.iter().map(|evaluations| interpolate_nodes(evaluations)).collect(),
+            challenges,
+            final_evaluation,
+        }
+    }
+
+    /// 还原每轮在 0, 1, ..., degree 处的值；有一轮的次数超过 `degree` 时返回 None
+    pub fn to_product(&self, degree: usize) -> Option<ProductSumcheckProof<F>> {
+        let rounds = self
+            .round_polynomials
+            .iter()
+            .map(|polynomial| {
+                (polynomial.coeffs.len() <= degree + 1)
+                    .then(|| (0..=degree).map(|t| polynomial.evaluate(&F::from(t as u64))).collect())
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some(ProductSumcheckProof { rounds })
+    }
+}
+
+/// 在 0, 1, ..., d 处取给定值的次数不超过 d 的多项式
+fn interpolate_nodes<F: PrimeField>(evaluations: &[F]) -> DensePolynomial<F> {
+    let node = |i: usize| F::from(i as u64);
+    let mut result = DensePolynomial::from_coefficients_vec(vec![]);
+    for (i, value) in evaluations.iter().enumerate() {
+        let mut basis = DensePolynomial::from_coefficients_vec(vec![*value]);
+        for j in (0..evaluations.len()).filter(|j| *j != i) {
+            let scale = (node(i) - node(j)).inverse().expect("interpolation nodes are distinct");
+            basis = &basis * &DensePolynomial::from_coefficients_vec(vec![-node(j) * scale, scale]);
+        }
+        result = &result + &basis;
+    }
+    result
+}
+
 impl<F: PrimeField> ConsistencyChecker<F> {
     /// 创建新的一致性检查器
     pub fn new() -> Self {
         Self {
             num_constraints: 0,
             commitment_scheme: None,
-            witness_polynomials: HashMap::new(),
-            public_polynomials: HashMap::new(),
+            witness_polynomials: BTreeMap::new(),
+            public_polynomials: BTreeMap::new(),
         }
     }
     
     /// 设置多项式承诺方案
     pub fn set_commitment_scheme(
         &mut self, 
-        scheme: KZGCommitmentScheme<F, ark_ec::short_weierstrass::Projective<ark_bls12_381::g1::Config>>
+        scheme: KZGCommitmentScheme<ark_bls12_381::Bls12_381>
     ) {
         self.commitment_scheme = Some(scheme);
     }
@@ -133,18 +207,30 @@ impl<F: PrimeField> ConsistencyChecker<F> {
     /// 生成一致性证明
     pub fn generate_consistency_proof(
         &self,
+    ) -> Result<PolynomialConsistencyProof<F, ark_ec::short_weierstrass::Projective<ark_bls12_381::g1::Config>>, &'static str> {
+        self.generate_consistency_proof_in(&mut Transcript::new(CONSISTENCY_TRANSCRIPT_DOMAIN))
+    }
+
+    /// 生成一致性证明，求和检查的挑战由 `transcript` 按 Fiat–Shamir 导出
+    ///
+    /// 每个见证多项式一个求和检查，证明其系数之和；验证须用
+    /// `verify_consistency_proof_in` 并传入状态相同的转录。
+    pub fn generate_consistency_proof_in(
+        &self,
+        transcript: &mut Transcript,
     ) -> Result<PolynomialConsistencyProof<F, ark_ec::short_weierstrass::Projective<ark_bls12_381::g1::Config>>, &'static str> {
         // 简化的一致性证明生成
         let witness_commitments = Vec::new();
         let consistency_proofs = Vec::new();
         
         // 生成求和检查证明
-        let sumcheck_proofs = self.generate_sumcheck_proofs()?;
+        let sumcheck_proofs = self.generate_sumcheck_proofs(transcript)?;
         
         Ok(PolynomialConsistencyProof {
             witness_commitments,
             consistency_proofs,
             sumcheck_proofs,
+            constraint_claims: None,
         })
     }
     
@@ -153,15 +239,57 @@ impl<F: PrimeField> ConsistencyChecker<F> {
         &self,
         proof: &PolynomialConsistencyProof<F, ark_ec::short_weierstrass::Projective<ark_bls12_381::g1::Config>>,
     ) -> bool {
-        // 简化的一致性证明验证
-        // 验证求和检查证明
-        for sumcheck_proof in &proof.sumcheck_proofs {
-            if !self.verify_sumcheck_proof(sumcheck_proof) {
+        self.verify_consistency_proof_in(proof, &mut Transcript::new(CONSISTENCY_TRANSCRIPT_DOMAIN))
+    }
+
+    /// 在给定转录上验证一致性证明
+    ///
+    /// 每个求和检查的挑战由转录重新导出，断言对照检查器持有的见证多项式
+    /// 检查；证明中携带的挑战不参与验证。
+    pub fn verify_consistency_proof_in(
+        &self,
+        proof: &PolynomialConsistencyProof<F, ark_ec::short_weierstrass::Projective<ark_bls12_381::g1::Config>>,
+        transcript: &mut Transcript,
+    ) -> bool {
+        self.check_sumcheck_proofs(proof, transcript, false)
+    }
+
+    /// 审计模式验证：在 `verify_consistency_proof_in` 之外，要求证明中携带的
+    /// 挑战与转录导出的挑战逐一相同
+    ///
+    /// `transcript` 须与证明者生成时的状态相同。
+    pub fn audit_consistency_proof(
+        &self,
+        proof: &PolynomialConsistencyProof<F, ark_ec::short_weierstrass::Projective<ark_bls12_381::g1::Config>>,
+        transcript: &mut Transcript,
+    ) -> bool {
+        self.check_sumcheck_proofs(proof, transcript, true)
+    }
+
+    fn check_sumcheck_proofs(
+        &self,
+        proof: &PolynomialConsistencyProof<F, ark_ec::short_weierstrass::Projective<ark_bls12_381::g1::Config>>,
+        transcript: &mut Transcript,
+        audit: bool,
+    ) -> bool {
+        if proof.sumcheck_proofs.len() != self.witness_polynomials.len() {
+            return false;
+        }
+        self.witness_polynomials.values().zip(&proof.sumcheck_proofs).all(|(polynomial, sumcheck)| {
+            let table = zero_padded(&polynomial.coeffs);
+            let num_vars = Domain::for_len(table.len()).num_vars();
+            let Some(rounds) = sumcheck.to_product(1) else {
                 return false;
+            };
+            let claim = table.iter().copied().sum();
+            match verify_product_sumcheck(num_vars, 1, claim, &rounds, transcript) {
+                Ok(subclaim) => {
+                    (!audit || subclaim.point == sumcheck.challenges)
+                        && evaluate_multilinear(&table, &subclaim.point) == subclaim.expected_evaluation
+                }
+                Err(_) => false,
             }
-        }
-        
-        true
+        })
     }
     
     /// 执行批量一致性检查
@@ -228,65 +356,28 @@ impl<F: PrimeField> ConsistencyChecker<F> {
     }
     
     /// 生成求和检查证明
-    fn generate_sumcheck_proofs(&self) -> Result<Vec<SumcheckProof<F>>, &'static str> {
+    fn generate_sumcheck_proofs(&self, transcript: &mut Transcript) -> Result<Vec<SumcheckProof<F>>, &'static str> {
         let mut proofs = Vec::new();
         
         // 为每个多项式生成求和检查证明
-        for (_name, poly) in &self.witness_polynomials {
-            let proof = self.generate_single_sumcheck_proof(poly)?;
+        for poly in self.witness_polynomials.values() {
+            let proof = self.generate_single_sumcheck_proof(poly, transcript)?;
             proofs.push(proof);
         }
         
         Ok(proofs)
     }
     
-    /// 生成单个求和检查证明
-    fn generate_single_sumcheck_proof(&self, polynomial: &DensePolynomial<F>) -> Result<SumcheckProof<F>, &'static str> {
-        let mut round_polynomials = Vec::new();
-        let mut challenges = Vec::new();
-        
-        // 简化的求和检查协议
-        let num_rounds = 3; // 假设 3 轮
-        
-        for round in 0..num_rounds {
-            // 生成当前轮的多项式（简化版本）
-            let round_poly = DensePolynomial::from_coefficients_vec(vec![
-                F::from((round + 1) as u64),
-                F::from((round + 2) as u64),
-            ]);
-            round_polynomials.push(round_poly);
-            
-            // 生成挑战（在实际实现中应该由验证者提供）
-            let challenge = F::from((round * 13 + 7) as u64);
-            challenges.push(challenge);
-        }
-        
-        let final_evaluation = polynomial.evaluate(&F::from(42u64));
-        
-        Ok(SumcheckProof {
-            round_polynomials,
-            challenges,
-            final_evaluation,
-        })
-    }
-    
-    /// 验证求和检查证明
-    fn verify_sumcheck_proof(&self, proof: &SumcheckProof<F>) -> bool {
-        // 简化的求和检查验证
-        if proof.round_polynomials.len() != proof.challenges.len() {
-            return false;
-        }
-        
-        // 检查每轮的一致性
-        for (poly, challenge) in proof.round_polynomials.iter().zip(proof.challenges.iter()) {
-            let evaluation = poly.evaluate(challenge);
-            // 在实际实现中，这里需要更复杂的验证逻辑
-            if evaluation.is_zero() && !challenge.is_zero() {
-                return false;
-            }
-        }
-        
-        true
+    /// 生成单个求和检查证明：把系数向量补零到 2 的幂，证明其各项之和
+    fn generate_single_sumcheck_proof(
+        &self,
+        polynomial: &DensePolynomial<F>,
+        transcript: &mut Transcript,
+    ) -> Result<SumcheckProof<F>, &'static str> {
+        let table = zero_padded(&polynomial.coeffs);
+        let num_vars = Domain::for_len(table.len()).num_vars();
+        let output = prove_product_sumcheck(num_vars, vec![table], transcript).map_err(|_| "求和检查的表长不是 2 的幂")?;
+        Ok(SumcheckProof::from_product(&output.proof, output.point, output.factors[0]))
     }
     
     /// 检查交互式一致性
@@ -0,0 +1,331 @@
+use ark_bls12_381::Fr as BlsFr;
+use ark_ff::Field;
+use ark_r1cs_std::fields::{fp::FpVar, FieldVar};
+use ark_relations::r1cs::SynthesisError;
+use blake2::Blake2s256;
+use sha2::{Digest, Sha256};
+use sha3::{Keccak256, Sha3_256};
+
+use crate::serialization::FieldEncoding;
+
+/// 转录和 Merkle 树使用的哈希函数，按部署选择
+/// 链上验证者通常偏好 Keccak 或 Poseidon，默认仍为 SHA-256
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TranscriptHash {
+    #[default]
+    Sha256,
+    Blake2s,
+    Sha3,
+    Keccak,
+    /// BLS12-381 标量域上的 Poseidon 海绵
+    Poseidon,
+}
+
+impl TranscriptHash {
+    pub const ALL: [TranscriptHash; 5] = [
+        TranscriptHash::Sha256,
+        TranscriptHash::Blake2s,
+        TranscriptHash::Sha3,
+        TranscriptHash::Keccak,
+        TranscriptHash::Poseidon,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            TranscriptHash::Sha256 => "sha256",
+            TranscriptHash::Blake2s => "blake2s",
+            TranscriptHash::Sha3 => "sha3",
+            TranscriptHash::Keccak => "keccak",
+            TranscriptHash::Poseidon => "poseidon",
+        }
+    }
+
+    /// 编码中使用的单字节标签
+    pub fn tag(self) -> u8 {
+        self as u8
+    }
+
+    pub fn from_tag(tag: u8) -> Option<Self> {
+        Self::ALL.into_iter().find(|hash| hash.tag() == tag)
+    }
+
+    /// 创建增量哈希状态
+    pub fn hasher(self) -> HashState {
+        match self {
+            TranscriptHash::Sha256 => HashState::Sha256(Sha256::new()),
+            TranscriptHash::Blake2s => HashState::Blake2s(Blake2s256::new()),
+            TranscriptHash::Sha3 => HashState::Sha3(Sha3_256::new()),
+            TranscriptHash::Keccak => HashState::Keccak(Keccak256::new()),
+            TranscriptHash::Poseidon => HashState::Poseidon(PoseidonSponge::new()),
+        }
+    }
+
+    /// 一次性哈希
+    pub fn digest(self, data: &[u8]) -> [u8; 32] {
+        let mut state = self.hasher();
+        state.update(data);
+        state.finalize()
+    }
+
+    /// Merkle 树的双子节点压缩
+    pub fn compress(self, left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+        let mut state = self.hasher();
+        state.update(left);
+        state.update(right);
+        state.finalize()
+    }
+}
+
+impl std::str::FromStr for TranscriptHash {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        TranscriptHash::ALL
+            .into_iter()
+            .find(|hash| hash.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("unknown transcript hash '{}'", s))
+    }
+}
+
+/// 增量哈希状态，输出统一为 32 字节
+#[derive(Clone)]
+pub enum HashState {
+    Sha256(Sha256),
+    Blake2s(Blake2s256),
+    Sha3(Sha3_256),
+    Keccak(Keccak256),
+    Poseidon(PoseidonSponge),
+}
+
+impl HashState {
+    pub fn update(&mut self, data: impl AsRef<[u8]>) {
+        let data = data.as_ref();
+        match self {
+            HashState::Sha256(h) => h.update(data),
+            HashState::Blake2s(h) => h.update(data),
+            HashState::Sha3(h) => h.update(data),
+            HashState::Keccak(h) => h.update(data),
+            HashState::Poseidon(h) => h.absorb_bytes(data),
+        }
+    }
+
+    pub fn finalize(self) -> [u8; 32] {
+        match self {
+            HashState::Sha256(h) => h.finalize().into(),
+            HashState::Blake2s(h) => h.finalize().into(),
+            HashState::Sha3(h) => h.finalize().into(),
+            HashState::Keccak(h) => h.finalize().into(),
+            HashState::Poseidon(h) => {
+                let mut out = [0u8; 32];
+                out.copy_from_slice(&FieldEncoding::wire::<BlsFr>().encode(&h.squeeze()));
+                out
+            }
+        }
+    }
+}
+
+/// Poseidon 状态宽度（容量 1，速率 2）
+pub(crate) const POSEIDON_WIDTH: usize = 3;
+pub(crate) const POSEIDON_RATE: usize = 2;
+const POSEIDON_FULL_ROUNDS: usize = 8;
+const POSEIDON_PARTIAL_ROUNDS: usize = 57;
+/// 每个域元素装入的字节数，保证小于模数
+pub(crate) const BYTES_PER_ELEMENT: usize = 31;
+
+/// BLS12-381 标量域上的 Poseidon 海绵（x^5 S 盒，8 个完全轮，57 个部分轮）
+/// 轮常数由 SHA-256 派生，MDS 为 Cauchy 矩阵；与 MiMC 一样是演示参数集，未经审计
+#[derive(Clone)]
+pub struct PoseidonSponge {
+    state: [BlsFr; POSEIDON_WIDTH],
+    /// 当前块中已吸收的域元素个数
+    absorbed: usize,
+    /// 尚未凑满一个域元素的字节
+    pending: Vec<u8>,
+    /// 吸收的总字节数，在输出前吸收以区分填充
+    total_bytes: u64,
+}
+
+impl Default for PoseidonSponge {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PoseidonSponge {
+    pub fn new() -> Self {
+        Self {
+            state: [BlsFr::from(0u64); POSEIDON_WIDTH],
+            absorbed: 0,
+            pending: Vec::new(),
+            total_bytes: 0,
+        }
+    }
+
+    /// 吸收域元素
+    pub fn absorb(&mut self, element: BlsFr) {
+        if self.absorbed == POSEIDON_RATE {
+            poseidon_permutation(&mut self.state);
+            self.absorbed = 0;
+        }
+        self.state[self.absorbed] += element;
+        self.absorbed += 1;
+    }
+
+    /// 吸收字节，每 31 字节装成一个域元素
+    pub fn absorb_bytes(&mut self, data: &[u8]) {
+        self.total_bytes += data.len() as u64;
+        self.pending.extend_from_slice(data);
+        let full = self.pending.len() / BYTES_PER_ELEMENT * BYTES_PER_ELEMENT;
+        let chunks: Vec<BlsFr> = self.pending[..full]
+            .chunks(BYTES_PER_ELEMENT)
+            .map(|chunk| FieldEncoding::wire::<BlsFr>().reduce(chunk))
+            .collect();
+        for chunk in chunks {
+            self.absorb(chunk);
+        }
+        self.pending.drain(..full);
+    }
+
+    /// 填充并输出一个域元素
+    pub fn squeeze(mut self) -> BlsFr {
+        if !self.pending.is_empty() {
+            let rest = FieldEncoding::wire::<BlsFr>().reduce(&self.pending);
+            self.absorb(rest);
+        }
+        self.absorb(BlsFr::from(self.total_bytes));
+        poseidon_permutation(&mut self.state);
+        self.state[0]
+    }
+
+    /// 直接哈希域元素序列（电路友好的用法）
+    pub fn hash_fields(inputs: &[BlsFr]) -> BlsFr {
+        let mut sponge = Self::new();
+        for input in inputs {
+            sponge.absorb(*input);
+        }
+        sponge.absorb(BlsFr::from(inputs.len() as u64));
+        poseidon_permutation(&mut sponge.state);
+        sponge.state[0]
+    }
+}
+
+struct PoseidonParameters {
+    round_constants: Vec<[BlsFr; POSEIDON_WIDTH]>,
+    mds: [[BlsFr; POSEIDON_WIDTH]; POSEIDON_WIDTH],
+}
+
+fn poseidon_parameters() -> &'static PoseidonParameters {
+    static PARAMETERS: std::sync::OnceLock<PoseidonParameters> = std::sync::OnceLock::new();
+    PARAMETERS.get_or_init(|| {
+        let constant = |round: usize, i: usize| {
+            let digest = Sha256::new()
+                .chain_update(b"EOS-poseidon-constants")
+                .chain_update((round as u64).to_le_bytes())
+                .chain_update((i as u64).to_le_bytes())
+                .finalize();
+            FieldEncoding::wire::<BlsFr>().reduce(&digest)
+        };
+        let round_constants = (0..POSEIDON_FULL_ROUNDS + POSEIDON_PARTIAL_ROUNDS)
+            .map(|round| std::array::from_fn(|i| constant(round, i)))
+            .collect();
+        // Cauchy 矩阵 1 / (x_i + y_j)，x_i = i，y_j = WIDTH + j，分母均非零
+        let mds = std::array::from_fn(|i| {
+            std::array::from_fn(|j| {
+                BlsFr::from((i + POSEIDON_WIDTH + j) as u64)
+                    .inverse()
+                    .expect("Cauchy denominators are non-zero")
+            })
+        });
+        PoseidonParameters { round_constants, mds }
+    })
+}
+
+/// Poseidon 置换：前后各一半完全轮，中间为部分轮
+fn poseidon_permutation(state: &mut [BlsFr; POSEIDON_WIDTH]) {
+    let parameters = poseidon_parameters();
+    let half_full = POSEIDON_FULL_ROUNDS / 2;
+    for (round, constants) in parameters.round_constants.iter().enumerate() {
+        for (value, constant) in state.iter_mut().zip(constants) {
+            *value += constant;
+        }
+        let full = round < half_full || round >= half_full + POSEIDON_PARTIAL_ROUNDS;
+        let sbox = |x: BlsFr| x.square().square() * x;
+        if full {
+            for value in state.iter_mut() {
+                *value = sbox(*value);
+            }
+        } else {
+            state[0] = sbox(state[0]);
+        }
+        let previous = *state;
+        for (i, value) in state.iter_mut().enumerate() {
+            *value = parameters.mds[i]
+                .iter()
+                .zip(&previous)
+                .map(|(m, x)| *m * x)
+                .sum();
+        }
+    }
+}
+
+/// `poseidon_permutation` 的约束版本，逐轮对应，每个 S 盒 3 个约束
+pub(crate) fn poseidon_permutation_var(state: &mut [FpVar<BlsFr>; POSEIDON_WIDTH]) -> Result<(), SynthesisError> {
+    let parameters = poseidon_parameters();
+    let half_full = POSEIDON_FULL_ROUNDS / 2;
+    let sbox = |x: &FpVar<BlsFr>| -> Result<FpVar<BlsFr>, SynthesisError> { Ok(x.square()?.square()? * x) };
+    for (round, constants) in parameters.round_constants.iter().enumerate() {
+        for (value, constant) in state.iter_mut().zip(constants) {
+            *value += *constant;
+        }
+        let full = round < half_full || round >= half_full + POSEIDON_PARTIAL_ROUNDS;
+        if full {
+            for value in state.iter_mut() {
+                *value = sbox(value)?;
+            }
+        } else {
+            state[0] = sbox(&state[0])?;
+        }
+        let previous = state.clone();
+        for (i, value) in state.iter_mut().enumerate() {
+            *value = parameters.mds[i]
+                .iter()
+                .zip(&previous)
+                .map(|(m, x)| x * *m)
+                .fold(FpVar::zero(), |sum, term| sum + term);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hashes_are_distinct_and_incremental() {
+        let data = vec![7u8; 100];
+        let digests: Vec<[u8; 32]> = TranscriptHash::ALL.iter().map(|hash| hash.digest(&data)).collect();
+        for (i, a) in digests.iter().enumerate() {
+            for b in &digests[i + 1..] {
+                assert_ne!(a, b);
+            }
+        }
+
+        // 分块吸收与一次性哈希一致
+        for hash in TranscriptHash::ALL {
+            let mut state = hash.hasher();
+            state.update(&data[..45]);
+            state.update(&data[45..]);
+            assert_eq!(state.finalize(), hash.digest(&data));
+        }
+
+        // Poseidon 的填充区分末尾的零字节
+        let poseidon = TranscriptHash::Poseidon;
+        assert_ne!(poseidon.digest(&[1]), poseidon.digest(&[1, 0]));
+        assert_ne!(poseidon.digest(&[]), poseidon.digest(&[0]));
+
+        assert_eq!("Keccak".parse::<TranscriptHash>().unwrap(), TranscriptHash::Keccak);
+        assert_eq!(TranscriptHash::from_tag(TranscriptHash::Poseidon.tag()), Some(TranscriptHash::Poseidon));
+        assert!(TranscriptHash::from_tag(9).is_none());
+    }
+}
@@ -0,0 +1,317 @@
+//! 零知识求和检查（掩码多项式变体）
+//!
+//! 普通求和检查的每轮消息是 f 在部分固定变量后的部分和，对见证导出的 f
+//! 会把这些部分和泄露给验证者。隐藏变体中证明者先取一个随机多线性掩码 g，
+//! 承诺 g 并公布 G = Σ_x g(x)，两者吸收进转录后验证者才给出随机数 ρ，双方
+//! 对 f + ρ·g 运行和为 H + ρ·G 的求和检查。g 在 ρ 之前已被承诺，证明者
+//! 无法事后调整 g 去抵消一个错误的 H。g 的求值表均匀随机，每轮消息只反映
+//! f + ρ·g 的部分和，对验证者是随机的；末轮断言由 f 和 g 的 oracle（或
+//! 承诺的打开）各查询一次检查，f 在随机点处的值由承诺方案负责隐藏。
+//!
+//! `prove_hiding_sumcheck` 是单表变体，由 `SumcheckParameters::zk` 选择，与
+//! `EosConfig::zk` 一致；`prove_hiding_sum_of_products` 是乘积求和检查的
+//! 变体，委托协议在 zk 模式下用它掩盖约束证明的两个求和检查。g 的承诺由
+//! 调用方给出，委托协议用 `KZGCommitmentScheme::commit_hiding`。
+
+use ark_ff::{Field, PrimeField};
+use ark_std::rand::Rng;
+use ark_std::vec::Vec;
+
+use crate::piop::sumcheck::{absorb_statement, check_proof_shape, check_table_size, prove_rounds, verify_rounds};
+use crate::piop::{
+    prove_sum_of_products, verify_product_sumcheck, ExtensionSumcheckProof, Oracle, OracleError, ProductSumcheckOutput,
+    ProductSumcheckProof, QueriedOracle, SumcheckError, SumcheckParameters, Transcript,
+};
+use crate::serialization::WireFormat;
+
+/// 掩码 g 的承诺与和 G，在掩码权重 ρ 之前吸收进转录
+#[derive(Debug, Clone, PartialEq)]
+pub struct SumcheckMask<F: Field, C> {
+    pub commitment: C,
+    pub sum: F,
+}
+
+/// 隐藏求和检查证明
+#[derive(Debug, Clone, PartialEq)]
+pub struct HidingSumcheckProof<F: Field, K: Field, C> {
+    pub mask: SumcheckMask<F, C>,
+    /// f + ρ·g 的求和检查
+    pub sumcheck: ExtensionSumcheckProof<K>,
+}
+
+/// 证明者输出；`mask` 是 g 的求值表，末轮须按 `proof.mask.commitment` 打开
+#[derive(Debug, Clone, PartialEq)]
+pub struct HidingSumcheckOutput<F: Field, K: Field, C> {
+    pub proof: HidingSumcheckProof<F, K, C>,
+    pub mask: Vec<F>,
+}
+
+/// 隐藏乘积求和检查的证明者输出
+#[derive(Debug, Clone, PartialEq)]
+pub struct HidingProductSumcheckOutput<K: Field, C> {
+    pub mask: SumcheckMask<K, C>,
+    /// g 的求值表
+    pub mask_table: Vec<K>,
+    /// f + ρ·g 的求和检查；`factors` 只含 f 各项的因子
+    pub output: ProductSumcheckOutput<K>,
+    pub mask_weight: K,
+    /// g 在末点处的值
+    pub mask_evaluation: K,
+}
+
+/// 末轮断言：f(point) + mask_weight·g(point) = expected_evaluation
+#[derive(Debug, Clone, PartialEq)]
+pub struct HidingSumcheckSubclaim<K: Field> {
+    pub point: Vec<K>,
+    pub mask_weight: K,
+    pub expected_evaluation: K,
+}
+
+impl<K: Field> HidingSumcheckSubclaim<K> {
+    /// 向 f 和掩码 g 的 oracle 各查询一次，检查断言是否成立
+    pub fn check<O, M>(&self, f: &mut QueriedOracle<K, O>, mask: &mut QueriedOracle<K, M>) -> Result<bool, OracleError>
+    where
+        O: Oracle<K, Point = Vec<K>>,
+        M: Oracle<K, Point = Vec<K>>,
+    {
+        Ok(f.query(&self.point)? + self.mask_weight * mask.query(&self.point)? == self.expected_evaluation)
+    }
+}
+
+/// 吸收掩码的承诺与和，导出掩码权重 ρ
+fn mask_weight<F: Field, K: Field, C: WireFormat>(mask: &SumcheckMask<F, C>, transcript: &mut Transcript) -> K {
+    transcript.append_message(b"hiding_sumcheck_mask_commitment", &mask.commitment.to_bytes());
+    transcript.append_field(b"hiding_sumcheck_mask_sum", &mask.sum);
+    transcript.challenge_element(b"hiding_sumcheck_mask_weight")
+}
+
+/// 抽取长为 `len` 的掩码并由 `commit_mask` 承诺
+fn sample_mask<F: Field, C>(len: usize, commit_mask: impl FnOnce(&[F]) -> C, rng: &mut impl Rng) -> (Vec<F>, SumcheckMask<F, C>) {
+    let table: Vec<F> = (0..len).map(|_| F::rand(rng)).collect();
+    let mask = SumcheckMask { commitment: commit_mask(&table), sum: table.iter().copied().sum() };
+    (table, mask)
+}
+
+/// 生成隐藏求和检查证明，掩码由 `rng` 抽取、由 `commit_mask` 承诺
+pub fn prove_hiding_sumcheck<F: PrimeField, K: Field<BasePrimeField = F>, C: WireFormat>(
+    parameters: &SumcheckParameters,
+    table: &[F],
+    commit_mask: impl FnOnce(&[F]) -> C,
+    transcript: &mut Transcript,
+    rng: &mut impl Rng,
+) -> Result<HidingSumcheckOutput<F, K, C>, SumcheckError> {
+    if !parameters.zk {
+        return Err(SumcheckError::ZkMismatch);
+    }
+    check_table_size(parameters, table)?;
+    let (mask_table, mask) = sample_mask(table.len(), commit_mask, rng);
+    absorb_statement(transcript, parameters, table.iter().copied().sum::<F>());
+    let weight: K = mask_weight(&mask, transcript);
+    let combined: Vec<K> = table
+        .iter()
+        .zip(&mask_table)
+        .map(|(f, g)| K::from_base_prime_field(*f) + weight * K::from_base_prime_field(*g))
+        .collect();
+    let rounds = prove_rounds(parameters, &combined, transcript);
+    Ok(HidingSumcheckOutput {
+        proof: HidingSumcheckProof { mask, sumcheck: ExtensionSumcheckProof { rounds } },
+        mask: mask_table,
+    })
+}
+
+/// 验证隐藏求和检查，返回每次重复留下的断言
+pub fn verify_hiding_sumcheck<F: PrimeField, K: Field<BasePrimeField = F>, C: WireFormat>(
+    parameters: &SumcheckParameters,
+    claim: F,
+    proof: &HidingSumcheckProof<F, K, C>,
+    transcript: &mut Transcript,
+) -> Result<Vec<HidingSumcheckSubclaim<K>>, SumcheckError> {
+    if !parameters.zk {
+        return Err(SumcheckError::ZkMismatch);
+    }
+    check_proof_shape(parameters, &proof.sumcheck)?;
+    absorb_statement(transcript, parameters, claim);
+    let weight: K = mask_weight(&proof.mask, transcript);
+    let combined = K::from_base_prime_field(claim) + weight * K::from_base_prime_field(proof.mask.sum);
+    Ok(verify_rounds(combined, &proof.sumcheck, transcript)?
+        .into_iter()
+        .map(|subclaim| HidingSumcheckSubclaim {
+            point: subclaim.point,
+            mask_weight: weight,
+            expected_evaluation: subclaim.expected_evaluation,
+        })
+        .collect())
+}
+
+/// 生成 `prove_sum_of_products` 的隐藏变体：在 `terms` 之外加一项 ρ·g
+///
+/// g 是 `num_vars` 个变量上的随机多线性掩码，由 `rng` 抽取、由 `commit_mask`
+/// 承诺，承诺与 G 吸收进转录后才导出 ρ。
+pub fn prove_hiding_sum_of_products<K: Field, C: WireFormat>(
+    num_vars: usize,
+    terms: Vec<(K, Vec<Vec<K>>)>,
+    commit_mask: impl FnOnce(&[K]) -> C,
+    transcript: &mut Transcript,
+    rng: &mut impl Rng,
+) -> Result<HidingProductSumcheckOutput<K, C>, SumcheckError> {
+    let (mask_table, mask) = sample_mask(1 << num_vars, commit_mask, rng);
+    let weight: K = mask_weight(&mask, transcript);
+    let mut terms = terms;
+    terms.push((weight, vec![mask_table.clone()]));
+    let mut output = prove_sum_of_products(num_vars, terms, transcript)?;
+    let mask_evaluation = output.factors.pop().expect("the mask term has one factor");
+    Ok(HidingProductSumcheckOutput { mask, mask_table, output, mask_weight: weight, mask_evaluation })
+}
+
+/// 验证隐藏乘积求和检查，`claim` 为 f 的和，`degree` 为 f 各项因子个数的最大值
+///
+/// 返回断言：f 在 `point` 处的值加 `mask_weight`·g(point) 为 `expected_evaluation`。
+pub fn verify_hiding_product_sumcheck<K: Field, C: WireFormat>(
+    num_vars: usize,
+    degree: usize,
+    claim: K,
+    mask: &SumcheckMask<K, C>,
+    proof: &ProductSumcheckProof<K>,
+    transcript: &mut Transcript,
+) -> Result<HidingSumcheckSubclaim<K>, SumcheckError> {
+    let weight: K = mask_weight(mask, transcript);
+    let subclaim = verify_product_sumcheck(num_vars, degree.max(1), claim + weight * mask.sum, proof, transcript)?;
+    Ok(HidingSumcheckSubclaim {
+        point: subclaim.point,
+        mask_weight: weight,
+        expected_evaluation: subclaim.expected_evaluation,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::{Blinding, FieldChoice, Goldilocks, GoldilocksExt, KZGCommitmentScheme, MerkleVectorCommitment};
+    use crate::config::EosConfig;
+    use crate::piop::{eq_table, prove_sumcheck, MultilinearOracle, TranscriptHash};
+    use ark_bls12_381::{Bls12_381, Fr};
+    use ark_poly::{univariate::DensePolynomial, DenseUVPolynomial};
+    use ark_std::{test_rng, UniformRand};
+
+    type TestField = Fr;
+
+    fn merkle_root<F: Field>(mask: &[F]) -> crate::circuit::MerkleCommitment {
+        MerkleVectorCommitment::new(TranscriptHash::default()).commit(mask).commitment()
+    }
+
+    #[test]
+    fn test_hiding_sumcheck_masks_partial_sums() {
+        let mut rng = test_rng();
+        let config = EosConfig { field: FieldChoice::Goldilocks, zk: true, ..EosConfig::default() };
+        let parameters = config.sumcheck_parameters(4);
+        assert!(parameters.zk);
+        let table: Vec<Goldilocks> = (0..16u64).map(|i| Goldilocks::from(i * 7 + 1)).collect();
+        let claim: Goldilocks = table.iter().copied().sum();
+
+        let output = prove_hiding_sumcheck::<_, GoldilocksExt, _>(
+            &parameters,
+            &table,
+            merkle_root,
+            &mut Transcript::new(b"test"),
+            &mut rng,
+        )
+        .unwrap();
+        assert_eq!(output.proof.mask.commitment, merkle_root(&output.mask));
+        let subclaims = verify_hiding_sumcheck(&parameters, claim, &output.proof, &mut Transcript::new(b"test")).unwrap();
+        assert_eq!(subclaims.len(), parameters.repetitions);
+        let mut f = QueriedOracle::new(MultilinearOracle { label: "f".to_string(), table: table.clone() });
+        let mut g = QueriedOracle::new(MultilinearOracle { label: "g".to_string(), table: output.mask.clone() });
+        for subclaim in &subclaims {
+            assert!(subclaim.check(&mut f, &mut g).unwrap());
+        }
+        assert_eq!((f.query_count(), g.query_count()), (parameters.repetitions, parameters.repetitions));
+
+        // 第一轮消息不再是 f 两半的部分和
+        let plain = prove_sumcheck::<_, GoldilocksExt>(&parameters.with_zk(false), &table, &mut Transcript::new(b"test"))
+            .unwrap();
+        assert_ne!(output.proof.sumcheck.rounds[0][0], plain.rounds[0][0]);
+        let other = prove_hiding_sumcheck::<_, GoldilocksExt, _>(
+            &parameters,
+            &table,
+            merkle_root,
+            &mut Transcript::new(b"test"),
+            &mut rng,
+        )
+        .unwrap();
+        assert_ne!(other.proof.sumcheck.rounds[0][0], output.proof.sumcheck.rounds[0][0]);
+
+        let wrong = claim + Goldilocks::from(1u64);
+        assert!(verify_hiding_sumcheck(&parameters, wrong, &output.proof, &mut Transcript::new(b"test")).is_err());
+        // 掩码权重在承诺之后导出，换掉承诺，权重随之改变
+        let mut recommitted = output.proof.clone();
+        recommitted.mask.commitment = other.proof.mask.commitment;
+        assert!(verify_hiding_sumcheck(&parameters, claim, &recommitted, &mut Transcript::new(b"test")).is_err());
+        let mut other_mask = output.mask.clone();
+        other_mask[3] += Goldilocks::from(1u64);
+        let mut g = QueriedOracle::new(MultilinearOracle { label: "g".to_string(), table: other_mask });
+        assert!(!subclaims[0].check(&mut f, &mut g).unwrap());
+
+        // zk 设置必须与所用的变体一致
+        let plain = SumcheckParameters::for_security::<TestField>(3, 128);
+        let table: Vec<TestField> = (1..=8u64).map(TestField::from).collect();
+        assert_eq!(
+            prove_hiding_sumcheck::<_, TestField, _>(
+                &plain,
+                &table,
+                merkle_root,
+                &mut Transcript::new(b"test"),
+                &mut rng
+            ),
+            Err(SumcheckError::ZkMismatch)
+        );
+        assert_eq!(
+            prove_sumcheck::<_, TestField>(&plain.with_zk(true), &table, &mut Transcript::new(b"test")),
+            Err(SumcheckError::ZkMismatch)
+        );
+    }
+
+    #[test]
+    fn test_hiding_product_sumcheck_opens_a_committed_mask() {
+        let mut rng = test_rng();
+        let scheme = KZGCommitmentScheme::<Bls12_381>::setup(16, &mut rng);
+        let num_vars = 3;
+        let u: Vec<TestField> = (0..8u64).map(|i| TestField::from(i + 2)).collect();
+        let w: Vec<TestField> = (0..8u64).map(|i| TestField::from(3 * i + 1)).collect();
+        let claim: TestField = u.iter().zip(&w).map(|(u, w)| *u * w).sum();
+
+        // g 的系数向量即求值表，盲化系数在 x^8 之上，多线性打开时多出的最高位取 0
+        let randomness = (0..3).map(|_| TestField::rand(&mut rng)).collect();
+        let blinding = Blinding::above_degree(8, DensePolynomial::from_coefficients_vec(randomness));
+        let commit = |table: &[TestField]| scheme.commit_hiding(&DensePolynomial::from_coefficients_slice(table), &blinding);
+        let terms = vec![(TestField::from(1u64), vec![u.clone(), w.clone()])];
+        let output = prove_hiding_sum_of_products(num_vars, terms, commit, &mut Transcript::new(b"test"), &mut rng).unwrap();
+        let blinded = blinding.apply(&DensePolynomial::from_coefficients_slice(&output.mask_table));
+        let point: Vec<TestField> = std::iter::once(TestField::from(0u64)).chain(output.output.point.iter().copied()).collect();
+        let opening = scheme.open_multilinear(&blinded, &output.mask.commitment, &point, &mut Transcript::new(b"mask"));
+        assert_eq!(opening.evaluation, output.mask_evaluation);
+
+        let mut transcript = Transcript::new(b"test");
+        let subclaim =
+            verify_hiding_product_sumcheck(num_vars, 2, claim, &output.mask, &output.output.proof, &mut transcript).unwrap();
+        assert!(scheme.verify_multilinear(&output.mask.commitment, &opening, &mut Transcript::new(b"mask")));
+        let at = |table: &[TestField]| {
+            eq_table(&subclaim.point).iter().zip(table).map(|(eq, value)| *eq * value).sum::<TestField>()
+        };
+        assert_eq!(at(&u) * at(&w) + subclaim.mask_weight * opening.evaluation, subclaim.expected_evaluation);
+
+        // 第一轮消息不是 u·w 的部分和
+        let plain =
+            crate::piop::prove_product_sumcheck(num_vars, vec![u.clone(), w.clone()], &mut Transcript::new(b"test")).unwrap();
+        assert_ne!(plain.proof.rounds[0], output.output.proof.rounds[0]);
+
+        let wrong = claim + TestField::from(1u64);
+        let mut transcript = Transcript::new(b"test");
+        assert!(verify_hiding_product_sumcheck(num_vars, 2, wrong, &output.mask, &output.output.proof, &mut transcript)
+            .is_err());
+        let mut resummed = output.mask.clone();
+        resummed.sum += TestField::from(1u64);
+        let mut transcript = Transcript::new(b"test");
+        assert!(verify_hiding_product_sumcheck(num_vars, 2, claim, &resummed, &output.output.proof, &mut transcript)
+            .is_err());
+    }
+}
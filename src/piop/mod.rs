@@ -3,6 +3,24 @@
 //! This module implements PIOP consistency checkers and related functionality
 //! for the EOS delegation protocol.
 
-pub mod consistency_checker;
+pub(crate) mod consistency_checker;
+pub(crate) mod hash;
+pub(crate) mod hiding_sumcheck;
+pub(crate) mod oracle;
+pub(crate) mod padding;
+pub(crate) mod prodcheck;
+pub(crate) mod sumcheck;
+pub(crate) mod transcript;
+pub(crate) mod transcript_var;
+pub(crate) mod zerocheck;
 
 pub use consistency_checker::*;
+pub use hash::*;
+pub use hiding_sumcheck::*;
+pub use oracle::*;
+pub use padding::*;
+pub use prodcheck::*;
+pub use sumcheck::*;
+pub use transcript::*;
+pub use transcript_var::*;
+pub use zerocheck::*;
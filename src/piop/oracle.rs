@@ -0,0 +1,180 @@
+//! 多项式 oracle 与查询记录
+//!
+//! 按论文的 oracle 模型，证明者发送的多项式对验证者而言是只能在点上查询的
+//! oracle。`Oracle` 描述这样的多项式，`QueriedOracle` 包装一个 oracle，
+//! 记录每次查询的点和答案，并可限制查询次数，便于在测试中断言查询复杂度。
+//! 编译成实际协议时，每次查询对应一个承诺打开。
+
+use ark_ff::{Field, PrimeField};
+use ark_poly::{univariate::DensePolynomial, Polynomial};
+use ark_std::vec::Vec;
+
+use crate::piop::evaluate_multilinear;
+
+/// 验证者可以查询的已承诺多项式
+pub trait Oracle<F: Field> {
+    /// 查询点：单变量多项式为一个域元素，多线性多项式为一个向量
+    type Point: Clone;
+
+    fn label(&self) -> &str;
+
+    /// 多项式在 `point` 处的值，不计入查询
+    fn evaluate(&self, point: &Self::Point) -> F;
+}
+
+/// 单变量多项式 oracle
+#[derive(Debug, Clone)]
+pub struct UnivariateOracle<F: Field> {
+    pub label: String,
+    pub polynomial: DensePolynomial<F>,
+}
+
+impl<F: Field> Oracle<F> for UnivariateOracle<F> {
+    type Point = F;
+
+    fn label(&self) -> &str {
+        &self.label
+    }
+
+    fn evaluate(&self, point: &F) -> F {
+        self.polynomial.evaluate(point)
+    }
+}
+
+/// 由 {0,1}^n 上的求值表给出的多线性多项式 oracle，可在扩域点处查询
+#[derive(Debug, Clone)]
+pub struct MultilinearOracle<F: PrimeField> {
+    pub label: String,
+    pub table: Vec<F>,
+}
+
+impl<F: PrimeField, K: Field<BasePrimeField = F>> Oracle<K> for MultilinearOracle<F> {
+    type Point = Vec<K>;
+
+    fn label(&self) -> &str {
+        &self.label
+    }
+
+    fn evaluate(&self, point: &Vec<K>) -> K {
+        evaluate_multilinear(&self.table, point)
+    }
+}
+
+/// 一次查询
+#[derive(Debug, Clone, PartialEq)]
+pub struct OracleQuery<P, F> {
+    pub point: P,
+    pub answer: F,
+}
+
+/// 记录查询并限制查询次数的 oracle
+#[derive(Debug, Clone)]
+pub struct QueriedOracle<F: Field, O: Oracle<F>> {
+    oracle: O,
+    budget: Option<usize>,
+    queries: Vec<OracleQuery<O::Point, F>>,
+}
+
+impl<F: Field, O: Oracle<F>> QueriedOracle<F, O> {
+    /// 不限查询次数
+    pub fn new(oracle: O) -> Self {
+        Self { oracle, budget: None, queries: Vec::new() }
+    }
+
+    /// 最多允许 `budget` 次查询
+    pub fn with_budget(oracle: O, budget: usize) -> Self {
+        Self { oracle, budget: Some(budget), queries: Vec::new() }
+    }
+
+    pub fn label(&self) -> &str {
+        self.oracle.label()
+    }
+
+    /// 查询 oracle；超出预算时返回错误且不记录
+    pub fn query(&mut self, point: &O::Point) -> Result<F, OracleError> {
+        if let Some(budget) = self.budget {
+            if self.queries.len() >= budget {
+                return Err(OracleError::BudgetExceeded { oracle: self.label().to_string(), budget });
+            }
+        }
+        let answer = self.oracle.evaluate(point);
+        self.queries.push(OracleQuery { point: point.clone(), answer });
+        Ok(answer)
+    }
+
+    pub fn queries(&self) -> &[OracleQuery<O::Point, F>] {
+        &self.queries
+    }
+
+    pub fn query_count(&self) -> usize {
+        self.queries.len()
+    }
+
+    /// 剩余可查询次数，不限时为 None
+    pub fn remaining(&self) -> Option<usize> {
+        self.budget.map(|budget| budget - self.queries.len())
+    }
+
+    pub fn into_inner(self) -> O {
+        self.oracle
+    }
+}
+
+/// Oracle 查询错误类型
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OracleError {
+    /// 查询次数超过该 oracle 的预算
+    BudgetExceeded { oracle: String, budget: usize },
+}
+
+impl std::fmt::Display for OracleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            OracleError::BudgetExceeded { oracle, budget } => {
+                write!(f, "Oracle {} queried more than {} times", oracle, budget)
+            }
+        }
+    }
+}
+
+impl std::error::Error for OracleError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::{Goldilocks, GoldilocksExt};
+    use crate::piop::{prove_sumcheck, verify_sumcheck, SumcheckParameters, Transcript};
+    use ark_bls12_381::Fr;
+    use ark_poly::DenseUVPolynomial;
+
+    type TestField = Fr;
+
+    #[test]
+    fn test_queries_are_logged_and_budgeted() {
+        let polynomial = DensePolynomial::from_coefficients_vec(vec![TestField::from(1u64), TestField::from(2u64)]);
+        let mut oracle = QueriedOracle::with_budget(UnivariateOracle { label: "w".to_string(), polynomial }, 2);
+        assert_eq!(oracle.query(&TestField::from(3u64)).unwrap(), TestField::from(7u64));
+        assert_eq!(oracle.remaining(), Some(1));
+        oracle.query(&TestField::from(0u64)).unwrap();
+        assert_eq!(
+            oracle.query(&TestField::from(5u64)),
+            Err(OracleError::BudgetExceeded { oracle: "w".to_string(), budget: 2 })
+        );
+        assert_eq!(oracle.query_count(), 2);
+        assert_eq!(oracle.queries()[0], OracleQuery { point: TestField::from(3u64), answer: TestField::from(7u64) });
+
+        // 求和检查的验证者对 f 每次重复恰好查询一次
+        let table: Vec<Goldilocks> = (0..16u64).map(Goldilocks::from).collect();
+        let claim: Goldilocks = table.iter().copied().sum();
+        let parameters = SumcheckParameters::for_security::<GoldilocksExt>(4, 128);
+        let proof = prove_sumcheck::<_, GoldilocksExt>(&parameters, &table, &mut Transcript::new(b"test")).unwrap();
+        let subclaims = verify_sumcheck(&parameters, claim, &proof, &mut Transcript::new(b"test")).unwrap();
+        let f = MultilinearOracle { label: "f".to_string(), table };
+        let mut f = QueriedOracle::<GoldilocksExt, _>::with_budget(f, parameters.repetitions);
+        for subclaim in &subclaims {
+            assert!(subclaim.check(&mut f).unwrap());
+        }
+        assert_eq!(f.query_count(), parameters.repetitions);
+        assert!(subclaims[0].check(&mut f).is_err());
+    }
+}
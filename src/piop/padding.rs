@@ -0,0 +1,138 @@
+//! 求值域大小与补齐
+//!
+//! 求和检查、零检查和 FFT 插值都要求表长为 2 的幂。补齐方式决定补上的
+//! 项是什么：
+//!
+//! - `Zero`：补零。零检查的恒等式表只能这样补，补零不改变多线性扩展在
+//!   原表上的取值之和。
+//! - `Random`：补均匀随机值，使承诺的多项式在原表之外也没有可预测的
+//!   结构。见证向量的补齐位置不被任何约束引用，可以随意取值；R1CS 的
+//!   行 (a, b, c) 补随机的 a、b，并取 c = a·b，补齐的行仍然满足约束。
+//!
+//! 手工补齐时容易漏掉 c = a·b 这一点而让补上的行违反约束，这里统一处理。
+
+use ark_ff::Field;
+use ark_std::rand::Rng;
+use ark_std::vec::Vec;
+
+use crate::piop::SumcheckError;
+
+/// 补齐方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PaddingStrategy {
+    #[default]
+    Zero,
+    Random,
+}
+
+/// 长度为 `len` 的向量所在的求值域
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Domain {
+    /// 原始长度
+    pub len: usize,
+    /// 不小于 `len` 的最小的 2 的幂，至少为 1
+    pub size: usize,
+}
+
+impl Domain {
+    pub fn for_len(len: usize) -> Self {
+        Self { len, size: len.next_power_of_two() }
+    }
+
+    /// 多线性扩展的变量个数
+    pub fn num_vars(&self) -> usize {
+        self.size.trailing_zeros() as usize
+    }
+
+    /// 需要补上的项数
+    pub fn padding(&self) -> usize {
+        self.size - self.len
+    }
+}
+
+/// 把向量补齐到求值域大小
+pub fn pad_column<F: Field, R: Rng + ?Sized>(values: &[F], strategy: PaddingStrategy, rng: &mut R) -> Vec<F> {
+    let domain = Domain::for_len(values.len());
+    let mut padded = values.to_vec();
+    padded.extend((0..domain.padding()).map(|_| match strategy {
+        PaddingStrategy::Zero => F::zero(),
+        PaddingStrategy::Random => F::rand(rng),
+    }));
+    padded
+}
+
+/// 补零，不需要随机源
+pub fn zero_padded<F: Field>(values: &[F]) -> Vec<F> {
+    let mut padded = values.to_vec();
+    padded.resize(Domain::for_len(values.len()).size, F::zero());
+    padded
+}
+
+/// 把 R1CS 的三列 a、b、c 补齐到同一求值域，补上的行满足 a·b = c
+pub fn pad_r1cs<F: Field, R: Rng + ?Sized>(
+    columns: [&[F]; 3],
+    strategy: PaddingStrategy,
+    rng: &mut R,
+) -> Result<[Vec<F>; 3], SumcheckError> {
+    let [a, b, c] = columns;
+    for column in [b, c] {
+        if column.len() != a.len() {
+            return Err(SumcheckError::TableSize { expected: a.len(), actual: column.len() });
+        }
+    }
+    let (mut a, mut b, mut c) = (a.to_vec(), b.to_vec(), c.to_vec());
+    for _ in 0..Domain::for_len(a.len()).padding() {
+        let (x, y) = match strategy {
+            PaddingStrategy::Zero => (F::zero(), F::zero()),
+            PaddingStrategy::Random => (F::rand(rng), F::rand(rng)),
+        };
+        a.push(x);
+        b.push(y);
+        c.push(x * y);
+    }
+    Ok([a, b, c])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::custom_circuits::CircuitTemplates;
+    use crate::piop::{prove_zerocheck, verify_zerocheck, Transcript};
+    use ark_bls12_381::Fr;
+    use ark_ff::Zero;
+    use ark_std::test_rng;
+
+    type TestField = Fr;
+
+    #[test]
+    fn test_padding_keeps_r1cs_rows_satisfied() {
+        let mut rng = test_rng();
+        assert_eq!(Domain::for_len(0), Domain { len: 0, size: 1 });
+        let domain = Domain::for_len(5);
+        assert_eq!((domain.size, domain.num_vars(), domain.padding()), (8, 3, 3));
+
+        // 5 个乘法约束和 1 个加法约束，补齐到 8 行
+        let circuit = CircuitTemplates::repeated_squaring(TestField::from(3u64), 5);
+        for strategy in [PaddingStrategy::Zero, PaddingStrategy::Random] {
+            let [a, b, c] = circuit.constraint_tables(strategy, &mut rng);
+            assert_eq!(a.len(), 8);
+            let identity: Vec<TestField> = a.iter().zip(&b).zip(&c).map(|((a, b), c)| *a * b - c).collect();
+            let proof = prove_zerocheck::<_, TestField>(&identity, &mut Transcript::new(b"test")).unwrap();
+            assert!(verify_zerocheck(3, &proof, &mut Transcript::new(b"test")).is_ok());
+            // 随机补齐的行不是零，但同样满足约束
+            assert_eq!(a[6..].iter().all(|value| value.is_zero()), strategy == PaddingStrategy::Zero);
+        }
+
+        // 补齐的见证位置不被约束引用
+        let witness = circuit.padded_witness(PaddingStrategy::Random, &mut rng);
+        assert_eq!(witness.len(), circuit.private_witnesses.len().next_power_of_two());
+        assert_eq!(witness[..circuit.private_witnesses.len()], circuit.private_witnesses);
+        assert_eq!(zero_padded(&[TestField::from(1u64); 3])[3], TestField::from(0u64));
+
+        let short = [TestField::from(1u64)];
+        assert_eq!(
+            pad_r1cs([&short, &short, &[]], PaddingStrategy::Zero, &mut rng),
+            Err(SumcheckError::TableSize { expected: 1, actual: 0 })
+        );
+    }
+}
@@ -0,0 +1,141 @@
+//! 乘积检查：求值表各项之积等于声明的值
+//!
+//! 把 f 的各项排成一棵二叉乘积树：V_n = f，V_{k}(x) = V_{k+1}(0, x)·V_{k+1}(1, x)，
+//! 树根 V_0 就是声明的乘积。验证者从树根往下逐层归约：对 V_k 在某点的断言，
+//! 运行 eq(r, x)·V_{k+1}(0, x)·V_{k+1}(1, x) 的乘积求和检查，证明者给出两半
+//! 在挑战点的值，验证者再用随机数把两半合成 V_{k+1} 在新点处的一个断言。
+//! 最后留下 f 在一个点处的求值断言。
+//!
+//! 置换论证的 Π (w_i + β·i + γ) = Π (w_i + β·σ(i) + γ) 由两次乘积检查给出。
+
+use ark_ff::{Field, PrimeField};
+use ark_std::vec::Vec;
+
+use crate::piop::zerocheck::num_vars_of;
+use crate::piop::{
+    eq_evaluation, eq_table, prove_product_sumcheck, verify_product_sumcheck, ProductSumcheckProof,
+    SumcheckError, SumcheckSubclaim, Transcript,
+};
+
+/// 乘积树一层的归约
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProdcheckLayer<K: Field> {
+    pub sumcheck: ProductSumcheckProof<K>,
+    /// 下一层两半在挑战点的值
+    pub halves: [K; 2],
+}
+
+/// 乘积检查证明，从树根往下每层一项
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProdcheckProof<K: Field> {
+    pub layers: Vec<ProdcheckLayer<K>>,
+}
+
+fn absorb_statement<K: Field>(transcript: &mut Transcript, num_vars: usize, product: K) {
+    transcript.append_message(b"prodcheck_num_vars", &(num_vars as u64).to_le_bytes());
+    transcript.append_field(b"prodcheck_product", &product);
+}
+
+/// 用随机数把两半合成下一层在 [τ, ρ] 处的断言
+fn next_claim<K: Field>(halves: &[K; 2], point: Vec<K>, transcript: &mut Transcript) -> (Vec<K>, K) {
+    transcript.append_fields(b"prodcheck_halves", halves);
+    let tau: K = transcript.challenge_element(b"prodcheck_challenge");
+    let mut next = Vec::with_capacity(point.len() + 1);
+    next.push(tau);
+    next.extend(point);
+    (next, halves[0] + tau * (halves[1] - halves[0]))
+}
+
+/// 证明 `table` 各项之积，返回乘积和证明
+pub fn prove_prodcheck<F: PrimeField, K: Field<BasePrimeField = F>>(
+    table: &[F],
+    transcript: &mut Transcript,
+) -> Result<(F, ProdcheckProof<K>), SumcheckError> {
+    let num_vars = num_vars_of(table.len())?;
+    let mut layers: Vec<Vec<K>> = vec![table.iter().map(|value| K::from_base_prime_field(*value)).collect()];
+    while layers.last().expect("starts with f").len() > 1 {
+        let layer = layers.last().expect("starts with f");
+        let (low, high) = layer.split_at(layer.len() / 2);
+        layers.push(low.iter().zip(high).map(|(l, h)| *l * h).collect());
+    }
+    let product: F = table.iter().copied().product();
+    absorb_statement(transcript, num_vars, K::from_base_prime_field(product));
+
+    let mut point = Vec::new();
+    let mut proof = Vec::with_capacity(num_vars);
+    for layer in layers[..num_vars].iter().rev() {
+        let (low, high) = layer.split_at(layer.len() / 2);
+        let tables = vec![eq_table(&point), low.to_vec(), high.to_vec()];
+        let output = prove_product_sumcheck(point.len(), tables, transcript)?;
+        let halves = [output.factors[1], output.factors[2]];
+        point = next_claim(&halves, output.point, transcript).0;
+        proof.push(ProdcheckLayer { sumcheck: output.proof, halves });
+    }
+    Ok((product, ProdcheckProof { layers: proof }))
+}
+
+/// 验证乘积检查，返回 f 在随机点处的求值断言
+pub fn verify_prodcheck<F: PrimeField, K: Field<BasePrimeField = F>>(
+    num_vars: usize,
+    product: F,
+    proof: &ProdcheckProof<K>,
+    transcript: &mut Transcript,
+) -> Result<SumcheckSubclaim<K>, SumcheckError> {
+    if proof.layers.len() != num_vars {
+        return Err(SumcheckError::ProofShape);
+    }
+    let mut claim = K::from_base_prime_field(product);
+    absorb_statement(transcript, num_vars, claim);
+    let mut point = Vec::new();
+    for layer in &proof.layers {
+        let subclaim = verify_product_sumcheck(point.len(), 3, claim, &layer.sumcheck, transcript)?;
+        let [low, high] = layer.halves;
+        if eq_evaluation(&point, &subclaim.point) * low * high != subclaim.expected_evaluation {
+            return Err(SumcheckError::FinalEvaluation);
+        }
+        (point, claim) = next_claim(&layer.halves, subclaim.point, transcript);
+    }
+    Ok(SumcheckSubclaim { point, expected_evaluation: claim })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::{Goldilocks, GoldilocksExt};
+    use crate::piop::{MultilinearOracle, QueriedOracle};
+    use ark_bls12_381::Fr;
+
+    type TestField = Fr;
+
+    #[test]
+    fn test_prodcheck_grand_product() {
+        // 置换论证：w 与其置换在加上 γ 后乘积相同
+        let gamma = TestField::from(1000u64);
+        let w: Vec<TestField> = [3u64, 1, 4, 1, 5, 9, 2, 6].map(TestField::from).to_vec();
+        let sigma = [7, 2, 5, 0, 1, 6, 3, 4];
+        let left: Vec<TestField> = w.iter().map(|w| *w + gamma).collect();
+        let right: Vec<TestField> = sigma.iter().map(|i| w[*i] + gamma).collect();
+
+        let (product, proof) = prove_prodcheck::<_, TestField>(&left, &mut Transcript::new(b"test")).unwrap();
+        let (other, _) = prove_prodcheck::<_, TestField>(&right, &mut Transcript::new(b"test")).unwrap();
+        assert_eq!(product, other);
+        let subclaim = verify_prodcheck(3, product, &proof, &mut Transcript::new(b"test")).unwrap();
+        let mut oracle = QueriedOracle::with_budget(MultilinearOracle { label: "left".to_string(), table: left }, 1);
+        assert!(subclaim.check(&mut oracle).unwrap());
+
+        let wrong = product + TestField::from(1u64);
+        assert!(verify_prodcheck(3, wrong, &proof, &mut Transcript::new(b"test")).is_err());
+        let mut tampered = proof.clone();
+        tampered.layers[1].halves[0] += TestField::from(1u64);
+        assert_eq!(
+            verify_prodcheck(3, product, &tampered, &mut Transcript::new(b"test")),
+            Err(SumcheckError::FinalEvaluation)
+        );
+
+        let table: Vec<Goldilocks> = (1..=16u64).map(Goldilocks::from).collect();
+        let (product, proof) = prove_prodcheck::<_, GoldilocksExt>(&table, &mut Transcript::new(b"test")).unwrap();
+        let subclaim = verify_prodcheck(4, product, &proof, &mut Transcript::new(b"test")).unwrap();
+        let mut oracle = QueriedOracle::new(MultilinearOracle { label: "f".to_string(), table });
+        assert!(subclaim.check(&mut oracle).unwrap());
+    }
+}
@@ -0,0 +1,417 @@
+//! 扩域挑战的多线性求和检查
+//!
+//! 证明 Σ_{x∈{0,1}^n} f(x) = claim，f 由基域 F 上的求值表给出。每轮的
+//! 单变量多项式为一次式，一轮的可靠性误差为 1/|K|，K 为挑战所在的域。
+//! 小域（Goldilocks、Mersenne31）本身只有 64 或 31 比特，挑战必须取自扩域
+//! K ⊇ F；扩域仍不足配置的安全级别时，用独立挑战重复整个协议。
+//! 大素数域上取 K = F 即可。
+
+use ark_ff::{Field, PrimeField};
+use ark_std::vec::Vec;
+
+use crate::piop::{Oracle, OracleError, QueriedOracle, Transcript};
+
+/// 求和检查参数
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SumcheckParameters {
+    pub num_vars: usize,
+    /// 以独立挑战重复的次数
+    pub repetitions: usize,
+    /// 用随机掩码多项式隐藏部分和，见 `prove_hiding_sumcheck`
+    pub zk: bool,
+}
+
+impl SumcheckParameters {
+    /// 选择重复次数，使挑战域 K 上的可靠性达到 `security_bits`
+    pub fn for_security<K: Field>(num_vars: usize, security_bits: usize) -> Self {
+        let per_repetition = Self::repetition_bits::<K>(num_vars).max(1.0);
+        let repetitions = (security_bits as f64 / per_repetition).ceil().max(1.0) as usize;
+        Self { num_vars, repetitions, zk: false }
+    }
+
+    pub fn with_zk(self, zk: bool) -> Self {
+        Self { zk, ..self }
+    }
+
+    /// 单次执行的可靠性比特数：log2(|K| / n)
+    fn repetition_bits<K: Field>(num_vars: usize) -> f64 {
+        challenge_space_bits::<K>() - (num_vars.max(1) as f64).log2()
+    }
+
+    /// 整体可靠性比特数
+    pub fn soundness_bits<K: Field>(&self) -> f64 {
+        Self::repetition_bits::<K>(self.num_vars) * self.repetitions as f64
+    }
+}
+
+/// 挑战域大小的比特数下界
+pub fn challenge_space_bits<K: Field>() -> f64 {
+    (K::extension_degree() * (K::BasePrimeField::MODULUS_BIT_SIZE as u64 - 1)) as f64
+}
+
+/// 求和检查证明：每次重复、每轮的 (g(0), g(1))
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExtensionSumcheckProof<K: Field> {
+    pub rounds: Vec<Vec<[K; 2]>>,
+}
+
+/// 验证通过后留给调用方的断言：f 在 `point` 处的值为 `expected_evaluation`
+#[derive(Debug, Clone, PartialEq)]
+pub struct SumcheckSubclaim<K: Field> {
+    pub point: Vec<K>,
+    pub expected_evaluation: K,
+}
+
+impl<K: Field> SumcheckSubclaim<K> {
+    /// 向 f 的 oracle 查询一次，检查断言是否成立
+    pub fn check<O: Oracle<K, Point = Vec<K>>>(&self, oracle: &mut QueriedOracle<K, O>) -> Result<bool, OracleError> {
+        Ok(oracle.query(&self.point)? == self.expected_evaluation)
+    }
+}
+
+/// 求和检查错误类型
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SumcheckError {
+    /// 求值表长度不是 2^num_vars
+    TableSize { expected: usize, actual: usize },
+    /// 证明的重复次数或轮数与参数不符
+    ProofShape,
+    /// g(0) + g(1) 与上一轮的断言不符
+    RoundSum { repetition: usize, round: usize },
+    /// 最后一轮的断言与证明者给出的因子值不符
+    FinalEvaluation,
+    /// 挑战恰好使 eq 多项式为零，无法还原断言
+    DegenerateChallenge,
+    /// 参数的 `zk` 与所用的证明或验证函数不符
+    ZkMismatch,
+}
+
+impl std::fmt::Display for SumcheckError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            SumcheckError::TableSize { expected, actual } => {
+                write!(f, "Sumcheck table has {} entries, expected {}", actual, expected)
+            }
+            SumcheckError::ProofShape => write!(f, "Sumcheck proof does not match the parameters"),
+            SumcheckError::RoundSum { repetition, round } => {
+                write!(f, "Sumcheck round {} of repetition {} does not sum to the claim", round, repetition)
+            }
+            SumcheckError::FinalEvaluation => write!(f, "Sumcheck final evaluation does not match the claimed factors"),
+            SumcheckError::DegenerateChallenge => write!(f, "Sumcheck challenge zeroes the eq polynomial"),
+            SumcheckError::ZkMismatch => write!(f, "Sumcheck zk setting does not match the protocol variant"),
+        }
+    }
+}
+
+impl std::error::Error for SumcheckError {}
+
+pub(crate) fn absorb_statement<F: PrimeField>(transcript: &mut Transcript, parameters: &SumcheckParameters, claim: F) {
+    transcript.append_message(b"sumcheck_num_vars", &(parameters.num_vars as u64).to_le_bytes());
+    transcript.append_message(b"sumcheck_repetitions", &(parameters.repetitions as u64).to_le_bytes());
+    transcript.append_field(b"sumcheck_claim", &claim);
+}
+
+/// 按最高位变量优先的顺序把表折叠一轮
+pub(crate) fn fold<K: Field>(table: &[K], challenge: K) -> Vec<K> {
+    let (low, high) = table.split_at(table.len() / 2);
+    low.iter().zip(high).map(|(l, h)| *l + challenge * (*h - l)).collect()
+}
+
+/// 基域求值表在扩域点处的多线性扩展值
+pub fn evaluate_multilinear<F: PrimeField, K: Field<BasePrimeField = F>>(table: &[F], point: &[K]) -> K {
+    let lifted: Vec<K> = table.iter().map(|value| K::from_base_prime_field(*value)).collect();
+    point.iter().fold(lifted, |table, challenge| fold(&table, *challenge))[0]
+}
+
+pub(crate) fn check_table_size<F>(parameters: &SumcheckParameters, table: &[F]) -> Result<(), SumcheckError> {
+    let expected = 1 << parameters.num_vars;
+    if table.len() != expected {
+        return Err(SumcheckError::TableSize { expected, actual: table.len() });
+    }
+    Ok(())
+}
+
+pub(crate) fn check_proof_shape<K: Field>(
+    parameters: &SumcheckParameters,
+    proof: &ExtensionSumcheckProof<K>,
+) -> Result<(), SumcheckError> {
+    if proof.rounds.len() != parameters.repetitions
+        || proof.rounds.iter().any(|rounds| rounds.len() != parameters.num_vars)
+    {
+        return Err(SumcheckError::ProofShape);
+    }
+    Ok(())
+}
+
+/// 对扩域上的表运行各次重复的全部轮次
+pub(crate) fn prove_rounds<K: Field>(parameters: &SumcheckParameters, table: &[K], transcript: &mut Transcript) -> Vec<Vec<[K; 2]>> {
+    (0..parameters.repetitions)
+        .map(|_| {
+            let mut current = table.to_vec();
+            (0..parameters.num_vars)
+                .map(|_| {
+                    let half = current.len() / 2;
+                    let evaluations = [current[..half].iter().copied().sum(), current[half..].iter().copied().sum()];
+                    transcript.append_fields(b"sumcheck_round", &evaluations);
+                    current = fold(&current, transcript.challenge_element(b"sumcheck_challenge"));
+                    evaluations
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// 逐轮检查 g(0) + g(1)，返回每次重复的求值断言
+pub(crate) fn verify_rounds<K: Field>(
+    claim: K,
+    proof: &ExtensionSumcheckProof<K>,
+    transcript: &mut Transcript,
+) -> Result<Vec<SumcheckSubclaim<K>>, SumcheckError> {
+    proof
+        .rounds
+        .iter()
+        .enumerate()
+        .map(|(repetition, rounds)| {
+            let mut expected = claim;
+            let mut point = Vec::with_capacity(rounds.len());
+            for (round, evaluations) in rounds.iter().enumerate() {
+                if evaluations[0] + evaluations[1] != expected {
+                    return Err(SumcheckError::RoundSum { repetition, round });
+                }
+                transcript.append_fields(b"sumcheck_round", evaluations);
+                let challenge: K = transcript.challenge_element(b"sumcheck_challenge");
+                expected = evaluations[0] + challenge * (evaluations[1] - evaluations[0]);
+                point.push(challenge);
+            }
+            Ok(SumcheckSubclaim { point, expected_evaluation: expected })
+        })
+        .collect()
+}
+
+/// 生成证明，挑战由 `transcript` 派生
+pub fn prove_sumcheck<F: PrimeField, K: Field<BasePrimeField = F>>(
+    parameters: &SumcheckParameters,
+    table: &[F],
+    transcript: &mut Transcript,
+) -> Result<ExtensionSumcheckProof<K>, SumcheckError> {
+    if parameters.zk {
+        return Err(SumcheckError::ZkMismatch);
+    }
+    check_table_size(parameters, table)?;
+    absorb_statement(transcript, parameters, table.iter().copied().sum::<F>());
+    let lifted: Vec<K> = table.iter().map(|value| K::from_base_prime_field(*value)).collect();
+    let rounds = prove_rounds(parameters, &lifted, transcript);
+    Ok(ExtensionSumcheckProof { rounds })
+}
+
+/// 验证证明，返回每次重复留下的求值断言，由调用方对照 f 的承诺或用
+/// `SumcheckSubclaim::check` 查询 oracle 检查
+pub fn verify_sumcheck<F: PrimeField, K: Field<BasePrimeField = F>>(
+    parameters: &SumcheckParameters,
+    claim: F,
+    proof: &ExtensionSumcheckProof<K>,
+    transcript: &mut Transcript,
+) -> Result<Vec<SumcheckSubclaim<K>>, SumcheckError> {
+    if parameters.zk {
+        return Err(SumcheckError::ZkMismatch);
+    }
+    check_proof_shape(parameters, proof)?;
+    absorb_statement(transcript, parameters, claim);
+    verify_rounds(K::from_base_prime_field(claim), proof, transcript)
+}
+
+/// 乘积求和检查证明：每轮单变量多项式在 0, 1, ..., d 处的值，d 为因子个数
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProductSumcheckProof<K: Field> {
+    pub rounds: Vec<Vec<K>>,
+}
+
+/// 乘积求和检查的证明者输出
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProductSumcheckOutput<K: Field> {
+    pub proof: ProductSumcheckProof<K>,
+    /// 全部挑战组成的点
+    pub point: Vec<K>,
+    /// 各因子在 `point` 处的值
+    pub factors: Vec<K>,
+}
+
+/// 由 0, 1, ..., d 处的值求单变量多项式在 `x` 处的值
+fn interpolate_at<K: Field>(evaluations: &[K], x: K) -> K {
+    let node = |i: usize| K::from(i as u64);
+    evaluations
+        .iter()
+        .enumerate()
+        .map(|(i, value)| {
+            let (numerator, denominator) = (0..evaluations.len()).filter(|j| *j != i).fold(
+                (K::one(), K::one()),
+                |(numerator, denominator), j| (numerator * (x - node(j)), denominator * (node(i) - node(j))),
+            );
+            *value * numerator * denominator.inverse().expect("interpolation nodes are distinct")
+        })
+        .sum()
+}
+
+/// eq(r, x) 在 {0,1}^n 上的求值表，r[0] 对应最高位变量，与 `fold` 的顺序一致
+pub fn eq_table<K: Field>(point: &[K]) -> Vec<K> {
+    point.iter().fold(vec![K::one()], |table, r| {
+        table.iter().flat_map(|value| [*value * (K::one() - r), *value * r]).collect()
+    })
+}
+
+/// eq(a, b) = Π (a_i b_i + (1 - a_i)(1 - b_i))
+pub fn eq_evaluation<K: Field>(a: &[K], b: &[K]) -> K {
+    a.iter().zip(b).map(|(a, b)| *a * b + (K::one() - a) * (K::one() - b)).product()
+}
+
+/// 证明 Σ_x Π_j t_j(x) = claim，t_j 为同样大小的多线性求值表
+///
+/// 每轮发送次数为因子个数的单变量多项式。零检查和乘积检查都建立在它之上。
+pub fn prove_product_sumcheck<K: Field>(
+    num_vars: usize,
+    tables: Vec<Vec<K>>,
+    transcript: &mut Transcript,
+) -> Result<ProductSumcheckOutput<K>, SumcheckError> {
+    prove_sum_of_products(num_vars, vec![(K::one(), tables)], transcript)
+}
+
+/// 证明 Σ_x Σ_k c_k·Π_j t_kj(x) = claim：若干乘积项的线性组合
+///
+/// 每轮多项式的次数取各项因子个数的最大值，验证用 `verify_product_sumcheck`。
+/// 只有一项时与 `prove_product_sumcheck` 完全相同；`factors` 按项的顺序
+/// 依次给出每一项各因子的值。
+pub fn prove_sum_of_products<K: Field>(
+    num_vars: usize,
+    terms: Vec<(K, Vec<Vec<K>>)>,
+    transcript: &mut Transcript,
+) -> Result<ProductSumcheckOutput<K>, SumcheckError> {
+    let expected = 1 << num_vars;
+    if let Some(table) = terms.iter().flat_map(|(_, tables)| tables).find(|table| table.len() != expected) {
+        return Err(SumcheckError::TableSize { expected, actual: table.len() });
+    }
+    let degree = terms.iter().map(|(_, tables)| tables.len()).max().unwrap_or(0);
+    let claim: K = (0..expected)
+        .map(|x| terms.iter().map(|(c, tables)| *c * tables.iter().map(|table| table[x]).product::<K>()).sum::<K>())
+        .sum();
+    absorb_product_statement(transcript, num_vars, degree, claim);
+    let mut terms = terms;
+    let mut point = Vec::with_capacity(num_vars);
+    let mut rounds = Vec::with_capacity(num_vars);
+    for _ in 0..num_vars {
+        let half = expected >> (point.len() + 1);
+        let evaluations: Vec<K> = (0..=degree)
+            .map(|t| {
+                let t = K::from(t as u64);
+                (0..half)
+                    .map(|j| {
+                        terms
+                            .iter()
+                            .map(|(c, tables)| {
+                                *c * tables.iter().map(|table| table[j] + t * (table[j + half] - table[j])).product::<K>()
+                            })
+                            .sum::<K>()
+                    })
+                    .sum()
+            })
+            .collect();
+        transcript.append_fields(b"product_sumcheck_round", &evaluations);
+        let challenge: K = transcript.challenge_element(b"product_sumcheck_challenge");
+        for (_, tables) in terms.iter_mut() {
+            *tables = tables.iter().map(|table| fold(table, challenge)).collect();
+        }
+        point.push(challenge);
+        rounds.push(evaluations);
+    }
+    let factors = terms.iter().flat_map(|(_, tables)| tables.iter().map(|table| table[0])).collect();
+    Ok(ProductSumcheckOutput { proof: ProductSumcheckProof { rounds }, point, factors })
+}
+
+/// 验证乘积求和检查，返回断言：各因子在 `point` 处的值之积为 `expected_evaluation`
+pub fn verify_product_sumcheck<K: Field>(
+    num_vars: usize,
+    degree: usize,
+    claim: K,
+    proof: &ProductSumcheckProof<K>,
+    transcript: &mut Transcript,
+) -> Result<SumcheckSubclaim<K>, SumcheckError> {
+    if proof.rounds.len() != num_vars || proof.rounds.iter().any(|round| round.len() != degree + 1) {
+        return Err(SumcheckError::ProofShape);
+    }
+    absorb_product_statement(transcript, num_vars, degree, claim);
+    let mut expected = claim;
+    let mut point = Vec::with_capacity(num_vars);
+    for (round, evaluations) in proof.rounds.iter().enumerate() {
+        if evaluations[0] + evaluations[1] != expected {
+            return Err(SumcheckError::RoundSum { repetition: 0, round });
+        }
+        transcript.append_fields(b"product_sumcheck_round", evaluations);
+        let challenge: K = transcript.challenge_element(b"product_sumcheck_challenge");
+        expected = interpolate_at(evaluations, challenge);
+        point.push(challenge);
+    }
+    Ok(SumcheckSubclaim { point, expected_evaluation: expected })
+}
+
+fn absorb_product_statement<K: Field>(transcript: &mut Transcript, num_vars: usize, degree: usize, claim: K) {
+    transcript.append_message(b"product_sumcheck_num_vars", &(num_vars as u64).to_le_bytes());
+    transcript.append_message(b"product_sumcheck_degree", &(degree as u64).to_le_bytes());
+    transcript.append_field(b"product_sumcheck_claim", &claim);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::{FieldChoice, Goldilocks, GoldilocksExt, Mersenne31, Mersenne31Ext};
+    use crate::config::EosConfig;
+    use ark_bls12_381::Fr;
+
+    type TestField = Fr;
+
+    #[test]
+    fn test_sumcheck_with_extension_challenges() {
+        assert_eq!(SumcheckParameters::for_security::<Mersenne31>(4, 128).repetitions, 5);
+        assert_eq!(SumcheckParameters::for_security::<Mersenne31Ext>(4, 128).repetitions, 3);
+        assert_eq!(SumcheckParameters::for_security::<GoldilocksExt>(4, 128).repetitions, 2);
+        assert_eq!(SumcheckParameters::for_security::<TestField>(4, 128).repetitions, 1);
+        let security = EosConfig::default().security_parameter;
+        assert_eq!(FieldChoice::Mersenne31.sumcheck_parameters(4, security).repetitions, 3);
+        let parameters = FieldChoice::Goldilocks.sumcheck_parameters(4, security);
+        assert!(parameters.soundness_bits::<GoldilocksExt>() >= 128.0);
+
+        let table: Vec<Goldilocks> = (0..16u64).map(|i| Goldilocks::from(i * i + 3)).collect();
+        let claim: Goldilocks = table.iter().copied().sum();
+        let proof = prove_sumcheck::<_, GoldilocksExt>(&parameters, &table, &mut Transcript::new(b"test")).unwrap();
+        let subclaims = verify_sumcheck(&parameters, claim, &proof, &mut Transcript::new(b"test")).unwrap();
+        assert_eq!(subclaims.len(), 2);
+        for subclaim in &subclaims {
+            // 挑战确实落在扩域中，而不是基域
+            assert!(subclaim.point.iter().all(|challenge| challenge.c1 != Goldilocks::from(0u64)));
+            assert_eq!(evaluate_multilinear(&table, &subclaim.point), subclaim.expected_evaluation);
+        }
+        assert_ne!(subclaims[0].point, subclaims[1].point);
+
+        let wrong = claim + Goldilocks::from(1u64);
+        assert_eq!(
+            verify_sumcheck(&parameters, wrong, &proof, &mut Transcript::new(b"test")),
+            Err(SumcheckError::RoundSum { repetition: 0, round: 0 })
+        );
+        let mut tampered = proof.clone();
+        tampered.rounds[1].pop();
+        assert_eq!(
+            verify_sumcheck(&parameters, claim, &tampered, &mut Transcript::new(b"test")),
+            Err(SumcheckError::ProofShape)
+        );
+
+        // 大素数域上挑战域就是基域
+        let table: Vec<TestField> = (1..=8u64).map(TestField::from).collect();
+        let parameters = SumcheckParameters::for_security::<TestField>(3, 128);
+        let proof = prove_sumcheck::<_, TestField>(&parameters, &table, &mut Transcript::new(b"test")).unwrap();
+        let subclaims =
+            verify_sumcheck(&parameters, TestField::from(36u64), &proof, &mut Transcript::new(b"test")).unwrap();
+        assert_eq!(evaluate_multilinear(&table, &subclaims[0].point), subclaims[0].expected_evaluation);
+        assert_eq!(
+            prove_sumcheck::<_, TestField>(&parameters, &table[..4], &mut Transcript::new(b"test")),
+            Err(SumcheckError::TableSize { expected: 8, actual: 4 })
+        );
+    }
+}
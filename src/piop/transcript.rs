@@ -0,0 +1,122 @@
+use ark_ec::CurveGroup;
+use ark_ff::{Field, PrimeField};
+use crate::piop::{HashState, TranscriptHash};
+use crate::serialization::{write_field, write_point, FieldEncoding};
+
+/// Fiat–Shamir 转录
+/// 证明者和验证者按相同顺序吸收消息，并从中派生相同的挑战
+#[derive(Clone)]
+pub struct Transcript {
+    hash: TranscriptHash,
+    hasher: HashState,
+}
+
+impl Transcript {
+    /// 以协议域标签创建新的转录（SHA-256）
+    pub fn new(domain: &[u8]) -> Self {
+        Self::with_hash(domain, TranscriptHash::Sha256)
+    }
+
+    /// 以指定哈希函数创建转录
+    pub fn with_hash(domain: &[u8], hash: TranscriptHash) -> Self {
+        let mut transcript = Self { hash, hasher: hash.hasher() };
+        transcript.append_message(b"domain", domain);
+        transcript
+    }
+
+    pub fn hash(&self) -> TranscriptHash {
+        self.hash
+    }
+
+    /// 吸收带标签的字节消息（标签和消息都带长度前缀，避免拼接歧义）
+    pub fn append_message(&mut self, label: &[u8], message: &[u8]) {
+        self.hasher.update((label.len() as u64).to_le_bytes());
+        self.hasher.update(label);
+        self.hasher.update((message.len() as u64).to_le_bytes());
+        self.hasher.update(message);
+    }
+
+    /// 吸收域元素
+    pub fn append_field<F: Field>(&mut self, label: &[u8], value: &F) {
+        let mut bytes = Vec::new();
+        write_field(&mut bytes, value);
+        self.append_message(label, &bytes);
+    }
+
+    /// 吸收域元素序列
+    pub fn append_fields<F: Field>(&mut self, label: &[u8], values: &[F]) {
+        let mut bytes = Vec::new();
+        for value in values {
+            write_field(&mut bytes, value);
+        }
+        self.append_message(label, &bytes);
+    }
+
+    /// 吸收群元素
+    pub fn append_point<G: CurveGroup>(&mut self, label: &[u8], point: &G::Affine) {
+        let mut bytes = Vec::new();
+        write_point::<G>(&mut bytes, point);
+        self.append_message(label, &bytes);
+    }
+
+    /// 派生 32 字节挑战，并把挑战本身吸收回转录
+    pub fn challenge_bytes(&mut self, label: &[u8]) -> [u8; 32] {
+        let mut hasher = self.hasher.clone();
+        hasher.update(b"challenge");
+        hasher.update(label);
+        let output = hasher.finalize();
+        self.append_message(label, &output);
+        output
+    }
+
+    /// 派生域元素挑战（64 字节取模，偏差可忽略）
+    pub fn challenge_field<F: PrimeField>(&mut self, label: &[u8]) -> F {
+        let mut wide = [0u8; 64];
+        wide[..32].copy_from_slice(&self.challenge_bytes(label));
+        wide[32..].copy_from_slice(&self.challenge_bytes(label));
+        FieldEncoding::wire::<F>().reduce(&wide)
+    }
+
+    /// 派生任意域（含扩域）的挑战，每个基域分量各取一次 `challenge_field`
+    pub fn challenge_element<F: Field>(&mut self, label: &[u8]) -> F {
+        let limbs: Vec<F::BasePrimeField> =
+            (0..F::extension_degree()).map(|_| self.challenge_field(label)).collect();
+        F::from_base_prime_field_elems(&limbs).expect("one limb per base field coordinate")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::Fr;
+
+    type TestField = Fr;
+
+    #[test]
+    fn test_transcript_determinism() {
+        let mut t1 = Transcript::new(b"test");
+        let mut t2 = Transcript::new(b"test");
+        t1.append_field(b"x", &TestField::from(3u64));
+        t2.append_field(b"x", &TestField::from(3u64));
+
+        let c1: TestField = t1.challenge_field(b"c");
+        let c2: TestField = t2.challenge_field(b"c");
+        assert_eq!(c1, c2);
+
+        // 连续的挑战互不相同
+        let d1: TestField = t1.challenge_field(b"c");
+        assert_ne!(c1, d1);
+
+        // 不同的消息产生不同的挑战
+        let mut t3 = Transcript::new(b"test");
+        t3.append_field(b"x", &TestField::from(4u64));
+        assert_ne!(c1, t3.challenge_field::<TestField>(b"c"));
+
+        // 哈希函数是转录的一部分
+        for hash in [TranscriptHash::Keccak, TranscriptHash::Poseidon] {
+            let mut t4 = Transcript::with_hash(b"test", hash);
+            t4.append_field(b"x", &TestField::from(3u64));
+            assert_ne!(c1, t4.challenge_field::<TestField>(b"c"));
+        }
+    }
+}
@@ -0,0 +1,192 @@
+//! Poseidon 转录的约束版本
+//!
+//! 在电路里按 `Transcript` 的顺序吸收同样的字节并派生同样的挑战，使验证者
+//! 的 Fiat–Shamir 步骤可以在 SNARK 中重放。只支持 `TranscriptHash::Poseidon`：
+//! 其余哈希按位运算，放进 R1CS 代价过高。标签、长度前缀等常量字节不产生约束。
+
+use ark_bls12_381::Fr as BlsFr;
+use ark_ff::{Field, PrimeField};
+use ark_r1cs_std::boolean::Boolean;
+use ark_r1cs_std::fields::{fp::FpVar, FieldVar};
+use ark_r1cs_std::uint8::UInt8;
+use ark_r1cs_std::{ToBitsGadget, ToBytesGadget};
+use ark_relations::r1cs::SynthesisError;
+
+use crate::piop::{poseidon_permutation_var, BYTES_PER_ELEMENT, POSEIDON_RATE, POSEIDON_WIDTH};
+
+/// 把不超过 31 字节的小端字节串装成域元素，与 `FieldEncoding::reduce` 一致
+fn pack_bytes(bytes: &[UInt8<BlsFr>]) -> Result<FpVar<BlsFr>, SynthesisError> {
+    let mut bits = Vec::with_capacity(bytes.len() * 8);
+    for byte in bytes {
+        bits.extend(byte.to_bits_le()?);
+    }
+    Boolean::le_bits_to_fp_var(&bits)
+}
+
+/// `PoseidonSponge` 的约束版本，吸收的字节数在电路构造时确定
+#[derive(Clone)]
+pub struct PoseidonSpongeVar {
+    state: [FpVar<BlsFr>; POSEIDON_WIDTH],
+    absorbed: usize,
+    pending: Vec<UInt8<BlsFr>>,
+    total_bytes: u64,
+}
+
+impl Default for PoseidonSpongeVar {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PoseidonSpongeVar {
+    pub fn new() -> Self {
+        Self {
+            state: std::array::from_fn(|_| FpVar::zero()),
+            absorbed: 0,
+            pending: Vec::new(),
+            total_bytes: 0,
+        }
+    }
+
+    /// 吸收域元素
+    pub fn absorb(&mut self, element: &FpVar<BlsFr>) -> Result<(), SynthesisError> {
+        if self.absorbed == POSEIDON_RATE {
+            poseidon_permutation_var(&mut self.state)?;
+            self.absorbed = 0;
+        }
+        self.state[self.absorbed] += element;
+        self.absorbed += 1;
+        Ok(())
+    }
+
+    /// 吸收字节，每 31 字节装成一个域元素
+    pub fn absorb_bytes(&mut self, data: &[UInt8<BlsFr>]) -> Result<(), SynthesisError> {
+        self.total_bytes += data.len() as u64;
+        self.pending.extend_from_slice(data);
+        let full = self.pending.len() / BYTES_PER_ELEMENT * BYTES_PER_ELEMENT;
+        let chunks: Vec<Vec<UInt8<BlsFr>>> =
+            self.pending[..full].chunks(BYTES_PER_ELEMENT).map(<[_]>::to_vec).collect();
+        for chunk in chunks {
+            self.absorb(&pack_bytes(&chunk)?)?;
+        }
+        self.pending.drain(..full);
+        Ok(())
+    }
+
+    /// 填充并输出一个域元素
+    pub fn squeeze(mut self) -> Result<FpVar<BlsFr>, SynthesisError> {
+        if !self.pending.is_empty() {
+            let rest = pack_bytes(&self.pending)?;
+            self.absorb(&rest)?;
+        }
+        self.absorb(&FpVar::constant(BlsFr::from(self.total_bytes)))?;
+        poseidon_permutation_var(&mut self.state)?;
+        let [output, ..] = self.state;
+        Ok(output)
+    }
+}
+
+/// `Transcript::with_hash(domain, TranscriptHash::Poseidon)` 的约束版本
+#[derive(Clone)]
+pub struct TranscriptVar {
+    hasher: PoseidonSpongeVar,
+}
+
+impl TranscriptVar {
+    pub fn new(domain: &[u8]) -> Result<Self, SynthesisError> {
+        let mut transcript = Self { hasher: PoseidonSpongeVar::new() };
+        transcript.append_message(b"domain", &UInt8::constant_vec(domain))?;
+        Ok(transcript)
+    }
+
+    /// 吸收带标签的字节消息，长度前缀与 `Transcript::append_message` 相同
+    pub fn append_message(&mut self, label: &[u8], message: &[UInt8<BlsFr>]) -> Result<(), SynthesisError> {
+        self.hasher.absorb_bytes(&UInt8::constant_vec(&(label.len() as u64).to_le_bytes()))?;
+        self.hasher.absorb_bytes(&UInt8::constant_vec(label))?;
+        self.hasher.absorb_bytes(&UInt8::constant_vec(&(message.len() as u64).to_le_bytes()))?;
+        self.hasher.absorb_bytes(message)
+    }
+
+    /// 吸收域元素，按 `write_field` 的 32 字节小端规范编码
+    pub fn append_field(&mut self, label: &[u8], value: &FpVar<BlsFr>) -> Result<(), SynthesisError> {
+        self.append_fields(label, std::slice::from_ref(value))
+    }
+
+    /// 吸收域元素序列
+    pub fn append_fields(&mut self, label: &[u8], values: &[FpVar<BlsFr>]) -> Result<(), SynthesisError> {
+        let mut bytes = Vec::with_capacity(values.len() * 32);
+        for value in values {
+            bytes.extend(value.to_bytes()?);
+        }
+        self.append_message(label, &bytes)
+    }
+
+    /// 派生 32 字节挑战，并把挑战本身吸收回转录
+    pub fn challenge_bytes(&mut self, label: &[u8]) -> Result<Vec<UInt8<BlsFr>>, SynthesisError> {
+        let mut hasher = self.hasher.clone();
+        hasher.absorb_bytes(&UInt8::constant_vec(b"challenge"))?;
+        hasher.absorb_bytes(&UInt8::constant_vec(label))?;
+        let output = hasher.squeeze()?.to_bytes()?;
+        self.append_message(label, &output)?;
+        Ok(output)
+    }
+
+    /// 派生域元素挑战：64 字节按 31 字节分块装入，再以 2^248 的幂合并取模
+    pub fn challenge_field(&mut self, label: &[u8]) -> Result<FpVar<BlsFr>, SynthesisError> {
+        let mut wide = self.challenge_bytes(label)?;
+        wide.extend(self.challenge_bytes(label)?);
+        let shift = BlsFr::from(2u64).pow([8 * BYTES_PER_ELEMENT as u64]);
+        let mut scale = BlsFr::from(1u64);
+        let mut value = FpVar::zero();
+        for chunk in wide.chunks(BYTES_PER_ELEMENT) {
+            value += pack_bytes(chunk)? * scale;
+            scale *= shift;
+        }
+        Ok(value)
+    }
+}
+
+/// 电路外的值：字节串在 `BlsFr` 上的打包，与 `UInt8::new_input_vec` 分配公开输入的方式一致
+pub fn packed_bytes_input(bytes: &[u8]) -> Vec<BlsFr> {
+    bytes
+        .chunks(((BlsFr::MODULUS_BIT_SIZE - 1) / 8) as usize)
+        .map(BlsFr::from_le_bytes_mod_order)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::piop::{Transcript, TranscriptHash};
+    use ark_r1cs_std::alloc::AllocVar;
+    use ark_r1cs_std::R1CSVar;
+    use ark_relations::r1cs::ConstraintSystem;
+
+    #[test]
+    fn test_transcript_var_matches_transcript() {
+        let cs = ConstraintSystem::<BlsFr>::new_ref();
+        let values: Vec<BlsFr> = (0..3u64).map(|i| BlsFr::from(i * 1_000_003 + 5) - BlsFr::from(9u64)).collect();
+        let message: Vec<u8> = (0..45u8).collect();
+
+        let mut native = Transcript::with_hash(b"test", TranscriptHash::Poseidon);
+        native.append_message(b"message", &message);
+        native.append_fields(b"values", &values);
+        let first: BlsFr = native.challenge_field(b"c");
+        native.append_field(b"value", &values[0]);
+        let second = native.challenge_bytes(b"d");
+
+        let mut gadget = TranscriptVar::new(b"test").unwrap();
+        let message_var = UInt8::new_input_vec(cs.clone(), &message).unwrap();
+        let value_vars = Vec::<FpVar<BlsFr>>::new_witness(cs.clone(), || Ok(values.clone())).unwrap();
+        gadget.append_message(b"message", &message_var).unwrap();
+        gadget.append_fields(b"values", &value_vars).unwrap();
+        assert_eq!(gadget.challenge_field(b"c").unwrap().value().unwrap(), first);
+        gadget.append_field(b"value", &value_vars[0]).unwrap();
+        assert_eq!(gadget.challenge_bytes(b"d").unwrap().value().unwrap(), second);
+        assert!(cs.is_satisfied().unwrap());
+
+        // 公开的字节输入按 `packed_bytes_input` 打包
+        let inputs = cs.borrow().unwrap().instance_assignment[1..].to_vec();
+        assert_eq!(inputs, packed_bytes_input(&message));
+    }
+}
@@ -0,0 +1,261 @@
+//! 零检查：多线性多项式在布尔超立方体上处处为零
+//!
+//! 验证者取随机点 r，双方对 eq(r, x)·f(x) 运行和为 0 的乘积求和检查。
+//! f 在 {0,1}^n 上处处为零当且仅当 Σ_x eq(r, x)·f(x) 作为 r 的多项式恒为
+//! 零，不为零时随机 r 使和为零的概率至多 n/|K|。最终留下 f 在一个点处的
+//! 求值断言，交给 f 的 oracle 或承诺检查。
+//!
+//! R1CS 的 Az∘Bz − Cz = 0 正是这种形式，`prove_r1cs_zerocheck` 直接对三张
+//! 表证明它，不必先算出差表；`prove_hiding_r1cs_zerocheck` 是加了掩码的
+//! 隐藏变体。挑战取自 K，小域上应取足够大的扩域。
+
+use ark_ff::{Field, PrimeField};
+use ark_std::rand::Rng;
+use ark_std::vec::Vec;
+
+use crate::piop::{
+    eq_evaluation, eq_table, prove_hiding_sum_of_products, prove_product_sumcheck, prove_sum_of_products,
+    verify_hiding_product_sumcheck, verify_product_sumcheck, ProductSumcheckOutput, ProductSumcheckProof,
+    SumcheckError, SumcheckMask, SumcheckSubclaim, Transcript,
+};
+use crate::serialization::WireFormat;
+
+/// 零检查证明
+#[derive(Debug, Clone, PartialEq)]
+pub struct ZerocheckProof<K: Field> {
+    pub sumcheck: ProductSumcheckProof<K>,
+}
+
+/// 求值表长度对应的变量个数
+pub(crate) fn num_vars_of(len: usize) -> Result<usize, SumcheckError> {
+    if len.is_power_of_two() {
+        Ok(len.trailing_zeros() as usize)
+    } else {
+        Err(SumcheckError::TableSize { expected: len.next_power_of_two(), actual: len })
+    }
+}
+
+fn random_point<K: Field>(num_vars: usize, transcript: &mut Transcript) -> Vec<K> {
+    transcript.append_message(b"zerocheck_num_vars", &(num_vars as u64).to_le_bytes());
+    (0..num_vars).map(|_| transcript.challenge_element(b"zerocheck_point")).collect()
+}
+
+/// 证明 `table` 的每一项都为零
+pub fn prove_zerocheck<F: PrimeField, K: Field<BasePrimeField = F>>(
+    table: &[F],
+    transcript: &mut Transcript,
+) -> Result<ZerocheckProof<K>, SumcheckError> {
+    let num_vars = num_vars_of(table.len())?;
+    let r: Vec<K> = random_point(num_vars, transcript);
+    let lifted = table.iter().map(|value| K::from_base_prime_field(*value)).collect();
+    let output = prove_product_sumcheck(num_vars, vec![eq_table(&r), lifted], transcript)?;
+    Ok(ZerocheckProof { sumcheck: output.proof })
+}
+
+/// 验证零检查，返回 f 在随机点处的求值断言
+pub fn verify_zerocheck<K: Field>(
+    num_vars: usize,
+    proof: &ZerocheckProof<K>,
+    transcript: &mut Transcript,
+) -> Result<SumcheckSubclaim<K>, SumcheckError> {
+    let r: Vec<K> = random_point(num_vars, transcript);
+    let subclaim = verify_product_sumcheck(num_vars, 2, K::zero(), &proof.sumcheck, transcript)?;
+    // 末轮断言是 eq(r, ρ)·f(ρ)，eq 由验证者自己计算
+    let eq = eq_evaluation(&r, &subclaim.point).inverse().ok_or(SumcheckError::DegenerateChallenge)?;
+    Ok(SumcheckSubclaim { expected_evaluation: subclaim.expected_evaluation * eq, point: subclaim.point })
+}
+
+/// R1CS 零检查的证明者输出
+#[derive(Debug, Clone, PartialEq)]
+pub struct R1csZerocheckOutput<K: Field> {
+    pub proof: ZerocheckProof<K>,
+    /// 末轮断言：`point` 即 ρ，`expected_evaluation` 为 eq(r, ρ)·(ã(ρ)·b̃(ρ) − c̃(ρ))
+    pub subclaim: SumcheckSubclaim<K>,
+    /// ã(ρ)、b̃(ρ)、c̃(ρ)，随证明发送
+    pub evaluations: [K; 3],
+}
+
+/// 隐藏 R1CS 零检查的证明者输出
+#[derive(Debug, Clone, PartialEq)]
+pub struct HidingR1csZerocheckOutput<K: Field, C> {
+    /// 零检查本身；`subclaim.expected_evaluation` 不含掩码项
+    pub zerocheck: R1csZerocheckOutput<K>,
+    pub mask: SumcheckMask<K, C>,
+    /// g 的求值表，须在 ρ 处按 `mask.commitment` 打开
+    pub mask_table: Vec<K>,
+    pub mask_weight: K,
+    /// g(ρ)
+    pub mask_evaluation: K,
+}
+
+/// 乘积求和检查的项：系数与各因子的求值表
+type ProductTerms<K> = Vec<(K, Vec<Vec<K>>)>;
+
+/// 取随机点 r，列出 eq(r, x)·a(x)·b(x) − eq(r, x)·c(x) 的两项
+fn r1cs_terms<F: PrimeField, K: Field<BasePrimeField = F>>(
+    columns: [&[F]; 3],
+    transcript: &mut Transcript,
+) -> Result<(usize, ProductTerms<K>), SumcheckError> {
+    let [a, b, c] = columns;
+    for column in [b, c] {
+        if column.len() != a.len() {
+            return Err(SumcheckError::TableSize { expected: a.len(), actual: column.len() });
+        }
+    }
+    let num_vars = num_vars_of(a.len())?;
+    let r: Vec<K> = random_point(num_vars, transcript);
+    let lift = |table: &[F]| table.iter().map(|value| K::from_base_prime_field(*value)).collect::<Vec<K>>();
+    let eq = eq_table(&r);
+    Ok((num_vars, vec![(K::one(), vec![eq.clone(), lift(a), lift(b)]), (-K::one(), vec![eq, lift(c)])]))
+}
+
+/// 由两项的因子得出三张表在 ρ 处的值并吸收进转录
+fn r1cs_output<K: Field>(output: ProductSumcheckOutput<K>, transcript: &mut Transcript) -> R1csZerocheckOutput<K> {
+    // 因子依次为 eq、a、b、eq、c
+    let [eq, a, b, _, c] = output.factors[..] else { unreachable!("two terms of three and two factors") };
+    let evaluations = [a, b, c];
+    transcript.append_fields(b"zerocheck_evaluations", &evaluations);
+    R1csZerocheckOutput {
+        proof: ZerocheckProof { sumcheck: output.proof },
+        subclaim: SumcheckSubclaim { point: output.point, expected_evaluation: eq * (a * b - c) },
+        evaluations,
+    }
+}
+
+/// 证明 a∘b − c 处处为零，a、b、c 为补齐到同一个 2 的幂的 Az、Bz、Cz
+///
+/// 对 eq(r, x)·a(x)·b(x) − eq(r, x)·c(x) 运行和为 0 的三次求和检查，末轮
+/// 留下 a、b、c 在 ρ 处的值。这三个值随后吸收进转录，调用方须对照三张表
+/// 的来源（例如见证承诺）检查它们。
+pub fn prove_r1cs_zerocheck<F: PrimeField, K: Field<BasePrimeField = F>>(
+    columns: [&[F]; 3],
+    transcript: &mut Transcript,
+) -> Result<R1csZerocheckOutput<K>, SumcheckError> {
+    let (num_vars, terms) = r1cs_terms(columns, transcript)?;
+    let output = prove_sum_of_products(num_vars, terms, transcript)?;
+    Ok(r1cs_output(output, transcript))
+}
+
+/// `prove_r1cs_zerocheck` 的隐藏变体：求和式加上掩码项 ρ·g，见 `prove_hiding_sum_of_products`
+pub fn prove_hiding_r1cs_zerocheck<F: PrimeField, K: Field<BasePrimeField = F>, C: WireFormat>(
+    columns: [&[F]; 3],
+    commit_mask: impl FnOnce(&[K]) -> C,
+    transcript: &mut Transcript,
+    rng: &mut impl Rng,
+) -> Result<HidingR1csZerocheckOutput<K, C>, SumcheckError> {
+    let (num_vars, terms) = r1cs_terms(columns, transcript)?;
+    let hiding = prove_hiding_sum_of_products(num_vars, terms, commit_mask, transcript, rng)?;
+    Ok(HidingR1csZerocheckOutput {
+        zerocheck: r1cs_output(hiding.output, transcript),
+        mask: hiding.mask,
+        mask_table: hiding.mask_table,
+        mask_weight: hiding.mask_weight,
+        mask_evaluation: hiding.mask_evaluation,
+    })
+}
+
+/// 验证 R1CS 零检查，`evaluations` 为证明者给出的 ã(ρ)、b̃(ρ)、c̃(ρ)
+///
+/// 返回末点 ρ；通过后剩下的断言是三张表在 ρ 处的值确为 `evaluations`。
+pub fn verify_r1cs_zerocheck<K: Field>(
+    num_vars: usize,
+    proof: &ZerocheckProof<K>,
+    evaluations: &[K; 3],
+    transcript: &mut Transcript,
+) -> Result<Vec<K>, SumcheckError> {
+    let r: Vec<K> = random_point(num_vars, transcript);
+    let subclaim = verify_product_sumcheck(num_vars, 3, K::zero(), &proof.sumcheck, transcript)?;
+    check_r1cs_evaluations(&r, subclaim, K::zero(), evaluations, transcript)
+}
+
+/// 验证隐藏 R1CS 零检查，`mask_evaluation` 为证明者给出的 g(ρ)
+///
+/// 返回末点 ρ；通过后剩下的断言还包括 g 在 ρ 处的值确为 `mask_evaluation`。
+pub fn verify_hiding_r1cs_zerocheck<K: Field, C: WireFormat>(
+    num_vars: usize,
+    proof: &ZerocheckProof<K>,
+    evaluations: &[K; 3],
+    mask: &SumcheckMask<K, C>,
+    mask_evaluation: K,
+    transcript: &mut Transcript,
+) -> Result<Vec<K>, SumcheckError> {
+    let r: Vec<K> = random_point(num_vars, transcript);
+    let subclaim = verify_hiding_product_sumcheck(num_vars, 3, K::zero(), mask, &proof.sumcheck, transcript)?;
+    let masked = subclaim.mask_weight * mask_evaluation;
+    let subclaim = SumcheckSubclaim { point: subclaim.point, expected_evaluation: subclaim.expected_evaluation };
+    check_r1cs_evaluations(&r, subclaim, masked, evaluations, transcript)
+}
+
+fn check_r1cs_evaluations<K: Field>(
+    r: &[K],
+    subclaim: SumcheckSubclaim<K>,
+    masked: K,
+    evaluations: &[K; 3],
+    transcript: &mut Transcript,
+) -> Result<Vec<K>, SumcheckError> {
+    let [a, b, c] = *evaluations;
+    if eq_evaluation(r, &subclaim.point) * (a * b - c) + masked != subclaim.expected_evaluation {
+        return Err(SumcheckError::FinalEvaluation);
+    }
+    transcript.append_fields(b"zerocheck_evaluations", evaluations);
+    Ok(subclaim.point)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::{Goldilocks, GoldilocksExt};
+    use crate::piop::{MultilinearOracle, QueriedOracle};
+    use ark_bls12_381::Fr;
+
+    type TestField = Fr;
+
+    #[test]
+    fn test_zerocheck_accepts_only_vanishing_tables() {
+        // R1CS 恒等式 a·b − c 在满足的赋值上处处为零
+        let a: Vec<TestField> = (1..=8u64).map(TestField::from).collect();
+        let b: Vec<TestField> = (11..=18u64).map(TestField::from).collect();
+        let c: Vec<TestField> = a.iter().zip(&b).map(|(a, b)| *a * b).collect();
+        let identity: Vec<TestField> = a.iter().zip(&b).zip(&c).map(|((a, b), c)| *a * b - c).collect();
+        let proof = prove_zerocheck::<_, TestField>(&identity, &mut Transcript::new(b"test")).unwrap();
+        let subclaim = verify_zerocheck(3, &proof, &mut Transcript::new(b"test")).unwrap();
+        let mut oracle = QueriedOracle::with_budget(MultilinearOracle { label: "f".to_string(), table: identity }, 1);
+        assert!(subclaim.check(&mut oracle).unwrap());
+
+        let mut broken = c.clone();
+        broken[5] += TestField::from(1u64);
+        let identity: Vec<TestField> = a.iter().zip(&b).zip(&broken).map(|((a, b), c)| *a * b - c).collect();
+        let proof = prove_zerocheck::<_, TestField>(&identity, &mut Transcript::new(b"test")).unwrap();
+        assert_eq!(
+            verify_zerocheck(3, &proof, &mut Transcript::new(b"test")),
+            Err(SumcheckError::RoundSum { repetition: 0, round: 0 })
+        );
+
+        // 直接对三张表证明，末轮的三个值就是各表在 ρ 处的多线性扩展值
+        let output = prove_r1cs_zerocheck::<_, TestField>([&a, &b, &c], &mut Transcript::new(b"test")).unwrap();
+        let mut transcript = Transcript::new(b"test");
+        let point = verify_r1cs_zerocheck(3, &output.proof, &output.evaluations, &mut transcript).unwrap();
+        assert_eq!(point, output.subclaim.point);
+        for (table, value) in [&a, &b, &c].into_iter().zip(output.evaluations) {
+            assert_eq!(crate::piop::evaluate_multilinear(table, &point), value);
+        }
+        let mut wrong = output.evaluations;
+        wrong[2] += TestField::from(1u64);
+        assert_eq!(
+            verify_r1cs_zerocheck(3, &output.proof, &wrong, &mut Transcript::new(b"test")),
+            Err(SumcheckError::FinalEvaluation)
+        );
+        let output = prove_r1cs_zerocheck::<_, TestField>([&a, &b, &broken], &mut Transcript::new(b"test")).unwrap();
+        assert!(verify_r1cs_zerocheck(3, &output.proof, &output.evaluations, &mut Transcript::new(b"test")).is_err());
+
+        // 小域上的挑战取自扩域
+        let table = vec![Goldilocks::from(0u64); 16];
+        let proof = prove_zerocheck::<_, GoldilocksExt>(&table, &mut Transcript::new(b"test")).unwrap();
+        let subclaim = verify_zerocheck(4, &proof, &mut Transcript::new(b"test")).unwrap();
+        assert_eq!(subclaim.expected_evaluation, GoldilocksExt::from(0u64));
+        assert_eq!(
+            prove_zerocheck::<_, TestField>(&[TestField::from(0u64); 6], &mut Transcript::new(b"test")),
+            Err(SumcheckError::TableSize { expected: 8, actual: 6 })
+        );
+    }
+}
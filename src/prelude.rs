@@ -0,0 +1,33 @@
+//! Curated exports for `use eos_delegation::prelude::*;`
+//!
+//! Only names that are part of the supported API are listed here, one by
+//! one, so adding an item to a module never changes what the prelude brings
+//! into scope.
+
+pub use crate::circuit::{
+    FieldChoice, FriCommitmentScheme, KZGCommitmentScheme, OpeningProof, PolynomialCommitment,
+    PolynomialCommitmentScheme, UniversalParams,
+};
+pub use crate::circuit_dsl::CircuitBuilder;
+pub use crate::config::{ConfigError, ConfigIssue, EosConfig};
+pub use crate::custom_circuits::{
+    BudgetExceeded, CircuitBudget, CircuitTemplates, CustomCircuit, Privacy, Variable, WitnessReport,
+};
+pub use crate::evaluation::{BackendKind, CostModel, PerformanceMetrics, PerformanceReport};
+#[cfg(feature = "evaluation")]
+pub use crate::evaluation::{ProofSizeReport, ReportFormat};
+#[cfg(feature = "delegator")]
+pub use crate::inputs::{Assignment, InputError, InputSchema};
+#[cfg(feature = "worker")]
+pub use crate::mpc::{
+    AdditiveSecretSharing, AdditiveShare, CollaborationMode, CommunicationPattern, ExecCircuit, ExecutionError,
+    IsolationMode, OperationMode, SecretSharing, ShamirSecretSharing, ShamirShare,
+};
+pub use crate::piop::{ConsistencyChecker, PolynomialConsistencyProof, Transcript};
+pub use crate::protocol::{
+    build_backend, verification_key_hash, verify_bundle, verify_transcript, ArchiveRecord, ContentId, ProofArchive,
+    ProofBundle, ProvingBackend, StreamingVerifier, Verdict, VerifierKey,
+};
+#[cfg(feature = "delegator")]
+pub use crate::protocol::{CompressedProof, DelegationResult, EOSError, EOSProtocol};
+pub use crate::serialization::{DecodeError, WireFormat};
@@ -0,0 +1,416 @@
+//! Two-phase acceptance of delegation results
+//!
+//! Workers first commit to the result they produced by signing its digest
+//! with their session key. Once every worker has committed and the proof has
+//! verified, the delegator signs an `AcceptanceReceipt` over the session,
+//! attempt and digest; the receipt is what a payment or accountability layer
+//! settles on. Both sides are idempotent: a worker that never saw its
+//! receipt re-sends the same commitment and gets the same receipt back,
+//! while a session can only ever be accepted for one result.
+
+use std::collections::{BTreeSet, HashMap};
+
+use ark_bls12_381::Fr as BlsFr;
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
+use ark_ed_on_bls12_381::{EdwardsAffine, Fr as JubjubScalar};
+use ark_ff::{Field, PrimeField, UniformRand};
+use ark_std::rand::Rng;
+use sha2::{Digest, Sha256};
+
+use crate::custom_circuits::SchnorrSignature;
+use crate::mpc::{KeyDirectory, KeyError, SessionId, SignedMessage, WorkerKeyring};
+use crate::protocol::DelegationResult;
+use crate::serialization::{write_u32, write_u64, ByteReader, FieldEncoding};
+
+const COMMITMENT_DOMAIN: &[u8] = b"EOS-result-commitment-v1";
+const RECEIPT_DOMAIN: &[u8] = b"EOS-acceptance-receipt-v1";
+
+/// Digest identifying a delegation result
+pub fn result_digest<E: Pairing, F: Field>(result: &DelegationResult<E, F>) -> [u8; 32] {
+    let mut hasher = Sha256::new().chain_update(b"EOS-result-digest-v1");
+    hasher.update(result.verification_key_hash);
+    let proof = result.piop_proof.as_deref().unwrap_or_default();
+    hasher.update((proof.len() as u64).to_le_bytes());
+    hasher.update(proof);
+    for commitment in &result.polynomial_commitments {
+        hasher.update((commitment.len() as u64).to_le_bytes());
+        hasher.update(commitment);
+    }
+    hasher.update(result.seed_commitment.unwrap_or_default());
+    hasher.finalize().into()
+}
+
+/// What a worker commits to: a result for one attempt of a session
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResultClaim {
+    pub session: SessionId,
+    /// Incremented each time the delegation is re-run
+    pub attempt: u32,
+    pub result_digest: [u8; 32],
+}
+
+impl ResultClaim {
+    fn encode(&self, domain: &[u8]) -> Vec<u8> {
+        let mut out = domain.to_vec();
+        write_u64(&mut out, self.session);
+        write_u32(&mut out, self.attempt);
+        out.extend_from_slice(&self.result_digest);
+        out
+    }
+
+    fn decode(domain: &[u8], bytes: &[u8]) -> Option<Self> {
+        let mut reader = ByteReader::new(bytes.strip_prefix(domain)?);
+        let claim = ResultClaim {
+            session: reader.read_u64().ok()?,
+            attempt: reader.read_u32().ok()?,
+            result_digest: reader.read_bytes(32).ok()?.try_into().ok()?,
+        };
+        reader.finish().ok()?;
+        Some(claim)
+    }
+
+    fn receipt_message(&self) -> BlsFr {
+        FieldEncoding::wire::<BlsFr>().reduce(&Sha256::digest(self.encode(RECEIPT_DOMAIN)))
+    }
+}
+
+/// The claim a worker's signed commitment makes, if it is well-formed
+///
+/// The signature is not checked here; see `KeyDirectory::verify`.
+pub(crate) fn committed_claim(commitment: &SignedMessage) -> Option<ResultClaim> {
+    ResultClaim::decode(COMMITMENT_DOMAIN, &commitment.payload)
+}
+
+/// Commit to a result with the worker's key for the session
+pub fn commit_result<F: PrimeField, R: Rng + ?Sized>(
+    keyring: &WorkerKeyring<F>,
+    claim: ResultClaim,
+    rng: &mut R,
+) -> Result<SignedMessage, KeyError> {
+    keyring.sign_for_session(claim.session, &claim.encode(COMMITMENT_DOMAIN), rng)
+}
+
+/// The delegator's signed acknowledgement of a verified result
+#[derive(Debug, Clone, PartialEq)]
+pub struct AcceptanceReceipt {
+    pub claim: ResultClaim,
+    pub signature: SchnorrSignature,
+}
+
+impl AcceptanceReceipt {
+    pub fn verify(&self, delegator_key: &EdwardsAffine) -> bool {
+        self.signature.verify(delegator_key, self.claim.receipt_message())
+    }
+}
+
+/// Delegator signing key for receipts
+pub struct DelegatorSigningKey {
+    secret: JubjubScalar,
+    pub verifying_key: EdwardsAffine,
+}
+
+impl DelegatorSigningKey {
+    pub fn generate<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        let secret = JubjubScalar::rand(rng);
+        Self { secret, verifying_key: (EdwardsAffine::generator() * secret).into_affine() }
+    }
+}
+
+/// Delegator-side state of one session
+#[derive(Debug, Clone, PartialEq)]
+pub enum AcceptanceState {
+    /// Commitments received so far for the latest attempt
+    Committing { attempt: u32, result_digest: [u8; 32], committed: BTreeSet<usize> },
+    /// The proof of this attempt failed; a later attempt may be committed
+    Rejected { attempt: u32 },
+    Accepted(AcceptanceReceipt),
+}
+
+/// Delegator-side bookkeeping of result acceptance
+pub struct AcceptanceLedger {
+    key: DelegatorSigningKey,
+    num_workers: usize,
+    sessions: HashMap<SessionId, AcceptanceState>,
+}
+
+impl AcceptanceLedger {
+    pub fn new(key: DelegatorSigningKey, num_workers: usize) -> Self {
+        Self { key, num_workers, sessions: HashMap::new() }
+    }
+
+    pub fn verifying_key(&self) -> EdwardsAffine {
+        self.key.verifying_key
+    }
+
+    pub fn state(&self, session: SessionId) -> Option<&AcceptanceState> {
+        self.sessions.get(&session)
+    }
+
+    /// Record a worker's commitment
+    ///
+    /// Returns the existing receipt if the session was already accepted for
+    /// this result, so a worker retrying after a lost receipt gets it again.
+    pub fn receive_commitment(
+        &mut self,
+        commitment: &SignedMessage,
+        directory: &KeyDirectory,
+    ) -> Result<Option<AcceptanceReceipt>, AcceptanceError> {
+        directory.verify(commitment).map_err(AcceptanceError::Signature)?;
+        let claim = committed_claim(commitment).ok_or(AcceptanceError::MalformedCommitment)?;
+        let worker = commitment.key_id.party;
+        if worker >= self.num_workers {
+            return Err(AcceptanceError::UnknownWorker(worker));
+        }
+
+        let state = self.sessions.entry(claim.session).or_insert_with(|| AcceptanceState::Committing {
+            attempt: claim.attempt,
+            result_digest: claim.result_digest,
+            committed: BTreeSet::new(),
+        });
+        match state {
+            AcceptanceState::Accepted(receipt) if receipt.claim == claim => Ok(Some(receipt.clone())),
+            AcceptanceState::Accepted(_) => Err(AcceptanceError::AlreadyAccepted(claim.session)),
+            AcceptanceState::Rejected { attempt } if claim.attempt <= *attempt => {
+                Err(AcceptanceError::StaleAttempt { session: claim.session, attempt: claim.attempt })
+            }
+            AcceptanceState::Committing { attempt, .. } if claim.attempt < *attempt => {
+                Err(AcceptanceError::StaleAttempt { session: claim.session, attempt: claim.attempt })
+            }
+            AcceptanceState::Committing { attempt, result_digest, committed } if claim.attempt == *attempt => {
+                if claim.result_digest != *result_digest {
+                    return Err(AcceptanceError::ConflictingResults(claim.session));
+                }
+                committed.insert(worker);
+                Ok(None)
+            }
+            _ => {
+                // A newer attempt supersedes an unaccepted one
+                *state = AcceptanceState::Committing {
+                    attempt: claim.attempt,
+                    result_digest: claim.result_digest,
+                    committed: BTreeSet::from([worker]),
+                };
+                Ok(None)
+            }
+        }
+    }
+
+    /// Record the proof check of the committed result and, if it passed,
+    /// issue the receipt
+    pub fn accept<R: Rng + ?Sized>(
+        &mut self,
+        session: SessionId,
+        proof_valid: bool,
+        rng: &mut R,
+    ) -> Result<AcceptanceReceipt, AcceptanceError> {
+        let state = self.sessions.get_mut(&session).ok_or(AcceptanceError::UnknownSession(session))?;
+        let (attempt, result_digest) = match state {
+            AcceptanceState::Accepted(receipt) => return Ok(receipt.clone()),
+            AcceptanceState::Rejected { .. } => return Err(AcceptanceError::Rejected(session)),
+            AcceptanceState::Committing { committed, .. } if committed.len() < self.num_workers => {
+                return Err(AcceptanceError::MissingCommitments { session, received: committed.len() });
+            }
+            AcceptanceState::Committing { attempt, result_digest, .. } => (*attempt, *result_digest),
+        };
+        if !proof_valid {
+            *state = AcceptanceState::Rejected { attempt };
+            return Err(AcceptanceError::Rejected(session));
+        }
+        let claim = ResultClaim { session, attempt, result_digest };
+        let receipt = AcceptanceReceipt {
+            claim,
+            signature: SchnorrSignature::sign(self.key.secret, claim.receipt_message(), rng),
+        };
+        *state = AcceptanceState::Accepted(receipt.clone());
+        Ok(receipt)
+    }
+
+    /// Sessions with commitments but no receipt yet
+    pub fn unacknowledged(&self) -> Vec<SessionId> {
+        let mut sessions: Vec<SessionId> = self
+            .sessions
+            .iter()
+            .filter(|(_, state)| !matches!(state, AcceptanceState::Accepted(_)))
+            .map(|(session, _)| *session)
+            .collect();
+        sessions.sort_unstable();
+        sessions
+    }
+}
+
+/// Worker-side tracking of commitments awaiting a receipt
+#[derive(Debug, Clone, Default)]
+pub struct PendingReceipts {
+    pending: HashMap<SessionId, (ResultClaim, SignedMessage)>,
+    receipts: HashMap<SessionId, AcceptanceReceipt>,
+}
+
+impl PendingReceipts {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Commit to a result and remember the commitment for retries
+    pub fn commit<F: PrimeField, R: Rng + ?Sized>(
+        &mut self,
+        keyring: &WorkerKeyring<F>,
+        claim: ResultClaim,
+        rng: &mut R,
+    ) -> Result<SignedMessage, KeyError> {
+        let commitment = commit_result(keyring, claim, rng)?;
+        self.pending.insert(claim.session, (claim, commitment.clone()));
+        Ok(commitment)
+    }
+
+    /// Commitments to re-send because no receipt arrived
+    pub fn retries(&self) -> Vec<SignedMessage> {
+        self.pending.values().map(|(_, commitment)| commitment.clone()).collect()
+    }
+
+    /// Store a receipt after checking it matches what this worker committed to
+    pub fn record_receipt(
+        &mut self,
+        receipt: AcceptanceReceipt,
+        delegator_key: &EdwardsAffine,
+    ) -> Result<(), AcceptanceError> {
+        let session = receipt.claim.session;
+        let (claim, _) = self.pending.get(&session).ok_or(AcceptanceError::UnknownSession(session))?;
+        if *claim != receipt.claim || !receipt.verify(delegator_key) {
+            return Err(AcceptanceError::InvalidReceipt(session));
+        }
+        self.pending.remove(&session);
+        self.receipts.insert(session, receipt);
+        Ok(())
+    }
+
+    pub fn receipt(&self, session: SessionId) -> Option<&AcceptanceReceipt> {
+        self.receipts.get(&session)
+    }
+}
+
+/// Result acceptance error types
+#[derive(Debug, Clone, PartialEq)]
+pub enum AcceptanceError {
+    Signature(KeyError),
+    MalformedCommitment,
+    UnknownWorker(usize),
+    UnknownSession(SessionId),
+    ConflictingResults(SessionId),
+    StaleAttempt { session: SessionId, attempt: u32 },
+    MissingCommitments { session: SessionId, received: usize },
+    AlreadyAccepted(SessionId),
+    Rejected(SessionId),
+    InvalidReceipt(SessionId),
+}
+
+impl std::fmt::Display for AcceptanceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            AcceptanceError::Signature(e) => write!(f, "Commitment signature rejected: {}", e),
+            AcceptanceError::MalformedCommitment => write!(f, "Malformed result commitment"),
+            AcceptanceError::UnknownWorker(party) => write!(f, "Unknown worker {}", party),
+            AcceptanceError::UnknownSession(s) => write!(f, "Unknown session {}", s),
+            AcceptanceError::ConflictingResults(s) => write!(f, "Workers committed to different results in session {}", s),
+            AcceptanceError::StaleAttempt { session, attempt } => {
+                write!(f, "Attempt {} of session {} has been superseded", attempt, session)
+            }
+            AcceptanceError::MissingCommitments { session, received } => {
+                write!(f, "Session {} has only {} worker commitments", session, received)
+            }
+            AcceptanceError::AlreadyAccepted(s) => write!(f, "Session {} was already accepted for another result", s),
+            AcceptanceError::Rejected(s) => write!(f, "Result of session {} failed verification", s),
+            AcceptanceError::InvalidReceipt(s) => write!(f, "Receipt for session {} does not match the commitment", s),
+        }
+    }
+}
+
+impl std::error::Error for AcceptanceError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mpc::RotationPolicy;
+    use ark_std::test_rng;
+
+    type TestField = BlsFr;
+
+    fn workers() -> (Vec<WorkerKeyring<TestField>>, KeyDirectory) {
+        let mut directory = KeyDirectory::new();
+        let keyrings: Vec<WorkerKeyring<TestField>> = (0..2)
+            .map(|party| {
+                let mut keyring = WorkerKeyring::new(party, [party as u8; 32], RotationPolicy::default(), 0);
+                directory.accept(&keyring.initial_announcement().unwrap()).unwrap();
+                keyring.begin_session(7);
+                keyring
+            })
+            .collect();
+        (keyrings, directory)
+    }
+
+    #[test]
+    fn test_receipt_after_all_commitments_and_verification() {
+        let mut rng = test_rng();
+        let (keyrings, directory) = workers();
+        let mut ledger = AcceptanceLedger::new(DelegatorSigningKey::generate(&mut rng), 2);
+        let claim = ResultClaim { session: 7, attempt: 0, result_digest: [9; 32] };
+
+        let mut trackers = [PendingReceipts::new(), PendingReceipts::new()];
+        let first = trackers[0].commit(&keyrings[0], claim, &mut rng).unwrap();
+        assert_eq!(ledger.receive_commitment(&first, &directory).unwrap(), None);
+        assert!(matches!(ledger.accept(7, true, &mut rng), Err(AcceptanceError::MissingCommitments { received: 1, .. })));
+
+        // A worker claiming a different result is refused
+        let conflicting = ResultClaim { result_digest: [8; 32], ..claim };
+        let bad = commit_result(&keyrings[1], conflicting, &mut rng).unwrap();
+        assert_eq!(ledger.receive_commitment(&bad, &directory), Err(AcceptanceError::ConflictingResults(7)));
+
+        let second = trackers[1].commit(&keyrings[1], claim, &mut rng).unwrap();
+        ledger.receive_commitment(&second, &directory).unwrap();
+        let receipt = ledger.accept(7, true, &mut rng).unwrap();
+        assert!(receipt.verify(&ledger.verifying_key()));
+        assert_eq!(ledger.unacknowledged(), Vec::<SessionId>::new());
+
+        // Worker 1's receipt is lost: retrying returns the same receipt
+        trackers[0].record_receipt(receipt.clone(), &ledger.verifying_key()).unwrap();
+        let retry = trackers[1].retries();
+        assert_eq!(retry.len(), 1);
+        let resent = ledger.receive_commitment(&retry[0], &directory).unwrap().unwrap();
+        assert_eq!(resent, receipt);
+        trackers[1].record_receipt(resent, &ledger.verifying_key()).unwrap();
+        assert!(trackers[1].retries().is_empty());
+
+        // The accepted session cannot be re-accepted for another result
+        let later = ResultClaim { attempt: 1, result_digest: [8; 32], ..claim };
+        let replacement = commit_result(&keyrings[0], later, &mut rng).unwrap();
+        assert_eq!(ledger.receive_commitment(&replacement, &directory), Err(AcceptanceError::AlreadyAccepted(7)));
+    }
+
+    #[test]
+    fn test_rejected_session_can_be_retried() {
+        let mut rng = test_rng();
+        let (keyrings, directory) = workers();
+        let mut ledger = AcceptanceLedger::new(DelegatorSigningKey::generate(&mut rng), 2);
+        let claim = ResultClaim { session: 7, attempt: 0, result_digest: [1; 32] };
+        for keyring in &keyrings {
+            ledger.receive_commitment(&commit_result(keyring, claim, &mut rng).unwrap(), &directory).unwrap();
+        }
+        assert_eq!(ledger.accept(7, false, &mut rng), Err(AcceptanceError::Rejected(7)));
+        assert_eq!(ledger.unacknowledged(), vec![7]);
+
+        // The failed attempt cannot be re-committed, a fresh one can
+        let stale = commit_result(&keyrings[0], claim, &mut rng).unwrap();
+        assert!(matches!(ledger.receive_commitment(&stale, &directory), Err(AcceptanceError::StaleAttempt { .. })));
+        let retry = ResultClaim { attempt: 1, result_digest: [2; 32], ..claim };
+        for keyring in &keyrings {
+            ledger.receive_commitment(&commit_result(keyring, retry, &mut rng).unwrap(), &directory).unwrap();
+        }
+        let receipt = ledger.accept(7, true, &mut rng).unwrap();
+        assert_eq!(receipt.claim, retry);
+
+        // A receipt forged under another key is rejected by the worker
+        let mut tracker = PendingReceipts::new();
+        tracker.commit(&keyrings[0], retry, &mut rng).unwrap();
+        let other = DelegatorSigningKey::generate(&mut rng);
+        assert_eq!(tracker.record_receipt(receipt, &other.verifying_key), Err(AcceptanceError::InvalidReceipt(7)));
+    }
+}
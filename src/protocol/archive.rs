@@ -0,0 +1,370 @@
+//! Content-addressed archive of verified delegations
+//!
+//! A `ProofArchive` keeps every proof an operator verified, together with the
+//! verification key and public inputs it was checked against, so the history
+//! can be audited and re-verified later. Each blob is stored once under its
+//! `ContentId`, the SHA-256 of its bytes, and checked against that digest on
+//! every read. An `ArchiveRecord` ties one verification to the three blobs
+//! and its verdict; records are content-addressed the same way, so nothing in
+//! the archive can be edited without changing its identifier.
+//!
+//! Layout on disk:
+//!
+//! - `objects/<id>`: proofs (`DelegationResult::transcript_bytes`), keys
+//!   (`VerifierKey::to_bytes`) and public inputs (field encodings, concatenated)
+//! - `records/<id>`: `ArchiveRecord`s
+
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use ark_ec::pairing::Pairing;
+use ark_ff::Field;
+use ark_std::rand::Rng;
+use sha2::{Digest, Sha256};
+
+use crate::protocol::{verify_transcript, BundleError, VerifierKey};
+use crate::serialization::{field_size, write_field, write_u64, ByteReader, DecodeError, WireFormat};
+
+const OBJECT_DOMAIN: &[u8] = b"EOS-archive-object-v1";
+
+/// Encoding version of `ArchiveRecord`
+pub const ARCHIVE_RECORD_VERSION: u8 = 1;
+
+/// Identifier of an archived blob: the domain-separated SHA-256 of its bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ContentId(pub [u8; 32]);
+
+impl ContentId {
+    pub fn of(bytes: &[u8]) -> Self {
+        ContentId(Sha256::new().chain_update(OBJECT_DOMAIN).chain_update(bytes).finalize().into())
+    }
+
+    pub fn to_hex(&self) -> String {
+        self.0.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    pub fn from_hex(text: &str) -> Option<Self> {
+        if text.len() != 64 || !text.is_ascii() {
+            return None;
+        }
+        let mut id = [0u8; 32];
+        for (byte, pair) in id.iter_mut().zip(text.as_bytes().chunks(2)) {
+            *byte = u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok()?;
+        }
+        Some(ContentId(id))
+    }
+}
+
+impl std::fmt::Display for ContentId {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(&self.to_hex())
+    }
+}
+
+/// Outcome of verifying an archived proof
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Accepted,
+    /// Well-formed, but does not verify or was made for another key
+    Rejected,
+    /// The proof or the public inputs do not decode
+    Malformed,
+}
+
+impl Verdict {
+    fn of(outcome: Result<bool, BundleError>) -> Self {
+        match outcome {
+            Ok(true) => Verdict::Accepted,
+            Ok(false) | Err(BundleError::SeedContextMismatch) => Verdict::Rejected,
+            Err(BundleError::Malformed(_) | BundleError::Streaming(_)) => Verdict::Malformed,
+        }
+    }
+
+    fn tag(self) -> u8 {
+        match self {
+            Verdict::Accepted => 0,
+            Verdict::Rejected => 1,
+            Verdict::Malformed => 2,
+        }
+    }
+}
+
+/// One verification, as retained in the archive
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchiveRecord {
+    pub proof: ContentId,
+    pub verification_key: ContentId,
+    pub public_inputs: ContentId,
+    pub verdict: Verdict,
+    /// Seconds since the Unix epoch
+    pub verified_at: u64,
+}
+
+impl WireFormat for ArchiveRecord {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.push(ARCHIVE_RECORD_VERSION);
+        out.extend_from_slice(&self.proof.0);
+        out.extend_from_slice(&self.verification_key.0);
+        out.extend_from_slice(&self.public_inputs.0);
+        out.push(self.verdict.tag());
+        write_u64(out, self.verified_at);
+    }
+
+    fn decode(reader: &mut ByteReader<'_>) -> Result<Self, DecodeError> {
+        let version = reader.read_u8()?;
+        if version != ARCHIVE_RECORD_VERSION {
+            return Err(DecodeError::InvalidTag(version));
+        }
+        let mut read_id = || -> Result<ContentId, DecodeError> {
+            Ok(ContentId(reader.read_bytes(32)?.try_into().expect("slice has length 32")))
+        };
+        let (proof, verification_key, public_inputs) = (read_id()?, read_id()?, read_id()?);
+        let verdict = match reader.read_u8()? {
+            0 => Verdict::Accepted,
+            1 => Verdict::Rejected,
+            2 => Verdict::Malformed,
+            tag => return Err(DecodeError::InvalidTag(tag)),
+        };
+        Ok(ArchiveRecord { proof, verification_key, public_inputs, verdict, verified_at: reader.read_u64()? })
+    }
+}
+
+/// Public inputs as archived and passed to the C API: field encodings, concatenated
+pub fn encode_public_inputs<F: Field>(inputs: &[F]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(inputs.len() * field_size::<F>());
+    for input in inputs {
+        write_field(&mut out, input);
+    }
+    out
+}
+
+pub fn decode_public_inputs<F: Field>(bytes: &[u8]) -> Result<Vec<F>, DecodeError> {
+    let mut reader = ByteReader::new(bytes);
+    let mut inputs = Vec::with_capacity(bytes.len() / field_size::<F>());
+    while reader.remaining() >= field_size::<F>() {
+        inputs.push(reader.read_field()?);
+    }
+    reader.finish()?;
+    Ok(inputs)
+}
+
+/// Directory of archived proofs, keys, public inputs and verification records
+pub struct ProofArchive {
+    dir: PathBuf,
+}
+
+impl ProofArchive {
+    /// Open an archive, creating its directories if needed
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self, ArchiveError> {
+        let dir = dir.as_ref().to_path_buf();
+        std::fs::create_dir_all(dir.join("objects"))?;
+        std::fs::create_dir_all(dir.join("records"))?;
+        Ok(Self { dir })
+    }
+
+    fn object_path(&self, id: &ContentId) -> PathBuf {
+        self.dir.join("objects").join(id.to_hex())
+    }
+
+    fn record_path(&self, id: &ContentId) -> PathBuf {
+        self.dir.join("records").join(id.to_hex())
+    }
+
+    /// Write `bytes` to `path` unless it is already there
+    fn write_once(path: &Path, bytes: &[u8]) -> Result<(), ArchiveError> {
+        if path.exists() {
+            return Ok(());
+        }
+        // Write then rename so a crash never leaves a torn object behind
+        let name = path.file_name().and_then(|name| name.to_str()).expect("archive paths are hex names");
+        let temporary = path.with_file_name(format!(".{}.tmp", name));
+        std::fs::write(&temporary, bytes)?;
+        std::fs::rename(&temporary, path)?;
+        Ok(())
+    }
+
+    fn read_checked(path: &Path, id: &ContentId) -> Result<Vec<u8>, ArchiveError> {
+        let bytes = match std::fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Err(ArchiveError::NotFound(*id)),
+            Err(e) => return Err(e.into()),
+        };
+        if ContentId::of(&bytes) != *id {
+            return Err(ArchiveError::Corrupted(*id));
+        }
+        Ok(bytes)
+    }
+
+    /// Store a blob; storing the same bytes again is a no-op
+    pub fn put(&self, bytes: &[u8]) -> Result<ContentId, ArchiveError> {
+        let id = ContentId::of(bytes);
+        Self::write_once(&self.object_path(&id), bytes)?;
+        Ok(id)
+    }
+
+    /// Read a blob back, checking it against its identifier
+    pub fn get(&self, id: &ContentId) -> Result<Vec<u8>, ArchiveError> {
+        Self::read_checked(&self.object_path(id), id)
+    }
+
+    pub fn contains(&self, id: &ContentId) -> bool {
+        self.object_path(id).exists()
+    }
+
+    /// Verify a transcript and archive it with its key, inputs and verdict
+    ///
+    /// Rejected and malformed proofs are archived too, so the history shows
+    /// everything that was presented, not only what passed.
+    pub fn verify_and_record<E: Pairing>(
+        &self,
+        key: &VerifierKey<E>,
+        transcript: &[u8],
+        public_inputs: &[E::ScalarField],
+        rng: &mut impl Rng,
+    ) -> Result<(ContentId, ArchiveRecord), ArchiveError> {
+        let verdict = Verdict::of(verify_transcript(key, transcript, public_inputs, rng));
+        let record = ArchiveRecord {
+            proof: self.put(transcript)?,
+            verification_key: self.put(&key.to_bytes())?,
+            public_inputs: self.put(&encode_public_inputs(public_inputs))?,
+            verdict,
+            verified_at: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+        };
+        let bytes = record.to_bytes();
+        let id = ContentId::of(&bytes);
+        Self::write_once(&self.record_path(&id), &bytes)?;
+        Ok((id, record))
+    }
+
+    pub fn record(&self, id: &ContentId) -> Result<ArchiveRecord, ArchiveError> {
+        Ok(ArchiveRecord::from_bytes(&Self::read_checked(&self.record_path(id), id)?)?)
+    }
+
+    /// All records, oldest first
+    pub fn records(&self) -> Result<Vec<(ContentId, ArchiveRecord)>, ArchiveError> {
+        let mut records = Vec::new();
+        for entry in std::fs::read_dir(self.dir.join("records"))? {
+            let name = entry?.file_name();
+            // Skips temporaries left by an interrupted write
+            if let Some(id) = name.to_str().and_then(ContentId::from_hex) {
+                records.push((id, self.record(&id)?));
+            }
+        }
+        records.sort_by(|a, b| a.1.verified_at.cmp(&b.1.verified_at).then_with(|| a.0.cmp(&b.0)));
+        Ok(records)
+    }
+
+    /// Records of every verification of the given proof
+    pub fn records_for_proof(&self, proof: &ContentId) -> Result<Vec<(ContentId, ArchiveRecord)>, ArchiveError> {
+        Ok(self.records()?.into_iter().filter(|(_, record)| record.proof == *proof).collect())
+    }
+
+    /// Records of every proof checked against the given verification key
+    pub fn records_for_key(&self, key: &ContentId) -> Result<Vec<(ContentId, ArchiveRecord)>, ArchiveError> {
+        Ok(self.records()?.into_iter().filter(|(_, record)| record.verification_key == *key).collect())
+    }
+
+    /// Verify an archived record again from the stored blobs
+    pub fn reverify<E: Pairing>(&self, id: &ContentId, rng: &mut impl Rng) -> Result<Verdict, ArchiveError> {
+        let record = self.record(id)?;
+        let key = VerifierKey::<E>::from_bytes(&self.get(&record.verification_key)?)?;
+        let transcript = self.get(&record.proof)?;
+        let Ok(public_inputs) = decode_public_inputs::<E::ScalarField>(&self.get(&record.public_inputs)?) else {
+            return Ok(Verdict::Malformed);
+        };
+        Ok(Verdict::of(verify_transcript(&key, &transcript, &public_inputs, rng)))
+    }
+}
+
+/// Archive error types
+#[derive(Debug)]
+pub enum ArchiveError {
+    Io(std::io::Error),
+    Decode(DecodeError),
+    NotFound(ContentId),
+    /// The stored bytes no longer hash to their identifier
+    Corrupted(ContentId),
+}
+
+impl std::fmt::Display for ArchiveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ArchiveError::Io(e) => write!(f, "Archive I/O error: {}", e),
+            ArchiveError::Decode(e) => write!(f, "Archived value is malformed: {}", e),
+            ArchiveError::NotFound(id) => write!(f, "Nothing archived under {}", id),
+            ArchiveError::Corrupted(id) => write!(f, "Archived object {} does not match its digest", id),
+        }
+    }
+}
+
+impl std::error::Error for ArchiveError {}
+
+impl From<std::io::Error> for ArchiveError {
+    fn from(e: std::io::Error) -> Self {
+        ArchiveError::Io(e)
+    }
+}
+
+impl From<DecodeError> for ArchiveError {
+    fn from(e: DecodeError) -> Self {
+        ArchiveError::Decode(e)
+    }
+}
+
+#[cfg(all(test, feature = "delegator"))]
+mod tests {
+    use super::*;
+    use crate::mpc::{CollaborationMode, ExecCircuit, ShamirSecretSharing};
+    use crate::protocol::EOSProtocol;
+    use ark_bls12_381::{Bls12_381, Fr};
+    use ark_relations::r1cs::ConstraintSystem;
+    use ark_std::test_rng;
+
+    type TestField = Fr;
+    type Protocol = EOSProtocol<Bls12_381, TestField, ShamirSecretSharing<TestField>, CollaborationMode>;
+
+    #[test]
+    fn test_archive_records_and_reverifies_delegations() {
+        let mut rng = test_rng();
+        let executor = ExecCircuit::new(1, 3, ShamirSecretSharing::new());
+        let mut protocol = Protocol::new(executor, CollaborationMode::new(2, true, false), 128, &mut rng);
+        let circuit = ConstraintSystem::new();
+        protocol.preprocessing_state = Some(Protocol::preprocessing(&circuit, 128, &mut rng).unwrap());
+        let witness: Vec<TestField> = (1..=4u64).map(TestField::from).collect();
+        let public_inputs = vec![TestField::from(12u64)];
+        let result = protocol.delegate_computation_seeded(&circuit, &witness, &public_inputs, [2u8; 32]).unwrap();
+        let key = protocol.export_verifier_key().unwrap();
+        let transcript = result.transcript_bytes();
+
+        let dir = std::env::temp_dir().join(format!("eos-archive-{}", std::process::id()));
+        let archive = ProofArchive::open(&dir).unwrap();
+        let (accepted, record) = archive.verify_and_record(&key, &transcript, &public_inputs, &mut rng).unwrap();
+        assert_eq!(record.verdict, Verdict::Accepted);
+        let wrong_inputs = [TestField::from(13u64)];
+        let (rejected, other) = archive.verify_and_record(&key, &transcript, &wrong_inputs, &mut rng).unwrap();
+        assert_eq!(other.verdict, Verdict::Rejected);
+
+        // The proof and key are stored once and shared by both records
+        assert_eq!((other.proof, other.verification_key), (record.proof, record.verification_key));
+        assert_eq!(archive.get(&record.proof).unwrap(), transcript);
+        assert_eq!(std::fs::read_dir(dir.join("objects")).unwrap().count(), 4);
+        assert_eq!(archive.records().unwrap().len(), 2);
+        assert_eq!(archive.records_for_proof(&record.proof).unwrap().len(), 2);
+        assert_eq!(archive.records_for_key(&record.verification_key).unwrap().len(), 2);
+        assert_eq!(archive.record(&accepted).unwrap(), record);
+        assert_eq!(ContentId::from_hex(&accepted.to_hex()), Some(accepted));
+
+        assert_eq!(archive.reverify::<Bls12_381>(&accepted, &mut rng).unwrap(), Verdict::Accepted);
+        assert_eq!(archive.reverify::<Bls12_381>(&rejected, &mut rng).unwrap(), Verdict::Rejected);
+
+        // Editing an archived proof is detected rather than re-verified
+        let path = dir.join("objects").join(record.proof.to_hex());
+        let mut bytes = std::fs::read(&path).unwrap();
+        bytes[0] ^= 1;
+        std::fs::write(&path, &bytes).unwrap();
+        assert!(matches!(archive.reverify::<Bls12_381>(&accepted, &mut rng), Err(ArchiveError::Corrupted(id)) if id == record.proof));
+        assert!(matches!(archive.get(&ContentId([0; 32])), Err(ArchiveError::NotFound(_))));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
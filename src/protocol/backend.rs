@@ -0,0 +1,411 @@
+//! Interchangeable proving backends
+//!
+//! `ProvingBackend` is the interface between the delegation protocol and the
+//! proof system that turns a satisfied circuit into a proof. Sharing,
+//! transport and evaluation only see this trait, so a deployment picks its
+//! backend at runtime from `EosConfig::backend` via `build_backend`, and
+//! benchmarks can sweep `BackendKind::ALL` with the same code.
+
+use ark_ec::pairing::Pairing;
+use ark_ff::PrimeField;
+use ark_groth16::{prepare_verifying_key, Groth16, PreparedVerifyingKey, Proof, ProvingKey};
+#[cfg(feature = "delegator")]
+use ark_poly::{univariate::DensePolynomial, DenseUVPolynomial};
+use ark_relations::lc;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError, Variable};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::rand::RngCore;
+
+use crate::circuit::KZGCommitmentScheme;
+use crate::custom_circuits::{CustomCircuit, Variable as CircuitVariable};
+use crate::evaluation::BackendKind;
+use crate::piop::Transcript;
+use crate::protocol::{verification_key_hash, ConstraintMatrices, StreamingVerifier};
+#[cfg(feature = "delegator")]
+use crate::{
+    piop::PolynomialConsistencyProof,
+    protocol::{delegation_transcript, prove_constraints, CommittedWitness},
+    serialization::WireFormat,
+};
+
+/// A proof system the delegation protocol can run on
+pub trait ProvingBackend<F: PrimeField> {
+    fn kind(&self) -> BackendKind;
+
+    /// Circuit-specific setup; must run before `prove` and `verify`
+    fn setup(&mut self, circuit: &CustomCircuit<F>, rng: &mut dyn RngCore) -> Result<(), BackendError>;
+
+    /// Prove that `circuit` is satisfied, binding the proof to `context`
+    fn prove(&self, circuit: &CustomCircuit<F>, context: &[u8], rng: &mut dyn RngCore) -> Result<Vec<u8>, BackendError>;
+
+    /// Check a proof against the circuit's public inputs and `context`
+    fn verify(
+        &self,
+        public_inputs: &[F],
+        context: &[u8],
+        proof: &[u8],
+        rng: &mut dyn RngCore,
+    ) -> Result<bool, BackendError>;
+}
+
+/// Instantiate the backend selected by `kind`
+pub fn build_backend<E: Pairing>(kind: BackendKind) -> Box<dyn ProvingBackend<E::ScalarField>> {
+    match kind {
+        BackendKind::Piop => Box::new(PiopBackend::<E>::new()),
+        BackendKind::Groth16 => Box::new(Groth16Backend::<E>::new()),
+    }
+}
+
+/// Digest of a circuit's constraints, independent of its assignment
+fn shape_fingerprint<F: PrimeField>(circuit: &CustomCircuit<F>) -> [u8; 32] {
+    let mut transcript = Transcript::new(b"EOS-custom-circuit-fingerprint-v2");
+    let num_instance = circuit.num_instance_variables();
+    for count in [circuit.private_witnesses.len(), circuit.public_inputs.len()] {
+        transcript.append_message(b"count", &(count as u64).to_le_bytes());
+    }
+    for (label, constraints) in [
+        (b"mul", &circuit.multiplication_constraints),
+        (b"add", &circuit.addition_constraints),
+    ] {
+        transcript.append_message(label, &(constraints.len() as u64).to_le_bytes());
+        for &(a, b, c) in constraints {
+            for wire in [a, b, c] {
+                transcript.append_message(b"wire", &(wire.index(num_instance) as u64).to_le_bytes());
+            }
+        }
+    }
+    transcript.challenge_bytes(b"fingerprint")
+}
+
+/// The circuit's constraints as R1CS matrices over `z = (1, public_inputs, private_witnesses)`
+///
+/// Rows follow `CircuitSynthesizer`: multiplication constraints `a·b = c`,
+/// then addition constraints `(a + b)·1 = c`.
+fn constraint_matrices<F: PrimeField>(circuit: &CustomCircuit<F>) -> ConstraintMatrices<F> {
+    let num_instance = circuit.num_instance_variables();
+    let column = |wire: CircuitVariable| wire.index(num_instance);
+    let one = CircuitVariable::ONE.index(num_instance);
+    let mut matrices = ConstraintMatrices { a_matrix: Vec::new(), b_matrix: Vec::new(), c_matrix: Vec::new() };
+    for &(a, b, c) in &circuit.multiplication_constraints {
+        matrices.a_matrix.push(vec![(column(a), F::one())]);
+        matrices.b_matrix.push(vec![(column(b), F::one())]);
+        matrices.c_matrix.push(vec![(column(c), F::one())]);
+    }
+    for &(a, b, c) in &circuit.addition_constraints {
+        matrices.a_matrix.push(vec![(column(a), F::one()), (column(b), F::one())]);
+        matrices.b_matrix.push(vec![(one, F::one())]);
+        matrices.c_matrix.push(vec![(column(c), F::one())]);
+    }
+    matrices
+}
+
+/// Commitment key, the key hash bound into every transcript and the circuit's matrices
+type PiopKeys<E> = (KZGCommitmentScheme<E>, [u8; 32], ConstraintMatrices<<E as Pairing>::ScalarField>);
+
+/// KZG commitment to the private witness with the constraint proof that it
+/// satisfies the circuit (see `prove_constraints`)
+pub struct PiopBackend<E: Pairing> {
+    keys: Option<PiopKeys<E>>,
+}
+
+impl<E: Pairing> PiopBackend<E> {
+    pub fn new() -> Self {
+        Self { keys: None }
+    }
+
+    /// Prove for the circuit's assignment without checking it first
+    #[cfg(feature = "delegator")]
+    fn prove_assignment(
+        &self,
+        circuit: &CustomCircuit<E::ScalarField>,
+        context: &[u8],
+    ) -> Result<Vec<u8>, BackendError> {
+        let (scheme, key_hash, matrices) = self.keys.as_ref().ok_or(BackendError::NotSetUp)?;
+        let witness = &circuit.private_witnesses;
+        if witness.len() > scheme.powers_of_g.len() {
+            return Err(BackendError::Setup(format!(
+                "witness of length {} exceeds SRS size {}",
+                witness.len(),
+                scheme.powers_of_g.len()
+            )));
+        }
+        let polynomial = DensePolynomial::from_coefficients_slice(witness);
+        let commitment = scheme.commit(&polynomial);
+
+        let mut transcript = delegation_transcript(key_hash, context, &circuit.public_inputs);
+        transcript.append_point::<E::G1>(b"witness_commitment", &commitment.commitment);
+        let point = transcript.challenge_field(b"opening_point");
+        let opening = scheme.open(&polynomial, point);
+
+        let (sumcheck_proofs, claims) = prove_constraints(
+            scheme,
+            matrices,
+            &circuit.public_inputs,
+            CommittedWitness { witness, polynomial: &polynomial, commitment: &commitment },
+            &mut transcript,
+            None::<&mut rand_chacha::ChaCha20Rng>,
+        )
+        .map_err(|e| BackendError::Setup(e.to_string()))?;
+        let proof = PolynomialConsistencyProof {
+            witness_commitments: vec![commitment],
+            consistency_proofs: vec![opening],
+            sumcheck_proofs,
+            constraint_claims: Some(claims),
+        };
+        Ok(proof.to_bytes())
+    }
+
+    /// The constraint prover lives with the delegator
+    #[cfg(not(feature = "delegator"))]
+    fn prove_assignment(
+        &self,
+        _circuit: &CustomCircuit<E::ScalarField>,
+        _context: &[u8],
+    ) -> Result<Vec<u8>, BackendError> {
+        self.keys.as_ref().ok_or(BackendError::NotSetUp)?;
+        Err(BackendError::Setup("PIOP proving needs the `delegator` feature".to_string()))
+    }
+}
+
+impl<E: Pairing> Default for PiopBackend<E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<E: Pairing> ProvingBackend<E::ScalarField> for PiopBackend<E> {
+    fn kind(&self) -> BackendKind {
+        BackendKind::Piop
+    }
+
+    fn setup(&mut self, circuit: &CustomCircuit<E::ScalarField>, rng: &mut dyn RngCore) -> Result<(), BackendError> {
+        let max_degree = circuit.num_variables.next_power_of_two().max(2);
+        let scheme = KZGCommitmentScheme::setup(max_degree, &mut { rng });
+        let key_hash = verification_key_hash::<E>(&scheme, &shape_fingerprint(circuit));
+        self.keys = Some((scheme, key_hash, constraint_matrices(circuit)));
+        Ok(())
+    }
+
+    fn prove(
+        &self,
+        circuit: &CustomCircuit<E::ScalarField>,
+        context: &[u8],
+        _rng: &mut dyn RngCore,
+    ) -> Result<Vec<u8>, BackendError> {
+        self.keys.as_ref().ok_or(BackendError::NotSetUp)?;
+        let report = circuit.check_witness();
+        if !report.is_satisfied() {
+            return Err(BackendError::UnsatisfiedCircuit(report.to_string()));
+        }
+        self.prove_assignment(circuit, context)
+    }
+
+    fn verify(
+        &self,
+        public_inputs: &[E::ScalarField],
+        context: &[u8],
+        proof: &[u8],
+        rng: &mut dyn RngCore,
+    ) -> Result<bool, BackendError> {
+        let (scheme, key_hash, matrices) = self.keys.as_ref().ok_or(BackendError::NotSetUp)?;
+        StreamingVerifier::<E>::new(scheme, *key_hash, matrices)
+            .with_context(context)
+            .verify(proof, public_inputs, &mut { rng })
+            .map_err(|e| BackendError::MalformedProof(e.to_string()))
+    }
+}
+
+/// Groth16 over an R1CS translation of the circuit
+///
+/// The context is hashed to a field element and appended as an extra public
+/// input, so a proof made for one context fails under any other.
+pub struct Groth16Backend<E: Pairing> {
+    keys: Option<(ProvingKey<E>, PreparedVerifyingKey<E>)>,
+}
+
+impl<E: Pairing> Groth16Backend<E> {
+    pub fn new() -> Self {
+        Self { keys: None }
+    }
+}
+
+impl<E: Pairing> Default for Groth16Backend<E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn context_input<F: PrimeField>(context: &[u8]) -> F {
+    let mut transcript = Transcript::new(b"EOS-groth16-context-v1");
+    transcript.append_message(b"context", context);
+    transcript.challenge_field(b"context_input")
+}
+
+/// `CustomCircuit` as an arkworks constraint synthesizer
+struct CircuitSynthesizer<'a, F: PrimeField> {
+    circuit: &'a CustomCircuit<F>,
+    context: F,
+}
+
+impl<F: PrimeField> ConstraintSynthesizer<F> for CircuitSynthesizer<'_, F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        // Allocation order keeps the circuit's numbering: public inputs are
+        // instance variables 1.., the context input follows them, and
+        // private witnesses are witness variables 0..
+        for value in &self.circuit.public_inputs {
+            cs.new_input_variable(|| Ok(*value))?;
+        }
+        let context = cs.new_input_variable(|| Ok(self.context))?;
+        for value in &self.circuit.private_witnesses {
+            cs.new_witness_variable(|| Ok(*value))?;
+        }
+        let wire = |variable: CircuitVariable| -> Result<Variable, SynthesisError> {
+            self.circuit
+                .variable(variable)
+                .map(|_| variable.into())
+                .ok_or(SynthesisError::AssignmentMissing)
+        };
+
+        for &(a, b, c) in &self.circuit.multiplication_constraints {
+            cs.enforce_constraint(lc!() + wire(a)?, lc!() + wire(b)?, lc!() + wire(c)?)?;
+        }
+        for &(a, b, c) in &self.circuit.addition_constraints {
+            cs.enforce_constraint(lc!() + wire(a)? + wire(b)?, lc!() + Variable::One, lc!() + wire(c)?)?;
+        }
+        // An input that appears in no constraint would not be bound by the proof
+        cs.enforce_constraint(lc!() + context, lc!() + Variable::One, lc!() + context)?;
+        Ok(())
+    }
+}
+
+impl<E: Pairing> ProvingBackend<E::ScalarField> for Groth16Backend<E> {
+    fn kind(&self) -> BackendKind {
+        BackendKind::Groth16
+    }
+
+    fn setup(&mut self, circuit: &CustomCircuit<E::ScalarField>, rng: &mut dyn RngCore) -> Result<(), BackendError> {
+        let synthesizer = CircuitSynthesizer { circuit, context: E::ScalarField::from(0u64) };
+        let proving_key = Groth16::<E>::generate_random_parameters_with_reduction(synthesizer, &mut { rng })
+            .map_err(|e| BackendError::Setup(e.to_string()))?;
+        let verifying_key = prepare_verifying_key(&proving_key.vk);
+        self.keys = Some((proving_key, verifying_key));
+        Ok(())
+    }
+
+    fn prove(
+        &self,
+        circuit: &CustomCircuit<E::ScalarField>,
+        context: &[u8],
+        rng: &mut dyn RngCore,
+    ) -> Result<Vec<u8>, BackendError> {
+        let (proving_key, _) = self.keys.as_ref().ok_or(BackendError::NotSetUp)?;
+        let report = circuit.check_witness();
+        if !report.is_satisfied() {
+            return Err(BackendError::UnsatisfiedCircuit(report.to_string()));
+        }
+        let synthesizer = CircuitSynthesizer { circuit, context: context_input(context) };
+        let proof = Groth16::<E>::create_random_proof_with_reduction(synthesizer, proving_key, &mut { rng })
+            .map_err(|e| BackendError::Setup(e.to_string()))?;
+        let mut bytes = Vec::new();
+        proof
+            .serialize_compressed(&mut bytes)
+            .expect("serialization into a Vec cannot fail");
+        Ok(bytes)
+    }
+
+    fn verify(
+        &self,
+        public_inputs: &[E::ScalarField],
+        context: &[u8],
+        proof: &[u8],
+        _rng: &mut dyn RngCore,
+    ) -> Result<bool, BackendError> {
+        let (_, verifying_key) = self.keys.as_ref().ok_or(BackendError::NotSetUp)?;
+        let proof = Proof::<E>::deserialize_compressed(proof).map_err(|e| BackendError::MalformedProof(e.to_string()))?;
+        let mut inputs = public_inputs.to_vec();
+        inputs.push(context_input(context));
+        if inputs.len() + 1 != verifying_key.vk.gamma_abc_g1.len() {
+            return Ok(false);
+        }
+        Groth16::<E>::verify_proof(verifying_key, &proof, &inputs).map_err(|e| BackendError::MalformedProof(e.to_string()))
+    }
+}
+
+/// Proving backend error types
+#[derive(Debug, Clone, PartialEq)]
+pub enum BackendError {
+    NotSetUp,
+    /// Carries the witness report naming every violated constraint
+    UnsatisfiedCircuit(String),
+    Setup(String),
+    MalformedProof(String),
+}
+
+impl std::fmt::Display for BackendError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            BackendError::NotSetUp => write!(f, "Backend setup has not been run"),
+            BackendError::UnsatisfiedCircuit(report) => write!(f, "Circuit is not satisfied: {}", report),
+            BackendError::Setup(msg) => write!(f, "Backend error: {}", msg),
+            BackendError::MalformedProof(msg) => write!(f, "Malformed proof: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for BackendError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::custom_circuits::CircuitTemplates;
+    use ark_bls12_381::{Bls12_381, Fr};
+    use ark_std::test_rng;
+
+    type TestField = Fr;
+
+    #[test]
+    fn test_backends_are_interchangeable() {
+        let mut rng = test_rng();
+        let circuit = CircuitTemplates::repeated_squaring(TestField::from(3u64), 8);
+
+        // The same code drives every backend
+        for kind in BackendKind::ALL {
+            let mut backend = build_backend::<Bls12_381>(kind);
+            assert_eq!(backend.kind(), kind);
+            assert_eq!(backend.prove(&circuit, b"ctx", &mut rng).unwrap_err(), BackendError::NotSetUp);
+
+            backend.setup(&circuit, &mut rng).unwrap();
+            let proof = backend.prove(&circuit, b"ctx", &mut rng).unwrap();
+            assert!(backend.verify(&circuit.public_inputs, b"ctx", &proof, &mut rng).unwrap(), "{:?}", kind);
+            assert!(!backend.verify(&circuit.public_inputs, b"other", &proof, &mut rng).unwrap(), "{:?}", kind);
+
+            let mut wrong_inputs = circuit.public_inputs.clone();
+            wrong_inputs[0] += TestField::from(1u64);
+            assert!(!backend.verify(&wrong_inputs, b"ctx", &proof, &mut rng).unwrap(), "{:?}", kind);
+
+            let mut unsatisfied = circuit.clone();
+            unsatisfied.private_witnesses[0] += TestField::from(1u64);
+            assert!(matches!(
+                backend.prove(&unsatisfied, b"ctx", &mut rng),
+                Err(BackendError::UnsatisfiedCircuit(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_piop_backend_rejects_cheating_prover() {
+        let mut rng = test_rng();
+        let circuit = CircuitTemplates::repeated_squaring(TestField::from(3u64), 8);
+        let mut backend = PiopBackend::<Bls12_381>::new();
+        backend.setup(&circuit, &mut rng).unwrap();
+        let proof = backend.prove_assignment(&circuit, b"ctx").unwrap();
+        assert!(backend.verify(&circuit.public_inputs, b"ctx", &proof, &mut rng).unwrap());
+
+        // A prover that skips the witness check still produces a well-formed
+        // proof, but the constraint proof fails against the matrices from setup
+        let mut cheating = circuit.clone();
+        cheating.private_witnesses[0] += TestField::from(1u64);
+        let proof = backend.prove_assignment(&cheating, b"ctx").unwrap();
+        assert!(!backend.verify(&cheating.public_inputs, b"ctx", &proof, &mut rng).unwrap());
+    }
+}
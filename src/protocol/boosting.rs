@@ -0,0 +1,171 @@
+//! Boosting confidence in a result with extra openings
+//!
+//! A delegation result opens the witness commitment at a single point
+//! derived from the transcript. A delegator who finds that borderline (a
+//! small field, a large circuit, a worker it does not trust) can ask for
+//! further openings of the same commitment at points it samples itself,
+//! instead of running the delegation again. Each independent point adds
+//! roughly log2(|F| / degree) bits of soundness by Schwartz–Zippel.
+//!
+//! All the points are answered by one multi-point opening of the witness
+//! polynomial, two group elements however many points were asked for.
+//! Every extra point still reveals one more evaluation of the witness, so
+//! the prover records each with the leakage monitor under
+//! `witness_opening` and a policy can refuse them.
+
+use ark_ec::pairing::Pairing;
+use ark_ff::PrimeField;
+use ark_poly::{univariate::DensePolynomial, DenseUVPolynomial};
+use ark_std::rand::Rng;
+
+use crate::circuit::{MultiPointOpeningProof, PolynomialCommitment};
+use crate::mpc::{ExecutionError, OperationMode, SecretSharing};
+use crate::protocol::{DelegationResult, EOSError, EOSProtocol, VerificationCheck, VerificationOutcome};
+use crate::serialization::WireFormat;
+
+/// Extra challenge points chosen by the delegator
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BoostRequest<F: PrimeField> {
+    pub points: Vec<F>,
+}
+
+impl<F: PrimeField> BoostRequest<F> {
+    /// `count` fresh points, independent of the proof transcript
+    pub fn sample<R: Rng + ?Sized>(count: usize, rng: &mut R) -> Self {
+        Self { points: (0..count).map(|_| F::rand(rng)).collect() }
+    }
+
+    /// Points needed for `target_bits` of soundness against a witness of length `degree`
+    pub fn for_security<R: Rng + ?Sized>(target_bits: usize, degree: usize, rng: &mut R) -> Self {
+        let per_point = point_soundness_bits::<F>(degree).max(1.0);
+        Self::sample((target_bits as f64 / per_point).ceil().max(1.0) as usize, rng)
+    }
+
+    /// Soundness the extra points add for a witness of length `degree`
+    pub fn soundness_bits(&self, degree: usize) -> f64 {
+        point_soundness_bits::<F>(degree) * self.points.len() as f64
+    }
+}
+
+fn point_soundness_bits<F: PrimeField>(degree: usize) -> f64 {
+    (F::MODULUS_BIT_SIZE - 1) as f64 - (degree.max(1) as f64).log2()
+}
+
+impl<E, F, SS, OM> EOSProtocol<E, F, SS, OM>
+where
+    E: Pairing<ScalarField = F>,
+    F: PrimeField,
+    SS: SecretSharing<F>,
+    OM: OperationMode<F, SS>,
+{
+    /// Prover side: open the committed witness at every requested point
+    ///
+    /// The points must be distinct and there must be at least one.
+    pub fn answer_boost(
+        &self,
+        witness: &[F],
+        request: &BoostRequest<F>,
+    ) -> Result<MultiPointOpeningProof<F, E::G1>, EOSError> {
+        let points = &request.points;
+        if points.is_empty() || points.iter().enumerate().any(|(i, point)| points[..i].contains(point)) {
+            return Err(EOSError::CommitmentError("boost request needs distinct points".to_string()));
+        }
+        if witness.len() > self.commitment_scheme.powers_of_g.len() {
+            return Err(EOSError::CommitmentError(format!(
+                "witness of length {} exceeds SRS size {}",
+                witness.len(),
+                self.commitment_scheme.powers_of_g.len()
+            )));
+        }
+        self.circuit_executor
+            .leakage
+            .record("witness_opening", request.points.len())
+            .map_err(|e| EOSError::MPCError(ExecutionError::RevealBlocked(e)))?;
+        let polynomial = DensePolynomial::from_coefficients_slice(witness);
+        Ok(self.commitment_scheme.open_at_points(&polynomial, points))
+    }
+
+    /// Check the extra openings against the commitment `result` publishes
+    ///
+    /// Rejects with `CommitmentOpening` at the first requested point if the
+    /// openings are for other points or do not match the commitment. The
+    /// result itself should already have passed `verify_computation`.
+    pub fn verify_boost(
+        &self,
+        result: &DelegationResult<E, F>,
+        request: &BoostRequest<F>,
+        response: &MultiPointOpeningProof<F, E::G1>,
+    ) -> Result<VerificationOutcome<F>, EOSError> {
+        let start = std::time::Instant::now();
+        let challenge = request.points.first().copied();
+        let [commitment] = result.polynomial_commitments.as_slice() else {
+            return Err(EOSError::CommitmentError("result publishes no single witness commitment".to_string()));
+        };
+        let commitment = PolynomialCommitment::<E::G1>::from_bytes(commitment)
+            .map_err(|e| EOSError::CommitmentError(format!("malformed commitment: {}", e)))?;
+        if response.points != request.points || !self.commitment_scheme.verify_at_points(&commitment, response) {
+            return Ok(VerificationOutcome::Rejected {
+                check: VerificationCheck::CommitmentOpening,
+                challenge,
+                elapsed: start.elapsed(),
+            });
+        }
+        Ok(VerificationOutcome::Accepted { challenge, elapsed: start.elapsed() })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::Goldilocks;
+    use crate::mpc::{CollaborationMode, ExecCircuit, RevealPolicy, ShamirSecretSharing};
+    use ark_bls12_381::{Bls12_381, Fr};
+    use ark_relations::r1cs::ConstraintSystem;
+    use ark_std::test_rng;
+
+    type TestField = Fr;
+    type Protocol = EOSProtocol<Bls12_381, TestField, ShamirSecretSharing<TestField>, CollaborationMode>;
+
+    #[test]
+    fn test_boost_opens_committed_witness_at_fresh_points() {
+        let mut rng = test_rng();
+        let executor = ExecCircuit::new(1, 3, ShamirSecretSharing::new());
+        let mut protocol = Protocol::new(executor, CollaborationMode::new(2, true, false), 128, &mut rng);
+        let circuit = ConstraintSystem::new();
+        protocol.preprocessing_state = Some(Protocol::preprocessing(&circuit, 128, &mut rng).unwrap());
+        let witness: Vec<TestField> = (1..=4u64).map(TestField::from).collect();
+        let public_inputs = [TestField::from(10u64)];
+        let result = protocol.delegate_computation(&circuit, &witness, &public_inputs, &mut rng).unwrap();
+        assert!(protocol.verify_computation(&result, &public_inputs).unwrap().is_accepted());
+
+        let request = BoostRequest::sample(3, &mut rng);
+        let response = protocol.answer_boost(&witness, &request).unwrap();
+        assert!(protocol.verify_boost(&result, &request, &response).unwrap().is_accepted());
+        assert_eq!(protocol.circuit_executor.leakage.counts_by_label().get("witness_opening"), Some(&4));
+
+        // Openings of another witness, or at other points, do not match the commitment
+        let mut other = witness.clone();
+        other[0] += TestField::from(1u64);
+        let forged = protocol.answer_boost(&other, &request).unwrap();
+        let outcome = protocol.verify_boost(&result, &request, &forged).unwrap();
+        assert_eq!(outcome.failed_check(), Some(VerificationCheck::CommitmentOpening));
+        assert_eq!(outcome.challenge(), Some(request.points[0]));
+        let moved = protocol.answer_boost(&witness, &BoostRequest::sample(3, &mut rng)).unwrap();
+        assert!(!protocol.verify_boost(&result, &request, &moved).unwrap().is_accepted());
+
+        assert!(protocol.answer_boost(&witness, &BoostRequest { points: vec![] }).is_err());
+
+        // A policy that forbids witness openings refuses the boost
+        protocol.circuit_executor.leakage.set_policy(RevealPolicy::allow_only(["output"]));
+        assert!(matches!(
+            protocol.answer_boost(&witness, &request),
+            Err(EOSError::MPCError(ExecutionError::RevealBlocked(_)))
+        ));
+
+        // On a 64-bit field one point is not enough for 128 bits
+        let request = BoostRequest::<Goldilocks>::for_security(128, 1 << 10, &mut rng);
+        assert_eq!(request.points.len(), 3);
+        assert!(request.soundness_bits(1 << 10) >= 128.0);
+        assert_eq!(BoostRequest::<TestField>::for_security(128, 1 << 10, &mut rng).points.len(), 1);
+    }
+}
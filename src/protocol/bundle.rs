@@ -0,0 +1,568 @@
+//! Self-contained, publicly verifiable proof bundles
+//!
+//! A `ProofBundle` packs everything a third party needs to check a
+//! delegation: the commitment verification key, the circuit, the transcript
+//! context, the public inputs and the serialized proof. `verify_bundle`
+//! checks it without an `EOSProtocol` instance or any preprocessing state:
+//! the circuit's matrices must hash to the bundle's `circuit_fingerprint`,
+//! and the proof's constraint proof is always checked against them, so an
+//! accepted bundle shows the committed witness satisfies that circuit.
+//! Auditors should compare `BundleVerificationKey::fingerprint` and
+//! `circuit_fingerprint` against the values published for the deployment
+//! before trusting a result.
+//!
+//! A verifier that checks many results for one deployment can instead hold a
+//! `VerifierKey` and pass each `DelegationResult::transcript_bytes` to
+//! `verify_transcript`. Both paths only need the verifier core and are
+//! available in `verifier-only` builds.
+
+use ark_ec::pairing::Pairing;
+#[cfg(feature = "delegator")]
+use ark_ff::Field;
+use ark_std::rand::Rng;
+use sha2::{Digest, Sha256};
+
+use crate::circuit::KZGCommitmentScheme;
+#[cfg(feature = "delegator")]
+use crate::mpc::{OperationMode, SecretSharing};
+use crate::piop::{PolynomialConsistencyProof, TranscriptHash};
+#[cfg(feature = "delegator")]
+use crate::protocol::{DelegationResult, EOSError, EOSProtocol};
+use crate::protocol::{
+    circuit_fingerprint, seed_context, verification_key_hash, CircuitParameters, ConstraintMatrices, StreamingError,
+    StreamingVerifier,
+};
+use crate::serialization::{
+    field_size, write_field, write_len, write_point, ByteReader, DecodeError, FieldEncoding, WireFormat,
+};
+
+/// Encoding version of `ProofBundle`
+///
+/// Version 2 added the transcript hash. Version 3 moved `h` and `h_tau`
+/// into G2; earlier bundles carry G1 stand-ins that no pairing check can
+/// use, so they are rejected. Version 4 added the circuit; earlier bundles
+/// cannot have their constraint proof checked, so they are rejected too.
+pub const BUNDLE_VERSION: u8 = 4;
+
+/// Encoding version of `VerifierKey`
+///
+/// Version 2 added the circuit, for the same reason as bundle version 4.
+pub const VERIFIER_KEY_VERSION: u8 = 2;
+
+/// Public parameters needed to check commitment openings
+#[derive(Debug, Clone, PartialEq)]
+pub struct BundleVerificationKey<E: Pairing> {
+    pub g: E::G1Affine,
+    pub h: E::G2Affine,
+    pub h_tau: E::G2Affine,
+}
+
+impl<E: Pairing> BundleVerificationKey<E> {
+    pub fn from_scheme(scheme: &KZGCommitmentScheme<E>) -> Self {
+        Self {
+            g: scheme.powers_of_g[0],
+            h: scheme.verification_key.0,
+            h_tau: scheme.verification_key.1,
+        }
+    }
+
+    /// Verifier-only commitment scheme
+    pub fn scheme(&self) -> KZGCommitmentScheme<E> {
+        KZGCommitmentScheme::from_verification_key(self.g, (self.h, self.h_tau))
+    }
+
+    /// Digest identifying this key, for comparison with a published value
+    pub fn fingerprint(&self) -> [u8; 32] {
+        let mut bytes = Vec::new();
+        write_point::<E::G1>(&mut bytes, &self.g);
+        write_point::<E::G2>(&mut bytes, &self.h);
+        write_point::<E::G2>(&mut bytes, &self.h_tau);
+        Sha256::new()
+            .chain_update(b"EOS-bundle-vk")
+            .chain_update(bytes)
+            .finalize()
+            .into()
+    }
+}
+
+/// Everything needed to verify one delegation
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProofBundle<E: Pairing> {
+    pub verification_key: BundleVerificationKey<E>,
+    /// Fingerprint of the circuit the proof is for
+    pub circuit_fingerprint: [u8; 32],
+    /// The circuit itself, which must hash to `circuit_fingerprint`
+    pub circuit: CircuitParameters<E::ScalarField>,
+    /// Transcript context the proof was bound to
+    pub context: Vec<u8>,
+    /// Hash function of the delegation transcript
+    pub transcript_hash: TranscriptHash,
+    pub public_inputs: Vec<E::ScalarField>,
+    /// Commitment to the prover seed, for seeded delegations
+    pub seed_commitment: Option<[u8; 32]>,
+    /// Serialized `PolynomialConsistencyProof`
+    pub proof: Vec<u8>,
+}
+
+impl<E: Pairing> ProofBundle<E> {
+    /// The deployment key this bundle's proof is checked against
+    pub fn verifier_key(&self) -> VerifierKey<E> {
+        VerifierKey {
+            verification_key: self.verification_key.clone(),
+            circuit_fingerprint: self.circuit_fingerprint,
+            circuit: self.circuit.clone(),
+            transcript_hash: self.transcript_hash,
+        }
+    }
+
+    /// Public inputs as consecutive 32-byte big-endian words, the layout
+    /// on-chain verifiers take as calldata
+    pub fn evm_public_inputs(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(32 * self.public_inputs.len());
+        for input in &self.public_inputs {
+            FieldEncoding::EVM_WORD.encode_into(&mut out, input);
+        }
+        out
+    }
+}
+
+/// Everything a deployment fixes for its verifiers: the commitment key, the
+/// circuit and the transcript hash
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerifierKey<E: Pairing> {
+    pub verification_key: BundleVerificationKey<E>,
+    pub circuit_fingerprint: [u8; 32],
+    /// The circuit itself, which must hash to `circuit_fingerprint`
+    pub circuit: CircuitParameters<E::ScalarField>,
+    pub transcript_hash: TranscriptHash,
+}
+
+impl<E: Pairing> VerifierKey<E> {
+    /// Hash bound into every transcript produced under this key
+    pub fn key_hash(&self) -> [u8; 32] {
+        verification_key_hash::<E>(&self.verification_key.scheme(), &self.circuit_fingerprint)
+    }
+}
+
+/// The circuit's constraint matrices, if the circuit is the one `fingerprint` names
+fn pinned_matrices<'c, F: ark_ff::Field>(
+    circuit: &'c CircuitParameters<F>,
+    fingerprint: &[u8; 32],
+) -> Option<&'c ConstraintMatrices<F>> {
+    (circuit_fingerprint(circuit) == *fingerprint).then_some(&circuit.constraint_matrices)
+}
+
+#[cfg(feature = "delegator")]
+impl<E: Pairing, F: Field> DelegationResult<E, F> {
+    /// Transcript context of a standalone delegation producing this result
+    pub fn transcript_context(&self) -> Vec<u8> {
+        self.seed_commitment.as_ref().map(seed_context).unwrap_or_default()
+    }
+}
+
+#[cfg(feature = "delegator")]
+impl<E, F, SS, OM> EOSProtocol<E, F, SS, OM>
+where
+    E: Pairing<ScalarField = F>,
+    F: ark_ff::PrimeField,
+    SS: SecretSharing<F>,
+    OM: OperationMode<F, SS>,
+{
+    /// Export a bundle for a result produced under `context`
+    ///
+    /// Standalone delegations use `result.transcript_context()`; session
+    /// delegations use `delegator_context(id)`.
+    pub fn export_bundle(
+        &self,
+        result: &DelegationResult<E, F>,
+        public_inputs: &[F],
+        context: &[u8],
+    ) -> Result<ProofBundle<E>, EOSError> {
+        let proof = result
+            .piop_proof
+            .clone()
+            .ok_or_else(|| EOSError::PIOPError("result carries no proof".to_string()))?;
+        let state = self.preprocessing_state
+            .as_ref()
+            .ok_or(EOSError::PreprocessingNotDone)?;
+        Ok(ProofBundle {
+            verification_key: BundleVerificationKey::from_scheme(&self.commitment_scheme),
+            circuit_fingerprint: circuit_fingerprint(&state.circuit_params),
+            circuit: state.circuit_params.clone(),
+            context: context.to_vec(),
+            transcript_hash: self.params.transcript_hash,
+            public_inputs: public_inputs.to_vec(),
+            seed_commitment: result.seed_commitment,
+            proof,
+        })
+    }
+
+    /// Export the key verifiers of this deployment check transcripts against
+    pub fn export_verifier_key(&self) -> Result<VerifierKey<E>, EOSError> {
+        let state = self.preprocessing_state
+            .as_ref()
+            .ok_or(EOSError::PreprocessingNotDone)?;
+        Ok(VerifierKey {
+            verification_key: BundleVerificationKey::from_scheme(&self.commitment_scheme),
+            circuit_fingerprint: circuit_fingerprint(&state.circuit_params),
+            circuit: state.circuit_params.clone(),
+            transcript_hash: self.params.transcript_hash,
+        })
+    }
+}
+
+/// Verify a bundle with no protocol state
+///
+/// Returns `Ok(false)` when the bundle's circuit does not hash to its
+/// fingerprint or the proof does not verify against that circuit. `rng`
+/// supplies batching weights and must not be predictable by the prover.
+pub fn verify_bundle<E: Pairing>(bundle: &ProofBundle<E>, rng: &mut impl Rng) -> Result<bool, BundleError> {
+    if let Some(commitment) = &bundle.seed_commitment {
+        if bundle.context != seed_context(commitment) {
+            return Err(BundleError::SeedContextMismatch);
+        }
+    }
+    let Some(matrices) = pinned_matrices(&bundle.circuit, &bundle.circuit_fingerprint) else {
+        return Ok(false);
+    };
+    let scheme = bundle.verification_key.scheme();
+    let key_hash = verification_key_hash::<E>(&scheme, &bundle.circuit_fingerprint);
+    StreamingVerifier::<E>::new(&scheme, key_hash, matrices)
+        .with_context(&bundle.context)
+        .with_transcript_hash(bundle.transcript_hash)
+        .verify(bundle.proof.as_slice(), &bundle.public_inputs, rng)
+        .map_err(BundleError::Streaming)
+}
+
+/// Verify the transcript of a standalone or seeded delegation
+///
+/// `transcript` is `DelegationResult::transcript_bytes`. The context is the
+/// one such delegations use, derived from the seed commitment; session and
+/// joint proofs need `verify_bundle` with their context. Returns `Ok(false)`
+/// when the transcript was produced for another key, the key's circuit does
+/// not hash to its fingerprint, or the proof does not verify against that
+/// circuit.
+pub fn verify_transcript<E: Pairing>(
+    key: &VerifierKey<E>,
+    transcript: &[u8],
+    public_inputs: &[E::ScalarField],
+    rng: &mut impl Rng,
+) -> Result<bool, BundleError> {
+    let mut reader = ByteReader::new(transcript);
+    let proof_len = reader.read_len(1)?;
+    let proof = reader.read_bytes(proof_len)?;
+    let num_commitments = reader.read_len(4)?;
+    let mut published = Vec::with_capacity(num_commitments);
+    for _ in 0..num_commitments {
+        let len = reader.read_len(1)?;
+        published.push(reader.read_bytes(len)?);
+    }
+    let seed_commitment: Option<[u8; 32]> = match reader.read_u8()? {
+        0 => None,
+        1 => Some(reader.read_bytes(32)?.try_into().expect("slice has length 32")),
+        tag => return Err(DecodeError::InvalidTag(tag).into()),
+    };
+    let key_hash: [u8; 32] = reader.read_bytes(32)?.try_into().expect("slice has length 32");
+    reader.finish()?;
+
+    let Some(matrices) = pinned_matrices(&key.circuit, &key.circuit_fingerprint) else {
+        return Ok(false);
+    };
+    let scheme = key.verification_key.scheme();
+    if key_hash != verification_key_hash::<E>(&scheme, &key.circuit_fingerprint) {
+        return Ok(false);
+    }
+    // The commitments listed next to the proof must be the ones it opens
+    let decoded = PolynomialConsistencyProof::<E::ScalarField, E::G1>::from_bytes(proof)?;
+    if decoded.witness_commitments.len() != published.len()
+        || decoded.witness_commitments.iter().zip(&published).any(|(c, bytes)| c.to_bytes() != *bytes)
+    {
+        return Ok(false);
+    }
+    let context = seed_commitment.as_ref().map(seed_context).unwrap_or_default();
+    StreamingVerifier::<E>::new(&scheme, key_hash, matrices)
+        .with_context(&context)
+        .with_transcript_hash(key.transcript_hash)
+        .verify(proof, public_inputs, rng)
+        .map_err(BundleError::Streaming)
+}
+
+impl<E: Pairing> WireFormat for VerifierKey<E> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.push(VERIFIER_KEY_VERSION);
+        out.push(self.transcript_hash.tag());
+        write_point::<E::G1>(out, &self.verification_key.g);
+        write_point::<E::G2>(out, &self.verification_key.h);
+        write_point::<E::G2>(out, &self.verification_key.h_tau);
+        out.extend_from_slice(&self.circuit_fingerprint);
+        self.circuit.encode(out);
+    }
+
+    fn decode(reader: &mut ByteReader<'_>) -> Result<Self, DecodeError> {
+        let version = reader.read_u8()?;
+        if version != VERIFIER_KEY_VERSION {
+            return Err(DecodeError::InvalidTag(version));
+        }
+        let tag = reader.read_u8()?;
+        let transcript_hash = TranscriptHash::from_tag(tag).ok_or(DecodeError::InvalidTag(tag))?;
+        Ok(VerifierKey {
+            transcript_hash,
+            verification_key: BundleVerificationKey {
+                g: reader.read_point::<E::G1>()?,
+                h: reader.read_point::<E::G2>()?,
+                h_tau: reader.read_point::<E::G2>()?,
+            },
+            circuit_fingerprint: reader.read_bytes(32)?.try_into().expect("slice has length 32"),
+            circuit: CircuitParameters::decode(reader)?,
+        })
+    }
+}
+
+impl<E: Pairing> WireFormat for ProofBundle<E> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.push(BUNDLE_VERSION);
+        out.push(self.transcript_hash.tag());
+        write_point::<E::G1>(out, &self.verification_key.g);
+        write_point::<E::G2>(out, &self.verification_key.h);
+        write_point::<E::G2>(out, &self.verification_key.h_tau);
+        out.extend_from_slice(&self.circuit_fingerprint);
+        self.circuit.encode(out);
+        write_len(out, self.context.len());
+        out.extend_from_slice(&self.context);
+        write_len(out, self.public_inputs.len());
+        for input in &self.public_inputs {
+            write_field(out, input);
+        }
+        match &self.seed_commitment {
+            Some(commitment) => {
+                out.push(1);
+                out.extend_from_slice(commitment);
+            }
+            None => out.push(0),
+        }
+        write_len(out, self.proof.len());
+        out.extend_from_slice(&self.proof);
+    }
+
+    fn decode(reader: &mut ByteReader<'_>) -> Result<Self, DecodeError> {
+        let version = reader.read_u8()?;
+        if version != BUNDLE_VERSION {
+            return Err(DecodeError::InvalidTag(version));
+        }
+        let tag = reader.read_u8()?;
+        let transcript_hash = TranscriptHash::from_tag(tag).ok_or(DecodeError::InvalidTag(tag))?;
+        let verification_key = BundleVerificationKey {
+            g: reader.read_point::<E::G1>()?,
+            h: reader.read_point::<E::G2>()?,
+            h_tau: reader.read_point::<E::G2>()?,
+        };
+        let circuit_fingerprint = reader.read_bytes(32)?.try_into().expect("slice has length 32");
+        let circuit = CircuitParameters::decode(reader)?;
+        let context_len = reader.read_len(1)?;
+        let context = reader.read_bytes(context_len)?.to_vec();
+        let num_inputs = reader.read_len(field_size::<E::ScalarField>())?;
+        let public_inputs = (0..num_inputs)
+            .map(|_| reader.read_field())
+            .collect::<Result<Vec<_>, _>>()?;
+        let seed_commitment = match reader.read_u8()? {
+            0 => None,
+            1 => Some(reader.read_bytes(32)?.try_into().expect("slice has length 32")),
+            tag => return Err(DecodeError::InvalidTag(tag)),
+        };
+        let proof_len = reader.read_len(1)?;
+        let proof = reader.read_bytes(proof_len)?.to_vec();
+        Ok(ProofBundle {
+            verification_key,
+            circuit_fingerprint,
+            circuit,
+            context,
+            transcript_hash,
+            public_inputs,
+            seed_commitment,
+            proof,
+        })
+    }
+}
+
+/// Bundle verification error types
+#[derive(Debug)]
+pub enum BundleError {
+    Streaming(StreamingError),
+    SeedContextMismatch,
+    /// The delegation transcript around the proof does not decode
+    Malformed(DecodeError),
+}
+
+impl From<DecodeError> for BundleError {
+    fn from(e: DecodeError) -> Self {
+        BundleError::Malformed(e)
+    }
+}
+
+impl std::fmt::Display for BundleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            BundleError::Streaming(e) => write!(f, "Malformed bundle proof: {}", e),
+            BundleError::SeedContextMismatch => write!(f, "Bundle context does not match its seed commitment"),
+            BundleError::Malformed(e) => write!(f, "Malformed delegation transcript: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for BundleError {}
+
+#[cfg(all(test, feature = "delegator"))]
+mod tests {
+    use super::*;
+    use crate::mpc::{CollaborationMode, ExecCircuit, ShamirSecretSharing};
+    use ark_bls12_381::{Bls12_381, Fr};
+    use ark_relations::r1cs::ConstraintSystem;
+    use ark_std::test_rng;
+
+    type TestField = Fr;
+    type Protocol = EOSProtocol<Bls12_381, TestField, ShamirSecretSharing<TestField>, CollaborationMode>;
+
+    #[test]
+    fn test_bundle_verifies_without_protocol() {
+        let mut rng = test_rng();
+        let witness: Vec<TestField> = (1..=8u64).map(TestField::from).collect();
+        let public_inputs = [TestField::from(5u64)];
+
+        let bytes = {
+            let executor = ExecCircuit::new(1, 3, ShamirSecretSharing::new());
+            let mut protocol = Protocol::new(executor, CollaborationMode::new(2, true, false), 128, &mut rng);
+            let circuit = ConstraintSystem::new();
+            protocol.preprocessing_state = Some(Protocol::preprocessing(&circuit, 128, &mut rng).unwrap());
+            let result = protocol
+                .delegate_computation_seeded(&circuit, &witness, &public_inputs, [3u8; 32])
+                .unwrap();
+            protocol
+                .export_bundle(&result, &public_inputs, &result.transcript_context())
+                .unwrap()
+                .to_bytes()
+        };
+
+        // Only the bytes leave the delegator
+        let bundle = ProofBundle::<Bls12_381>::from_bytes(&bytes).unwrap();
+        assert!(verify_bundle(&bundle, &mut rng).unwrap());
+        let calldata = bundle.evm_public_inputs();
+        assert_eq!(calldata.len(), 32);
+        assert_eq!(calldata[31], 5);
+
+        let mut altered_inputs = bundle.clone();
+        altered_inputs.public_inputs[0] = TestField::from(6u64);
+        assert!(!verify_bundle(&altered_inputs, &mut rng).unwrap());
+
+        let mut altered_context = bundle.clone();
+        altered_context.context = Vec::new();
+        assert!(matches!(verify_bundle(&altered_context, &mut rng), Err(BundleError::SeedContextMismatch)));
+        altered_context.seed_commitment = None;
+        assert!(!verify_bundle(&altered_context, &mut rng).unwrap());
+
+        // A bundle claiming another circuit does not verify
+        let mut altered_circuit = bundle.clone();
+        altered_circuit.circuit_fingerprint[0] ^= 1;
+        assert!(!verify_bundle(&altered_circuit, &mut rng).unwrap());
+
+        assert!(ProofBundle::<Bls12_381>::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+
+        // Bundles from before the G2 verification key are rejected
+        let mut v2 = bytes.clone();
+        v2[0] = 2;
+        assert!(matches!(ProofBundle::<Bls12_381>::from_bytes(&v2), Err(DecodeError::InvalidTag(2))));
+    }
+
+    #[test]
+    fn test_transcript_verifies_against_exported_key() {
+        let mut rng = test_rng();
+        let witness: Vec<TestField> = (1..=4u64).map(TestField::from).collect();
+        let public_inputs = [TestField::from(9u64)];
+        let executor = ExecCircuit::new(1, 3, ShamirSecretSharing::new());
+        let mut protocol = Protocol::new(executor, CollaborationMode::new(2, true, false), 128, &mut rng);
+        let circuit = ConstraintSystem::new();
+        protocol.preprocessing_state = Some(Protocol::preprocessing(&circuit, 128, &mut rng).unwrap());
+        let key_bytes = protocol.export_verifier_key().unwrap().to_bytes();
+        let key = VerifierKey::<Bls12_381>::from_bytes(&key_bytes).unwrap();
+
+        let standalone = protocol.delegate_computation(&circuit, &witness, &public_inputs, &mut rng).unwrap();
+        let seeded = protocol.delegate_computation_seeded(&circuit, &witness, &public_inputs, [5u8; 32]).unwrap();
+        for result in [&standalone, &seeded] {
+            let transcript = result.transcript_bytes();
+            assert!(verify_transcript(&key, &transcript, &public_inputs, &mut rng).unwrap());
+            assert!(!verify_transcript(&key, &transcript, &[TestField::from(10u64)], &mut rng).unwrap());
+            assert!(matches!(
+                verify_transcript(&key, &transcript[..transcript.len() - 1], &public_inputs, &mut rng),
+                Err(BundleError::Malformed(_))
+            ));
+        }
+
+        // Another circuit's key, and a bundle's key, for the same result
+        let mut other = key.clone();
+        other.circuit_fingerprint[0] ^= 1;
+        assert!(!verify_transcript(&other, &standalone.transcript_bytes(), &public_inputs, &mut rng).unwrap());
+        let bundle = protocol.export_bundle(&seeded, &public_inputs, &seeded.transcript_context()).unwrap();
+        assert_eq!(bundle.verifier_key(), key);
+    }
+
+    #[test]
+    fn test_bundle_records_transcript_hash() {
+        let mut rng = test_rng();
+        let witness: Vec<TestField> = (1..=4u64).map(TestField::from).collect();
+        let executor = ExecCircuit::new(1, 3, ShamirSecretSharing::new());
+        let mut protocol = Protocol::new(executor, CollaborationMode::new(2, true, false), 128, &mut rng);
+        protocol.params.transcript_hash = TranscriptHash::Keccak;
+        let circuit = ConstraintSystem::new();
+        protocol.preprocessing_state = Some(Protocol::preprocessing(&circuit, 128, &mut rng).unwrap());
+        let result = protocol.delegate_computation(&circuit, &witness, &[], &mut rng).unwrap();
+        let bytes = protocol.export_bundle(&result, &[], &result.transcript_context()).unwrap().to_bytes();
+
+        let bundle = ProofBundle::<Bls12_381>::from_bytes(&bytes).unwrap();
+        assert_eq!(bundle.transcript_hash, TranscriptHash::Keccak);
+        assert!(verify_bundle(&bundle, &mut rng).unwrap());
+
+        // The same proof checked with another hash derives another opening point
+        let mut relabelled = bundle.clone();
+        relabelled.transcript_hash = TranscriptHash::Sha256;
+        assert!(!verify_bundle(&relabelled, &mut rng).unwrap());
+    }
+
+    #[test]
+    fn test_bundle_rejects_unsatisfying_witness() {
+        let mut rng = test_rng();
+        // x·x = t, t·x = y with y public
+        let cs = ConstraintSystem::<TestField>::new_ref();
+        let y = cs.new_input_variable(|| Ok(TestField::from(343u64))).unwrap();
+        let x = cs.new_witness_variable(|| Ok(TestField::from(7u64))).unwrap();
+        let t = cs.new_witness_variable(|| Ok(TestField::from(49u64))).unwrap();
+        cs.enforce_constraint(ark_relations::lc!() + x, ark_relations::lc!() + x, ark_relations::lc!() + t).unwrap();
+        cs.enforce_constraint(ark_relations::lc!() + t, ark_relations::lc!() + x, ark_relations::lc!() + y).unwrap();
+        let circuit = cs.into_inner().unwrap();
+        let executor = ExecCircuit::new(1, 3, ShamirSecretSharing::new());
+        let mut protocol = Protocol::new(executor, CollaborationMode::new(2, true, false), 128, &mut rng);
+        protocol.preprocessing_state = Some(Protocol::preprocessing(&circuit, 128, &mut rng).unwrap());
+        let public_inputs = [TestField::from(343u64)];
+        let key = VerifierKey::<Bls12_381>::from_bytes(&protocol.export_verifier_key().unwrap().to_bytes()).unwrap();
+
+        // The second witness satisfies x·x = t but not t·x = y
+        let [(honest, transcript), (cheating, cheating_transcript)] = [[7u64, 49], [8, 64]].map(|values| {
+            let witness = values.map(TestField::from);
+            let result = protocol.delegate_computation(&circuit, &witness, &public_inputs, &mut rng).unwrap();
+            let bundle = protocol.export_bundle(&result, &public_inputs, &result.transcript_context()).unwrap();
+            (ProofBundle::<Bls12_381>::from_bytes(&bundle.to_bytes()).unwrap(), result.transcript_bytes())
+        });
+        assert!(verify_bundle(&honest, &mut rng).unwrap());
+        assert!(verify_transcript(&key, &transcript, &public_inputs, &mut rng).unwrap());
+
+        // A valid opening of a witness that breaks the circuit is not enough
+        assert!(!verify_bundle(&cheating, &mut rng).unwrap());
+        assert!(!verify_transcript(&key, &cheating_transcript, &public_inputs, &mut rng).unwrap());
+
+        // Swapping in a circuit the proof satisfies breaks the fingerprint pin
+        let mut relaxed = cheating.clone();
+        relaxed.circuit = Protocol::preprocessing(&ConstraintSystem::new(), 128, &mut rng).unwrap().circuit_params;
+        assert!(!verify_bundle(&relaxed, &mut rng).unwrap());
+        let mut relaxed_key = key.clone();
+        relaxed_key.circuit = relaxed.circuit.clone();
+        assert!(!verify_transcript(&relaxed_key, &cheating_transcript, &public_inputs, &mut rng).unwrap());
+    }
+}
@@ -0,0 +1,300 @@
+//! Joint challenge randomness for proofs checked by several delegators
+//!
+//! When one proof must convince several delegators, none of them should be
+//! able to steer the PIOP challenges. Each delegator commits to a random
+//! contribution, and contributions are revealed only once every commitment
+//! is in. The revealed values are folded into a hash chain in participant
+//! order, so the resulting beacon is unpredictable as long as one
+//! participant is honest. The beacon enters the delegation transcript via
+//! `joint_context`, and every challenge is derived after it.
+
+use std::collections::BTreeMap;
+
+use ark_ec::pairing::Pairing;
+use ark_ff::PrimeField;
+use ark_relations::r1cs::ConstraintSystem;
+use ark_std::rand::Rng;
+use sha2::{Digest, Sha256};
+
+use crate::mpc::{OperationMode, SecretSharing};
+use crate::protocol::{DelegationResult, EOSError, EOSProtocol};
+use crate::sampling::SeededSampler;
+
+/// One participant's secret contribution
+pub struct CoinContribution {
+    participant: u64,
+    value: [u8; 32],
+}
+
+/// Binding commitment to a contribution
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CoinCommitment {
+    pub participant: u64,
+    pub digest: [u8; 32],
+}
+
+/// Opened contribution
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CoinReveal {
+    pub participant: u64,
+    pub value: [u8; 32],
+}
+
+fn commitment_digest(participant: u64, value: &[u8; 32]) -> [u8; 32] {
+    Sha256::new()
+        .chain_update(b"EOS-coin-commit")
+        .chain_update(participant.to_le_bytes())
+        .chain_update(value)
+        .finalize()
+        .into()
+}
+
+impl CoinContribution {
+    pub fn new<R: Rng + ?Sized>(participant: u64, rng: &mut R) -> Self {
+        let mut value = [0u8; 32];
+        rng.fill_bytes(&mut value);
+        Self { participant, value }
+    }
+
+    pub fn commitment(&self) -> CoinCommitment {
+        CoinCommitment { participant: self.participant, digest: commitment_digest(self.participant, &self.value) }
+    }
+
+    pub fn reveal(&self) -> CoinReveal {
+        CoinReveal { participant: self.participant, value: self.value }
+    }
+}
+
+/// Outcome of a coin flip
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JointChallenge {
+    pub beacon: [u8; 32],
+    pub participants: Vec<u64>,
+}
+
+impl JointChallenge {
+    /// Transcript context of proofs bound to this beacon
+    pub fn context(&self) -> Vec<u8> {
+        joint_context(&self.beacon)
+    }
+
+    /// Field elements derived from the beacon, identical for every
+    /// participant on every platform
+    pub fn sampler(&self, domain: &[u8]) -> SeededSampler {
+        SeededSampler::new(domain, &self.beacon)
+    }
+}
+
+/// Transcript context that binds a proof to a jointly chosen beacon
+pub fn joint_context(beacon: &[u8; 32]) -> Vec<u8> {
+    let mut context = b"joint".to_vec();
+    context.extend_from_slice(beacon);
+    context
+}
+
+/// Commit-reveal ceremony among a fixed set of participants
+///
+/// Every participant runs its own copy and feeds it the same broadcast
+/// messages; all honest copies finish with the same beacon.
+#[derive(Debug, Clone)]
+pub struct CoinFlip {
+    commitments: BTreeMap<u64, Option<[u8; 32]>>,
+    reveals: BTreeMap<u64, [u8; 32]>,
+}
+
+impl CoinFlip {
+    pub fn new(participants: impl IntoIterator<Item = u64>) -> Self {
+        Self {
+            commitments: participants.into_iter().map(|participant| (participant, None)).collect(),
+            reveals: BTreeMap::new(),
+        }
+    }
+
+    /// Whether every participant has committed, so reveals may be sent
+    pub fn all_committed(&self) -> bool {
+        self.commitments.values().all(Option::is_some)
+    }
+
+    pub fn add_commitment(&mut self, commitment: CoinCommitment) -> Result<(), CoinFlipError> {
+        let slot = self
+            .commitments
+            .get_mut(&commitment.participant)
+            .ok_or(CoinFlipError::UnknownParticipant(commitment.participant))?;
+        match slot {
+            Some(existing) if *existing == commitment.digest => Ok(()),
+            Some(_) => Err(CoinFlipError::Equivocation(commitment.participant)),
+            None => {
+                *slot = Some(commitment.digest);
+                Ok(())
+            }
+        }
+    }
+
+    /// Accept a reveal; refused until every participant has committed, so
+    /// no one can choose a commitment after seeing another's value
+    pub fn add_reveal(&mut self, reveal: CoinReveal) -> Result<(), CoinFlipError> {
+        if !self.all_committed() {
+            return Err(CoinFlipError::RevealBeforeCommitments);
+        }
+        let digest = self
+            .commitments
+            .get(&reveal.participant)
+            .ok_or(CoinFlipError::UnknownParticipant(reveal.participant))?
+            .expect("all participants committed");
+        if commitment_digest(reveal.participant, &reveal.value) != digest {
+            return Err(CoinFlipError::InvalidReveal(reveal.participant));
+        }
+        self.reveals.insert(reveal.participant, reveal.value);
+        Ok(())
+    }
+
+    /// Participants whose reveal is still outstanding
+    pub fn missing_reveals(&self) -> Vec<u64> {
+        self.commitments
+            .keys()
+            .filter(|participant| !self.reveals.contains_key(participant))
+            .copied()
+            .collect()
+    }
+
+    /// Fold all reveals into the beacon
+    pub fn finish(&self) -> Result<JointChallenge, CoinFlipError> {
+        let missing = self.missing_reveals();
+        if !missing.is_empty() {
+            return Err(CoinFlipError::MissingReveals(missing));
+        }
+        // The chain starts from every commitment, then absorbs each reveal
+        let mut start = Sha256::new().chain_update(b"EOS-coin-chain");
+        for (participant, digest) in &self.commitments {
+            start.update(participant.to_le_bytes());
+            start.update(digest.expect("all participants committed"));
+        }
+        let beacon = self.reveals.iter().fold(start.finalize().into(), |link: [u8; 32], (participant, value)| {
+            Sha256::new()
+                .chain_update(link)
+                .chain_update(participant.to_le_bytes())
+                .chain_update(value)
+                .finalize()
+                .into()
+        });
+        Ok(JointChallenge { beacon, participants: self.commitments.keys().copied().collect() })
+    }
+}
+
+impl<E, F, SS, OM> EOSProtocol<E, F, SS, OM>
+where
+    E: Pairing<ScalarField = F>,
+    F: PrimeField,
+    SS: SecretSharing<F>,
+    OM: OperationMode<F, SS>,
+{
+    /// Delegate with challenges derived after a joint beacon
+    ///
+    /// Verifiers check the proof with `joint.context()` as the transcript
+    /// context.
+    pub fn delegate_with_joint_challenge(
+        &mut self,
+        circuit: &ConstraintSystem<F>,
+        witness: &[F],
+        public_inputs: &[F],
+        joint: &JointChallenge,
+        rng: &mut impl Rng,
+    ) -> Result<DelegationResult<E, F>, EOSError> {
+        self.preprocessing_state
+            .as_ref()
+            .ok_or(EOSError::PreprocessingNotDone)?;
+        let witness_shares = self.share_witness(witness, rng);
+        let _mpc_result = self.execute_circuit_mpc(circuit, &witness_shares, public_inputs)?;
+        self.prove_delegation(witness, public_inputs, &joint.context())
+    }
+}
+
+/// Coin flip error types
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CoinFlipError {
+    UnknownParticipant(u64),
+    /// A participant sent two different commitments
+    Equivocation(u64),
+    RevealBeforeCommitments,
+    InvalidReveal(u64),
+    MissingReveals(Vec<u64>),
+}
+
+impl std::fmt::Display for CoinFlipError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            CoinFlipError::UnknownParticipant(p) => write!(f, "Unknown coin flip participant {}", p),
+            CoinFlipError::Equivocation(p) => write!(f, "Participant {} sent conflicting commitments", p),
+            CoinFlipError::RevealBeforeCommitments => write!(f, "Reveal received before all commitments"),
+            CoinFlipError::InvalidReveal(p) => write!(f, "Reveal of participant {} does not match its commitment", p),
+            CoinFlipError::MissingReveals(ps) => write!(f, "Missing reveals from participants {:?}", ps),
+        }
+    }
+}
+
+impl std::error::Error for CoinFlipError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mpc::{CollaborationMode, ExecCircuit, ShamirSecretSharing};
+    use crate::protocol::StreamingVerifier;
+    use ark_bls12_381::{Bls12_381, Fr};
+    use ark_std::test_rng;
+
+    type TestField = Fr;
+    type Protocol = EOSProtocol<Bls12_381, TestField, ShamirSecretSharing<TestField>, CollaborationMode>;
+
+    #[test]
+    fn test_joint_challenge_binds_all_delegators() {
+        let mut rng = test_rng();
+        let contributions: Vec<CoinContribution> = (1..=3).map(|id| CoinContribution::new(id, &mut rng)).collect();
+
+        // Two participants' views, fed messages in different orders
+        let mut views = [CoinFlip::new(1..=3), CoinFlip::new([3, 2, 1])];
+        for (i, view) in views.iter_mut().enumerate() {
+            let mut order: Vec<&CoinContribution> = contributions.iter().collect();
+            if i == 1 {
+                order.reverse();
+            }
+            assert_eq!(view.add_reveal(order[0].reveal()), Err(CoinFlipError::RevealBeforeCommitments));
+            for contribution in &order {
+                view.add_commitment(contribution.commitment()).unwrap();
+            }
+            for contribution in &order {
+                view.add_reveal(contribution.reveal()).unwrap();
+            }
+        }
+        let joint = views[0].finish().unwrap();
+        assert_eq!(joint, views[1].finish().unwrap());
+        let coins: Vec<TestField> = joint.sampler(b"EOS-test-coins").fields(2);
+        assert_eq!(coins, views[1].finish().unwrap().sampler(b"EOS-test-coins").fields::<TestField>(2));
+
+        // A participant cannot swap its value after committing
+        let mut flip = CoinFlip::new(1..=3);
+        for contribution in &contributions {
+            flip.add_commitment(contribution.commitment()).unwrap();
+        }
+        let other = CoinContribution::new(2, &mut rng);
+        assert_eq!(flip.add_commitment(other.commitment()), Err(CoinFlipError::Equivocation(2)));
+        assert_eq!(flip.add_reveal(other.reveal()), Err(CoinFlipError::InvalidReveal(2)));
+        assert_eq!(flip.finish(), Err(CoinFlipError::MissingReveals(vec![1, 2, 3])));
+        assert!(matches!(flip.add_commitment(CoinContribution::new(9, &mut rng).commitment()), Err(CoinFlipError::UnknownParticipant(9))));
+
+        // The proof verifies only under the joint context
+        let executor = ExecCircuit::new(1, 3, ShamirSecretSharing::new());
+        let mut protocol = Protocol::new(executor, CollaborationMode::new(2, true, false), 128, &mut rng);
+        let circuit = ConstraintSystem::new();
+        protocol.preprocessing_state = Some(Protocol::preprocessing(&circuit, 128, &mut rng).unwrap());
+        let witness: Vec<TestField> = (1..=4u64).map(TestField::from).collect();
+        let result = protocol
+            .delegate_with_joint_challenge(&circuit, &witness, &[], &joint, &mut rng)
+            .unwrap();
+        let proof = result.piop_proof.unwrap();
+        let key_hash = protocol.verification_key_hash().unwrap();
+        let matrices = protocol.constraint_matrices().unwrap();
+        let verifier = || StreamingVerifier::<Bls12_381>::new(&protocol.commitment_scheme, key_hash, matrices);
+        assert!(verifier().with_context(&joint.context()).verify(proof.as_slice(), &[], &mut rng).unwrap());
+        assert!(!verifier().verify(proof.as_slice(), &[], &mut rng).unwrap());
+    }
+}
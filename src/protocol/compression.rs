@@ -0,0 +1,650 @@
+//! Constant-size compression of a delegation proof
+//!
+//! A delegation proof grows with the circuit: the zerocheck and linear
+//! sumcheck send a round per variable, and the multilinear witness opening
+//! adds a commitment per fold. `DelegationResult::compress` replaces it with
+//! a Groth16 proof that the PIOP verifier of `verify_computation` accepts,
+//! plus one KZG opening, so the artifact is the same size for every circuit.
+//!
+//! The Groth16 circuit replays the delegation transcript with `TranscriptVar`
+//! and runs the constraint proof's checks over the PIOP proof as private
+//! input: the zerocheck rounds and row evaluations, the column weights, the
+//! linear sumcheck and the final `u·w̃(ρ') = expected` identity. Pairings do
+//! not fit in a circuit over the scalar field, so the KZG openings of the
+//! witness commitment are not checked inside it. Instead the circuit takes
+//! the committed coefficients as private input, evaluates `w̃(ρ')` itself,
+//! and binds the coefficients to the commitment: it derives a point `z'`
+//! from the commitment and the coefficients and exposes `(z', w(z'))`; the
+//! outer verifier checks that the commitment opens to that value at `z'`.
+//! Since `z'` is fixed only after the coefficients, a commitment to any
+//! other polynomial passes with negligible probability. The univariate
+//! opening at the delegation's opening point then holds for the committed
+//! witness by construction and is not repeated.
+//!
+//! The circuit is over BLS12-381's scalar field, so compression needs a
+//! proof over `Bls12_381` with the Poseidon transcript
+//! (`EOSParams::transcript_hash`) and without zk masks. The Groth16 keys are
+//! specific to the circuit, its verification key hash and a
+//! `CompressionShape`; `EOSProtocol::compression_setup` runs the setup with
+//! local randomness, which whoever verifies must trust.
+
+use ark_bls12_381::{Bls12_381, Fr, G1Projective, G2Projective};
+use ark_ec::pairing::Pairing;
+use ark_ff::{Field, One, Zero};
+use ark_groth16::{prepare_verifying_key, Groth16, PreparedVerifyingKey, Proof, ProvingKey};
+use ark_poly::{univariate::DensePolynomial, DenseUVPolynomial};
+use ark_r1cs_std::alloc::AllocVar;
+use ark_r1cs_std::eq::EqGadget;
+use ark_r1cs_std::fields::{fp::FpVar, FieldVar};
+use ark_r1cs_std::uint8::UInt8;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use ark_std::rand::RngCore;
+
+use crate::circuit::{OpeningProof, PolynomialCommitment};
+use crate::mpc::{OperationMode, SecretSharing};
+use crate::piop::{
+    packed_bytes_input, Domain, PolynomialConsistencyProof, SumcheckProof, Transcript, TranscriptHash, TranscriptVar,
+};
+use crate::protocol::{ConstraintMatrices, DelegationResult, EOSError, EOSProtocol, DELEGATION_TRANSCRIPT_DOMAIN};
+use crate::serialization::{point_size, write_len, write_point, ByteReader, DecodeError, WireFormat};
+
+/// Domain separator for the transcript that binds the witness to its commitment
+pub const COMPRESSION_TRANSCRIPT_DOMAIN: &[u8] = b"EOS-compression-v2";
+
+/// The sizes a compression circuit is built for
+///
+/// The constraint matrices fix the zerocheck; these fix the rest of the
+/// verifier's transcript.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompressionShape {
+    pub context_len: usize,
+    pub num_public_inputs: usize,
+    /// Variables of the linear sumcheck, i.e. log2 of the padded witness
+    pub witness_vars: usize,
+}
+
+impl CompressionShape {
+    /// The shape of `result`'s proof under `context`
+    pub fn of(result: &DelegationResult<Bls12_381, Fr>, public_inputs: &[Fr], context: &[u8]) -> Result<Self, EOSError> {
+        let proof = parse_proof(result)?;
+        let [_, linear] = proof.sumcheck_proofs.as_slice() else {
+            return Err(EOSError::PIOPError("proof has no constraint proof to compress".to_string()));
+        };
+        Ok(Self {
+            context_len: context.len(),
+            num_public_inputs: public_inputs.len(),
+            witness_vars: linear.round_polynomials.len(),
+        })
+    }
+}
+
+/// Groth16 keys for one compression circuit
+pub struct CompressionKey {
+    pub shape: CompressionShape,
+    /// The verification key hash the circuit replays the transcript under
+    pub key_hash: [u8; 32],
+    pub proving_key: ProvingKey<Bls12_381>,
+    pub verifying_key: PreparedVerifyingKey<Bls12_381>,
+}
+
+/// A delegation proof compressed to a constant size
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompressedProof {
+    /// The transcript context the proof was produced under
+    pub context: Vec<u8>,
+    pub witness_commitment: PolynomialCommitment<G1Projective>,
+    /// Groth16 proof that the PIOP verifier accepts
+    pub proof: Proof<Bls12_381>,
+    /// Opening of the witness commitment at the circuit's binding point
+    pub binding_opening: OpeningProof<Fr, G1Projective>,
+}
+
+impl<E: Pairing, F: Field> DelegationResult<E, F> {
+    /// Canonical bytes of everything the verifier receives
+    pub fn transcript_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        let piop_proof = self.piop_proof.as_deref().unwrap_or(&[]);
+        write_len(&mut out, piop_proof.len());
+        out.extend_from_slice(piop_proof);
+        write_len(&mut out, self.polynomial_commitments.len());
+        for commitment in &self.polynomial_commitments {
+            write_len(&mut out, commitment.len());
+            out.extend_from_slice(commitment);
+        }
+        match &self.seed_commitment {
+            Some(commitment) => {
+                out.push(1);
+                out.extend_from_slice(commitment);
+            }
+            None => out.push(0),
+        }
+        out.extend_from_slice(&self.verification_key_hash);
+        out
+    }
+}
+
+/// The values the verifier circuit runs on
+///
+/// `blank` gives the all-zero assignment of a shape, which is enough for
+/// the setup: only the constraints matter there.
+#[derive(Clone)]
+struct VerifierAssignment {
+    context: Vec<u8>,
+    public_inputs: Vec<Fr>,
+    commitment: Vec<u8>,
+    binding_point: Fr,
+    binding_evaluation: Fr,
+    zerocheck_rounds: Vec<Vec<Fr>>,
+    row_evaluations: [Fr; 3],
+    linear_rounds: Vec<Vec<Fr>>,
+    coefficients: Vec<Fr>,
+}
+
+impl VerifierAssignment {
+    fn blank(shape: &CompressionShape, row_vars: usize) -> Self {
+        Self {
+            context: vec![0; shape.context_len],
+            public_inputs: vec![Fr::zero(); shape.num_public_inputs],
+            commitment: vec![0; point_size::<G1Projective>()],
+            binding_point: Fr::zero(),
+            binding_evaluation: Fr::zero(),
+            zerocheck_rounds: vec![vec![Fr::zero(); 4]; row_vars],
+            row_evaluations: [Fr::zero(); 3],
+            linear_rounds: vec![vec![Fr::zero(); 3]; shape.witness_vars],
+            coefficients: vec![Fr::zero(); 1 << shape.witness_vars],
+        }
+    }
+}
+
+/// The Groth16 public inputs in the order the circuit allocates them
+fn public_input_vector(context: &[u8], public_inputs: &[Fr], commitment: &[u8], point: Fr, evaluation: Fr) -> Vec<Fr> {
+    let mut inputs = packed_bytes_input(context);
+    inputs.extend_from_slice(public_inputs);
+    inputs.extend(packed_bytes_input(commitment));
+    inputs.extend([point, evaluation]);
+    inputs
+}
+
+/// `verify_computation`'s PIOP checks as a constraint synthesizer
+struct VerifierCircuit<'a> {
+    matrices: &'a ConstraintMatrices<Fr>,
+    key_hash: [u8; 32],
+    assignment: VerifierAssignment,
+}
+
+impl ConstraintSynthesizer<Fr> for VerifierCircuit<'_> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        let VerifierCircuit { matrices, key_hash, assignment } = self;
+        let context = UInt8::new_input_vec(cs.clone(), &assignment.context)?;
+        let public_inputs = Vec::<FpVar<Fr>>::new_input(cs.clone(), || Ok(assignment.public_inputs.clone()))?;
+        let commitment = UInt8::new_input_vec(cs.clone(), &assignment.commitment)?;
+        let binding_point = FpVar::new_input(cs.clone(), || Ok(assignment.binding_point))?;
+        let binding_evaluation = FpVar::new_input(cs.clone(), || Ok(assignment.binding_evaluation))?;
+        let witness_rows = |rounds: &[Vec<Fr>]| -> Result<Vec<Vec<FpVar<Fr>>>, SynthesisError> {
+            rounds.iter().map(|round| Vec::new_witness(cs.clone(), || Ok(round.clone()))).collect()
+        };
+        let zerocheck_rounds = witness_rows(&assignment.zerocheck_rounds)?;
+        let linear_rounds = witness_rows(&assignment.linear_rounds)?;
+        let [a, b, c] = assignment.row_evaluations.map(|value| FpVar::new_witness(cs.clone(), || Ok(value)));
+        let row_evaluations = [a?, b?, c?];
+        let coefficients = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(assignment.coefficients.clone()))?;
+
+        // The delegation transcript up to the opening point
+        let mut transcript = TranscriptVar::new(DELEGATION_TRANSCRIPT_DOMAIN)?;
+        transcript.append_message(b"verification_key", &UInt8::constant_vec(&key_hash))?;
+        transcript.append_message(b"context", &context)?;
+        transcript.append_fields(b"public_inputs", &public_inputs)?;
+        transcript.append_message(b"witness_commitment", &commitment)?;
+        // The opening point only matters through its absorption; a field
+        // challenge draws two byte challenges
+        for _ in 0..2 {
+            transcript.challenge_bytes(b"opening_point")?;
+        }
+
+        // 1. The zerocheck over a∘b − c (`verify_r1cs_zerocheck`)
+        let num_rows = matrices.a_matrix.len().max(matrices.b_matrix.len()).max(matrices.c_matrix.len());
+        let row_vars = Domain::for_len(num_rows).num_vars();
+        transcript.append_message(b"zerocheck_num_vars", &UInt8::constant_vec(&(row_vars as u64).to_le_bytes()))?;
+        let r = (0..row_vars)
+            .map(|_| transcript.challenge_field(b"zerocheck_point"))
+            .collect::<Result<Vec<_>, _>>()?;
+        let (row_point, expected) = verify_product_sumcheck_var(&mut transcript, 3, FpVar::zero(), &zerocheck_rounds)?;
+        let [a, b, c] = &row_evaluations;
+        (eq_evaluation_var(&r, &row_point)? * (a * b - c)).enforce_equal(&expected)?;
+        transcript.append_fields(b"zerocheck_evaluations", &row_evaluations)?;
+
+        // 2. The row evaluations are ⟨u, z⟩, the witness part by a linear sumcheck
+        let gamma = transcript.challenge_field(b"constraint_combination")?;
+        let weights = column_weights_var(matrices, &row_point, &gamma)?;
+        let offset = 1 + public_inputs.len();
+        let public = std::iter::once(FpVar::one()).chain(public_inputs.iter().cloned());
+        let mut public_part = FpVar::zero();
+        for (weight, value) in weights.iter().zip(public) {
+            public_part += weight * value;
+        }
+        let claim = a + &gamma * b + gamma.square()? * c - public_part;
+        let (point, expected) = verify_product_sumcheck_var(&mut transcript, 2, claim, &linear_rounds)?;
+
+        // 3. The committed witness has the multilinear evaluation the sumcheck ends in
+        let eq = eq_table_var(&point)?;
+        let mut u = FpVar::zero();
+        for (weight, eq) in weights.iter().skip(offset).zip(&eq) {
+            u += weight * eq;
+        }
+        let mut evaluation = FpVar::zero();
+        for (coefficient, eq) in coefficients.iter().zip(&eq) {
+            evaluation += coefficient * eq;
+        }
+        (u * evaluation).enforce_equal(&expected)?;
+
+        // 4. The coefficients are the committed polynomial's: their value at
+        //    a point derived from both is checked against the commitment outside
+        let mut binding = TranscriptVar::new(COMPRESSION_TRANSCRIPT_DOMAIN)?;
+        binding.append_message(b"witness_commitment", &commitment)?;
+        binding.append_fields(b"witness", &coefficients)?;
+        binding.challenge_field(b"binding_point")?.enforce_equal(&binding_point)?;
+        let mut value = FpVar::zero();
+        for coefficient in coefficients.iter().rev() {
+            value = value * &binding_point + coefficient;
+        }
+        value.enforce_equal(&binding_evaluation)
+    }
+}
+
+/// `verify_product_sumcheck` on round evaluations at 0, 1, ..., degree;
+/// returns the point and the expected evaluation there
+fn verify_product_sumcheck_var(
+    transcript: &mut TranscriptVar,
+    degree: usize,
+    claim: FpVar<Fr>,
+    rounds: &[Vec<FpVar<Fr>>],
+) -> Result<(Vec<FpVar<Fr>>, FpVar<Fr>), SynthesisError> {
+    let constant = |value: usize| UInt8::constant_vec(&(value as u64).to_le_bytes());
+    transcript.append_message(b"product_sumcheck_num_vars", &constant(rounds.len()))?;
+    transcript.append_message(b"product_sumcheck_degree", &constant(degree))?;
+    transcript.append_field(b"product_sumcheck_claim", &claim)?;
+    let mut expected = claim;
+    let mut point = Vec::with_capacity(rounds.len());
+    for evaluations in rounds {
+        (&evaluations[0] + &evaluations[1]).enforce_equal(&expected)?;
+        transcript.append_fields(b"product_sumcheck_round", evaluations)?;
+        let challenge = transcript.challenge_field(b"product_sumcheck_challenge")?;
+        expected = interpolate_at_var(evaluations, &challenge)?;
+        point.push(challenge);
+    }
+    Ok((point, expected))
+}
+
+/// The polynomial with the given values at 0, 1, ..., d, evaluated at `x`
+fn interpolate_at_var(evaluations: &[FpVar<Fr>], x: &FpVar<Fr>) -> Result<FpVar<Fr>, SynthesisError> {
+    let node = |i: usize| Fr::from(i as u64);
+    let mut result = FpVar::zero();
+    for (i, value) in evaluations.iter().enumerate() {
+        let mut numerator = value.clone();
+        let mut denominator = Fr::one();
+        for j in (0..evaluations.len()).filter(|j| *j != i) {
+            numerator *= x - node(j);
+            denominator *= node(i) - node(j);
+        }
+        result += numerator * denominator.inverse().expect("interpolation nodes are distinct");
+    }
+    Ok(result)
+}
+
+/// `eq_evaluation`: Π (a_i b_i + (1 − a_i)(1 − b_i))
+fn eq_evaluation_var(a: &[FpVar<Fr>], b: &[FpVar<Fr>]) -> Result<FpVar<Fr>, SynthesisError> {
+    let mut result = FpVar::one();
+    for (a, b) in a.iter().zip(b) {
+        let ab = a * b;
+        result *= ab.double()? - a - b + Fr::one();
+    }
+    Ok(result)
+}
+
+/// `eq_table`, with `point[0]` the highest bit of the index
+fn eq_table_var(point: &[FpVar<Fr>]) -> Result<Vec<FpVar<Fr>>, SynthesisError> {
+    let mut table = vec![FpVar::one()];
+    for r in point {
+        let mut next = Vec::with_capacity(table.len() * 2);
+        for value in &table {
+            let high = value * r;
+            next.push(value - &high);
+            next.push(high);
+        }
+        table = next;
+    }
+    Ok(table)
+}
+
+/// `column_weights`: the weight of every column of `z` in `Σ_i eq(ρ, i)·(A_i + γ·B_i + γ²·C_i)`
+fn column_weights_var(
+    matrices: &ConstraintMatrices<Fr>,
+    row_point: &[FpVar<Fr>],
+    gamma: &FpVar<Fr>,
+) -> Result<Vec<FpVar<Fr>>, SynthesisError> {
+    let eq = eq_table_var(row_point)?;
+    let mut weights: Vec<FpVar<Fr>> = Vec::new();
+    let mut scale = FpVar::one();
+    for matrix in [&matrices.a_matrix, &matrices.b_matrix, &matrices.c_matrix] {
+        for (row, entries) in matrix.iter().enumerate() {
+            if entries.is_empty() {
+                continue;
+            }
+            let scaled = &scale * &eq[row];
+            for &(column, coefficient) in entries {
+                if column >= weights.len() {
+                    weights.resize(column + 1, FpVar::zero());
+                }
+                weights[column] += &scaled * coefficient;
+            }
+        }
+        scale *= gamma;
+    }
+    Ok(weights)
+}
+
+fn parse_proof(result: &DelegationResult<Bls12_381, Fr>) -> Result<PolynomialConsistencyProof<Fr, G1Projective>, EOSError> {
+    let bytes = result.piop_proof.as_deref().ok_or(EOSError::VerificationFailed)?;
+    PolynomialConsistencyProof::from_bytes(bytes).map_err(|e| EOSError::PIOPError(format!("malformed proof: {}", e)))
+}
+
+/// The point binding `coefficients` to `commitment`, as the circuit derives it
+fn binding_point(commitment: &PolynomialCommitment<G1Projective>, coefficients: &[Fr]) -> Fr {
+    let mut transcript = Transcript::with_hash(COMPRESSION_TRANSCRIPT_DOMAIN, TranscriptHash::Poseidon);
+    transcript.append_point::<G1Projective>(b"witness_commitment", &commitment.commitment);
+    transcript.append_fields(b"witness", coefficients);
+    transcript.challenge_field(b"binding_point")
+}
+
+impl<SS, OM> EOSProtocol<Bls12_381, Fr, SS, OM>
+where
+    SS: SecretSharing<Fr>,
+    OM: OperationMode<Fr, SS>,
+{
+    /// Constraint matrices and key hash, if this protocol's proofs can be compressed
+    fn compression_keys(&self) -> Result<(&ConstraintMatrices<Fr>, [u8; 32]), EOSError> {
+        if self.params.transcript_hash != TranscriptHash::Poseidon || self.params.zk {
+            return Err(EOSError::PIOPError(
+                "compression needs the Poseidon transcript and a proof without zk masks".to_string(),
+            ));
+        }
+        let state = self.preprocessing_state.as_ref().ok_or(EOSError::PreprocessingNotDone)?;
+        Ok((&state.circuit_params.constraint_matrices, self.verification_key_hash()?))
+    }
+
+    /// Generate the Groth16 keys of the compression circuit for `shape`
+    pub fn compression_setup<R: RngCore>(
+        &self,
+        shape: CompressionShape,
+        rng: &mut R,
+    ) -> Result<CompressionKey, EOSError> {
+        let (matrices, key_hash) = self.compression_keys()?;
+        let num_rows = matrices.a_matrix.len().max(matrices.b_matrix.len()).max(matrices.c_matrix.len());
+        let assignment = VerifierAssignment::blank(&shape, Domain::for_len(num_rows).num_vars());
+        let circuit = VerifierCircuit { matrices, key_hash, assignment };
+        let proving_key = Groth16::<Bls12_381>::generate_random_parameters_with_reduction(circuit, rng)
+            .map_err(|e| EOSError::PIOPError(format!("compression setup failed: {}", e)))?;
+        let verifying_key = prepare_verifying_key(&proving_key.vk);
+        Ok(CompressionKey { shape, key_hash, proving_key, verifying_key })
+    }
+
+    /// Verify a compressed proof for `public_inputs`
+    ///
+    /// Accepts iff the key was made for this protocol's circuit and key
+    /// hash, the Groth16 proof verifies, and the witness commitment opens
+    /// to the circuit's binding evaluation.
+    pub fn verify_compressed(
+        &self,
+        key: &CompressionKey,
+        compressed: &CompressedProof,
+        public_inputs: &[Fr],
+    ) -> Result<bool, EOSError> {
+        let (_, key_hash) = self.compression_keys()?;
+        if key.key_hash != key_hash
+            || key.shape.context_len != compressed.context.len()
+            || key.shape.num_public_inputs != public_inputs.len()
+        {
+            return Ok(false);
+        }
+        let opening = &compressed.binding_opening;
+        let inputs = public_input_vector(
+            &compressed.context,
+            public_inputs,
+            &compressed.witness_commitment.to_bytes(),
+            opening.point,
+            opening.evaluation,
+        );
+        let accepted = Groth16::<Bls12_381>::verify_proof(&key.verifying_key, &compressed.proof, &inputs)
+            .map_err(|e| EOSError::PIOPError(format!("malformed compressed proof: {}", e)))?;
+        Ok(accepted && self.commitment_scheme.verify(&compressed.witness_commitment, opening))
+    }
+}
+
+impl DelegationResult<Bls12_381, Fr> {
+    /// Compress the result into a constant-size `CompressedProof`
+    ///
+    /// `witness` is the witness the result commits to; only the delegator
+    /// holds it. The result must verify under `protocol`, and `key` must
+    /// come from `protocol.compression_setup` for this result's shape.
+    pub fn compress<SS, OM, R>(
+        &self,
+        protocol: &EOSProtocol<Bls12_381, Fr, SS, OM>,
+        key: &CompressionKey,
+        witness: &[Fr],
+        public_inputs: &[Fr],
+        rng: &mut R,
+    ) -> Result<CompressedProof, EOSError>
+    where
+        SS: SecretSharing<Fr>,
+        OM: OperationMode<Fr, SS>,
+        R: RngCore,
+    {
+        let (matrices, key_hash) = protocol.compression_keys()?;
+        let context = self.transcript_context();
+        if !protocol.verify_computation(self, public_inputs)?.is_accepted() {
+            return Err(EOSError::VerificationFailed);
+        }
+        let shape = CompressionShape::of(self, public_inputs, &context)?;
+        if key.key_hash != key_hash || key.shape != shape {
+            return Err(EOSError::PIOPError(format!("compression key is for {:?}, not {:?}", key.shape, shape)));
+        }
+
+        let proof = parse_proof(self)?;
+        let (Some(claims), [zerocheck, linear], [commitment]) =
+            (&proof.constraint_claims, proof.sumcheck_proofs.as_slice(), proof.witness_commitments.as_slice())
+        else {
+            return Err(EOSError::PIOPError("proof has no constraint proof to compress".to_string()));
+        };
+        let polynomial = DensePolynomial::from_coefficients_slice(witness);
+        if polynomial.coeffs.len() > 1 << shape.witness_vars || protocol.commitment_scheme.commit(&polynomial) != *commitment {
+            return Err(EOSError::CommitmentError("witness does not match the result's commitment".to_string()));
+        }
+        let mut coefficients = polynomial.coeffs.clone();
+        coefficients.resize(1 << shape.witness_vars, Fr::zero());
+        let point = binding_point(commitment, &coefficients);
+        let binding_opening = protocol.commitment_scheme.open(&polynomial, point);
+        let rounds = |proof: &SumcheckProof<Fr>, degree| {
+            proof.to_product(degree).map(|product| product.rounds).ok_or(EOSError::VerificationFailed)
+        };
+
+        let assignment = VerifierAssignment {
+            context: context.clone(),
+            public_inputs: public_inputs.to_vec(),
+            commitment: commitment.to_bytes(),
+            binding_point: point,
+            binding_evaluation: binding_opening.evaluation,
+            zerocheck_rounds: rounds(zerocheck, 3)?,
+            row_evaluations: claims.row_evaluations,
+            linear_rounds: rounds(linear, 2)?,
+            coefficients,
+        };
+        let circuit = VerifierCircuit { matrices, key_hash, assignment };
+        let proof = Groth16::<Bls12_381>::create_random_proof_with_reduction(circuit, &key.proving_key, rng)
+            .map_err(|e| EOSError::PIOPError(format!("compression failed: {}", e)))?;
+        Ok(CompressedProof { context, witness_commitment: commitment.clone(), proof, binding_opening })
+    }
+}
+
+impl WireFormat for CompressedProof {
+    fn encode(&self, out: &mut Vec<u8>) {
+        write_len(out, self.context.len());
+        out.extend_from_slice(&self.context);
+        self.witness_commitment.encode(out);
+        write_point::<G1Projective>(out, &self.proof.a);
+        write_point::<G2Projective>(out, &self.proof.b);
+        write_point::<G1Projective>(out, &self.proof.c);
+        self.binding_opening.encode(out);
+    }
+
+    fn decode(reader: &mut ByteReader<'_>) -> Result<Self, DecodeError> {
+        let len = reader.read_len(1)?;
+        let context = reader.read_bytes(len)?.to_vec();
+        let witness_commitment = PolynomialCommitment::decode(reader)?;
+        let proof = Proof {
+            a: reader.read_point::<G1Projective>()?,
+            b: reader.read_point::<G2Projective>()?,
+            c: reader.read_point::<G1Projective>()?,
+        };
+        Ok(CompressedProof { context, witness_commitment, proof, binding_opening: OpeningProof::decode(reader)? })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mpc::{CollaborationMode, ExecCircuit, ShamirSecretSharing};
+    use ark_poly::Polynomial;
+    use ark_relations::r1cs::ConstraintSystem;
+    use ark_std::test_rng;
+
+    type Protocol = EOSProtocol<Bls12_381, Fr, ShamirSecretSharing<Fr>, CollaborationMode>;
+
+    /// `x_{i+1} = x_i²` from x_0 = 3 over `steps` steps, with the last value public
+    fn squaring_chain(steps: usize) -> (ConstraintSystem<Fr>, Vec<Fr>, Fr) {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let mut values = vec![Fr::from(3u64)];
+        for _ in 0..steps {
+            values.push(values.last().unwrap().square());
+        }
+        let output = *values.last().unwrap();
+        let y = cs.new_input_variable(|| Ok(output)).unwrap();
+        let vars: Vec<_> = values.iter().map(|value| cs.new_witness_variable(|| Ok(*value)).unwrap()).collect();
+        for step in vars.windows(2) {
+            cs.enforce_constraint(ark_relations::lc!() + step[0], ark_relations::lc!() + step[0], ark_relations::lc!() + step[1])
+                .unwrap();
+        }
+        cs.enforce_constraint(
+            ark_relations::lc!() + vars[steps],
+            ark_relations::lc!() + ark_relations::r1cs::Variable::One,
+            ark_relations::lc!() + y,
+        )
+        .unwrap();
+        (cs.into_inner().unwrap(), values, output)
+    }
+
+    fn delegate(steps: usize) -> (Protocol, DelegationResult<Bls12_381, Fr>, Vec<Fr>, Fr) {
+        let mut rng = test_rng();
+        let (circuit, witness, output) = squaring_chain(steps);
+        let executor = ExecCircuit::new(1, 3, ShamirSecretSharing::new());
+        let mut protocol = Protocol::new(executor, CollaborationMode::new(2, true, false), 128, &mut rng);
+        protocol.params.transcript_hash = TranscriptHash::Poseidon;
+        protocol.preprocessing_state = Some(Protocol::preprocessing(&circuit, 128, &mut rng).unwrap());
+        let result = protocol.delegate_computation(&circuit, &witness, &[output], &mut rng).unwrap();
+        (protocol, result, witness, output)
+    }
+
+    #[test]
+    fn test_compressed_proof_is_constant_size() {
+        let mut rng = test_rng();
+        let (protocol, result, witness, output) = delegate(3);
+        let shape = CompressionShape::of(&result, &[output], &result.transcript_context()).unwrap();
+        let key = protocol.compression_setup(shape, &mut rng).unwrap();
+        let compressed = result.compress(&protocol, &key, &witness, &[output], &mut rng).unwrap();
+        assert!(protocol.verify_compressed(&key, &compressed, &[output]).unwrap());
+
+        // Three group elements of Groth16, the commitment and the binding
+        // opening, whatever the circuit
+        let bytes = compressed.to_bytes();
+        let opening = compressed.binding_opening.to_bytes().len();
+        assert_eq!(bytes.len(), 4 + 3 * point_size::<G1Projective>() + point_size::<G2Projective>() + opening);
+        let (_, larger, _, _) = delegate(12);
+        assert!(bytes.len() < result.piop_proof.as_ref().unwrap().len());
+        assert!(result.piop_proof.as_ref().unwrap().len() < larger.piop_proof.as_ref().unwrap().len());
+        let decoded = CompressedProof::from_bytes(&bytes).unwrap();
+        assert!(protocol.verify_compressed(&key, &decoded, &[output]).unwrap());
+
+        // The proof is for its public input, key and commitment only
+        assert!(!protocol.verify_compressed(&key, &compressed, &[output + Fr::one()]).unwrap());
+        let (other_protocol, other, _, other_output) = delegate(2);
+        assert!(!other_protocol.verify_compressed(&key, &compressed, &[output]).unwrap());
+        let mut swapped = compressed.clone();
+        swapped.witness_commitment = parse_proof(&other).unwrap().witness_commitments[0].clone();
+        assert!(!protocol.verify_compressed(&key, &swapped, &[output]).unwrap());
+        let mut moved = compressed;
+        moved.binding_opening = protocol.commitment_scheme.open(
+            &DensePolynomial::from_coefficients_slice(&witness),
+            moved.binding_opening.point + Fr::one(),
+        );
+        assert!(!protocol.verify_compressed(&key, &moved, &[output]).unwrap());
+
+        // A result that does not verify, or a witness it does not commit to,
+        // is not compressed
+        assert!(other.compress(&protocol, &key, &witness, &[other_output], &mut rng).is_err());
+        assert!(result.compress(&protocol, &key, &witness[1..], &[output], &mut rng).is_err());
+        let mut forged = result;
+        forged.piop_proof.as_mut().unwrap()[0] ^= 1;
+        assert!(forged.compress(&protocol, &key, &witness, &[output], &mut rng).is_err());
+    }
+
+    #[test]
+    fn test_verifier_circuit_rejects_a_bad_transcript() {
+        let (protocol, result, witness, output) = delegate(2);
+        let shape = CompressionShape::of(&result, &[output], &result.transcript_context()).unwrap();
+        let (matrices, key_hash) = protocol.compression_keys().unwrap();
+
+        // The honest assignment `compress` builds, then one piece broken at a time
+        let proof = parse_proof(&result).unwrap();
+        let commitment = &proof.witness_commitments[0];
+        let mut coefficients = witness;
+        coefficients.resize(1 << shape.witness_vars, Fr::zero());
+        let point = binding_point(commitment, &coefficients);
+        let honest = VerifierAssignment {
+            context: result.transcript_context(),
+            public_inputs: vec![output],
+            commitment: commitment.to_bytes(),
+            binding_point: point,
+            binding_evaluation: DensePolynomial::from_coefficients_slice(&coefficients).evaluate(&point),
+            zerocheck_rounds: proof.sumcheck_proofs[0].to_product(3).unwrap().rounds,
+            row_evaluations: proof.constraint_claims.as_ref().unwrap().row_evaluations,
+            linear_rounds: proof.sumcheck_proofs[1].to_product(2).unwrap().rounds,
+            coefficients,
+        };
+        let satisfied = |assignment: VerifierAssignment| {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            VerifierCircuit { matrices, key_hash, assignment }.generate_constraints(cs.clone()).unwrap();
+            cs.is_satisfied().unwrap()
+        };
+        assert!(satisfied(honest.clone()));
+
+        let mut tampered = honest.clone();
+        tampered.zerocheck_rounds[0][1] += Fr::one();
+        assert!(!satisfied(tampered));
+        let mut tampered = honest.clone();
+        tampered.row_evaluations[2] += Fr::one();
+        assert!(!satisfied(tampered));
+        let mut tampered = honest.clone();
+        tampered.linear_rounds[0][0] += Fr::one();
+        assert!(!satisfied(tampered));
+        let mut tampered = honest.clone();
+        tampered.public_inputs[0] += Fr::one();
+        assert!(!satisfied(tampered));
+        // The binding point is the one the coefficients and commitment give
+        let mut tampered = honest.clone();
+        tampered.binding_point += Fr::one();
+        assert!(!satisfied(tampered));
+        let mut tampered = honest;
+        let other = DensePolynomial::from_coefficients_vec(vec![Fr::one()]);
+        tampered.commitment = protocol.commitment_scheme.commit(&other).to_bytes();
+        assert!(!satisfied(tampered));
+    }
+}
@@ -0,0 +1,355 @@
+//! The R1CS constraint proof inside a delegation proof
+//!
+//! A witness commitment opened at a challenge says nothing about whether the
+//! witness satisfies the circuit. The constraint proof closes that gap in
+//! three steps, all on the delegation transcript after the opening point:
+//!
+//! 1. A zerocheck that `a∘b − c` vanishes on every row, where `a = Az`,
+//!    `b = Bz`, `c = Cz` for `z = (1, public_inputs, witness)`. It ends in
+//!    claims about `ã`, `b̃` and `c̃` at a random row point `ρ`.
+//! 2. A sumcheck reducing `ã(ρ) + γ·b̃(ρ) + γ²·c̃(ρ)` to the witness. With
+//!    `u = Σ_i eq(ρ, i)·(A_i + γ·B_i + γ²·C_i)` the combination is `⟨u, z⟩`;
+//!    the verifier computes the part of the inner product over `(1, public_inputs)`
+//!    itself and the sumcheck proves the rest, `⟨u_w, w⟩`, ending in a claim
+//!    about `w̃` at a point `ρ'`.
+//! 3. A multilinear opening of the committed witness polynomial, whose
+//!    coefficients are the witness, at `ρ'`.
+//!
+//! Coefficients of the committed polynomial past the witness (the blinding
+//! of a hiding commitment) meet zeros in `u_w` and do not enter the claim.
+//!
+//! In zk mode both sumchecks are the hiding variant of `piop::hiding_sumcheck`:
+//! each adds a random multilinear mask `g`, committed with `commit_hiding`
+//! and absorbed before the mask weight, so the round messages no longer
+//! carry partial sums of witness-derived tables. Each mask's commitment is
+//! opened at the end of its sumcheck after the witness opening.
+
+use ark_ec::pairing::Pairing;
+use ark_ff::{Field, One, PrimeField, Zero};
+
+use crate::circuit::KZGCommitmentScheme;
+use crate::piop::{
+    eq_table, verify_hiding_product_sumcheck, verify_hiding_r1cs_zerocheck, verify_product_sumcheck,
+    verify_r1cs_zerocheck, Domain, PolynomialConsistencyProof, SumcheckProof, Transcript, ZerocheckProof,
+};
+use crate::protocol::ConstraintMatrices;
+#[cfg(feature = "delegator")]
+use {
+    crate::circuit::{Blinding, PolynomialCommitment},
+    crate::piop::{
+        prove_hiding_r1cs_zerocheck, prove_hiding_sum_of_products, prove_product_sumcheck, prove_r1cs_zerocheck,
+        zero_padded, ConstraintClaims, MaskOpening, SumcheckMask,
+    },
+    crate::protocol::{witness_tables, EOSError},
+    ark_poly::{univariate::DensePolynomial, DenseUVPolynomial},
+    ark_std::{rand::Rng, UniformRand},
+};
+
+/// A check of the constraint proof
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConstraintCheck {
+    /// The sumcheck rounds and the claims they end in
+    Identity,
+    /// The round challenges are the transcript's (audit mode only)
+    ChallengeDerivation,
+    /// The multilinear opening of the witness commitment
+    WitnessOpening,
+    /// The multilinear opening of a sumcheck mask's commitment (zk mode only)
+    MaskOpening,
+}
+
+/// A constraint proof check that failed, with the challenge it was evaluated at
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConstraintRejection<F: Field> {
+    pub check: ConstraintCheck,
+    pub challenge: Option<F>,
+}
+
+/// Weight of every column of `z` in `u = Σ_i eq(ρ, i)·(A_i + γ·B_i + γ²·C_i)`
+fn column_weights<F: PrimeField>(matrices: &ConstraintMatrices<F>, row_point: &[F], gamma: F) -> Vec<F> {
+    let eq = eq_table(row_point);
+    let mut weights = Vec::new();
+    let mut scale = F::one();
+    for matrix in [&matrices.a_matrix, &matrices.b_matrix, &matrices.c_matrix] {
+        for (row, entries) in matrix.iter().enumerate() {
+            for &(column, coefficient) in entries {
+                if column >= weights.len() {
+                    weights.resize(column + 1, F::zero());
+                }
+                weights[column] += scale * eq[row] * coefficient;
+            }
+        }
+        scale *= gamma;
+    }
+    weights
+}
+
+/// `eq(point, index)` with `point[0]` the highest bit of `index`
+fn eq_at_index<F: Field>(point: &[F], index: usize) -> F {
+    let n = point.len();
+    point
+        .iter()
+        .enumerate()
+        .map(|(k, r)| {
+            let shift = n - 1 - k;
+            if shift < usize::BITS as usize && (index >> shift) & 1 == 1 {
+                *r
+            } else {
+                F::one() - r
+            }
+        })
+        .product()
+}
+
+/// The zerocheck and linear sumcheck of a constraint proof, and their claims
+#[cfg(feature = "delegator")]
+type ConstraintProof<F, G> = (Vec<SumcheckProof<F>>, ConstraintClaims<F, G>);
+
+/// Random coefficients above a sumcheck mask in its hiding commitment
+#[cfg(feature = "delegator")]
+pub(crate) const MASK_BLINDING: usize = 3;
+
+/// A witness with its committed polynomial and the commitment
+///
+/// The polynomial's coefficients start with `witness`; any past it are
+/// blinding.
+#[cfg(feature = "delegator")]
+pub(crate) struct CommittedWitness<'a, E: Pairing> {
+    pub witness: &'a [E::ScalarField],
+    pub polynomial: &'a DensePolynomial<E::ScalarField>,
+    pub commitment: &'a PolynomialCommitment<E::G1>,
+}
+
+/// A committed sumcheck mask, opened once the constraint proof's sumchecks are done
+#[cfg(feature = "delegator")]
+struct PendingMask<E: Pairing> {
+    mask: SumcheckMask<E::ScalarField, PolynomialCommitment<E::G1>>,
+    /// The mask table as coefficients, plus the blinding above them
+    polynomial: DensePolynomial<E::ScalarField>,
+    num_vars: usize,
+    point: Vec<E::ScalarField>,
+}
+
+#[cfg(feature = "delegator")]
+impl<E: Pairing> PendingMask<E> {
+    /// Draw the blinding of a mask of `len` entries
+    fn blinding(
+        scheme: &KZGCommitmentScheme<E>,
+        len: usize,
+        rng: &mut impl Rng,
+    ) -> Result<Blinding<E::ScalarField>, EOSError> {
+        if len + MASK_BLINDING > scheme.powers_of_g.len() {
+            return Err(EOSError::CommitmentError(format!(
+                "sumcheck mask of length {} exceeds SRS size {}",
+                len + MASK_BLINDING,
+                scheme.powers_of_g.len()
+            )));
+        }
+        let randomness = (0..MASK_BLINDING).map(|_| E::ScalarField::rand(rng)).collect();
+        Ok(Blinding::above_degree(len, DensePolynomial::from_coefficients_vec(randomness)))
+    }
+
+    fn new(
+        mask: SumcheckMask<E::ScalarField, PolynomialCommitment<E::G1>>,
+        table: &[E::ScalarField],
+        blinding: &Blinding<E::ScalarField>,
+        point: Vec<E::ScalarField>,
+    ) -> Self {
+        let num_vars = Domain::for_len(table.len() + MASK_BLINDING).num_vars();
+        Self { mask, polynomial: blinding.apply(&DensePolynomial::from_coefficients_slice(table)), num_vars, point }
+    }
+
+    /// Open the mask's multilinear extension at the end of its sumcheck
+    fn open(self, scheme: &KZGCommitmentScheme<E>, transcript: &mut Transcript) -> MaskOpening<E::ScalarField, E::G1> {
+        let mut point = vec![E::ScalarField::zero(); self.num_vars - self.point.len()];
+        point.extend(self.point);
+        let opening = scheme.open_multilinear(&self.polynomial, &self.mask.commitment, &point, transcript);
+        MaskOpening { mask: self.mask, opening }
+    }
+}
+
+/// Prove that the witness satisfies the circuit, continuing `transcript`
+///
+/// Returns the zerocheck and linear sumcheck, in that order, and the claims
+/// they end in. With `masking` set both sumchecks are hiding: each adds a
+/// random mask drawn from it, committed with `commit_hiding` before the
+/// mask weight is derived and opened after the witness.
+#[cfg(feature = "delegator")]
+pub(crate) fn prove_constraints<E: Pairing, R: Rng>(
+    scheme: &KZGCommitmentScheme<E>,
+    matrices: &ConstraintMatrices<E::ScalarField>,
+    public_inputs: &[E::ScalarField],
+    committed: CommittedWitness<'_, E>,
+    transcript: &mut Transcript,
+    mut masking: Option<&mut R>,
+) -> Result<ConstraintProof<E::ScalarField, E::G1>, EOSError> {
+    let piop_error = |e: crate::piop::SumcheckError| EOSError::PIOPError(e.to_string());
+    let CommittedWitness { witness, polynomial, commitment } = committed;
+    let tables = witness_tables(matrices, public_inputs, witness).map_err(EOSError::TraceMismatch)?;
+    let [a, b, c] = [&tables.a, &tables.b, &tables.c].map(|column| zero_padded(column));
+    let mut pending = Vec::new();
+    let (zerocheck, row_masked) = match masking.as_deref_mut() {
+        None => (prove_r1cs_zerocheck::<_, E::ScalarField>([&a, &b, &c], transcript).map_err(piop_error)?, None),
+        Some(rng) => {
+            let blinding = PendingMask::blinding(scheme, a.len(), rng)?;
+            let commit = |table: &[E::ScalarField]| {
+                scheme.commit_hiding(&DensePolynomial::from_coefficients_slice(table), &blinding)
+            };
+            let hiding = prove_hiding_r1cs_zerocheck([&a, &b, &c], commit, transcript, rng).map_err(piop_error)?;
+            let point = hiding.zerocheck.subclaim.point.clone();
+            pending.push(PendingMask::new(hiding.mask, &hiding.mask_table, &blinding, point));
+            (hiding.zerocheck, Some(hiding.mask_weight * hiding.mask_evaluation))
+        }
+    };
+
+    let gamma: E::ScalarField = transcript.challenge_field(b"constraint_combination");
+    let weights = column_weights(matrices, &zerocheck.subclaim.point, gamma);
+    let offset = 1 + public_inputs.len();
+    let witness_weights = weights.get(offset..).unwrap_or_default();
+    let domain = Domain::for_len(polynomial.coeffs.len().max(witness_weights.len()));
+    let mut coefficients = polynomial.coeffs.clone();
+    coefficients.resize(domain.size, E::ScalarField::zero());
+    let mut u = witness_weights.to_vec();
+    u.resize(domain.size, E::ScalarField::zero());
+    let (linear, linear_masked) = match masking {
+        None => {
+            let linear =
+                prove_product_sumcheck(domain.num_vars(), vec![u, coefficients], transcript).map_err(piop_error)?;
+            (linear, E::ScalarField::zero())
+        }
+        Some(rng) => {
+            let blinding = PendingMask::blinding(scheme, domain.size, rng)?;
+            let commit = |table: &[E::ScalarField]| {
+                scheme.commit_hiding(&DensePolynomial::from_coefficients_slice(table), &blinding)
+            };
+            let terms = vec![(E::ScalarField::one(), vec![u, coefficients])];
+            let hiding = prove_hiding_sum_of_products(domain.num_vars(), terms, commit, transcript, rng)
+                .map_err(piop_error)?;
+            let point = hiding.output.point.clone();
+            pending.push(PendingMask::new(hiding.mask, &hiding.mask_table, &blinding, point));
+            (hiding.output, hiding.mask_weight * hiding.mask_evaluation)
+        }
+    };
+    let witness_opening = scheme.open_multilinear(polynomial, commitment, &linear.point, transcript);
+    let masks = pending.into_iter().map(|mask| mask.open(scheme, transcript)).collect();
+
+    let sumchecks = vec![
+        SumcheckProof::from_product(
+            &zerocheck.proof.sumcheck,
+            zerocheck.subclaim.point,
+            zerocheck.subclaim.expected_evaluation + row_masked.unwrap_or_default(),
+        ),
+        SumcheckProof::from_product(&linear.proof, linear.point, linear.factors[0] * linear.factors[1] + linear_masked),
+    ];
+    Ok((sumchecks, ConstraintClaims { row_evaluations: zerocheck.evaluations, witness_opening, masks }))
+}
+
+/// Verify the constraint proof in `proof` against its witness commitment,
+/// continuing `transcript`
+///
+/// The round challenges are derived from the transcript. With `audit` set
+/// the challenges the proof carries must also be those. A proof with masks
+/// is checked as the hiding variant, whose masks are opened last.
+pub(crate) fn verify_constraints<E: Pairing>(
+    scheme: &KZGCommitmentScheme<E>,
+    matrices: &ConstraintMatrices<E::ScalarField>,
+    public_inputs: &[E::ScalarField],
+    proof: &PolynomialConsistencyProof<E::ScalarField, E::G1>,
+    transcript: &mut Transcript,
+    audit: bool,
+) -> Result<(), ConstraintRejection<E::ScalarField>> {
+    let reject = |check, challenge| ConstraintRejection { check, challenge };
+    let (Some(claims), [zerocheck, linear], [commitment]) =
+        (&proof.constraint_claims, proof.sumcheck_proofs.as_slice(), proof.witness_commitments.as_slice())
+    else {
+        return Err(reject(ConstraintCheck::Identity, None));
+    };
+    let masks = match claims.masks.as_slice() {
+        [] => None,
+        [row_mask, linear_mask] => Some((row_mask, linear_mask)),
+        _ => return Err(reject(ConstraintCheck::Identity, None)),
+    };
+
+    // 1. a∘b − c vanishes on every row, given the claimed row evaluations
+    let num_rows = matrices.a_matrix.len().max(matrices.b_matrix.len()).max(matrices.c_matrix.len());
+    let last = |proof: &SumcheckProof<E::ScalarField>| proof.challenges.last().copied();
+    let rounds = ZerocheckProof { sumcheck: zerocheck.to_product(3).ok_or(reject(ConstraintCheck::Identity, last(zerocheck)))? };
+    let num_vars = Domain::for_len(num_rows).num_vars();
+    let row_point = match masks {
+        None => verify_r1cs_zerocheck(num_vars, &rounds, &claims.row_evaluations, transcript),
+        Some((row_mask, _)) => verify_hiding_r1cs_zerocheck(
+            num_vars,
+            &rounds,
+            &claims.row_evaluations,
+            &row_mask.mask,
+            row_mask.opening.evaluation,
+            transcript,
+        ),
+    }
+    .map_err(|_| reject(ConstraintCheck::Identity, last(zerocheck)))?;
+    if audit && row_point != zerocheck.challenges {
+        return Err(reject(ConstraintCheck::ChallengeDerivation, last(zerocheck)));
+    }
+
+    // 2. The row evaluations are ⟨u, z⟩; the public part is computed here
+    let gamma: E::ScalarField = transcript.challenge_field(b"constraint_combination");
+    let weights = column_weights(matrices, &row_point, gamma);
+    let offset = 1 + public_inputs.len();
+    let public = std::iter::once(E::ScalarField::one()).chain(public_inputs.iter().copied());
+    let public_part: E::ScalarField = weights.iter().zip(public).map(|(weight, value)| *weight * value).sum();
+    let [a, b, c] = claims.row_evaluations;
+    let claim = a + gamma * b + gamma * gamma * c - public_part;
+    let rounds = linear.to_product(2).ok_or(reject(ConstraintCheck::Identity, last(linear)))?;
+    let num_vars = rounds.rounds.len();
+    let witness_weights = weights.get(offset..).unwrap_or_default();
+    if num_vars < usize::BITS as usize && witness_weights.len() > 1 << num_vars {
+        return Err(reject(ConstraintCheck::Identity, None));
+    }
+    let (point, expected, masked) = match masks {
+        None => verify_product_sumcheck(num_vars, 2, claim, &rounds, transcript)
+            .map(|subclaim| (subclaim.point, subclaim.expected_evaluation, E::ScalarField::zero())),
+        Some((_, linear_mask)) => {
+            verify_hiding_product_sumcheck(num_vars, 2, claim, &linear_mask.mask, &rounds, transcript).map(|subclaim| {
+                let masked = subclaim.mask_weight * linear_mask.opening.evaluation;
+                (subclaim.point, subclaim.expected_evaluation, masked)
+            })
+        }
+    }
+    .map_err(|_| reject(ConstraintCheck::Identity, last(linear)))?;
+    if audit && point != linear.challenges {
+        return Err(reject(ConstraintCheck::ChallengeDerivation, last(linear)));
+    }
+    let u: E::ScalarField = witness_weights
+        .iter()
+        .enumerate()
+        .map(|(index, weight)| *weight * eq_at_index(&point, index))
+        .sum();
+    let opening = &claims.witness_opening;
+    let challenge = point.last().copied();
+    if opening.point != point || u * opening.evaluation + masked != expected {
+        return Err(reject(ConstraintCheck::Identity, challenge));
+    }
+
+    // 3. The committed witness has that multilinear evaluation
+    if !scheme.verify_multilinear(commitment, opening, transcript) {
+        return Err(reject(ConstraintCheck::WitnessOpening, challenge));
+    }
+
+    // 4. Each committed mask has the evaluation its sumcheck used. The
+    //    opening point pads the sumcheck's point with zeros in front, which
+    //    selects the mask table below the blinding.
+    if let Some((row_mask, linear_mask)) = masks {
+        for (mask, point) in [(row_mask, &row_point), (linear_mask, &point)] {
+            let challenge = point.last().copied();
+            let opened = &mask.opening.point;
+            let padding = opened.len().checked_sub(point.len()).ok_or(reject(ConstraintCheck::Identity, challenge))?;
+            if opened[..padding].iter().any(|x| !x.is_zero()) || opened[padding..] != point[..] {
+                return Err(reject(ConstraintCheck::Identity, challenge));
+            }
+            if !scheme.verify_multilinear(&mask.mask.commitment, &mask.opening, transcript) {
+                return Err(reject(ConstraintCheck::MaskOpening, challenge));
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,159 @@
+//! Time-boxed delegation of a computation split into sub-circuits
+//!
+//! Latency-sensitive callers would rather get part of a computation proved
+//! on time than all of it late. `delegate_within` delegates the parts in
+//! order, each indexed against one `UniversalSetup`, and verifies every
+//! finished part before moving on. It stops before a part once the deadline
+//! has passed, or once the time per constraint measured on the finished
+//! parts says the next one would overrun it. A part already running is
+//! never interrupted, so the finished parts are always complete, verified
+//! proofs; what is left is described by an `UnfinishedWork` the delegator
+//! can run locally or delegate again later.
+
+use std::time::{Duration, Instant};
+
+use ark_ec::pairing::Pairing;
+use ark_ff::PrimeField;
+use ark_std::rand::Rng;
+
+use crate::custom_circuits::{CircuitUsage, CustomCircuit};
+use crate::mpc::{OperationMode, SecretSharing};
+use crate::protocol::{DelegationResult, EOSError, EOSProtocol, UniversalSetup, VerificationOutcome};
+
+/// A sub-circuit delegated and verified before the deadline
+#[derive(Debug)]
+pub struct CompletedPart<E: Pairing, F: PrimeField> {
+    /// Position of the part in the delegated list
+    pub index: usize,
+    pub result: DelegationResult<E, F>,
+    pub outcome: VerificationOutcome<F>,
+}
+
+/// Parts not started before the deadline
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnfinishedWork {
+    /// Positions of the remaining parts, in order
+    pub remaining: Vec<usize>,
+    /// Combined size of the remaining parts
+    pub usage: CircuitUsage,
+    /// Time already spent when the run stopped
+    pub elapsed: Duration,
+}
+
+impl UnfinishedWork {
+    /// The remaining parts of `parts`, the list that was delegated
+    pub fn remaining_parts<'a, F: PrimeField>(
+        &'a self,
+        parts: &'a [CustomCircuit<F>],
+    ) -> impl Iterator<Item = &'a CustomCircuit<F>> + 'a {
+        self.remaining.iter().map(move |&index| &parts[index])
+    }
+}
+
+/// Outcome of a time-boxed delegation
+#[derive(Debug)]
+pub struct TimeBoxedResult<E: Pairing, F: PrimeField> {
+    pub completed: Vec<CompletedPart<E, F>>,
+    /// `None` if every part finished in time
+    pub unfinished: Option<UnfinishedWork>,
+}
+
+impl<E: Pairing, F: PrimeField> TimeBoxedResult<E, F> {
+    pub fn is_complete(&self) -> bool {
+        self.unfinished.is_none()
+    }
+}
+
+impl<E, F, SS, OM> EOSProtocol<E, F, SS, OM>
+where
+    E: Pairing<ScalarField = F>,
+    F: PrimeField,
+    SS: SecretSharing<F>,
+    OM: OperationMode<F, SS>,
+{
+    /// Delegate `parts` in order, stopping at the first part that cannot finish by `deadline`
+    ///
+    /// `self` must commit under `setup` (see `EOSProtocol::with_setup`).
+    /// Each part is indexed into `preprocessing_state` before it runs, so
+    /// afterwards the state is that of the last part started. A part whose
+    /// proof fails verification aborts the run with `VerificationFailed`.
+    pub fn delegate_within(
+        &mut self,
+        setup: &UniversalSetup<E>,
+        parts: &[CustomCircuit<F>],
+        deadline: Instant,
+        rng: &mut impl Rng,
+    ) -> Result<TimeBoxedResult<E, F>, EOSError> {
+        let start = Instant::now();
+        let mut completed = Vec::new();
+        let mut constraints_done = 0usize;
+        for (index, part) in parts.iter().enumerate() {
+            let now = Instant::now();
+            let per_constraint = match constraints_done {
+                0 => Duration::ZERO,
+                done => (now - start) / done as u32,
+            };
+            if now + per_constraint * part.num_constraints.max(1) as u32 >= deadline {
+                let mut usage = CircuitUsage::default();
+                for part in &parts[index..] {
+                    let part_usage = part.usage();
+                    usage.constraints += part_usage.constraints;
+                    usage.variables += part_usage.variables;
+                    usage.multiplications += part_usage.multiplications;
+                }
+                let remaining = (index..parts.len()).collect();
+                let unfinished = UnfinishedWork { remaining, usage, elapsed: start.elapsed() };
+                return Ok(TimeBoxedResult { completed, unfinished: Some(unfinished) });
+            }
+
+            self.preprocessing_state = Some(Self::index(setup, &part.to_constraint_system())?);
+            let result = self.delegate_circuit(part, rng)?;
+            let outcome = self.verify_computation(&result, &part.public_inputs)?;
+            if !outcome.is_accepted() {
+                return Err(EOSError::VerificationFailed);
+            }
+            constraints_done += part.num_constraints.max(1);
+            completed.push(CompletedPart { index, result, outcome });
+        }
+        Ok(TimeBoxedResult { completed, unfinished: None })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::custom_circuits::CircuitTemplates;
+    use crate::mpc::{CollaborationMode, ExecCircuit, ShamirSecretSharing};
+    use ark_bls12_381::{Bls12_381, Fr};
+    use ark_std::test_rng;
+
+    type TestField = Fr;
+    type Protocol = EOSProtocol<Bls12_381, TestField, ShamirSecretSharing<TestField>, CollaborationMode>;
+
+    #[test]
+    fn test_deadline_returns_verified_prefix() {
+        let mut rng = test_rng();
+        let setup = Protocol::universal_setup(32, &mut rng);
+        let executor = ExecCircuit::new(0, 3, ShamirSecretSharing::new());
+        let mut protocol = Protocol::with_setup(executor, CollaborationMode::new(2, true, false), 128, &setup);
+        let parts = vec![
+            CircuitTemplates::square_root_verification(TestField::from(7u64), TestField::from(49u64)),
+            CircuitTemplates::repeated_squaring(TestField::from(3u64), 3),
+            CircuitTemplates::square_root_verification(TestField::from(5u64), TestField::from(25u64)),
+        ];
+
+        let far = Instant::now() + Duration::from_secs(3600);
+        let result = protocol.delegate_within(&setup, &parts, far, &mut rng).unwrap();
+        assert!(result.is_complete());
+        assert_eq!(result.completed.iter().map(|part| part.index).collect::<Vec<_>>(), vec![0, 1, 2]);
+        assert!(result.completed.iter().all(|part| part.outcome.is_accepted()));
+
+        // A deadline already passed leaves everything for the delegator
+        let result = protocol.delegate_within(&setup, &parts, Instant::now(), &mut rng).unwrap();
+        assert!(result.completed.is_empty());
+        let unfinished = result.unfinished.unwrap();
+        assert_eq!(unfinished.remaining, vec![0, 1, 2]);
+        assert_eq!(unfinished.usage.constraints, parts.iter().map(|part| part.num_constraints).sum::<usize>());
+        assert_eq!(unfinished.remaining_parts(&parts).count(), 3);
+    }
+}
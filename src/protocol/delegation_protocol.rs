@@ -4,20 +4,40 @@
 //! as described in the paper. EOS allows efficient outsourcing of SNARK computations
 //! while maintaining privacy and verifiability through MPC and PIOP techniques.
 
-use ark_ff::{Field, PrimeField};
-use ark_ec::{pairing::Pairing, AffineRepr};
+use ark_ff::{Field, PrimeField, Zero};
+use ark_ec::{pairing::Pairing, CurveGroup};
+use ark_poly::{DenseUVPolynomial, Polynomial, univariate::DensePolynomial};
 use ark_relations::r1cs::ConstraintSystem;
-use ark_std::rand::Rng;
+use ark_std::rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
 
-use crate::mpc::{ExecCircuit, SecretSharing, OperationMode, ExecutionError, ShamirShare};
-use crate::piop::ConsistencyChecker;
-use crate::circuit::KZGCommitmentScheme;
+use crate::evaluation::{HybridSplit, MetricsSnapshot, PerformanceMetrics, ProvingStage, Timer};
+use crate::mpc::{ExecCircuit, ExecutionTrace, SecretSharing, OperationMode, ExecutionError, ShamirShare, WitnessInput};
+use crate::piop::{ConsistencyChecker, PolynomialConsistencyProof, Transcript, TranscriptHash};
+use crate::circuit::{Blinding, KZGCommitmentScheme, OpeningProof, PolynomialCommitment, UniversalParams};
+use crate::custom_circuits::{BudgetExceeded, BudgetResource, CircuitBudget, CircuitUsage};
+use crate::protocol::{
+    circuit_fingerprint, commit_prover_seed, delegation_transcript_with, seed_context, verification_key_hash,
+    check_trace_binding, prove_constraints, verify_constraints, witness_tables, CircuitParameters, CommittedWitness, ConstraintMatrices, SessionError, TraceBindingError, VerificationCheck,
+    VerificationKey, VerificationOutcome, MASK_BLINDING, PROVER_SEED_DOMAIN,
+};
+use crate::sampling::SeededSampler;
+use crate::serialization::WireFormat;
+
+/// Evaluations of the witness polynomial a proof reveals, each of which a
+/// hiding commitment must mask: the KZG opening and the final evaluation of
+/// the sumcheck
+const HIDDEN_EVALUATIONS: usize = 2;
+
+/// Domain of the generator the zk sumcheck masks are drawn from
+const MASK_SEED_DOMAIN: &[u8] = b"EOS-sumcheck-masks-v1";
 
 /// MPC computation result
 #[derive(Debug, Clone)]
 pub struct MPCResult<F: Field> {
     pub shared_outputs: Vec<ShamirShare<F>>,
-    pub computation_trace: Vec<F>,
+    /// Every constraint's `⟨A_i, z⟩`, `⟨B_i, z⟩`, `⟨C_i, z⟩` as the workers computed them
+    pub computation_trace: ExecutionTrace<F>,
 }
 
 /// EOS protocol errors
@@ -28,6 +48,16 @@ pub enum EOSError {
     PIOPError(String),
     CommitmentError(String),
     VerificationFailed,
+    DuplicateDelegator(u64),
+    InvalidWitnessSplit(String),
+    /// A retry would send share vectors that an earlier attempt already sent
+    SharingReused(u32),
+    /// The witness being proven is not the one the workers executed
+    TraceMismatch(TraceBindingError),
+    /// The circuit does not fit the preprocessing capacity
+    BudgetExceeded(BudgetExceeded),
+    /// A multi-delegator session message could not be delivered or decoded
+    Session(SessionError),
 }
 
 impl std::fmt::Display for EOSError {
@@ -38,6 +68,12 @@ impl std::fmt::Display for EOSError {
             EOSError::PIOPError(msg) => write!(f, "PIOP error: {}", msg),
             EOSError::CommitmentError(msg) => write!(f, "Commitment error: {}", msg),
             EOSError::VerificationFailed => write!(f, "Verification failed"),
+            EOSError::DuplicateDelegator(id) => write!(f, "Delegator {} appears twice in session", id),
+            EOSError::InvalidWitnessSplit(msg) => write!(f, "Invalid witness split: {}", msg),
+            EOSError::SharingReused(attempt) => write!(f, "Attempt {} would resend an earlier witness sharing", attempt),
+            EOSError::TraceMismatch(e) => write!(f, "Trace mismatch: {}", e),
+            EOSError::BudgetExceeded(e) => write!(f, "Budget exceeded: {}", e),
+            EOSError::Session(e) => write!(f, "Session error: {}", e),
         }
     }
 }
@@ -62,6 +98,46 @@ impl<E: Pairing> Default for KZGCommitment<E> {
 /// 1. Preprocessing: Setup trusted parameters and circuit preprocessing
 /// 2. Delegation: Outsource computation with privacy preservation
 /// 3. Verification: Verify the outsourced computation results
+///
+/// A delegation of `x * x = y` with `x` private, from setup to verdict:
+///
+/// ```
+/// use ark_bls12_381::{Bls12_381, Fr};
+/// use eos_delegation::custom_circuits::CustomCircuit;
+/// use eos_delegation::mpc::{CollaborationMode, ExecCircuit, ShamirSecretSharing};
+/// use eos_delegation::protocol::EOSProtocol;
+///
+/// type Protocol = EOSProtocol<Bls12_381, Fr, ShamirSecretSharing<Fr>, CollaborationMode>;
+/// let mut rng = ark_std::test_rng();
+///
+/// let mut circuit = CustomCircuit::new("square".to_string());
+/// let x = circuit.add_private_witness(Fr::from(7u64));
+/// let y = circuit.add_public_input(Fr::from(49u64));
+/// circuit.add_multiplication_constraint(x, x, y);
+/// let cs = circuit.to_constraint_system();
+///
+/// // Phase 1: a universal setup, then the circuit's keys
+/// let setup = Protocol::universal_setup(16, &mut rng);
+/// let executor = ExecCircuit::new(1, 3, ShamirSecretSharing::new());
+/// let mut protocol = Protocol::with_setup(executor, CollaborationMode::new(2, true, false), 128, &setup);
+/// protocol.preprocessing_state = Some(Protocol::index(&setup, &cs).unwrap());
+///
+/// // Phase 2: the workers compute on shares of the witness
+/// let result = protocol
+///     .delegate_computation(&cs, &circuit.private_witnesses, &circuit.public_inputs, &mut rng)
+///     .unwrap();
+///
+/// // Phase 3: the verifier sees the proof and the public inputs only
+/// assert!(protocol.verify_computation(&result, &circuit.public_inputs).unwrap().is_accepted());
+/// let wrong_output = [Fr::from(50u64)];
+/// assert!(!protocol.verify_computation(&result, &wrong_output).unwrap().is_accepted());
+///
+/// // A witness that does not satisfy the circuit yields a proof that fails
+/// let result = protocol
+///     .delegate_computation(&cs, &[Fr::from(8u64)], &circuit.public_inputs, &mut rng)
+///     .unwrap();
+/// assert!(!protocol.verify_computation(&result, &circuit.public_inputs).unwrap().is_accepted());
+/// ```
 pub struct EOSProtocol<E, F, SS, OM>
 where
     E: Pairing,
@@ -76,11 +152,15 @@ where
     /// PIOP consistency checker
     pub piop_checker: ConsistencyChecker<F>,
     /// KZG commitment scheme for polynomial commitments
-    pub commitment_scheme: KZGCommitmentScheme<F, E::G1>,
+    pub commitment_scheme: KZGCommitmentScheme<E>,
     /// Protocol parameters
     pub params: EOSParams<E, F>,
     /// Preprocessing state
     pub preprocessing_state: Option<PreprocessingState<E, F>>,
+    /// Timings of the delegation phases, accumulated across delegations
+    pub metrics: PerformanceMetrics,
+    /// Snapshot of `metrics` taken at the end of every phase, with its name
+    pub phase_snapshots: Vec<(String, MetricsSnapshot)>,
 }
 
 /// EOS protocol parameters
@@ -94,9 +174,45 @@ pub struct EOSParams<E: Pairing, F: Field> {
     pub max_degree: usize,
     /// Soundness error bound
     pub soundness_error: f64,
+    /// Hash function of the delegation transcript
+    pub transcript_hash: TranscriptHash,
+    /// Largest circuit accepted for delegation, on top of the SRS capacity
+    pub budget: CircuitBudget,
+    /// Proving stages the delegator runs itself instead of outsourcing
+    pub hybrid: HybridSplit,
+    /// Commit to the witness with a hiding commitment, so neither the
+    /// commitment nor the evaluations in the proof reveal anything about it;
+    /// the deployment setting is `EosConfig::zk`
+    pub zk: bool,
+    /// Verify in audit mode: besides checking the constraint proof at the
+    /// challenges the transcript derives, reject a proof whose carried copies
+    /// of those challenges differ
+    pub audit: bool,
     _phantom: std::marker::PhantomData<(E, F)>,
 }
 
+impl<E: Pairing, F: Field> EOSParams<E, F> {
+    /// Default maximum degree of the protocol's KZG setup
+    pub const DEFAULT_MAX_DEGREE: usize = 1 << 8;
+
+    pub fn new(security_parameter: usize) -> Self {
+        // Same threshold choice as `ProtocolParams::new`, usable with small committees
+        let threshold = std::cmp::min(security_parameter / 2, 2);
+        Self {
+            security_parameter,
+            threshold,
+            max_degree: Self::DEFAULT_MAX_DEGREE,
+            soundness_error: 2f64.powi(-(security_parameter as i32)),
+            transcript_hash: TranscriptHash::default(),
+            budget: CircuitBudget::unlimited(),
+            hybrid: HybridSplit::default(),
+            zk: false,
+            audit: false,
+            _phantom: std::marker::PhantomData,
+        }
+    }
+}
+
 /// EOS preprocessing state
 #[derive(Debug, Clone)]
 pub struct PreprocessingState<E: Pairing, F: Field> {
@@ -108,43 +224,45 @@ pub struct PreprocessingState<E: Pairing, F: Field> {
     pub verification_key: VerificationKey<E>,
 }
 
-/// Circuit parameters from preprocessing
+/// Evaluation key for the prover
 #[derive(Debug, Clone)]
-pub struct CircuitParameters<F: Field> {
-    /// Number of constraints
-    pub num_constraints: usize,
-    /// Number of variables
-    pub num_variables: usize,
-    /// Number of public inputs
-    pub num_public_inputs: usize,
-    /// Constraint matrices (A, B, C) in compressed form
-    pub constraint_matrices: ConstraintMatrices<F>,
+pub struct EvaluationKey<E: Pairing> {
+    pub powers_of_tau: Vec<E::G1Affine>,
 }
 
-/// Constraint matrices for R1CS
-#[derive(Debug, Clone)]
-pub struct ConstraintMatrices<F: Field> {
-    pub a_matrix: Vec<Vec<(usize, F)>>, // Sparse representation
-    pub b_matrix: Vec<Vec<(usize, F)>>,
-    pub c_matrix: Vec<Vec<(usize, F)>>,
+impl<E: Pairing, F: Field> PreprocessingState<E, F> {
+    /// Resources a delegation of the indexed circuit consumes
+    ///
+    /// Compare against `PreprocessingStore::budget` to check that a triple
+    /// pool generated in the universal stage covers this circuit.
+    pub fn usage(&self) -> CircuitUsage {
+        let params = &self.circuit_params;
+        CircuitUsage {
+            constraints: params.num_constraints,
+            variables: params.num_variables - params.num_public_inputs - 1,
+            multiplications: params.num_constraints,
+        }
+    }
 }
 
-/// Evaluation key for the prover
+/// Circuit-independent output of the trusted setup
+///
+/// Produced once by `EOSProtocol::universal_setup` and shared by every
+/// circuit up to its size: `EOSProtocol::index` derives a circuit's keys by
+/// truncating these, and `EOSProtocol::with_setup` builds protocol instances
+/// that commit under its SRS trimmed to the degree they need. Beaver triples and random bits do not depend
+/// on the circuit either; a `PreprocessingStore` generated alongside the
+/// setup serves any circuit whose `PreprocessingState::usage` fits its budget.
 #[derive(Debug, Clone)]
-pub struct EvaluationKey<E: Pairing> {
-    pub powers_of_tau: Vec<E::G1Affine>,
-    pub beta_powers: Vec<E::G1Affine>,
-    pub alpha_beta_powers: Vec<E::G1Affine>,
+pub struct UniversalSetup<E: Pairing> {
+    pub params: UniversalParams<E>,
 }
 
-/// Verification key for the verifier
-#[derive(Debug, Clone)]
-pub struct VerificationKey<E: Pairing> {
-    pub alpha: E::G2Affine,
-    pub beta: E::G2Affine,
-    pub gamma: E::G2Affine,
-    pub delta: E::G2Affine,
-    pub ic: Vec<E::G1Affine>, // For public inputs
+impl<E: Pairing> UniversalSetup<E> {
+    /// Number of variables the setup supports
+    pub fn capacity(&self) -> usize {
+        self.params.powers_of_g.len()
+    }
 }
 
 impl<E, F, SS, OM> EOSProtocol<E, F, SS, OM>
@@ -154,40 +272,143 @@ where
     SS: SecretSharing<F>,
     OM: OperationMode<F, SS>,
 {
+    /// Create a protocol instance with a freshly sampled KZG setup
+    pub fn new<R: Rng>(
+        circuit_executor: ExecCircuit<F, SS>,
+        operation_mode: OM,
+        security_parameter: usize,
+        rng: &mut R,
+    ) -> Self
+    where
+        E: Pairing<ScalarField = F>,
+    {
+        let params = EOSParams::new(security_parameter);
+        let commitment_scheme = KZGCommitmentScheme::setup(params.max_degree, rng);
+
+        Self {
+            circuit_executor,
+            operation_mode,
+            piop_checker: ConsistencyChecker::new(),
+            commitment_scheme,
+            params,
+            preprocessing_state: None,
+            metrics: PerformanceMetrics::new(),
+            phase_snapshots: Vec::new(),
+        }
+    }
+
+    /// Create a protocol instance committing under a shared universal setup
+    pub fn with_setup(
+        circuit_executor: ExecCircuit<F, SS>,
+        operation_mode: OM,
+        security_parameter: usize,
+        setup: &UniversalSetup<E>,
+    ) -> Self {
+        Self::with_trimmed_setup(circuit_executor, operation_mode, security_parameter, setup, setup.capacity() - 1)
+            .expect("a setup covers its own degree")
+    }
+
+    /// Create a protocol instance committing under a universal setup
+    /// trimmed to `max_degree`, so an instance serving small circuits does
+    /// not hold the whole SRS; `None` if the setup is smaller
+    pub fn with_trimmed_setup(
+        circuit_executor: ExecCircuit<F, SS>,
+        operation_mode: OM,
+        security_parameter: usize,
+        setup: &UniversalSetup<E>,
+        max_degree: usize,
+    ) -> Option<Self> {
+        let commitment_scheme = setup.params.trim(max_degree)?;
+        let mut params = EOSParams::new(security_parameter);
+        params.max_degree = max_degree;
+
+        Some(Self {
+            circuit_executor,
+            operation_mode,
+            piop_checker: ConsistencyChecker::new(),
+            commitment_scheme,
+            params,
+            preprocessing_state: None,
+            metrics: PerformanceMetrics::new(),
+            phase_snapshots: Vec::new(),
+        })
+    }
+
+    /// Charge a proving stage to the side `params.hybrid` assigns it to
+    ///
+    /// Both sides run in this process; the split only decides whose time a
+    /// stage counts as, which `HybridReport::from_metrics` reads back.
+    fn record_stage(&mut self, stage: ProvingStage, elapsed: std::time::Duration) {
+        let label = stage.metric_label(self.params.hybrid.is_local(stage));
+        self.metrics.accumulate_stage(&label, elapsed);
+    }
+
+    /// Record a finished phase and snapshot the metrics at the boundary
+    pub(crate) fn end_phase(&mut self, timer: Timer) {
+        let timing = timer.finish();
+        let phase = timing.phase.clone();
+        self.metrics.record_phase(timing);
+        self.phase_snapshots.push((phase, self.metrics.snapshot()));
+    }
+
+    /// Remove and return the snapshots emitted so far
+    pub fn take_phase_snapshots(&mut self) -> Vec<(String, MetricsSnapshot)> {
+        std::mem::take(&mut self.phase_snapshots)
+    }
+
     /// Phase 1: Preprocessing
     /// Setup trusted parameters and preprocess the circuit
+    ///
+    /// One-shot form of `universal_setup` followed by `index`, with a setup
+    /// just large enough for `circuit`. Deployments serving several circuits
+    /// run the two stages separately so the setup is sampled once.
     pub fn preprocessing<R: Rng>(
         circuit: &ConstraintSystem<F>,
         _security_parameter: usize,
         rng: &mut R,
     ) -> Result<PreprocessingState<E, F>, EOSError> {
-        // 1. Generate circuit parameters
+        let num_variables = circuit.num_instance_variables + circuit.num_witness_variables;
+        let setup = Self::universal_setup(num_variables.next_power_of_two(), rng);
+        Self::index(&setup, circuit)
+    }
+
+    /// Phase 1a: circuit-independent setup supporting up to `max_degree + 1` variables
+    pub fn universal_setup<R: Rng>(max_degree: usize, rng: &mut R) -> UniversalSetup<E> {
+        Self::universal_setup_from(UniversalParams::setup(max_degree, rng))
+    }
+
+    /// Phase 1a over an existing SRS instead of a locally sampled tau
+    ///
+    /// `params` may be converted from `KZGCommitmentScheme::load` or from a
+    /// public ceremony via `crate::srs::import_ptau`; the setup supports as
+    /// many variables as it has powers. Every key `index` derives is read
+    /// off `params`, so nothing depends on a tau sampled here.
+    pub fn universal_setup_from(params: UniversalParams<E>) -> UniversalSetup<E> {
+        UniversalSetup { params }
+    }
+
+    /// Phase 1b: derive a circuit's parameters and keys from a universal setup
+    ///
+    /// Costs one pass over the constraint matrices; no group operations.
+    pub fn index(setup: &UniversalSetup<E>, circuit: &ConstraintSystem<F>) -> Result<PreprocessingState<E, F>, EOSError> {
         let circuit_params = CircuitParameters {
             num_constraints: circuit.num_constraints,
             num_variables: circuit.num_instance_variables + circuit.num_witness_variables,
-            num_public_inputs: circuit.num_instance_variables,
+            // ark-relations counts the constant one as an instance variable
+            num_public_inputs: circuit.num_instance_variables - 1,
             constraint_matrices: Self::extract_constraint_matrices(circuit),
         };
+        if circuit_params.num_variables > setup.capacity() {
+            return Err(EOSError::BudgetExceeded(BudgetExceeded {
+                resource: BudgetResource::Variables,
+                limit: setup.capacity(),
+                requested: circuit_params.num_variables,
+            }));
+        }
 
-        // 2. Generate trusted setup for KZG
-        let max_degree = circuit_params.num_variables.next_power_of_two();
-        let tau = F::rand(rng);
-        
-        // Generate evaluation key
-        let evaluation_key = EvaluationKey {
-            powers_of_tau: Self::generate_powers_of_tau(tau, max_degree),
-            beta_powers: Self::generate_beta_powers(tau, max_degree, rng),
-            alpha_beta_powers: Self::generate_alpha_beta_powers(tau, max_degree, rng),
-        };
-
-        // Generate verification key (simplified placeholders)
-        let verification_key = VerificationKey {
-            alpha: E::G2Affine::zero(),
-            beta: E::G2Affine::zero(), 
-            gamma: E::G2Affine::zero(),
-            delta: E::G2Affine::zero(),
-            ic: vec![E::G1Affine::zero(); circuit_params.num_public_inputs],
-        };
+        let degree = circuit_params.num_variables.next_power_of_two().min(setup.capacity());
+        let evaluation_key = EvaluationKey { powers_of_tau: setup.params.powers_of_g[..degree].to_vec() };
+        let verification_key = VerificationKey::from_params(&setup.params, circuit_params.num_public_inputs);
 
         Ok(PreprocessingState {
             circuit_params,
@@ -198,154 +419,540 @@ where
 
     /// Phase 2: Delegation
     /// Outsource computation with privacy preservation
+    ///
+    /// `witness[j]` is the value of `Variable::Witness(j)` and
+    /// `public_inputs[i]` the value of `Variable::Instance(i + 1)`
     pub fn delegate_computation(
         &mut self,
         circuit: &ConstraintSystem<F>,
         witness: &[F],
         public_inputs: &[F],
         rng: &mut impl Rng,
-    ) -> Result<DelegationResult<E, F>, EOSError> {
+    ) -> Result<DelegationResult<E, F>, EOSError>
+    where
+        E: Pairing<ScalarField = F>,
+    {
         // Ensure preprocessing is done
         let _preprocessing_state = self.preprocessing_state
             .as_ref()
             .ok_or(EOSError::PreprocessingNotDone)?;
+        self.check_budget(circuit, witness)?;
 
         // 1. Secret share the witness using MPC
-        let threshold = self.params.threshold;
-        let mut witness_shares = Vec::new();
-        
-        for &w in witness {
-            let shares = self.circuit_executor.input_secret(w, threshold, rng);
-            witness_shares.push(shares);
-        }
+        let witness_shares = self.share_witness(witness, rng);
 
         // 2. Perform MPC computation on shared circuit
         let mpc_result = self.execute_circuit_mpc(circuit, &witness_shares, public_inputs)?;
+        self.bind_trace(&mpc_result, witness, public_inputs, &[])?;
+
+        // 3-4. Commit to the witness and prove consistency
+        self.prove_delegation(witness, public_inputs, &[])
+    }
+
+    /// Phase 2 with all prover randomness derived from `seed`
+    ///
+    /// The seed commitment is bound into the transcript and returned with the
+    /// result, so the same seed and inputs reproduce the proof bit for bit.
+    pub fn delegate_computation_seeded(
+        &mut self,
+        circuit: &ConstraintSystem<F>,
+        witness: &[F],
+        public_inputs: &[F],
+        seed: [u8; 32],
+    ) -> Result<DelegationResult<E, F>, EOSError>
+    where
+        E: Pairing<ScalarField = F>,
+    {
+        self.preprocessing_state
+            .as_ref()
+            .ok_or(EOSError::PreprocessingNotDone)?;
+        self.check_budget(circuit, witness)?;
+
+        let mut transcript = Transcript::new(PROVER_SEED_DOMAIN);
+        transcript.append_message(b"seed", &seed);
+        let mut sampler = SeededSampler::new(PROVER_SEED_DOMAIN, &transcript.challenge_bytes(b"prover_rng"));
+        let seed_commitment = commit_prover_seed(&seed);
+
+        let witness_shares = self.share_witness_seeded(witness, &mut sampler);
+        let mpc_result = self.execute_circuit_mpc(circuit, &witness_shares, public_inputs)?;
+        let context = seed_context(&seed_commitment);
+        self.bind_trace(&mpc_result, witness, public_inputs, &context)?;
+
+        let blinding = self.witness_blinding(witness.len(), |count| sampler.fields(count));
+        let mut result = self.prove_blinded(&[witness], blinding.as_ref(), public_inputs, &context)?;
+        result.seed_commitment = Some(seed_commitment);
+        Ok(result)
+    }
+
+    /// Largest circuit this instance can delegate
+    ///
+    /// `params.budget` tightened by the SRS, which commits witness polynomials
+    /// of at most `powers_of_g.len()` coefficients. In zk mode it also commits
+    /// the sumcheck masks, one entry per row or per padded witness coefficient,
+    /// which bounds the constraints too. To bound the circuit by a triple pool
+    /// as well, fold `PreprocessingStore::budget` into `params.budget`.
+    pub fn circuit_capacity(&self) -> CircuitBudget {
+        let powers = self.commitment_scheme.powers_of_g.len();
+        let srs = if self.params.zk {
+            // The largest mask table that fits with its blinding; the blinded
+            // witness pads to at most that many coefficients
+            let table: usize = match powers.checked_sub(MASK_BLINDING) {
+                Some(fit) if fit > 0 => 1 << fit.ilog2(),
+                _ => 0,
+            };
+            CircuitBudget::unlimited()
+                .with_max_variables(table.saturating_sub(HIDDEN_EVALUATIONS + 1))
+                .with_max_constraints(table)
+        } else {
+            CircuitBudget::unlimited().with_max_variables(powers)
+        };
+        self.params.budget.intersect(srs)
+    }
+
+    /// Reject a circuit over capacity before any witness is shared
+    pub(crate) fn check_budget(&self, circuit: &ConstraintSystem<F>, witness: &[F]) -> Result<(), EOSError> {
+        // Every R1CS constraint is one multiplication of two linear combinations
+        let usage = CircuitUsage {
+            constraints: circuit.num_constraints,
+            variables: witness.len().max(circuit.num_witness_variables),
+            multiplications: circuit.num_constraints,
+        };
+        self.circuit_capacity().check(&usage).map_err(EOSError::BudgetExceeded)
+    }
+
+    /// Hash of the key and circuit this instance proves and verifies against
+    pub fn verification_key_hash(&self) -> Result<[u8; 32], EOSError>
+    where
+        E: Pairing<ScalarField = F>,
+    {
+        let state = self.preprocessing_state
+            .as_ref()
+            .ok_or(EOSError::PreprocessingNotDone)?;
+        Ok(verification_key_hash::<E>(
+            &self.commitment_scheme,
+            &circuit_fingerprint(&state.circuit_params),
+        ))
+    }
 
-        // 3. Generate PIOP proof for consistency
-        let _piop_proof = self.generate_piop_proof(&mpc_result, public_inputs)?;
+    /// The constraint matrices this instance proves against, for a
+    /// `StreamingVerifier`
+    pub fn constraint_matrices(&self) -> Result<&ConstraintMatrices<F>, EOSError> {
+        let state = self.preprocessing_state
+            .as_ref()
+            .ok_or(EOSError::PreprocessingNotDone)?;
+        Ok(&state.circuit_params.constraint_matrices)
+    }
+
+    /// Secret share each witness element among the workers
+    pub(crate) fn share_witness(&mut self, witness: &[F], rng: &mut impl Rng) -> Vec<Vec<SS::Share>> {
+        let timer = self.metrics.start_timer("witness_sharing");
+        let threshold = self.params.threshold;
+        let shares = witness
+            .iter()
+            .map(|&w| self.circuit_executor.input_secret(w, threshold, rng))
+            .collect();
+        self.end_phase(timer);
+        shares
+    }
+
+    /// Secret share the witness with reproducible randomness
+    pub(crate) fn share_witness_seeded(&mut self, witness: &[F], sampler: &mut SeededSampler) -> Vec<Vec<SS::Share>> {
+        let timer = self.metrics.start_timer("witness_sharing");
+        let threshold = self.params.threshold;
+        let shares = witness
+            .iter()
+            .map(|&w| self.circuit_executor.input_secret_seeded(w, threshold, sampler))
+            .collect();
+        self.end_phase(timer);
+        shares
+    }
+
+    /// Commit to the witness polynomial and generate the PIOP proof
+    pub(crate) fn prove_delegation(
+        &mut self,
+        witness: &[F],
+        public_inputs: &[F],
+        context: &[u8],
+    ) -> Result<DelegationResult<E, F>, EOSError>
+    where
+        E: Pairing<ScalarField = F>,
+    {
+        self.prove_contributions(&[witness], public_inputs, context)
+    }
 
-        // 4. Generate KZG commitments for polynomials
-        let _polynomial_commitments = self.generate_polynomial_commitments(&mpc_result)?;
+    /// Prove for a witness given as a sum of additive contributions
+    ///
+    /// Each contribution is committed and opened on its own. KZG is linear,
+    /// so the sums are exactly the commitment and opening of the combined
+    /// witness, and the proof is the one the whole witness would produce.
+    pub(crate) fn prove_contributions(
+        &mut self,
+        contributions: &[&[F]],
+        public_inputs: &[F],
+        context: &[u8],
+    ) -> Result<DelegationResult<E, F>, EOSError>
+    where
+        E: Pairing<ScalarField = F>,
+    {
+        let len = contributions.iter().map(|contribution| contribution.len()).max().unwrap_or(0);
+        let blinding = self.witness_blinding(len, |count| {
+            let mut rng = rand::thread_rng();
+            (0..count).map(|_| F::rand(&mut rng)).collect()
+        });
+        self.prove_blinded(contributions, blinding.as_ref(), public_inputs, context)
+    }
+
+    /// Blinding of a witness of `len` coefficients when `params.zk` is set
+    ///
+    /// Draws `HIDDEN_EVALUATIONS + 1` coefficients from `randomness` and
+    /// places them above the witness, so the witness coefficients are kept.
+    fn witness_blinding(&self, len: usize, randomness: impl FnOnce(usize) -> Vec<F>) -> Option<Blinding<F>> {
+        self.params.zk.then(|| {
+            Blinding::above_degree(len, DensePolynomial::from_coefficients_vec(randomness(HIDDEN_EVALUATIONS + 1)))
+        })
+    }
+
+    /// Prove for the contributions, adding `blinding` as one more contribution
+    ///
+    /// The blinding is committed and opened like the others, so the proof
+    /// is that of the blinded witness and verifies unchanged.
+    pub(crate) fn prove_blinded(
+        &mut self,
+        contributions: &[&[F]],
+        blinding: Option<&Blinding<F>>,
+        public_inputs: &[F],
+        context: &[u8],
+    ) -> Result<DelegationResult<E, F>, EOSError>
+    where
+        E: Pairing<ScalarField = F>,
+    {
+        let timer = self.metrics.start_timer("proof_generation");
+        let result = self.prove_contributions_inner(contributions, blinding, public_inputs, context);
+        self.end_phase(timer);
+        result
+    }
+
+    fn prove_contributions_inner(
+        &mut self,
+        contributions: &[&[F]],
+        blinding: Option<&Blinding<F>>,
+        public_inputs: &[F],
+        context: &[u8],
+    ) -> Result<DelegationResult<E, F>, EOSError>
+    where
+        E: Pairing<ScalarField = F>,
+    {
+        let key_hash = self.verification_key_hash()?;
+        let start = std::time::Instant::now();
+        let polynomials: Vec<DensePolynomial<F>> = contributions
+            .iter()
+            .map(|contribution| DensePolynomial::from_coefficients_slice(contribution))
+            .chain(blinding.map(Blinding::polynomial))
+            .collect();
+        self.record_stage(ProvingStage::WitnessPolynomial, start.elapsed());
+        let start = std::time::Instant::now();
+        let mut combined = E::G1::zero();
+        for polynomial in &polynomials {
+            for commitment in self.generate_polynomial_commitments(polynomial)? {
+                combined += commitment.commitment;
+            }
+        }
+        self.record_stage(ProvingStage::Commitment, start.elapsed());
+        let polynomial_commitments = vec![PolynomialCommitment { commitment: combined.into_affine() }];
+        // The witness is the sum of the contributions, without the blinding
+        let mut witness = vec![F::zero(); contributions.iter().map(|c| c.len()).max().unwrap_or(0)];
+        for contribution in contributions {
+            for (sum, value) in witness.iter_mut().zip(contribution.iter()) {
+                *sum += value;
+            }
+        }
+        let piop_proof = self.generate_piop_proof(
+            &polynomials,
+            &witness,
+            &polynomial_commitments,
+            &key_hash,
+            public_inputs,
+            context,
+        )?;
 
         Ok(DelegationResult {
             verification_result: true,
             execution_stats: crate::mpc::ExecutionStats::new(),
-            piop_proof: Some(vec![0u8; 32]), // Placeholder proof data
-            polynomial_commitments: vec![vec![0u8; 32]; 3], // Placeholder commitments
+            piop_proof: Some(piop_proof.to_bytes()),
+            polynomial_commitments: polynomial_commitments.iter().map(|c| c.to_bytes()).collect(),
+            seed_commitment: None,
+            verification_key_hash: key_hash,
             _phantom: std::marker::PhantomData,
         })
     }
 
     /// Phase 3: Verification
     /// Verify the outsourced computation results
+    ///
+    /// The proof is checked under the context a standalone or seeded
+    /// delegation uses (`DelegationResult::transcript_context`); see
+    /// `verify_computation_in_context` for session and joint proofs.
     pub fn verify_computation(
         &self,
         result: &DelegationResult<E, F>,
         public_inputs: &[F],
-    ) -> Result<bool, EOSError> {
+    ) -> Result<VerificationOutcome<F>, EOSError>
+    where
+        E: Pairing<ScalarField = F>,
+    {
+        self.verify_computation_in_context(result, public_inputs, &result.transcript_context())
+    }
+
+    /// Verify a result whose proof was produced under `context`
+    ///
+    /// Checks run in order and stop at the first failure: key binding,
+    /// public-input binding of the opening point, the KZG openings, and the
+    /// constraint proof (with its challenges under `params.audit`), which
+    /// shows the committed witness satisfies the circuit for `public_inputs`.
+    pub fn verify_computation_in_context(
+        &self,
+        result: &DelegationResult<E, F>,
+        public_inputs: &[F],
+        context: &[u8],
+    ) -> Result<VerificationOutcome<F>, EOSError>
+    where
+        E: Pairing<ScalarField = F>,
+    {
+        let start = std::time::Instant::now();
+        let reject = |check, challenge| VerificationOutcome::Rejected { check, challenge, elapsed: start.elapsed() };
         let preprocessing_state = self.preprocessing_state
             .as_ref()
             .ok_or(EOSError::PreprocessingNotDone)?;
 
-        // 1. Verify PIOP proof (simplified)
-        if let Some(ref _piop_proof) = result.piop_proof {
-            // Simplified verification - in real implementation would use actual PIOP verification
-            let piop_valid = true; // Placeholder
-            if !piop_valid {
-                return Ok(false);
-            }
+        // 0. The proof must have been produced for our key and circuit; derive
+        //    the hash from the state we hold instead of trusting it
+        let key_hash = self.verification_key_hash()?;
+        if result.verification_key_hash != key_hash {
+            return Ok(reject(VerificationCheck::KeyBinding, None));
         }
+        let Some(bytes) = result.piop_proof.as_deref() else {
+            return Ok(reject(VerificationCheck::PiopIdentity, None));
+        };
+        let proof = PolynomialConsistencyProof::<F, E::G1>::from_bytes(bytes)
+            .map_err(|e| EOSError::PIOPError(format!("malformed proof: {}", e)))?;
 
-        // 2. Verify polynomial commitments (simplified)
-        let commitments_valid = true; // Simplified placeholder
-        if !commitments_valid {
-            return Ok(false);
+        // 1. The opening point is re-derived from the commitments the result
+        //    publishes, the public inputs and the context
+        let published: Vec<Vec<u8>> = proof.witness_commitments.iter().map(|c| c.to_bytes()).collect();
+        if published != result.polynomial_commitments
+            || proof.consistency_proofs.len() != proof.witness_commitments.len()
+        {
+            return Ok(reject(VerificationCheck::CommitmentOpening, None));
+        }
+        let mut transcript = delegation_transcript_with(self.params.transcript_hash, &key_hash, context, public_inputs);
+        for commitment in &proof.witness_commitments {
+            transcript.append_point::<E::G1>(b"witness_commitment", &commitment.commitment);
+        }
+        let point: F = transcript.challenge_field(b"opening_point");
+        if proof.consistency_proofs.iter().any(|opening| opening.point != point) {
+            return Ok(reject(VerificationCheck::PublicInputBinding, Some(point)));
         }
 
-        // 3. Verify final result against public inputs
-        let final_valid = self.verify_final_result(result, public_inputs, &preprocessing_state.verification_key)?;
-
-        Ok(final_valid)
-    }
+        // 2. Every commitment opens to its claimed evaluation
+        for (commitment, opening) in proof.witness_commitments.iter().zip(&proof.consistency_proofs) {
+            if !self.commitment_scheme.verify(commitment, opening) {
+                return Ok(reject(VerificationCheck::CommitmentOpening, Some(point)));
+            }
+        }
 
-    // Helper methods
-    fn extract_constraint_matrices(_circuit: &ConstraintSystem<F>) -> ConstraintMatrices<F> {
-        // Simplified implementation - in practice this would extract
-        // the actual constraint matrices from the R1CS
-        ConstraintMatrices {
-            a_matrix: vec![],
-            b_matrix: vec![],
-            c_matrix: vec![],
+        // 3. The committed witness satisfies the circuit: zerocheck over
+        //    Az∘Bz − Cz, then its claims reduced to an opening of the
+        //    commitment. In audit mode the round challenges must be the ones
+        //    the transcript derives after the opening point
+        let matrices = &preprocessing_state.circuit_params.constraint_matrices;
+        if let Err(rejection) =
+            verify_constraints(&self.commitment_scheme, matrices, public_inputs, &proof, &mut transcript, self.params.audit)
+        {
+            return Ok(reject(rejection.check.into(), rejection.challenge));
         }
+
+        Ok(VerificationOutcome::Accepted { challenge: Some(point), elapsed: start.elapsed() })
     }
 
-    fn generate_powers_of_tau(_tau: F, max_degree: usize) -> Vec<E::G1Affine> {
-        // Simplified placeholder implementation
-        vec![E::G1Affine::zero(); max_degree]
+    /// Check that `witness` gives the trace the workers computed
+    ///
+    /// The binding point is drawn from the delegation transcript, so it is
+    /// fixed only once the key, context and public inputs are.
+    pub(crate) fn bind_trace(
+        &mut self,
+        mpc_result: &MPCResult<F>,
+        witness: &[F],
+        public_inputs: &[F],
+        context: &[u8],
+    ) -> Result<(), EOSError>
+    where
+        E: Pairing<ScalarField = F>,
+    {
+        let start = std::time::Instant::now();
+        let state = self.preprocessing_state.as_ref().ok_or(EOSError::PreprocessingNotDone)?;
+        let tables = witness_tables(&state.circuit_params.constraint_matrices, public_inputs, witness)
+            .map_err(EOSError::TraceMismatch)?;
+        let key_hash = self.verification_key_hash()?;
+        let mut transcript = delegation_transcript_with(self.params.transcript_hash, &key_hash, context, public_inputs);
+        check_trace_binding(&mpc_result.computation_trace, &tables, &mut transcript).map_err(EOSError::TraceMismatch)?;
+        self.metrics.accumulate_timing("trace_binding", start.elapsed());
+        Ok(())
     }
 
-    fn generate_beta_powers(_tau: F, max_degree: usize, _rng: &mut impl Rng) -> Vec<E::G1Affine> {
-        // Simplified placeholder implementation
-        vec![E::G1Affine::zero(); max_degree]
+    // Helper methods
+    fn extract_constraint_matrices(circuit: &ConstraintSystem<F>) -> ConstraintMatrices<F> {
+        // Matrices are only defined once every symbolic combination is inlined
+        let mut circuit = circuit.clone();
+        circuit.inline_all_lcs();
+        let transpose = |rows: Vec<Vec<(F, usize)>>| {
+            rows.into_iter()
+                .map(|row| row.into_iter().map(|(coefficient, column)| (column, coefficient)).collect())
+                .collect()
+        };
+        match circuit.to_matrices() {
+            Some(matrices) => ConstraintMatrices {
+                a_matrix: transpose(matrices.a),
+                b_matrix: transpose(matrices.b),
+                c_matrix: transpose(matrices.c),
+            },
+            // Proving-only systems keep no matrices
+            None => ConstraintMatrices { a_matrix: vec![], b_matrix: vec![], c_matrix: vec![] },
+        }
     }
 
-    fn generate_alpha_beta_powers(_tau: F, max_degree: usize, _rng: &mut impl Rng) -> Vec<E::G1Affine> {
-        // Simplified placeholder implementation
-        vec![E::G1Affine::zero(); max_degree]
+    /// Run the preprocessed constraints on the shared witness and
+    /// reconstruct the trace they produce
+    pub(crate) fn execute_circuit_mpc(
+        &mut self,
+        circuit: &ConstraintSystem<F>,
+        witness_shares: &[Vec<SS::Share>],
+        public_inputs: &[F],
+    ) -> Result<MPCResult<F>, EOSError> {
+        let inputs: Vec<WitnessInput<'_, F, SS>> = witness_shares.iter().map(WitnessInput::Shared).collect();
+        self.execute_circuit_mpc_with_local(circuit, &inputs, public_inputs)
     }
 
-    fn execute_circuit_mpc(
+    /// `execute_circuit_mpc` with the delegator's local witnesses folded in
+    pub(crate) fn execute_circuit_mpc_with_local(
         &mut self,
         _circuit: &ConstraintSystem<F>,
-        _witness_shares: &[Vec<SS::Share>],
-        _public_inputs: &[F],
+        witness: &[WitnessInput<'_, F, SS>],
+        public_inputs: &[F],
     ) -> Result<MPCResult<F>, EOSError> {
-        // Simplified MPC execution
-        Ok(MPCResult {
-            shared_outputs: vec![],
-            computation_trace: vec![],
-        })
+        let timer = self.metrics.start_timer("mpc_execution");
+        let result = self.execute_constraints(witness, public_inputs);
+        self.end_phase(timer);
+        result
+    }
+
+    fn execute_constraints(&mut self, witness: &[WitnessInput<'_, F, SS>], public_inputs: &[F]) -> Result<MPCResult<F>, EOSError> {
+        let matrices = &self.preprocessing_state.as_ref().ok_or(EOSError::PreprocessingNotDone)?.circuit_params.constraint_matrices;
+        let trace = self
+            .circuit_executor
+            .execute_r1cs_with_local(matrices, public_inputs, witness)
+            .and_then(|shared| shared.reconstruct(&self.circuit_executor))
+            .map_err(EOSError::MPCError)?;
+        Ok(MPCResult { shared_outputs: vec![], computation_trace: trace })
     }
 
     fn generate_piop_proof(
-        &self,
-        _mpc_result: &MPCResult<F>,
-        _public_inputs: &[F],
-    ) -> Result<crate::piop::PolynomialConsistencyProof<F, E::G1>, EOSError> {
-        // Generate PIOP consistency proof
-        Ok(crate::piop::PolynomialConsistencyProof {
-            witness_commitments: vec![],
-            consistency_proofs: vec![],
-            sumcheck_proofs: vec![],
+        &mut self,
+        contributions: &[DensePolynomial<F>],
+        witness: &[F],
+        commitments: &[PolynomialCommitment<E::G1>],
+        key_hash: &[u8; 32],
+        public_inputs: &[F],
+        context: &[u8],
+    ) -> Result<PolynomialConsistencyProof<F, E::G1>, EOSError>
+    where
+        E: Pairing<ScalarField = F>,
+    {
+        // Derive the opening point from everything the verifier will see
+        let mut transcript = delegation_transcript_with(self.params.transcript_hash, key_hash, context, public_inputs);
+        for commitment in commitments {
+            transcript.append_point::<E::G1>(b"witness_commitment", &commitment.commitment);
+        }
+        let point: F = transcript.challenge_field(b"opening_point");
+        self.circuit_executor
+            .leakage
+            .record("witness_opening", 1)
+            .map_err(|e| EOSError::MPCError(ExecutionError::RevealBlocked(e)))?;
+
+        // Open every contribution at the same point and add the openings up.
+        // The division by (X - z) and the MSM on its result are timed apart
+        // so a hybrid split can charge them to different sides.
+        let start = std::time::Instant::now();
+        let mut evaluation = F::zero();
+        let mut quotients = Vec::with_capacity(contributions.len());
+        for contribution in contributions {
+            let value = contribution.evaluate(&point);
+            evaluation += value;
+            quotients.push(self.commitment_scheme.compute_quotient_polynomial(contribution, point, value));
+        }
+        self.record_stage(ProvingStage::Quotient, start.elapsed());
+        let start = std::time::Instant::now();
+        let mut proof = E::G1::zero();
+        for quotient in &quotients {
+            proof += self.commitment_scheme.commit(quotient).commitment;
+        }
+        let opening = OpeningProof { proof: proof.into_affine(), evaluation, point };
+        self.record_stage(ProvingStage::OpeningProof, start.elapsed());
+
+        let start = std::time::Instant::now();
+        let witness_polynomial = contributions
+            .iter()
+            .fold(DensePolynomial::zero(), |sum, contribution| &sum + contribution);
+        self.record_stage(ProvingStage::WitnessPolynomial, start.elapsed());
+
+        // Prove the witness satisfies the circuit, against the combined
+        // commitment and on the transcript past the opening point
+        let start = std::time::Instant::now();
+        let state = self.preprocessing_state.as_ref().ok_or(EOSError::PreprocessingNotDone)?;
+        let [commitment] = commitments else {
+            return Err(EOSError::CommitmentError(format!("expected one witness commitment, got {}", commitments.len())));
+        };
+        // In zk mode the sumcheck masks are drawn from the blinding above the
+        // witness, so a seeded proof stays reproducible
+        let mut masking = self.params.zk.then(|| {
+            let mut seed = Transcript::new(MASK_SEED_DOMAIN);
+            seed.append_fields(b"witness_blinding", witness_polynomial.coeffs.get(witness.len()..).unwrap_or_default());
+            ChaCha20Rng::from_seed(seed.challenge_bytes(b"sumcheck_masks"))
+        });
+        let (sumcheck_proofs, claims) = prove_constraints(
+            &self.commitment_scheme,
+            &state.circuit_params.constraint_matrices,
+            public_inputs,
+            CommittedWitness { witness, polynomial: &witness_polynomial, commitment },
+            &mut transcript,
+            masking.as_mut(),
+        )?;
+        self.record_stage(ProvingStage::Sumcheck, start.elapsed());
+
+        Ok(PolynomialConsistencyProof {
+            witness_commitments: commitments.to_vec(),
+            consistency_proofs: vec![opening],
+            sumcheck_proofs,
+            constraint_claims: Some(claims),
         })
     }
 
     fn generate_polynomial_commitments(
         &self,
-        _mpc_result: &MPCResult<F>,
-    ) -> Result<Vec<crate::circuit::PolynomialCommitment<E::G1>>, EOSError> {
-        // Generate polynomial commitments using KZG
-        Ok(vec![])
-    }
-
-    fn verify_polynomial_commitments(
-        &self,
-        _commitments: &[crate::circuit::PolynomialCommitment<E::G1>],
-    ) -> Result<bool, EOSError> {
-        // Verify polynomial commitments
-        Ok(true)
+        witness_polynomial: &DensePolynomial<F>,
+    ) -> Result<Vec<PolynomialCommitment<E::G1>>, EOSError>
+    where
+        E: Pairing<ScalarField = F>,
+    {
+        if witness_polynomial.coeffs.len() > self.commitment_scheme.powers_of_g.len() {
+            return Err(EOSError::CommitmentError(format!(
+                "witness of length {} exceeds SRS size {}",
+                witness_polynomial.coeffs.len(),
+                self.commitment_scheme.powers_of_g.len()
+            )));
+        }
+        Ok(vec![self.commitment_scheme.commit(witness_polynomial)])
     }
 
-    fn verify_final_result(
-        &self,
-        _result: &DelegationResult<E, F>,
-        _public_inputs: &[F],
-        _verification_key: &VerificationKey<E>,
-    ) -> Result<bool, EOSError> {
-        // Verify final computation result
-        Ok(true)
-    }
 }
 
 /// Protocol parameters
@@ -380,6 +987,10 @@ pub struct DelegationResult<E: Pairing, F: Field> {
     pub execution_stats: crate::mpc::ExecutionStats,
     pub piop_proof: Option<Vec<u8>>, // Simplified PIOP proof placeholder
     pub polynomial_commitments: Vec<Vec<u8>>, // Simplified commitment placeholder
+    /// Commitment to the prover seed, for seeded (reproducible) delegations
+    pub seed_commitment: Option<[u8; 32]>,
+    /// Verification key hash bound into the proof transcript
+    pub verification_key_hash: [u8; 32],
     _phantom: std::marker::PhantomData<(E, F)>,
 }
 
@@ -404,3 +1015,308 @@ impl std::fmt::Display for DelegationError {
 }
 
 impl std::error::Error for DelegationError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mpc::{CollaborationMode, ShamirSecretSharing};
+    use crate::protocol::StreamingVerifier;
+    use ark_bls12_381::{Bls12_381, Fr, G1Projective};
+    use ark_std::test_rng;
+    use std::time::Duration;
+
+    type TestField = Fr;
+    type Protocol = EOSProtocol<Bls12_381, TestField, ShamirSecretSharing<TestField>, CollaborationMode>;
+
+    #[test]
+    fn test_phase_snapshots_are_emitted() {
+        let mut rng = test_rng();
+        let executor = ExecCircuit::new(1, 3, ShamirSecretSharing::new());
+        let mut protocol = Protocol::new(executor, CollaborationMode::new(2, true, false), 128, &mut rng);
+        let circuit = ConstraintSystem::new();
+        protocol.preprocessing_state = Some(Protocol::preprocessing(&circuit, 128, &mut rng).unwrap());
+        let witness: Vec<TestField> = (1..=8u64).map(TestField::from).collect();
+
+        let before = protocol.metrics.snapshot();
+        protocol.delegate_computation(&circuit, &witness, &[], &mut rng).unwrap();
+        let phases: Vec<String> = protocol.take_phase_snapshots().into_iter().map(|(phase, _)| phase).collect();
+        assert_eq!(phases, ["witness_sharing", "mpc_execution", "proof_generation"]);
+        assert!(protocol.phase_snapshots.is_empty());
+
+        // A second delegation adds to the same phases; the diff isolates it
+        let between = protocol.metrics.snapshot();
+        protocol.delegate_computation(&circuit, &witness, &[], &mut rng).unwrap();
+        let second = protocol.metrics.diff(&between);
+        let both = protocol.metrics.diff(&before);
+        let proving = |diff: &crate::evaluation::MetricsDiff| diff.timings["proof_generation"];
+        assert!(proving(&second) > Duration::ZERO);
+        assert!(proving(&both) > proving(&second));
+        assert!(second.elapsed >= second.timings.values().sum::<Duration>());
+
+        // Simulated MPC never waits on the network, so every phase is compute-bound
+        let proof = &protocol.metrics.phase_timings["proof_generation"];
+        assert_eq!(proof.runs, 2);
+        assert!(!proof.is_network_bound());
+        if let Some(cpu) = proof.thread_cpu {
+            assert!(cpu <= proof.wall + Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn test_seeded_delegation_is_reproducible() {
+        let mut rng = test_rng();
+        let executor = ExecCircuit::new(1, 3, ShamirSecretSharing::new());
+        let mut protocol = Protocol::new(executor, CollaborationMode::new(2, true, false), 128, &mut rng);
+        let circuit = ConstraintSystem::new();
+        protocol.preprocessing_state = Some(Protocol::preprocessing(&circuit, 128, &mut rng).unwrap());
+
+        let witness: Vec<TestField> = (1..=8u64).map(TestField::from).collect();
+        let public_inputs = [TestField::from(36u64)];
+        let seed = [7u8; 32];
+
+        let first = protocol.delegate_computation_seeded(&circuit, &witness, &public_inputs, seed).unwrap();
+        let second = protocol.delegate_computation_seeded(&circuit, &witness, &public_inputs, seed).unwrap();
+        assert_eq!(first.transcript_bytes(), second.transcript_bytes());
+        assert_eq!(first.seed_commitment, Some(commit_prover_seed(&seed)));
+
+        let other = protocol.delegate_computation_seeded(&circuit, &witness, &public_inputs, [8u8; 32]).unwrap();
+        assert_ne!(first.piop_proof, other.piop_proof);
+
+        // The verifier rebuilds the transcript from the published seed commitment
+        let proof = first.piop_proof.unwrap();
+        let key_hash = protocol.verification_key_hash().unwrap();
+        let matrices = protocol.constraint_matrices().unwrap();
+        let verifier = StreamingVerifier::<Bls12_381>::new(&protocol.commitment_scheme, key_hash, matrices)
+            .with_context(&seed_context(&first.seed_commitment.unwrap()));
+        assert!(verifier.verify(proof.as_slice(), &public_inputs, &mut rng).unwrap());
+    }
+
+    #[test]
+    fn test_hybrid_split_charges_stages_without_changing_proof() {
+        let mut rng = test_rng();
+        let executor = ExecCircuit::new(1, 3, ShamirSecretSharing::new());
+        let mut protocol = Protocol::new(executor, CollaborationMode::new(2, true, false), 128, &mut rng);
+        let circuit = ConstraintSystem::new();
+        protocol.preprocessing_state = Some(Protocol::preprocessing(&circuit, 128, &mut rng).unwrap());
+        let witness: Vec<TestField> = (1..=8u64).map(TestField::from).collect();
+        let public_inputs = [TestField::from(36u64)];
+
+        let outsourced = protocol.delegate_computation_seeded(&circuit, &witness, &public_inputs, [7u8; 32]).unwrap();
+        let report = protocol.metrics.generate_report().hybrid();
+        assert_eq!(report.stages.len(), ProvingStage::ALL.len());
+        assert!(report.stages.iter().all(|timing| !timing.local));
+
+        protocol.metrics = PerformanceMetrics::new();
+        protocol.params.hybrid = HybridSplit::linear();
+        let hybrid = protocol.delegate_computation_seeded(&circuit, &witness, &public_inputs, [7u8; 32]).unwrap();
+        assert_eq!(hybrid.transcript_bytes(), outsourced.transcript_bytes());
+        let report = protocol.metrics.generate_report().hybrid();
+        let local: Vec<_> = report.stages.iter().filter(|timing| timing.local).map(|timing| timing.stage).collect();
+        assert_eq!(local, [ProvingStage::WitnessPolynomial, ProvingStage::Quotient]);
+    }
+
+    #[test]
+    fn test_zk_delegation_hides_the_witness_commitment() {
+        let mut rng = test_rng();
+        let executor = ExecCircuit::new(1, 3, ShamirSecretSharing::new());
+        let mut protocol = Protocol::new(executor, CollaborationMode::new(2, true, false), 128, &mut rng);
+        let circuit = ConstraintSystem::new();
+        protocol.preprocessing_state = Some(Protocol::preprocessing(&circuit, 128, &mut rng).unwrap());
+        let witness: Vec<TestField> = (1..=8u64).map(TestField::from).collect();
+        let public_inputs = [TestField::from(36u64)];
+        let plain = protocol.delegate_computation(&circuit, &witness, &public_inputs, &mut rng).unwrap();
+
+        protocol.params.zk = true;
+        let mut first = protocol.delegate_computation(&circuit, &witness, &public_inputs, &mut rng).unwrap();
+        let second = protocol.delegate_computation(&circuit, &witness, &public_inputs, &mut rng).unwrap();
+        assert_ne!(first.polynomial_commitments, plain.polynomial_commitments);
+        assert_ne!(first.polynomial_commitments, second.polynomial_commitments);
+        for result in [&first, &second] {
+            assert!(protocol.verify_computation(result, &public_inputs).unwrap().is_accepted());
+        }
+
+        // The seeded path draws its blinding from the seed and stays reproducible
+        let seeded = protocol.delegate_computation_seeded(&circuit, &witness, &public_inputs, [7u8; 32]).unwrap();
+        let again = protocol.delegate_computation_seeded(&circuit, &witness, &public_inputs, [7u8; 32]).unwrap();
+        assert_eq!(seeded.transcript_bytes(), again.transcript_bytes());
+        assert!(protocol.verify_computation(&seeded, &public_inputs).unwrap().is_accepted());
+
+        // Both constraint sumchecks are masked, and the masks are load-bearing
+        let decode = |result: &DelegationResult<Bls12_381, TestField>| {
+            PolynomialConsistencyProof::<TestField, G1Projective>::from_bytes(result.piop_proof.as_ref().unwrap()).unwrap()
+        };
+        assert!(decode(&plain).constraint_claims.unwrap().masks.is_empty());
+        let mut proof = decode(&first);
+        assert_eq!(proof.constraint_claims.as_ref().unwrap().masks.len(), 2);
+        assert_ne!(proof.sumcheck_proofs[1].round_polynomials, decode(&second).sumcheck_proofs[1].round_polynomials);
+        proof.constraint_claims.as_mut().unwrap().masks.clear();
+        first.piop_proof = Some(proof.to_bytes());
+        let outcome = protocol.verify_computation(&first, &public_inputs).unwrap();
+        assert_eq!(outcome.failed_check(), Some(VerificationCheck::PiopIdentity));
+
+        // The blinding and the masks take SRS room: the blinded witness pads
+        // to a mask table, which must fit below the SRS with its own blinding
+        let table = 1 << (protocol.commitment_scheme.powers_of_g.len() - MASK_BLINDING).ilog2();
+        let capacity = protocol.circuit_capacity();
+        assert_eq!(capacity.max_variables, Some(table - HIDDEN_EVALUATIONS - 1));
+        assert_eq!(capacity.max_constraints, Some(table));
+    }
+
+    #[test]
+    fn test_unsatisfied_witness_is_rejected() {
+        let mut rng = test_rng();
+        // x·x = t, t·x = y with y public
+        let cs = ConstraintSystem::<TestField>::new_ref();
+        let y = cs.new_input_variable(|| Ok(TestField::from(343u64))).unwrap();
+        let x = cs.new_witness_variable(|| Ok(TestField::from(7u64))).unwrap();
+        let t = cs.new_witness_variable(|| Ok(TestField::from(49u64))).unwrap();
+        cs.enforce_constraint(ark_relations::lc!() + x, ark_relations::lc!() + x, ark_relations::lc!() + t).unwrap();
+        cs.enforce_constraint(ark_relations::lc!() + t, ark_relations::lc!() + x, ark_relations::lc!() + y).unwrap();
+        let circuit = cs.into_inner().unwrap();
+        let executor = ExecCircuit::new(1, 3, ShamirSecretSharing::new());
+        let mut protocol = Protocol::new(executor, CollaborationMode::new(2, true, false), 128, &mut rng);
+        protocol.preprocessing_state = Some(Protocol::preprocessing(&circuit, 128, &mut rng).unwrap());
+        let public_inputs = [TestField::from(343u64)];
+        let witness = |values: [u64; 2]| values.map(TestField::from);
+        let key_hash = protocol.verification_key_hash().unwrap();
+        let matrices = protocol.constraint_matrices().unwrap().clone();
+        let scheme = protocol.commitment_scheme.clone();
+        let verifier = StreamingVerifier::<Bls12_381>::new(&scheme, key_hash, &matrices);
+
+        for zk in [false, true] {
+            protocol.params.zk = zk;
+            let honest = protocol.delegate_computation(&circuit, &witness([7, 49]), &public_inputs, &mut rng).unwrap();
+            assert!(protocol.verify_computation(&honest, &public_inputs).unwrap().is_accepted());
+            let proof = honest.piop_proof.unwrap();
+            assert!(verifier.verify(proof.as_slice(), &public_inputs, &mut rng).unwrap());
+
+            // Each witness breaks one constraint; the second breaks only the last row
+            for values in [[8, 49], [7, 50], [8, 64]] {
+                let result = protocol.delegate_computation(&circuit, &witness(values), &public_inputs, &mut rng).unwrap();
+                let outcome = protocol.verify_computation(&result, &public_inputs).unwrap();
+                assert_eq!(outcome.failed_check(), Some(VerificationCheck::PiopIdentity));
+                let proof = result.piop_proof.unwrap();
+                assert!(!verifier.verify(proof.as_slice(), &public_inputs, &mut rng).unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn test_proof_is_bound_to_verification_key() {
+        let mut rng = test_rng();
+        let circuit = ConstraintSystem::new();
+        let mut setup = || {
+            let executor = ExecCircuit::new(1, 3, ShamirSecretSharing::new());
+            let mut protocol = Protocol::new(executor, CollaborationMode::new(2, true, false), 128, &mut rng);
+            protocol.preprocessing_state = Some(Protocol::preprocessing(&circuit, 128, &mut rng).unwrap());
+            protocol
+        };
+        let mut protocol = setup();
+        let other = setup();
+        assert_ne!(protocol.verification_key_hash().unwrap(), other.verification_key_hash().unwrap());
+
+        let witness = [TestField::from(2u64), TestField::from(3u64)];
+        let result = protocol.delegate_computation(&circuit, &witness, &[], &mut rng).unwrap();
+        assert!(protocol.verify_computation(&result, &[]).unwrap().is_accepted());
+        let outcome = other.verify_computation(&result, &[]).unwrap();
+        assert_eq!(outcome.failed_check(), Some(VerificationCheck::KeyBinding));
+
+        // The proof itself fails under another key, whatever hash it claims
+        let proof = result.piop_proof.unwrap();
+        let verifier = StreamingVerifier::<Bls12_381>::new(
+            &protocol.commitment_scheme,
+            other.verification_key_hash().unwrap(),
+            protocol.constraint_matrices().unwrap(),
+        );
+        assert!(!verifier.verify(proof.as_slice(), &[], &mut rng).unwrap());
+    }
+
+    #[test]
+    fn test_oversized_circuit_is_rejected_before_sharing() {
+        let mut rng = test_rng();
+        let executor = ExecCircuit::new(1, 3, ShamirSecretSharing::new());
+        let mut protocol = Protocol::new(executor, CollaborationMode::new(2, true, false), 128, &mut rng);
+        let circuit = ConstraintSystem::new();
+        protocol.preprocessing_state = Some(Protocol::preprocessing(&circuit, 128, &mut rng).unwrap());
+
+        // The SRS bounds the witness length on its own
+        let capacity = protocol.commitment_scheme.powers_of_g.len();
+        assert_eq!(protocol.circuit_capacity().max_variables, Some(capacity));
+        let witness = vec![TestField::from(1u64); capacity + 1];
+        assert!(matches!(
+            protocol.delegate_computation(&circuit, &witness, &[], &mut rng),
+            Err(EOSError::BudgetExceeded(BudgetExceeded { limit, requested, .. }))
+                if limit == capacity && requested == capacity + 1
+        ));
+        assert!(protocol.metrics.timings.is_empty());
+
+        // A triple pool smaller than the circuit is caught the same way
+        let mut cs = ConstraintSystem::<TestField>::new();
+        let x = cs.new_witness_variable(|| Ok(TestField::from(2u64))).unwrap();
+        for _ in 0..3 {
+            cs.enforce_constraint(ark_relations::lc!() + x, ark_relations::lc!() + x, ark_relations::lc!() + x).unwrap();
+        }
+        let pool = crate::mpc::PreprocessingStore::<TestField, ShamirSecretSharing<TestField>>::generate(2, 0, 1, 3, &mut rng);
+        protocol.params.budget = pool.budget();
+        let error = protocol.delegate_computation_seeded(&cs, &[TestField::from(2u64)], &[], [0u8; 32]).unwrap_err();
+        assert_eq!(error.to_string(), "Budget exceeded: circuit needs 3 multiplication constraints but the budget allows 2");
+        assert!(protocol.take_phase_snapshots().is_empty());
+    }
+
+    #[test]
+    fn test_universal_setup_is_shared_across_circuits() {
+        let mut rng = test_rng();
+        let setup = Protocol::universal_setup(7, &mut rng);
+        assert_eq!(setup.capacity(), 8);
+
+        // x·x = y with y public, and a circuit with one more witness
+        let square = |extra: bool| {
+            let cs = ConstraintSystem::<TestField>::new_ref();
+            let y = cs.new_input_variable(|| Ok(TestField::from(9u64))).unwrap();
+            let x = cs.new_witness_variable(|| Ok(TestField::from(3u64))).unwrap();
+            cs.enforce_constraint(ark_relations::lc!() + x, ark_relations::lc!() + x, ark_relations::lc!() + y).unwrap();
+            if extra {
+                cs.new_witness_variable(|| Ok(TestField::from(1u64))).unwrap();
+            }
+            cs.into_inner().unwrap()
+        };
+        let executor = ExecCircuit::new(1, 3, ShamirSecretSharing::new());
+        let mut protocol = Protocol::with_setup(executor, CollaborationMode::new(2, true, false), 128, &setup);
+        let mut key_hashes = Vec::new();
+        for (circuit, witness) in [(square(false), vec![3u64]), (square(true), vec![3, 1])] {
+            let state = Protocol::index(&setup, &circuit).unwrap();
+            assert_eq!(state.evaluation_key.powers_of_tau, setup.params.powers_of_g[..4]);
+            assert_eq!(state.verification_key, VerificationKey::from_params(&setup.params, 1));
+            assert_eq!(state.usage().variables, witness.len());
+            protocol.preprocessing_state = Some(state);
+            let witness: Vec<TestField> = witness.into_iter().map(TestField::from).collect();
+            let public_inputs = [TestField::from(9u64)];
+            let result = protocol.delegate_computation(&circuit, &witness, &public_inputs, &mut rng).unwrap();
+            assert!(protocol.verify_computation(&result, &public_inputs).unwrap().is_accepted());
+            key_hashes.push(protocol.verification_key_hash().unwrap());
+        }
+        assert_ne!(key_hashes[0], key_hashes[1]);
+
+        // An instance for small circuits holds only the prefix it needs
+        let executor = || ExecCircuit::new(1, 3, ShamirSecretSharing::new());
+        let mode = || CollaborationMode::new(2, true, false);
+        assert!(Protocol::with_trimmed_setup(executor(), mode(), 128, &setup, 8).is_none());
+        let mut trimmed = Protocol::with_trimmed_setup(executor(), mode(), 128, &setup, 3).unwrap();
+        assert_eq!(trimmed.commitment_scheme.powers_of_g, setup.params.powers_of_g[..4]);
+        let circuit = square(false);
+        trimmed.preprocessing_state = Some(Protocol::index(&setup, &circuit).unwrap());
+        let public_inputs = [TestField::from(9u64)];
+        let result = trimmed.delegate_computation(&circuit, &[TestField::from(3u64)], &public_inputs, &mut rng).unwrap();
+        assert!(trimmed.verify_computation(&result, &public_inputs).unwrap().is_accepted());
+
+        // A circuit larger than the setup is rejected at indexing
+        let cs = ConstraintSystem::<TestField>::new_ref();
+        for _ in 0..8 {
+            cs.new_witness_variable(|| Ok(TestField::from(1u64))).unwrap();
+        }
+        assert!(matches!(
+            Protocol::index(&setup, &cs.into_inner().unwrap()),
+            Err(EOSError::BudgetExceeded(BudgetExceeded { limit: 8, requested: 9, .. }))
+        ));
+    }
+}
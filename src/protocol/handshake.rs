@@ -0,0 +1,494 @@
+//! Session handshake and capability negotiation
+//!
+//! Before any share is sent, the delegator and the workers agree on the
+//! protocol version, curve, commitment scheme, security mode and proof
+//! compression of the session. The delegator offers its capabilities in
+//! preference order, every worker answers with its own, and the delegator
+//! picks the highest common version and its most preferred option that all
+//! workers support. A party that cannot run the session is named in the
+//! error, so a mismatch stops the session in the first round instead of
+//! surfacing later as a proof that does not verify.
+
+use crate::mpc::{MessageKind, MessageRuntime, NetworkError};
+use crate::serialization::{write_len, write_u32, ByteReader, DecodeError, WireFormat};
+
+/// Handshake version spoken by this implementation
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Options negotiated one by one, each with a stable wire tag
+trait Negotiable: Copy + PartialEq + Sized + 'static {
+    /// Name of the option in error messages
+    const FEATURE: &'static str;
+    const ALL: &'static [Self];
+
+    fn name(self) -> &'static str;
+
+    fn tag(self) -> u8 {
+        Self::ALL.iter().position(|option| *option == self).expect("option is listed in ALL") as u8
+    }
+
+    fn from_tag(tag: u8) -> Result<Self, DecodeError> {
+        Self::ALL.get(tag as usize).copied().ok_or(DecodeError::InvalidTag(tag))
+    }
+}
+
+/// Pairing-friendly curve of the session
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CurveId {
+    Bls12_381,
+    Bn254,
+}
+
+impl Negotiable for CurveId {
+    const FEATURE: &'static str = "curve";
+    const ALL: &'static [Self] = &[CurveId::Bls12_381, CurveId::Bn254];
+
+    fn name(self) -> &'static str {
+        match self {
+            CurveId::Bls12_381 => "bls12-381",
+            CurveId::Bn254 => "bn254",
+        }
+    }
+}
+
+/// Polynomial or vector commitment scheme of the session
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PcsKind {
+    Kzg,
+    Merkle,
+}
+
+impl Negotiable for PcsKind {
+    const FEATURE: &'static str = "commitment scheme";
+    const ALL: &'static [Self] = &[PcsKind::Kzg, PcsKind::Merkle];
+
+    fn name(self) -> &'static str {
+        match self {
+            PcsKind::Kzg => "kzg",
+            PcsKind::Merkle => "merkle",
+        }
+    }
+}
+
+/// Adversary model the workers are run under
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SecurityMode {
+    SemiHonest,
+    /// Shares are authenticated and checked before any value is opened
+    Malicious,
+}
+
+impl Negotiable for SecurityMode {
+    const FEATURE: &'static str = "security mode";
+    const ALL: &'static [Self] = &[SecurityMode::SemiHonest, SecurityMode::Malicious];
+
+    fn name(self) -> &'static str {
+        match self {
+            SecurityMode::SemiHonest => "semi-honest",
+            SecurityMode::Malicious => "malicious",
+        }
+    }
+}
+
+/// Form of the proof returned to the delegator
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CompressionKind {
+    None,
+    /// The proof compressed to a constant-size `CompressedProof` (Poseidon transcripts only)
+    Groth16,
+}
+
+impl Negotiable for CompressionKind {
+    const FEATURE: &'static str = "compression";
+    const ALL: &'static [Self] = &[CompressionKind::None, CompressionKind::Groth16];
+
+    fn name(self) -> &'static str {
+        match self {
+            CompressionKind::None => "none",
+            CompressionKind::Groth16 => "groth16",
+        }
+    }
+}
+
+/// What one party can run, each list in preference order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Capabilities {
+    pub min_version: u32,
+    pub max_version: u32,
+    pub curves: Vec<CurveId>,
+    pub commitment_schemes: Vec<PcsKind>,
+    pub security_modes: Vec<SecurityMode>,
+    pub compression: Vec<CompressionKind>,
+}
+
+impl Default for Capabilities {
+    /// Everything this implementation supports
+    fn default() -> Self {
+        Self {
+            min_version: PROTOCOL_VERSION,
+            max_version: PROTOCOL_VERSION,
+            curves: vec![CurveId::Bls12_381],
+            commitment_schemes: vec![PcsKind::Kzg, PcsKind::Merkle],
+            security_modes: vec![SecurityMode::SemiHonest, SecurityMode::Malicious],
+            compression: vec![CompressionKind::None, CompressionKind::Groth16],
+        }
+    }
+}
+
+/// Parameters every party agreed to run the session with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SessionParameters {
+    pub version: u32,
+    pub curve: CurveId,
+    pub commitment_scheme: PcsKind,
+    pub security_mode: SecurityMode,
+    pub compression: CompressionKind,
+}
+
+impl SessionParameters {
+    /// Transcript context binding proofs to the negotiated parameters
+    pub fn context(&self) -> Vec<u8> {
+        let mut context = b"session-parameters".to_vec();
+        self.encode(&mut context);
+        context
+    }
+}
+
+fn pick<T: Negotiable>(offered: &[T], supported: &[T], party: usize) -> Result<T, HandshakeError> {
+    offered
+        .iter()
+        .copied()
+        .find(|option| supported.contains(option))
+        .ok_or_else(|| HandshakeError::Unsupported {
+            party,
+            feature: T::FEATURE,
+            offered: offered.iter().map(|option| option.name()).collect::<Vec<_>>().join(", "),
+        })
+}
+
+fn common<T: Negotiable>(a: &[T], b: &[T]) -> Vec<T> {
+    a.iter().copied().filter(|option| b.contains(option)).collect()
+}
+
+impl Capabilities {
+    /// Whether this party can run a session with `parameters`
+    pub fn supports(&self, parameters: &SessionParameters) -> bool {
+        (self.min_version..=self.max_version).contains(&parameters.version)
+            && self.curves.contains(&parameters.curve)
+            && self.commitment_schemes.contains(&parameters.commitment_scheme)
+            && self.security_modes.contains(&parameters.security_mode)
+            && self.compression.contains(&parameters.compression)
+    }
+
+    /// Choose parameters from this offer that `peer` (party `party`) supports
+    pub fn negotiate(&self, peer: &Capabilities, party: usize) -> Result<SessionParameters, HandshakeError> {
+        let version = self.max_version.min(peer.max_version);
+        if version < self.min_version.max(peer.min_version) {
+            return Err(HandshakeError::VersionMismatch {
+                party,
+                ours: (self.min_version, self.max_version),
+                theirs: (peer.min_version, peer.max_version),
+            });
+        }
+        Ok(SessionParameters {
+            version,
+            curve: pick(&self.curves, &peer.curves, party)?,
+            commitment_scheme: pick(&self.commitment_schemes, &peer.commitment_schemes, party)?,
+            security_mode: pick(&self.security_modes, &peer.security_modes, party)?,
+            compression: pick(&self.compression, &peer.compression, party)?,
+        })
+    }
+
+    /// Options supported by both parties, keeping this party's preferences
+    fn intersect(&self, other: &Capabilities) -> Capabilities {
+        Capabilities {
+            min_version: self.min_version.max(other.min_version),
+            max_version: self.max_version.min(other.max_version),
+            curves: common(&self.curves, &other.curves),
+            commitment_schemes: common(&self.commitment_schemes, &other.commitment_schemes),
+            security_modes: common(&self.security_modes, &other.security_modes),
+            compression: common(&self.compression, &other.compression),
+        }
+    }
+}
+
+/// Choose parameters every worker supports, preferring the delegator's order
+///
+/// Each worker is first checked on its own so the error names the party
+/// that cannot take part.
+pub fn negotiate_session(
+    offer: &Capabilities,
+    workers: &[(usize, Capabilities)],
+) -> Result<SessionParameters, HandshakeError> {
+    for (party, capabilities) in workers {
+        offer.negotiate(capabilities, *party)?;
+    }
+    let shared = workers.iter().fold(offer.clone(), |shared, (_, capabilities)| shared.intersect(capabilities));
+    offer.negotiate(&shared, usize::MAX).map_err(|e| match e {
+        HandshakeError::Unsupported { feature, .. } => HandshakeError::NoCommonChoice(feature),
+        HandshakeError::VersionMismatch { .. } => HandshakeError::NoCommonChoice("version"),
+        e => e,
+    })
+}
+
+/// Messages of the handshake, sent as `MessageKind::Control`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HandshakeMessage {
+    /// Capabilities of the sender
+    Hello(Capabilities),
+    /// Parameters chosen by the delegator
+    Accept(SessionParameters),
+    /// The session cannot run; the reason is for logs only
+    Reject(String),
+}
+
+fn write_options<T: Negotiable>(out: &mut Vec<u8>, options: &[T]) {
+    write_len(out, options.len());
+    out.extend(options.iter().map(|option| option.tag()));
+}
+
+fn read_options<T: Negotiable>(reader: &mut ByteReader<'_>) -> Result<Vec<T>, DecodeError> {
+    let len = reader.read_len(1)?;
+    (0..len).map(|_| T::from_tag(reader.read_u8()?)).collect()
+}
+
+impl WireFormat for Capabilities {
+    fn encode(&self, out: &mut Vec<u8>) {
+        write_u32(out, self.min_version);
+        write_u32(out, self.max_version);
+        write_options(out, &self.curves);
+        write_options(out, &self.commitment_schemes);
+        write_options(out, &self.security_modes);
+        write_options(out, &self.compression);
+    }
+
+    fn decode(reader: &mut ByteReader<'_>) -> Result<Self, DecodeError> {
+        Ok(Capabilities {
+            min_version: reader.read_u32()?,
+            max_version: reader.read_u32()?,
+            curves: read_options(reader)?,
+            commitment_schemes: read_options(reader)?,
+            security_modes: read_options(reader)?,
+            compression: read_options(reader)?,
+        })
+    }
+}
+
+impl WireFormat for SessionParameters {
+    fn encode(&self, out: &mut Vec<u8>) {
+        write_u32(out, self.version);
+        out.extend_from_slice(&[
+            self.curve.tag(),
+            self.commitment_scheme.tag(),
+            self.security_mode.tag(),
+            self.compression.tag(),
+        ]);
+    }
+
+    fn decode(reader: &mut ByteReader<'_>) -> Result<Self, DecodeError> {
+        Ok(SessionParameters {
+            version: reader.read_u32()?,
+            curve: CurveId::from_tag(reader.read_u8()?)?,
+            commitment_scheme: PcsKind::from_tag(reader.read_u8()?)?,
+            security_mode: SecurityMode::from_tag(reader.read_u8()?)?,
+            compression: CompressionKind::from_tag(reader.read_u8()?)?,
+        })
+    }
+}
+
+impl WireFormat for HandshakeMessage {
+    fn encode(&self, out: &mut Vec<u8>) {
+        match self {
+            HandshakeMessage::Hello(capabilities) => {
+                out.push(0);
+                capabilities.encode(out);
+            }
+            HandshakeMessage::Accept(parameters) => {
+                out.push(1);
+                parameters.encode(out);
+            }
+            HandshakeMessage::Reject(reason) => {
+                out.push(2);
+                write_len(out, reason.len());
+                out.extend_from_slice(reason.as_bytes());
+            }
+        }
+    }
+
+    fn decode(reader: &mut ByteReader<'_>) -> Result<Self, DecodeError> {
+        match reader.read_u8()? {
+            0 => Ok(HandshakeMessage::Hello(Capabilities::decode(reader)?)),
+            1 => Ok(HandshakeMessage::Accept(SessionParameters::decode(reader)?)),
+            2 => {
+                let len = reader.read_len(1)?;
+                let reason = std::str::from_utf8(reader.read_bytes(len)?).map_err(|_| DecodeError::InvalidUtf8)?;
+                Ok(HandshakeMessage::Reject(reason.to_string()))
+            }
+            tag => Err(DecodeError::InvalidTag(tag)),
+        }
+    }
+}
+
+/// Take the control message `party` received from `from`
+fn expect_message(runtime: &mut MessageRuntime, party: usize, from: usize) -> Result<HandshakeMessage, HandshakeError> {
+    let message = runtime
+        .receive(party)?
+        .into_iter()
+        .find(|message| message.from == from && message.kind == MessageKind::Control)
+        .ok_or(HandshakeError::NoResponse(from))?;
+    Ok(HandshakeMessage::from_bytes(&message.payload)?)
+}
+
+/// Run the three-round handshake over `runtime`
+///
+/// The delegator sends its offer, the workers answer with their
+/// capabilities, and the delegator announces the chosen parameters or a
+/// rejection. Returns the parameters once every worker has checked that it
+/// supports them.
+pub fn run_handshake(
+    runtime: &mut MessageRuntime,
+    delegator: usize,
+    offer: &Capabilities,
+    workers: &[(usize, Capabilities)],
+) -> Result<SessionParameters, HandshakeError> {
+    let hello = HandshakeMessage::Hello(offer.clone()).to_bytes();
+    for (party, _) in workers {
+        runtime.send(delegator, *party, MessageKind::Control, hello.clone())?;
+    }
+    runtime.finish_round();
+
+    for (party, capabilities) in workers {
+        expect_message(runtime, *party, delegator)?;
+        runtime.send(*party, delegator, MessageKind::Control, HandshakeMessage::Hello(capabilities.clone()).to_bytes())?;
+    }
+    runtime.finish_round();
+
+    let replies = runtime.receive(delegator)?;
+    let mut received = Vec::with_capacity(workers.len());
+    for (party, _) in workers {
+        let reply = replies
+            .iter()
+            .find(|message| message.from == *party && message.kind == MessageKind::Control)
+            .ok_or(HandshakeError::NoResponse(*party))?;
+        match HandshakeMessage::from_bytes(&reply.payload)? {
+            HandshakeMessage::Hello(capabilities) => received.push((*party, capabilities)),
+            _ => return Err(HandshakeError::UnexpectedMessage(*party)),
+        }
+    }
+
+    let outcome = negotiate_session(offer, &received);
+    let announcement = match &outcome {
+        Ok(parameters) => HandshakeMessage::Accept(*parameters),
+        Err(e) => HandshakeMessage::Reject(e.to_string()),
+    };
+    for (party, _) in workers {
+        runtime.send(delegator, *party, MessageKind::Control, announcement.to_bytes())?;
+    }
+    runtime.finish_round();
+    let parameters = outcome?;
+
+    for (party, capabilities) in workers {
+        match expect_message(runtime, *party, delegator)? {
+            HandshakeMessage::Accept(announced) if capabilities.supports(&announced) => {}
+            HandshakeMessage::Accept(_) => return Err(HandshakeError::UnsupportedParameters(*party)),
+            _ => return Err(HandshakeError::UnexpectedMessage(*party)),
+        }
+    }
+    Ok(parameters)
+}
+
+/// Handshake error types
+#[derive(Debug, Clone, PartialEq)]
+pub enum HandshakeError {
+    VersionMismatch { party: usize, ours: (u32, u32), theirs: (u32, u32) },
+    /// A party supports none of the offered options
+    Unsupported { party: usize, feature: &'static str, offered: String },
+    /// Every worker is compatible with the offer, but not with each other
+    NoCommonChoice(&'static str),
+    /// A worker was told to run parameters it does not support
+    UnsupportedParameters(usize),
+    NoResponse(usize),
+    UnexpectedMessage(usize),
+    Network(NetworkError),
+    Decode(DecodeError),
+}
+
+impl std::fmt::Display for HandshakeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            HandshakeError::VersionMismatch { party, ours, theirs } => write!(
+                f,
+                "Party {} speaks protocol versions {}..={}, offer was {}..={}",
+                party, theirs.0, theirs.1, ours.0, ours.1
+            ),
+            HandshakeError::Unsupported { party, feature, offered } => {
+                write!(f, "Party {} supports no offered {} (offered: {})", party, feature, offered)
+            }
+            HandshakeError::NoCommonChoice(feature) => write!(f, "Workers share no common {}", feature),
+            HandshakeError::UnsupportedParameters(party) => {
+                write!(f, "Party {} does not support the announced session parameters", party)
+            }
+            HandshakeError::NoResponse(party) => write!(f, "No handshake message from party {}", party),
+            HandshakeError::UnexpectedMessage(party) => write!(f, "Unexpected handshake message from party {}", party),
+            HandshakeError::Network(e) => write!(f, "Network error: {}", e),
+            HandshakeError::Decode(e) => write!(f, "Malformed handshake message: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for HandshakeError {}
+
+impl From<NetworkError> for HandshakeError {
+    fn from(e: NetworkError) -> Self {
+        HandshakeError::Network(e)
+    }
+}
+
+impl From<DecodeError> for HandshakeError {
+    fn from(e: DecodeError) -> Self {
+        HandshakeError::Decode(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_handshake_negotiates_or_names_the_mismatch() {
+        let offer = Capabilities {
+            security_modes: vec![SecurityMode::Malicious, SecurityMode::SemiHonest],
+            compression: vec![CompressionKind::Groth16, CompressionKind::None],
+            ..Capabilities::default()
+        };
+        let semi_honest_only = Capabilities { security_modes: vec![SecurityMode::SemiHonest], ..Capabilities::default() };
+        let workers = vec![(1, Capabilities::default()), (2, semi_honest_only.clone())];
+
+        let mut runtime = MessageRuntime::new(3);
+        let parameters = run_handshake(&mut runtime, 0, &offer, &workers).unwrap();
+        assert_eq!(parameters.version, PROTOCOL_VERSION);
+        assert_eq!(parameters.security_mode, SecurityMode::SemiHonest);
+        assert_eq!(parameters.compression, CompressionKind::Groth16);
+        assert_eq!(runtime.stats().rounds, 3);
+        assert_eq!(SessionParameters::from_bytes(&parameters.to_bytes()).unwrap(), parameters);
+        let hello = HandshakeMessage::Hello(offer.clone());
+        assert_eq!(HandshakeMessage::from_bytes(&hello.to_bytes()).unwrap(), hello);
+
+        // A worker on another curve or a newer version is named in the error
+        let bn254 = Capabilities { curves: vec![CurveId::Bn254], ..Capabilities::default() };
+        let err = run_handshake(&mut MessageRuntime::new(3), 0, &offer, &[(1, Capabilities::default()), (2, bn254)]).unwrap_err();
+        assert!(matches!(err, HandshakeError::Unsupported { party: 2, feature: "curve", .. }));
+        assert_eq!(err.to_string(), "Party 2 supports no offered curve (offered: bls12-381)");
+        let newer = Capabilities { min_version: 2, max_version: 3, ..Capabilities::default() };
+        assert!(matches!(negotiate_session(&offer, &[(4, newer)]), Err(HandshakeError::VersionMismatch { party: 4, .. })));
+
+        // Each worker fits the offer, but they have nothing in common
+        let merkle = Capabilities { commitment_schemes: vec![PcsKind::Merkle], ..Capabilities::default() };
+        let kzg = Capabilities { commitment_schemes: vec![PcsKind::Kzg], ..Capabilities::default() };
+        assert_eq!(
+            negotiate_session(&offer, &[(1, merkle), (2, kzg)]),
+            Err(HandshakeError::NoCommonChoice("commitment scheme"))
+        );
+        assert!(!semi_honest_only.supports(&SessionParameters { security_mode: SecurityMode::Malicious, ..parameters }));
+    }
+}
@@ -0,0 +1,347 @@
+//! Interactive variant of the distributed sumcheck, for research comparisons
+//!
+//! The protocol proper is non-interactive: the aggregator derives every
+//! sumcheck challenge from the Fiat–Shamir transcript and ships the finished
+//! proof to the verifier in one flight. Here the same proving phase runs
+//! against a live verifier instead. The aggregator sends each combined round
+//! message over a `MessageRuntime`, the verifier checks it and answers with a
+//! freshly sampled challenge, and the workers fold only once that challenge
+//! has come back. Every round trip is timed.
+//!
+//! `run_fiat_shamir` delivers the non-interactive proof over the same
+//! runtime, so `InteractionOverhead` compares the two on equal terms: the
+//! extra flights and bytes, the measured time, and the latency the extra
+//! flights would add on a link with a given one-way delay.
+
+use std::time::{Duration, Instant};
+
+use ark_ff::{Field, PrimeField};
+use ark_std::rand::Rng;
+
+use crate::evaluation::CommunicationStats;
+use crate::mpc::distributed_sumcheck::{run_distributed, ChallengeSource};
+use crate::mpc::{
+    Aggregator, DistributedSumcheckError, DistributedSumcheckOutput, MessageKind, MessageRuntime, NetworkError,
+    SumcheckWorker,
+};
+use crate::piop::{
+    verify_sumcheck, ExtensionSumcheckProof, SumcheckError, SumcheckParameters, SumcheckSubclaim, Transcript,
+};
+use crate::serialization::{write_field, ByteReader, DecodeError, WireFormat};
+
+/// Runtime party of the aggregating prover
+pub const PROVER_PARTY: usize = 0;
+/// Runtime party of the verifier
+pub const VERIFIER_PARTY: usize = 1;
+
+/// Verifier answering round messages with fresh random challenges
+pub struct InteractiveVerifier<K: Field, R: Rng> {
+    parameters: SumcheckParameters,
+    claim: K,
+    rng: R,
+    expected: K,
+    point: Vec<K>,
+    subclaims: Vec<SumcheckSubclaim<K>>,
+}
+
+impl<K: Field, R: Rng> InteractiveVerifier<K, R> {
+    /// Verifier of `claim` = Σ f(x), sampling challenges from `rng`
+    pub fn new<F: PrimeField>(parameters: SumcheckParameters, claim: F, rng: R) -> Self
+    where
+        K: Field<BasePrimeField = F>,
+    {
+        let claim = K::from_base_prime_field(claim);
+        Self { parameters, claim, rng, expected: claim, point: Vec::new(), subclaims: Vec::new() }
+    }
+
+    /// Check one round message and sample the challenge answering it
+    pub fn respond(&mut self, message: &[K; 2]) -> Result<K, SumcheckError> {
+        let (repetition, round) = (self.subclaims.len(), self.point.len());
+        if repetition == self.parameters.repetitions {
+            return Err(SumcheckError::ProofShape);
+        }
+        if message[0] + message[1] != self.expected {
+            return Err(SumcheckError::RoundSum { repetition, round });
+        }
+        let challenge = K::rand(&mut self.rng);
+        self.expected = message[0] + challenge * (message[1] - message[0]);
+        self.point.push(challenge);
+        if self.point.len() == self.parameters.num_vars {
+            let point = std::mem::take(&mut self.point);
+            self.subclaims.push(SumcheckSubclaim { point, expected_evaluation: self.expected });
+            self.expected = self.claim;
+        }
+        Ok(challenge)
+    }
+
+    /// Evaluation claims left once every round has been answered
+    pub fn finish(self) -> Result<Vec<SumcheckSubclaim<K>>, SumcheckError> {
+        if self.subclaims.len() != self.parameters.repetitions || !self.point.is_empty() {
+            return Err(SumcheckError::ProofShape);
+        }
+        Ok(self.subclaims)
+    }
+}
+
+/// One run of the proving phase as seen by the verifier
+#[derive(Debug, Clone)]
+pub struct InteractiveRun<K: Field> {
+    /// Workers' side: the round messages, their final shares and their traffic to the aggregator
+    pub output: DistributedSumcheckOutput<K>,
+    /// Evaluation claims the verifier is left with
+    pub subclaims: Vec<SumcheckSubclaim<K>>,
+    /// Traffic between the prover and the verifier
+    pub transport: CommunicationStats,
+    /// Measured time of each prover–verifier round trip
+    pub round_latency: Vec<Duration>,
+    /// Time from the first worker message to the verifier's last check
+    pub elapsed: Duration,
+}
+
+impl<K: Field> InteractiveRun<K> {
+    /// Prover–verifier flights, each a one-way trip over the link
+    pub fn flights(&self) -> usize {
+        self.transport.rounds
+    }
+}
+
+/// Cost of running with a live verifier instead of Fiat–Shamir
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InteractionOverhead {
+    pub extra_flights: usize,
+    pub extra_bytes: isize,
+    /// Difference in measured time, zero if the interactive run was faster
+    pub extra_time: Duration,
+}
+
+impl InteractionOverhead {
+    pub fn between<K: Field>(interactive: &InteractiveRun<K>, fiat_shamir: &InteractiveRun<K>) -> Self {
+        Self {
+            extra_flights: interactive.flights().saturating_sub(fiat_shamir.flights()),
+            extra_bytes: interactive.transport.total_bytes() as isize - fiat_shamir.transport.total_bytes() as isize,
+            extra_time: interactive.elapsed.saturating_sub(fiat_shamir.elapsed),
+        }
+    }
+
+    /// Latency the extra flights add on a link with the given one-way delay
+    pub fn projected_latency(&self, one_way: Duration) -> Duration {
+        one_way * self.extra_flights as u32
+    }
+}
+
+/// Interactive mode error types
+#[derive(Debug, Clone, PartialEq)]
+pub enum InteractiveError {
+    Distributed(DistributedSumcheckError),
+    Network(NetworkError),
+    Decode(DecodeError),
+    /// A party expected one message of the given kind and got something else
+    UnexpectedMessage { party: usize, kind: MessageKind },
+}
+
+impl std::fmt::Display for InteractiveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            InteractiveError::Distributed(e) => write!(f, "{}", e),
+            InteractiveError::Network(e) => write!(f, "{}", e),
+            InteractiveError::Decode(e) => write!(f, "Malformed protocol message: {}", e),
+            InteractiveError::UnexpectedMessage { party, kind } => {
+                write!(f, "Party {} expected a single {:?} message", party, kind)
+            }
+        }
+    }
+}
+
+impl std::error::Error for InteractiveError {}
+
+impl From<DistributedSumcheckError> for InteractiveError {
+    fn from(e: DistributedSumcheckError) -> Self {
+        InteractiveError::Distributed(e)
+    }
+}
+
+impl From<SumcheckError> for InteractiveError {
+    fn from(e: SumcheckError) -> Self {
+        InteractiveError::Distributed(DistributedSumcheckError::Sumcheck(e))
+    }
+}
+
+impl From<NetworkError> for InteractiveError {
+    fn from(e: NetworkError) -> Self {
+        InteractiveError::Network(e)
+    }
+}
+
+impl From<DecodeError> for InteractiveError {
+    fn from(e: DecodeError) -> Self {
+        InteractiveError::Decode(e)
+    }
+}
+
+/// Send one message and deliver it, returning its payload at the receiver
+fn exchange(
+    runtime: &mut MessageRuntime,
+    from: usize,
+    to: usize,
+    kind: MessageKind,
+    payload: Vec<u8>,
+) -> Result<Vec<u8>, InteractiveError> {
+    runtime.send(from, to, kind, payload)?;
+    runtime.finish_round();
+    let mut received = runtime.receive(to)?;
+    match received.pop() {
+        Some(message) if received.is_empty() && message.kind == kind => Ok(message.payload),
+        _ => Err(InteractiveError::UnexpectedMessage { party: to, kind }),
+    }
+}
+
+fn decode_fields<K: Field, const N: usize>(payload: &[u8]) -> Result<[K; N], DecodeError> {
+    let mut reader = ByteReader::new(payload);
+    let mut values = [K::zero(); N];
+    for value in values.iter_mut() {
+        *value = reader.read_field()?;
+    }
+    reader.finish()?;
+    Ok(values)
+}
+
+struct LiveVerifier<'a, K: Field, R: Rng> {
+    verifier: &'a mut InteractiveVerifier<K, R>,
+    runtime: &'a mut MessageRuntime,
+    round_latency: Vec<Duration>,
+}
+
+impl<F: PrimeField, K: Field<BasePrimeField = F>, R: Rng> ChallengeSource<F, K> for LiveVerifier<'_, K, R> {
+    type Error = InteractiveError;
+
+    // The verifier already knows the claim it is checking
+    fn claim(&mut self, _parameters: &SumcheckParameters, _claim: F) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn challenge(&mut self, message: &[K; 2]) -> Result<K, Self::Error> {
+        let start = Instant::now();
+        let mut payload = Vec::new();
+        message.iter().for_each(|value| write_field(&mut payload, value));
+        let received = exchange(self.runtime, PROVER_PARTY, VERIFIER_PARTY, MessageKind::Commitment, payload)?;
+        let challenge = self.verifier.respond(&decode_fields(&received)?)?;
+
+        let mut payload = Vec::new();
+        write_field(&mut payload, &challenge);
+        let received = exchange(self.runtime, VERIFIER_PARTY, PROVER_PARTY, MessageKind::Challenge, payload)?;
+        let [challenge] = decode_fields(&received)?;
+        self.round_latency.push(start.elapsed());
+        Ok(challenge)
+    }
+}
+
+/// Run the sumcheck with the verifier choosing every challenge
+///
+/// Two flights per round: the combined round message to the verifier and
+/// its challenge back. The workers' messages are the same as in the
+/// Fiat–Shamir run; only the challenges differ.
+pub fn run_interactive<F: PrimeField, K: Field<BasePrimeField = F>, R: Rng>(
+    parameters: &SumcheckParameters,
+    workers: &mut [SumcheckWorker<F, K>],
+    aggregator: &mut Aggregator,
+    mut verifier: InteractiveVerifier<K, R>,
+) -> Result<InteractiveRun<K>, InteractiveError> {
+    let start = Instant::now();
+    let mut runtime = MessageRuntime::new(2);
+    let mut live = LiveVerifier { verifier: &mut verifier, runtime: &mut runtime, round_latency: Vec::new() };
+    let output = run_distributed(parameters, workers, aggregator, &mut live)?;
+    let round_latency = live.round_latency;
+    let subclaims = verifier.finish()?;
+    Ok(InteractiveRun { output, subclaims, transport: runtime.stats().clone(), round_latency, elapsed: start.elapsed() })
+}
+
+/// Run the sumcheck with Fiat–Shamir challenges and deliver the proof in one flight
+pub fn run_fiat_shamir<F: PrimeField, K: Field<BasePrimeField = F>>(
+    parameters: &SumcheckParameters,
+    claim: F,
+    workers: &mut [SumcheckWorker<F, K>],
+    aggregator: &mut Aggregator,
+    domain: &[u8],
+) -> Result<InteractiveRun<K>, InteractiveError> {
+    let start = Instant::now();
+    let output = run_distributed(parameters, workers, aggregator, &mut Transcript::new(domain))?;
+
+    let flight = Instant::now();
+    let mut runtime = MessageRuntime::new(2);
+    let received =
+        exchange(&mut runtime, PROVER_PARTY, VERIFIER_PARTY, MessageKind::Opening, output.proof.to_bytes())?;
+    let proof = ExtensionSumcheckProof::<K>::from_bytes(&received)?;
+    let subclaims = verify_sumcheck(parameters, claim, &proof, &mut Transcript::new(domain))?;
+    let round_latency = vec![flight.elapsed()];
+    Ok(InteractiveRun { output, subclaims, transport: runtime.stats().clone(), round_latency, elapsed: start.elapsed() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mpc::{AggregatorRole, SecretSharing, ShamirShare, ShamirSecretSharing};
+    use crate::piop::{MultilinearOracle, QueriedOracle};
+    use crate::serialization::field_size;
+    use ark_bls12_381::Fr;
+    use ark_std::test_rng;
+
+    type TestField = Fr;
+
+    fn share_table(table: &[TestField]) -> Vec<SumcheckWorker<TestField, TestField>> {
+        let mut rng = test_rng();
+        let shared: Vec<Vec<ShamirShare<TestField>>> =
+            table.iter().map(|value| ShamirSecretSharing::share_secret(*value, 2, 3, &mut rng)).collect();
+        (0..3)
+            .map(|party| {
+                let shares: Vec<_> = shared.iter().map(|entry| entry[party].clone()).collect();
+                SumcheckWorker::from_shares(&shares).unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_interactive_matches_fiat_shamir_soundness() {
+        let table: Vec<TestField> = (1..=16u64).map(TestField::from).collect();
+        let claim: TestField = table.iter().copied().sum();
+        let parameters = SumcheckParameters::for_security::<TestField>(4, 128);
+        let mut f = QueriedOracle::new(MultilinearOracle { label: "f".to_string(), table: table.clone() });
+
+        let verifier = InteractiveVerifier::new(parameters, claim, test_rng());
+        let mut aggregator = Aggregator::new(AggregatorRole::Delegator, 2);
+        let interactive = run_interactive(&parameters, &mut share_table(&table), &mut aggregator, verifier).unwrap();
+        let mut aggregator = Aggregator::new(AggregatorRole::Delegator, 2);
+        let fiat_shamir =
+            run_fiat_shamir(&parameters, claim, &mut share_table(&table), &mut aggregator, b"test").unwrap();
+
+        // Both leave the verifier with claims that hold
+        for run in [&interactive, &fiat_shamir] {
+            assert!(run.subclaims.iter().all(|subclaim| subclaim.check(&mut f).unwrap()));
+        }
+        // Two flights per sumcheck round against a single flight for the whole proof
+        let rounds = parameters.repetitions * parameters.num_vars;
+        assert_eq!(interactive.flights(), 2 * rounds);
+        assert_eq!(interactive.round_latency.len(), rounds);
+        assert_eq!(fiat_shamir.flights(), 1);
+        let overhead = InteractionOverhead::between(&interactive, &fiat_shamir);
+        assert_eq!(overhead.extra_flights, 2 * rounds - 1);
+        assert_eq!(
+            overhead.projected_latency(Duration::from_millis(20)),
+            Duration::from_millis(20 * (2 * rounds as u64 - 1))
+        );
+        assert_eq!(
+            ExtensionSumcheckProof::<TestField>::from_bytes(&fiat_shamir.output.proof.to_bytes()).unwrap(),
+            fiat_shamir.output.proof
+        );
+        // The proof alone carries the round messages; interactively each also costs a challenge
+        assert_eq!(interactive.transport.bytes_per_round.len(), 2 * rounds);
+        assert!(interactive.transport.total_bytes() > rounds * 3 * field_size::<TestField>());
+
+        // A prover claiming the wrong sum is caught in the first round
+        let verifier = InteractiveVerifier::new(parameters, claim + TestField::from(1u64), test_rng());
+        let mut aggregator = Aggregator::new(AggregatorRole::Delegator, 2);
+        assert_eq!(
+            run_interactive(&parameters, &mut share_table(&table), &mut aggregator, verifier).unwrap_err(),
+            InteractiveError::from(SumcheckError::RoundSum { repetition: 0, round: 0 })
+        );
+    }
+}
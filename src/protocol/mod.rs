@@ -2,7 +2,86 @@
 //! 
 //! This module implements the core delegation protocol (ISNARK) and related
 //! functionality for efficient outsourcing of SNARK computations.
+//!
+//! The statement, verification keys, constraint proof, streaming verifier, bundles, proof
+//! archive and proving backends are always built; everything that drives workers needs the
+//! `delegator` feature, and the worker handshake needs `worker`.
 
-pub mod delegation_protocol;
+pub(crate) mod statement;
+pub(crate) mod verification_key;
+pub(crate) mod constraint_proof;
+pub(crate) mod streaming_verifier;
+pub(crate) mod backend;
+#[cfg(feature = "delegator")]
+pub(crate) mod delegation_protocol;
+#[cfg(feature = "delegator")]
+pub(crate) mod compression;
+#[cfg(feature = "delegator")]
+pub(crate) mod multi_delegator;
+#[cfg(feature = "delegator")]
+pub(crate) mod privacy;
+pub(crate) mod bundle;
+pub(crate) mod archive;
+#[cfg(feature = "delegator")]
+pub(crate) mod translation;
+#[cfg(feature = "delegator")]
+pub(crate) mod partial_witness;
+#[cfg(feature = "delegator")]
+pub(crate) mod acceptance;
+#[cfg(feature = "delegator")]
+pub(crate) mod coin_flip;
+#[cfg(feature = "worker")]
+pub(crate) mod handshake;
+#[cfg(feature = "delegator")]
+pub(crate) mod resharing;
+#[cfg(feature = "delegator")]
+pub(crate) mod verification;
+#[cfg(feature = "delegator")]
+pub(crate) mod trace_binding;
+#[cfg(feature = "delegator")]
+pub(crate) mod interactive;
+#[cfg(feature = "delegator")]
+pub(crate) mod boosting;
+#[cfg(feature = "delegator")]
+pub(crate) mod deadline;
 
+#[cfg(all(test, feature = "delegator"))]
+mod model_check;
+
+pub use statement::*;
+pub use verification_key::*;
+pub use constraint_proof::*;
+pub use streaming_verifier::*;
+pub use backend::*;
+#[cfg(feature = "delegator")]
 pub use delegation_protocol::*;
+#[cfg(feature = "delegator")]
+pub use compression::*;
+#[cfg(feature = "delegator")]
+pub use multi_delegator::*;
+#[cfg(feature = "delegator")]
+pub use privacy::*;
+pub use bundle::*;
+pub use archive::*;
+#[cfg(feature = "delegator")]
+pub use translation::*;
+#[cfg(feature = "delegator")]
+pub use partial_witness::*;
+#[cfg(feature = "delegator")]
+pub use acceptance::*;
+#[cfg(feature = "delegator")]
+pub use coin_flip::*;
+#[cfg(feature = "worker")]
+pub use handshake::*;
+#[cfg(feature = "delegator")]
+pub use resharing::*;
+#[cfg(feature = "delegator")]
+pub use verification::*;
+#[cfg(feature = "delegator")]
+pub use trace_binding::*;
+#[cfg(feature = "delegator")]
+pub use interactive::*;
+#[cfg(feature = "delegator")]
+pub use boosting::*;
+#[cfg(feature = "delegator")]
+pub use deadline::*;
@@ -0,0 +1,341 @@
+//! State-machine model of the delegation rounds
+//!
+//! The model follows `delegate_computation` and `verify_computation` on the
+//! circuit `x·x = t, t·x = y` with `y` public:
+//!
+//! 1. sharing: the delegator sends each of three workers its Shamir share of
+//!    the witness `(x, t)`;
+//! 2. execution: every worker evaluates the linear gates of each constraint
+//!    row on its shares and sends its trace shares back; one worker may
+//!    tamper with a trace share first;
+//! 3. opening: the delegator reconstructs the trace with `SharedTrace` and
+//!    binds it to its witness with `check_trace_binding`, aborting on a
+//!    mismatch;
+//! 4. proof: the delegator commits to a witness and proves it for some
+//!    public inputs, and may lie about either;
+//! 5. verify: the verifier accepts under the conditions of
+//!    `verify_computation`: the proof is for its key (`KeyBinding`), the
+//!    opening point is derived from its public inputs (`PublicInputBinding`)
+//!    and the committed witness satisfies the constraints (`PiopIdentity`).
+//!
+//! Commitments, openings and the sumcheck are taken to be sound, so the proof
+//! message carries the committed witness itself. The model is explored
+//! exhaustively with stateright over a small range of witnesses and sharing
+//! coefficients.
+
+use ark_bls12_381::Fr;
+use ark_ff::{Field, One};
+use stateright::{Checker, Model, Property};
+
+use crate::mpc::{ExecCircuit, ShamirSecretSharing, ShamirShare, SharedTrace, TraceCell};
+use crate::piop::Transcript;
+use crate::protocol::{check_trace_binding, witness_tables, ConstraintMatrices, VerificationCheck};
+
+type SS = ShamirSecretSharing<Fr>;
+
+const NUM_WORKERS: usize = 3;
+/// Key hash the verifier holds; a proof under any other key is rejected
+const KEY: u8 = 0;
+
+/// `x·x = t`, `t·x = y` over `z = (1, y, x, t)`
+fn matrices() -> ConstraintMatrices<Fr> {
+    let (y, x, t) = (1, 2, 3);
+    let one = Fr::one();
+    ConstraintMatrices {
+        a_matrix: vec![vec![(x, one)], vec![(t, one)]],
+        b_matrix: vec![vec![(x, one)], vec![(x, one)]],
+        c_matrix: vec![vec![(t, one)], vec![(y, one)]],
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum WorkerState {
+    Waiting,
+    /// Shares of `(x, t)` received in the sharing round
+    Holding(Vec<Fr>),
+    /// Trace shares sent back, one per shared cell in column order
+    Answered(Vec<Fr>),
+}
+
+/// What the delegator claims in its proof
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Claim {
+    Honest,
+    /// Commit to `(x, x²)` instead of the delegated witness
+    OtherWitness,
+    /// Prove for `y + 1`
+    OtherPublicInput,
+    /// Prove under another verification key
+    OtherKey,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct Proof {
+    key: u8,
+    public_inputs: Vec<Fr>,
+    witness: Vec<Fr>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum Verdict {
+    Accepted,
+    Rejected(VerificationCheck),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct RoundState {
+    /// The verifier's public input `y = x³`
+    public_input: Fr,
+    /// The delegator's witness `(x, t)`, not necessarily satisfying
+    witness: Vec<Fr>,
+    /// Degree-one sharing coefficient of every witness element
+    coefficient: Fr,
+    workers: Vec<WorkerState>,
+    tampered: Option<usize>,
+    /// Outcome of trace binding once the trace is opened
+    trace_bound: Option<bool>,
+    proof: Option<Proof>,
+    verdict: Option<Verdict>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum RoundAction {
+    DeliverShares(usize),
+    Execute(usize),
+    Tamper(usize),
+    OpenTrace,
+    Prove(Claim),
+    Verify,
+}
+
+struct DelegationModel {
+    /// Range of witnesses and sharing coefficients to explore
+    values: u64,
+    /// Check the constraint proof, as `verify_computation` does; without it
+    /// the verifier only checks key and public-input binding
+    check_constraints: bool,
+}
+
+impl DelegationModel {
+    fn shares_for(state: &RoundState, worker: usize) -> Vec<Fr> {
+        let x = Fr::from((worker + 1) as u64);
+        state.witness.iter().map(|w| *w + state.coefficient * x).collect()
+    }
+
+    /// A worker's trace shares: every row's witness columns applied to its
+    /// shares, the public columns being left to the delegator
+    fn execute(shares: &[Fr]) -> Vec<Fr> {
+        let matrices = matrices();
+        [&matrices.a_matrix, &matrices.b_matrix, &matrices.c_matrix]
+            .into_iter()
+            .flatten()
+            .filter(|row| row.iter().any(|&(column, _)| column >= 2))
+            .map(|row| {
+                row.iter()
+                    .filter(|&&(column, _)| column >= 2)
+                    .map(|&(column, coefficient)| coefficient * shares[column - 2])
+                    .sum()
+            })
+            .collect()
+    }
+
+    /// Reconstruct the trace from the workers' answers and bind it to the
+    /// delegator's witness
+    fn open_trace(state: &RoundState) -> bool {
+        let answers: Vec<&Vec<Fr>> = state
+            .workers
+            .iter()
+            .map(|worker| match worker {
+                WorkerState::Answered(values) => values,
+                _ => unreachable!("the trace is opened once every worker answered"),
+            })
+            .collect();
+        let public_inputs = [state.public_input];
+        let matrices = matrices();
+        let mut cell = 0;
+        let mut column = |rows: &[Vec<(usize, Fr)>]| -> Vec<TraceCell<Fr, SS>> {
+            rows.iter()
+                .map(|row| {
+                    let z = [Fr::one(), state.public_input];
+                    let public = row.iter().filter(|&&(c, _)| c < 2).map(|&(c, coefficient)| coefficient * z[c]).sum();
+                    if row.iter().all(|&(c, _)| c < 2) {
+                        return TraceCell { shares: None, public };
+                    }
+                    let shares = answers
+                        .iter()
+                        .enumerate()
+                        .map(|(worker, values)| ShamirShare { index: worker + 1, value: values[cell] })
+                        .collect();
+                    cell += 1;
+                    TraceCell { shares: Some(shares), public }
+                })
+                .collect()
+        };
+        let columns = [column(&matrices.a_matrix), column(&matrices.b_matrix), column(&matrices.c_matrix)];
+        let shared = SharedTrace { columns };
+
+        let executor = ExecCircuit::new(1, NUM_WORKERS, ShamirSecretSharing::new());
+        let trace = shared.reconstruct(&executor).expect("every trace cell has all shares");
+        let tables = witness_tables(&matrices, &public_inputs, &state.witness).expect("witness covers every column");
+        check_trace_binding(&trace, &tables, &mut Transcript::new(b"model_check")).is_ok()
+    }
+
+    fn prove(state: &RoundState, claim: Claim) -> Proof {
+        let x = state.witness[0];
+        let mut proof = Proof { key: KEY, public_inputs: vec![state.public_input], witness: state.witness.clone() };
+        match claim {
+            Claim::Honest => {}
+            Claim::OtherWitness => proof.witness = vec![x, x.square()],
+            Claim::OtherPublicInput => proof.public_inputs = vec![state.public_input + Fr::one()],
+            Claim::OtherKey => proof.key = KEY + 1,
+        }
+        proof
+    }
+
+    /// `verify_computation`'s checks, in its order
+    fn verify(&self, state: &RoundState, proof: &Proof) -> Verdict {
+        if proof.key != KEY {
+            return Verdict::Rejected(VerificationCheck::KeyBinding);
+        }
+        if proof.public_inputs != [state.public_input] {
+            return Verdict::Rejected(VerificationCheck::PublicInputBinding);
+        }
+        if self.check_constraints && !satisfies(&[state.public_input], &proof.witness) {
+            return Verdict::Rejected(VerificationCheck::PiopIdentity);
+        }
+        Verdict::Accepted
+    }
+}
+
+fn satisfies(public_inputs: &[Fr], witness: &[Fr]) -> bool {
+    witness_tables(&matrices(), public_inputs, witness).is_ok_and(|tables| tables.unsatisfied().is_empty())
+}
+
+impl Model for DelegationModel {
+    type State = RoundState;
+    type Action = RoundAction;
+
+    fn init_states(&self) -> Vec<Self::State> {
+        let mut states = Vec::new();
+        for x in (0..self.values).map(Fr::from) {
+            // The delegator may also hold a witness with the wrong t
+            for t in [x.square(), x.square() + Fr::one()] {
+                for coefficient in (0..self.values).map(Fr::from) {
+                    states.push(RoundState {
+                        public_input: x.square() * x,
+                        witness: vec![x, t],
+                        coefficient,
+                        workers: vec![WorkerState::Waiting; NUM_WORKERS],
+                        tampered: None,
+                        trace_bound: None,
+                        proof: None,
+                        verdict: None,
+                    });
+                }
+            }
+        }
+        states
+    }
+
+    fn actions(&self, state: &Self::State, actions: &mut Vec<Self::Action>) {
+        if state.verdict.is_some() || state.trace_bound == Some(false) {
+            return;
+        }
+        if state.proof.is_some() {
+            actions.push(RoundAction::Verify);
+            return;
+        }
+        if state.trace_bound == Some(true) {
+            actions.extend(
+                [Claim::Honest, Claim::OtherWitness, Claim::OtherPublicInput, Claim::OtherKey].map(RoundAction::Prove),
+            );
+            return;
+        }
+        for (worker, worker_state) in state.workers.iter().enumerate() {
+            match worker_state {
+                WorkerState::Waiting => actions.push(RoundAction::DeliverShares(worker)),
+                WorkerState::Holding(_) => actions.push(RoundAction::Execute(worker)),
+                WorkerState::Answered(_) => {
+                    if state.tampered.is_none() {
+                        actions.push(RoundAction::Tamper(worker));
+                    }
+                }
+            }
+        }
+        if state.workers.iter().all(|w| matches!(w, WorkerState::Answered(_))) {
+            actions.push(RoundAction::OpenTrace);
+        }
+    }
+
+    fn next_state(&self, last_state: &Self::State, action: Self::Action) -> Option<Self::State> {
+        let mut state = last_state.clone();
+        match action {
+            RoundAction::DeliverShares(worker) => {
+                state.workers[worker] = WorkerState::Holding(Self::shares_for(&state, worker));
+            }
+            RoundAction::Execute(worker) => {
+                if let WorkerState::Holding(shares) = &state.workers[worker] {
+                    state.workers[worker] = WorkerState::Answered(Self::execute(shares));
+                }
+            }
+            RoundAction::Tamper(worker) => {
+                if let WorkerState::Answered(values) = &mut state.workers[worker] {
+                    values[0] += Fr::one();
+                    state.tampered = Some(worker);
+                }
+            }
+            RoundAction::OpenTrace => state.trace_bound = Some(Self::open_trace(&state)),
+            RoundAction::Prove(claim) => state.proof = Some(Self::prove(&state, claim)),
+            RoundAction::Verify => {
+                let proof = state.proof.as_ref().expect("verify follows the proof");
+                state.verdict = Some(self.verify(&state, proof));
+            }
+        }
+        Some(state)
+    }
+
+    fn properties(&self) -> Vec<Property<Self>> {
+        vec![
+            Property::always("accepted proofs satisfy the circuit", |_, state: &RoundState| {
+                match (&state.verdict, &state.proof) {
+                    (Some(Verdict::Accepted), Some(proof)) => satisfies(&[state.public_input], &proof.witness),
+                    _ => true,
+                }
+            }),
+            Property::always("a tampered trace is never bound", |_, state: &RoundState| {
+                !(state.tampered.is_some() && state.trace_bound == Some(true))
+            }),
+            Property::sometimes("honest run is accepted", |_, state: &RoundState| {
+                state.verdict == Some(Verdict::Accepted)
+            }),
+            Property::sometimes("tampered trace aborts the delegation", |_, state: &RoundState| {
+                state.tampered.is_some() && state.trace_bound == Some(false)
+            }),
+            Property::sometimes("unsatisfying witness is rejected", |_, state: &RoundState| {
+                state.verdict == Some(Verdict::Rejected(VerificationCheck::PiopIdentity))
+            }),
+            Property::sometimes("foreign public input is rejected", |_, state: &RoundState| {
+                state.verdict == Some(Verdict::Rejected(VerificationCheck::PublicInputBinding))
+            }),
+        ]
+    }
+}
+
+#[test]
+fn test_model_safety_properties() {
+    let checker = DelegationModel { values: 3, check_constraints: true }
+        .checker()
+        .spawn_bfs()
+        .join();
+    checker.assert_properties();
+}
+
+#[test]
+fn test_model_finds_missing_constraint_check() {
+    // Key and public-input binding alone accept any committed witness
+    let checker = DelegationModel { values: 3, check_constraints: false }
+        .checker()
+        .spawn_bfs()
+        .join();
+    assert!(checker.discovery("accepted proofs satisfy the circuit").is_some());
+}
@@ -0,0 +1,310 @@
+//! Multi-delegator sessions
+//!
+//! A worker committee can serve several delegators in one MPC session. All
+//! delegators' inputs are shared in a single input round and every trace is
+//! returned in a single evaluation round, both carried by one
+//! `MessageRuntime`, so the round latency is paid once per session rather
+//! than once per client. Delegators stay separated: each one's shares are
+//! drawn from an independently seeded RNG, and each proof transcript is
+//! bound to the delegator's ID via `delegator_context`.
+
+use std::collections::HashSet;
+
+use ark_ec::pairing::Pairing;
+use ark_ff::{Field, PrimeField};
+use ark_std::rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::evaluation::CommunicationStats;
+use crate::mpc::{
+    ExecutionStats, MessageKind, MessageRuntime, NetworkError, OperationMode, SecretSharing, SharedTrace, WireShares,
+    WitnessInput,
+};
+use crate::protocol::{DelegationResult, EOSError, EOSProtocol, MPCResult};
+use crate::serialization::{write_len, ByteReader, DecodeError, WireFormat};
+
+/// One delegator's job within a shared session
+#[derive(Debug, Clone)]
+pub struct DelegationRequest<F: Field> {
+    pub delegator_id: u64,
+    pub witness: Vec<F>,
+    pub public_inputs: Vec<F>,
+}
+
+/// Outcome of a multi-delegator session
+#[derive(Debug)]
+pub struct SessionResult<E: Pairing, F: Field> {
+    /// Per-delegator results, in request order
+    pub results: Vec<(u64, DelegationResult<E, F>)>,
+    /// Statistics for the session as a whole
+    pub session_stats: ExecutionStats,
+    /// Traffic of the session's input and evaluation rounds
+    pub communication: CommunicationStats,
+}
+
+/// Transcript context that binds a proof to one delegator
+pub fn delegator_context(delegator_id: u64) -> Vec<u8> {
+    let mut context = b"delegator".to_vec();
+    context.extend_from_slice(&delegator_id.to_le_bytes());
+    context
+}
+
+impl<E, F, SS, OM> EOSProtocol<E, F, SS, OM>
+where
+    E: Pairing<ScalarField = F>,
+    F: PrimeField,
+    SS: SecretSharing<F>,
+    SS::Share: WireFormat,
+    OM: OperationMode<F, SS>,
+{
+    /// Run several delegations from different delegators in one session
+    pub fn delegate_session(
+        &mut self,
+        requests: &[DelegationRequest<F>],
+        rng: &mut impl Rng,
+    ) -> Result<SessionResult<E, F>, EOSError> {
+        self.preprocessing_state
+            .as_ref()
+            .ok_or(EOSError::PreprocessingNotDone)?;
+
+        let mut seen = HashSet::new();
+        for request in requests {
+            if !seen.insert(request.delegator_id) {
+                return Err(EOSError::DuplicateDelegator(request.delegator_id));
+            }
+        }
+
+        // Workers are runtime parties 0..n, delegator i is party n + i
+        let num_workers = self.circuit_executor.num_parties;
+        let mut runtime = MessageRuntime::new(num_workers + requests.len());
+
+        // 1. Input round: every delegator's witness is shared with its own RNG
+        for (index, request) in requests.iter().enumerate() {
+            let mut seed = [0u8; 32];
+            rng.fill_bytes(&mut seed);
+            let mut delegator_rng = StdRng::from_seed(seed);
+            let shares = self.share_witness(&request.witness, &mut delegator_rng);
+            for worker in 0..num_workers {
+                let payload = encode_shares(shares.iter().map(|wire| &wire[worker]));
+                runtime.send(num_workers + index, worker, MessageKind::Share, payload).map_err(communication)?;
+            }
+        }
+        runtime.finish_round();
+
+        // Each worker's inbox holds its share of every delegator's witness
+        let mut witnesses: Vec<Vec<WireShares<F, SS>>> =
+            requests.iter().map(|request| vec![Vec::with_capacity(num_workers); request.witness.len()]).collect();
+        for worker in 0..num_workers {
+            for message in runtime.receive(worker).map_err(communication)? {
+                let shares = decode_shares::<F, SS>(&message.payload).map_err(communication)?;
+                let witness = &mut witnesses[message.from - num_workers];
+                if shares.len() != witness.len() {
+                    return Err(communication(SessionError::ShareCount {
+                        from: message.from,
+                        expected: witness.len(),
+                        received: shares.len(),
+                    }));
+                }
+                for (wire, share) in witness.iter_mut().zip(shares) {
+                    wire.push(share);
+                }
+            }
+        }
+
+        // 2. Evaluation round: all traces go back to their delegators at once
+        let timer = self.metrics.start_timer("mpc_execution");
+        let matrices = &self.preprocessing_state.as_ref().ok_or(EOSError::PreprocessingNotDone)?.circuit_params.constraint_matrices;
+        let mut traces = Vec::with_capacity(requests.len());
+        for (request, witness) in requests.iter().zip(&witnesses) {
+            let inputs: Vec<WitnessInput<'_, F, SS>> = witness.iter().map(WitnessInput::Shared).collect();
+            let trace = self
+                .circuit_executor
+                .execute_r1cs_with_local(matrices, &request.public_inputs, &inputs)
+                .map_err(EOSError::MPCError)?;
+            traces.push(trace);
+        }
+        for (index, trace) in traces.iter().enumerate() {
+            for worker in 0..num_workers {
+                let payload = encode_shares(trace_shares(trace).map(|cell| &cell[worker]));
+                runtime.send(worker, num_workers + index, MessageKind::Opening, payload).map_err(communication)?;
+            }
+        }
+        runtime.finish_round();
+
+        // Each delegator rebuilds its trace from the shares it received
+        let mut mpc_results = Vec::with_capacity(requests.len());
+        for (index, mut trace) in traces.into_iter().enumerate() {
+            for message in runtime.receive(num_workers + index).map_err(communication)? {
+                let shares = decode_shares::<F, SS>(&message.payload).map_err(communication)?;
+                let mut cells: Vec<&mut WireShares<F, SS>> = trace
+                    .columns
+                    .iter_mut()
+                    .flatten()
+                    .filter_map(|cell| cell.shares.as_mut())
+                    .collect();
+                if shares.len() != cells.len() {
+                    return Err(communication(SessionError::ShareCount {
+                        from: message.from,
+                        expected: cells.len(),
+                        received: shares.len(),
+                    }));
+                }
+                for (cell, share) in cells.iter_mut().zip(shares) {
+                    cell[message.from] = share;
+                }
+            }
+            let computation_trace = trace.reconstruct(&self.circuit_executor).map_err(EOSError::MPCError)?;
+            mpc_results.push(MPCResult { shared_outputs: vec![], computation_trace });
+        }
+        self.end_phase(timer);
+
+        // 3. Per-delegator commitments and proofs
+        let communication = runtime.stats().clone();
+        let mut results = Vec::with_capacity(requests.len());
+        for (request, mpc_result) in requests.iter().zip(&mpc_results) {
+            let context = delegator_context(request.delegator_id);
+            self.bind_trace(mpc_result, &request.witness, &request.public_inputs, &context)?;
+            let mut result = self.prove_delegation(&request.witness, &request.public_inputs, &context)?;
+            result.execution_stats.communication_rounds = communication.rounds;
+            results.push((request.delegator_id, result));
+        }
+
+        let mut session_stats = ExecutionStats::new();
+        session_stats.communication_rounds = communication.rounds;
+
+        Ok(SessionResult { results, session_stats, communication })
+    }
+}
+
+/// Shares of a trace's witness-dependent cells, in column order
+fn trace_shares<F: Field, SS: SecretSharing<F>>(trace: &SharedTrace<F, SS>) -> impl Iterator<Item = &WireShares<F, SS>> {
+    trace.columns.iter().flatten().filter_map(|cell| cell.shares.as_ref())
+}
+
+fn encode_shares<'a, S: WireFormat + 'a>(shares: impl Iterator<Item = &'a S>) -> Vec<u8> {
+    let shares: Vec<&S> = shares.collect();
+    let mut out = Vec::new();
+    write_len(&mut out, shares.len());
+    for share in shares {
+        share.encode(&mut out);
+    }
+    out
+}
+
+fn decode_shares<F: Field, SS: SecretSharing<F>>(payload: &[u8]) -> Result<Vec<SS::Share>, DecodeError>
+where
+    SS::Share: WireFormat,
+{
+    let mut reader = ByteReader::new(payload);
+    let len = reader.read_len(1)?;
+    let shares = (0..len).map(|_| SS::Share::decode(&mut reader)).collect::<Result<_, _>>()?;
+    reader.finish()?;
+    Ok(shares)
+}
+
+/// A session message that could not be delivered or decoded
+fn communication(error: impl Into<SessionError>) -> EOSError {
+    EOSError::Session(error.into())
+}
+
+/// Session message error types
+#[derive(Debug, Clone, PartialEq)]
+pub enum SessionError {
+    Network(NetworkError),
+    Decode(DecodeError),
+    /// A message carries a different number of shares than its recipient expects
+    ShareCount { from: usize, expected: usize, received: usize },
+}
+
+impl From<NetworkError> for SessionError {
+    fn from(e: NetworkError) -> Self {
+        SessionError::Network(e)
+    }
+}
+
+impl From<DecodeError> for SessionError {
+    fn from(e: DecodeError) -> Self {
+        SessionError::Decode(e)
+    }
+}
+
+impl std::fmt::Display for SessionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            SessionError::Network(e) => write!(f, "Network error: {}", e),
+            SessionError::Decode(e) => write!(f, "Malformed message: {}", e),
+            SessionError::ShareCount { from, expected, received } => {
+                write!(f, "Party {} sent {} shares, expected {}", from, received, expected)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SessionError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mpc::{CollaborationMode, ExecCircuit, ShamirSecretSharing};
+    use crate::protocol::StreamingVerifier;
+    use ark_bls12_381::{Bls12_381, Fr};
+    use ark_relations::r1cs::ConstraintSystem;
+    use ark_std::test_rng;
+
+    type Protocol = EOSProtocol<Bls12_381, Fr, ShamirSecretSharing<Fr>, CollaborationMode>;
+
+    #[test]
+    fn test_session_separates_delegators() {
+        let mut rng = test_rng();
+        let executor = ExecCircuit::new(1, 3, ShamirSecretSharing::new());
+        let mut protocol = Protocol::new(executor, CollaborationMode::new(2, true, false), 128, &mut rng);
+        let circuit = ConstraintSystem::new();
+        protocol.preprocessing_state = Some(Protocol::preprocessing(&circuit, 128, &mut rng).unwrap());
+
+        // Same witness and public inputs, differing only in the delegator
+        let requests: Vec<_> = (0..3u64)
+            .map(|id| DelegationRequest {
+                delegator_id: id,
+                witness: vec![Fr::from(3u64), Fr::from(5u64)],
+                public_inputs: vec![Fr::from(15u64)],
+            })
+            .collect();
+        let session = protocol.delegate_session(&requests, &mut rng).unwrap();
+        assert_eq!(session.results.len(), 3);
+        assert_eq!(session.session_stats.communication_rounds, session.communication.rounds);
+        assert_eq!(session.communication.rounds, 2);
+
+        let proofs: Vec<Vec<u8>> = session
+            .results
+            .iter()
+            .map(|(_, result)| result.piop_proof.clone().unwrap())
+            .collect();
+        assert_ne!(proofs[0], proofs[1]);
+
+        let public_inputs = [Fr::from(15u64)];
+        let key_hash = protocol.verification_key_hash().unwrap();
+        let matrices = protocol.constraint_matrices().unwrap();
+        for (id, proof) in proofs.iter().enumerate() {
+            let verifier = StreamingVerifier::<Bls12_381>::new(&protocol.commitment_scheme, key_hash, matrices)
+                .with_context(&delegator_context(id as u64));
+            assert!(verifier.verify(proof.as_slice(), &public_inputs, &mut rng).unwrap());
+        }
+
+        // A proof does not verify under another delegator's context
+        let other = StreamingVerifier::<Bls12_381>::new(&protocol.commitment_scheme, key_hash, matrices)
+            .with_context(&delegator_context(1));
+        assert!(!other.verify(proofs[0].as_slice(), &public_inputs, &mut rng).unwrap());
+
+        // A lone delegator pays the same two rounds for less traffic
+        let single = protocol.delegate_session(&requests[..1], &mut rng).unwrap();
+        assert_eq!(single.communication.rounds, session.communication.rounds);
+        let bytes = |stats: &CommunicationStats| stats.bytes_per_round.iter().sum::<usize>();
+        assert!(bytes(&single.communication) < bytes(&session.communication));
+
+        // Duplicate delegator IDs are rejected
+        let duplicate = vec![requests[0].clone(), requests[0].clone()];
+        assert!(matches!(
+            protocol.delegate_session(&duplicate, &mut rng),
+            Err(EOSError::DuplicateDelegator(0))
+        ));
+    }
+}
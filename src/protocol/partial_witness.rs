@@ -0,0 +1,245 @@
+//! Delegation with part of the witness kept by the delegator
+//!
+//! A `WitnessSplit` marks witness positions the delegator keeps local. Only
+//! the remaining positions are secret shared with the workers. The witness
+//! polynomial is the sum of the workers' contribution (zero at local
+//! positions) and the delegator's contribution (zero everywhere else), and
+//! both are committed and opened separately before being added up, so the
+//! verifier sees an ordinary proof for the whole witness.
+//!
+//! The workers execute the circuit with the local positions folded into the
+//! delegator's part of each trace cell, and the trace is bound to the full
+//! witness like in an ordinary delegation. `delegate_circuit` takes the split
+//! from the circuit's own privacy annotations.
+
+use std::collections::BTreeSet;
+
+use ark_ec::pairing::Pairing;
+use ark_ff::PrimeField;
+use ark_relations::r1cs::ConstraintSystem;
+use ark_std::rand::Rng;
+
+use crate::custom_circuits::CustomCircuit;
+use crate::mpc::{OperationMode, SecretSharing, WitnessInput};
+use crate::protocol::{DelegationResult, EOSError, EOSProtocol};
+
+/// Witness positions that stay with the delegator
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WitnessSplit {
+    len: usize,
+    local: BTreeSet<usize>,
+}
+
+impl WitnessSplit {
+    pub fn new(len: usize, local: impl IntoIterator<Item = usize>) -> Result<Self, EOSError> {
+        let local: BTreeSet<usize> = local.into_iter().collect();
+        if let Some(&index) = local.iter().find(|&&index| index >= len) {
+            return Err(EOSError::InvalidWitnessSplit(format!(
+                "local position {} outside a witness of length {}",
+                index, len
+            )));
+        }
+        Ok(Self { len, local })
+    }
+
+    /// Split keeping the circuit's `Privacy::DelegatorOnly` witnesses local
+    pub fn from_circuit<F: PrimeField>(circuit: &CustomCircuit<F>) -> Self {
+        Self { len: circuit.private_witnesses.len(), local: circuit.delegator_only.clone() }
+    }
+
+    pub fn is_local(&self, index: usize) -> bool {
+        self.local.contains(&index)
+    }
+
+    pub fn num_local(&self) -> usize {
+        self.local.len()
+    }
+
+    /// Values handed to the workers, in witness order
+    pub fn shared_values<F: PrimeField>(&self, witness: &[F]) -> Vec<F> {
+        witness
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !self.is_local(*i))
+            .map(|(_, value)| *value)
+            .collect()
+    }
+
+    /// Workers' additive contribution: the witness with local positions zeroed
+    pub fn worker_contribution<F: PrimeField>(&self, witness: &[F]) -> Vec<F> {
+        self.mask(witness, false)
+    }
+
+    /// Delegator's additive contribution: only the local positions
+    pub fn delegator_contribution<F: PrimeField>(&self, witness: &[F]) -> Vec<F> {
+        self.mask(witness, true)
+    }
+
+    fn mask<F: PrimeField>(&self, witness: &[F], keep_local: bool) -> Vec<F> {
+        witness
+            .iter()
+            .enumerate()
+            .map(|(i, value)| if self.is_local(i) == keep_local { *value } else { F::zero() })
+            .collect()
+    }
+}
+
+impl<E, F, SS, OM> EOSProtocol<E, F, SS, OM>
+where
+    E: Pairing<ScalarField = F>,
+    F: PrimeField,
+    SS: SecretSharing<F>,
+    OM: OperationMode<F, SS>,
+{
+    /// Delegate with the positions in `split` kept local
+    ///
+    /// Local values are never shared with the workers; the delegator adds
+    /// its contribution to theirs when the proof is assembled.
+    pub fn delegate_with_local_inputs(
+        &mut self,
+        circuit: &ConstraintSystem<F>,
+        witness: &[F],
+        split: &WitnessSplit,
+        public_inputs: &[F],
+        rng: &mut impl Rng,
+    ) -> Result<DelegationResult<E, F>, EOSError> {
+        self.preprocessing_state
+            .as_ref()
+            .ok_or(EOSError::PreprocessingNotDone)?;
+        if split.len != witness.len() {
+            return Err(EOSError::InvalidWitnessSplit(format!(
+                "split covers {} positions but the witness has {}",
+                split.len,
+                witness.len()
+            )));
+        }
+
+        self.check_budget(circuit, witness)?;
+
+        let witness_shares = self.share_witness(&split.shared_values(witness), rng);
+        let mut shares = witness_shares.iter();
+        let inputs: Vec<WitnessInput<'_, F, SS>> = witness
+            .iter()
+            .enumerate()
+            .map(|(i, value)| {
+                if split.is_local(i) {
+                    WitnessInput::Local(*value)
+                } else {
+                    WitnessInput::Shared(shares.next().expect("one share vector per shared position"))
+                }
+            })
+            .collect();
+        let mpc_result = self.execute_circuit_mpc_with_local(circuit, &inputs, public_inputs)?;
+        self.bind_trace(&mpc_result, witness, public_inputs, &[])?;
+
+        let worker = split.worker_contribution(witness);
+        let delegator = split.delegator_contribution(witness);
+        self.prove_contributions(&[&worker, &delegator], public_inputs, &[])
+    }
+
+    /// Delegate a `CustomCircuit`, sharing each private witness unless it is
+    /// annotated `Privacy::DelegatorOnly`
+    ///
+    /// `preprocessing_state` must have been indexed from
+    /// `circuit.to_constraint_system()`.
+    pub fn delegate_circuit(
+        &mut self,
+        circuit: &CustomCircuit<F>,
+        rng: &mut impl Rng,
+    ) -> Result<DelegationResult<E, F>, EOSError> {
+        let split = WitnessSplit::from_circuit(circuit);
+        self.delegate_with_local_inputs(
+            &circuit.to_constraint_system(),
+            &circuit.private_witnesses,
+            &split,
+            &circuit.public_inputs,
+            rng,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::custom_circuits::Privacy;
+    use crate::mpc::{CollaborationMode, ExecCircuit, ShamirSecretSharing};
+    use crate::protocol::verify_bundle;
+    use crate::serialization::WireFormat;
+    use ark_bls12_381::{Bls12_381, Fr};
+    use ark_std::test_rng;
+
+    type TestField = Fr;
+    type Protocol = EOSProtocol<Bls12_381, TestField, ShamirSecretSharing<TestField>, CollaborationMode>;
+
+    fn protocol(rng: &mut impl Rng) -> Protocol {
+        let executor = ExecCircuit::new(0, 3, ShamirSecretSharing::new());
+        let mut protocol = Protocol::new(executor, CollaborationMode::new(2, true, false), 128, rng);
+        let circuit = ConstraintSystem::new();
+        protocol.preprocessing_state = Some(Protocol::preprocessing(&circuit, 128, rng).unwrap());
+        protocol
+    }
+
+    #[test]
+    fn test_local_inputs_combine_into_ordinary_proof() {
+        let mut rng = test_rng();
+        let witness: Vec<TestField> = (1..=8u64).map(TestField::from).collect();
+        let public_inputs = [TestField::from(5u64)];
+        let split = WitnessSplit::new(witness.len(), [1, 4, 6]).unwrap();
+        assert_eq!(split.shared_values(&witness).len(), 5);
+
+        // Identical setups, so both proofs can be compared byte for byte
+        let mut full = protocol(&mut test_rng());
+        let mut partial = protocol(&mut test_rng());
+        let circuit = ConstraintSystem::new();
+        let expected = full.delegate_computation(&circuit, &witness, &public_inputs, &mut rng).unwrap();
+        let result = partial
+            .delegate_with_local_inputs(&circuit, &witness, &split, &public_inputs, &mut rng)
+            .unwrap();
+
+        // The combined contributions give exactly the full-witness proof
+        assert_eq!(result.piop_proof, expected.piop_proof);
+        assert_eq!(result.polynomial_commitments, expected.polynomial_commitments);
+
+        let bundle = partial.export_bundle(&result, &public_inputs, &[]).unwrap();
+        assert!(verify_bundle(&bundle, &mut rng).unwrap());
+
+        assert!(WitnessSplit::new(4, [4]).is_err());
+        let short = WitnessSplit::new(4, [0]).unwrap();
+        assert!(partial.delegate_with_local_inputs(&circuit, &witness, &short, &public_inputs, &mut rng).is_err());
+    }
+
+    #[test]
+    fn test_circuit_annotations_drive_sharing() {
+        let mut rng = test_rng();
+        // x·k = y with x shared, k kept by the delegator and y public
+        let x_value = TestField::from(6u64);
+        let mut circuit = CustomCircuit::new("annotated".to_string());
+        let x = circuit.add_private_witness(x_value);
+        let k = circuit.add_delegator_witness(TestField::from(7u64));
+        let y = circuit.add_public_input(TestField::from(42u64));
+        circuit.add_multiplication_constraint(x, k, y);
+        assert_eq!(circuit.privacy(x), Privacy::Shared);
+        assert_eq!(circuit.privacy(k), Privacy::DelegatorOnly);
+        assert_eq!(circuit.privacy(y), Privacy::Public);
+        let decoded = CustomCircuit::<TestField>::from_bytes(&circuit.to_bytes()).unwrap();
+        assert_eq!(decoded.delegator_only, circuit.delegator_only);
+
+        let mut protocol = protocol(&mut rng);
+        let cs = circuit.to_constraint_system();
+        protocol.preprocessing_state = Some(Protocol::preprocessing(&cs, 128, &mut rng).unwrap());
+        let result = protocol.delegate_circuit(&circuit, &mut rng).unwrap();
+        assert!(protocol.verify_computation(&result, &circuit.public_inputs).unwrap().is_accepted());
+
+        // Only x reached the workers; the trace still covers the constraint on k
+        assert_eq!(WitnessSplit::from_circuit(&circuit).shared_values(&circuit.private_witnesses), [x_value]);
+        let shares = protocol.share_witness(&[x_value], &mut rng);
+        let inputs = [WitnessInput::Shared(&shares[0]), WitnessInput::Local(TestField::from(7u64))];
+        let executed = protocol.execute_circuit_mpc_with_local(&cs, &inputs, &circuit.public_inputs).unwrap();
+        assert!(executed.computation_trace.unsatisfied().is_empty());
+
+        // Annotations can be changed after allocation
+        circuit.set_privacy(k, Privacy::Shared);
+        assert!(circuit.delegator_only.is_empty());
+        assert!(protocol.delegate_circuit(&circuit, &mut rng).is_ok());
+    }
+}
@@ -0,0 +1,257 @@
+//! Simulation-based privacy harness
+//!
+//! A single worker below the sharing threshold should learn nothing about the
+//! witness: its view of a delegation must be simulatable from public data
+//! alone. This module records one worker's full view of the MPC phase of a
+//! delegation — its share of every witness element, the trace shares it
+//! computes and sends back, and every reveal the leakage monitor records
+//! during the run — produces simulated views from the constraint matrices,
+//! the number of public inputs and the witness length, and compares the two
+//! empirically with a two-sample chi-square test over value buckets.
+//!
+//! Commitments and proofs go to the verifier and are not part of a worker's
+//! view here.
+
+use ark_ec::pairing::Pairing;
+use ark_ff::PrimeField;
+use ark_std::rand::Rng;
+
+use crate::mpc::{OperationMode, ShamirSecretSharing};
+use crate::protocol::{ConstraintMatrices, EOSError, EOSProtocol};
+use crate::serialization::FieldEncoding;
+
+/// Everything one worker observes during a delegation
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkerView<F: PrimeField> {
+    pub worker: usize,
+    /// Shares received in the input round, one per witness element
+    pub received: Vec<F>,
+    /// Trace shares sent to the delegator, column by column, for every
+    /// cell that touches a shared witness
+    pub sent: Vec<F>,
+    /// Reveals the leakage monitor recorded during the run, as
+    /// `(label, count)`: the worker learns that they happen, not their values
+    pub reveals: Vec<(String, usize)>,
+}
+
+/// Outcome of comparing real and simulated views
+#[derive(Debug, Clone, PartialEq)]
+pub struct ViewComparison {
+    pub statistic: f64,
+    pub degrees_of_freedom: usize,
+    pub critical_value: f64,
+    /// Whether every view performed the same reveals
+    pub reveals_match: bool,
+}
+
+impl ViewComparison {
+    /// Whether the test fails to distinguish the two distributions
+    pub fn is_indistinguishable(&self) -> bool {
+        self.reveals_match && self.statistic <= self.critical_value
+    }
+}
+
+impl<E, F, OM> EOSProtocol<E, F, ShamirSecretSharing<F>, OM>
+where
+    E: Pairing,
+    F: PrimeField,
+    OM: OperationMode<F, ShamirSecretSharing<F>>,
+{
+    /// Run the MPC phase of a delegation and record what `worker` sees
+    ///
+    /// The witness is shared and the preprocessed constraints are executed
+    /// on it exactly as `delegate_computation` does, trace reconstruction
+    /// included, so the recorded reveals are the ones a real run performs.
+    pub fn record_worker_view(
+        &mut self,
+        witness: &[F],
+        public_inputs: &[F],
+        worker: usize,
+        rng: &mut impl Rng,
+    ) -> Result<WorkerView<F>, EOSError> {
+        let shares = self.share_witness(witness, rng);
+        let matrices = &self.preprocessing_state.as_ref().ok_or(EOSError::PreprocessingNotDone)?.circuit_params.constraint_matrices;
+        let executor = &mut self.circuit_executor;
+        let already_recorded = executor.leakage.records().len();
+        let trace = executor.execute_r1cs(matrices, public_inputs, &shares).map_err(EOSError::MPCError)?;
+        trace.reconstruct(executor).map_err(EOSError::MPCError)?;
+
+        let received = shares.iter().map(|shares| shares[worker].value).collect();
+        let sent = trace
+            .columns
+            .iter()
+            .flatten()
+            .filter_map(|cell| cell.shares.as_ref().map(|shares| shares[worker].value))
+            .collect();
+        let reveals = executor.leakage.records()[already_recorded..]
+            .iter()
+            .map(|record| (record.label.clone(), record.count))
+            .collect();
+        Ok(WorkerView { worker, received, sent, reveals })
+    }
+}
+
+/// Simulate a worker's view from public data only
+///
+/// Input shares are uniform; the trace shares follow from them through the
+/// witness columns of each row, the public columns being added back by the
+/// delegator alone. The trace is revealed once, to the delegator.
+pub fn simulate_worker_view<F: PrimeField>(
+    worker: usize,
+    matrices: &ConstraintMatrices<F>,
+    num_public_inputs: usize,
+    witness_len: usize,
+    rng: &mut impl Rng,
+) -> WorkerView<F> {
+    let received: Vec<F> = (0..witness_len).map(|_| F::rand(rng)).collect();
+    let num_instance = num_public_inputs + 1;
+    let rows = [&matrices.a_matrix, &matrices.b_matrix, &matrices.c_matrix];
+    let sent = rows
+        .iter()
+        .flat_map(|column| column.iter())
+        .filter(|row| row.iter().any(|&(column, _)| column >= num_instance))
+        .map(|row| {
+            row.iter()
+                .filter(|&&(column, _)| column >= num_instance)
+                .map(|&(column, coefficient)| coefficient * received[column - num_instance])
+                .sum()
+        })
+        .collect();
+    let cells: usize = rows.iter().map(|column| column.len()).sum();
+    let reveals = if cells > 0 { vec![("execution_trace".to_string(), cells)] } else { vec![] };
+    WorkerView { worker, received, sent, reveals }
+}
+
+/// Two-sample chi-square test between real and simulated views
+///
+/// Received and sent field elements are bucketed by their low bits; the
+/// critical value is the Wilson–Hilferty approximation at significance level
+/// 0.001. Views whose reveals differ are distinguishable outright.
+pub fn compare_views<F: PrimeField>(
+    real: &[WorkerView<F>],
+    simulated: &[WorkerView<F>],
+    buckets: usize,
+) -> ViewComparison {
+    assert!(buckets >= 2, "need at least two buckets");
+    let histogram = |views: &[WorkerView<F>]| {
+        let mut counts = vec![0f64; buckets];
+        for value in views.iter().flat_map(|view| view.received.iter().chain(&view.sent)) {
+            let low = FieldEncoding::wire::<F>().encode(value)[..8]
+                .iter()
+                .rev()
+                .fold(0u64, |acc, byte| (acc << 8) | *byte as u64);
+            counts[(low % buckets as u64) as usize] += 1.0;
+        }
+        counts
+    };
+
+    let real_counts = histogram(real);
+    let simulated_counts = histogram(simulated);
+    let real_total: f64 = real_counts.iter().sum();
+    let simulated_total: f64 = simulated_counts.iter().sum();
+    let (k1, k2) = ((simulated_total / real_total).sqrt(), (real_total / simulated_total).sqrt());
+
+    let statistic = real_counts
+        .iter()
+        .zip(&simulated_counts)
+        .filter(|(r, s)| *r + *s > 0.0)
+        .map(|(r, s)| (k1 * r - k2 * s).powi(2) / (r + s))
+        .sum();
+
+    let degrees_of_freedom = buckets - 1;
+    let k = degrees_of_freedom as f64;
+    let z = 3.09; // upper 0.001 quantile of the standard normal
+    let critical_value = k * (1.0 - 2.0 / (9.0 * k) + z * (2.0 / (9.0 * k)).sqrt()).powi(3);
+
+    let expected_reveals = real.first().map(|view| &view.reveals);
+    let reveals_match = real.iter().chain(simulated).all(|view| Some(&view.reveals) == expected_reveals);
+
+    ViewComparison {
+        statistic,
+        degrees_of_freedom,
+        critical_value,
+        reveals_match,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mpc::{CollaborationMode, ExecCircuit};
+    use ark_bls12_381::{Bls12_381, Fr};
+    use ark_relations::{lc, r1cs::{ConstraintSystem, Variable}};
+    use ark_std::test_rng;
+
+    type Protocol = EOSProtocol<Bls12_381, Fr, ShamirSecretSharing<Fr>, CollaborationMode>;
+
+    const WITNESS_LEN: usize = 16;
+
+    /// (w_i + 2·w_{i+1})·1 = w_{i+1} + y along a chain of witnesses
+    fn chain_circuit() -> ConstraintSystem<Fr> {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let y = cs.new_input_variable(|| Ok(Fr::from(3u64))).unwrap();
+        let w: Vec<_> = (0..WITNESS_LEN).map(|_| cs.new_witness_variable(|| Ok(Fr::from(5u64))).unwrap()).collect();
+        for pair in w.windows(2) {
+            let a = lc!() + pair[0] + (Fr::from(2u64), pair[1]);
+            cs.enforce_constraint(a, lc!() + Variable::One, lc!() + pair[1] + y).unwrap();
+        }
+        cs.into_inner().unwrap()
+    }
+
+    fn compare_for_threshold(threshold: usize) -> ViewComparison {
+        let mut rng = test_rng();
+        let executor = ExecCircuit::new(1, 3, ShamirSecretSharing::new());
+        let mut protocol = Protocol::new(executor, CollaborationMode::new(2, true, false), 128, &mut rng);
+        protocol.params.threshold = threshold;
+        protocol.preprocessing_state = Some(Protocol::preprocessing(&chain_circuit(), 128, &mut rng).unwrap());
+        let matrices = protocol.constraint_matrices().unwrap().clone();
+
+        // Fixed witness chosen by the adversary
+        let witness = vec![Fr::from(5u64); WITNESS_LEN];
+        let public_inputs = [Fr::from(3u64)];
+        let real: Vec<_> = (0..200)
+            .map(|_| protocol.record_worker_view(&witness, &public_inputs, 0, &mut rng).unwrap())
+            .collect();
+        let simulated: Vec<_> = (0..200)
+            .map(|_| simulate_worker_view(0, &matrices, public_inputs.len(), witness.len(), &mut rng))
+            .collect();
+        assert_eq!(real[0].sent.len(), simulated[0].sent.len());
+        compare_views(&real, &simulated, 16)
+    }
+
+    #[test]
+    fn test_single_worker_view_is_simulatable() {
+        let comparison = compare_for_threshold(2);
+        assert!(comparison.is_indistinguishable(), "{:?}", comparison);
+    }
+
+    #[test]
+    fn test_harness_detects_leaky_sharing() {
+        // With threshold 1 every share equals the secret
+        let comparison = compare_for_threshold(1);
+        assert!(comparison.reveals_match);
+        assert!(!comparison.is_indistinguishable(), "{:?}", comparison);
+    }
+
+    #[test]
+    fn test_recorded_view_includes_trace_and_reveals() {
+        let mut rng = test_rng();
+        let executor = ExecCircuit::new(1, 3, ShamirSecretSharing::new());
+        let mut protocol = Protocol::new(executor, CollaborationMode::new(2, true, false), 128, &mut rng);
+        protocol.preprocessing_state = Some(Protocol::preprocessing(&chain_circuit(), 128, &mut rng).unwrap());
+        let witness = vec![Fr::from(5u64); WITNESS_LEN];
+        let view = protocol.record_worker_view(&witness, &[Fr::from(3u64)], 1, &mut rng).unwrap();
+
+        // The b column is the constant one, so only a and c cells are shared
+        let rows = WITNESS_LEN - 1;
+        assert_eq!(view.received.len(), WITNESS_LEN);
+        assert_eq!(view.sent.len(), 2 * rows);
+        assert_eq!(view.sent[0], view.received[0] + Fr::from(2u64) * view.received[1]);
+        assert_eq!(view.reveals, [("execution_trace".to_string(), 3 * rows)]);
+
+        // A simulator that forgets the trace reveal is caught
+        let mut simulated = simulate_worker_view(1, protocol.constraint_matrices().unwrap(), 1, WITNESS_LEN, &mut rng);
+        simulated.reveals.clear();
+        assert!(!compare_views(&[view], &[simulated], 4).reveals_match);
+    }
+}
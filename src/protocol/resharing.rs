@@ -0,0 +1,155 @@
+//! Fresh witness sharings for retried delegations
+//!
+//! When a delegation has to be retried, the witness must be shared again
+//! with new randomness: resending an earlier sharing to a changed committee,
+//! or replaying a seeded delegation, hands the same share vectors out twice.
+//! `WitnessResharing` derives the sharing randomness of every attempt from a
+//! per-session secret and the attempt number, and refuses any sharing whose
+//! share vectors were already emitted by an earlier attempt.
+
+use std::collections::HashSet;
+
+use ark_ec::pairing::Pairing;
+use ark_ff::PrimeField;
+use ark_relations::r1cs::ConstraintSystem;
+use ark_std::rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use sha2::{Digest, Sha256};
+
+use crate::mpc::{OperationMode, SecretSharing, SessionId};
+use crate::protocol::{DelegationResult, EOSError, EOSProtocol};
+use crate::serialization::WireFormat;
+
+const RESHARING_DOMAIN: &[u8] = b"EOS-witness-resharing-v1";
+
+/// Sharing randomness and emitted sharings of one session's attempts
+pub struct WitnessResharing {
+    session: SessionId,
+    secret: [u8; 32],
+    next_attempt: u32,
+    emitted: HashSet<[u8; 32]>,
+}
+
+impl WitnessResharing {
+    pub fn new<R: Rng + ?Sized>(session: SessionId, rng: &mut R) -> Self {
+        let mut secret = [0u8; 32];
+        rng.fill_bytes(&mut secret);
+        Self { session, secret, next_attempt: 0, emitted: HashSet::new() }
+    }
+
+    /// Number of the next attempt
+    pub fn next_attempt(&self) -> u32 {
+        self.next_attempt
+    }
+
+    /// Randomness of the next attempt; each attempt number is used once
+    pub fn attempt_rng(&mut self) -> (u32, ChaCha20Rng) {
+        let attempt = self.next_attempt;
+        self.next_attempt += 1;
+        let seed = Sha256::new()
+            .chain_update(RESHARING_DOMAIN)
+            .chain_update(self.secret)
+            .chain_update(self.session.to_le_bytes())
+            .chain_update(attempt.to_le_bytes())
+            .finalize();
+        (attempt, ChaCha20Rng::from_seed(seed.into()))
+    }
+
+    /// Accept the share vectors of `attempt`, one per witness element,
+    /// unless an earlier attempt already emitted one of them
+    pub fn admit<S: WireFormat>(&mut self, attempt: u32, shares: &[Vec<S>]) -> Result<(), EOSError> {
+        let digests: Vec<[u8; 32]> = shares
+            .iter()
+            .map(|vector| {
+                let mut hasher = Sha256::new().chain_update(RESHARING_DOMAIN);
+                for share in vector {
+                    hasher.update(share.to_bytes());
+                }
+                hasher.finalize().into()
+            })
+            .collect();
+        if digests.iter().any(|digest| self.emitted.contains(digest)) {
+            return Err(EOSError::SharingReused(attempt));
+        }
+        self.emitted.extend(digests);
+        Ok(())
+    }
+}
+
+impl<E, F, SS, OM> EOSProtocol<E, F, SS, OM>
+where
+    E: Pairing<ScalarField = F>,
+    F: PrimeField,
+    SS: SecretSharing<F>,
+    SS::Share: WireFormat,
+    OM: OperationMode<F, SS>,
+{
+    /// Run one attempt of a delegation with a fresh witness sharing
+    ///
+    /// Returns the attempt number with the result; the same `resharing`
+    /// must be passed to every retry of the session.
+    pub fn delegate_attempt(
+        &mut self,
+        circuit: &ConstraintSystem<F>,
+        witness: &[F],
+        public_inputs: &[F],
+        resharing: &mut WitnessResharing,
+    ) -> Result<(u32, DelegationResult<E, F>), EOSError> {
+        self.preprocessing_state
+            .as_ref()
+            .ok_or(EOSError::PreprocessingNotDone)?;
+        let (attempt, mut rng) = resharing.attempt_rng();
+        let witness_shares = self.share_witness(witness, &mut rng);
+        resharing.admit(attempt, &witness_shares)?;
+        let _mpc_result = self.execute_circuit_mpc(circuit, &witness_shares, public_inputs)?;
+        Ok((attempt, self.prove_delegation(witness, public_inputs, &[])?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mpc::{CollaborationMode, ExecCircuit, ShamirSecretSharing};
+    use ark_bls12_381::{Bls12_381, Fr};
+    use ark_std::test_rng;
+
+    type TestField = Fr;
+    type Protocol = EOSProtocol<Bls12_381, TestField, ShamirSecretSharing<TestField>, CollaborationMode>;
+
+    #[test]
+    fn test_retries_never_resend_a_sharing() {
+        let mut rng = test_rng();
+        let witness: Vec<TestField> = (1..=4u64).map(TestField::from).collect();
+        let mut resharing = WitnessResharing::new(3, &mut rng);
+
+        // Every attempt shares the same witness into different vectors
+        let mut seen: Vec<Vec<Vec<u8>>> = Vec::new();
+        let mut last_shares = Vec::new();
+        for expected in 0..3 {
+            let (attempt, mut attempt_rng) = resharing.attempt_rng();
+            assert_eq!(attempt, expected);
+            let shares: Vec<_> = witness
+                .iter()
+                .map(|&w| ShamirSecretSharing::share_secret(w, 2, 4, &mut attempt_rng))
+                .collect();
+            for (vector, value) in shares.iter().zip(&witness) {
+                assert_eq!(ShamirSecretSharing::reconstruct_secret(vector).unwrap(), *value);
+                let encoded: Vec<Vec<u8>> = vector.iter().map(WireFormat::to_bytes).collect();
+                assert!(!seen.contains(&encoded));
+                seen.push(encoded);
+            }
+            resharing.admit(attempt, &shares).unwrap();
+            last_shares = shares;
+        }
+        // Resending any earlier vector is refused
+        assert!(matches!(resharing.admit(3, &last_shares[1..2]), Err(EOSError::SharingReused(3))));
+
+        let executor = ExecCircuit::new(1, 3, ShamirSecretSharing::new());
+        let mut protocol = Protocol::new(executor, CollaborationMode::new(2, true, false), 128, &mut rng);
+        let circuit = ConstraintSystem::new();
+        protocol.preprocessing_state = Some(Protocol::preprocessing(&circuit, 128, &mut rng).unwrap());
+        let (attempt, result) = protocol.delegate_attempt(&circuit, &witness, &[], &mut resharing).unwrap();
+        assert_eq!(attempt, 3);
+        assert!(result.piop_proof.is_some());
+    }
+}
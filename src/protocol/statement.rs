@@ -0,0 +1,112 @@
+//! Public statement a delegation proof is bound to
+//!
+//! The transcript seeding, key hash and circuit fingerprint are everything a
+//! verifier needs from the delegation protocol, so they live here and are
+//! compiled in every build, including `verifier-only`.
+
+use ark_ec::pairing::Pairing;
+use ark_ff::Field;
+
+use crate::circuit::KZGCommitmentScheme;
+use crate::piop::{Transcript, TranscriptHash};
+
+/// Domain separator for the delegation transcript
+pub const DELEGATION_TRANSCRIPT_DOMAIN: &[u8] = b"EOS-delegation-v1";
+
+/// Start the delegation transcript for one delegator
+///
+/// `key_hash` (see `verification_key_hash`) ties the proof to one key and
+/// circuit. `context` separates delegators that share a worker session; a
+/// standalone delegation uses an empty context.
+pub fn delegation_transcript<F: Field>(key_hash: &[u8; 32], context: &[u8], public_inputs: &[F]) -> Transcript {
+    delegation_transcript_with(TranscriptHash::Sha256, key_hash, context, public_inputs)
+}
+
+/// Start the delegation transcript with the deployment's hash function
+pub fn delegation_transcript_with<F: Field>(
+    hash: TranscriptHash,
+    key_hash: &[u8; 32],
+    context: &[u8],
+    public_inputs: &[F],
+) -> Transcript {
+    let mut transcript = Transcript::with_hash(DELEGATION_TRANSCRIPT_DOMAIN, hash);
+    transcript.append_message(b"verification_key", key_hash);
+    transcript.append_message(b"context", context);
+    transcript.append_fields(b"public_inputs", public_inputs);
+    transcript
+}
+
+/// Digest of the circuit shape fixed during preprocessing
+pub fn circuit_fingerprint<F: Field>(params: &CircuitParameters<F>) -> [u8; 32] {
+    let mut transcript = Transcript::new(b"EOS-circuit-fingerprint-v1");
+    for count in [params.num_constraints, params.num_variables, params.num_public_inputs] {
+        transcript.append_message(b"count", &(count as u64).to_le_bytes());
+    }
+    let matrices = &params.constraint_matrices;
+    for (label, matrix) in [(b"a", &matrices.a_matrix), (b"b", &matrices.b_matrix), (b"c", &matrices.c_matrix)] {
+        transcript.append_message(label, &(matrix.len() as u64).to_le_bytes());
+        for row in matrix {
+            transcript.append_message(b"row", &(row.len() as u64).to_le_bytes());
+            for (column, coefficient) in row {
+                transcript.append_message(b"column", &(*column as u64).to_le_bytes());
+                transcript.append_fields(b"coefficient", &[*coefficient]);
+            }
+        }
+    }
+    transcript.challenge_bytes(b"fingerprint")
+}
+
+/// Hash of the commitment verification key and circuit fingerprint
+///
+/// Bound into every delegation transcript, so a proof only verifies against
+/// the key and circuit it was produced for.
+pub fn verification_key_hash<E: Pairing>(
+    scheme: &KZGCommitmentScheme<E>,
+    circuit_fingerprint: &[u8; 32],
+) -> [u8; 32] {
+    let mut transcript = Transcript::new(b"EOS-verification-key-v1");
+    transcript.append_point::<E::G1>(b"g", &scheme.powers_of_g[0]);
+    transcript.append_point::<E::G2>(b"h", &scheme.verification_key.0);
+    transcript.append_point::<E::G2>(b"h_tau", &scheme.verification_key.1);
+    transcript.append_message(b"circuit", circuit_fingerprint);
+    transcript.challenge_bytes(b"key_hash")
+}
+
+/// Domain separator for deriving prover randomness from a seed
+pub const PROVER_SEED_DOMAIN: &[u8] = b"EOS-prover-seed-v1";
+
+/// Commitment to a prover seed, as bound into a seeded delegation transcript
+pub fn commit_prover_seed(seed: &[u8; 32]) -> [u8; 32] {
+    let mut transcript = Transcript::new(PROVER_SEED_DOMAIN);
+    transcript.append_message(b"seed", seed);
+    transcript.challenge_bytes(b"commitment")
+}
+
+/// Transcript context of a delegation seeded with the committed seed
+pub fn seed_context(seed_commitment: &[u8; 32]) -> Vec<u8> {
+    let mut context = b"seed".to_vec();
+    context.extend_from_slice(seed_commitment);
+    context
+}
+
+/// Circuit parameters from preprocessing
+#[derive(Debug, Clone, PartialEq)]
+pub struct CircuitParameters<F: Field> {
+    /// Number of constraints
+    pub num_constraints: usize,
+    /// Number of variables, including the constant one
+    pub num_variables: usize,
+    /// Number of public inputs, excluding the constant one
+    pub num_public_inputs: usize,
+    /// Constraint matrices (A, B, C) in compressed form; columns follow
+    /// `custom_circuits::Variable::index`
+    pub constraint_matrices: ConstraintMatrices<F>,
+}
+
+/// Constraint matrices for R1CS
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConstraintMatrices<F: Field> {
+    pub a_matrix: Vec<Vec<(usize, F)>>, // Sparse representation
+    pub b_matrix: Vec<Vec<(usize, F)>>,
+    pub c_matrix: Vec<Vec<(usize, F)>>,
+}
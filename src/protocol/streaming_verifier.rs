@@ -0,0 +1,389 @@
+//! Streaming verification of PIOP proofs
+//!
+//! `StreamingVerifier` reads a serialized `PolynomialConsistencyProof` from any
+//! `std::io::Read` one component at a time: commitments, then openings, then
+//! sumcheck proofs and the constraint claims. Commitments and openings are
+//! folded into running accumulators with verifier-chosen batching weights, so
+//! memory use does not grow with the number of components; the constraint
+//! proof is two sumchecks and a logarithmic number of openings, each capped
+//! by `StreamingLimits`. The verifier is built with the circuit's matrices,
+//! so an accepted proof always shows the committed witness satisfies them.
+
+use std::io::Read;
+
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
+use ark_ff::{Field, PrimeField, UniformRand, Zero};
+use ark_poly::{DenseUVPolynomial, univariate::DensePolynomial};
+use ark_std::rand::Rng;
+
+use crate::circuit::{
+    AggregatedOpeningProof, KZGCommitmentScheme, MultilinearOpeningProof, OpeningProof, PolynomialCommitment,
+};
+use crate::piop::{
+    ConstraintClaims, MaskOpening, PolynomialConsistencyProof, SumcheckMask, SumcheckProof, TranscriptHash,
+};
+use crate::protocol::{delegation_transcript_with, verify_constraints, ConstraintMatrices};
+use crate::serialization::{field_size, point_size, ByteReader, DecodeError, WireFormat};
+
+/// Upper bounds on the size of a single streamed component
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StreamingLimits {
+    /// Maximum number of rounds in one sumcheck proof
+    pub max_sumcheck_rounds: usize,
+    /// Maximum number of coefficients in one round polynomial
+    pub max_round_coefficients: usize,
+}
+
+impl Default for StreamingLimits {
+    fn default() -> Self {
+        Self {
+            max_sumcheck_rounds: 64,
+            max_round_coefficients: 16,
+        }
+    }
+}
+
+/// Verifier that checks a proof while reading it
+pub struct StreamingVerifier<'a, E: Pairing> {
+    scheme: &'a KZGCommitmentScheme<E>,
+    key_hash: [u8; 32],
+    limits: StreamingLimits,
+    context: Vec<u8>,
+    transcript_hash: TranscriptHash,
+    audit: bool,
+    matrices: &'a ConstraintMatrices<E::ScalarField>,
+}
+
+impl<'a, E: Pairing> StreamingVerifier<'a, E> {
+    /// Verifier for proofs bound to `key_hash` (see `verification_key_hash`)
+    /// that the committed witness satisfies `matrices`
+    pub fn new(
+        scheme: &'a KZGCommitmentScheme<E>,
+        key_hash: [u8; 32],
+        matrices: &'a ConstraintMatrices<E::ScalarField>,
+    ) -> Self {
+        Self {
+            scheme,
+            key_hash,
+            limits: StreamingLimits::default(),
+            context: Vec::new(),
+            transcript_hash: TranscriptHash::default(),
+            audit: false,
+            matrices,
+        }
+    }
+
+    pub fn with_limits(mut self, limits: StreamingLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Verify proofs bound to a delegator context (see `delegator_context`)
+    pub fn with_context(mut self, context: &[u8]) -> Self {
+        self.context = context.to_vec();
+        self
+    }
+
+    /// Verify proofs whose transcript uses `hash` (see `EOSParams::transcript_hash`)
+    pub fn with_transcript_hash(mut self, hash: TranscriptHash) -> Self {
+        self.transcript_hash = hash;
+        self
+    }
+
+    /// Reject proofs whose carried sumcheck challenges are not the ones the
+    /// transcript derives (see `EOSParams::audit`)
+    pub fn with_audit(mut self, audit: bool) -> Self {
+        self.audit = audit;
+        self
+    }
+
+    /// Verify a proof read from `reader`
+    ///
+    /// Returns `Ok(false)` when the proof is well-formed but does not verify,
+    /// and an error when the stream is malformed, truncated or exceeds the
+    /// configured limits. `rng` supplies the batching weights and must not be
+    /// predictable by the prover.
+    pub fn verify<R: Read>(
+        &self,
+        reader: R,
+        public_inputs: &[E::ScalarField],
+        rng: &mut impl Rng,
+    ) -> Result<bool, StreamingError> {
+        let mut stream = ProofStream::new(reader);
+        let mut transcript = delegation_transcript_with(self.transcript_hash, &self.key_hash, &self.context, public_inputs);
+
+        // 1. Commitments: absorb into the transcript and fold with weights r^i
+        let batching_weight = E::ScalarField::rand(rng);
+        let num_commitments = stream.read_count()?;
+        let mut commitment_acc = E::G1::zero();
+        let mut weight = E::ScalarField::from(1u64);
+        let mut witness_commitments = Vec::new();
+        for _ in 0..num_commitments {
+            let commitment: PolynomialCommitment<E::G1> = stream.read_fixed(point_size::<E::G1>())?;
+            transcript.append_point::<E::G1>(b"witness_commitment", &commitment.commitment);
+            commitment_acc += commitment.commitment.into_group() * weight;
+            weight *= batching_weight;
+            // The constraint proof is against a single commitment; keep no more
+            if witness_commitments.len() < 2 {
+                witness_commitments.push(commitment);
+            }
+        }
+        let point: E::ScalarField = transcript.challenge_field(b"opening_point");
+
+        // 2. Openings: one per commitment, all at the transcript point
+        let num_openings = stream.read_count()?;
+        if num_openings != num_commitments {
+            return Ok(false);
+        }
+        let opening_size = point_size::<E::G1>() + 2 * field_size::<E::ScalarField>();
+        let mut proof_acc = E::G1::zero();
+        let mut evaluation_acc = E::ScalarField::zero();
+        let mut weight = E::ScalarField::from(1u64);
+        for _ in 0..num_openings {
+            let opening: OpeningProof<E::ScalarField, E::G1> = stream.read_fixed(opening_size)?;
+            if opening.point != point {
+                return Ok(false);
+            }
+            proof_acc += opening.proof.into_group() * weight;
+            evaluation_acc += weight * opening.evaluation;
+            weight *= batching_weight;
+        }
+        if num_openings > 0 {
+            // KZG is linear in the commitment, so openings at a shared point batch
+            let commitment = PolynomialCommitment { commitment: commitment_acc.into_affine() };
+            let opening = OpeningProof {
+                proof: proof_acc.into_affine(),
+                evaluation: evaluation_acc,
+                point,
+            };
+            if !self.scheme.verify(&commitment, &opening) {
+                return Ok(false);
+            }
+        }
+
+        // 3. The constraint proof: two sumchecks and the claims they end in
+        let num_sumchecks = stream.read_bounded_count(2)?;
+        let sumcheck_proofs = (0..num_sumchecks)
+            .map(|_| stream.read_sumcheck(&self.limits))
+            .collect::<Result<Vec<SumcheckProof<E::ScalarField>>, _>>()?;
+        let constraint_claims = match stream.read_bounded_count(1)? {
+            0 => None,
+            _ => Some(stream.read_claims(&self.limits)?),
+        };
+        let proof = PolynomialConsistencyProof {
+            witness_commitments,
+            consistency_proofs: Vec::new(),
+            sumcheck_proofs,
+            constraint_claims,
+        };
+        if verify_constraints(self.scheme, self.matrices, public_inputs, &proof, &mut transcript, self.audit).is_err() {
+            return Ok(false);
+        }
+
+        stream.finish()?;
+        Ok(true)
+    }
+}
+
+/// Sequential reader over a proof stream
+struct ProofStream<R: Read> {
+    reader: R,
+    buffer: Vec<u8>,
+}
+
+impl<R: Read> ProofStream<R> {
+    fn new(reader: R) -> Self {
+        Self { reader, buffer: Vec::new() }
+    }
+
+    fn fill(&mut self, len: usize) -> Result<&[u8], StreamingError> {
+        self.buffer.resize(len, 0);
+        self.reader.read_exact(&mut self.buffer).map_err(|e| match e.kind() {
+            std::io::ErrorKind::UnexpectedEof => StreamingError::Decode(DecodeError::UnexpectedEnd),
+            _ => StreamingError::Io(e),
+        })?;
+        Ok(&self.buffer)
+    }
+
+    fn read_count(&mut self) -> Result<usize, StreamingError> {
+        let bytes = self.fill(4)?;
+        Ok(u32::from_le_bytes(bytes.try_into().expect("slice has length 4")) as usize)
+    }
+
+    fn read_bounded_count(&mut self, limit: usize) -> Result<usize, StreamingError> {
+        let count = self.read_count()?;
+        if count > limit {
+            return Err(StreamingError::LimitExceeded { count, limit });
+        }
+        Ok(count)
+    }
+
+    /// Read a component whose encoding has a known fixed size
+    fn read_fixed<T: WireFormat>(&mut self, len: usize) -> Result<T, StreamingError> {
+        Ok(T::from_bytes(self.fill(len)?)?)
+    }
+
+    fn read_field<F: Field>(&mut self) -> Result<F, StreamingError> {
+        let len = field_size::<F>();
+        Ok(ByteReader::new(self.fill(len)?).read_field()?)
+    }
+
+    fn read_sumcheck<F: PrimeField>(
+        &mut self,
+        limits: &StreamingLimits,
+    ) -> Result<SumcheckProof<F>, StreamingError> {
+        let num_rounds = self.read_bounded_count(limits.max_sumcheck_rounds)?;
+        let mut round_polynomials = Vec::with_capacity(num_rounds);
+        for _ in 0..num_rounds {
+            let num_coeffs = self.read_bounded_count(limits.max_round_coefficients)?;
+            let coeffs = (0..num_coeffs)
+                .map(|_| self.read_field())
+                .collect::<Result<Vec<F>, _>>()?;
+            round_polynomials.push(DensePolynomial::from_coefficients_vec(coeffs));
+        }
+
+        let num_challenges = self.read_bounded_count(limits.max_sumcheck_rounds)?;
+        let challenges = (0..num_challenges)
+            .map(|_| self.read_field())
+            .collect::<Result<Vec<F>, _>>()?;
+
+        Ok(SumcheckProof {
+            round_polynomials,
+            challenges,
+            final_evaluation: self.read_field()?,
+        })
+    }
+
+    /// Read `ConstraintClaims`, bounding every list by `limits`
+    fn read_claims<F: PrimeField, G: CurveGroup>(
+        &mut self,
+        limits: &StreamingLimits,
+    ) -> Result<ConstraintClaims<F, G>, StreamingError> {
+        let row_evaluations = [self.read_field()?, self.read_field()?, self.read_field()?];
+        let witness_opening = self.read_multilinear_opening(limits)?;
+        // One mask per sumcheck in zk mode
+        let num_masks = self.read_bounded_count(2)?;
+        let mut masks = Vec::with_capacity(num_masks);
+        for _ in 0..num_masks {
+            let commitment = self.read_fixed(point_size::<G>())?;
+            let mask = SumcheckMask { commitment, sum: self.read_field()? };
+            masks.push(MaskOpening { mask, opening: self.read_multilinear_opening(limits)? });
+        }
+        Ok(ConstraintClaims { row_evaluations, witness_opening, masks })
+    }
+
+    /// Read a `MultilinearOpeningProof`, bounding every list by `limits`
+    fn read_multilinear_opening<F: PrimeField, G: CurveGroup>(
+        &mut self,
+        limits: &StreamingLimits,
+    ) -> Result<MultilinearOpeningProof<F, G>, StreamingError> {
+        let num_folds = self.read_bounded_count(limits.max_sumcheck_rounds)?;
+        let folds = (0..num_folds)
+            .map(|_| self.read_fixed(point_size::<G>()))
+            .collect::<Result<Vec<PolynomialCommitment<G>>, _>>()?;
+        // Openings at β, −β and β²
+        let num_openings = self.read_bounded_count(3)?;
+        let mut openings = Vec::with_capacity(num_openings);
+        for _ in 0..num_openings {
+            let proof = ByteReader::new(self.fill(point_size::<G>())?).read_point::<G>()?;
+            let num_evaluations = self.read_bounded_count(limits.max_sumcheck_rounds + 1)?;
+            let evaluations = (0..num_evaluations).map(|_| self.read_field()).collect::<Result<Vec<F>, _>>()?;
+            openings.push(AggregatedOpeningProof { proof, evaluations, point: self.read_field()? });
+        }
+        let evaluation = self.read_field()?;
+        let num_vars = self.read_bounded_count(limits.max_sumcheck_rounds)?;
+        let point = (0..num_vars).map(|_| self.read_field()).collect::<Result<Vec<F>, _>>()?;
+        Ok(MultilinearOpeningProof { folds, openings, evaluation, point })
+    }
+
+    /// Ensure nothing follows the proof
+    fn finish(mut self) -> Result<(), StreamingError> {
+        let mut extra = [0u8; 1];
+        match self.reader.read(&mut extra) {
+            Ok(0) => Ok(()),
+            Ok(_) => Err(StreamingError::Decode(DecodeError::TrailingBytes(1))),
+            Err(e) => Err(StreamingError::Io(e)),
+        }
+    }
+}
+
+/// Streaming verification error types
+#[derive(Debug)]
+pub enum StreamingError {
+    Io(std::io::Error),
+    Decode(DecodeError),
+    LimitExceeded { count: usize, limit: usize },
+}
+
+impl From<DecodeError> for StreamingError {
+    fn from(e: DecodeError) -> Self {
+        StreamingError::Decode(e)
+    }
+}
+
+impl std::fmt::Display for StreamingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            StreamingError::Io(e) => write!(f, "I/O error: {}", e),
+            StreamingError::Decode(e) => write!(f, "Decode error: {}", e),
+            StreamingError::LimitExceeded { count, limit } => {
+                write!(f, "Component count {} exceeds limit {}", count, limit)
+            }
+        }
+    }
+}
+
+impl std::error::Error for StreamingError {}
+
+#[cfg(all(test, feature = "delegator"))]
+mod tests {
+    use super::*;
+    use crate::mpc::{CollaborationMode, ExecCircuit, ShamirSecretSharing};
+    use crate::protocol::EOSProtocol;
+    use ark_bls12_381::{Bls12_381, Fr};
+    use ark_relations::r1cs::ConstraintSystem;
+    use ark_std::test_rng;
+
+    type Protocol = EOSProtocol<Bls12_381, Fr, ShamirSecretSharing<Fr>, CollaborationMode>;
+
+    #[test]
+    fn test_streaming_verification() {
+        let mut rng = test_rng();
+        let executor = ExecCircuit::new(1, 3, ShamirSecretSharing::new());
+        let mut protocol = Protocol::new(executor, CollaborationMode::new(2, true, false), 128, &mut rng);
+        let circuit = ConstraintSystem::new();
+        protocol.preprocessing_state = Some(Protocol::preprocessing(&circuit, 128, &mut rng).unwrap());
+
+        let witness: Vec<Fr> = (1..=16u64).map(Fr::from).collect();
+        let public_inputs = [Fr::from(7u64)];
+        let result = protocol
+            .delegate_computation(&circuit, &witness, &public_inputs, &mut rng)
+            .unwrap();
+        let proof = result.piop_proof.unwrap();
+
+        let key_hash = protocol.verification_key_hash().unwrap();
+        let matrices = protocol.constraint_matrices().unwrap();
+        let verifier = StreamingVerifier::<Bls12_381>::new(&protocol.commitment_scheme, key_hash, matrices);
+        assert!(verifier.verify(proof.as_slice(), &public_inputs, &mut rng).unwrap());
+
+        // Different public inputs derive a different opening point
+        assert!(!verifier.verify(proof.as_slice(), &[Fr::from(8u64)], &mut rng).unwrap());
+
+        // Truncated input and trailing bytes
+        assert!(matches!(
+            verifier.verify(&proof[..proof.len() - 1], &public_inputs, &mut rng),
+            Err(StreamingError::Decode(DecodeError::UnexpectedEnd))
+        ));
+        let mut extended = proof.clone();
+        extended.push(0);
+        assert!(verifier.verify(extended.as_slice(), &public_inputs, &mut rng).is_err());
+
+        // Components beyond the configured limits
+        let strict = StreamingVerifier::<Bls12_381>::new(&protocol.commitment_scheme, key_hash, matrices)
+            .with_limits(StreamingLimits { max_sumcheck_rounds: 0, max_round_coefficients: 0 });
+        assert!(matches!(
+            strict.verify(proof.as_slice(), &public_inputs, &mut rng),
+            Err(StreamingError::LimitExceeded { .. })
+        ));
+    }
+}
@@ -0,0 +1,174 @@
+//! Binding the MPC execution trace to the PIOP witness
+//!
+//! The constraint proof (`constraint_proof`, checked by `verify_constraints`)
+//! proves `a∘b = c` about the tables `Az`, `Bz` and `Cz` of the witness the
+//! prover commits to: a zerocheck over the three tables, reduced to a
+//! multilinear opening of the witness commitment. Nothing in that proof ties
+//! those tables to the values the workers computed, so an execution on one
+//! assignment could be followed by a proof about another. This check closes
+//! the gap: both sets of tables are read as multilinear oracles and queried
+//! once each at a point drawn after both are fixed. Distinct tables of
+//! `2^n` entries agree there with probability at most `n / |F|`.
+
+use ark_ff::{Field, PrimeField};
+
+use crate::mpc::ExecutionTrace;
+use crate::piop::{zero_padded, Domain, MultilinearOracle, OracleError, QueriedOracle, Transcript};
+use crate::protocol::ConstraintMatrices;
+
+const COLUMNS: [&str; 3] = ["a", "b", "c"];
+
+/// The PIOP witness tables `Az`, `Bz`, `Cz` for `z = (1, public_inputs, witness)`
+pub fn witness_tables<F: Field>(
+    matrices: &ConstraintMatrices<F>,
+    public_inputs: &[F],
+    witness: &[F],
+) -> Result<ExecutionTrace<F>, TraceBindingError> {
+    let z: Vec<F> = std::iter::once(F::one()).chain(public_inputs.iter().copied()).chain(witness.iter().copied()).collect();
+    let multiply = |rows: &[Vec<(usize, F)>]| {
+        rows.iter()
+            .map(|row| {
+                row.iter()
+                    .map(|&(column, coefficient)| {
+                        z.get(column).map(|value| coefficient * value).ok_or(TraceBindingError::UnassignedVariable(column))
+                    })
+                    .sum()
+            })
+            .collect::<Result<Vec<F>, _>>()
+    };
+    Ok(ExecutionTrace {
+        a: multiply(&matrices.a_matrix)?,
+        b: multiply(&matrices.b_matrix)?,
+        c: multiply(&matrices.c_matrix)?,
+    })
+}
+
+/// Check that the PIOP witness tables are the ones the MPC execution produced
+pub fn check_trace_binding<F: PrimeField>(
+    trace: &ExecutionTrace<F>,
+    witness: &ExecutionTrace<F>,
+    transcript: &mut Transcript,
+) -> Result<(), TraceBindingError> {
+    for ((name, computed), committed) in COLUMNS.iter().zip(trace.columns()).zip(witness.columns()) {
+        if computed.len() != committed.len() {
+            return Err(TraceBindingError::Length { column: name, trace: computed.len(), witness: committed.len() });
+        }
+    }
+    if trace.is_empty() {
+        return Ok(());
+    }
+    let num_vars = Domain::for_len(trace.num_constraints()).num_vars();
+    transcript.append_message(b"trace_binding_constraints", &(trace.num_constraints() as u64).to_le_bytes());
+    let point: Vec<F> = (0..num_vars).map(|_| transcript.challenge_element(b"trace_binding_point")).collect();
+    for ((name, computed), committed) in COLUMNS.iter().zip(trace.columns()).zip(witness.columns()) {
+        let query = |label: String, column: &[F]| {
+            QueriedOracle::with_budget(MultilinearOracle { label, table: zero_padded(column) }, 1).query(&point)
+        };
+        if query(format!("trace_{}", name), computed)? != query(format!("witness_{}", name), committed)? {
+            return Err(TraceBindingError::Mismatch { column: name });
+        }
+    }
+    Ok(())
+}
+
+/// Trace binding error types
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TraceBindingError {
+    /// A constraint reads a variable the assignment does not have
+    UnassignedVariable(usize),
+    Length { column: &'static str, trace: usize, witness: usize },
+    /// The witness table disagrees with the executed trace
+    Mismatch { column: &'static str },
+    Oracle(OracleError),
+}
+
+impl std::fmt::Display for TraceBindingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            TraceBindingError::UnassignedVariable(column) => write!(f, "Constraint reads unassigned variable {}", column),
+            TraceBindingError::Length { column, trace, witness } => {
+                write!(f, "Column {} has {} trace rows but {} witness rows", column, trace, witness)
+            }
+            TraceBindingError::Mismatch { column } => {
+                write!(f, "Witness column {} does not match the MPC execution trace", column)
+            }
+            TraceBindingError::Oracle(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for TraceBindingError {}
+
+impl From<OracleError> for TraceBindingError {
+    fn from(e: OracleError) -> Self {
+        TraceBindingError::Oracle(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mpc::{CollaborationMode, ExecCircuit, ShamirSecretSharing};
+    use crate::protocol::{EOSError, EOSProtocol};
+    use ark_bls12_381::{Bls12_381, Fr};
+    use ark_relations::lc;
+    use ark_relations::r1cs::ConstraintSystem;
+    use ark_std::test_rng;
+
+    type TestField = Fr;
+    type Protocol = EOSProtocol<Bls12_381, TestField, ShamirSecretSharing<TestField>, CollaborationMode>;
+
+    #[test]
+    fn test_proven_witness_must_match_executed_trace() {
+        let mut rng = test_rng();
+        // x·y = t and (t + 1)·1 = out, with out public
+        let cs = ConstraintSystem::<TestField>::new_ref();
+        let out = cs.new_input_variable(|| Ok(TestField::from(16u64))).unwrap();
+        let x = cs.new_witness_variable(|| Ok(TestField::from(3u64))).unwrap();
+        let y = cs.new_witness_variable(|| Ok(TestField::from(5u64))).unwrap();
+        let t = cs.new_witness_variable(|| Ok(TestField::from(15u64))).unwrap();
+        cs.enforce_constraint(lc!() + x, lc!() + y, lc!() + t).unwrap();
+        let one = ark_relations::r1cs::Variable::One;
+        cs.enforce_constraint(lc!() + t + one, lc!() + one, lc!() + out).unwrap();
+        let circuit = cs.into_inner().unwrap();
+
+        let executor = ExecCircuit::new(1, 3, ShamirSecretSharing::new());
+        let mut protocol = Protocol::new(executor, CollaborationMode::new(2, true, false), 128, &mut rng);
+        protocol.preprocessing_state = Some(Protocol::preprocessing(&circuit, 128, &mut rng).unwrap());
+        let witness = [3u64, 5, 15].map(TestField::from);
+        let public_inputs = [TestField::from(16u64)];
+        let result = protocol.delegate_computation(&circuit, &witness, &public_inputs, &mut rng).unwrap();
+        assert!(protocol.verify_computation(&result, &public_inputs).unwrap().is_accepted());
+        assert!(protocol.metrics.timings.contains_key("trace_binding"));
+
+        // The trace is the workers' gate outputs and satisfies every constraint
+        let shares = protocol.share_witness(&witness, &mut rng);
+        let executed = protocol.execute_circuit_mpc(&circuit, &shares, &public_inputs).unwrap();
+        let trace = &executed.computation_trace;
+        assert_eq!(trace.c, [15u64, 16].map(TestField::from));
+        assert!(trace.unsatisfied().is_empty());
+        assert!(protocol.bind_trace(&executed, &witness, &public_inputs, &[]).is_ok());
+
+        // Proving another witness than the one executed is caught
+        let other = [5u64, 3, 15].map(TestField::from);
+        assert!(matches!(
+            protocol.bind_trace(&executed, &other, &public_inputs, &[]),
+            Err(EOSError::TraceMismatch(TraceBindingError::Mismatch { column: "a" }))
+        ));
+
+        // So is a worker computing on a corrupted share
+        let mut corrupted = shares.clone();
+        corrupted[2][0].value += TestField::from(1u64);
+        let executed = protocol.execute_circuit_mpc(&circuit, &corrupted, &public_inputs).unwrap();
+        assert_eq!(executed.computation_trace.unsatisfied(), vec![0, 1]);
+        assert!(matches!(
+            protocol.bind_trace(&executed, &witness, &public_inputs, &[]),
+            Err(EOSError::TraceMismatch(TraceBindingError::Mismatch { column: "a" }))
+        ));
+        let matrices = &protocol.preprocessing_state.as_ref().unwrap().circuit_params.constraint_matrices;
+        assert_eq!(
+            witness_tables(matrices, &public_inputs, &witness[..2]),
+            Err(TraceBindingError::UnassignedVariable(4))
+        );
+    }
+}
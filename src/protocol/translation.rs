@@ -0,0 +1,256 @@
+//! Translating statements between curves
+//!
+//! An application chain may express its statement over one curve's scalar
+//! field (e.g. BN254) while workers prove over another (BLS12-381). Values
+//! are moved between fields as canonical integers: an element that always
+//! fits in the target field is carried as a single element, otherwise it is
+//! split into `STATEMENT_LIMB_BITS`-bit limbs. Hashes are always carried as
+//! two 128-bit limbs. The statement digest is computed over the source-field
+//! encoding, so both chains derive the same value and the proof can be bound
+//! to it through the delegation context.
+
+use ark_ec::pairing::Pairing;
+use ark_ff::{BigInteger, PrimeField};
+use ark_relations::r1cs::ConstraintSystem;
+use ark_std::rand::Rng;
+use num_bigint::BigUint;
+use sha2::{Digest, Sha256};
+
+use crate::mpc::{OperationMode, SecretSharing};
+use crate::protocol::{DelegationResult, EOSError, EOSProtocol};
+use crate::serialization::{Endianness, FieldEncoding};
+
+/// Width of the limbs used when a value does not fit in the target field
+pub const STATEMENT_LIMB_BITS: usize = 128;
+
+const CROSS_CURVE_DOMAIN: &[u8] = b"EOS-cross-curve-statement-v1";
+
+fn to_biguint<F: PrimeField>(value: &F) -> BigUint {
+    BigUint::from_bytes_le(&FieldEncoding::wire::<F>().encode(value))
+}
+
+fn modulus<F: PrimeField>() -> BigUint {
+    BigUint::from_bytes_le(&F::MODULUS.to_bytes_le())
+}
+
+/// Number of target elements used to carry one source element
+pub fn limbs_per_element<Src: PrimeField, Dst: PrimeField>() -> usize {
+    if modulus::<Src>() <= modulus::<Dst>() {
+        1
+    } else {
+        (Src::MODULUS_BIT_SIZE as usize).div_ceil(STATEMENT_LIMB_BITS)
+    }
+}
+
+fn split(value: &BigUint, num_limbs: usize) -> Vec<BigUint> {
+    let mask = (BigUint::from(1u64) << STATEMENT_LIMB_BITS) - 1u64;
+    (0..num_limbs)
+        .map(|i| (value >> (i * STATEMENT_LIMB_BITS)) & &mask)
+        .collect()
+}
+
+/// Carry a source-field element into the target field
+pub fn embed_element<Src: PrimeField, Dst: PrimeField>(value: &Src) -> Vec<Dst> {
+    split_or_keep::<Src, Dst>(&to_biguint(value))
+}
+
+fn split_or_keep<Src: PrimeField, Dst: PrimeField>(value: &BigUint) -> Vec<Dst> {
+    let num_limbs = limbs_per_element::<Src, Dst>();
+    let limbs = if num_limbs == 1 { vec![value.clone()] } else { split(value, num_limbs) };
+    limbs
+        .iter()
+        .map(|limb| Dst::from_le_bytes_mod_order(&limb.to_bytes_le()))
+        .collect()
+}
+
+/// Recover a source-field element, rejecting non-canonical encodings
+pub fn extract_element<Src: PrimeField, Dst: PrimeField>(limbs: &[Dst]) -> Result<Src, TranslationError> {
+    let num_limbs = limbs_per_element::<Src, Dst>();
+    if limbs.len() != num_limbs {
+        return Err(TranslationError::WrongLength { expected: num_limbs, actual: limbs.len() });
+    }
+    let value = if num_limbs == 1 {
+        to_biguint(&limbs[0])
+    } else {
+        let bound = BigUint::from(1u64) << STATEMENT_LIMB_BITS;
+        limbs.iter().rev().try_fold(BigUint::from(0u64), |acc, limb| {
+            let limb = to_biguint(limb);
+            if limb >= bound {
+                return Err(TranslationError::NonCanonical);
+            }
+            Ok((acc << STATEMENT_LIMB_BITS) + limb)
+        })?
+    };
+    if value >= modulus::<Src>() {
+        return Err(TranslationError::NonCanonical);
+    }
+    Ok(Src::from_le_bytes_mod_order(&value.to_bytes_le()))
+}
+
+/// Carry a 32-byte digest as two 128-bit limbs
+pub fn embed_hash<Dst: PrimeField>(digest: &[u8; 32]) -> [Dst; 2] {
+    [
+        FieldEncoding::wire::<Dst>().reduce(&digest[..16]),
+        FieldEncoding::wire::<Dst>().reduce(&digest[16..]),
+    ]
+}
+
+/// Recover a digest from its two limbs
+pub fn extract_hash<Dst: PrimeField>(limbs: &[Dst; 2]) -> Result<[u8; 32], TranslationError> {
+    let mut digest = [0u8; 32];
+    for (chunk, limb) in digest.chunks_mut(16).zip(limbs) {
+        let bytes = to_biguint(limb).to_bytes_le();
+        if bytes.len() > 16 {
+            return Err(TranslationError::NonCanonical);
+        }
+        chunk[..bytes.len()].copy_from_slice(&bytes);
+    }
+    Ok(digest)
+}
+
+/// A public statement of the application chain
+#[derive(Debug, Clone, PartialEq)]
+pub struct CrossCurveStatement<Src: PrimeField> {
+    /// Public inputs in the application chain's field
+    pub inputs: Vec<Src>,
+    /// Application-level hash, e.g. a state root or message hash
+    pub app_hash: [u8; 32],
+}
+
+impl<Src: PrimeField> CrossCurveStatement<Src> {
+    /// Curve-independent digest of the statement
+    pub fn digest(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new()
+            .chain_update(CROSS_CURVE_DOMAIN)
+            .chain_update((Src::MODULUS_BIT_SIZE as u64).to_le_bytes())
+            .chain_update((self.inputs.len() as u64).to_le_bytes());
+        let encoding = FieldEncoding::minimal::<Src>(Endianness::Big);
+        for input in &self.inputs {
+            hasher.update(encoding.encode(input));
+        }
+        hasher.chain_update(self.app_hash).finalize().into()
+    }
+
+    /// Delegation context binding a proof to this statement
+    pub fn context(&self) -> Vec<u8> {
+        let mut context = b"cross-curve".to_vec();
+        context.extend_from_slice(&self.digest());
+        context
+    }
+
+    /// Public inputs for workers proving over `Dst`: the embedded inputs
+    /// followed by the two limbs of the application hash
+    pub fn embed<Dst: PrimeField>(&self) -> Vec<Dst> {
+        let mut embedded: Vec<Dst> = self.inputs.iter().flat_map(embed_element::<Src, Dst>).collect();
+        embedded.extend(embed_hash::<Dst>(&self.app_hash));
+        embedded
+    }
+
+    /// Inverse of `embed`
+    pub fn extract<Dst: PrimeField>(embedded: &[Dst]) -> Result<Self, TranslationError> {
+        let per_element = limbs_per_element::<Src, Dst>();
+        let body_len = embedded
+            .len()
+            .checked_sub(2)
+            .filter(|len| len % per_element == 0)
+            .ok_or(TranslationError::WrongLength { expected: per_element + 2, actual: embedded.len() })?;
+        let inputs = embedded[..body_len]
+            .chunks(per_element)
+            .map(extract_element::<Src, Dst>)
+            .collect::<Result<Vec<_>, _>>()?;
+        let app_hash = extract_hash(&[embedded[body_len], embedded[body_len + 1]])?;
+        Ok(Self { inputs, app_hash })
+    }
+}
+
+impl<E, F, SS, OM> EOSProtocol<E, F, SS, OM>
+where
+    E: Pairing<ScalarField = F>,
+    F: PrimeField,
+    SS: SecretSharing<F>,
+    OM: OperationMode<F, SS>,
+{
+    /// Delegate a computation for a statement of another curve
+    ///
+    /// The proof's public inputs are `statement.embed()` and its transcript
+    /// context is `statement.context()`; both are needed to verify it.
+    pub fn delegate_statement<Src: PrimeField>(
+        &mut self,
+        circuit: &ConstraintSystem<F>,
+        witness: &[F],
+        statement: &CrossCurveStatement<Src>,
+        rng: &mut impl Rng,
+    ) -> Result<DelegationResult<E, F>, EOSError> {
+        self.preprocessing_state
+            .as_ref()
+            .ok_or(EOSError::PreprocessingNotDone)?;
+        let public_inputs = statement.embed::<F>();
+        let witness_shares = self.share_witness(witness, rng);
+        let _mpc_result = self.execute_circuit_mpc(circuit, &witness_shares, &public_inputs)?;
+        self.prove_delegation(witness, &public_inputs, &statement.context())
+    }
+}
+
+/// Statement translation error types
+#[derive(Debug, Clone, PartialEq)]
+pub enum TranslationError {
+    WrongLength { expected: usize, actual: usize },
+    NonCanonical,
+}
+
+impl std::fmt::Display for TranslationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            TranslationError::WrongLength { expected, actual } => {
+                write!(f, "Expected {} embedded elements, got {}", expected, actual)
+            }
+            TranslationError::NonCanonical => write!(f, "Embedded value is not canonical"),
+        }
+    }
+}
+
+impl std::error::Error for TranslationError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::Fr;
+    use ark_bn254::Fr as Bn254Fr;
+    use ark_ff::Field;
+
+    type TestField = Fr;
+
+    #[test]
+    fn test_bn254_statement_roundtrip() {
+        // BN254's scalar field fits in BLS12-381's, so no splitting is needed
+        assert_eq!(limbs_per_element::<Bn254Fr, TestField>(), 1);
+        assert_eq!(limbs_per_element::<TestField, Bn254Fr>(), 2);
+
+        let statement = CrossCurveStatement {
+            inputs: vec![Bn254Fr::from(0u64) - Bn254Fr::ONE, Bn254Fr::from(12345u64)],
+            app_hash: [0xab; 32],
+        };
+        let embedded = statement.embed::<TestField>();
+        assert_eq!(embedded.len(), 4);
+        assert_eq!(CrossCurveStatement::<Bn254Fr>::extract(&embedded).unwrap(), statement);
+
+        // The other direction splits into limbs; the largest value survives
+        let reverse = CrossCurveStatement { inputs: vec![TestField::from(0u64) - TestField::ONE], app_hash: [1; 32] };
+        let limbs = reverse.embed::<Bn254Fr>();
+        assert_eq!(limbs.len(), 4);
+        assert_eq!(CrossCurveStatement::<TestField>::extract(&limbs).unwrap(), reverse);
+    }
+
+    #[test]
+    fn test_non_canonical_embeddings_are_rejected() {
+        // A BLS12-381 value at or above the BN254 modulus names no BN254 element
+        let bn254_modulus = TestField::from_le_bytes_mod_order(&Bn254Fr::MODULUS.to_bytes_le());
+        assert_eq!(
+            extract_element::<Bn254Fr, TestField>(&[bn254_modulus]).unwrap_err(),
+            TranslationError::NonCanonical
+        );
+        let oversized_limb = TestField::from(2u64).pow([STATEMENT_LIMB_BITS as u64]);
+        assert!(extract_hash(&[oversized_limb, TestField::from(0u64)]).is_err());
+        assert!(CrossCurveStatement::<Bn254Fr>::extract(&[TestField::from(1u64)]).is_err());
+    }
+}
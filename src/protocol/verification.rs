@@ -0,0 +1,353 @@
+//! Detailed outcomes of verifying a delegation result
+//!
+//! `EOSProtocol::verify_computation` runs its checks in a fixed order and
+//! stops at the first one that fails. Instead of a bare `bool` it reports
+//! which check that was, the challenge point the check was evaluated at and
+//! how long verification took, so a rejected result can be diagnosed from
+//! logs alone.
+
+use std::time::Duration;
+
+use ark_ff::{Field, PrimeField};
+use ark_ec::pairing::Pairing;
+
+use crate::mpc::{KeyDirectory, OperationMode, SecretSharing, SignedMessage};
+use crate::protocol::{committed_claim, result_digest, ConstraintCheck, DelegationResult, EOSError, EOSProtocol};
+
+/// A check performed while verifying a delegation result
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum VerificationCheck {
+    /// The proof was produced for another verification key or circuit
+    KeyBinding,
+    /// A sumcheck round or final claim of the constraint proof does not
+    /// hold, or the proof is missing
+    PiopIdentity,
+    /// A KZG opening does not match its commitment
+    CommitmentOpening,
+    /// The proof was derived for other public inputs or another context
+    PublicInputBinding,
+    /// A worker's signed commitment to the result is invalid
+    Signature,
+    /// A challenge in the proof is not the one the transcript derives;
+    /// checked in audit mode only
+    ChallengeDerivation,
+}
+
+impl std::fmt::Display for VerificationCheck {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            VerificationCheck::KeyBinding => write!(f, "verification key binding"),
+            VerificationCheck::PiopIdentity => write!(f, "PIOP identity"),
+            VerificationCheck::CommitmentOpening => write!(f, "commitment opening"),
+            VerificationCheck::PublicInputBinding => write!(f, "public-input binding"),
+            VerificationCheck::Signature => write!(f, "worker signature"),
+            VerificationCheck::ChallengeDerivation => write!(f, "challenge derivation"),
+        }
+    }
+}
+
+impl From<ConstraintCheck> for VerificationCheck {
+    fn from(check: ConstraintCheck) -> Self {
+        match check {
+            ConstraintCheck::Identity => VerificationCheck::PiopIdentity,
+            ConstraintCheck::ChallengeDerivation => VerificationCheck::ChallengeDerivation,
+            ConstraintCheck::WitnessOpening | ConstraintCheck::MaskOpening => VerificationCheck::CommitmentOpening,
+        }
+    }
+}
+
+/// Result of verifying a delegation result
+#[derive(Debug, Clone, PartialEq)]
+pub enum VerificationOutcome<F: Field> {
+    Accepted {
+        /// Opening point the witness commitment was checked at
+        challenge: Option<F>,
+        elapsed: Duration,
+    },
+    Rejected {
+        check: VerificationCheck,
+        /// Challenge point of the failing check, if it had one
+        challenge: Option<F>,
+        elapsed: Duration,
+    },
+}
+
+impl<F: Field> VerificationOutcome<F> {
+    pub fn is_accepted(&self) -> bool {
+        matches!(self, VerificationOutcome::Accepted { .. })
+    }
+
+    /// The check that rejected the result
+    pub fn failed_check(&self) -> Option<VerificationCheck> {
+        match self {
+            VerificationOutcome::Accepted { .. } => None,
+            VerificationOutcome::Rejected { check, .. } => Some(*check),
+        }
+    }
+
+    pub fn challenge(&self) -> Option<F> {
+        match self {
+            VerificationOutcome::Accepted { challenge, .. } | VerificationOutcome::Rejected { challenge, .. } => {
+                *challenge
+            }
+        }
+    }
+
+    /// Time spent verifying
+    pub fn elapsed(&self) -> Duration {
+        match self {
+            VerificationOutcome::Accepted { elapsed, .. } | VerificationOutcome::Rejected { elapsed, .. } => *elapsed,
+        }
+    }
+}
+
+impl<F: Field> std::fmt::Display for VerificationOutcome<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            VerificationOutcome::Accepted { elapsed, .. } => write!(f, "accepted in {:?}", elapsed),
+            VerificationOutcome::Rejected { check, challenge: Some(challenge), elapsed } => {
+                write!(f, "rejected by {} check at challenge {} after {:?}", check, challenge, elapsed)
+            }
+            VerificationOutcome::Rejected { check, challenge: None, elapsed } => {
+                write!(f, "rejected by {} check after {:?}", check, elapsed)
+            }
+        }
+    }
+}
+
+impl<E, F, SS, OM> EOSProtocol<E, F, SS, OM>
+where
+    E: Pairing<ScalarField = F>,
+    F: PrimeField,
+    SS: SecretSharing<F>,
+    OM: OperationMode<F, SS>,
+{
+    /// Verify a result together with every worker's signed commitment to it
+    ///
+    /// Each commitment must carry a valid signature from `directory` and
+    /// name this result's digest; the proof is checked only after that.
+    pub fn verify_committed_computation(
+        &self,
+        result: &DelegationResult<E, F>,
+        public_inputs: &[F],
+        commitments: &[SignedMessage],
+        directory: &KeyDirectory,
+    ) -> Result<VerificationOutcome<F>, EOSError> {
+        let start = std::time::Instant::now();
+        let digest = result_digest(result);
+        let signed = |commitment: &SignedMessage| {
+            directory.verify(commitment).is_ok()
+                && committed_claim(commitment).is_some_and(|claim| claim.result_digest == digest)
+        };
+        if !commitments.iter().all(signed) {
+            return Ok(VerificationOutcome::Rejected {
+                check: VerificationCheck::Signature,
+                challenge: None,
+                elapsed: start.elapsed(),
+            });
+        }
+        Ok(match self.verify_computation(result, public_inputs)? {
+            VerificationOutcome::Accepted { challenge, .. } => {
+                VerificationOutcome::Accepted { challenge, elapsed: start.elapsed() }
+            }
+            VerificationOutcome::Rejected { check, challenge, .. } => {
+                VerificationOutcome::Rejected { check, challenge, elapsed: start.elapsed() }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mpc::{CollaborationMode, ExecCircuit, RotationPolicy, ShamirSecretSharing, WorkerKeyring};
+    use crate::piop::{ConstraintClaims, PolynomialConsistencyProof};
+    use crate::protocol::{commit_result, ResultClaim};
+    use crate::serialization::WireFormat;
+    use ark_bls12_381::{Bls12_381, Fr, G1Projective};
+    use ark_relations::r1cs::ConstraintSystem;
+    use ark_std::test_rng;
+
+    type TestField = Fr;
+    type Protocol = EOSProtocol<Bls12_381, TestField, ShamirSecretSharing<TestField>, CollaborationMode>;
+
+    /// x·x = y with y = 49 public, and x·1 = x so the zerocheck has a round
+    fn square_protocol(rng: &mut impl ark_std::rand::Rng) -> (Protocol, ConstraintSystem<TestField>) {
+        let cs = ConstraintSystem::<TestField>::new_ref();
+        let y = cs.new_input_variable(|| Ok(TestField::from(49u64))).unwrap();
+        let x = cs.new_witness_variable(|| Ok(TestField::from(7u64))).unwrap();
+        cs.enforce_constraint(ark_relations::lc!() + x, ark_relations::lc!() + x, ark_relations::lc!() + y).unwrap();
+        let one = ark_relations::r1cs::Variable::One;
+        cs.enforce_constraint(ark_relations::lc!() + x, ark_relations::lc!() + one, ark_relations::lc!() + x).unwrap();
+        let circuit = cs.into_inner().unwrap();
+        let executor = ExecCircuit::new(1, 3, ShamirSecretSharing::new());
+        let mut protocol = Protocol::new(executor, CollaborationMode::new(2, true, false), 128, rng);
+        protocol.preprocessing_state = Some(Protocol::preprocessing(&circuit, 128, rng).unwrap());
+        (protocol, circuit)
+    }
+
+    #[test]
+    fn test_outcome_names_the_failing_check() {
+        let mut rng = test_rng();
+        let executor = ExecCircuit::new(1, 3, ShamirSecretSharing::new());
+        let mut protocol = Protocol::new(executor, CollaborationMode::new(2, true, false), 128, &mut rng);
+        let circuit = ConstraintSystem::new();
+        protocol.preprocessing_state = Some(Protocol::preprocessing(&circuit, 128, &mut rng).unwrap());
+        let witness: Vec<TestField> = (1..=4u64).map(TestField::from).collect();
+        let public_inputs = [TestField::from(10u64)];
+        let mut result = protocol.delegate_computation(&circuit, &witness, &public_inputs, &mut rng).unwrap();
+
+        let outcome = protocol.verify_computation(&result, &public_inputs).unwrap();
+        assert!(outcome.is_accepted());
+        let point = outcome.challenge().unwrap();
+        assert!(outcome.to_string().starts_with("accepted"));
+
+        // Other public inputs derive another opening point
+        let outcome = protocol.verify_computation(&result, &[TestField::from(11u64)]).unwrap();
+        assert_eq!(outcome.failed_check(), Some(VerificationCheck::PublicInputBinding));
+        assert_ne!(outcome.challenge(), Some(point));
+
+        // A wrong evaluation fails the KZG check at the honest point
+        let honest = result.piop_proof.clone().unwrap();
+        let mut proof = PolynomialConsistencyProof::<TestField, G1Projective>::from_bytes(&honest).unwrap();
+        proof.consistency_proofs[0].evaluation += TestField::from(1u64);
+        result.piop_proof = Some(proof.to_bytes());
+        let outcome = protocol.verify_computation(&result, &public_inputs).unwrap();
+        assert_eq!(outcome.failed_check(), Some(VerificationCheck::CommitmentOpening));
+        assert_eq!(outcome.challenge(), Some(point));
+        assert!(outcome.to_string().contains("commitment opening"));
+
+        // A wrong round polynomial in the constraint proof fails the PIOP identity
+        let mut proof = PolynomialConsistencyProof::<TestField, G1Projective>::from_bytes(&honest).unwrap();
+        proof.sumcheck_proofs[1].round_polynomials[0].coeffs = vec![TestField::from(1u64)];
+        result.piop_proof = Some(proof.to_bytes());
+        let outcome = protocol.verify_computation(&result, &public_inputs).unwrap();
+        assert_eq!(outcome.failed_check(), Some(VerificationCheck::PiopIdentity));
+
+        result.piop_proof = None;
+        let outcome = protocol.verify_computation(&result, &public_inputs).unwrap();
+        assert_eq!(outcome.failed_check(), Some(VerificationCheck::PiopIdentity));
+        result.piop_proof = Some(honest);
+
+        // Worker commitments must be signed and name this result
+        let mut directory = KeyDirectory::new();
+        let mut keyring = WorkerKeyring::<TestField>::new(0, [1; 32], RotationPolicy::default(), 0);
+        directory.accept(&keyring.initial_announcement().unwrap()).unwrap();
+        keyring.begin_session(3);
+        let claim = ResultClaim { session: 3, attempt: 0, result_digest: result_digest(&result) };
+        let commitment = commit_result(&keyring, claim, &mut rng).unwrap();
+        let verify = |commitment: &SignedMessage| {
+            protocol.verify_committed_computation(&result, &public_inputs, std::slice::from_ref(commitment), &directory)
+        };
+        assert!(verify(&commitment).unwrap().is_accepted());
+        let other = commit_result(&keyring, ResultClaim { result_digest: [0; 32], ..claim }, &mut rng).unwrap();
+        assert_eq!(verify(&other).unwrap().failed_check(), Some(VerificationCheck::Signature));
+        let mut forged = commitment.clone();
+        forged.payload.push(0);
+        assert_eq!(verify(&forged).unwrap().failed_check(), Some(VerificationCheck::Signature));
+    }
+
+    #[test]
+    fn test_every_constraint_check_runs_on_verification() {
+        let mut rng = test_rng();
+        let (mut protocol, circuit) = square_protocol(&mut rng);
+        let public_inputs = [TestField::from(49u64)];
+        let mut result = protocol.delegate_computation(&circuit, &[TestField::from(7u64)], &public_inputs, &mut rng).unwrap();
+        assert!(protocol.verify_computation(&result, &public_inputs).unwrap().is_accepted());
+        let honest = result.piop_proof.clone().unwrap();
+        let one = TestField::from(1u64);
+
+        use VerificationCheck::{CommitmentOpening, PiopIdentity};
+        type Proof = PolynomialConsistencyProof<TestField, G1Projective>;
+        fn claims(proof: &mut Proof) -> &mut ConstraintClaims<TestField, G1Projective> {
+            proof.constraint_claims.as_mut().unwrap()
+        }
+        type Tamper = fn(&mut Proof);
+        let cases: [(&str, Tamper, VerificationCheck); 7] = [
+            ("zerocheck round", |p| p.sumcheck_proofs[0].round_polynomials[0].coeffs = vec![TestField::from(1u64)], PiopIdentity),
+            ("zerocheck rounds", |p| p.sumcheck_proofs[0].round_polynomials.clear(), PiopIdentity),
+            ("row evaluation", |p| claims(p).row_evaluations[2] += TestField::from(1u64), PiopIdentity),
+            ("linear sumcheck", |p| p.sumcheck_proofs[1].round_polynomials.push(Default::default()), PiopIdentity),
+            ("witness claim", |p| claims(p).witness_opening.evaluation += TestField::from(1u64), PiopIdentity),
+            ("missing claims", |p| p.constraint_claims = None, PiopIdentity),
+            ("witness opening", |p| claims(p).witness_opening.openings[0].evaluations[0] += TestField::from(1u64), CommitmentOpening),
+        ];
+        for (name, tamper, check) in cases {
+            let mut proof = Proof::from_bytes(&honest).unwrap();
+            tamper(&mut proof);
+            result.piop_proof = Some(proof.to_bytes());
+            let outcome = protocol.verify_computation(&result, &public_inputs).unwrap();
+            assert_eq!(outcome.failed_check(), Some(check), "{}", name);
+        }
+
+        // A proof of a witness that does not satisfy the circuit fails the zerocheck
+        let wrong = protocol.delegate_computation(&circuit, &[TestField::from(7u64) + one], &public_inputs, &mut rng).unwrap();
+        let outcome = protocol.verify_computation(&wrong, &public_inputs).unwrap();
+        assert_eq!(outcome.failed_check(), Some(VerificationCheck::PiopIdentity));
+        assert!(outcome.challenge().is_some());
+    }
+
+    #[test]
+    fn test_audit_mode_rederives_challenges() {
+        let mut rng = test_rng();
+        let executor = ExecCircuit::new(1, 3, ShamirSecretSharing::new());
+        let mut protocol = Protocol::new(executor, CollaborationMode::new(2, true, false), 128, &mut rng);
+        let circuit = ConstraintSystem::new();
+        protocol.preprocessing_state = Some(Protocol::preprocessing(&circuit, 128, &mut rng).unwrap());
+        let witness: Vec<TestField> = (1..=4u64).map(TestField::from).collect();
+        let public_inputs = [TestField::from(10u64)];
+        let mut result = protocol.delegate_computation(&circuit, &witness, &public_inputs, &mut rng).unwrap();
+
+        protocol.params.audit = true;
+        assert!(protocol.verify_computation(&result, &public_inputs).unwrap().is_accepted());
+
+        // A prover-chosen round challenge passes the standard checks only
+        let honest = result.piop_proof.clone().unwrap();
+        let mut proof = PolynomialConsistencyProof::<TestField, G1Projective>::from_bytes(&honest).unwrap();
+        proof.sumcheck_proofs[1].challenges[0] += TestField::from(1u64);
+        result.piop_proof = Some(proof.to_bytes());
+        let outcome = protocol.verify_computation(&result, &public_inputs).unwrap();
+        assert_eq!(outcome.failed_check(), Some(VerificationCheck::ChallengeDerivation));
+        assert!(outcome.to_string().contains("challenge derivation"));
+
+        protocol.params.audit = false;
+        assert!(protocol.verify_computation(&result, &public_inputs).unwrap().is_accepted());
+    }
+
+    #[test]
+    fn test_audit_mode_rejects_a_wrong_constraint() {
+        let mut rng = test_rng();
+        let (mut protocol, circuit) = square_protocol(&mut rng);
+        protocol.params.audit = true;
+        let public_inputs = [TestField::from(49u64)];
+
+        let honest = protocol.delegate_computation(&circuit, &[TestField::from(7u64)], &public_inputs, &mut rng).unwrap();
+        assert!(protocol.verify_computation(&honest, &public_inputs).unwrap().is_accepted());
+
+        // The challenges of a proof for x = 8 are the transcript's, but its
+        // zerocheck does not sum to zero
+        let wrong = protocol.delegate_computation(&circuit, &[TestField::from(8u64)], &public_inputs, &mut rng).unwrap();
+        let outcome = protocol.verify_computation(&wrong, &public_inputs).unwrap();
+        assert_eq!(outcome.failed_check(), Some(VerificationCheck::PiopIdentity));
+
+        // Nor does changing its final claims to satisfy the constraint pass:
+        // the witness opening no longer matches
+        let bytes = wrong.piop_proof.clone().unwrap();
+        let mut proof = PolynomialConsistencyProof::<TestField, G1Projective>::from_bytes(&bytes).unwrap();
+        let claims = proof.constraint_claims.as_mut().unwrap();
+        claims.row_evaluations = [TestField::from(7u64), TestField::from(7u64), TestField::from(49u64)];
+        let mut forged = wrong;
+        forged.piop_proof = Some(proof.to_bytes());
+        assert!(!protocol.verify_computation(&forged, &public_inputs).unwrap().is_accepted());
+
+        // Every challenge of the zerocheck is re-derived, not only the linear sumcheck's
+        let bytes = honest.piop_proof.clone().unwrap();
+        let mut proof = PolynomialConsistencyProof::<TestField, G1Projective>::from_bytes(&bytes).unwrap();
+        assert!(!proof.sumcheck_proofs[0].challenges.is_empty());
+        proof.sumcheck_proofs[0].challenges[0] += TestField::from(1u64);
+        let mut tampered = honest;
+        tampered.piop_proof = Some(proof.to_bytes());
+        let outcome = protocol.verify_computation(&tampered, &public_inputs).unwrap();
+        assert_eq!(outcome.failed_check(), Some(VerificationCheck::ChallengeDerivation));
+    }
+}
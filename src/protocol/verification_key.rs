@@ -0,0 +1,158 @@
+//! Verification keys and their published encoding
+//!
+//! `EOSProtocol::index` derives a `VerificationKey` per circuit from the
+//! SRS alone: the G1 generator, `h` and `h^τ`, so a key indexed from an
+//! imported ceremony holds nothing sampled locally. Keys are published in
+//! the byte layout below, version 2, so third-party verifiers can load them
+//! without the delegator's code:
+//!
+//! | offset            | size      | field                                         |
+//! |-------------------|-----------|-----------------------------------------------|
+//! | 0                 | 4         | magic `EOVK`                                  |
+//! | 4                 | 1         | layout version, `VERIFICATION_KEY_VERSION`    |
+//! | 5                 | 1         | curve tag: 0 = BLS12-381, 1 = BN254           |
+//! | 6                 | `g1`      | `g`                                           |
+//! | 6 + `g1`          | 2 × `g2`  | `h`, `h_tau`                                  |
+//! | 6 + `g1` + 2·`g2` | 4         | number of public inputs, u32 little-endian    |
+//!
+//! Points use the arkworks compressed encoding: `g1` is 48 bytes and `g2`
+//! 96 bytes on BLS12-381, 32 and 64 bytes on BN254. Decoding rejects points
+//! outside the prime-order subgroup, an unknown version or curve and
+//! trailing bytes. Version 1 carried Groth16-shaped `alpha`..`delta` and `ic`
+//! points that no verifier read; it is no longer accepted. The `vk-inspect`
+//! binary prints a key file.
+
+use ark_ec::pairing::Pairing;
+use sha2::{Digest, Sha256};
+
+use crate::circuit::UniversalParams;
+use crate::serialization::{write_len, write_point, ByteReader, DecodeError, WireFormat};
+
+/// Magic bytes opening an encoded verification key
+pub const VERIFICATION_KEY_MAGIC: [u8; 4] = *b"EOVK";
+
+/// Layout version of the verification key encoding
+pub const VERIFICATION_KEY_VERSION: u8 = 2;
+
+/// Verification key for the verifier: the SRS's verifier part and the
+/// circuit's public input count
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerificationKey<E: Pairing> {
+    /// G1 generator, `powers_of_g[0]`
+    pub g: E::G1Affine,
+    pub h: E::G2Affine,
+    pub h_tau: E::G2Affine,
+    pub num_public_inputs: usize,
+}
+
+/// Pairings a verification key can be published for
+pub trait KeyCurve: Pairing {
+    /// Curve tag of the encoding
+    const TAG: u8;
+    const NAME: &'static str;
+}
+
+impl KeyCurve for ark_bls12_381::Bls12_381 {
+    const TAG: u8 = 0;
+    const NAME: &'static str = "bls12-381";
+}
+
+impl KeyCurve for ark_bn254::Bn254 {
+    const TAG: u8 = 1;
+    const NAME: &'static str = "bn254";
+}
+
+impl<E: Pairing> VerificationKey<E> {
+    /// The key for a circuit with `num_public_inputs` inputs over `params`
+    pub fn from_params(params: &UniversalParams<E>, num_public_inputs: usize) -> Self {
+        let (h, h_tau) = params.verification_key;
+        Self { g: params.powers_of_g[0], h, h_tau, num_public_inputs }
+    }
+
+    /// SHA-256 of the encoded key, for comparison with a published value
+    pub fn fingerprint(&self) -> [u8; 32]
+    where
+        E: KeyCurve,
+    {
+        Sha256::digest(self.to_bytes()).into()
+    }
+}
+
+/// Curve tag of an encoded key, read without decoding the points
+pub fn verification_key_curve(bytes: &[u8]) -> Result<u8, DecodeError> {
+    let mut reader = ByteReader::new(bytes);
+    read_header(&mut reader)
+}
+
+fn read_header(reader: &mut ByteReader<'_>) -> Result<u8, DecodeError> {
+    if reader.read_bytes(VERIFICATION_KEY_MAGIC.len())? != VERIFICATION_KEY_MAGIC {
+        return Err(DecodeError::InvalidMagic);
+    }
+    let version = reader.read_u8()?;
+    if version != VERIFICATION_KEY_VERSION {
+        return Err(DecodeError::InvalidTag(version));
+    }
+    reader.read_u8()
+}
+
+impl<E: KeyCurve> WireFormat for VerificationKey<E> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&VERIFICATION_KEY_MAGIC);
+        out.push(VERIFICATION_KEY_VERSION);
+        out.push(E::TAG);
+        write_point::<E::G1>(out, &self.g);
+        write_point::<E::G2>(out, &self.h);
+        write_point::<E::G2>(out, &self.h_tau);
+        write_len(out, self.num_public_inputs);
+    }
+
+    fn decode(reader: &mut ByteReader<'_>) -> Result<Self, DecodeError> {
+        let curve = read_header(reader)?;
+        if curve != E::TAG {
+            return Err(DecodeError::InvalidTag(curve));
+        }
+        let g = reader.read_point::<E::G1>()?;
+        let h = reader.read_point::<E::G2>()?;
+        let h_tau = reader.read_point::<E::G2>()?;
+        let num_public_inputs = reader.read_u32()? as usize;
+        Ok(VerificationKey { g, h, h_tau, num_public_inputs })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::Bls12_381;
+    use ark_bn254::Bn254;
+    use ark_std::test_rng;
+
+    fn sample<E: Pairing>(num_public_inputs: usize) -> VerificationKey<E> {
+        VerificationKey::from_params(&UniversalParams::setup(4, &mut test_rng()), num_public_inputs)
+    }
+
+    #[test]
+    fn test_verification_key_layout() {
+        let key = sample::<Bls12_381>(2);
+        let bytes = key.to_bytes();
+        // magic, version, curve, one G1 and two G2 points, input count
+        assert_eq!(bytes.len(), 4 + 1 + 1 + 48 + 2 * 96 + 4);
+        assert_eq!(&bytes[..6], b"EOVK\x02\x00");
+        assert_eq!(VerificationKey::<Bls12_381>::from_bytes(&bytes).unwrap(), key);
+        assert_eq!(verification_key_curve(&bytes), Ok(<Bls12_381 as KeyCurve>::TAG));
+        assert_eq!(key.num_public_inputs, 2);
+        assert_ne!(key.fingerprint(), sample::<Bls12_381>(3).fingerprint());
+
+        let bn = sample::<Bn254>(0).to_bytes();
+        assert_eq!(bn.len(), 4 + 1 + 1 + 32 + 2 * 64 + 4);
+        assert_eq!(VerificationKey::<Bls12_381>::from_bytes(&bn), Err(DecodeError::InvalidTag(1)));
+
+        let mut old = bytes.clone();
+        old[4] = 1;
+        assert_eq!(VerificationKey::<Bls12_381>::from_bytes(&old), Err(DecodeError::InvalidTag(1)));
+        assert_eq!(verification_key_curve(b"EOVX\x02\x00"), Err(DecodeError::InvalidMagic));
+        assert!(VerificationKey::<Bls12_381>::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        let mut extended = bytes;
+        extended.push(0);
+        assert_eq!(VerificationKey::<Bls12_381>::from_bytes(&extended), Err(DecodeError::TrailingBytes(1)));
+    }
+}
@@ -0,0 +1,384 @@
+//! Python bindings
+//!
+//! Built with the `python` feature as the extension module `eos_delegation`
+//! (see `pyproject.toml`). Field elements cross the boundary as Python
+//! integers, reduced modulo the BLS12-381 scalar field; proofs and key
+//! hashes as `bytes`.
+//!
+//! ```python
+//! import eos_delegation as eos
+//!
+//! circuit = eos.Circuit("square")
+//! x = circuit.witness(3)
+//! y = circuit.public(9)
+//! circuit.mul(x, x, y)
+//!
+//! delegation = eos.Delegation(circuit, seed=1)
+//! proof = delegation.delegate()
+//! assert delegation.verify(proof)
+//! assert not delegation.verify(proof, public_inputs=[10])
+//! ```
+
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+
+use ark_bls12_381::{Bls12_381, Fr};
+use ark_ff::PrimeField;
+use ark_relations::lc;
+use ark_relations::r1cs::{ConstraintSystem, ConstraintSystemRef, SynthesisError, Variable as R1csVariable};
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+use num_bigint::{BigInt, BigUint};
+use pyo3::create_exception;
+use pyo3::exceptions::{PyException, PyValueError};
+use pyo3::prelude::*;
+
+use crate::custom_circuits::{CustomCircuit, TemplateRegistry, Variable};
+use crate::evaluation::ProofSizeReport;
+use crate::mpc::{CollaborationMode, ExecCircuit, SecretSharing, ShamirSecretSharing, ShamirShare};
+use crate::protocol::{DelegationResult, EOSProtocol};
+
+type Protocol = EOSProtocol<Bls12_381, Fr, ShamirSecretSharing<Fr>, CollaborationMode>;
+
+create_exception!(eos_delegation, EosError, PyException);
+
+fn eos_error(error: impl std::fmt::Display) -> PyErr {
+    EosError::new_err(error.to_string())
+}
+
+fn to_field(value: &BigInt) -> Fr {
+    let modulus = BigInt::from(BigUint::from(Fr::MODULUS));
+    let reduced = ((value % &modulus) + &modulus) % &modulus;
+    Fr::from(reduced.magnitude().clone())
+}
+
+fn from_field(value: &Fr) -> BigUint {
+    value.into_bigint().into()
+}
+
+fn rng_from(seed: Option<u64>) -> StdRng {
+    match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    }
+}
+
+/// A circuit variable: `one`, `instance[i]` or `witness[j]`
+#[pyclass(name = "Variable", module = "eos_delegation", frozen, eq, hash)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct PyVariable(Variable);
+
+#[pymethods]
+impl PyVariable {
+    /// `"instance"` or `"witness"`
+    #[getter]
+    fn kind(&self) -> &'static str {
+        match self.0 {
+            Variable::Instance(_) => "instance",
+            Variable::Witness(_) => "witness",
+        }
+    }
+
+    #[getter]
+    fn index(&self) -> usize {
+        match self.0 {
+            Variable::Instance(i) | Variable::Witness(i) => i,
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        self.0.to_string()
+    }
+}
+
+/// A circuit under construction, together with its assignment
+#[pyclass(name = "Circuit", module = "eos_delegation")]
+struct PyCircuit {
+    inner: CustomCircuit<Fr>,
+}
+
+#[pymethods]
+impl PyCircuit {
+    #[new]
+    #[pyo3(signature = (name = "circuit"))]
+    fn new(name: &str) -> Self {
+        Self { inner: CustomCircuit::new(name.to_string()) }
+    }
+
+    /// Instantiate a built-in template, e.g. `Circuit.template("repeated_squaring", rounds=16)`
+    #[staticmethod]
+    #[pyo3(signature = (name, seed = None, **params))]
+    fn template(name: &str, seed: Option<u64>, params: Option<HashMap<String, u64>>) -> PyResult<Self> {
+        let raw: HashMap<String, String> =
+            params.unwrap_or_default().into_iter().map(|(key, value)| (key, value.to_string())).collect();
+        let inner = TemplateRegistry::with_builtin_templates()
+            .instantiate(name, &raw, &mut rng_from(seed))
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok(Self { inner })
+    }
+
+    #[getter]
+    fn name(&self) -> &str {
+        &self.inner.name
+    }
+
+    #[getter]
+    fn num_constraints(&self) -> usize {
+        self.inner.multiplication_constraints.len() + self.inner.addition_constraints.len()
+    }
+
+    #[getter]
+    fn num_witnesses(&self) -> usize {
+        self.inner.private_witnesses.len()
+    }
+
+    #[getter]
+    fn public_inputs(&self) -> Vec<BigUint> {
+        self.inner.public_inputs.iter().map(from_field).collect()
+    }
+
+    /// Add a private witness and return its variable
+    fn witness(&mut self, value: BigInt) -> PyVariable {
+        PyVariable(self.inner.add_private_witness(to_field(&value)))
+    }
+
+    /// Add a public input and return its variable
+    fn public(&mut self, value: BigInt) -> PyVariable {
+        PyVariable(self.inner.add_public_input(to_field(&value)))
+    }
+
+    /// Constrain `a * b == c`
+    fn mul(&mut self, a: PyVariable, b: PyVariable, c: PyVariable) -> PyResult<()> {
+        self.check_variables(&[a, b, c])?;
+        self.inner.add_multiplication_constraint(a.0, b.0, c.0);
+        Ok(())
+    }
+
+    /// Constrain `a + b == c`
+    fn add(&mut self, a: PyVariable, b: PyVariable, c: PyVariable) -> PyResult<()> {
+        self.check_variables(&[a, b, c])?;
+        self.inner.add_addition_constraint(a.0, b.0, c.0);
+        Ok(())
+    }
+
+    /// Value assigned to `variable`
+    fn value(&self, variable: PyVariable) -> PyResult<BigUint> {
+        self.check_variables(&[variable])?;
+        Ok(self.inner.variable(variable.0).map(|value| from_field(&value)).unwrap_or_default())
+    }
+
+    fn is_satisfied(&self) -> bool {
+        self.inner.check_witness().is_satisfied()
+    }
+
+    /// Human-readable list of the violated constraints
+    fn check(&self) -> String {
+        self.inner.check_witness().to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Circuit({:?}, witnesses={}, public_inputs={}, constraints={})",
+            self.inner.name,
+            self.num_witnesses(),
+            self.inner.public_inputs.len(),
+            self.num_constraints()
+        )
+    }
+}
+
+impl PyCircuit {
+    fn check_variables(&self, variables: &[PyVariable]) -> PyResult<()> {
+        match variables.iter().find(|variable| self.inner.variable(variable.0).is_none()) {
+            Some(variable) => Err(PyValueError::new_err(format!("{} is not in circuit {}", variable.0, self.inner.name))),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The circuit's constraints in `ConstraintSystem` form, laid out as `Variable::index`
+fn constraint_system(circuit: &CustomCircuit<Fr>) -> Result<ConstraintSystem<Fr>, SynthesisError> {
+    let cs: ConstraintSystemRef<Fr> = ConstraintSystem::new_ref();
+    for &value in &circuit.public_inputs {
+        cs.new_input_variable(|| Ok(value))?;
+    }
+    for &value in &circuit.private_witnesses {
+        cs.new_witness_variable(|| Ok(value))?;
+    }
+    let wire = |variable: Variable| -> R1csVariable { variable.into() };
+    for &(a, b, c) in &circuit.multiplication_constraints {
+        cs.enforce_constraint(lc!() + wire(a), lc!() + wire(b), lc!() + wire(c))?;
+    }
+    for &(a, b, c) in &circuit.addition_constraints {
+        cs.enforce_constraint(lc!() + wire(a) + wire(b), lc!() + wire(Variable::ONE), lc!() + wire(c))?;
+    }
+    cs.into_inner().ok_or(SynthesisError::MissingCS)
+}
+
+/// Shamir-share `secret` among `parties`, any `threshold` of which reconstruct it
+#[pyfunction]
+#[pyo3(signature = (secret, threshold, parties, seed = None))]
+fn share(secret: BigInt, threshold: usize, parties: usize, seed: Option<u64>) -> PyResult<Vec<(usize, BigUint)>> {
+    if threshold == 0 || threshold > parties {
+        return Err(PyValueError::new_err(format!("threshold {} out of range for {} parties", threshold, parties)));
+    }
+    let shares = ShamirSecretSharing::share_secret(to_field(&secret), threshold, parties, &mut rng_from(seed));
+    Ok(shares.iter().map(|share| (share.index, from_field(&share.value))).collect())
+}
+
+/// Reconstruct a secret from `(index, value)` shares
+#[pyfunction]
+fn reconstruct(shares: Vec<(usize, BigInt)>) -> PyResult<BigUint> {
+    let shares: Vec<ShamirShare<Fr>> =
+        shares.iter().map(|(index, value)| ShamirShare { index: *index, value: to_field(value) }).collect();
+    let secret = ShamirSecretSharing::<Fr>::reconstruct_secret(&shares).map_err(eos_error)?;
+    Ok(from_field(&secret))
+}
+
+/// Names of the built-in circuit templates
+#[pyfunction]
+fn templates() -> Vec<&'static str> {
+    TemplateRegistry::with_builtin_templates().names()
+}
+
+/// A delegation result as received by the verifier
+#[pyclass(name = "Proof", module = "eos_delegation")]
+struct PyProof {
+    result: DelegationResult<Bls12_381, Fr>,
+    public_inputs: Vec<Fr>,
+}
+
+#[pymethods]
+impl PyProof {
+    /// The encoded transcript the verifier receives
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(self.result.transcript_bytes())
+    }
+
+    /// Encoded size in bytes
+    fn __len__(&self) -> usize {
+        self.result.transcript_bytes().len()
+    }
+
+    /// Encoded bytes per proof component category, e.g. `{"sumcheck_round": 204, ...}`
+    fn size_breakdown(&self) -> PyResult<BTreeMap<String, usize>> {
+        let report = ProofSizeReport::of_result(&self.result).map_err(eos_error)?;
+        Ok(report.by_category().into_iter().map(|(category, bytes)| (category.name().replace(' ', "_"), bytes)).collect())
+    }
+
+    #[getter]
+    fn public_inputs(&self) -> Vec<BigUint> {
+        self.public_inputs.iter().map(from_field).collect()
+    }
+
+    /// Flip the low bit of byte `position` of the PIOP proof
+    fn tamper(&mut self, position: usize) -> PyResult<()> {
+        let proof = self.result.piop_proof.as_mut().ok_or_else(|| eos_error("result carries no proof"))?;
+        let len = proof.len();
+        let byte = proof
+            .get_mut(position)
+            .ok_or_else(|| PyValueError::new_err(format!("position {} past the {}-byte proof", position, len)))?;
+        *byte ^= 0x01;
+        Ok(())
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Proof({} bytes)", self.__len__())
+    }
+}
+
+/// A preprocessed circuit that delegates proofs and verifies them
+#[pyclass(name = "Delegation", module = "eos_delegation", unsendable)]
+struct PyDelegation {
+    protocol: Protocol,
+    circuit: CustomCircuit<Fr>,
+    rng: StdRng,
+}
+
+#[pymethods]
+impl PyDelegation {
+    /// Run preprocessing for `circuit`; `seed` makes the setup reproducible
+    #[new]
+    #[pyo3(signature = (circuit, security_parameter = 128, parties = 3, seed = None))]
+    fn new(circuit: &PyCircuit, security_parameter: usize, parties: usize, seed: Option<u64>) -> PyResult<Self> {
+        let mut rng = rng_from(seed);
+        let cs = constraint_system(&circuit.inner).map_err(eos_error)?;
+        let executor = ExecCircuit::new(1, parties, ShamirSecretSharing::new());
+        let mode = CollaborationMode::new(2, true, false);
+        let mut protocol = Protocol::new(executor, mode, security_parameter, &mut rng);
+        protocol.preprocessing_state = Some(Protocol::preprocessing(&cs, security_parameter, &mut rng).map_err(eos_error)?);
+        Ok(Self { protocol, circuit: circuit.inner.clone(), rng })
+    }
+
+    /// Hash of the verification key and circuit, bound into every proof
+    #[getter]
+    fn verification_key_hash(&self) -> PyResult<Cow<'_, [u8]>> {
+        Ok(Cow::Owned(self.protocol.verification_key_hash().map_err(eos_error)?.to_vec()))
+    }
+
+    /// Delegate the circuit's assignment; a 32-byte `seed` makes the proof reproducible
+    #[pyo3(signature = (seed = None))]
+    fn delegate(&mut self, seed: Option<Vec<u8>>) -> PyResult<PyProof> {
+        let report = self.circuit.check_witness();
+        if !report.is_satisfied() {
+            return Err(eos_error(report));
+        }
+        let cs = constraint_system(&self.circuit).map_err(eos_error)?;
+        let (witness, public_inputs) = (&self.circuit.private_witnesses, &self.circuit.public_inputs);
+        let result = match seed {
+            Some(seed) => {
+                let seed: [u8; 32] =
+                    seed.try_into().map_err(|_| PyValueError::new_err("seed must be exactly 32 bytes"))?;
+                self.protocol.delegate_computation_seeded(&cs, witness, public_inputs, seed)
+            }
+            None => self.protocol.delegate_computation(&cs, witness, public_inputs, &mut self.rng),
+        }
+        .map_err(eos_error)?;
+        Ok(PyProof { result, public_inputs: public_inputs.clone() })
+    }
+
+    /// Whether `proof` verifies against `public_inputs` (default: the proof's own)
+    #[pyo3(signature = (proof, public_inputs = None))]
+    fn verify(&self, proof: &PyProof, public_inputs: Option<Vec<BigInt>>) -> PyResult<bool> {
+        Ok(self.verify_detailed(proof, public_inputs)?.0)
+    }
+
+    /// `(accepted, failed_check, seconds)`; `failed_check` is `None` when accepted
+    #[pyo3(signature = (proof, public_inputs = None))]
+    fn verify_detailed(
+        &self,
+        proof: &PyProof,
+        public_inputs: Option<Vec<BigInt>>,
+    ) -> PyResult<(bool, Option<String>, f64)> {
+        let public_inputs = match public_inputs {
+            Some(values) => values.iter().map(to_field).collect(),
+            None => proof.public_inputs.clone(),
+        };
+        match self.protocol.verify_computation(&proof.result, &public_inputs) {
+            Ok(outcome) => Ok((
+                outcome.is_accepted(),
+                outcome.failed_check().map(|check| check.to_string()),
+                outcome.elapsed().as_secs_f64(),
+            )),
+            // A proof that no longer decodes is rejected, not an error of the caller
+            Err(e) => Ok((false, Some(e.to_string()), 0.0)),
+        }
+    }
+
+    /// Seconds spent in each protocol phase so far
+    fn timings(&self) -> HashMap<String, f64> {
+        self.protocol.metrics.timings.iter().map(|(phase, duration)| (phase.clone(), duration.as_secs_f64())).collect()
+    }
+}
+
+#[pymodule]
+fn eos_delegation(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyVariable>()?;
+    m.add_class::<PyCircuit>()?;
+    m.add_class::<PyProof>()?;
+    m.add_class::<PyDelegation>()?;
+    m.add_function(wrap_pyfunction!(share, m)?)?;
+    m.add_function(wrap_pyfunction!(reconstruct, m)?)?;
+    m.add_function(wrap_pyfunction!(templates, m)?)?;
+    m.add("EosError", m.py().get_type::<EosError>())?;
+    Ok(())
+}
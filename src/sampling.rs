@@ -0,0 +1,145 @@
+//! Deterministic field sampling from seed bytes
+//!
+//! Wherever randomness has to be reproducible — test vectors, replaying a
+//! seeded delegation, turning a coin-flip beacon into field elements — it
+//! is drawn through this module instead of `F::rand`. `F::rand` depends on
+//! the RNG's word size and on arkworks' rejection-sampling loop, so the same
+//! seed is not guaranteed to give the same elements across platforms or
+//! library versions. The mapping here is fixed and fully specified:
+//!
+//! * bytes are expanded with `expand_message_xmd` over SHA-256
+//!   (RFC 9380, section 5.3.1);
+//! * `hash_to_field` takes `L = ceil((ceil(log2 p) + 128) / 8)` bytes per
+//!   element, reads them big endian and reduces modulo `p` (RFC 9380,
+//!   section 5.2, with `k = 128`), so the bias is at most `2^-128`;
+//! * the `i`-th element of a `SeededSampler` is
+//!   `hash_to_field(seed || I2OSP(i, 8), DST)` with `i` counted from 0.
+//!
+//! Any implementation following these three rules reproduces the same
+//! elements from the same seed and domain tag.
+
+use ark_ff::PrimeField;
+use sha2::{Digest, Sha256};
+
+/// Security level, in bits, of the reduction to the field
+const SAMPLING_SECURITY: usize = 128;
+
+/// `expand_message_xmd` of RFC 9380 with SHA-256
+///
+/// Panics if `len_in_bytes` exceeds `255 * 32` or the domain tag is longer
+/// than 255 bytes, the limits the RFC places on both.
+pub fn expand_message_xmd(message: &[u8], dst: &[u8], len_in_bytes: usize) -> Vec<u8> {
+    const B_IN_BYTES: usize = 32;
+    const S_IN_BYTES: usize = 64;
+    let ell = len_in_bytes.div_ceil(B_IN_BYTES);
+    assert!(ell <= 255 && len_in_bytes <= u16::MAX as usize, "requested {} bytes, at most 8160 allowed", len_in_bytes);
+    assert!(dst.len() <= 255, "domain separation tag longer than 255 bytes");
+    let dst_prime = [dst, &[dst.len() as u8]].concat();
+
+    let b_0 = Sha256::new()
+        .chain_update([0u8; S_IN_BYTES])
+        .chain_update(message)
+        .chain_update((len_in_bytes as u16).to_be_bytes())
+        .chain_update([0u8])
+        .chain_update(&dst_prime)
+        .finalize();
+    let mut b_i = Sha256::new().chain_update(b_0).chain_update([1u8]).chain_update(&dst_prime).finalize();
+    let mut uniform_bytes = b_i.to_vec();
+    for i in 2..=ell {
+        let mixed: Vec<u8> = b_0.iter().zip(b_i.iter()).map(|(a, b)| a ^ b).collect();
+        b_i = Sha256::new().chain_update(mixed).chain_update([i as u8]).chain_update(&dst_prime).finalize();
+        uniform_bytes.extend_from_slice(&b_i);
+    }
+    uniform_bytes.truncate(len_in_bytes);
+    uniform_bytes
+}
+
+/// Bytes hashed per element of `F`
+fn bytes_per_element<F: PrimeField>() -> usize {
+    (F::MODULUS_BIT_SIZE as usize + SAMPLING_SECURITY).div_ceil(8)
+}
+
+/// `hash_to_field` of RFC 9380 for a prime field, `count` elements
+pub fn hash_to_field<F: PrimeField>(message: &[u8], dst: &[u8], count: usize) -> Vec<F> {
+    let len = bytes_per_element::<F>();
+    expand_message_xmd(message, dst, count * len)
+        .chunks(len)
+        .map(F::from_be_bytes_mod_order)
+        .collect()
+}
+
+/// Stream of field elements determined by a seed and a domain tag
+#[derive(Debug, Clone)]
+pub struct SeededSampler {
+    dst: Vec<u8>,
+    seed: Vec<u8>,
+    counter: u64,
+}
+
+impl SeededSampler {
+    pub fn new(dst: &[u8], seed: &[u8]) -> Self {
+        Self { dst: dst.to_vec(), seed: seed.to_vec(), counter: 0 }
+    }
+
+    /// Number of elements drawn so far
+    pub fn position(&self) -> u64 {
+        self.counter
+    }
+
+    /// The next element of the stream
+    pub fn next_field<F: PrimeField>(&mut self) -> F {
+        let message = [self.seed.as_slice(), &self.counter.to_be_bytes()].concat();
+        self.counter += 1;
+        hash_to_field(&message, &self.dst, 1)[0]
+    }
+
+    /// The next `count` elements of the stream
+    pub fn fields<F: PrimeField>(&mut self, count: usize) -> Vec<F> {
+        (0..count).map(|_| self.next_field()).collect()
+    }
+}
+
+#[cfg(all(test, feature = "worker"))]
+mod tests {
+    use super::*;
+    use crate::mpc::{SecretSharing, ShamirSecretSharing};
+    use ark_bls12_381::Fr;
+
+    type TestField = Fr;
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    #[test]
+    fn test_sampling_matches_rfc_and_is_reproducible() {
+        // RFC 9380, appendix K.1
+        let dst = b"QUUX-V01-CS02-with-expander-SHA256-128";
+        assert_eq!(
+            hex(&expand_message_xmd(b"", dst, 0x20)),
+            "68a985b87eb6b46952128911f2a4412bbc302a9d759667f87f7a21d803f07235"
+        );
+        assert_eq!(
+            hex(&expand_message_xmd(b"abc", dst, 0x20)),
+            "d8ccab23b5985ccea865c6c97b6e5b8350e794e603b4b97902f53a8a0d605615"
+        );
+        assert_eq!(expand_message_xmd(b"abc", dst, 0x80).len(), 0x80);
+
+        let mut first = SeededSampler::new(b"EOS-test", &[1, 2, 3]);
+        let mut second = SeededSampler::new(b"EOS-test", &[1, 2, 3]);
+        let drawn: Vec<TestField> = first.fields(4);
+        assert_eq!(drawn, second.fields::<TestField>(4));
+        assert_eq!(first.position(), 4);
+        assert_eq!(drawn[2], hash_to_field(&[1, 2, 3, 0, 0, 0, 0, 0, 0, 0, 2], b"EOS-test", 1)[0]);
+        assert_ne!(drawn[0], drawn[1]);
+        assert_ne!(drawn[0], SeededSampler::new(b"EOS-other", &[1, 2, 3]).next_field::<TestField>());
+
+        // Seeded sharing reproduces the same shares
+        let share = |seed: &[u8]| {
+            ShamirSecretSharing::share_secret_seeded(TestField::from(5u64), 3, 4, &mut SeededSampler::new(b"EOS-test", seed))
+        };
+        let values: Vec<TestField> = share(&[9]).iter().map(|s| s.value).collect();
+        assert_eq!(values, share(&[9]).iter().map(|s| s.value).collect::<Vec<_>>());
+        assert_eq!(ShamirSecretSharing::reconstruct_secret(&share(&[9])[..3]).unwrap(), TestField::from(5u64));
+    }
+}
@@ -0,0 +1,1009 @@
+//! Byte encodings for protocol messages
+//!
+//! This module defines the wire format used for shares, proofs and circuits
+//! exchanged between the delegator and the workers. Decoding treats its input
+//! as untrusted: every length prefix is checked against the remaining input
+//! before allocating, field and group elements are validated, and circuit
+//! variable indices are bounds-checked before a value is handed out.
+
+use ark_ec::{pairing::Pairing, CurveGroup};
+use ark_ff::{BigInteger, Field, PrimeField};
+use ark_poly::{DenseUVPolynomial, univariate::DensePolynomial};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use std::collections::BTreeSet;
+#[cfg(feature = "worker")]
+use std::collections::HashMap;
+
+use crate::circuit::{
+    AggregatedOpeningProof, KZGCommitmentScheme, MerkleCommitment, MerkleOpening, MultilinearOpeningProof, OpeningProof,
+    PolynomialCommitment,
+};
+use crate::custom_circuits::{CustomCircuit, Variable};
+#[cfg(feature = "worker")]
+use crate::mpc::{
+    AdditiveShare, BeaverTriple, MaterialKind, PreprocessingLedger, PreprocessingStore, SecretSharing,
+    SessionAllocation, ShamirShare, ThresholdConfig, ThresholdPreprocessing,
+};
+use crate::piop::{
+    ConstraintClaims, ExtensionSumcheckProof, MaskOpening, PolynomialConsistencyProof, SumcheckMask, SumcheckProof,
+};
+use crate::protocol::{CircuitParameters, ConstraintMatrices};
+
+/// Types with a canonical byte encoding
+pub trait WireFormat: Sized {
+    /// Append the encoding of `self` to `out`
+    fn encode(&self, out: &mut Vec<u8>);
+
+    /// Decode a value from the reader's current position
+    fn decode(reader: &mut ByteReader<'_>) -> Result<Self, DecodeError>;
+
+    /// Encode into a fresh buffer
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        self.encode(&mut out);
+        out
+    }
+
+    /// Decode from a complete buffer, rejecting trailing bytes
+    fn from_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut reader = ByteReader::new(bytes);
+        let value = Self::decode(&mut reader)?;
+        reader.finish()?;
+        Ok(value)
+    }
+}
+
+/// Cursor over untrusted input bytes
+pub struct ByteReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, position: 0 }
+    }
+
+    /// Number of unread bytes
+    pub fn remaining(&self) -> usize {
+        self.bytes.len() - self.position
+    }
+
+    /// Read exactly `len` bytes
+    pub fn read_bytes(&mut self, len: usize) -> Result<&'a [u8], DecodeError> {
+        if len > self.remaining() {
+            return Err(DecodeError::UnexpectedEnd);
+        }
+        let slice = &self.bytes[self.position..self.position + len];
+        self.position += len;
+        Ok(slice)
+    }
+
+    pub fn read_u8(&mut self) -> Result<u8, DecodeError> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    pub fn read_u32(&mut self) -> Result<u32, DecodeError> {
+        let bytes = self.read_bytes(4)?;
+        Ok(u32::from_le_bytes(bytes.try_into().expect("slice has length 4")))
+    }
+
+    pub fn read_u64(&mut self) -> Result<u64, DecodeError> {
+        let bytes = self.read_bytes(8)?;
+        Ok(u64::from_le_bytes(bytes.try_into().expect("slice has length 8")))
+    }
+
+    /// Read a `u64` that must fit in `usize`
+    pub fn read_usize(&mut self) -> Result<usize, DecodeError> {
+        let value = self.read_u64()?;
+        usize::try_from(value).map_err(|_| DecodeError::LengthOutOfRange(value))
+    }
+
+    /// Read a collection length, rejecting lengths whose elements (each at
+    /// least `min_element_size` bytes) could not fit in the remaining input
+    pub fn read_len(&mut self, min_element_size: usize) -> Result<usize, DecodeError> {
+        let len = self.read_u32()? as usize;
+        if len.saturating_mul(min_element_size.max(1)) > self.remaining() {
+            return Err(DecodeError::LengthOutOfRange(len as u64));
+        }
+        Ok(len)
+    }
+
+    /// Read a field element in compressed canonical form
+    pub fn read_field<F: Field>(&mut self) -> Result<F, DecodeError> {
+        let bytes = self.read_bytes(field_size::<F>())?;
+        F::deserialize_compressed(bytes).map_err(|_| DecodeError::InvalidFieldElement)
+    }
+
+    /// Read a compressed curve point, checking it lies in the prime-order subgroup
+    pub fn read_point<G: CurveGroup>(&mut self) -> Result<G::Affine, DecodeError> {
+        let bytes = self.read_bytes(point_size::<G>())?;
+        G::Affine::deserialize_compressed(bytes).map_err(|_| DecodeError::InvalidGroupElement)
+    }
+
+    /// Ensure the whole input was consumed
+    pub fn finish(self) -> Result<(), DecodeError> {
+        match self.remaining() {
+            0 => Ok(()),
+            extra => Err(DecodeError::TrailingBytes(extra)),
+        }
+    }
+}
+
+/// Encoded size of a field element
+pub fn field_size<F: Field>() -> usize {
+    F::zero().compressed_size()
+}
+
+/// Encoded size of a curve point
+pub fn point_size<G: CurveGroup>() -> usize {
+    G::Affine::default().compressed_size()
+}
+
+/// Byte order of a field element encoding
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endianness {
+    Little,
+    Big,
+}
+
+/// Canonical fixed-width encoding of prime field elements
+///
+/// An element is written as its integer representative in `[0, p)`,
+/// zero-padded to `len` bytes in the chosen byte order. Transcripts, the
+/// wire format and on-chain export all go through this type, so another
+/// implementation only needs the endianness and width to interoperate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldEncoding {
+    pub endianness: Endianness,
+    pub len: usize,
+}
+
+impl FieldEncoding {
+    /// 32-byte big-endian words, as EVM verifiers expect
+    pub const EVM_WORD: FieldEncoding = FieldEncoding { endianness: Endianness::Big, len: 32 };
+
+    pub const fn new(endianness: Endianness, len: usize) -> Self {
+        Self { endianness, len }
+    }
+
+    /// Shortest width that holds every element of `F`
+    pub fn minimal<F: PrimeField>(endianness: Endianness) -> Self {
+        Self::new(endianness, (F::MODULUS_BIT_SIZE as usize).div_ceil(8))
+    }
+
+    /// Encoding of `write_field` for prime fields: little endian, minimal width
+    pub fn wire<F: PrimeField>() -> Self {
+        Self::minimal::<F>(Endianness::Little)
+    }
+
+    /// Append the encoding of `value`; panics if `len` is too short for `F`
+    pub fn encode_into<F: PrimeField>(&self, out: &mut Vec<u8>, value: &F) {
+        let mut bytes = value.into_bigint().to_bytes_le();
+        assert!(
+            bytes.iter().skip(self.len).all(|byte| *byte == 0),
+            "field element does not fit in {} bytes",
+            self.len
+        );
+        bytes.resize(self.len, 0);
+        if self.endianness == Endianness::Big {
+            bytes.reverse();
+        }
+        out.extend_from_slice(&bytes);
+    }
+
+    pub fn encode<F: PrimeField>(&self, value: &F) -> Vec<u8> {
+        let mut out = Vec::with_capacity(self.len);
+        self.encode_into(&mut out, value);
+        out
+    }
+
+    /// Decode exactly `len` bytes, rejecting values not below the modulus
+    pub fn decode<F: PrimeField>(&self, bytes: &[u8]) -> Result<F, DecodeError> {
+        match bytes.len().cmp(&self.len) {
+            std::cmp::Ordering::Less => return Err(DecodeError::UnexpectedEnd),
+            std::cmp::Ordering::Greater => return Err(DecodeError::TrailingBytes(bytes.len() - self.len)),
+            std::cmp::Ordering::Equal => {}
+        }
+        // Canonical iff re-encoding the reduced value gives the input back
+        let value = self.reduce(bytes);
+        if self.len < (F::MODULUS_BIT_SIZE as usize).div_ceil(8) || self.encode(&value) != bytes {
+            return Err(DecodeError::InvalidFieldElement);
+        }
+        Ok(value)
+    }
+
+    /// Read one element from the reader's current position
+    pub fn read<F: PrimeField>(&self, reader: &mut ByteReader<'_>) -> Result<F, DecodeError> {
+        self.decode(reader.read_bytes(self.len)?)
+    }
+
+    /// Interpret bytes of any length in this byte order, reduced modulo `p`
+    ///
+    /// For hashing to the field; use `decode` for untrusted encodings.
+    pub fn reduce<F: PrimeField>(&self, bytes: &[u8]) -> F {
+        match self.endianness {
+            Endianness::Little => F::from_le_bytes_mod_order(bytes),
+            Endianness::Big => F::from_be_bytes_mod_order(bytes),
+        }
+    }
+}
+
+pub fn write_u32(out: &mut Vec<u8>, value: u32) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+pub fn write_u64(out: &mut Vec<u8>, value: u64) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+/// Write a collection length prefix
+pub fn write_len(out: &mut Vec<u8>, len: usize) {
+    let len = u32::try_from(len).expect("collection too large for the wire format");
+    write_u32(out, len);
+}
+
+pub fn write_field<F: Field>(out: &mut Vec<u8>, value: &F) {
+    value
+        .serialize_compressed(&mut *out)
+        .expect("serializing into a Vec cannot fail");
+}
+
+pub fn write_point<G: CurveGroup>(out: &mut Vec<u8>, point: &G::Affine) {
+    point
+        .serialize_compressed(&mut *out)
+        .expect("serializing into a Vec cannot fail");
+}
+
+fn write_field_vec<F: Field>(out: &mut Vec<u8>, values: &[F]) {
+    write_len(out, values.len());
+    for value in values {
+        write_field(out, value);
+    }
+}
+
+fn read_field_vec<F: Field>(reader: &mut ByteReader<'_>) -> Result<Vec<F>, DecodeError> {
+    let len = reader.read_len(field_size::<F>())?;
+    (0..len).map(|_| reader.read_field()).collect()
+}
+
+fn write_vec<T: WireFormat>(out: &mut Vec<u8>, items: &[T]) {
+    write_len(out, items.len());
+    for item in items {
+        item.encode(out);
+    }
+}
+
+fn read_vec<T: WireFormat>(
+    reader: &mut ByteReader<'_>,
+    min_element_size: usize,
+) -> Result<Vec<T>, DecodeError> {
+    let len = reader.read_len(min_element_size)?;
+    (0..len).map(|_| T::decode(reader)).collect()
+}
+
+#[cfg(feature = "worker")]
+impl<F: Field> WireFormat for ShamirShare<F> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        write_u64(out, self.index as u64);
+        write_field(out, &self.value);
+    }
+
+    fn decode(reader: &mut ByteReader<'_>) -> Result<Self, DecodeError> {
+        Ok(ShamirShare {
+            index: reader.read_usize()?,
+            value: reader.read_field()?,
+        })
+    }
+}
+
+#[cfg(feature = "worker")]
+impl<F: Field> WireFormat for AdditiveShare<F> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        write_u64(out, self.party_id as u64);
+        write_field(out, &self.value);
+    }
+
+    fn decode(reader: &mut ByteReader<'_>) -> Result<Self, DecodeError> {
+        Ok(AdditiveShare {
+            party_id: reader.read_usize()?,
+            value: reader.read_field()?,
+        })
+    }
+}
+
+impl<F: Field> WireFormat for DensePolynomial<F> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        write_field_vec(out, &self.coeffs);
+    }
+
+    fn decode(reader: &mut ByteReader<'_>) -> Result<Self, DecodeError> {
+        Ok(DensePolynomial::from_coefficients_vec(read_field_vec(reader)?))
+    }
+}
+
+impl<F: Field> WireFormat for SumcheckProof<F> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        write_vec(out, &self.round_polynomials);
+        write_field_vec(out, &self.challenges);
+        write_field(out, &self.final_evaluation);
+    }
+
+    fn decode(reader: &mut ByteReader<'_>) -> Result<Self, DecodeError> {
+        Ok(SumcheckProof {
+            round_polynomials: read_vec(reader, 4)?,
+            challenges: read_field_vec(reader)?,
+            final_evaluation: reader.read_field()?,
+        })
+    }
+}
+
+impl<K: Field> WireFormat for ExtensionSumcheckProof<K> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        write_len(out, self.rounds.len());
+        for repetition in &self.rounds {
+            let flat: Vec<K> = repetition.iter().flatten().copied().collect();
+            write_field_vec(out, &flat);
+        }
+    }
+
+    fn decode(reader: &mut ByteReader<'_>) -> Result<Self, DecodeError> {
+        let repetitions = reader.read_len(4)?;
+        let rounds = (0..repetitions)
+            .map(|_| {
+                let flat: Vec<K> = read_field_vec(reader)?;
+                if !flat.len().is_multiple_of(2) {
+                    return Err(DecodeError::LengthOutOfRange(flat.len() as u64));
+                }
+                Ok(flat.chunks(2).map(|pair| [pair[0], pair[1]]).collect())
+            })
+            .collect::<Result<Vec<_>, DecodeError>>()?;
+        Ok(ExtensionSumcheckProof { rounds })
+    }
+}
+
+impl<G: CurveGroup> WireFormat for PolynomialCommitment<G> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        write_point::<G>(out, &self.commitment);
+    }
+
+    fn decode(reader: &mut ByteReader<'_>) -> Result<Self, DecodeError> {
+        Ok(PolynomialCommitment {
+            commitment: reader.read_point::<G>()?,
+        })
+    }
+}
+
+/// The crate's SRS file format: the G2 verification key, then the powers of g
+///
+/// The powers come last so that a prefix of them can be read without
+/// touching the rest of the file; see `srs::load_prefix`.
+impl<E: Pairing> WireFormat for KZGCommitmentScheme<E> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        write_point::<E::G2>(out, &self.verification_key.0);
+        write_point::<E::G2>(out, &self.verification_key.1);
+        write_len(out, self.powers_of_g.len());
+        for power in &self.powers_of_g {
+            write_point::<E::G1>(out, power);
+        }
+    }
+
+    fn decode(reader: &mut ByteReader<'_>) -> Result<Self, DecodeError> {
+        let verification_key = (reader.read_point::<E::G2>()?, reader.read_point::<E::G2>()?);
+        let len = reader.read_len(point_size::<E::G1>())?;
+        if len == 0 {
+            return Err(DecodeError::LengthOutOfRange(0));
+        }
+        let powers_of_g = (0..len).map(|_| reader.read_point::<E::G1>()).collect::<Result<Vec<_>, _>>()?;
+        Ok(KZGCommitmentScheme::from_parts(powers_of_g, verification_key))
+    }
+}
+
+impl<F: Field, G: CurveGroup> WireFormat for OpeningProof<F, G> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        write_point::<G>(out, &self.proof);
+        write_field(out, &self.evaluation);
+        write_field(out, &self.point);
+    }
+
+    fn decode(reader: &mut ByteReader<'_>) -> Result<Self, DecodeError> {
+        Ok(OpeningProof {
+            proof: reader.read_point::<G>()?,
+            evaluation: reader.read_field()?,
+            point: reader.read_field()?,
+        })
+    }
+}
+
+impl<F: Field, G: CurveGroup> WireFormat for AggregatedOpeningProof<F, G> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        write_point::<G>(out, &self.proof);
+        write_field_vec(out, &self.evaluations);
+        write_field(out, &self.point);
+    }
+
+    fn decode(reader: &mut ByteReader<'_>) -> Result<Self, DecodeError> {
+        Ok(AggregatedOpeningProof {
+            proof: reader.read_point::<G>()?,
+            evaluations: read_field_vec(reader)?,
+            point: reader.read_field()?,
+        })
+    }
+}
+
+impl<F: Field, G: CurveGroup> WireFormat for MultilinearOpeningProof<F, G> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        write_vec(out, &self.folds);
+        write_vec(out, &self.openings);
+        write_field(out, &self.evaluation);
+        write_field_vec(out, &self.point);
+    }
+
+    fn decode(reader: &mut ByteReader<'_>) -> Result<Self, DecodeError> {
+        Ok(MultilinearOpeningProof {
+            folds: read_vec(reader, point_size::<G>())?,
+            openings: read_vec(reader, point_size::<G>())?,
+            evaluation: reader.read_field()?,
+            point: read_field_vec(reader)?,
+        })
+    }
+}
+
+impl<F: Field, G: CurveGroup> WireFormat for ConstraintClaims<F, G> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        for evaluation in &self.row_evaluations {
+            write_field(out, evaluation);
+        }
+        self.witness_opening.encode(out);
+        write_vec(out, &self.masks);
+    }
+
+    fn decode(reader: &mut ByteReader<'_>) -> Result<Self, DecodeError> {
+        Ok(ConstraintClaims {
+            row_evaluations: [reader.read_field()?, reader.read_field()?, reader.read_field()?],
+            witness_opening: MultilinearOpeningProof::decode(reader)?,
+            masks: read_vec(reader, point_size::<G>() + field_size::<F>())?,
+        })
+    }
+}
+
+impl<F: Field, G: CurveGroup> WireFormat for MaskOpening<F, G> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.mask.commitment.encode(out);
+        write_field(out, &self.mask.sum);
+        self.opening.encode(out);
+    }
+
+    fn decode(reader: &mut ByteReader<'_>) -> Result<Self, DecodeError> {
+        Ok(MaskOpening {
+            mask: SumcheckMask { commitment: PolynomialCommitment::decode(reader)?, sum: reader.read_field()? },
+            opening: MultilinearOpeningProof::decode(reader)?,
+        })
+    }
+}
+
+impl WireFormat for MerkleCommitment {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.root);
+        write_u64(out, self.len as u64);
+    }
+
+    fn decode(reader: &mut ByteReader<'_>) -> Result<Self, DecodeError> {
+        Ok(MerkleCommitment {
+            root: reader.read_bytes(32)?.try_into().expect("slice has length 32"),
+            len: reader.read_usize()?,
+        })
+    }
+}
+
+impl<F: Field> WireFormat for MerkleOpening<F> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        write_u64(out, self.index as u64);
+        write_field(out, &self.value);
+        write_len(out, self.siblings.len());
+        for sibling in &self.siblings {
+            out.extend_from_slice(sibling);
+        }
+    }
+
+    fn decode(reader: &mut ByteReader<'_>) -> Result<Self, DecodeError> {
+        let index = reader.read_usize()?;
+        let value = reader.read_field()?;
+        let num_siblings = reader.read_len(32)?;
+        let siblings = (0..num_siblings)
+            .map(|_| Ok(reader.read_bytes(32)?.try_into().expect("slice has length 32")))
+            .collect::<Result<Vec<_>, DecodeError>>()?;
+        Ok(MerkleOpening { index, value, siblings })
+    }
+}
+
+impl<F: Field, G: CurveGroup> WireFormat for PolynomialConsistencyProof<F, G> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        write_vec(out, &self.witness_commitments);
+        write_vec(out, &self.consistency_proofs);
+        write_vec(out, &self.sumcheck_proofs);
+        // The constraint claims are optional and encoded as a list of at most one
+        write_vec(out, self.constraint_claims.as_slice());
+    }
+
+    fn decode(reader: &mut ByteReader<'_>) -> Result<Self, DecodeError> {
+        let witness_commitments = read_vec(reader, point_size::<G>())?;
+        let consistency_proofs = read_vec(reader, point_size::<G>())?;
+        let sumcheck_proofs = read_vec(reader, 8)?;
+        let mut claims: Vec<ConstraintClaims<F, G>> = read_vec(reader, 3 * field_size::<F>())?;
+        if claims.len() > 1 {
+            return Err(DecodeError::LengthOutOfRange(claims.len() as u64));
+        }
+        Ok(PolynomialConsistencyProof {
+            witness_commitments,
+            consistency_proofs,
+            sumcheck_proofs,
+            constraint_claims: claims.pop(),
+        })
+    }
+}
+
+fn write_constraints(out: &mut Vec<u8>, constraints: &[(Variable, Variable, Variable)], num_instance: usize) {
+    write_len(out, constraints.len());
+    for &(a, b, c) in constraints {
+        for variable in [a, b, c] {
+            write_u64(out, variable.index(num_instance) as u64);
+        }
+    }
+}
+
+fn read_constraints(
+    reader: &mut ByteReader<'_>,
+    num_instance: usize,
+    num_witnesses: usize,
+) -> Result<Vec<(Variable, Variable, Variable)>, DecodeError> {
+    let len = reader.read_len(24)?;
+    let mut constraints = Vec::with_capacity(len);
+    for _ in 0..len {
+        let mut read_index = || -> Result<Variable, DecodeError> {
+            let index = reader.read_usize()?;
+            if index >= num_instance + num_witnesses {
+                return Err(DecodeError::InvalidVariableIndex(index));
+            }
+            Ok(Variable::from_index(index, num_instance))
+        };
+        constraints.push((read_index()?, read_index()?, read_index()?));
+    }
+    Ok(constraints)
+}
+
+impl<F: PrimeField> WireFormat for CustomCircuit<F> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        write_len(out, self.name.len());
+        out.extend_from_slice(self.name.as_bytes());
+        write_field_vec(out, &self.private_witnesses);
+        write_field_vec(out, &self.public_inputs);
+        let num_instance = self.num_instance_variables();
+        write_constraints(out, &self.multiplication_constraints, num_instance);
+        write_constraints(out, &self.addition_constraints, num_instance);
+        write_len(out, self.delegator_only.len());
+        for &index in &self.delegator_only {
+            write_u64(out, index as u64);
+        }
+    }
+
+    fn decode(reader: &mut ByteReader<'_>) -> Result<Self, DecodeError> {
+        let name_len = reader.read_len(1)?;
+        let name = std::str::from_utf8(reader.read_bytes(name_len)?)
+            .map_err(|_| DecodeError::InvalidUtf8)?
+            .to_string();
+        let private_witnesses: Vec<F> = read_field_vec(reader)?;
+        let public_inputs: Vec<F> = read_field_vec(reader)?;
+
+        // Constraint indices use the ark-relations layout: the constant one,
+        // the public inputs, then the private witnesses
+        let num_instance = public_inputs.len() + 1;
+        let num_variables = private_witnesses.len() + public_inputs.len();
+        let multiplication_constraints = read_constraints(reader, num_instance, private_witnesses.len())?;
+        let addition_constraints = read_constraints(reader, num_instance, private_witnesses.len())?;
+        // Delegator-only witness positions, strictly increasing
+        let num_local = reader.read_len(8)?;
+        let mut delegator_only = BTreeSet::new();
+        for _ in 0..num_local {
+            let index = reader.read_usize()?;
+            if index >= private_witnesses.len() {
+                return Err(DecodeError::InvalidVariableIndex(index));
+            }
+            if delegator_only.last().is_some_and(|&last| last >= index) {
+                return Err(DecodeError::DuplicateEntry);
+            }
+            delegator_only.insert(index);
+        }
+
+        Ok(CustomCircuit {
+            name,
+            num_constraints: multiplication_constraints.len() + addition_constraints.len(),
+            num_variables,
+            private_witnesses,
+            public_inputs,
+            multiplication_constraints,
+            addition_constraints,
+            delegator_only,
+        })
+    }
+}
+
+type SparseMatrix<F> = Vec<Vec<(usize, F)>>;
+
+fn write_matrix<F: Field>(out: &mut Vec<u8>, matrix: &SparseMatrix<F>) {
+    write_len(out, matrix.len());
+    for row in matrix {
+        write_len(out, row.len());
+        for (column, coefficient) in row {
+            write_u64(out, *column as u64);
+            write_field(out, coefficient);
+        }
+    }
+}
+
+/// Read a sparse matrix of at most `num_rows` rows over `num_variables` columns
+fn read_matrix<F: Field>(
+    reader: &mut ByteReader<'_>,
+    num_rows: usize,
+    num_variables: usize,
+) -> Result<SparseMatrix<F>, DecodeError> {
+    let len = reader.read_len(4)?;
+    if len > num_rows {
+        return Err(DecodeError::LengthOutOfRange(len as u64));
+    }
+    let entry_size = 8 + field_size::<F>();
+    (0..len)
+        .map(|_| {
+            let num_entries = reader.read_len(entry_size)?;
+            (0..num_entries)
+                .map(|_| {
+                    let column = reader.read_usize()?;
+                    if column >= num_variables {
+                        return Err(DecodeError::InvalidVariableIndex(column));
+                    }
+                    Ok((column, reader.read_field()?))
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Only the counts and matrices are encoded; verifiers pin the result to
+/// a published `circuit_fingerprint` before trusting it
+impl<F: Field> WireFormat for CircuitParameters<F> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        for count in [self.num_constraints, self.num_variables, self.num_public_inputs] {
+            write_u64(out, count as u64);
+        }
+        let matrices = &self.constraint_matrices;
+        for matrix in [&matrices.a_matrix, &matrices.b_matrix, &matrices.c_matrix] {
+            write_matrix(out, matrix);
+        }
+    }
+
+    fn decode(reader: &mut ByteReader<'_>) -> Result<Self, DecodeError> {
+        let num_constraints = reader.read_usize()?;
+        let num_variables = reader.read_usize()?;
+        let num_public_inputs = reader.read_usize()?;
+        // The constant one and the public inputs are variables too
+        if num_public_inputs >= num_variables {
+            return Err(DecodeError::InvalidVariableIndex(num_public_inputs));
+        }
+        let constraint_matrices = ConstraintMatrices {
+            a_matrix: read_matrix(reader, num_constraints, num_variables)?,
+            b_matrix: read_matrix(reader, num_constraints, num_variables)?,
+            c_matrix: read_matrix(reader, num_constraints, num_variables)?,
+        };
+        Ok(CircuitParameters { num_constraints, num_variables, num_public_inputs, constraint_matrices })
+    }
+}
+
+#[cfg(feature = "worker")]
+impl<S: WireFormat> WireFormat for BeaverTriple<S> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        write_vec(out, &self.a);
+        write_vec(out, &self.b);
+        write_vec(out, &self.c);
+    }
+
+    fn decode(reader: &mut ByteReader<'_>) -> Result<Self, DecodeError> {
+        Ok(BeaverTriple {
+            a: read_vec(reader, 1)?,
+            b: read_vec(reader, 1)?,
+            c: read_vec(reader, 1)?,
+        })
+    }
+}
+
+#[cfg(feature = "worker")]
+fn material_tag(kind: MaterialKind) -> u8 {
+    match kind {
+        MaterialKind::Triple => 0,
+        MaterialKind::Bit => 1,
+    }
+}
+
+#[cfg(feature = "worker")]
+fn read_material_kind(reader: &mut ByteReader<'_>) -> Result<MaterialKind, DecodeError> {
+    match reader.read_u8()? {
+        0 => Ok(MaterialKind::Triple),
+        1 => Ok(MaterialKind::Bit),
+        tag => Err(DecodeError::InvalidTag(tag)),
+    }
+}
+
+#[cfg(feature = "worker")]
+/// Encodes the material together with its ledger, so a reloaded store
+/// still refuses items consumed before it was persisted
+impl<F: Field, SS: SecretSharing<F>> WireFormat for PreprocessingStore<F, SS>
+where
+    SS::Share: WireFormat,
+{
+    fn encode(&self, out: &mut Vec<u8>) {
+        write_vec(out, &self.triples);
+        write_len(out, self.bits.len());
+        for bit in &self.bits {
+            write_vec(out, bit);
+        }
+
+        let ledger = &self.ledger;
+        write_u64(out, ledger.next_triple as u64);
+        write_u64(out, ledger.next_bit as u64);
+        let mut allocations: Vec<_> = ledger.allocations.iter().collect();
+        allocations.sort_by_key(|(session, _)| **session);
+        write_len(out, allocations.len());
+        for (session, allocation) in allocations {
+            write_u64(out, *session);
+            for bound in [allocation.triples.start, allocation.triples.end, allocation.bits.start, allocation.bits.end] {
+                write_u64(out, bound as u64);
+            }
+        }
+        let mut consumed: Vec<_> = ledger.consumed.iter().collect();
+        consumed.sort_by_key(|((kind, index), _)| (material_tag(*kind), *index));
+        write_len(out, consumed.len());
+        for ((kind, index), session) in consumed {
+            out.push(material_tag(*kind));
+            write_u64(out, *index as u64);
+            write_u64(out, *session);
+        }
+    }
+
+    fn decode(reader: &mut ByteReader<'_>) -> Result<Self, DecodeError> {
+        let triples: Vec<BeaverTriple<SS::Share>> = read_vec(reader, 12)?;
+        let num_bits = reader.read_len(4)?;
+        let bits = (0..num_bits)
+            .map(|_| read_vec(reader, 1))
+            .collect::<Result<Vec<Vec<SS::Share>>, _>>()?;
+
+        // Every recorded range and index must stay inside the material
+        let check = |value: usize, limit: usize| {
+            if value > limit {
+                Err(DecodeError::LengthOutOfRange(value as u64))
+            } else {
+                Ok(value)
+            }
+        };
+        let next_triple = check(reader.read_usize()?, triples.len())?;
+        let next_bit = check(reader.read_usize()?, bits.len())?;
+
+        let num_allocations = reader.read_len(40)?;
+        let mut allocations = HashMap::new();
+        for _ in 0..num_allocations {
+            let session = reader.read_u64()?;
+            let triples_start = reader.read_usize()?;
+            let triples_end = check(reader.read_usize()?, next_triple)?;
+            let bits_start = reader.read_usize()?;
+            let bits_end = check(reader.read_usize()?, next_bit)?;
+            let allocation = SessionAllocation {
+                triples: check(triples_start, triples_end)?..triples_end,
+                bits: check(bits_start, bits_end)?..bits_end,
+            };
+            allocations.insert(session, allocation);
+        }
+
+        let num_consumed = reader.read_len(17)?;
+        let mut consumed = HashMap::new();
+        for _ in 0..num_consumed {
+            let kind = read_material_kind(reader)?;
+            let limit = match kind {
+                MaterialKind::Triple => next_triple,
+                MaterialKind::Bit => next_bit,
+            };
+            let index = reader.read_usize()?;
+            if index >= limit {
+                return Err(DecodeError::LengthOutOfRange(index as u64));
+            }
+            consumed.insert((kind, index), reader.read_u64()?);
+        }
+
+        Ok(PreprocessingStore {
+            triples,
+            bits,
+            ledger: PreprocessingLedger { next_triple, next_bit, allocations, consumed },
+        })
+    }
+}
+
+#[cfg(feature = "worker")]
+impl<F: Field, SS: SecretSharing<F>> WireFormat for ThresholdPreprocessing<F, SS>
+where
+    SS::Share: WireFormat,
+{
+    fn encode(&self, out: &mut Vec<u8>) {
+        write_len(out, self.pools.len());
+        for (config, pool) in &self.pools {
+            write_u64(out, config.threshold as u64);
+            write_u64(out, config.num_parties as u64);
+            pool.encode(out);
+        }
+    }
+
+    fn decode(reader: &mut ByteReader<'_>) -> Result<Self, DecodeError> {
+        let num_pools = reader.read_len(16)?;
+        let mut material = ThresholdPreprocessing::default();
+        for _ in 0..num_pools {
+            let config = ThresholdConfig::new(reader.read_usize()?, reader.read_usize()?);
+            let pool = PreprocessingStore::decode(reader)?;
+            // Sessions are rebuilt from the pools' ledgers, so each session
+            // must appear in one pool only
+            for &session in pool.ledger.allocations.keys() {
+                if material.sessions.insert(session, config).is_some() {
+                    return Err(DecodeError::DuplicateEntry);
+                }
+            }
+            if material.pools.insert(config, pool).is_some() {
+                return Err(DecodeError::DuplicateEntry);
+            }
+        }
+        Ok(material)
+    }
+}
+
+/// Decoding error types
+#[derive(Debug, Clone, PartialEq)]
+pub enum DecodeError {
+    UnexpectedEnd,
+    TrailingBytes(usize),
+    LengthOutOfRange(u64),
+    InvalidFieldElement,
+    InvalidGroupElement,
+    InvalidUtf8,
+    InvalidVariableIndex(usize),
+    InvalidTag(u8),
+    /// A key that must be unique appears twice
+    DuplicateEntry,
+    /// The input does not start with the format's magic bytes
+    InvalidMagic,
+}
+
+impl std::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            DecodeError::UnexpectedEnd => write!(f, "Unexpected end of input"),
+            DecodeError::TrailingBytes(n) => write!(f, "{} trailing bytes after message", n),
+            DecodeError::LengthOutOfRange(len) => write!(f, "Length prefix {} exceeds input", len),
+            DecodeError::InvalidFieldElement => write!(f, "Non-canonical field element"),
+            DecodeError::InvalidGroupElement => write!(f, "Invalid curve point"),
+            DecodeError::InvalidUtf8 => write!(f, "Invalid UTF-8 string"),
+            DecodeError::InvalidVariableIndex(i) => write!(f, "Variable index {} out of range", i),
+            DecodeError::InvalidTag(tag) => write!(f, "Unknown tag byte {}", tag),
+            DecodeError::DuplicateEntry => write!(f, "Duplicate entry in message"),
+            DecodeError::InvalidMagic => write!(f, "Missing magic bytes"),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+#[cfg(all(test, feature = "worker"))]
+mod tests {
+    use super::*;
+    use crate::circuit::{KZGCommitmentScheme, MerkleVectorCommitment};
+    use crate::custom_circuits::CircuitTemplates;
+    use crate::mpc::{SecretSharing, ShamirSecretSharing};
+    use crate::piop::{ConsistencyChecker, TranscriptHash};
+    use ark_bls12_381::{Bls12_381, Fr, G1Projective};
+    use ark_std::test_rng;
+
+    type TestField = Fr;
+    type TestGroup = G1Projective;
+
+    #[test]
+    fn test_field_encoding() {
+        let value = -TestField::from(2u64);
+        let wire = FieldEncoding::wire::<TestField>();
+        let mut compressed = Vec::new();
+        write_field(&mut compressed, &value);
+        assert_eq!(wire.encode(&value), compressed);
+        let fq = -ark_bls12_381::Fq::from(5u64);
+        let mut compressed_fq = Vec::new();
+        write_field(&mut compressed_fq, &fq);
+        assert_eq!(FieldEncoding::wire::<ark_bls12_381::Fq>().encode(&fq), compressed_fq);
+
+        // Big endian is the byte reversal, and wider words pad at the high end
+        let big = FieldEncoding::minimal::<TestField>(Endianness::Big).encode(&value);
+        assert_eq!(big.iter().rev().copied().collect::<Vec<u8>>(), compressed);
+        let word = FieldEncoding::EVM_WORD.encode(&TestField::from(258u64));
+        assert_eq!(&word[30..], &[1, 2]);
+        assert!(word[..30].iter().all(|byte| *byte == 0));
+        let padded = FieldEncoding::new(Endianness::Little, 40);
+        for encoding in [wire, FieldEncoding::EVM_WORD, padded] {
+            assert_eq!(encoding.decode::<TestField>(&encoding.encode(&value)), Ok(value));
+        }
+
+        // The modulus itself, short input and dirty padding are rejected
+        let mut modulus_be = TestField::MODULUS.to_bytes_be();
+        assert_eq!(FieldEncoding::EVM_WORD.decode::<TestField>(&modulus_be), Err(DecodeError::InvalidFieldElement));
+        modulus_be.pop();
+        assert_eq!(FieldEncoding::EVM_WORD.decode::<TestField>(&modulus_be), Err(DecodeError::UnexpectedEnd));
+        let mut dirty = padded.encode(&value);
+        dirty[39] = 1;
+        assert_eq!(padded.decode::<TestField>(&dirty), Err(DecodeError::InvalidFieldElement));
+        assert_eq!(FieldEncoding::EVM_WORD.reduce::<TestField>(&TestField::MODULUS.to_bytes_be()), TestField::from(0u64));
+    }
+
+    #[test]
+    fn test_share_and_proof_roundtrip() {
+        let mut rng = test_rng();
+        let shares = ShamirSecretSharing::<TestField>::share_secret(TestField::from(7u64), 2, 3, &mut rng);
+        for share in &shares {
+            let decoded = ShamirShare::<TestField>::from_bytes(&share.to_bytes()).unwrap();
+            assert_eq!(decoded.index, share.index);
+            assert_eq!(decoded.value, share.value);
+        }
+
+        let kzg = KZGCommitmentScheme::<Bls12_381>::setup(4, &mut rng);
+        let poly = DensePolynomial::from_coefficients_vec(vec![TestField::from(1u64), TestField::from(2u64)]);
+        let mut checker = ConsistencyChecker::<TestField>::new();
+        checker.add_witness_polynomial("w".to_string(), poly.clone());
+        let mut proof = checker.generate_consistency_proof().unwrap();
+        proof.witness_commitments.push(kzg.commit(&poly));
+        proof.consistency_proofs.push(kzg.open(&poly, TestField::from(3u64)));
+
+        let bytes = proof.to_bytes();
+        let decoded = PolynomialConsistencyProof::<TestField, TestGroup>::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded.to_bytes(), bytes);
+        assert!(checker.verify_consistency_proof(&decoded));
+
+        let merkle = MerkleVectorCommitment::<TestField>::new(TranscriptHash::Sha256);
+        let committed = merkle.commit(&[TestField::from(4u64), TestField::from(5u64), TestField::from(6u64)]);
+        let commitment = MerkleCommitment::from_bytes(&committed.commitment().to_bytes()).unwrap();
+        let opening = merkle.open(&committed, 1).unwrap();
+        let decoded = MerkleOpening::<TestField>::from_bytes(&opening.to_bytes()).unwrap();
+        assert_eq!(decoded, opening);
+        assert!(merkle.verify(&commitment, &decoded));
+    }
+
+    #[test]
+    fn test_malformed_input_is_rejected() {
+        let circuit = CircuitTemplates::square_root_verification(TestField::from(3u64), TestField::from(9u64));
+        let bytes = circuit.to_bytes();
+        assert!(CustomCircuit::<TestField>::from_bytes(&bytes).is_ok());
+
+        // Truncated input and trailing bytes
+        for cut in 0..bytes.len() {
+            assert!(CustomCircuit::<TestField>::from_bytes(&bytes[..cut]).is_err());
+        }
+        let mut extended = bytes.clone();
+        extended.push(0);
+        assert_eq!(
+            CustomCircuit::<TestField>::from_bytes(&extended).unwrap_err(),
+            DecodeError::TrailingBytes(1)
+        );
+
+        // Out-of-range variable index
+        let mut bad = circuit.clone();
+        bad.multiplication_constraints.push((Variable::ONE, Variable::ONE, Variable::Witness(99)));
+        assert_eq!(
+            CustomCircuit::<TestField>::from_bytes(&bad.to_bytes()).unwrap_err(),
+            DecodeError::InvalidVariableIndex(circuit.num_instance_variables() + 99)
+        );
+
+        // Length prefixes cannot trigger huge allocations
+        let huge = u32::MAX.to_le_bytes();
+        assert!(matches!(
+            SumcheckProof::<TestField>::from_bytes(&huge),
+            Err(DecodeError::LengthOutOfRange(_))
+        ));
+    }
+}
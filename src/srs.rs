@@ -0,0 +1,701 @@
+//! Registry of public structured reference strings
+//!
+//! A KZG setup sampled locally is only as trustworthy as the machine that
+//! sampled tau. Production deployments should instead take the powers of
+//! tau from a public multi-party ceremony, where tau stays unknown as long
+//! as one participant was honest. `SrsRegistry` lists such ceremony files,
+//! downloads them into a cache directory and converts them into a
+//! `KZGCommitmentScheme`, which the crate stores in its own `WireFormat`
+//! encoding.
+//!
+//! A file is only used once its SHA-256 checksum has been pinned and
+//! matches. The registry ships no checksums of its own: operators pin the
+//! digest published by the ceremony, so a compromised mirror cannot
+//! substitute its own file. Converted points are checked to lie in the
+//! prime-order subgroup.
+//!
+//! Downloading runs the `curl` binary, which must be on `PATH` at runtime;
+//! the crate links no HTTP client. Without it, `fetch` still serves a file
+//! placed at `cache_path` by hand once it matches the pinned checksum.
+//!
+//! `import_ptau` reads the `.ptau` files that snarkjs ceremonies publish,
+//! over either curve.
+//!
+//! Ceremony SRSs are far larger than most circuits need. `load_prefix`
+//! reads only the powers up to the degree a circuit commits to from a
+//! stored SRS, and reports the memory that leaving the rest on disk saved.
+
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use ark_bls12_381::{Bls12_381, Fq as BlsFq, Fq2 as BlsFq2, G1Affine as BlsG1Affine, G2Affine as BlsG2Affine};
+use ark_bn254::{Bn254, Fq as BnFq, Fq2 as BnFq2, G1Affine as BnG1Affine, G2Affine as BnG2Affine};
+use ark_ec::pairing::Pairing;
+use ark_ec::AffineRepr;
+use ark_ff::{BigInt, BigInteger, Field, PrimeField};
+use ark_serialize::{CanonicalDeserialize, Compress, Validate};
+use sha2::{Digest, Sha256};
+
+use crate::circuit::KZGCommitmentScheme;
+use crate::evaluation::MemoryStats;
+use crate::serialization::{point_size, ByteReader, DecodeError, WireFormat};
+
+/// Curve an SRS file is defined over
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SrsCurve {
+    Bn254,
+    Bls12_381,
+}
+
+/// Byte layout of a ceremony file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SrsLayout {
+    /// Aztec Ignition transcript: a manifest of seven big-endian u32s
+    /// (transcript number, total transcripts, total G1 points, total G2
+    /// points, G1 points, G2 points, start index), then G1 points from
+    /// tau^1 on, then G2 points from tau^1 on. Each base field element is
+    /// four big-endian u64 limbs, least significant limb first; G2
+    /// coordinates are written `c0` then `c1`. Generators are implicit.
+    AztecIgnition,
+    /// Zcash powers of tau of size `2^power`: a 64-byte BLAKE2b hash of the
+    /// previous contribution, `2^(power + 1) - 1` G1 powers from tau^0 on,
+    /// then `2^power` G2 powers from tau^0 on, all in the Zcash point
+    /// encoding, uncompressed in challenge files and compressed in
+    /// response files
+    ZcashPowersOfTau { compressed: bool, power: u32 },
+    /// snarkjs Powers of Tau (`.ptau`), see `import_ptau`
+    Ptau(SrsCurve),
+}
+
+impl SrsLayout {
+    fn curve(self) -> SrsCurve {
+        match self {
+            SrsLayout::AztecIgnition => SrsCurve::Bn254,
+            SrsLayout::ZcashPowersOfTau { .. } => SrsCurve::Bls12_381,
+            SrsLayout::Ptau(curve) => curve,
+        }
+    }
+}
+
+const IGNITION_MANIFEST_LEN: usize = 28;
+const ZCASH_HEADER_LEN: usize = 64;
+const PTAU_MAGIC: &[u8] = b"ptau";
+const PTAU_HEADER_SECTION: u32 = 1;
+const PTAU_TAU_G1_SECTION: u32 = 2;
+const PTAU_TAU_G2_SECTION: u32 = 3;
+
+/// One ceremony file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SrsSource {
+    pub name: String,
+    pub layout: SrsLayout,
+    /// Where to download the file; `None` if it has to be placed in the
+    /// cache by hand
+    pub url: Option<String>,
+    /// Pinned SHA-256 of the file
+    pub sha256: Option<[u8; 32]>,
+}
+
+impl SrsSource {
+    pub fn curve(&self) -> SrsCurve {
+        self.layout.curve()
+    }
+
+    /// Check `bytes` against the pinned checksum
+    pub fn verify(&self, bytes: &[u8]) -> Result<(), SrsError> {
+        let expected = self.sha256.ok_or_else(|| SrsError::Unpinned(self.name.clone()))?;
+        let actual: [u8; 32] = Sha256::digest(bytes).into();
+        if actual != expected {
+            return Err(SrsError::ChecksumMismatch {
+                name: self.name.clone(),
+                expected: hex(&expected),
+                actual: hex(&actual),
+            });
+        }
+        Ok(())
+    }
+
+    /// Powers up to `max_degree` from a BN254 ceremony file
+    pub fn to_bn254(&self, bytes: &[u8], max_degree: usize) -> Result<KZGCommitmentScheme<Bn254>, SrsError> {
+        self.verify(bytes)?;
+        if self.layout == SrsLayout::Ptau(SrsCurve::Bn254) {
+            return import_ptau(bytes, max_degree);
+        }
+        if self.layout != SrsLayout::AztecIgnition {
+            return Err(SrsError::WrongCurve { name: self.name.clone(), curve: self.curve() });
+        }
+        let manifest = bytes
+            .get(..IGNITION_MANIFEST_LEN)
+            .ok_or(SrsError::Truncated { needed: IGNITION_MANIFEST_LEN, available: bytes.len() })?;
+        let field = |i: usize| u32::from_be_bytes(manifest[4 * i..4 * i + 4].try_into().expect("four bytes")) as usize;
+        let (num_g1_points, num_g2_points, start_from) = (field(4), field(5), field(6));
+        if start_from != 0 {
+            return Err(SrsError::Layout(format!("transcript starts at power {}, not at tau^1", start_from + 1)));
+        }
+        if num_g1_points < max_degree {
+            return Err(SrsError::Truncated { needed: max_degree, available: num_g1_points });
+        }
+        if num_g2_points == 0 {
+            return Err(SrsError::Layout("transcript carries no G2 power".to_string()));
+        }
+        let g2_offset = IGNITION_MANIFEST_LEN + 64 * num_g1_points;
+        let needed = (IGNITION_MANIFEST_LEN + 64 * max_degree).max(g2_offset + 128);
+        if bytes.len() < needed {
+            return Err(SrsError::Truncated { needed, available: bytes.len() });
+        }
+        let coordinate = |offset: usize| {
+            let limbs: [u64; 4] = std::array::from_fn(|i| {
+                u64::from_be_bytes(bytes[offset + 8 * i..offset + 8 * i + 8].try_into().expect("eight bytes"))
+            });
+            BnFq::from_bigint(BigInt::new(limbs))
+        };
+        let mut powers = vec![BnG1Affine::generator()];
+        for index in 1..=max_degree {
+            let offset = IGNITION_MANIFEST_LEN + 64 * (index - 1);
+            let point = match (coordinate(offset), coordinate(offset + 32)) {
+                (Some(x), Some(y)) => BnG1Affine::new_unchecked(x, y),
+                _ => return Err(SrsError::InvalidPoint(index)),
+            };
+            // BN254 G1 has cofactor one, so on the curve means in the group
+            if !point.is_on_curve() {
+                return Err(SrsError::InvalidPoint(index));
+            }
+            powers.push(point);
+        }
+        let h_tau = match [0, 1, 2, 3].map(|i| coordinate(g2_offset + 32 * i)) {
+            [Some(x0), Some(x1), Some(y0), Some(y1)] => BnG2Affine::new_unchecked(BnFq2::new(x0, x1), BnFq2::new(y0, y1)),
+            _ => return Err(SrsError::InvalidPoint(1)),
+        };
+        if !h_tau.is_on_curve() || !h_tau.is_in_correct_subgroup_assuming_on_curve() {
+            return Err(SrsError::InvalidPoint(1));
+        }
+        Ok(KZGCommitmentScheme::from_parts(powers, (BnG2Affine::generator(), h_tau)))
+    }
+
+    /// Powers up to `max_degree` from a BLS12-381 ceremony file
+    pub fn to_bls12_381(&self, bytes: &[u8], max_degree: usize) -> Result<KZGCommitmentScheme<Bls12_381>, SrsError> {
+        self.verify(bytes)?;
+        if self.layout == SrsLayout::Ptau(SrsCurve::Bls12_381) {
+            return import_ptau(bytes, max_degree);
+        }
+        let SrsLayout::ZcashPowersOfTau { compressed, power } = self.layout else {
+            return Err(SrsError::WrongCurve { name: self.name.clone(), curve: self.curve() });
+        };
+        let (compress, point_len, g2_len) = if compressed { (Compress::Yes, 48, 96) } else { (Compress::No, 96, 192) };
+        let num_g1_points = (1usize << (power + 1)) - 1;
+        if num_g1_points <= max_degree {
+            return Err(SrsError::Truncated { needed: max_degree + 1, available: num_g1_points });
+        }
+        let g2_offset = ZCASH_HEADER_LEN + point_len * num_g1_points;
+        let needed = g2_offset + 2 * g2_len;
+        if bytes.len() < needed {
+            return Err(SrsError::Truncated { needed, available: bytes.len() });
+        }
+        let powers = (0..=max_degree)
+            .map(|index| {
+                let offset = ZCASH_HEADER_LEN + point_len * index;
+                BlsG1Affine::deserialize_with_mode(&bytes[offset..offset + point_len], compress, Validate::Yes)
+                    .map_err(|_| SrsError::InvalidPoint(index))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let [h, h_tau] = [0, 1].map(|index| {
+            let offset = g2_offset + g2_len * index;
+            BlsG2Affine::deserialize_with_mode(&bytes[offset..offset + g2_len], compress, Validate::Yes)
+        });
+        let (h, h_tau) = (h.map_err(|_| SrsError::InvalidPoint(0))?, h_tau.map_err(|_| SrsError::InvalidPoint(1))?);
+        if powers[0] != BlsG1Affine::generator() || h != BlsG2Affine::generator() {
+            return Err(SrsError::Layout("first power is not the generator".to_string()));
+        }
+        Ok(KZGCommitmentScheme::from_parts(powers, (h, h_tau)))
+    }
+}
+
+/// Curve whose points a `.ptau` file can carry
+pub trait PtauCurve: Pairing {
+    /// The G1 point with these coordinates, if it is in the group
+    fn g1_point(x: Self::BaseField, y: Self::BaseField) -> Option<Self::G1Affine>;
+    /// The G2 point with these coordinates as `[c0, c1]`, if it is in the group
+    fn g2_point(x: [Self::BaseField; 2], y: [Self::BaseField; 2]) -> Option<Self::G2Affine>;
+}
+
+impl PtauCurve for Bn254 {
+    fn g1_point(x: BnFq, y: BnFq) -> Option<BnG1Affine> {
+        // BN254 G1 has cofactor one, so on the curve means in the group
+        let point = BnG1Affine::new_unchecked(x, y);
+        point.is_on_curve().then_some(point)
+    }
+
+    fn g2_point([x0, x1]: [BnFq; 2], [y0, y1]: [BnFq; 2]) -> Option<BnG2Affine> {
+        let point = BnG2Affine::new_unchecked(BnFq2::new(x0, x1), BnFq2::new(y0, y1));
+        (point.is_on_curve() && point.is_in_correct_subgroup_assuming_on_curve()).then_some(point)
+    }
+}
+
+impl PtauCurve for Bls12_381 {
+    fn g1_point(x: BlsFq, y: BlsFq) -> Option<BlsG1Affine> {
+        let point = BlsG1Affine::new_unchecked(x, y);
+        (point.is_on_curve() && point.is_in_correct_subgroup_assuming_on_curve()).then_some(point)
+    }
+
+    fn g2_point([x0, x1]: [BlsFq; 2], [y0, y1]: [BlsFq; 2]) -> Option<BlsG2Affine> {
+        let point = BlsG2Affine::new_unchecked(BlsFq2::new(x0, x1), BlsFq2::new(y0, y1));
+        (point.is_on_curve() && point.is_in_correct_subgroup_assuming_on_curve()).then_some(point)
+    }
+}
+
+/// Powers up to `max_degree` from a snarkjs Powers of Tau (`.ptau`) file
+///
+/// The file is the magic `ptau`, a little-endian u32 version and section
+/// count, then sections of a u32 id and u64 length. Section 1 holds the
+/// base field size `n8`, the base field prime and the power `p`; section 2
+/// holds `2^(p + 1) - 1` G1 powers and section 3 `2^p` G2 powers, both from
+/// tau^0 on. Coordinates are `n8` little-endian bytes in Montgomery form,
+/// G2 coordinates `c0` then `c1`. The prime must be the base field of `E`.
+///
+/// No checksum is checked here; pin one through `SrsRegistry` with
+/// `SrsLayout::Ptau` to take the file from an untrusted mirror.
+pub fn import_ptau<E: PtauCurve>(bytes: &[u8], max_degree: usize) -> Result<KZGCommitmentScheme<E>, SrsError> {
+    if bytes.get(..PTAU_MAGIC.len()) != Some(PTAU_MAGIC) {
+        return Err(SrsError::Layout("missing ptau magic".to_string()));
+    }
+    let mut sections = BTreeMap::new();
+    let mut offset = 12;
+    while offset < bytes.len() {
+        let id = u32::from_le_bytes(le_bytes(bytes, offset)?);
+        let len = u64::from_le_bytes(le_bytes(bytes, offset + 4)?) as usize;
+        let start = offset + 12;
+        let section = bytes
+            .get(start..start.saturating_add(len))
+            .ok_or(SrsError::Truncated { needed: start.saturating_add(len), available: bytes.len() })?;
+        sections.entry(id).or_insert(section);
+        offset = start + len;
+    }
+    let section = |id: u32| {
+        sections.get(&id).copied().ok_or_else(|| SrsError::Layout(format!("ptau file has no section {}", id)))
+    };
+
+    let header = section(PTAU_HEADER_SECTION)?;
+    let n8 = u32::from_le_bytes(le_bytes(header, 0)?) as usize;
+    let modulus = E::BaseField::MODULUS.to_bytes_le();
+    if header.get(4..4 + n8) != Some(&modulus[..]) {
+        return Err(SrsError::Layout("ptau file is over another curve".to_string()));
+    }
+    let power = u32::from_le_bytes(le_bytes(header, 4 + n8)?);
+    let num_g1_points = 1usize.checked_shl(power + 1).map_or(usize::MAX, |n| n - 1);
+    if num_g1_points <= max_degree {
+        return Err(SrsError::Truncated { needed: max_degree + 1, available: num_g1_points });
+    }
+
+    // Montgomery form stores x·R with R = 2^(8·n8)
+    let r_inv = E::BaseField::from(2u64).pow([8 * n8 as u64]).inverse().expect("R is invertible");
+    let coordinates = |section: &[u8], index: usize, count: usize| {
+        let start = index * count * n8;
+        section
+            .get(start..start + count * n8)
+            .map(|bytes| bytes.chunks(n8).map(|chunk| E::BaseField::from_le_bytes_mod_order(chunk) * r_inv).collect::<Vec<_>>())
+            .ok_or(SrsError::Truncated { needed: start + count * n8, available: section.len() })
+    };
+    let tau_g1 = section(PTAU_TAU_G1_SECTION)?;
+    let powers = (0..=max_degree)
+        .map(|index| {
+            let c = coordinates(tau_g1, index, 2)?;
+            E::g1_point(c[0], c[1]).ok_or(SrsError::InvalidPoint(index))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let tau_g2 = section(PTAU_TAU_G2_SECTION)?;
+    let [h, h_tau] = [0, 1].map(|index| {
+        let c = coordinates(tau_g2, index, 4)?;
+        E::g2_point([c[0], c[1]], [c[2], c[3]]).ok_or(SrsError::InvalidPoint(index))
+    });
+    let (h, h_tau) = (h?, h_tau?);
+    if powers[0] != E::G1Affine::generator() || h != E::G2Affine::generator() {
+        return Err(SrsError::Layout("first power is not the generator".to_string()));
+    }
+    Ok(KZGCommitmentScheme::from_parts(powers, (h, h_tau)))
+}
+
+fn le_bytes<const N: usize>(bytes: &[u8], offset: usize) -> Result<[u8; N], SrsError> {
+    bytes
+        .get(offset..offset + N)
+        .map(|b| b.try_into().expect("N bytes"))
+        .ok_or(SrsError::Truncated { needed: offset + N, available: bytes.len() })
+}
+
+/// Known ceremony files by name
+#[derive(Debug, Clone, Default)]
+pub struct SrsRegistry {
+    sources: BTreeMap<String, SrsSource>,
+}
+
+impl SrsRegistry {
+    /// Well-known public ceremonies, without pinned checksums
+    pub fn well_known() -> Self {
+        let mut registry = Self::default();
+        registry.register(SrsSource {
+            name: "aztec-ignition-00".to_string(),
+            layout: SrsLayout::AztecIgnition,
+            url: Some("https://aztec-ignition.s3.amazonaws.com/MAIN%20IGNITION/monomial/transcript00.dat".to_string()),
+            sha256: None,
+        });
+        registry.register(SrsSource {
+            name: "zcash-powersoftau".to_string(),
+            layout: SrsLayout::ZcashPowersOfTau { compressed: false, power: 21 },
+            url: None,
+            sha256: None,
+        });
+        registry
+    }
+
+    /// Add or replace a source
+    pub fn register(&mut self, source: SrsSource) {
+        self.sources.insert(source.name.clone(), source);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&SrsSource> {
+        self.sources.get(name)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.sources.keys().map(String::as_str)
+    }
+
+    /// Pin the checksum a source must match
+    pub fn pin(&mut self, name: &str, sha256: [u8; 32]) -> Result<(), SrsError> {
+        let source = self.sources.get_mut(name).ok_or_else(|| SrsError::UnknownSource(name.to_string()))?;
+        source.sha256 = Some(sha256);
+        Ok(())
+    }
+
+    /// Path of a source's file in `cache_dir`
+    pub fn cache_path(&self, name: &str, cache_dir: &Path) -> PathBuf {
+        cache_dir.join(format!("{}.srs", name))
+    }
+
+    /// Return the verified file of a source, downloading it with `curl`
+    /// if the cache has no valid copy
+    ///
+    /// `curl` is a runtime requirement of the download only; a missing
+    /// binary is reported as `SrsError::Download`.
+    pub fn fetch(&self, name: &str, cache_dir: &Path) -> Result<Vec<u8>, SrsError> {
+        let source = self.get(name).ok_or_else(|| SrsError::UnknownSource(name.to_string()))?;
+        if source.sha256.is_none() {
+            return Err(SrsError::Unpinned(name.to_string()));
+        }
+        let path = self.cache_path(name, cache_dir);
+        if let Ok(bytes) = std::fs::read(&path) {
+            if source.verify(&bytes).is_ok() {
+                return Ok(bytes);
+            }
+        }
+        let url = source.url.as_ref().ok_or_else(|| SrsError::NotDownloadable(name.to_string()))?;
+        std::fs::create_dir_all(cache_dir)?;
+        let partial = path.with_extension("part");
+        let status = Command::new("curl")
+            .args(["--fail", "--silent", "--show-error", "--location", "--output"])
+            .arg(&partial)
+            .arg(url)
+            .status()
+            .map_err(|e| match e.kind() {
+                std::io::ErrorKind::NotFound => SrsError::Download(format!(
+                    "curl is not installed; install it or place the file at {}",
+                    path.display()
+                )),
+                _ => SrsError::Download(e.to_string()),
+            })?;
+        if !status.success() {
+            return Err(SrsError::Download(format!("curl exited with {}", status)));
+        }
+        let bytes = std::fs::read(&partial)?;
+        // A file that fails verification is left out of the cache
+        source.verify(&bytes)?;
+        std::fs::rename(&partial, &path)?;
+        Ok(bytes)
+    }
+}
+
+/// What `load_prefix` read from a stored SRS
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SrsLoad {
+    pub points_available: usize,
+    pub points_loaded: usize,
+    /// Memory the points left on disk would have taken
+    pub bytes_saved: usize,
+}
+
+impl SrsLoad {
+    /// Add the savings to the memory statistics of a run
+    pub fn record(&self, stats: &mut MemoryStats) {
+        stats.srs_bytes_saved += self.bytes_saved;
+    }
+}
+
+/// Read the verification key and the powers up to `max_degree` from an SRS
+/// stored in the crate's format, leaving the remaining powers unread
+pub fn load_prefix<E: Pairing>(
+    path: impl AsRef<Path>,
+    max_degree: usize,
+) -> Result<(KZGCommitmentScheme<E>, SrsLoad), SrsError> {
+    let mut file = std::io::BufReader::new(std::fs::File::open(path)?);
+    let point_len = point_size::<E::G1>();
+    let mut header = vec![0u8; 2 * point_size::<E::G2>() + 4];
+    file.read_exact(&mut header)?;
+    let mut reader = ByteReader::new(&header);
+    let verification_key = (reader.read_point::<E::G2>()?, reader.read_point::<E::G2>()?);
+    let points_available = reader.read_u32()? as usize;
+    if points_available <= max_degree {
+        return Err(SrsError::Truncated { needed: max_degree + 1, available: points_available });
+    }
+    let mut body = vec![0u8; point_len * (max_degree + 1)];
+    file.read_exact(&mut body)?;
+    let mut reader = ByteReader::new(&body);
+    let powers = (0..=max_degree).map(|_| reader.read_point::<E::G1>()).collect::<Result<Vec<_>, _>>()?;
+    let load = SrsLoad {
+        points_available,
+        points_loaded: powers.len(),
+        bytes_saved: (points_available - powers.len()) * std::mem::size_of::<E::G1Affine>(),
+    };
+    Ok((KZGCommitmentScheme::from_parts(powers, verification_key), load))
+}
+
+/// Store an SRS in the crate's format
+pub fn save<E: Pairing>(
+    path: impl AsRef<Path>,
+    scheme: &KZGCommitmentScheme<E>,
+) -> Result<(), SrsError> {
+    Ok(std::fs::write(path, scheme.to_bytes())?)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// SRS registry error types
+#[derive(Debug)]
+pub enum SrsError {
+    Io(std::io::Error),
+    UnknownSource(String),
+    /// No checksum is pinned, so the file cannot be trusted
+    Unpinned(String),
+    ChecksumMismatch { name: String, expected: String, actual: String },
+    NotDownloadable(String),
+    Download(String),
+    WrongCurve { name: String, curve: SrsCurve },
+    Layout(String),
+    Truncated { needed: usize, available: usize },
+    Decode(DecodeError),
+    /// The power of tau at this index is not a valid group element
+    InvalidPoint(usize),
+}
+
+impl std::fmt::Display for SrsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            SrsError::Io(e) => write!(f, "SRS I/O error: {}", e),
+            SrsError::UnknownSource(name) => write!(f, "Unknown SRS source: {}", name),
+            SrsError::Unpinned(name) => write!(f, "No checksum pinned for SRS source {}", name),
+            SrsError::ChecksumMismatch { name, expected, actual } => {
+                write!(f, "SRS source {} has SHA-256 {}, expected {}", name, actual, expected)
+            }
+            SrsError::NotDownloadable(name) => {
+                write!(f, "SRS source {} has no download URL; place the file in the cache", name)
+            }
+            SrsError::Download(e) => write!(f, "SRS download failed: {}", e),
+            SrsError::WrongCurve { name, curve } => write!(f, "SRS source {} is over {:?}", name, curve),
+            SrsError::Layout(e) => write!(f, "Unexpected SRS file layout: {}", e),
+            SrsError::Truncated { needed, available } => {
+                write!(f, "SRS file too short: needed {}, found {}", needed, available)
+            }
+            SrsError::Decode(e) => write!(f, "Malformed stored SRS: {}", e),
+            SrsError::InvalidPoint(index) => write!(f, "SRS power {} is not a valid group element", index),
+        }
+    }
+}
+
+impl std::error::Error for SrsError {}
+
+impl From<std::io::Error> for SrsError {
+    fn from(e: std::io::Error) -> Self {
+        SrsError::Io(e)
+    }
+}
+
+impl From<DecodeError> for SrsError {
+    fn from(e: DecodeError) -> Self {
+        SrsError::Decode(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::{Fr as BlsFr, G1Projective as BlsG1, G2Projective as BlsG2};
+    use ark_bn254::{Fr as BnFr, G1Projective as BnG1, G2Projective as BnG2};
+    use ark_ec::CurveGroup;
+    use ark_ff::Field;
+    use ark_poly::{univariate::DensePolynomial, DenseUVPolynomial};
+    use ark_serialize::CanonicalSerialize;
+
+    fn powers<G: CurveGroup>(tau: G::ScalarField, count: usize) -> Vec<G::Affine> {
+        (0..count).map(|i| (G::generator() * tau.pow([i as u64])).into_affine()).collect()
+    }
+
+    fn pinned(registry: &mut SrsRegistry, name: &str, bytes: &[u8]) -> SrsSource {
+        registry.pin(name, Sha256::digest(bytes).into()).unwrap();
+        registry.get(name).unwrap().clone()
+    }
+
+    #[test]
+    fn test_ceremony_files_convert_after_checksum() {
+        let mut registry = SrsRegistry::well_known();
+        let degree = 4;
+
+        // Ignition: manifest, then tau^1.. in G1 and G2 with big-endian limbs
+        let expected = powers::<BnG1>(BnFr::from(7u64), degree + 1);
+        let expected_g2 = powers::<BnG2>(BnFr::from(7u64), 3);
+        let mut ignition = Vec::new();
+        for field in [0u32, 20, 100, 2, degree as u32, 2, 0] {
+            ignition.extend_from_slice(&field.to_be_bytes());
+        }
+        let coordinates = expected[1..].iter().flat_map(|point| [point.x, point.y]).chain(
+            expected_g2[1..].iter().flat_map(|point| [point.x.c0, point.x.c1, point.y.c0, point.y.c1]),
+        );
+        for coordinate in coordinates {
+            for limb in coordinate.into_bigint().as_ref() {
+                ignition.extend_from_slice(&limb.to_be_bytes());
+            }
+        }
+        let source = registry.get("aztec-ignition-00").unwrap().clone();
+        assert!(matches!(source.to_bn254(&ignition, degree), Err(SrsError::Unpinned(_))));
+        let source = pinned(&mut registry, "aztec-ignition-00", &ignition);
+        let scheme = source.to_bn254(&ignition, degree).unwrap();
+        assert_eq!(scheme.powers_of_g, expected);
+        assert_eq!(scheme.verification_key, (expected_g2[0], expected_g2[1]));
+        let polynomial = DensePolynomial::from_coefficients_vec((1..=4u64).map(BnFr::from).collect());
+        let opening = scheme.open(&polynomial, BnFr::from(5u64));
+        assert!(scheme.verify(&scheme.commit(&polynomial), &opening));
+        assert!(matches!(source.to_bn254(&ignition, degree + 1), Err(SrsError::Truncated { .. })));
+        assert!(matches!(source.to_bls12_381(&ignition, degree), Err(SrsError::WrongCurve { .. })));
+        let mut tampered = ignition.clone();
+        tampered[IGNITION_MANIFEST_LEN] ^= 1;
+        assert!(matches!(source.to_bn254(&tampered, degree), Err(SrsError::ChecksumMismatch { .. })));
+
+        // Zcash of size 2^2: hash header, then uncompressed G1 and G2 powers from tau^0
+        registry.register(SrsSource {
+            name: "zcash-powersoftau".to_string(),
+            layout: SrsLayout::ZcashPowersOfTau { compressed: false, power: 2 },
+            url: None,
+            sha256: None,
+        });
+        let expected = powers::<BlsG1>(BlsFr::from(11u64), 7);
+        let expected_g2 = powers::<BlsG2>(BlsFr::from(11u64), 4);
+        let mut zcash = vec![0u8; ZCASH_HEADER_LEN];
+        for point in &expected {
+            point.serialize_uncompressed(&mut zcash).unwrap();
+        }
+        for point in &expected_g2 {
+            point.serialize_uncompressed(&mut zcash).unwrap();
+        }
+        let source = pinned(&mut registry, "zcash-powersoftau", &zcash);
+        let scheme = source.to_bls12_381(&zcash, degree).unwrap();
+        assert_eq!(scheme.powers_of_g, expected[..=degree]);
+        assert_eq!(scheme.verification_key, (expected_g2[0], expected_g2[1]));
+        assert!(matches!(source.to_bls12_381(&zcash, 7), Err(SrsError::Truncated { .. })));
+        let stored = KZGCommitmentScheme::<Bls12_381>::from_bytes(&scheme.to_bytes()).unwrap();
+        assert_eq!(stored.powers_of_g, expected[..=degree]);
+
+        // A point off the curve is rejected even with a matching checksum
+        let mut corrupt = zcash.clone();
+        corrupt[ZCASH_HEADER_LEN + 96 + 95] ^= 1;
+        let source = pinned(&mut registry, "zcash-powersoftau", &corrupt);
+        assert!(matches!(source.to_bls12_381(&corrupt, degree), Err(SrsError::InvalidPoint(1))));
+
+        // A verified copy in the cache is used without downloading
+        let cache = std::env::temp_dir().join(format!("eos-srs-{}", std::process::id()));
+        std::fs::create_dir_all(&cache).unwrap();
+        pinned(&mut registry, "zcash-powersoftau", &zcash);
+        assert!(matches!(registry.fetch("zcash-powersoftau", &cache), Err(SrsError::NotDownloadable(_))));
+        std::fs::write(registry.cache_path("zcash-powersoftau", &cache), &zcash).unwrap();
+        assert_eq!(registry.fetch("zcash-powersoftau", &cache).unwrap(), zcash);
+        std::fs::remove_dir_all(&cache).unwrap();
+    }
+
+    #[test]
+    fn test_ptau_import() {
+        // snarkjs layout of size 2^2 over BN254, with an unused section after the powers
+        let degree = 4;
+        let expected = powers::<BnG1>(BnFr::from(13u64), 7);
+        let expected_g2 = powers::<BnG2>(BnFr::from(13u64), 4);
+        let montgomery = |x: BnFq| (x * BnFq::from(2u64).pow([256])).into_bigint().to_bytes_le();
+        let section = |ptau: &mut Vec<u8>, id: u32, data: Vec<u8>| {
+            ptau.extend_from_slice(&id.to_le_bytes());
+            ptau.extend_from_slice(&(data.len() as u64).to_le_bytes());
+            ptau.extend_from_slice(&data);
+        };
+        let mut ptau = b"ptau".to_vec();
+        ptau.extend_from_slice(&1u32.to_le_bytes());
+        ptau.extend_from_slice(&4u32.to_le_bytes());
+        let mut header = 32u32.to_le_bytes().to_vec();
+        header.extend_from_slice(&BnFq::MODULUS.to_bytes_le());
+        header.extend_from_slice(&2u32.to_le_bytes());
+        header.extend_from_slice(&2u32.to_le_bytes());
+        section(&mut ptau, 1, header);
+        section(&mut ptau, 2, expected.iter().flat_map(|p| [p.x, p.y]).flat_map(montgomery).collect());
+        let g2 = expected_g2.iter().flat_map(|p| [p.x.c0, p.x.c1, p.y.c0, p.y.c1]);
+        section(&mut ptau, 3, g2.flat_map(montgomery).collect());
+        section(&mut ptau, 4, vec![0; 64]);
+
+        let scheme = import_ptau::<Bn254>(&ptau, degree).unwrap();
+        assert_eq!(scheme.powers_of_g, expected[..=degree]);
+        assert_eq!(scheme.verification_key, (expected_g2[0], expected_g2[1]));
+        let polynomial = DensePolynomial::from_coefficients_vec((1..=5u64).map(BnFr::from).collect());
+        let opening = scheme.open(&polynomial, BnFr::from(3u64));
+        assert!(scheme.verify(&scheme.commit(&polynomial), &opening));
+        assert!(matches!(import_ptau::<Bn254>(&ptau, 7), Err(SrsError::Truncated { .. })));
+        assert!(matches!(import_ptau::<Bls12_381>(&ptau, degree), Err(SrsError::Layout(_))));
+
+        // Through the registry the checksum is checked first
+        let mut registry = SrsRegistry::default();
+        registry.register(SrsSource {
+            name: "hermez-ptau".to_string(),
+            layout: SrsLayout::Ptau(SrsCurve::Bn254),
+            url: None,
+            sha256: None,
+        });
+        let source = pinned(&mut registry, "hermez-ptau", &ptau);
+        assert_eq!(source.to_bn254(&ptau, degree).unwrap().powers_of_g, scheme.powers_of_g);
+        assert!(matches!(source.to_bls12_381(&ptau, degree), Err(SrsError::WrongCurve { .. })));
+
+        // A coordinate off the curve is rejected
+        let mut corrupt = ptau.clone();
+        let first_power = ptau.len() - (64 + 12) - 4 * 128 - 12 - 7 * 64;
+        corrupt[first_power + 64] ^= 1;
+        assert!(matches!(import_ptau::<Bn254>(&corrupt, degree), Err(SrsError::InvalidPoint(1))));
+    }
+
+    #[test]
+    fn test_prefix_load_and_truncation() {
+        let path = std::env::temp_dir().join(format!("eos-srs-prefix-{}", std::process::id()));
+        let full = KZGCommitmentScheme::<Bls12_381>::setup(63, &mut ark_std::test_rng());
+        save(&path, &full).unwrap();
+
+        let (prefix, load) = load_prefix::<Bls12_381>(&path, 15).unwrap();
+        assert_eq!(prefix.powers_of_g, full.powers_of_g[..16]);
+        assert_eq!(prefix.verification_key, full.verification_key);
+        assert_eq!((load.points_available, load.points_loaded), (64, 16));
+        assert_eq!(load.bytes_saved, 48 * std::mem::size_of::<BlsG1Affine>());
+        let mut stats = MemoryStats::new();
+        load.record(&mut stats);
+        assert_eq!(stats.srs_bytes_saved, load.bytes_saved);
+        assert!(matches!(load_prefix::<Bls12_381>(&path, 64), Err(SrsError::Truncated { .. })));
+        std::fs::remove_file(&path).unwrap();
+
+        // Commitments under a truncated SRS are unchanged
+        let coefficients: Vec<BlsFr> = (1..=16u64).map(BlsFr::from).collect();
+        let mut truncated = full.clone();
+        assert!(truncated.truncate(15) > 0);
+        assert_eq!(truncated.max_degree(), 15);
+        assert_eq!(truncated.commit_coefficients(&coefficients), full.commit_coefficients(&coefficients));
+    }
+}
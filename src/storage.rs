@@ -0,0 +1,595 @@
+//! Persistence of secret material at rest
+//!
+//! Workers keep shares, preprocessing material and checkpoints on disk
+//! between sessions. A `SecretStore` writes any `WireFormat` value to a file
+//! in its directory, optionally sealed with ChaCha20-Poly1305 under a key from
+//! a `KeyProvider`. Each file carries a fresh salt and nonce, and its name is
+//! bound into the associated data, so a sealed file can be neither read nor
+//! swapped for another without the key.
+//!
+//! The delegator keeps a `SessionJournal`, a write-ahead log of what it has
+//! sent and received in each delegation. Every entry is synced to disk
+//! before the corresponding message goes out, so after a crash the journal
+//! tells whether a session can resume or must be aborted. It never allows
+//! a witness to be shared twice for the same session: two independent
+//! sharings of one witness give a coalition twice as many shares.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+
+use crate::mpc::SessionId;
+use crate::serialization::{write_u32, write_u64, ByteReader, DecodeError, WireFormat};
+
+const MAGIC: &[u8; 4] = b"EOSS";
+const VERSION: u8 = 1;
+/// Length of the per-file salt passed to `KeyProvider::data_key`
+pub const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const HEADER_LEN: usize = MAGIC.len() + 2 + SALT_LEN + NONCE_LEN;
+
+/// Source of data-encryption keys
+///
+/// Implement this to fetch or unwrap keys through a KMS; the salt is unique
+/// per file and may be used as the key-derivation context.
+pub trait KeyProvider {
+    fn data_key(&self, salt: &[u8; SALT_LEN]) -> Result<[u8; 32], StorageError>;
+}
+
+/// Keys derived from a worker passphrase with PBKDF2-HMAC-SHA256
+pub struct PassphraseKey {
+    passphrase: String,
+    iterations: u32,
+}
+
+impl PassphraseKey {
+    pub const DEFAULT_ITERATIONS: u32 = 600_000;
+
+    pub fn new(passphrase: impl Into<String>) -> Self {
+        Self::with_iterations(passphrase, Self::DEFAULT_ITERATIONS)
+    }
+
+    pub fn with_iterations(passphrase: impl Into<String>, iterations: u32) -> Self {
+        Self { passphrase: passphrase.into(), iterations }
+    }
+}
+
+impl KeyProvider for PassphraseKey {
+    fn data_key(&self, salt: &[u8; SALT_LEN]) -> Result<[u8; 32], StorageError> {
+        let mut key = [0u8; 32];
+        pbkdf2::pbkdf2_hmac::<Sha256>(self.passphrase.as_bytes(), salt, self.iterations, &mut key);
+        Ok(key)
+    }
+}
+
+/// How files are protected on disk
+pub enum Protection {
+    /// Files are stored in the clear
+    Plaintext,
+    /// Files are sealed with keys from the provider
+    Encrypted(Box<dyn KeyProvider>),
+}
+
+/// Directory of persisted secret material
+pub struct SecretStore {
+    dir: PathBuf,
+    protection: Protection,
+}
+
+impl SecretStore {
+    /// Open a store, creating its directory if needed
+    pub fn open(dir: impl AsRef<Path>, protection: Protection) -> Result<Self, StorageError> {
+        std::fs::create_dir_all(dir.as_ref())?;
+        Ok(Self { dir: dir.as_ref().to_path_buf(), protection })
+    }
+
+    pub fn is_encrypted(&self) -> bool {
+        matches!(self.protection, Protection::Encrypted(_))
+    }
+
+    fn path(&self, name: &str) -> Result<PathBuf, StorageError> {
+        let allowed = |c: char| c.is_ascii_alphanumeric() || "-_.".contains(c);
+        if name.is_empty() || name.starts_with('.') || !name.chars().all(allowed) {
+            return Err(StorageError::InvalidName(name.to_string()));
+        }
+        Ok(self.dir.join(name))
+    }
+
+    /// Persist `value` under `name`
+    pub fn save<T: WireFormat>(&self, name: &str, value: &T) -> Result<(), StorageError> {
+        let path = self.path(name)?;
+        let plaintext = value.to_bytes();
+
+        let mut salt = [0u8; SALT_LEN];
+        let mut nonce = [0u8; NONCE_LEN];
+        let mode = match &self.protection {
+            Protection::Plaintext => 0,
+            Protection::Encrypted(_) => {
+                rand::thread_rng().fill_bytes(&mut salt);
+                rand::thread_rng().fill_bytes(&mut nonce);
+                1
+            }
+        };
+
+        let mut file = Vec::with_capacity(HEADER_LEN + plaintext.len() + 16);
+        file.extend_from_slice(MAGIC);
+        file.push(VERSION);
+        file.push(mode);
+        file.extend_from_slice(&salt);
+        file.extend_from_slice(&nonce);
+
+        match &self.protection {
+            Protection::Plaintext => file.extend_from_slice(&plaintext),
+            Protection::Encrypted(provider) => {
+                let cipher = ChaCha20Poly1305::new(Key::from_slice(&provider.data_key(&salt)?));
+                let aad = associated_data(&file, name);
+                let sealed = cipher
+                    .encrypt(Nonce::from_slice(&nonce), Payload { msg: &plaintext, aad: &aad })
+                    .map_err(|_| StorageError::Authentication)?;
+                file.extend_from_slice(&sealed);
+            }
+        }
+
+        // Write then rename so a crash never leaves a torn file behind
+        let temporary = self.dir.join(format!(".{}.tmp", name));
+        std::fs::write(&temporary, &file)?;
+        std::fs::rename(&temporary, &path)?;
+        Ok(())
+    }
+
+    /// Load the value stored under `name`
+    pub fn load<T: WireFormat>(&self, name: &str) -> Result<T, StorageError> {
+        let file = std::fs::read(self.path(name)?)?;
+        if file.len() < HEADER_LEN || &file[..MAGIC.len()] != MAGIC || file[MAGIC.len()] != VERSION {
+            return Err(StorageError::BadHeader);
+        }
+        let header = &file[..HEADER_LEN];
+        let body = &file[HEADER_LEN..];
+        let salt: [u8; SALT_LEN] = header[MAGIC.len() + 2..MAGIC.len() + 2 + SALT_LEN].try_into().expect("header has salt");
+        let nonce = &header[HEADER_LEN - NONCE_LEN..];
+
+        let plaintext = match (file[MAGIC.len() + 1], &self.protection) {
+            (0, Protection::Plaintext) => body.to_vec(),
+            // Refuse to silently accept a plaintext file where a sealed one is expected
+            (0, Protection::Encrypted(_)) => return Err(StorageError::UnexpectedPlaintext),
+            (1, Protection::Plaintext) => return Err(StorageError::KeyRequired),
+            (1, Protection::Encrypted(provider)) => {
+                let cipher = ChaCha20Poly1305::new(Key::from_slice(&provider.data_key(&salt)?));
+                cipher
+                    .decrypt(Nonce::from_slice(nonce), Payload { msg: body, aad: &associated_data(header, name) })
+                    .map_err(|_| StorageError::Authentication)?
+            }
+            _ => return Err(StorageError::BadHeader),
+        };
+        Ok(T::from_bytes(&plaintext)?)
+    }
+
+    /// Delete the value stored under `name`
+    pub fn remove(&self, name: &str) -> Result<(), StorageError> {
+        std::fs::remove_file(self.path(name)?)?;
+        Ok(())
+    }
+}
+
+/// One step of a delegation, recorded before it takes effect
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JournalEntry {
+    /// A delegation was set up with `num_workers` workers
+    SessionStarted { session: SessionId, num_workers: u32 },
+    /// Witness shares are about to be sent to `worker`
+    SharesSent { session: SessionId, worker: u32, digest: [u8; 32] },
+    /// A commitment is about to be sent to `worker`
+    CommitmentSent { session: SessionId, worker: u32, digest: [u8; 32] },
+    /// The proof arrived and was persisted under its digest
+    ProofReceived { session: SessionId, digest: [u8; 32] },
+    Completed { session: SessionId },
+    /// Workers were told to discard their shares
+    Aborted { session: SessionId },
+}
+
+impl JournalEntry {
+    pub fn session(&self) -> SessionId {
+        match self {
+            JournalEntry::SessionStarted { session, .. }
+            | JournalEntry::SharesSent { session, .. }
+            | JournalEntry::CommitmentSent { session, .. }
+            | JournalEntry::ProofReceived { session, .. }
+            | JournalEntry::Completed { session }
+            | JournalEntry::Aborted { session } => *session,
+        }
+    }
+}
+
+/// Digest of a message as recorded in the journal
+pub fn journal_digest(message: &[u8]) -> [u8; 32] {
+    Sha256::new().chain_update(b"EOS-journal").chain_update(message).finalize().into()
+}
+
+fn read_digest(reader: &mut ByteReader<'_>) -> Result<[u8; 32], DecodeError> {
+    Ok(reader.read_bytes(32)?.try_into().expect("slice has length 32"))
+}
+
+impl WireFormat for JournalEntry {
+    fn encode(&self, out: &mut Vec<u8>) {
+        let (tag, session) = match self {
+            JournalEntry::SessionStarted { session, .. } => (0, session),
+            JournalEntry::SharesSent { session, .. } => (1, session),
+            JournalEntry::CommitmentSent { session, .. } => (2, session),
+            JournalEntry::ProofReceived { session, .. } => (3, session),
+            JournalEntry::Completed { session } => (4, session),
+            JournalEntry::Aborted { session } => (5, session),
+        };
+        out.push(tag);
+        write_u64(out, *session);
+        match self {
+            JournalEntry::SessionStarted { num_workers, .. } => write_u32(out, *num_workers),
+            JournalEntry::SharesSent { worker, digest, .. } | JournalEntry::CommitmentSent { worker, digest, .. } => {
+                write_u32(out, *worker);
+                out.extend_from_slice(digest);
+            }
+            JournalEntry::ProofReceived { digest, .. } => out.extend_from_slice(digest),
+            JournalEntry::Completed { .. } | JournalEntry::Aborted { .. } => {}
+        }
+    }
+
+    fn decode(reader: &mut ByteReader<'_>) -> Result<Self, DecodeError> {
+        let tag = reader.read_u8()?;
+        let session = reader.read_u64()?;
+        Ok(match tag {
+            0 => JournalEntry::SessionStarted { session, num_workers: reader.read_u32()? },
+            1 | 2 => {
+                let worker = reader.read_u32()?;
+                let digest = read_digest(reader)?;
+                if tag == 1 {
+                    JournalEntry::SharesSent { session, worker, digest }
+                } else {
+                    JournalEntry::CommitmentSent { session, worker, digest }
+                }
+            }
+            3 => JournalEntry::ProofReceived { session, digest: read_digest(reader)? },
+            4 => JournalEntry::Completed { session },
+            5 => JournalEntry::Aborted { session },
+            tag => return Err(DecodeError::InvalidTag(tag)),
+        })
+    }
+}
+
+/// Progress of one session, rebuilt from the journal
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SessionProgress {
+    pub num_workers: u32,
+    pub shares_sent: BTreeSet<u32>,
+    pub commitments_sent: BTreeSet<u32>,
+    pub proof: Option<[u8; 32]>,
+    pub completed: bool,
+    pub aborted: bool,
+}
+
+/// What a restarted delegator should do with a session
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecoveryAction {
+    /// Nothing was shared yet; the session may start over
+    Restart,
+    /// Every worker holds its shares; wait for (or request again) the proof
+    AwaitProof,
+    /// The proof is persisted; verify it and mark the session completed
+    VerifyProof([u8; 32]),
+    /// Only some workers got shares, which cannot be re-sent without the
+    /// lost sharing; tell all workers to discard theirs
+    Abort { shared_with: Vec<u32> },
+    /// Completed or aborted
+    Closed,
+}
+
+impl SessionProgress {
+    fn apply(&mut self, entry: &JournalEntry) -> Result<(), StorageError> {
+        let session = entry.session();
+        if self.completed || self.aborted {
+            return Err(StorageError::SessionClosed(session));
+        }
+        let check_worker = |worker: u32| {
+            if worker < self.num_workers {
+                Ok(())
+            } else {
+                Err(StorageError::InvalidJournalEntry(format!("session {} has no worker {}", session, worker)))
+            }
+        };
+        match entry {
+            JournalEntry::SessionStarted { .. } => {
+                return Err(StorageError::InvalidJournalEntry(format!("session {} started twice", session)));
+            }
+            JournalEntry::SharesSent { worker, .. } => {
+                check_worker(*worker)?;
+                // A second sharing of the same witness must never go out
+                if !self.shares_sent.insert(*worker) {
+                    return Err(StorageError::WitnessAlreadyShared { session, worker: *worker });
+                }
+            }
+            JournalEntry::CommitmentSent { worker, .. } => {
+                check_worker(*worker)?;
+                self.commitments_sent.insert(*worker);
+            }
+            JournalEntry::ProofReceived { digest, .. } => self.proof = Some(*digest),
+            JournalEntry::Completed { .. } => self.completed = true,
+            JournalEntry::Aborted { .. } => self.aborted = true,
+        }
+        Ok(())
+    }
+
+    pub fn recovery(&self) -> RecoveryAction {
+        if self.completed || self.aborted {
+            RecoveryAction::Closed
+        } else if let Some(digest) = self.proof {
+            RecoveryAction::VerifyProof(digest)
+        } else if self.shares_sent.is_empty() {
+            RecoveryAction::Restart
+        } else if self.shares_sent.len() == self.num_workers as usize {
+            RecoveryAction::AwaitProof
+        } else {
+            RecoveryAction::Abort { shared_with: self.shares_sent.iter().copied().collect() }
+        }
+    }
+}
+
+/// Length and checksum in front of every journal record
+const RECORD_HEADER_LEN: usize = 8;
+
+fn record_checksum(body: &[u8]) -> [u8; 4] {
+    let digest = Sha256::digest(body);
+    [digest[0], digest[1], digest[2], digest[3]]
+}
+
+/// Append-only, crash-consistent log of the delegator's sessions
+pub struct SessionJournal {
+    file: std::fs::File,
+    sessions: BTreeMap<SessionId, SessionProgress>,
+}
+
+impl SessionJournal {
+    /// Open or create a journal and replay it
+    ///
+    /// A record torn by a crash during the last append is dropped and the
+    /// file truncated to the last complete record.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, StorageError> {
+        let mut file = std::fs::OpenOptions::new().create(true).truncate(false).read(true).append(true).open(path)?;
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)?;
+        let mut sessions: BTreeMap<SessionId, SessionProgress> = BTreeMap::new();
+        let mut offset = 0;
+        while bytes.len() - offset >= RECORD_HEADER_LEN {
+            let len = u32::from_le_bytes(bytes[offset..offset + 4].try_into().expect("slice has length 4")) as usize;
+            let body = match bytes.get(offset + RECORD_HEADER_LEN..offset + RECORD_HEADER_LEN + len) {
+                Some(body) if record_checksum(body) == bytes[offset + 4..offset + RECORD_HEADER_LEN] => body,
+                _ => break,
+            };
+            let entry = JournalEntry::from_bytes(body)?;
+            Self::apply_to(&mut sessions, &entry)?;
+            offset += RECORD_HEADER_LEN + len;
+        }
+        if offset < bytes.len() {
+            file.set_len(offset as u64)?;
+        }
+        Ok(Self { file, sessions })
+    }
+
+    fn apply_to(sessions: &mut BTreeMap<SessionId, SessionProgress>, entry: &JournalEntry) -> Result<(), StorageError> {
+        let session = entry.session();
+        match (entry, sessions.get_mut(&session)) {
+            (JournalEntry::SessionStarted { num_workers, .. }, None) => {
+                sessions.insert(session, SessionProgress { num_workers: *num_workers, ..SessionProgress::default() });
+                Ok(())
+            }
+            (_, Some(progress)) => progress.apply(entry),
+            (_, None) => Err(StorageError::UnknownSession(session)),
+        }
+    }
+
+    /// Validate and durably append an entry; call before acting on it
+    pub fn record(&mut self, entry: JournalEntry) -> Result<(), StorageError> {
+        // Validate on a copy so a refused entry leaves no trace
+        let mut sessions = self.sessions.clone();
+        Self::apply_to(&mut sessions, &entry)?;
+        let body = entry.to_bytes();
+        let mut record = Vec::with_capacity(RECORD_HEADER_LEN + body.len());
+        write_u32(&mut record, u32::try_from(body.len()).expect("journal entries are small"));
+        record.extend_from_slice(&record_checksum(&body));
+        record.extend_from_slice(&body);
+        self.file.write_all(&record)?;
+        self.file.sync_data()?;
+        self.sessions = sessions;
+        Ok(())
+    }
+
+    pub fn progress(&self, session: SessionId) -> Option<&SessionProgress> {
+        self.sessions.get(&session)
+    }
+
+    /// Sessions left open by a crash, with what to do about each
+    pub fn unfinished(&self) -> Vec<(SessionId, RecoveryAction)> {
+        self.sessions
+            .iter()
+            .map(|(session, progress)| (*session, progress.recovery()))
+            .filter(|(_, action)| *action != RecoveryAction::Closed)
+            .collect()
+    }
+}
+
+fn associated_data(header: &[u8], name: &str) -> Vec<u8> {
+    let mut aad = header[..HEADER_LEN].to_vec();
+    aad.extend_from_slice(name.as_bytes());
+    aad
+}
+
+/// Storage error types
+#[derive(Debug)]
+pub enum StorageError {
+    Io(std::io::Error),
+    Decode(DecodeError),
+    InvalidName(String),
+    BadHeader,
+    Authentication,
+    KeyRequired,
+    UnexpectedPlaintext,
+    KeyUnavailable(String),
+    UnknownSession(SessionId),
+    SessionClosed(SessionId),
+    /// The journal already shows shares of this session sent to the worker
+    WitnessAlreadyShared { session: SessionId, worker: u32 },
+    InvalidJournalEntry(String),
+}
+
+impl std::fmt::Display for StorageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            StorageError::Io(e) => write!(f, "Storage I/O error: {}", e),
+            StorageError::Decode(e) => write!(f, "Stored value is malformed: {}", e),
+            StorageError::InvalidName(name) => write!(f, "Invalid storage name: {}", name),
+            StorageError::BadHeader => write!(f, "Unrecognized storage file header"),
+            StorageError::Authentication => write!(f, "Stored file failed authentication"),
+            StorageError::KeyRequired => write!(f, "Stored file is encrypted but no key was configured"),
+            StorageError::UnexpectedPlaintext => write!(f, "Stored file is not encrypted but encryption is required"),
+            StorageError::KeyUnavailable(reason) => write!(f, "Data key unavailable: {}", reason),
+            StorageError::UnknownSession(session) => write!(f, "Session {} is not in the journal", session),
+            StorageError::SessionClosed(session) => write!(f, "Session {} is already closed", session),
+            StorageError::WitnessAlreadyShared { session, worker } => {
+                write!(f, "Witness of session {} was already shared with worker {}", session, worker)
+            }
+            StorageError::InvalidJournalEntry(reason) => write!(f, "Invalid journal entry: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for StorageError {}
+
+impl From<std::io::Error> for StorageError {
+    fn from(e: std::io::Error) -> Self {
+        StorageError::Io(e)
+    }
+}
+
+impl From<DecodeError> for StorageError {
+    fn from(e: DecodeError) -> Self {
+        StorageError::Decode(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mpc::{MaterialKind, PreprocessingError, PreprocessingStore, ShamirSecretSharing};
+    use ark_bls12_381::Fr;
+    use ark_std::test_rng;
+
+    type TestField = Fr;
+    type Store = PreprocessingStore<TestField, ShamirSecretSharing<TestField>>;
+
+    fn scratch_dir(test: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("eos-storage-{}-{}", test, std::process::id()))
+    }
+
+    fn passphrase(text: &str) -> Protection {
+        Protection::Encrypted(Box::new(PassphraseKey::with_iterations(text, 1_000)))
+    }
+
+    #[test]
+    fn test_encrypted_preprocessing_roundtrip() {
+        let mut rng = test_rng();
+        let mut material = Store::generate(3, 2, 2, 3, &mut rng);
+        material.allocate(7, 2, 1).unwrap();
+        material.next_triple(7).unwrap();
+        let first_share = material.triples[0].a[0].value;
+
+        let dir = scratch_dir("roundtrip");
+        let store = SecretStore::open(&dir, passphrase("correct horse")).unwrap();
+        store.save("preprocessing", &material).unwrap();
+
+        // The share encoding never appears on disk
+        let on_disk = std::fs::read(dir.join("preprocessing")).unwrap();
+        let mut share_bytes = Vec::new();
+        crate::serialization::write_field(&mut share_bytes, &first_share);
+        assert!(!on_disk.windows(share_bytes.len()).any(|w| w == share_bytes.as_slice()));
+
+        // The ledger survives the reload, so consumed material stays consumed
+        let mut reloaded: Store = store.load("preprocessing").unwrap();
+        assert_eq!(reloaded.triples[0].a[0].value, first_share);
+        assert_eq!(
+            reloaded.consume_triple(7, 0).unwrap_err(),
+            PreprocessingError::AlreadyConsumed { kind: MaterialKind::Triple, index: 0, session: 7 }
+        );
+        reloaded.next_triple(7).unwrap();
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_wrong_key_and_tampering_are_rejected() {
+        let mut rng = test_rng();
+        let material = Store::generate(1, 1, 2, 3, &mut rng);
+        let dir = scratch_dir("tamper");
+        let store = SecretStore::open(&dir, passphrase("right")).unwrap();
+        store.save("material", &material).unwrap();
+
+        let wrong = SecretStore::open(&dir, passphrase("wrong")).unwrap();
+        assert!(matches!(wrong.load::<Store>("material"), Err(StorageError::Authentication)));
+        let plain = SecretStore::open(&dir, Protection::Plaintext).unwrap();
+        assert!(matches!(plain.load::<Store>("material"), Err(StorageError::KeyRequired)));
+
+        // A sealed file copied under another name does not authenticate
+        std::fs::copy(dir.join("material"), dir.join("other")).unwrap();
+        assert!(matches!(store.load::<Store>("other"), Err(StorageError::Authentication)));
+
+        // A plaintext file is not accepted where encryption is required
+        plain.save("plain", &material).unwrap();
+        assert!(matches!(store.load::<Store>("plain"), Err(StorageError::UnexpectedPlaintext)));
+        assert!(plain.load::<Store>("plain").is_ok());
+
+        assert!(matches!(store.save("../escape", &material), Err(StorageError::InvalidName(_))));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_journal_recovers_after_crash_without_resharing() {
+        let dir = scratch_dir("journal");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("delegator.journal");
+        let shares = |worker: u32| JournalEntry::SharesSent { session: 1, worker, digest: journal_digest(&[worker as u8]) };
+        {
+            let mut journal = SessionJournal::open(&path).unwrap();
+            journal.record(JournalEntry::SessionStarted { session: 1, num_workers: 3 }).unwrap();
+            journal.record(shares(0)).unwrap();
+            journal.record(shares(1)).unwrap();
+            journal.record(JournalEntry::SessionStarted { session: 2, num_workers: 2 }).unwrap();
+            journal.record(JournalEntry::Aborted { session: 2 }).unwrap();
+        }
+        // A crash in the middle of the next append leaves a torn record
+        let intact = std::fs::metadata(&path).unwrap().len();
+        std::fs::OpenOptions::new().append(true).open(&path).unwrap().write_all(&[9, 0, 0, 0, 1, 2]).unwrap();
+
+        let mut journal = SessionJournal::open(&path).unwrap();
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), intact);
+        assert_eq!(journal.unfinished(), vec![(1, RecoveryAction::Abort { shared_with: vec![0, 1] })]);
+
+        // The witness is never shared with the same worker twice
+        assert!(matches!(journal.record(shares(1)), Err(StorageError::WitnessAlreadyShared { session: 1, worker: 1 })));
+        assert!(matches!(journal.record(JournalEntry::Completed { session: 2 }), Err(StorageError::SessionClosed(2))));
+        assert!(matches!(journal.record(JournalEntry::Completed { session: 5 }), Err(StorageError::UnknownSession(5))));
+
+        journal.record(shares(2)).unwrap();
+        assert_eq!(journal.progress(1).unwrap().recovery(), RecoveryAction::AwaitProof);
+        let proof = journal_digest(b"proof");
+        journal.record(JournalEntry::ProofReceived { session: 1, digest: proof }).unwrap();
+        drop(journal);
+        let mut journal = SessionJournal::open(&path).unwrap();
+        assert_eq!(journal.unfinished(), vec![(1, RecoveryAction::VerifyProof(proof))]);
+        journal.record(JournalEntry::Completed { session: 1 }).unwrap();
+        assert!(journal.unfinished().is_empty());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}